@@ -158,6 +158,12 @@ client_request_definitions! {
         params: #[ts(type = "undefined")] #[serde(skip_serializing_if = "Option::is_none")] Option<()>,
         response: GetUserAgentResponse,
     },
+    /// List the model presets a client can offer, including models declared
+    /// under `[models.<slug>]` in config.toml.
+    ListModels {
+        params: #[ts(type = "undefined")] #[serde(skip_serializing_if = "Option::is_none")] Option<()>,
+        response: ListModelsResponse,
+    },
     UserInfo {
         params: #[ts(type = "undefined")] #[serde(skip_serializing_if = "Option::is_none")] Option<()>,
         response: UserInfoResponse,
@@ -426,6 +432,27 @@ pub struct GetUserAgentResponse {
     pub user_agent: String,
 }
 
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ListModelsResponse {
+    pub items: Vec<ModelPresetInfo>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
+#[serde(rename_all = "camelCase")]
+pub struct ModelPresetInfo {
+    /// Stable identifier for the preset.
+    pub id: String,
+    /// Display label for the preset.
+    pub label: String,
+    pub description: String,
+    /// Model slug to pass as `model` when starting or updating a conversation.
+    pub model: String,
+    /// Reasoning effort to pass along with `model`, if any.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reasoning_effort: Option<ReasoningEffort>,
+}
+
 #[derive(Serialize, Deserialize, Debug, Clone, PartialEq, TS)]
 #[serde(rename_all = "camelCase")]
 pub struct UserInfoResponse {
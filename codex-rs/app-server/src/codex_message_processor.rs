@@ -27,10 +27,12 @@ use codex_app_server_protocol::InterruptConversationResponse;
 use codex_app_server_protocol::JSONRPCErrorError;
 use codex_app_server_protocol::ListConversationsParams;
 use codex_app_server_protocol::ListConversationsResponse;
+use codex_app_server_protocol::ListModelsResponse;
 use codex_app_server_protocol::LoginApiKeyParams;
 use codex_app_server_protocol::LoginApiKeyResponse;
 use codex_app_server_protocol::LoginChatGptCompleteNotification;
 use codex_app_server_protocol::LoginChatGptResponse;
+use codex_app_server_protocol::ModelPresetInfo;
 use codex_app_server_protocol::NewConversationParams;
 use codex_app_server_protocol::NewConversationResponse;
 use codex_app_server_protocol::RemoveConversationListenerParams;
@@ -49,6 +51,7 @@ use codex_app_server_protocol::SetDefaultModelParams;
 use codex_app_server_protocol::SetDefaultModelResponse;
 use codex_app_server_protocol::UserInfoResponse;
 use codex_app_server_protocol::UserSavedConfig;
+use codex_common::model_presets::model_presets;
 use codex_core::AuthManager;
 use codex_core::CodexConversation;
 use codex_core::ConversationManager;
@@ -57,9 +60,8 @@ use codex_core::NewConversation;
 use codex_core::RolloutRecorder;
 use codex_core::SessionMeta;
 use codex_core::auth::CLIENT_ID;
-use codex_core::auth::get_auth_file;
+use codex_core::auth::load_auth_dot_json;
 use codex_core::auth::login_with_api_key;
-use codex_core::auth::try_read_auth_json;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::config::ConfigToml;
@@ -226,6 +228,12 @@ impl CodexMessageProcessor {
             } => {
                 self.get_user_agent(request_id).await;
             }
+            ClientRequest::ListModels {
+                request_id,
+                params: _,
+            } => {
+                self.list_models(request_id).await;
+            }
             ClientRequest::UserInfo {
                 request_id,
                 params: _,
@@ -249,7 +257,11 @@ impl CodexMessageProcessor {
             }
         }
 
-        match login_with_api_key(&self.config.codex_home, &params.api_key) {
+        match login_with_api_key(
+            &self.config.codex_home,
+            &params.api_key,
+            &self.config.credential_store,
+        ) {
             Ok(()) => {
                 self.auth_manager.reload();
                 self.outgoing
@@ -279,6 +291,7 @@ impl CodexMessageProcessor {
 
         let opts = LoginServerOptions {
             open_browser: false,
+            credential_store: config.credential_store.clone(),
             ..LoginServerOptions::new(config.codex_home.clone(), CLIENT_ID.to_string())
         };
 
@@ -498,6 +511,22 @@ impl CodexMessageProcessor {
         self.outgoing.send_response(request_id, response).await;
     }
 
+    async fn list_models(&self, request_id: RequestId) {
+        let auth_mode = self.auth_manager.auth().map(|auth| auth.mode);
+        let items = model_presets(auth_mode, &self.config.models)
+            .into_iter()
+            .map(|preset| ModelPresetInfo {
+                id: preset.id,
+                label: preset.label,
+                description: preset.description,
+                model: preset.model,
+                reasoning_effort: preset.effort,
+            })
+            .collect();
+        let response = ListModelsResponse { items };
+        self.outgoing.send_response(request_id, response).await;
+    }
+
     async fn get_user_saved_config(&self, request_id: RequestId) {
         let toml_value = match load_config_as_toml(&self.config.codex_home) {
             Ok(val) => val,
@@ -535,11 +564,11 @@ impl CodexMessageProcessor {
 
     async fn get_user_info(&self, request_id: RequestId) {
         // Read alleged user email from auth.json (best-effort; not verified).
-        let auth_path = get_auth_file(&self.config.codex_home);
-        let alleged_user_email = match try_read_auth_json(&auth_path) {
-            Ok(auth) => auth.tokens.and_then(|t| t.id_token.email),
-            Err(_) => None,
-        };
+        let alleged_user_email =
+            match load_auth_dot_json(&self.config.codex_home, &self.config.credential_store) {
+                Ok(Some(auth)) => auth.tokens.and_then(|t| t.id_token.email),
+                Ok(None) | Err(_) => None,
+            };
 
         let response = UserInfoResponse { alleged_user_email };
         self.outgoing.send_response(request_id, response).await;
@@ -616,6 +645,7 @@ impl CodexMessageProcessor {
         };
         tracing::debug!("Sandbox type: {sandbox_type:?}");
         let codex_linux_sandbox_exe = self.config.codex_linux_sandbox_exe.clone();
+        let resource_limits = self.config.sandbox_resource_limits;
         let outgoing = self.outgoing.clone();
         let req_id = request_id;
         let sandbox_cwd = self.config.cwd.clone();
@@ -627,6 +657,7 @@ impl CodexMessageProcessor {
                 &effective_policy,
                 sandbox_cwd.as_path(),
                 &codex_linux_sandbox_exe,
+                &resource_limits,
                 None,
             )
             .await
@@ -236,6 +236,11 @@ impl McpProcess {
         self.send_request("getUserAgent", None).await
     }
 
+    /// Send a `listModels` JSON-RPC request.
+    pub async fn send_list_models_request(&mut self) -> anyhow::Result<i64> {
+        self.send_request("listModels", None).await
+    }
+
     /// Send a `userInfo` JSON-RPC request.
     pub async fn send_user_info_request(&mut self) -> anyhow::Result<i64> {
         self.send_request("userInfo", None).await
@@ -0,0 +1,63 @@
+use app_test_support::McpProcess;
+use app_test_support::to_response;
+use codex_app_server_protocol::JSONRPCResponse;
+use codex_app_server_protocol::ListModelsResponse;
+use codex_app_server_protocol::ModelPresetInfo;
+use codex_app_server_protocol::RequestId;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use tokio::time::timeout;
+
+const DEFAULT_READ_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(10);
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn list_models_includes_configured_models() {
+    let codex_home = TempDir::new().unwrap_or_else(|err| panic!("create tempdir: {err}"));
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+[models.qwen3-coder]
+display_name = "Qwen3 Coder"
+description = "self-hosted"
+context_window = 262144
+"#,
+    )
+    .expect("write config.toml");
+
+    let mut mcp = McpProcess::new(codex_home.path())
+        .await
+        .expect("spawn mcp process");
+    timeout(DEFAULT_READ_TIMEOUT, mcp.initialize())
+        .await
+        .expect("initialize timeout")
+        .expect("initialize request");
+
+    let request_id = mcp
+        .send_list_models_request()
+        .await
+        .expect("send listModels");
+    let response: JSONRPCResponse = timeout(
+        DEFAULT_READ_TIMEOUT,
+        mcp.read_stream_until_response_message(RequestId::Integer(request_id)),
+    )
+    .await
+    .expect("listModels timeout")
+    .expect("listModels response");
+
+    let ListModelsResponse { items } =
+        to_response(response).expect("deserialize listModels response");
+    assert!(
+        items.iter().any(|item| item.model == "gpt-5-codex"),
+        "expected built-in presets, got: {items:?}"
+    );
+    assert_eq!(
+        items.last(),
+        Some(&ModelPresetInfo {
+            id: "qwen3-coder".to_string(),
+            label: "Qwen3 Coder".to_string(),
+            description: "— self-hosted".to_string(),
+            model: "qwen3-coder".to_string(),
+            reasoning_effort: None,
+        })
+    );
+}
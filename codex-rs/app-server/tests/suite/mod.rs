@@ -5,6 +5,7 @@ mod config;
 mod create_conversation;
 mod fuzzy_file_search;
 mod interrupt;
+mod list_models;
 mod list_resume;
 mod login;
 mod send_message;
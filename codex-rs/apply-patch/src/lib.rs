@@ -1,6 +1,7 @@
 mod parser;
 mod seek_sequence;
 mod standalone_executable;
+mod transaction;
 
 use std::collections::HashMap;
 use std::path::Path;
@@ -17,6 +18,7 @@ use parser::UpdateFileChunk;
 pub use parser::parse_patch;
 use similar::TextDiff;
 use thiserror::Error;
+use transaction::FileOperation;
 use tree_sitter::LanguageError;
 use tree_sitter::Parser;
 use tree_sitter::Query;
@@ -31,6 +33,23 @@ pub const APPLY_PATCH_TOOL_INSTRUCTIONS: &str = include_str!("../apply_patch_too
 
 const APPLY_PATCH_COMMANDS: [&str; 2] = ["apply_patch", "applypatch"];
 
+/// Minimum similarity (from `0.0` to `1.0`) a region of a file must have with
+/// the lines a hunk expects before the hunk is applied there even though the
+/// lines do not match exactly. `1.0` disables fuzzy matching.
+pub const DEFAULT_FUZZY_MATCH_THRESHOLD: f64 = 0.9;
+
+/// Environment variable that overrides [`DEFAULT_FUZZY_MATCH_THRESHOLD`] for
+/// the `apply_patch` executable.
+pub const FUZZY_MATCH_THRESHOLD_ENV_VAR: &str = "CODEX_APPLY_PATCH_FUZZY_THRESHOLD";
+
+fn fuzzy_match_threshold_from_env() -> f64 {
+    std::env::var(FUZZY_MATCH_THRESHOLD_ENV_VAR)
+        .ok()
+        .and_then(|value| value.trim().parse::<f64>().ok())
+        .filter(|threshold| (0.0..=1.0).contains(threshold))
+        .unwrap_or(DEFAULT_FUZZY_MATCH_THRESHOLD)
+}
+
 #[derive(Debug, Error, PartialEq)]
 pub enum ApplyPatchError {
     #[error(transparent)]
@@ -167,6 +186,10 @@ pub struct ApplyPatchAction {
 
     /// The working directory that was used to resolve relative paths in the patch.
     pub cwd: PathBuf,
+
+    /// The fuzzy match threshold the changes were computed with, which must
+    /// also be used when the patch is applied.
+    fuzzy_threshold: f64,
 }
 
 impl ApplyPatchAction {
@@ -179,6 +202,10 @@ impl ApplyPatchAction {
         &self.changes
     }
 
+    pub fn fuzzy_threshold(&self) -> f64 {
+        self.fuzzy_threshold
+    }
+
     /// Should be used exclusively for testing. (Not worth the overhead of
     /// creating a feature flag for this.)
     pub fn new_add_for_test(path: &Path, content: String) -> Self {
@@ -207,13 +234,19 @@ impl ApplyPatchAction {
                 .expect("path should have parent")
                 .to_path_buf(),
             patch,
+            fuzzy_threshold: DEFAULT_FUZZY_MATCH_THRESHOLD,
         }
     }
 }
 
 /// cwd must be an absolute path so that we can resolve relative paths in the
-/// patch.
-pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApplyPatchVerified {
+/// patch. `fuzzy_threshold` is the similarity required to apply a hunk whose
+/// lines do not match the file exactly; see [`DEFAULT_FUZZY_MATCH_THRESHOLD`].
+pub fn maybe_parse_apply_patch_verified(
+    argv: &[String],
+    cwd: &Path,
+    fuzzy_threshold: f64,
+) -> MaybeApplyPatchVerified {
     // Detect a raw patch body passed directly as the command or as the body of a bash -lc
     // script. In these cases, report an explicit error rather than applying the patch.
     match argv {
@@ -278,7 +311,7 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                         let ApplyPatchFileUpdate {
                             unified_diff,
                             content: contents,
-                        } = match unified_diff_from_chunks(&path, &chunks) {
+                        } = match unified_diff_from_chunks(&path, &chunks, fuzzy_threshold) {
                             Ok(diff) => diff,
                             Err(e) => {
                                 return MaybeApplyPatchVerified::CorrectnessError(e);
@@ -299,6 +332,7 @@ pub fn maybe_parse_apply_patch_verified(argv: &[String], cwd: &Path) -> MaybeApp
                 changes,
                 patch,
                 cwd: effective_cwd,
+                fuzzy_threshold,
             })
         }
         MaybeApplyPatch::ShellParseError(e) => MaybeApplyPatchVerified::ShellParseError(e),
@@ -474,8 +508,42 @@ pub fn apply_patch(
     stdout: &mut impl std::io::Write,
     stderr: &mut impl std::io::Write,
 ) -> Result<(), ApplyPatchError> {
-    let hunks = match parse_patch(patch) {
-        Ok(source) => source.hunks,
+    let hunks = parse_patch_reporting_errors(patch, stderr)?;
+
+    apply_hunks(&hunks, stdout, stderr)?;
+
+    Ok(())
+}
+
+/// Checks that the patch applies cleanly without modifying any files, and
+/// prints the files it would change to stdout/stderr.
+pub fn check_patch(
+    patch: &str,
+    stdout: &mut impl std::io::Write,
+    stderr: &mut impl std::io::Write,
+) -> Result<(), ApplyPatchError> {
+    let hunks = parse_patch_reporting_errors(patch, stderr)?;
+
+    match plan_hunks(&hunks, fuzzy_match_threshold_from_env()) {
+        Ok(PlannedPatch { affected, .. }) => {
+            write_summary(
+                "Patch applies cleanly. It would update the following files:",
+                &affected,
+                stdout,
+            )
+            .map_err(ApplyPatchError::from)?;
+            Ok(())
+        }
+        Err(err) => Err(report_apply_error(err, stderr)?),
+    }
+}
+
+fn parse_patch_reporting_errors(
+    patch: &str,
+    stderr: &mut impl std::io::Write,
+) -> Result<Vec<Hunk>, ApplyPatchError> {
+    match parse_patch(patch) {
+        Ok(source) => Ok(source.hunks),
         Err(e) => {
             match &e {
                 InvalidPatchError(message) => {
@@ -492,13 +560,9 @@ pub fn apply_patch(
                     .map_err(ApplyPatchError::from)?;
                 }
             }
-            return Err(ApplyPatchError::ParseError(e));
+            Err(ApplyPatchError::ParseError(e))
         }
-    };
-
-    apply_hunks(&hunks, stdout, stderr)?;
-
-    Ok(())
+    }
 }
 
 /// Applies hunks and continues to update stdout/stderr
@@ -534,23 +598,30 @@ pub fn apply_hunks(
         .collect::<Vec<&Path>>();
 
     // Delegate to a helper that applies each hunk to the filesystem.
-    match apply_hunks_to_files(hunks) {
+    match apply_hunks_to_files(hunks, fuzzy_match_threshold_from_env()) {
         Ok(affected) => {
             print_summary(&affected, stdout).map_err(ApplyPatchError::from)?;
             Ok(())
         }
-        Err(err) => {
-            let msg = err.to_string();
-            writeln!(stderr, "{msg}").map_err(ApplyPatchError::from)?;
-            if let Some(io) = err.downcast_ref::<std::io::Error>() {
-                Err(ApplyPatchError::from(io))
-            } else {
-                Err(ApplyPatchError::IoError(IoError {
-                    context: msg,
-                    source: std::io::Error::other(err),
-                }))
-            }
-        }
+        Err(err) => Err(report_apply_error(err, stderr)?),
+    }
+}
+
+/// Prints `err` to stderr and converts it into the [`ApplyPatchError`] to
+/// return.
+fn report_apply_error(
+    err: anyhow::Error,
+    stderr: &mut impl std::io::Write,
+) -> Result<ApplyPatchError, ApplyPatchError> {
+    let msg = err.to_string();
+    writeln!(stderr, "{msg}").map_err(ApplyPatchError::from)?;
+    if let Some(io) = err.downcast_ref::<std::io::Error>() {
+        Ok(ApplyPatchError::from(io))
+    } else {
+        Ok(ApplyPatchError::IoError(IoError {
+            context: msg,
+            source: std::io::Error::other(err),
+        }))
     }
 }
 
@@ -564,32 +635,60 @@ pub struct AffectedPaths {
 }
 
 /// Apply the hunks to the filesystem, returning which files were added, modified, or deleted.
-/// Returns an error if the patch could not be applied.
-fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
+/// Returns an error if the patch could not be applied, in which case the
+/// filesystem is left as it was.
+fn apply_hunks_to_files(hunks: &[Hunk], fuzzy_threshold: f64) -> anyhow::Result<AffectedPaths> {
+    let PlannedPatch {
+        operations,
+        affected,
+    } = plan_hunks(hunks, fuzzy_threshold)?;
+    transaction::commit(&operations)?;
+    Ok(affected)
+}
+
+/// The file operations a patch will perform, computed without touching disk.
+struct PlannedPatch {
+    operations: Vec<FileOperation>,
+    affected: AffectedPaths,
+}
+
+/// Compute the new contents of every file the hunks touch. Later hunks see the
+/// results of earlier hunks in the same patch, as if the patch had already been
+/// applied up to that point.
+fn plan_hunks(hunks: &[Hunk], fuzzy_threshold: f64) -> anyhow::Result<PlannedPatch> {
     if hunks.is_empty() {
         anyhow::bail!("No files were modified.");
     }
 
+    // Files already touched by earlier hunks; `None` marks a deleted file.
+    let mut pending: HashMap<PathBuf, Option<String>> = HashMap::new();
+    let mut operations: Vec<FileOperation> = Vec::new();
     let mut added: Vec<PathBuf> = Vec::new();
     let mut modified: Vec<PathBuf> = Vec::new();
     let mut deleted: Vec<PathBuf> = Vec::new();
     for hunk in hunks {
         match hunk {
             Hunk::AddFile { path, contents } => {
-                if let Some(parent) = path.parent()
-                    && !parent.as_os_str().is_empty()
-                {
-                    std::fs::create_dir_all(parent).with_context(|| {
-                        format!("Failed to create parent directories for {}", path.display())
-                    })?;
-                }
-                std::fs::write(path, contents)
-                    .with_context(|| format!("Failed to write file {}", path.display()))?;
+                ensure_writable(path)?;
+                operations.push(FileOperation::Write {
+                    path: path.clone(),
+                    contents: contents.clone(),
+                    permissions: existing_permissions(path),
+                });
+                pending.insert(path.clone(), Some(contents.clone()));
                 added.push(path.clone());
             }
             Hunk::DeleteFile { path } => {
-                std::fs::remove_file(path)
-                    .with_context(|| format!("Failed to delete file {}", path.display()))?;
+                let exists = match pending.get(path) {
+                    Some(contents) => contents.is_some(),
+                    None => path.is_file(),
+                };
+                if !exists {
+                    return Err(std::io::Error::from(std::io::ErrorKind::NotFound))
+                        .with_context(|| format!("Failed to delete file {}", path.display()));
+                }
+                operations.push(FileOperation::Delete { path: path.clone() });
+                pending.insert(path.clone(), None);
                 deleted.push(path.clone());
             }
             Hunk::UpdateFile {
@@ -597,36 +696,76 @@ fn apply_hunks_to_files(hunks: &[Hunk]) -> anyhow::Result<AffectedPaths> {
                 move_path,
                 chunks,
             } => {
-                let AppliedPatch { new_contents, .. } =
-                    derive_new_contents_from_chunks(path, chunks)?;
-                if let Some(dest) = move_path {
-                    if let Some(parent) = dest.parent()
-                        && !parent.as_os_str().is_empty()
-                    {
-                        std::fs::create_dir_all(parent).with_context(|| {
-                            format!("Failed to create parent directories for {}", dest.display())
-                        })?;
+                let original_contents = match pending.get(path) {
+                    Some(Some(contents)) => contents.clone(),
+                    Some(None) => {
+                        return Err(std::io::Error::from(std::io::ErrorKind::NotFound))
+                            .with_context(|| {
+                                format!("Failed to read file to update {}", path.display())
+                            });
                     }
-                    std::fs::write(dest, new_contents)
-                        .with_context(|| format!("Failed to write file {}", dest.display()))?;
-                    std::fs::remove_file(path)
-                        .with_context(|| format!("Failed to remove original {}", path.display()))?;
+                    None => read_file_to_update(path)?,
+                };
+                let AppliedPatch { new_contents, .. } = derive_new_contents_from_original(
+                    original_contents,
+                    path,
+                    chunks,
+                    fuzzy_threshold,
+                )?;
+                let permissions = existing_permissions(path);
+                if let Some(dest) = move_path {
+                    ensure_writable(dest)?;
+                    operations.push(FileOperation::Write {
+                        path: dest.clone(),
+                        contents: new_contents.clone(),
+                        permissions,
+                    });
+                    operations.push(FileOperation::Delete { path: path.clone() });
+                    pending.insert(dest.clone(), Some(new_contents));
+                    pending.insert(path.clone(), None);
                     modified.push(dest.clone());
                 } else {
-                    std::fs::write(path, new_contents)
-                        .with_context(|| format!("Failed to write file {}", path.display()))?;
+                    ensure_writable(path)?;
+                    operations.push(FileOperation::Write {
+                        path: path.clone(),
+                        contents: new_contents.clone(),
+                        permissions,
+                    });
+                    pending.insert(path.clone(), Some(new_contents));
                     modified.push(path.clone());
                 }
             }
         }
     }
-    Ok(AffectedPaths {
-        added,
-        modified,
-        deleted,
+    Ok(PlannedPatch {
+        operations,
+        affected: AffectedPaths {
+            added,
+            modified,
+            deleted,
+        },
     })
 }
 
+fn existing_permissions(path: &Path) -> Option<std::fs::Permissions> {
+    std::fs::metadata(path)
+        .ok()
+        .map(|metadata| metadata.permissions())
+}
+
+/// Writes replace files by renaming over them, which would succeed even for a
+/// read-only file; refuse instead, like a plain write would.
+fn ensure_writable(path: &Path) -> anyhow::Result<()> {
+    if std::fs::metadata(path).is_ok_and(|metadata| metadata.permissions().readonly()) {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::PermissionDenied,
+            "file is read-only",
+        ))
+        .with_context(|| format!("Failed to write file {}", path.display()));
+    }
+    Ok(())
+}
+
 struct AppliedPatch {
     original_contents: String,
     new_contents: String,
@@ -637,18 +776,38 @@ struct AppliedPatch {
 fn derive_new_contents_from_chunks(
     path: &Path,
     chunks: &[UpdateFileChunk],
+    fuzzy_threshold: f64,
 ) -> std::result::Result<AppliedPatch, ApplyPatchError> {
-    let original_contents = match std::fs::read_to_string(path) {
-        Ok(contents) => contents,
-        Err(err) => {
-            return Err(ApplyPatchError::IoError(IoError {
-                context: format!("Failed to read file to update {}", path.display()),
-                source: err,
-            }));
-        }
-    };
+    let original_contents = read_file_to_update(path)?;
+    derive_new_contents_from_original(original_contents, path, chunks, fuzzy_threshold)
+}
+
+fn read_file_to_update(path: &Path) -> std::result::Result<String, ApplyPatchError> {
+    std::fs::read_to_string(path).map_err(|err| {
+        ApplyPatchError::IoError(IoError {
+            context: format!("Failed to read file to update {}", path.display()),
+            source: err,
+        })
+    })
+}
 
-    let mut original_lines: Vec<String> = original_contents.split('\n').map(String::from).collect();
+fn derive_new_contents_from_original(
+    original_contents: String,
+    path: &Path,
+    chunks: &[UpdateFileChunk],
+    fuzzy_threshold: f64,
+) -> std::result::Result<AppliedPatch, ApplyPatchError> {
+    // Patches are written with `\n` line endings; match against the lines
+    // without their `\r` and restore the file's own endings afterwards.
+    let line_ending = if original_contents.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let mut original_lines: Vec<String> = original_contents
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line).to_string())
+        .collect();
 
     // Drop the trailing empty element that results from the final newline so
     // that line counts match the behaviour of standard `diff`.
@@ -656,13 +815,13 @@ fn derive_new_contents_from_chunks(
         original_lines.pop();
     }
 
-    let replacements = compute_replacements(&original_lines, path, chunks)?;
+    let replacements = compute_replacements(&original_lines, path, chunks, fuzzy_threshold)?;
     let new_lines = apply_replacements(original_lines, &replacements);
     let mut new_lines = new_lines;
     if !new_lines.last().is_some_and(String::is_empty) {
         new_lines.push(String::new());
     }
-    let new_contents = new_lines.join("\n");
+    let new_contents = new_lines.join(line_ending);
     Ok(AppliedPatch {
         original_contents,
         new_contents,
@@ -671,11 +830,13 @@ fn derive_new_contents_from_chunks(
 
 /// Compute a list of replacements needed to transform `original_lines` into the
 /// new lines, given the patch `chunks`. Each replacement is returned as
-/// `(start_index, old_len, new_lines)`.
+/// `(start_index, old_len, new_lines)`. Lines that cannot be found exactly are
+/// matched fuzzily, provided the match is at least `fuzzy_threshold` similar.
 fn compute_replacements(
     original_lines: &[String],
     path: &Path,
     chunks: &[UpdateFileChunk],
+    fuzzy_threshold: f64,
 ) -> std::result::Result<Vec<(usize, usize, Vec<String>)>, ApplyPatchError> {
     let mut replacements: Vec<(usize, usize, Vec<String>)> = Vec::new();
     let mut line_index: usize = 0;
@@ -684,19 +845,28 @@ fn compute_replacements(
         // If a chunk has a `change_context`, we use seek_sequence to find it, then
         // adjust our `line_index` to continue from there.
         if let Some(ctx_line) = &chunk.change_context {
-            if let Some(idx) = seek_sequence::seek_sequence(
-                original_lines,
-                std::slice::from_ref(ctx_line),
-                line_index,
-                false,
-            ) {
-                line_index = idx + 1;
-            } else {
-                return Err(ApplyPatchError::ComputeReplacements(format!(
-                    "Failed to find context '{}' in {}",
-                    ctx_line,
-                    path.display()
-                )));
+            let pattern = std::slice::from_ref(ctx_line);
+            let found =
+                match seek_sequence::seek_sequence(original_lines, pattern, line_index, false) {
+                    Some(idx) => Ok(idx),
+                    None => seek_sequence::seek_sequence_fuzzy(
+                        original_lines,
+                        pattern,
+                        line_index,
+                        false,
+                        fuzzy_threshold,
+                    ),
+                };
+            match found {
+                Ok(idx) => line_index = idx + 1,
+                Err(closest) => {
+                    return Err(ApplyPatchError::ComputeReplacements(format!(
+                        "Failed to find context '{}' in {}{}",
+                        ctx_line,
+                        path.display(),
+                        describe_closest_match(original_lines, 1, closest, fuzzy_threshold),
+                    )));
+                }
             }
         }
 
@@ -745,15 +915,39 @@ fn compute_replacements(
             );
         }
 
-        if let Some(start_idx) = found {
-            replacements.push((start_idx, pattern.len(), new_slice.to_vec()));
-            line_index = start_idx + pattern.len();
-        } else {
-            return Err(ApplyPatchError::ComputeReplacements(format!(
-                "Failed to find expected lines in {}:\n{}",
-                path.display(),
-                chunk.old_lines.join("\n"),
-            )));
+        let is_fuzzy = found.is_none();
+        let found = match found {
+            Some(idx) => Ok(idx),
+            None => seek_sequence::seek_sequence_fuzzy(
+                original_lines,
+                pattern,
+                line_index,
+                chunk.is_end_of_file,
+                fuzzy_threshold,
+            ),
+        };
+        match found {
+            Ok(start_idx) => {
+                let new_lines = if is_fuzzy {
+                    keep_original_context_lines(
+                        &original_lines[start_idx..start_idx + pattern.len()],
+                        pattern,
+                        new_slice,
+                    )
+                } else {
+                    new_slice.to_vec()
+                };
+                replacements.push((start_idx, pattern.len(), new_lines));
+                line_index = start_idx + pattern.len();
+            }
+            Err(closest) => {
+                return Err(ApplyPatchError::ComputeReplacements(format!(
+                    "Failed to find expected lines in {}:\n{}{}",
+                    path.display(),
+                    chunk.old_lines.join("\n"),
+                    describe_closest_match(original_lines, pattern.len(), closest, fuzzy_threshold),
+                )));
+            }
         }
     }
 
@@ -762,6 +956,64 @@ fn compute_replacements(
     Ok(replacements)
 }
 
+/// After a fuzzy match the patch's context lines differ from the file, so
+/// copying them from the patch would silently rewrite lines the model never
+/// meant to change. Keep the matched file lines for the lines that `old_lines`
+/// and `new_lines` share and take only the changed lines from the patch.
+fn keep_original_context_lines(
+    matched_lines: &[String],
+    old_lines: &[String],
+    new_lines: &[String],
+) -> Vec<String> {
+    let mut lines = new_lines.to_vec();
+    for op in similar::capture_diff_slices(similar::Algorithm::Myers, old_lines, new_lines) {
+        if let similar::DiffOp::Equal {
+            old_index,
+            new_index,
+            len,
+        } = op
+        {
+            lines[new_index..new_index + len]
+                .clone_from_slice(&matched_lines[old_index..old_index + len]);
+        }
+    }
+    lines
+}
+
+/// Explains why the closest region of the file was not used, showing it with
+/// 1-based line numbers so the patch can be corrected against it.
+fn describe_closest_match(
+    original_lines: &[String],
+    len: usize,
+    closest: Option<seek_sequence::ClosestMatch>,
+    fuzzy_threshold: f64,
+) -> String {
+    let Some(closest) = closest else {
+        return String::new();
+    };
+    let first = closest.start + 1;
+    let last = closest.start + len;
+    let range = if len == 1 {
+        format!("line {first}")
+    } else {
+        format!("lines {first}-{last}")
+    };
+    let similarity = (closest.similarity * 100.0).floor();
+    let reason = if closest.ambiguous {
+        "but other regions match about as well; include more context".to_string()
+    } else {
+        let threshold = (fuzzy_threshold * 100.0).ceil();
+        format!("below the {threshold:.0}% required")
+    };
+    let width = last.to_string().len();
+    let mut description =
+        format!("\n\nClosest match ({range}, {similarity:.0}% similar, {reason}):");
+    for (number, line) in (first..).zip(&original_lines[closest.start..closest.start + len]) {
+        description.push_str(&format!("\n{number:>width$} | {line}"));
+    }
+    description
+}
+
 /// Apply the `(start_index, old_len, new_lines)` replacements to `original_lines`,
 /// returning the modified file contents as a vector of lines.
 fn apply_replacements(
@@ -800,19 +1052,21 @@ pub struct ApplyPatchFileUpdate {
 pub fn unified_diff_from_chunks(
     path: &Path,
     chunks: &[UpdateFileChunk],
+    fuzzy_threshold: f64,
 ) -> std::result::Result<ApplyPatchFileUpdate, ApplyPatchError> {
-    unified_diff_from_chunks_with_context(path, chunks, 1)
+    unified_diff_from_chunks_with_context(path, chunks, 1, fuzzy_threshold)
 }
 
 pub fn unified_diff_from_chunks_with_context(
     path: &Path,
     chunks: &[UpdateFileChunk],
     context: usize,
+    fuzzy_threshold: f64,
 ) -> std::result::Result<ApplyPatchFileUpdate, ApplyPatchError> {
     let AppliedPatch {
         original_contents,
         new_contents,
-    } = derive_new_contents_from_chunks(path, chunks)?;
+    } = derive_new_contents_from_chunks(path, chunks, fuzzy_threshold)?;
     let text_diff = TextDiff::from_lines(&original_contents, &new_contents);
     let unified_diff = text_diff.unified_diff().context_radius(context).to_string();
     Ok(ApplyPatchFileUpdate {
@@ -827,7 +1081,15 @@ pub fn print_summary(
     affected: &AffectedPaths,
     out: &mut impl std::io::Write,
 ) -> std::io::Result<()> {
-    writeln!(out, "Success. Updated the following files:")?;
+    write_summary("Success. Updated the following files:", affected, out)
+}
+
+fn write_summary(
+    header: &str,
+    affected: &AffectedPaths,
+    out: &mut impl std::io::Write,
+) -> std::io::Result<()> {
+    writeln!(out, "{header}")?;
     for path in &affected.added {
         writeln!(out, "A {}", path.display())?;
     }
@@ -906,7 +1168,7 @@ mod tests {
         let args = vec![patch];
         let dir = tempdir().unwrap();
         assert!(matches!(
-            maybe_parse_apply_patch_verified(&args, dir.path()),
+            maybe_parse_apply_patch_verified(&args, dir.path(), DEFAULT_FUZZY_MATCH_THRESHOLD),
             MaybeApplyPatchVerified::CorrectnessError(ApplyPatchError::ImplicitInvocation)
         ));
     }
@@ -917,7 +1179,7 @@ mod tests {
         let args = args_bash(script);
         let dir = tempdir().unwrap();
         assert!(matches!(
-            maybe_parse_apply_patch_verified(&args, dir.path()),
+            maybe_parse_apply_patch_verified(&args, dir.path(), DEFAULT_FUZZY_MATCH_THRESHOLD),
             MaybeApplyPatchVerified::CorrectnessError(ApplyPatchError::ImplicitInvocation)
         ));
     }
@@ -1361,7 +1623,9 @@ PATCH"#,
             [Hunk::UpdateFile { chunks, .. }] => chunks,
             _ => panic!("Expected a single UpdateFile hunk"),
         };
-        let diff = unified_diff_from_chunks(&path, update_file_chunks).unwrap();
+        let diff =
+            unified_diff_from_chunks(&path, update_file_chunks, DEFAULT_FUZZY_MATCH_THRESHOLD)
+                .unwrap();
         let expected_diff = r#"@@ -1,4 +1,4 @@
  foo
 -bar
@@ -1400,7 +1664,7 @@ PATCH"#,
             _ => panic!("Expected a single UpdateFile hunk"),
         };
 
-        let diff = unified_diff_from_chunks(&path, chunks).unwrap();
+        let diff = unified_diff_from_chunks(&path, chunks, DEFAULT_FUZZY_MATCH_THRESHOLD).unwrap();
         let expected_diff = r#"@@ -1,2 +1,2 @@
 -foo
 +FOO
@@ -1437,7 +1701,7 @@ PATCH"#,
             _ => panic!("Expected a single UpdateFile hunk"),
         };
 
-        let diff = unified_diff_from_chunks(&path, chunks).unwrap();
+        let diff = unified_diff_from_chunks(&path, chunks, DEFAULT_FUZZY_MATCH_THRESHOLD).unwrap();
         let expected_diff = r#"@@ -2,2 +2,2 @@
  bar
 -baz
@@ -1472,7 +1736,7 @@ PATCH"#,
             _ => panic!("Expected a single UpdateFile hunk"),
         };
 
-        let diff = unified_diff_from_chunks(&path, chunks).unwrap();
+        let diff = unified_diff_from_chunks(&path, chunks, DEFAULT_FUZZY_MATCH_THRESHOLD).unwrap();
         let expected_diff = r#"@@ -3 +3,2 @@
  baz
 +quux
@@ -1518,7 +1782,7 @@ PATCH"#,
             _ => panic!("Expected a single UpdateFile hunk"),
         };
 
-        let diff = unified_diff_from_chunks(&path, chunks).unwrap();
+        let diff = unified_diff_from_chunks(&path, chunks, DEFAULT_FUZZY_MATCH_THRESHOLD).unwrap();
 
         let expected_diff = r#"@@ -1,6 +1,7 @@
  a
@@ -1577,7 +1841,11 @@ g
                 .to_string(),
         ];
 
-        let result = maybe_parse_apply_patch_verified(&argv, session_dir.path());
+        let result = maybe_parse_apply_patch_verified(
+            &argv,
+            session_dir.path(),
+            DEFAULT_FUZZY_MATCH_THRESHOLD,
+        );
 
         // Verify the patch contents - as otherwise we may have pulled contents
         // from the wrong file (as we're using relative paths)
@@ -1598,10 +1866,215 @@ g
                 )]),
                 patch: argv[1].clone(),
                 cwd: session_dir.path().to_path_buf(),
+                fuzzy_threshold: DEFAULT_FUZZY_MATCH_THRESHOLD,
             })
         );
     }
 
+    #[test]
+    fn test_failed_hunk_leaves_earlier_files_untouched() {
+        let dir = tempdir().unwrap();
+        let first = dir.path().join("first.txt");
+        let second = dir.path().join("second.txt");
+        let added = dir.path().join("added.txt");
+        fs::write(&first, "one\n").unwrap();
+        fs::write(&second, "two\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-one
++ONE
+*** Add File: {}
++new
+*** Update File: {}
+@@
+-does not exist
++TWO"#,
+            first.display(),
+            added.display(),
+            second.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let result = apply_patch(&patch, &mut stdout, &mut stderr);
+
+        assert!(result.is_err());
+        assert!(stdout.is_empty());
+        assert_eq!(fs::read_to_string(&first).unwrap(), "one\n");
+        assert_eq!(fs::read_to_string(&second).unwrap(), "two\n");
+        assert!(!added.exists());
+    }
+
+    #[test]
+    fn test_later_hunks_see_earlier_hunks_of_the_same_patch() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("twice.txt");
+        let patch = wrap_patch(&format!(
+            r#"*** Add File: {0}
++alpha
++beta
+*** Update File: {0}
+@@
+ alpha
+-beta
++gamma"#,
+            path.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "alpha\ngamma\n");
+    }
+
+    #[test]
+    fn test_update_applies_close_enough_match() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("greet.rs");
+        fs::write(
+            &path,
+            "fn greet() {\n    let name = \"world\";\n    println!(\"hello {name}\");\n}\n",
+        )
+        .unwrap();
+        // The expected lines carry a typo and lost their indentation; the
+        // context line keeps the file's indentation.
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-let name = "wrld";
++    let name = "there";
+ println!("hello {{name}}");"#,
+            path.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "fn greet() {\n    let name = \"there\";\n    println!(\"hello {name}\");\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_failed_match_reports_closest_region() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("greek.txt");
+        fs::write(&path, "alpha\nbeta\ngamma\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-beta
+-gamme
++delta"#,
+            path.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        let result = apply_patch(&patch, &mut stdout, &mut stderr);
+
+        assert!(result.is_err());
+        assert_eq!(
+            String::from_utf8(stderr).unwrap(),
+            format!(
+                "Failed to find expected lines in {}:\nbeta\ngamme\n\nClosest match (lines 2-3, 88% similar, below the 90% required):\n2 | beta\n3 | gamma\n",
+                path.display()
+            )
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "alpha\nbeta\ngamma\n");
+    }
+
+    #[test]
+    fn test_update_preserves_crlf_line_endings() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("crlf.txt");
+        fs::write(&path, "foo\r\nbar\r\nbaz\r\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+ foo
+-bar
++BAR
++qux"#,
+            path.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "foo\r\nBAR\r\nqux\r\nbaz\r\n"
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_update_and_move_preserve_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempdir().unwrap();
+        let script = dir.path().join("script.sh");
+        let moved = dir.path().join("moved.sh");
+        fs::write(&script, "echo hi\n").unwrap();
+        fs::set_permissions(&script, fs::Permissions::from_mode(0o755)).unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+*** Move to: {}
+@@
+-echo hi
++echo bye"#,
+            script.display(),
+            moved.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        apply_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        let mode = fs::metadata(&moved).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o755);
+        assert_eq!(fs::read_to_string(&moved).unwrap(), "echo bye\n");
+    }
+
+    #[test]
+    fn test_check_patch_does_not_modify_files() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("check.txt");
+        let added = dir.path().join("added.txt");
+        fs::write(&path, "before\n").unwrap();
+        let patch = wrap_patch(&format!(
+            r#"*** Update File: {}
+@@
+-before
++after
+*** Add File: {}
++new"#,
+            path.display(),
+            added.display()
+        ));
+
+        let mut stdout = Vec::new();
+        let mut stderr = Vec::new();
+        check_patch(&patch, &mut stdout, &mut stderr).unwrap();
+
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            format!(
+                "Patch applies cleanly. It would update the following files:\nA {}\nM {}\n",
+                added.display(),
+                path.display()
+            )
+        );
+        assert_eq!(fs::read_to_string(&path).unwrap(), "before\n");
+        assert!(!added.exists());
+    }
+
     #[test]
     fn test_apply_patch_fails_on_write_error() {
         let dir = tempdir().unwrap();
@@ -73,26 +73,6 @@ pub(crate) fn seek_sequence(
     // differences when locating context lines.
     // ------------------------------------------------------------------
 
-    fn normalise(s: &str) -> String {
-        s.trim()
-            .chars()
-            .map(|c| match c {
-                // Various dash / hyphen code-points → ASCII '-'
-                '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}'
-                | '\u{2212}' => '-',
-                // Fancy single quotes → '\''
-                '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
-                // Fancy double quotes → '"'
-                '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
-                // Non-breaking space and other odd spaces → normal space
-                '\u{00A0}' | '\u{2002}' | '\u{2003}' | '\u{2004}' | '\u{2005}' | '\u{2006}'
-                | '\u{2007}' | '\u{2008}' | '\u{2009}' | '\u{200A}' | '\u{202F}' | '\u{205F}'
-                | '\u{3000}' => ' ',
-                other => other,
-            })
-            .collect::<String>()
-    }
-
     for i in search_start..=lines.len().saturating_sub(pattern.len()) {
         let mut ok = true;
         for (p_idx, pat) in pattern.iter().enumerate() {
@@ -109,9 +89,170 @@ pub(crate) fn seek_sequence(
     None
 }
 
+/// Candidates whose similarity differs by less than this are considered
+/// equally good matches for a pattern.
+const AMBIGUITY_MARGIN: f64 = 0.02;
+
+/// The region of the input that most resembles a pattern.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) struct ClosestMatch {
+    /// Index of the first line of the region.
+    pub(crate) start: usize,
+    /// How alike the region and the pattern are, from `0.0` to `1.0`.
+    pub(crate) similarity: f64,
+    /// Another, non-overlapping region is about as similar, so this one cannot
+    /// be trusted to be the intended location.
+    pub(crate) ambiguous: bool,
+}
+
+/// Fallback for when [`seek_sequence`] finds nothing: accepts the region most
+/// similar to `pattern` as long as it is at least `threshold` similar and no
+/// other region comes close. Otherwise returns the closest region, if any, so
+/// the caller can explain what it found instead.
+pub(crate) fn seek_sequence_fuzzy(
+    lines: &[String],
+    pattern: &[String],
+    start: usize,
+    eof: bool,
+    threshold: f64,
+) -> Result<usize, Option<ClosestMatch>> {
+    match closest_sequence(lines, pattern, start, eof) {
+        Some(closest) if !closest.ambiguous && closest.similarity >= threshold => Ok(closest.start),
+        closest => Err(closest),
+    }
+}
+
+/// Scores every window of `pattern.len()` lines at or after `start` (only the
+/// last window when `eof` is true, like [`seek_sequence`]) by the character
+/// edit distance between its lines and the pattern's, ignoring indentation,
+/// and returns the best one.
+pub(crate) fn closest_sequence(
+    lines: &[String],
+    pattern: &[String],
+    start: usize,
+    eof: bool,
+) -> Option<ClosestMatch> {
+    if pattern.is_empty() || pattern.len() > lines.len() {
+        return None;
+    }
+    let last_start = lines.len() - pattern.len();
+    let search_start = if eof { last_start } else { start };
+    if search_start > last_start {
+        return None;
+    }
+
+    let pattern: Vec<Vec<char>> = pattern.iter().map(|line| normalise_chars(line)).collect();
+    let lines: Vec<Vec<char>> = lines[search_start..]
+        .iter()
+        .map(|line| normalise_chars(line))
+        .collect();
+
+    let mut scored: Vec<(usize, f64)> = Vec::new();
+    let mut best = 0.0_f64;
+    for offset in 0..=lines.len() - pattern.len() {
+        let window = &lines[offset..offset + pattern.len()];
+        // Length differences bound the edit distance from below, which lets
+        // us skip windows that cannot beat (or tie with) the best so far
+        // without running the quadratic distance computation.
+        let total: usize = window
+            .iter()
+            .zip(&pattern)
+            .map(|(line, pat)| line.len().max(pat.len()))
+            .sum();
+        let min_distance: usize = window
+            .iter()
+            .zip(&pattern)
+            .map(|(line, pat)| line.len().abs_diff(pat.len()))
+            .sum();
+        if similarity(min_distance, total) < best - AMBIGUITY_MARGIN {
+            continue;
+        }
+        let distance: usize = window
+            .iter()
+            .zip(&pattern)
+            .map(|(line, pat)| edit_distance(line, pat))
+            .sum();
+        let score = similarity(distance, total);
+        best = best.max(score);
+        scored.push((search_start + offset, score));
+    }
+
+    let (best_start, best_score) = scored.iter().copied().reduce(|best, candidate| {
+        if candidate.1 > best.1 {
+            candidate
+        } else {
+            best
+        }
+    })?;
+    let ambiguous = scored.iter().any(|&(candidate_start, score)| {
+        candidate_start.abs_diff(best_start) >= pattern.len()
+            && score >= best_score - AMBIGUITY_MARGIN
+    });
+    Some(ClosestMatch {
+        start: best_start,
+        similarity: best_score,
+        ambiguous,
+    })
+}
+
+fn similarity(distance: usize, total: usize) -> f64 {
+    if total == 0 {
+        1.0
+    } else {
+        1.0 - distance as f64 / total as f64
+    }
+}
+
+/// Levenshtein distance between two lines.
+fn edit_distance(a: &[char], b: &[char]) -> usize {
+    if a == b {
+        return 0;
+    }
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, a_char) in a.iter().enumerate() {
+        current[0] = i + 1;
+        for (j, b_char) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(a_char != b_char);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    previous[b.len()]
+}
+
+fn normalise_chars(s: &str) -> Vec<char> {
+    normalise(s).chars().collect()
+}
+
+/// Trims `s` and maps common Unicode punctuation to its ASCII equivalent.
+fn normalise(s: &str) -> String {
+    s.trim()
+        .chars()
+        .map(|c| match c {
+            // Various dash / hyphen code-points → ASCII '-'
+            '\u{2010}' | '\u{2011}' | '\u{2012}' | '\u{2013}' | '\u{2014}' | '\u{2015}'
+            | '\u{2212}' => '-',
+            // Fancy single quotes → '\''
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => '\'',
+            // Fancy double quotes → '"'
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => '"',
+            // Non-breaking space and other odd spaces → normal space
+            '\u{00A0}' | '\u{2002}' | '\u{2003}' | '\u{2004}' | '\u{2005}' | '\u{2006}'
+            | '\u{2007}' | '\u{2008}' | '\u{2009}' | '\u{200A}' | '\u{202F}' | '\u{205F}'
+            | '\u{3000}' => ' ',
+            other => other,
+        })
+        .collect::<String>()
+}
+
 #[cfg(test)]
 mod tests {
+    use super::ClosestMatch;
+    use super::closest_sequence;
     use super::seek_sequence;
+    use super::seek_sequence_fuzzy;
+    use pretty_assertions::assert_eq;
     use std::string::ToString;
 
     fn to_vec(strings: &[&str]) -> Vec<String> {
@@ -148,4 +289,58 @@ mod tests {
         // Should not panic – must return None when pattern cannot possibly fit.
         assert_eq!(seek_sequence(&lines, &pattern, 0, false), None);
     }
+
+    #[test]
+    fn test_fuzzy_match_tolerates_small_edits_and_reindentation() {
+        let lines = to_vec(&[
+            "fn main() {",
+            "        let answer = compute(41);",
+            "        println!(\"{answer}\");",
+            "}",
+        ]);
+        let pattern = to_vec(&[
+            "    let answer = compute(42);",
+            "    println!(\"{answer}\");",
+        ]);
+        assert_eq!(seek_sequence(&lines, &pattern, 0, false), None);
+        assert_eq!(seek_sequence_fuzzy(&lines, &pattern, 0, false, 0.9), Ok(1));
+    }
+
+    #[test]
+    fn test_fuzzy_match_below_threshold_reports_closest_region() {
+        let lines = to_vec(&["alpha", "beta", "gamma", "delta"]);
+        let pattern = to_vec(&["gamma", "delta!!"]);
+        let Err(Some(closest)) = seek_sequence_fuzzy(&lines, &pattern, 0, false, 0.9) else {
+            panic!("expected no confident match");
+        };
+        assert_eq!(closest.start, 2);
+        assert!(!closest.ambiguous);
+        assert!(closest.similarity > 0.8 && closest.similarity < 0.9);
+    }
+
+    #[test]
+    fn test_fuzzy_match_refuses_ambiguous_regions() {
+        let lines = to_vec(&["let x = 1;", "other", "let x = 2;"]);
+        let pattern = to_vec(&["let x = 3;"]);
+        assert_eq!(
+            seek_sequence_fuzzy(&lines, &pattern, 0, false, 0.5),
+            Err(Some(ClosestMatch {
+                start: 0,
+                similarity: 0.9,
+                ambiguous: true,
+            }))
+        );
+        // Starting past the first candidate leaves only one.
+        assert_eq!(seek_sequence_fuzzy(&lines, &pattern, 1, false, 0.5), Ok(2));
+    }
+
+    #[test]
+    fn test_closest_sequence_honours_eof() {
+        let lines = to_vec(&["tail", "middle", "tail"]);
+        let pattern = to_vec(&["tail"]);
+        assert_eq!(
+            closest_sequence(&lines, &pattern, 0, true).map(|closest| closest.start),
+            Some(2)
+        );
+    }
 }
@@ -9,10 +9,13 @@ pub fn main() -> ! {
 /// We would prefer to return `std::process::ExitCode`, but its `exit_process()`
 /// method is still a nightly API and we want main() to return !.
 pub fn run_main() -> i32 {
-    // Expect either one argument (the full apply_patch payload) or read it from stdin.
-    let mut args = std::env::args_os();
+    // Expect either one argument (the full apply_patch payload) or read it from stdin,
+    // optionally preceded by `--check` to verify the patch without applying it.
+    let mut args = std::env::args_os().peekable();
     let _argv0 = args.next();
 
+    let check_only = args.next_if(|arg| arg == "--check").is_some();
+
     let patch_arg = match args.next() {
         Some(arg) => match arg.into_string() {
             Ok(s) => s,
@@ -27,7 +30,9 @@ pub fn run_main() -> i32 {
             match std::io::stdin().read_to_string(&mut buf) {
                 Ok(_) => {
                     if buf.is_empty() {
-                        eprintln!("Usage: apply_patch 'PATCH'\n       echo 'PATCH' | apply-patch");
+                        eprintln!(
+                            "Usage: apply_patch [--check] 'PATCH'\n       echo 'PATCH' | apply-patch [--check]"
+                        );
                         return 2;
                     }
                     buf
@@ -48,7 +53,12 @@ pub fn run_main() -> i32 {
 
     let mut stdout = std::io::stdout();
     let mut stderr = std::io::stderr();
-    match crate::apply_patch(&patch_arg, &mut stdout, &mut stderr) {
+    let result = if check_only {
+        crate::check_patch(&patch_arg, &mut stdout, &mut stderr)
+    } else {
+        crate::apply_patch(&patch_arg, &mut stdout, &mut stderr)
+    };
+    match result {
         Ok(()) => {
             // Flush to ensure output ordering when used in pipelines.
             let _ = stdout.flush();
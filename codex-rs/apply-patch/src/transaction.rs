@@ -0,0 +1,242 @@
+//! Commits a fully computed patch to disk as a single unit.
+//!
+//! Every file is written to a temporary sibling and renamed into place, so a
+//! reader never observes a partially written file. If any operation fails, the
+//! files touched so far are restored from the backups taken before they were
+//! first modified, and any directories created along the way are removed.
+
+use std::fs::Permissions;
+use std::io::Write;
+use std::path::Path;
+use std::path::PathBuf;
+
+use anyhow::Context;
+
+/// A single file system operation of a planned patch.
+#[derive(Debug)]
+pub(crate) enum FileOperation {
+    Write {
+        path: PathBuf,
+        contents: String,
+        /// Permissions to give the written file; `None` uses the defaults for
+        /// a new file.
+        permissions: Option<Permissions>,
+    },
+    Delete {
+        path: PathBuf,
+    },
+}
+
+/// Applies `operations` in order, rolling back all of them if one fails.
+pub(crate) fn commit(operations: &[FileOperation]) -> anyhow::Result<()> {
+    let mut journal = Journal::default();
+    for operation in operations {
+        if let Err(err) = journal.apply(operation) {
+            journal.rollback();
+            return Err(err);
+        }
+    }
+    Ok(())
+}
+
+/// Original state of a file before the patch touched it.
+struct Backup {
+    path: PathBuf,
+    /// `None` if the file did not exist.
+    original: Option<(Vec<u8>, Permissions)>,
+}
+
+#[derive(Default)]
+struct Journal {
+    backups: Vec<Backup>,
+    created_dirs: Vec<PathBuf>,
+}
+
+impl Journal {
+    fn apply(&mut self, operation: &FileOperation) -> anyhow::Result<()> {
+        match operation {
+            FileOperation::Write {
+                path,
+                contents,
+                permissions,
+            } => {
+                self.back_up(path)?;
+                self.create_parent_dirs(path)?;
+                write_atomically(path, contents.as_bytes(), permissions.as_ref())
+                    .with_context(|| format!("Failed to write file {}", path.display()))
+            }
+            FileOperation::Delete { path } => {
+                self.back_up(path)?;
+                std::fs::remove_file(path)
+                    .with_context(|| format!("Failed to delete file {}", path.display()))
+            }
+        }
+    }
+
+    fn back_up(&mut self, path: &Path) -> anyhow::Result<()> {
+        if self.backups.iter().any(|backup| backup.path == path) {
+            return Ok(());
+        }
+        let original = match std::fs::read(path) {
+            Ok(contents) => {
+                let permissions = std::fs::metadata(path)
+                    .with_context(|| format!("Failed to back up {}", path.display()))?
+                    .permissions();
+                Some((contents, permissions))
+            }
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => None,
+            Err(err) => {
+                return Err(err).with_context(|| format!("Failed to back up {}", path.display()));
+            }
+        };
+        self.backups.push(Backup {
+            path: path.to_path_buf(),
+            original,
+        });
+        Ok(())
+    }
+
+    fn create_parent_dirs(&mut self, path: &Path) -> anyhow::Result<()> {
+        let Some(parent) = path
+            .parent()
+            .filter(|parent| !parent.as_os_str().is_empty())
+        else {
+            return Ok(());
+        };
+        let missing: Vec<&Path> = parent
+            .ancestors()
+            .take_while(|dir| !dir.as_os_str().is_empty() && !dir.exists())
+            .collect();
+        for dir in missing.into_iter().rev() {
+            std::fs::create_dir(dir).with_context(|| {
+                format!("Failed to create parent directories for {}", path.display())
+            })?;
+            self.created_dirs.push(dir.to_path_buf());
+        }
+        Ok(())
+    }
+
+    /// Best effort: restores what it can and keeps going past failures.
+    fn rollback(self) {
+        for backup in self.backups.into_iter().rev() {
+            match backup.original {
+                Some((contents, permissions)) => {
+                    let _ = write_atomically(&backup.path, &contents, Some(&permissions));
+                }
+                None => {
+                    let _ = std::fs::remove_file(&backup.path);
+                }
+            }
+        }
+        for dir in self.created_dirs.into_iter().rev() {
+            let _ = std::fs::remove_dir(dir);
+        }
+    }
+}
+
+/// Writes `contents` to a temporary file next to `path` and renames it over
+/// `path`. Symlinks are followed so the link itself is preserved.
+fn write_atomically(
+    path: &Path,
+    contents: &[u8],
+    permissions: Option<&Permissions>,
+) -> std::io::Result<()> {
+    let path = match std::fs::symlink_metadata(path) {
+        Ok(metadata) if metadata.file_type().is_symlink() => std::fs::canonicalize(path)?,
+        _ => path.to_path_buf(),
+    };
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let mut builder = tempfile::Builder::new();
+    builder.prefix(".apply_patch");
+    // Temporary files are private by default; a new file should get the
+    // usual umask-derived mode instead.
+    #[cfg(unix)]
+    if permissions.is_none() {
+        use std::os::unix::fs::PermissionsExt;
+        builder.permissions(Permissions::from_mode(0o666));
+    }
+    let mut temp_file = builder.tempfile_in(dir)?;
+    temp_file.write_all(contents)?;
+    if let Some(permissions) = permissions {
+        temp_file.as_file().set_permissions(permissions.clone())?;
+    }
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(&path).map_err(|err| err.error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use tempfile::tempdir;
+
+    #[test]
+    fn failed_operation_rolls_back_earlier_ones() {
+        let dir = tempdir().unwrap();
+        let existing = dir.path().join("existing.txt");
+        let deleted = dir.path().join("deleted.txt");
+        let added = dir.path().join("new/dir/added.txt");
+        fs::write(&existing, "before\n").unwrap();
+        fs::write(&deleted, "keep me\n").unwrap();
+
+        let result = commit(&[
+            FileOperation::Write {
+                path: existing.clone(),
+                contents: "after\n".to_string(),
+                permissions: None,
+            },
+            FileOperation::Delete {
+                path: deleted.clone(),
+            },
+            FileOperation::Write {
+                path: added.clone(),
+                contents: "added\n".to_string(),
+                permissions: None,
+            },
+            FileOperation::Delete {
+                path: dir.path().join("missing.txt"),
+            },
+        ]);
+
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&existing).unwrap(), "before\n");
+        assert_eq!(fs::read_to_string(&deleted).unwrap(), "keep me\n");
+        assert!(!added.exists());
+        assert!(!dir.path().join("new").exists());
+        let leftovers: Vec<_> = fs::read_dir(dir.path())
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name())
+            .collect();
+        assert_eq!(leftovers.len(), 2, "unexpected files: {leftovers:?}");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn writes_through_symlinks() {
+        let dir = tempdir().unwrap();
+        let target = dir.path().join("target.txt");
+        let link = dir.path().join("link.txt");
+        fs::write(&target, "before\n").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+
+        commit(&[FileOperation::Write {
+            path: link.clone(),
+            contents: "after\n".to_string(),
+            permissions: None,
+        }])
+        .unwrap();
+
+        assert!(
+            fs::symlink_metadata(&link)
+                .unwrap()
+                .file_type()
+                .is_symlink()
+        );
+        assert_eq!(fs::read_to_string(&target).unwrap(), "after\n");
+    }
+}
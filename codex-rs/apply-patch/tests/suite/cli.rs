@@ -88,3 +88,42 @@ fn test_apply_patch_cli_stdin_add_and_update() -> anyhow::Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn test_apply_patch_cli_check_does_not_write() -> anyhow::Result<()> {
+    let tmp = tempdir()?;
+    let file = "cli_check.txt";
+    let absolute_path = tmp.path().join(file);
+    fs::write(&absolute_path, "hello\n")?;
+
+    let update_patch = format!(
+        r#"*** Begin Patch
+*** Update File: {file}
+@@
+-hello
++world
+*** End Patch"#
+    );
+    Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .arg("--check")
+        .arg(&update_patch)
+        .current_dir(tmp.path())
+        .assert()
+        .success()
+        .stdout(format!(
+            "Patch applies cleanly. It would update the following files:\nM {file}\n"
+        ));
+    assert_eq!(fs::read_to_string(&absolute_path)?, "hello\n");
+
+    let mismatched_patch = update_patch.replace("-hello", "-goodbye");
+    Command::cargo_bin("apply_patch")
+        .expect("should find apply_patch binary")
+        .arg("--check")
+        .arg(mismatched_patch)
+        .current_dir(tmp.path())
+        .assert()
+        .failure();
+
+    Ok(())
+}
@@ -1,4 +1,5 @@
 use codex_core::CodexAuth;
+use codex_core::credential_store::CredentialStore;
 use std::path::Path;
 use std::sync::LazyLock;
 use std::sync::RwLock;
@@ -17,9 +18,12 @@ pub fn set_chatgpt_token_data(value: TokenData) {
     }
 }
 
-/// Initialize the ChatGPT token from auth.json file
-pub async fn init_chatgpt_token_from_auth(codex_home: &Path) -> std::io::Result<()> {
-    let auth = CodexAuth::from_codex_home(codex_home)?;
+/// Initialize the ChatGPT token from the stored auth.json
+pub async fn init_chatgpt_token_from_auth(
+    codex_home: &Path,
+    credential_store: &CredentialStore,
+) -> std::io::Result<()> {
+    let auth = CodexAuth::from_codex_home(codex_home, credential_store)?;
     if let Some(auth) = auth {
         let token_data = auth.get_token_data().await?;
         set_chatgpt_token_data(token_data);
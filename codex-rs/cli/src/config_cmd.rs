@@ -0,0 +1,185 @@
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use codex_common::CliConfigOverrides;
+use codex_core::config::Config;
+use codex_core::config::ConfigOverrides;
+use serde::Serialize;
+use toml::Table;
+use toml::Value;
+
+/// Inspect the configuration Codex resolves from `config.toml`, profiles and
+/// CLI overrides.
+///
+/// Subcommands:
+/// - `show` — print the effective configuration as TOML
+#[derive(Debug, clap::Parser)]
+pub struct ConfigCli {
+    #[clap(flatten)]
+    pub config_overrides: CliConfigOverrides,
+
+    #[command(subcommand)]
+    pub subcommand: ConfigSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum ConfigSubcommand {
+    /// Print the effective configuration, after profiles (including their
+    /// `extends` chain) and overrides have been applied.
+    Show(ShowArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ShowArgs {
+    /// Profile to resolve instead of the one selected in config.toml.
+    #[arg(long = "profile", short = 'p')]
+    pub profile: Option<String>,
+}
+
+impl ConfigCli {
+    pub fn run(self) -> Result<()> {
+        let ConfigCli {
+            config_overrides,
+            subcommand,
+        } = self;
+
+        match subcommand {
+            ConfigSubcommand::Show(args) => run_show(&config_overrides, args),
+        }
+    }
+}
+
+fn run_show(config_overrides: &CliConfigOverrides, show_args: ShowArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(
+        overrides,
+        ConfigOverrides {
+            config_profile: show_args.profile,
+            ..Default::default()
+        },
+    )
+    .context("failed to load configuration")?;
+
+    print!(
+        "{}",
+        toml::to_string_pretty(&resolved_config_table(&config)?)?
+    );
+    Ok(())
+}
+
+fn to_value<T: Serialize>(value: &T) -> Result<Value> {
+    Value::try_from(value).context("failed to serialize configuration value")
+}
+
+/// Shown instead of values that may hold secrets, since the output tends to
+/// end up in bug reports.
+const REDACTED: &str = "<redacted>";
+
+/// `names` with every value replaced by [`REDACTED`].
+fn redacted_table<'a>(names: impl IntoIterator<Item = &'a String>) -> Table {
+    names
+        .into_iter()
+        .map(|name| (name.clone(), Value::String(REDACTED.to_string())))
+        .collect()
+}
+
+/// The settings of `config` that profiles can change, laid out like
+/// `config.toml`, with values that may hold secrets redacted.
+fn resolved_config_table(config: &Config) -> Result<Table> {
+    let mut table = Table::new();
+    if let Some(profile) = &config.active_profile {
+        table.insert("profile".to_string(), Value::String(profile.clone()));
+    }
+    table.insert("model".to_string(), Value::String(config.model.clone()));
+    table.insert(
+        "model_provider".to_string(),
+        Value::String(config.model_provider_id.clone()),
+    );
+    if let Some(effort) = &config.model_reasoning_effort {
+        table.insert("model_reasoning_effort".to_string(), to_value(effort)?);
+    }
+    table.insert(
+        "model_reasoning_summary".to_string(),
+        to_value(&config.model_reasoning_summary)?,
+    );
+    if let Some(verbosity) = &config.model_verbosity {
+        table.insert("model_verbosity".to_string(), to_value(verbosity)?);
+    }
+    table.insert(
+        "approval_policy".to_string(),
+        to_value(&config.approval_policy)?,
+    );
+    if let Some(notify) = &config.notify {
+        table.insert("notify".to_string(), to_value(notify)?);
+    }
+    table.insert(
+        "project_doc_max_bytes".to_string(),
+        to_value(&config.project_doc_max_bytes)?,
+    );
+
+    // `mode` plus the workspace-write settings, as in `SandboxPolicy`.
+    table.insert("sandbox".to_string(), to_value(&config.sandbox_policy)?);
+
+    let mut tools = Table::new();
+    tools.insert(
+        "web_search".to_string(),
+        Value::Boolean(config.tools_web_search_request),
+    );
+    tools.insert(
+        "view_image".to_string(),
+        Value::Boolean(config.include_view_image_tool),
+    );
+    tools.insert(
+        "spawn_agent".to_string(),
+        Value::Boolean(config.include_spawn_agent_tool),
+    );
+    table.insert("tools".to_string(), Value::Table(tools));
+
+    let policy = &config.shell_environment_policy;
+    let patterns = |patterns: &[codex_core::config_types::EnvironmentVariablePattern]| {
+        Value::Array(
+            patterns
+                .iter()
+                .map(|pattern| Value::String(pattern.to_string()))
+                .collect(),
+        )
+    };
+    let mut shell_environment_policy = Table::new();
+    shell_environment_policy.insert("inherit".to_string(), to_value(&policy.inherit)?);
+    shell_environment_policy.insert(
+        "ignore_default_excludes".to_string(),
+        Value::Boolean(policy.ignore_default_excludes),
+    );
+    shell_environment_policy.insert("exclude".to_string(), patterns(&policy.exclude));
+    shell_environment_policy.insert("include_only".to_string(), patterns(&policy.include_only));
+    shell_environment_policy.insert(
+        "set".to_string(),
+        Value::Table(redacted_table(policy.r#set.keys())),
+    );
+    shell_environment_policy.insert(
+        "use_profile".to_string(),
+        Value::Boolean(policy.use_profile),
+    );
+    table.insert(
+        "shell_environment_policy".to_string(),
+        Value::Table(shell_environment_policy),
+    );
+
+    let mut mcp_servers = Table::new();
+    for (name, server) in &config.mcp_servers {
+        let mut server_value = to_value(server)?;
+        if let Value::Table(server_table) = &mut server_value {
+            if let Some(token) = server_table.get_mut("bearer_token") {
+                *token = Value::String(REDACTED.to_string());
+            }
+            if let Some(Value::Table(env)) = server_table.get_mut("env") {
+                let redacted = redacted_table(env.keys());
+                *env = redacted;
+            }
+        }
+        mcp_servers.insert(name.clone(), server_value);
+    }
+    table.insert("mcp_servers".to_string(), Value::Table(mcp_servers));
+
+    Ok(table)
+}
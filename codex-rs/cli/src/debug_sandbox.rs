@@ -5,6 +5,7 @@ use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::exec_env::create_env;
 use codex_core::landlock::spawn_command_under_linux_sandbox;
+use codex_core::resource_limits::collect_cgroup_scope;
 use codex_core::seatbelt::spawn_command_under_seatbelt;
 use codex_core::spawn::StdioPolicy;
 use codex_protocol::config_types::SandboxMode;
@@ -109,13 +110,20 @@ async fn run_command_under_sandbox(
                 cwd,
                 &config.sandbox_policy,
                 sandbox_policy_cwd.as_path(),
+                &config.sandbox_resource_limits,
                 stdio_policy,
                 env,
             )
             .await?
         }
     };
+    let child_pid = child.id();
     let status = child.wait().await?;
+    if let Some(pid) = child_pid
+        && let Some(limit) = collect_cgroup_scope(pid)
+    {
+        eprintln!("command exceeded the sandbox {limit}");
+    }
 
     handle_exit_status(status);
 }
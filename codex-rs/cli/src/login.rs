@@ -6,13 +6,18 @@ use codex_core::auth::login_with_api_key;
 use codex_core::auth::logout;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::credential_store::CredentialStore;
 use codex_login::ServerOptions;
 use codex_login::run_device_code_login;
 use codex_login::run_login_server;
 use std::path::PathBuf;
 
-pub async fn login_with_chatgpt(codex_home: PathBuf) -> std::io::Result<()> {
-    let opts = ServerOptions::new(codex_home, CLIENT_ID.to_string());
+pub async fn login_with_chatgpt(
+    codex_home: PathBuf,
+    credential_store: CredentialStore,
+) -> std::io::Result<()> {
+    let mut opts = ServerOptions::new(codex_home, CLIENT_ID.to_string());
+    opts.credential_store = credential_store;
     let server = run_login_server(opts)?;
 
     eprintln!(
@@ -26,7 +31,7 @@ pub async fn login_with_chatgpt(codex_home: PathBuf) -> std::io::Result<()> {
 pub async fn run_login_with_chatgpt(cli_config_overrides: CliConfigOverrides) -> ! {
     let config = load_config_or_exit(cli_config_overrides);
 
-    match login_with_chatgpt(config.codex_home).await {
+    match login_with_chatgpt(config.codex_home, config.credential_store).await {
         Ok(_) => {
             eprintln!("Successfully logged in");
             std::process::exit(0);
@@ -44,7 +49,7 @@ pub async fn run_login_with_api_key(
 ) -> ! {
     let config = load_config_or_exit(cli_config_overrides);
 
-    match login_with_api_key(&config.codex_home, &api_key) {
+    match login_with_api_key(&config.codex_home, &api_key, &config.credential_store) {
         Ok(_) => {
             eprintln!("Successfully logged in");
             std::process::exit(0);
@@ -67,6 +72,7 @@ pub async fn run_login_with_device_code(
         config.codex_home,
         client_id.unwrap_or(CLIENT_ID.to_string()),
     );
+    opts.credential_store = config.credential_store;
     if let Some(iss) = issuer_base_url {
         opts.issuer = iss;
     }
@@ -85,7 +91,7 @@ pub async fn run_login_with_device_code(
 pub async fn run_login_status(cli_config_overrides: CliConfigOverrides) -> ! {
     let config = load_config_or_exit(cli_config_overrides);
 
-    match CodexAuth::from_codex_home(&config.codex_home) {
+    match CodexAuth::from_codex_home(&config.codex_home, &config.credential_store) {
         Ok(Some(auth)) => match auth.mode {
             AuthMode::ApiKey => match auth.get_token().await {
                 Ok(api_key) => {
@@ -116,7 +122,7 @@ pub async fn run_login_status(cli_config_overrides: CliConfigOverrides) -> ! {
 pub async fn run_logout(cli_config_overrides: CliConfigOverrides) -> ! {
     let config = load_config_or_exit(cli_config_overrides);
 
-    match logout(&config.codex_home) {
+    match logout(&config.codex_home, &config.credential_store) {
         Ok(true) => {
             eprintln!("Successfully logged out");
             std::process::exit(0);
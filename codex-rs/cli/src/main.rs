@@ -22,9 +22,14 @@ use owo_colors::OwoColorize;
 use std::path::PathBuf;
 use supports_color::Stream;
 
+mod config_cmd;
 mod mcp_cmd;
+mod worktree_cmd;
 
+use crate::config_cmd::ConfigCli;
 use crate::mcp_cmd::McpCli;
+use crate::worktree_cmd::WorktreeCli;
+use crate::worktree_cmd::finish_interactive_worktree;
 
 /// Codex CLI
 ///
@@ -69,9 +74,15 @@ enum Subcommand {
     /// [experimental] Run the Codex MCP server (stdio transport).
     McpServer,
 
+    /// Inspect the resolved configuration.
+    Config(ConfigCli),
+
     /// [experimental] Run the app server.
     AppServer,
 
+    /// Manage git worktrees created by `codex --worktree`.
+    Worktree(WorktreeCli),
+
     /// Generate shell completion scripts.
     Completion(CompletionCommand),
 
@@ -187,6 +198,7 @@ fn format_exit_messages(exit_info: AppExitInfo, color_enabled: bool) -> Vec<Stri
     let AppExitInfo {
         token_usage,
         conversation_id,
+        session_worktree: _,
     } = exit_info;
 
     if token_usage.is_zero() {
@@ -247,7 +259,11 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 root_config_overrides.clone(),
             );
             let exit_info = codex_tui::run_main(interactive, codex_linux_sandbox_exe).await?;
+            let session_worktree = exit_info.session_worktree.clone();
             print_exit_messages(exit_info);
+            if let Some(worktree) = session_worktree {
+                finish_interactive_worktree(&worktree)?;
+            }
         }
         Some(Subcommand::Exec(mut exec_cli)) => {
             prepend_config_flags(
@@ -264,6 +280,13 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             prepend_config_flags(&mut mcp_cli.config_overrides, root_config_overrides.clone());
             mcp_cli.run().await?;
         }
+        Some(Subcommand::Config(mut config_cli)) => {
+            prepend_config_flags(
+                &mut config_cli.config_overrides,
+                root_config_overrides.clone(),
+            );
+            config_cli.run()?;
+        }
         Some(Subcommand::AppServer) => {
             codex_app_server::run_main(codex_linux_sandbox_exe, root_config_overrides).await?;
         }
@@ -279,7 +302,10 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
                 last,
                 config_overrides,
             );
-            codex_tui::run_main(interactive, codex_linux_sandbox_exe).await?;
+            let exit_info = codex_tui::run_main(interactive, codex_linux_sandbox_exe).await?;
+            if let Some(worktree) = exit_info.session_worktree {
+                finish_interactive_worktree(&worktree)?;
+            }
         }
         Some(Subcommand::Login(mut login_cli)) => {
             prepend_config_flags(
@@ -313,6 +339,9 @@ async fn cli_main(codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()
             );
             run_logout(logout_cli.config_overrides).await;
         }
+        Some(Subcommand::Worktree(worktree_cli)) => {
+            worktree_cli.run()?;
+        }
         Some(Subcommand::Completion(completion_cli)) => {
             print_completion(completion_cli);
         }
@@ -429,6 +458,15 @@ fn merge_resume_cli_flags(interactive: &mut TuiCli, resume_cli: TuiCli) {
     if let Some(cwd) = resume_cli.cwd {
         interactive.cwd = Some(cwd);
     }
+    if resume_cli.worktree {
+        interactive.worktree = true;
+    }
+    if let Some(dir) = resume_cli.record_cassette {
+        interactive.record_cassette = Some(dir);
+    }
+    if let Some(dir) = resume_cli.replay_cassette {
+        interactive.replay_cassette = Some(dir);
+    }
     if resume_cli.web_search {
         interactive.web_search = true;
     }
@@ -488,6 +526,7 @@ mod tests {
             conversation_id: conversation
                 .map(ConversationId::from_string)
                 .map(Result::unwrap),
+            session_worktree: None,
         }
     }
 
@@ -496,6 +535,7 @@ mod tests {
         let exit_info = AppExitInfo {
             token_usage: TokenUsage::default(),
             conversation_id: None,
+            session_worktree: None,
         };
         let lines = format_exit_messages(exit_info, false);
         assert!(lines.is_empty());
@@ -12,6 +12,10 @@ use codex_core::config::load_global_mcp_servers;
 use codex_core::config::write_global_mcp_servers;
 use codex_core::config_types::McpServerConfig;
 use codex_core::config_types::McpServerTransportConfig;
+use codex_core::credential_store::mcp_server_credential;
+use codex_core::mcp_oauth::McpOAuthTokens;
+use codex_login::McpLoginOptions;
+use codex_login::run_mcp_login_server;
 
 /// [experimental] Launch Codex as an MCP server or manage configured MCP servers.
 ///
@@ -21,6 +25,9 @@ use codex_core::config_types::McpServerTransportConfig;
 /// - `get`    — show a single server (with `--json`)
 /// - `add`    — add a server launcher entry to `~/.codex/config.toml`
 /// - `remove` — delete a server entry
+/// - `set-token` — save a bearer token for a server in the credential store
+/// - `login`  — sign in to a streamable HTTP server with OAuth
+/// - `logout` — forget the OAuth tokens for a server
 #[derive(Debug, clap::Parser)]
 pub struct McpCli {
     #[clap(flatten)]
@@ -43,6 +50,15 @@ pub enum McpSubcommand {
 
     /// [experimental] Remove a global MCP server entry.
     Remove(RemoveArgs),
+
+    /// [experimental] Save the bearer token for an MCP server in the credential store.
+    SetToken(SetTokenArgs),
+
+    /// [experimental] Sign in to a streamable HTTP MCP server with OAuth.
+    Login(LoginArgs),
+
+    /// [experimental] Remove the OAuth tokens stored for an MCP server.
+    Logout(LogoutArgs),
 }
 
 #[derive(Debug, clap::Parser)]
@@ -82,6 +98,38 @@ pub struct RemoveArgs {
     pub name: String,
 }
 
+#[derive(Debug, clap::Parser)]
+pub struct SetTokenArgs {
+    /// Name of the MCP server the token is for.
+    pub name: String,
+
+    /// Remove the stored token instead of reading a new one from stdin.
+    #[arg(long)]
+    pub clear: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct LoginArgs {
+    /// Name of the MCP server to sign in to.
+    pub name: String,
+
+    /// Client id registered with the authorization server. Only needed when it
+    /// does not support dynamic client registration.
+    #[arg(long)]
+    pub client_id: Option<String>,
+
+    /// Scope to request (can be repeated). Defaults to the scopes the server
+    /// advertises.
+    #[arg(long = "scope", value_name = "SCOPE")]
+    pub scopes: Vec<String>,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct LogoutArgs {
+    /// Name of the MCP server to sign out of.
+    pub name: String,
+}
+
 impl McpCli {
     pub async fn run(self) -> Result<()> {
         let McpCli {
@@ -102,6 +150,15 @@ impl McpCli {
             McpSubcommand::Remove(args) => {
                 run_remove(&config_overrides, args)?;
             }
+            McpSubcommand::SetToken(args) => {
+                run_set_token(&config_overrides, args)?;
+            }
+            McpSubcommand::Login(args) => {
+                run_login(&config_overrides, args).await?;
+            }
+            McpSubcommand::Logout(args) => {
+                run_logout(&config_overrides, args)?;
+            }
         }
 
         Ok(())
@@ -157,21 +214,34 @@ fn run_add(config_overrides: &CliConfigOverrides, add_args: AddArgs) -> Result<(
 }
 
 fn run_remove(config_overrides: &CliConfigOverrides, remove_args: RemoveArgs) -> Result<()> {
-    config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .context("failed to load configuration")?;
 
     let RemoveArgs { name } = remove_args;
 
     validate_server_name(&name)?;
 
-    let codex_home = find_codex_home().context("failed to resolve CODEX_HOME")?;
-    let mut servers = load_global_mcp_servers(&codex_home)
+    let codex_home = &config.codex_home;
+    let mut servers = load_global_mcp_servers(codex_home)
         .with_context(|| format!("failed to load MCP servers from {}", codex_home.display()))?;
 
     let removed = servers.remove(&name).is_some();
 
     if removed {
-        write_global_mcp_servers(&codex_home, &servers)
+        write_global_mcp_servers(codex_home, &servers)
             .with_context(|| format!("failed to write MCP servers to {}", codex_home.display()))?;
+        // The server is gone at this point, so failing to erase its
+        // credentials only warrants a warning.
+        if let Err(err) = config
+            .credential_store
+            .erase(codex_home, &mcp_server_credential(&name))
+        {
+            eprintln!("Warning: failed to erase the stored token for '{name}': {err}");
+        }
+        if let Err(err) = McpOAuthTokens::erase(codex_home, &config.credential_store, &name) {
+            eprintln!("Warning: failed to erase the OAuth tokens for '{name}': {err}");
+        }
     }
 
     if removed {
@@ -183,6 +253,112 @@ fn run_remove(config_overrides: &CliConfigOverrides, remove_args: RemoveArgs) ->
     Ok(())
 }
 
+fn run_set_token(config_overrides: &CliConfigOverrides, args: SetTokenArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .context("failed to load configuration")?;
+
+    let SetTokenArgs { name, clear } = args;
+
+    validate_server_name(&name)?;
+
+    let credential = mcp_server_credential(&name);
+    if clear {
+        let erased = config
+            .credential_store
+            .erase(&config.codex_home, &credential)
+            .with_context(|| format!("failed to erase the stored token for '{name}'"))?;
+        if erased {
+            println!("Removed the stored token for MCP server '{name}'.");
+        } else {
+            println!("No token stored for MCP server '{name}'.");
+        }
+        return Ok(());
+    }
+
+    if std::io::IsTerminal::is_terminal(&std::io::stdin()) {
+        eprintln!("Paste the bearer token for '{name}' and press Enter:");
+    }
+    let mut token = String::new();
+    std::io::stdin()
+        .read_line(&mut token)
+        .context("failed to read token from stdin")?;
+    let token = token.trim();
+    if token.is_empty() {
+        bail!("no token provided on stdin");
+    }
+
+    config
+        .credential_store
+        .save(&config.codex_home, &credential, token)
+        .with_context(|| format!("failed to store the token for '{name}'"))?;
+
+    println!("Stored the bearer token for MCP server '{name}'.");
+
+    Ok(())
+}
+
+async fn run_login(config_overrides: &CliConfigOverrides, login_args: LoginArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .context("failed to load configuration")?;
+
+    let LoginArgs {
+        name,
+        client_id,
+        scopes,
+    } = login_args;
+
+    let Some(server) = config.mcp_servers.get(&name) else {
+        bail!("No MCP server named '{name}' found.");
+    };
+    let McpServerTransportConfig::StreamableHttp { url, .. } = &server.transport else {
+        bail!("OAuth login is only supported for streamable HTTP MCP servers.");
+    };
+
+    let mut opts = McpLoginOptions::new(config.codex_home.clone(), name.clone(), url.clone());
+    opts.credential_store = config.credential_store.clone();
+    opts.client_id = client_id;
+    opts.scopes = scopes;
+    let server = run_mcp_login_server(opts)
+        .await
+        .with_context(|| format!("failed to start OAuth login for '{name}'"))?;
+
+    eprintln!(
+        "Starting local login server on http://localhost:{}.\nIf your browser did not open, navigate to this URL to authenticate:\n\n{}",
+        server.actual_port, server.auth_url,
+    );
+
+    server
+        .block_until_done()
+        .await
+        .with_context(|| format!("failed to log in to MCP server '{name}'"))?;
+
+    println!("Successfully logged in to MCP server '{name}'.");
+
+    Ok(())
+}
+
+fn run_logout(config_overrides: &CliConfigOverrides, logout_args: LogoutArgs) -> Result<()> {
+    let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
+    let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
+        .context("failed to load configuration")?;
+
+    let LogoutArgs { name } = logout_args;
+
+    validate_server_name(&name)?;
+
+    let removed = McpOAuthTokens::erase(&config.codex_home, &config.credential_store, &name)
+        .with_context(|| format!("failed to erase the OAuth tokens for '{name}'"))?;
+    if removed {
+        println!("Logged out of MCP server '{name}'.");
+    } else {
+        println!("Not logged in to MCP server '{name}'.");
+    }
+
+    Ok(())
+}
+
 fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) -> Result<()> {
     let overrides = config_overrides.parse_overrides().map_err(|e| anyhow!(e))?;
     let config = Config::load_with_cli_overrides(overrides, ConfigOverrides::default())
@@ -202,11 +378,16 @@ fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) -> Resul
                         "args": args,
                         "env": env,
                     }),
-                    McpServerTransportConfig::StreamableHttp { url, bearer_token } => {
+                    McpServerTransportConfig::StreamableHttp {
+                        url,
+                        bearer_token,
+                        bearer_token_env_var,
+                    } => {
                         serde_json::json!({
                             "type": "streamable_http",
                             "url": url,
                             "bearer_token": bearer_token,
+                            "bearer_token_env_var": bearer_token_env_var,
                         })
                     }
                 };
@@ -259,8 +440,12 @@ fn run_list(config_overrides: &CliConfigOverrides, list_args: ListArgs) -> Resul
                 };
                 stdio_rows.push([name.clone(), command.clone(), args_display, env_display]);
             }
-            McpServerTransportConfig::StreamableHttp { url, bearer_token } => {
-                let has_bearer = if bearer_token.is_some() {
+            McpServerTransportConfig::StreamableHttp {
+                url,
+                bearer_token,
+                bearer_token_env_var,
+            } => {
+                let has_bearer = if bearer_token.is_some() || bearer_token_env_var.is_some() {
                     "True"
                 } else {
                     "False"
@@ -360,10 +545,15 @@ fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Result<(
                 "args": args,
                 "env": env,
             }),
-            McpServerTransportConfig::StreamableHttp { url, bearer_token } => serde_json::json!({
+            McpServerTransportConfig::StreamableHttp {
+                url,
+                bearer_token,
+                bearer_token_env_var,
+            } => serde_json::json!({
                 "type": "streamable_http",
                 "url": url,
                 "bearer_token": bearer_token,
+                "bearer_token_env_var": bearer_token_env_var,
             }),
         };
         let output = serde_json::to_string_pretty(&serde_json::json!({
@@ -406,11 +596,18 @@ fn run_get(config_overrides: &CliConfigOverrides, get_args: GetArgs) -> Result<(
             };
             println!("  env: {env_display}");
         }
-        McpServerTransportConfig::StreamableHttp { url, bearer_token } => {
+        McpServerTransportConfig::StreamableHttp {
+            url,
+            bearer_token,
+            bearer_token_env_var,
+        } => {
             println!("  transport: streamable_http");
             println!("  url: {url}");
             let bearer = bearer_token.as_deref().unwrap_or("-");
             println!("  bearer_token: {bearer}");
+            if let Some(env_var) = bearer_token_env_var {
+                println!("  bearer_token_env_var: {env_var}");
+            }
         }
     }
     if let Some(timeout) = server.startup_timeout_sec {
@@ -0,0 +1,238 @@
+use std::io::IsTerminal;
+use std::io::Write;
+use std::path::PathBuf;
+
+use anyhow::Context;
+use anyhow::Result;
+use codex_git_tooling::SessionWorktree;
+use codex_git_tooling::WorktreeDisposition;
+use codex_git_tooling::finish_session_worktree;
+use codex_git_tooling::list_worktrees;
+use codex_git_tooling::prune_worktrees;
+use codex_git_tooling::worktree_has_changes;
+
+/// Manage git worktrees created by `codex --worktree`.
+///
+/// Subcommands:
+/// - `list`  — list session worktrees of the current repository
+/// - `prune` — remove session worktrees and their merged branches
+#[derive(Debug, clap::Parser)]
+pub struct WorktreeCli {
+    /// Repository to operate on (defaults to the current directory).
+    #[clap(long = "cd", short = 'C', value_name = "DIR", global = true)]
+    pub cwd: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub subcommand: WorktreeSubcommand,
+}
+
+#[derive(Debug, clap::Subcommand)]
+pub enum WorktreeSubcommand {
+    /// List session worktrees.
+    List(ListArgs),
+
+    /// Remove session worktrees. Worktrees of running sessions are always
+    /// kept, worktrees with uncommitted changes are kept unless `--force` is
+    /// given; branches are only deleted once merged.
+    Prune(PruneArgs),
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct ListArgs {
+    /// Output the worktrees as JSON.
+    #[arg(long)]
+    pub json: bool,
+}
+
+#[derive(Debug, clap::Parser)]
+pub struct PruneArgs {
+    /// Also remove worktrees that have uncommitted changes.
+    #[arg(long)]
+    pub force: bool,
+}
+
+impl WorktreeCli {
+    pub fn run(self) -> Result<()> {
+        let repo_path = match self.cwd {
+            Some(cwd) => cwd,
+            None => std::env::current_dir().context("failed to resolve current directory")?,
+        };
+
+        match self.subcommand {
+            WorktreeSubcommand::List(args) => run_list(repo_path, args),
+            WorktreeSubcommand::Prune(args) => run_prune(repo_path, args),
+        }
+    }
+}
+
+fn run_list(repo_path: PathBuf, list_args: ListArgs) -> Result<()> {
+    let entries = list_worktrees(&repo_path)?;
+
+    if list_args.json {
+        let json_entries: Vec<_> = entries
+            .iter()
+            .map(|entry| {
+                serde_json::json!({
+                    "branch": entry.branch,
+                    "path": entry.path,
+                    "head": entry.head,
+                    "prunable": entry.prunable,
+                    "locked": entry.locked,
+                    "in_use": entry.in_use(),
+                })
+            })
+            .collect();
+        let output = serde_json::to_string_pretty(&json_entries)?;
+        println!("{output}");
+        return Ok(());
+    }
+
+    if entries.is_empty() {
+        println!("No Codex worktrees. Start one with `codex --worktree`.");
+        return Ok(());
+    }
+
+    let branch_w = entries
+        .iter()
+        .map(|entry| entry.branch.len())
+        .max()
+        .unwrap_or_default()
+        .max("Branch".len());
+    println!("{:<branch_w$}  {:<7}  Path", "Branch", "Head");
+    for entry in &entries {
+        let head: String = entry.head.chars().take(7).collect();
+        let missing = if entry.prunable { " (missing)" } else { "" };
+        let in_use = if entry.in_use() { " (in use)" } else { "" };
+        println!(
+            "{:<branch_w$}  {:<7}  {}{missing}{in_use}",
+            entry.branch,
+            head,
+            entry.path.display(),
+        );
+    }
+
+    Ok(())
+}
+
+fn run_prune(repo_path: PathBuf, prune_args: PruneArgs) -> Result<()> {
+    let report = prune_worktrees(&repo_path, prune_args.force)?;
+
+    for entry in &report.removed {
+        println!("Removed {} ({})", entry.path.display(), entry.branch);
+    }
+    for entry in &report.skipped_in_use {
+        println!(
+            "Skipped {} ({}): locked by a running session or by `git worktree lock`.",
+            entry.path.display(),
+            entry.branch
+        );
+    }
+    for entry in &report.skipped_dirty {
+        println!(
+            "Skipped {} ({}): uncommitted changes; use --force to remove it.",
+            entry.path.display(),
+            entry.branch
+        );
+    }
+    for branch in &report.kept_branches {
+        println!("Kept branch {branch}: it has commits that are not merged.");
+    }
+    if report.removed.is_empty()
+        && report.skipped_dirty.is_empty()
+        && report.skipped_in_use.is_empty()
+    {
+        println!("No Codex worktrees to prune.");
+    }
+
+    Ok(())
+}
+
+/// Ask the user what to do with the worktree an interactive session ran in.
+/// Without a terminal to ask on, the branch is kept so no work is lost.
+pub fn finish_interactive_worktree(worktree: &SessionWorktree) -> Result<()> {
+    if !worktree_has_changes(worktree)? {
+        finish_session_worktree(worktree, WorktreeDisposition::Discard)?;
+        return Ok(());
+    }
+
+    let disposition = if std::io::stdin().is_terminal() {
+        prompt_for_disposition(worktree)?
+    } else {
+        WorktreeDisposition::Keep
+    };
+
+    if let Err(err) = finish_session_worktree(worktree, disposition) {
+        return Err(err).with_context(|| {
+            format!(
+                "failed to finish worktree; the session's work remains on branch {}",
+                worktree.branch()
+            )
+        });
+    }
+
+    match disposition {
+        WorktreeDisposition::Merge => println!(
+            "Merged {} into {}.",
+            worktree.branch(),
+            worktree.repo_root().display()
+        ),
+        WorktreeDisposition::Keep => println!(
+            "Changes were committed to branch {}. Push it to open a pull request.",
+            worktree.branch()
+        ),
+        WorktreeDisposition::Discard => println!("Discarded the session's changes."),
+    }
+    Ok(())
+}
+
+fn prompt_for_disposition(worktree: &SessionWorktree) -> Result<WorktreeDisposition> {
+    println!(
+        "This session ran in worktree {} on branch {}.",
+        worktree.path().display(),
+        worktree.branch()
+    );
+    println!(
+        "  [m] merge the changes into {}",
+        worktree.repo_root().display()
+    );
+    println!("  [k] keep the branch (e.g. to open a pull request)");
+    println!("  [d] discard the changes");
+    loop {
+        print!("Choose m/k/d [k]: ");
+        std::io::stdout().flush()?;
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer)? == 0 {
+            return Ok(WorktreeDisposition::Keep);
+        }
+        if let Some(disposition) = parse_disposition(&answer) {
+            return Ok(disposition);
+        }
+    }
+}
+
+fn parse_disposition(answer: &str) -> Option<WorktreeDisposition> {
+    match answer.trim().to_ascii_lowercase().as_str() {
+        "m" | "merge" => Some(WorktreeDisposition::Merge),
+        "" | "k" | "keep" => Some(WorktreeDisposition::Keep),
+        "d" | "discard" => Some(WorktreeDisposition::Discard),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn parse_disposition_accepts_letters_and_words() {
+        assert_eq!(parse_disposition("m\n"), Some(WorktreeDisposition::Merge));
+        assert_eq!(parse_disposition(" Keep "), Some(WorktreeDisposition::Keep));
+        assert_eq!(parse_disposition("\n"), Some(WorktreeDisposition::Keep));
+        assert_eq!(
+            parse_disposition("discard"),
+            Some(WorktreeDisposition::Discard)
+        );
+        assert_eq!(parse_disposition("x"), None);
+    }
+}
@@ -0,0 +1,139 @@
+use std::path::Path;
+
+use anyhow::Result;
+use pretty_assertions::assert_eq;
+use tempfile::TempDir;
+use toml::Value as TomlValue;
+
+fn codex_command(codex_home: &Path) -> Result<assert_cmd::Command> {
+    let mut cmd = assert_cmd::Command::cargo_bin("codex")?;
+    cmd.env("CODEX_HOME", codex_home);
+    Ok(cmd)
+}
+
+fn show(codex_home: &Path, args: &[&str]) -> Result<TomlValue> {
+    let mut cmd = codex_command(codex_home)?;
+    let output = cmd.args(["config", "show"]).args(args).output()?;
+    assert!(output.status.success(), "config show failed: {output:?}");
+    Ok(toml::from_str(&String::from_utf8(output.stdout)?)?)
+}
+
+#[test]
+fn show_resolves_profile_inheritance() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+model = "o3"
+approval_policy = "on-request"
+
+[mcp_servers.docs]
+command = "docs-server"
+
+[mcp_servers.jira]
+command = "jira-server"
+
+[profiles.base]
+sandbox_mode = "workspace-write"
+exclude_mcp_servers = ["jira"]
+
+[profiles.ci]
+extends = "base"
+approval_policy = "never"
+notify = ["notify-send", "codex"]
+"#,
+    )?;
+
+    let resolved = show(codex_home.path(), &["--profile", "ci"])?;
+    assert_eq!(resolved["profile"].as_str(), Some("ci"));
+    assert_eq!(resolved["model"].as_str(), Some("o3"));
+    assert_eq!(resolved["approval_policy"].as_str(), Some("never"));
+    assert_eq!(
+        resolved["sandbox"]["mode"].as_str(),
+        Some("workspace-write")
+    );
+    assert_eq!(
+        resolved["notify"],
+        TomlValue::Array(vec![
+            TomlValue::String("notify-send".to_string()),
+            TomlValue::String("codex".to_string()),
+        ])
+    );
+    let servers: Vec<&String> = resolved["mcp_servers"]
+        .as_table()
+        .expect("mcp_servers table")
+        .keys()
+        .collect();
+    assert_eq!(servers, vec!["docs"]);
+
+    let resolved = show(codex_home.path(), &[])?;
+    assert_eq!(resolved.get("profile"), None);
+    assert_eq!(resolved["approval_policy"].as_str(), Some("on-request"));
+    assert_eq!(resolved["sandbox"]["mode"].as_str(), Some("read-only"));
+
+    Ok(())
+}
+
+#[test]
+fn show_fails_for_unknown_profile() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    let mut cmd = codex_command(codex_home.path())?;
+    cmd.args(["config", "show", "--profile", "missing"])
+        .assert()
+        .failure()
+        .stderr(predicates::str::contains(
+            "config profile `missing` not found",
+        ));
+
+    Ok(())
+}
+
+#[test]
+fn show_redacts_secrets() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    std::fs::write(
+        codex_home.path().join("config.toml"),
+        r#"
+[shell_environment_policy.set]
+DEPLOY_KEY = "set-secret"
+
+[mcp_servers.docs]
+command = "docs-server"
+env = { API_KEY = "env-secret" }
+
+[mcp_servers.remote]
+url = "https://mcp.example.com/mcp"
+bearer_token = "token-secret"
+"#,
+    )?;
+
+    let output = codex_command(codex_home.path())?
+        .args(["config", "show"])
+        .output()?;
+    assert!(output.status.success(), "config show failed: {output:?}");
+    let stdout = String::from_utf8(output.stdout)?;
+    for secret in ["set-secret", "env-secret", "token-secret"] {
+        assert!(!stdout.contains(secret), "{secret} leaked:\n{stdout}");
+    }
+
+    let resolved: TomlValue = toml::from_str(&stdout)?;
+    assert_eq!(
+        resolved["shell_environment_policy"]["set"]["DEPLOY_KEY"].as_str(),
+        Some("<redacted>")
+    );
+    assert_eq!(
+        resolved["mcp_servers"]["docs"]["env"]["API_KEY"].as_str(),
+        Some("<redacted>")
+    );
+    assert_eq!(
+        resolved["mcp_servers"]["remote"]["bearer_token"].as_str(),
+        Some("<redacted>")
+    );
+    assert_eq!(
+        resolved["mcp_servers"]["remote"]["url"].as_str(),
+        Some("https://mcp.example.com/mcp")
+    );
+
+    Ok(())
+}
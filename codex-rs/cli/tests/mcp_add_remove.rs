@@ -3,6 +3,9 @@ use std::path::Path;
 use anyhow::Result;
 use codex_core::config::load_global_mcp_servers;
 use codex_core::config_types::McpServerTransportConfig;
+use codex_core::credential_store::CredentialStore;
+use codex_core::credential_store::mcp_server_credential;
+use codex_core::mcp_oauth::McpOAuthTokens;
 use predicates::str::contains;
 use pretty_assertions::assert_eq;
 use tempfile::TempDir;
@@ -93,3 +96,147 @@ fn add_with_env_preserves_key_order_and_values() -> Result<()> {
 
     Ok(())
 }
+
+#[test]
+fn set_token_stores_token_until_server_is_removed() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let credential_store = CredentialStore::default();
+    let credential = mcp_server_credential("docs");
+
+    codex_command(codex_home.path())?
+        .args(["mcp", "add", "docs", "--", "echo", "hello"])
+        .assert()
+        .success();
+
+    codex_command(codex_home.path())?
+        .args(["mcp", "set-token", "docs"])
+        .write_stdin("secret-token\n")
+        .assert()
+        .success()
+        .stdout(contains("Stored the bearer token for MCP server 'docs'."));
+    assert_eq!(
+        credential_store.load(codex_home.path(), &credential)?,
+        Some("secret-token".to_string())
+    );
+
+    codex_command(codex_home.path())?
+        .args(["mcp", "remove", "docs"])
+        .assert()
+        .success();
+    assert_eq!(credential_store.load(codex_home.path(), &credential)?, None);
+
+    Ok(())
+}
+
+#[test]
+fn logout_and_remove_erase_oauth_tokens() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let credential_store = CredentialStore::default();
+    let tokens = McpOAuthTokens {
+        client_id: "client-1".to_string(),
+        client_secret: None,
+        token_endpoint: "https://auth.example.com/token".to_string(),
+        resource: "https://mcp.example.com/mcp".to_string(),
+        access_token: "access".to_string(),
+        refresh_token: Some("refresh".to_string()),
+        expires_at: None,
+        scope: None,
+    };
+
+    codex_command(codex_home.path())?
+        .args(["mcp", "add", "docs", "--", "echo", "hello"])
+        .assert()
+        .success();
+    tokens.save(codex_home.path(), &credential_store, "docs")?;
+
+    codex_command(codex_home.path())?
+        .args(["mcp", "logout", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("Logged out of MCP server 'docs'."));
+    assert_eq!(
+        McpOAuthTokens::load(codex_home.path(), &credential_store, "docs")?,
+        None
+    );
+
+    codex_command(codex_home.path())?
+        .args(["mcp", "logout", "docs"])
+        .assert()
+        .success()
+        .stdout(contains("Not logged in to MCP server 'docs'."));
+
+    tokens.save(codex_home.path(), &credential_store, "docs")?;
+    codex_command(codex_home.path())?
+        .args(["mcp", "remove", "docs"])
+        .assert()
+        .success();
+    assert_eq!(
+        McpOAuthTokens::load(codex_home.path(), &credential_store, "docs")?,
+        None
+    );
+
+    Ok(())
+}
+
+#[test]
+fn remove_erases_token_from_overridden_credential_store() -> Result<()> {
+    let codex_home = TempDir::new()?;
+    let store_override =
+        r#"credential_store={backend="encrypted-file", passphrase_env="CODEX_TEST_PASSPHRASE"}"#;
+
+    codex_command(codex_home.path())?
+        .args(["mcp", "add", "docs", "--", "echo", "hello"])
+        .assert()
+        .success();
+
+    codex_command(codex_home.path())?
+        .env("CODEX_TEST_PASSPHRASE", "hunter2")
+        .args(["-c", store_override, "mcp", "set-token", "docs"])
+        .write_stdin("secret-token\n")
+        .assert()
+        .success();
+
+    codex_command(codex_home.path())?
+        .env("CODEX_TEST_PASSPHRASE", "hunter2")
+        .args(["-c", store_override, "mcp", "remove", "docs"])
+        .assert()
+        .success();
+
+    codex_command(codex_home.path())?
+        .args(["-c", store_override, "mcp", "set-token", "docs", "--clear"])
+        .assert()
+        .success()
+        .stdout(contains("No token stored for MCP server 'docs'."));
+
+    Ok(())
+}
+
+#[test]
+fn remove_warns_when_credentials_cannot_be_erased() -> Result<()> {
+    let codex_home = TempDir::new()?;
+
+    codex_command(codex_home.path())?
+        .args(["mcp", "add", "docs", "--", "echo", "hello"])
+        .assert()
+        .success();
+
+    codex_command(codex_home.path())?
+        .args([
+            "-c",
+            r#"credential_store={backend="helper", command=["false"]}"#,
+            "mcp",
+            "remove",
+            "docs",
+        ])
+        .assert()
+        .success()
+        .stdout(contains("Removed global MCP server 'docs'."))
+        .stderr(contains(
+            "Warning: failed to erase the stored token for 'docs'",
+        ));
+
+    let servers = load_global_mcp_servers(codex_home.path())?;
+    assert!(servers.is_empty());
+
+    Ok(())
+}
@@ -188,11 +188,7 @@ pub async fn run_main(_cli: Cli, _codex_linux_sandbox_exe: Option<PathBuf>) -> a
         append_error_log(format!("startup: base_url={base_url} path_style={style}"));
 
         // Require ChatGPT login (SWIC). Exit with a clear message if missing.
-        let _token = match codex_core::config::find_codex_home()
-            .ok()
-            .map(codex_login::AuthManager::new)
-            .and_then(|am| am.auth())
-        {
+        let _token = match util::load_auth_manager().and_then(|am| am.auth()) {
             Some(auth) => {
                 // Log account context for debugging workspace selection.
                 if let Some(acc) = auth.get_account_id() {
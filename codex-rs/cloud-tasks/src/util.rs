@@ -20,6 +20,16 @@ pub fn append_error_log(message: impl AsRef<str>) {
     }
 }
 
+/// An [`codex_login::AuthManager`] for `CODEX_HOME`, using the credential store
+/// configured there.
+pub fn load_auth_manager() -> Option<codex_login::AuthManager> {
+    let home = codex_core::config::find_codex_home().ok()?;
+    let credential_store = codex_core::config::load_global_credential_store(&home)
+        .map_err(|e| append_error_log(format!("auth: invalid credential_store: {e}")))
+        .ok()?;
+    Some(codex_login::AuthManager::new(home, credential_store))
+}
+
 /// Normalize the configured base URL to a canonical form used by the backend client.
 /// - trims trailing '/'
 /// - appends '/backend-api' for ChatGPT hosts when missing
@@ -69,24 +79,22 @@ pub async fn build_chatgpt_headers() -> HeaderMap {
         USER_AGENT,
         HeaderValue::from_str(&ua).unwrap_or(HeaderValue::from_static("codex-cli")),
     );
-    if let Ok(home) = codex_core::config::find_codex_home() {
-        let am = codex_login::AuthManager::new(home);
-        if let Some(auth) = am.auth()
-            && let Ok(tok) = auth.get_token().await
-            && !tok.is_empty()
+    if let Some(am) = load_auth_manager()
+        && let Some(auth) = am.auth()
+        && let Ok(tok) = auth.get_token().await
+        && !tok.is_empty()
+    {
+        let v = format!("Bearer {tok}");
+        if let Ok(hv) = HeaderValue::from_str(&v) {
+            headers.insert(AUTHORIZATION, hv);
+        }
+        if let Some(acc) = auth
+            .get_account_id()
+            .or_else(|| extract_chatgpt_account_id(&tok))
+            && let Ok(name) = HeaderName::from_bytes(b"ChatGPT-Account-Id")
+            && let Ok(hv) = HeaderValue::from_str(&acc)
         {
-            let v = format!("Bearer {tok}");
-            if let Ok(hv) = HeaderValue::from_str(&v) {
-                headers.insert(AUTHORIZATION, hv);
-            }
-            if let Some(acc) = auth
-                .get_account_id()
-                .or_else(|| extract_chatgpt_account_id(&tok))
-                && let Ok(name) = HeaderName::from_bytes(b"ChatGPT-Account-Id")
-                && let Ok(hv) = HeaderValue::from_str(&acc)
-            {
-                headers.insert(name, hv);
-            }
+            headers.insert(name, hv);
         }
     }
     headers
@@ -1,65 +1,88 @@
+use std::collections::HashMap;
+
 use codex_app_server_protocol::AuthMode;
+use codex_core::config_types::ModelDefinition;
 use codex_core::protocol_config_types::ReasoningEffort;
 
 /// A simple preset pairing a model slug with a reasoning effort.
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct ModelPreset {
     /// Stable identifier for the preset.
-    pub id: &'static str,
+    pub id: String,
     /// Display label shown in UIs.
-    pub label: &'static str,
+    pub label: String,
     /// Short human description shown next to the label in UIs.
-    pub description: &'static str,
+    pub description: String,
     /// Model slug (e.g., "gpt-5").
-    pub model: &'static str,
+    pub model: String,
     /// Reasoning effort to apply for this preset.
     pub effort: Option<ReasoningEffort>,
 }
 
-const PRESETS: &[ModelPreset] = &[
-    ModelPreset {
+struct BuiltinPreset {
+    id: &'static str,
+    label: &'static str,
+    description: &'static str,
+    model: &'static str,
+    effort: Option<ReasoningEffort>,
+}
+
+impl From<&BuiltinPreset> for ModelPreset {
+    fn from(preset: &BuiltinPreset) -> Self {
+        Self {
+            id: preset.id.to_string(),
+            label: preset.label.to_string(),
+            description: preset.description.to_string(),
+            model: preset.model.to_string(),
+            effort: preset.effort,
+        }
+    }
+}
+
+const PRESETS: &[BuiltinPreset] = &[
+    BuiltinPreset {
         id: "gpt-5-codex-low",
         label: "gpt-5-codex low",
         description: "",
         model: "gpt-5-codex",
         effort: Some(ReasoningEffort::Low),
     },
-    ModelPreset {
+    BuiltinPreset {
         id: "gpt-5-codex-medium",
         label: "gpt-5-codex medium",
         description: "",
         model: "gpt-5-codex",
         effort: Some(ReasoningEffort::Medium),
     },
-    ModelPreset {
+    BuiltinPreset {
         id: "gpt-5-codex-high",
         label: "gpt-5-codex high",
         description: "",
         model: "gpt-5-codex",
         effort: Some(ReasoningEffort::High),
     },
-    ModelPreset {
+    BuiltinPreset {
         id: "gpt-5-minimal",
         label: "gpt-5 minimal",
         description: "— fastest responses with limited reasoning; ideal for coding, instructions, or lightweight tasks",
         model: "gpt-5",
         effort: Some(ReasoningEffort::Minimal),
     },
-    ModelPreset {
+    BuiltinPreset {
         id: "gpt-5-low",
         label: "gpt-5 low",
         description: "— balances speed with some reasoning; useful for straightforward queries and short explanations",
         model: "gpt-5",
         effort: Some(ReasoningEffort::Low),
     },
-    ModelPreset {
+    BuiltinPreset {
         id: "gpt-5-medium",
         label: "gpt-5 medium",
         description: "— default setting; provides a solid balance of reasoning depth and latency for general-purpose tasks",
         model: "gpt-5",
         effort: Some(ReasoningEffort::Medium),
     },
-    ModelPreset {
+    BuiltinPreset {
         id: "gpt-5-high",
         label: "gpt-5 high",
         description: "— maximizes reasoning depth for complex or ambiguous problems",
@@ -69,5 +92,80 @@ const PRESETS: &[ModelPreset] = &[
 ];
 
 pub fn builtin_model_presets(_auth_mode: Option<AuthMode>) -> Vec<ModelPreset> {
-    PRESETS.to_vec()
+    PRESETS.iter().map(ModelPreset::from).collect()
+}
+
+/// The built-in presets followed by one preset, sorted by slug, for each
+/// `[models.<slug>]` entry in `models` that no built-in preset covers.
+pub fn model_presets(
+    auth_mode: Option<AuthMode>,
+    models: &HashMap<String, ModelDefinition>,
+) -> Vec<ModelPreset> {
+    let mut presets = builtin_model_presets(auth_mode);
+    let mut configured: Vec<(&String, &ModelDefinition)> = models
+        .iter()
+        .filter(|(slug, _)| !PRESETS.iter().any(|preset| preset.model == slug.as_str()))
+        .collect();
+    configured.sort_by(|(a, _), (b, _)| a.cmp(b));
+    presets.extend(configured.into_iter().map(|(slug, definition)| {
+        ModelPreset {
+            id: slug.clone(),
+            label: definition
+                .display_name
+                .clone()
+                .unwrap_or_else(|| slug.clone()),
+            description: definition
+                .description
+                .as_ref()
+                .map(|description| format!("— {description}"))
+                .unwrap_or_default(),
+            model: slug.clone(),
+            effort: None,
+        }
+    }));
+    presets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn configured_models_follow_builtin_presets() {
+        let models = HashMap::from([
+            (
+                "qwen3-coder".to_string(),
+                ModelDefinition {
+                    display_name: Some("Qwen3 Coder".to_string()),
+                    description: Some("self-hosted".to_string()),
+                    ..Default::default()
+                },
+            ),
+            ("gpt-5".to_string(), ModelDefinition::default()),
+            ("llama-3.3".to_string(), ModelDefinition::default()),
+        ]);
+
+        let presets = model_presets(None, &models);
+        let builtin_count = PRESETS.len();
+        assert_eq!(presets.len(), builtin_count + 2);
+        assert_eq!(
+            presets[builtin_count..].to_vec(),
+            vec![
+                ModelPreset {
+                    id: "llama-3.3".to_string(),
+                    label: "llama-3.3".to_string(),
+                    description: String::new(),
+                    model: "llama-3.3".to_string(),
+                    effort: None,
+                },
+                ModelPreset {
+                    id: "qwen3-coder".to_string(),
+                    label: "Qwen3 Coder".to_string(),
+                    description: "— self-hosted".to_string(),
+                    model: "qwen3-coder".to_string(),
+                    effort: None,
+                },
+            ]
+        );
+    }
 }
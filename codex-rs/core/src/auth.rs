@@ -4,19 +4,20 @@ use serde::Deserialize;
 use serde::Serialize;
 use std::env;
 use std::fs::File;
-use std::fs::OpenOptions;
 use std::io::Read;
-use std::io::Write;
-#[cfg(unix)]
-use std::os::unix::fs::OpenOptionsExt;
 use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::Mutex;
 use std::time::Duration;
+use tracing::info;
+use tracing::warn;
 
 use codex_app_server_protocol::AuthMode;
 
+use crate::credential_store::AUTH_CREDENTIAL;
+use crate::credential_store::CredentialStore;
+use crate::credential_store::write_private_file;
 use crate::token_data::PlanType;
 use crate::token_data::TokenData;
 use crate::token_data::parse_id_token;
@@ -27,7 +28,8 @@ pub struct CodexAuth {
 
     pub(crate) api_key: Option<String>,
     pub(crate) auth_dot_json: Arc<Mutex<Option<AuthDotJson>>>,
-    pub(crate) auth_file: PathBuf,
+    pub(crate) codex_home: PathBuf,
+    pub(crate) credential_store: CredentialStore,
     pub(crate) client: reqwest::Client,
 }
 
@@ -49,7 +51,8 @@ impl CodexAuth {
             .map_err(std::io::Error::other)?;
 
         let updated = update_tokens(
-            &self.auth_file,
+            &self.codex_home,
+            &self.credential_store,
             refresh_response.id_token,
             refresh_response.access_token,
             refresh_response.refresh_token,
@@ -71,9 +74,13 @@ impl CodexAuth {
         Ok(access)
     }
 
-    /// Loads the available auth information from the auth.json.
-    pub fn from_codex_home(codex_home: &Path) -> std::io::Result<Option<CodexAuth>> {
-        load_auth(codex_home)
+    /// Loads the available auth information from the auth.json kept in
+    /// `credential_store`.
+    pub fn from_codex_home(
+        codex_home: &Path,
+        credential_store: &CredentialStore,
+    ) -> std::io::Result<Option<CodexAuth>> {
+        load_auth(codex_home, credential_store)
     }
 
     pub async fn get_token_data(&self) -> Result<TokenData, std::io::Error> {
@@ -96,7 +103,8 @@ impl CodexAuth {
                     .map_err(std::io::Error::other)?;
 
                     let updated_auth_dot_json = update_tokens(
-                        &self.auth_file,
+                        &self.codex_home,
+                        &self.credential_store,
                         refresh_response.id_token,
                         refresh_response.access_token,
                         refresh_response.refresh_token,
@@ -166,7 +174,8 @@ impl CodexAuth {
         Self {
             api_key: None,
             mode: AuthMode::ChatGPT,
-            auth_file: PathBuf::new(),
+            codex_home: PathBuf::new(),
+            credential_store: CredentialStore::default(),
             auth_dot_json,
             client: crate::default_client::create_client(),
         }
@@ -176,7 +185,8 @@ impl CodexAuth {
         Self {
             api_key: Some(api_key.to_owned()),
             mode: AuthMode::ApiKey,
-            auth_file: PathBuf::new(),
+            codex_home: PathBuf::new(),
+            credential_store: CredentialStore::default(),
             auth_dot_json: Arc::new(Mutex::new(None)),
             client,
         }
@@ -200,35 +210,88 @@ pub fn get_auth_file(codex_home: &Path) -> PathBuf {
     codex_home.join("auth.json")
 }
 
-/// Delete the auth.json file inside `codex_home` if it exists. Returns `Ok(true)`
-/// if a file was removed, `Ok(false)` if no auth file was present.
-pub fn logout(codex_home: &Path) -> std::io::Result<bool> {
-    let auth_file = get_auth_file(codex_home);
-    match std::fs::remove_file(&auth_file) {
-        Ok(_) => Ok(true),
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(false),
-        Err(err) => Err(err),
-    }
+/// Delete the stored auth.json if it exists. Returns `Ok(true)` if it was
+/// removed, `Ok(false)` if there was none.
+pub fn logout(codex_home: &Path, credential_store: &CredentialStore) -> std::io::Result<bool> {
+    credential_store.erase(codex_home, AUTH_CREDENTIAL)
 }
 
-/// Writes an `auth.json` that contains only the API key.
-pub fn login_with_api_key(codex_home: &Path, api_key: &str) -> std::io::Result<()> {
+/// Stores an auth.json that contains only the API key.
+pub fn login_with_api_key(
+    codex_home: &Path,
+    api_key: &str,
+    credential_store: &CredentialStore,
+) -> std::io::Result<()> {
     let auth_dot_json = AuthDotJson {
         openai_api_key: Some(api_key.to_string()),
         tokens: None,
         last_refresh: None,
     };
-    write_auth_json(&get_auth_file(codex_home), &auth_dot_json)
+    save_auth_dot_json(codex_home, credential_store, &auth_dot_json)
 }
 
-fn load_auth(codex_home: &Path) -> std::io::Result<Option<CodexAuth>> {
-    let auth_file = get_auth_file(codex_home);
-    let client = crate::default_client::create_client();
-    let auth_dot_json = match try_read_auth_json(&auth_file) {
-        Ok(auth) => auth,
+/// Read the auth.json kept in `credential_store`. Returns `Ok(None)` when the
+/// user is not logged in.
+pub fn load_auth_dot_json(
+    codex_home: &Path,
+    credential_store: &CredentialStore,
+) -> std::io::Result<Option<AuthDotJson>> {
+    match credential_store.load(codex_home, AUTH_CREDENTIAL)? {
+        Some(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+        None if *credential_store == CredentialStore::File => Ok(None),
+        None => migrate_plaintext_auth_dot_json(codex_home, credential_store),
+    }
+}
+
+/// Switching `credential_store` away from the plaintext file would otherwise
+/// log the user out, so move an existing `auth.json` into the new store and
+/// delete it. If the new store cannot take it, the file is left in place and
+/// still used for this session.
+fn migrate_plaintext_auth_dot_json(
+    codex_home: &Path,
+    credential_store: &CredentialStore,
+) -> std::io::Result<Option<AuthDotJson>> {
+    let plaintext = CredentialStore::File;
+    let Some(contents) = plaintext.load(codex_home, AUTH_CREDENTIAL)? else {
+        return Ok(None);
+    };
+    let auth_dot_json: AuthDotJson = serde_json::from_str(&contents)?;
+    match save_auth_dot_json(codex_home, credential_store, &auth_dot_json) {
+        Ok(()) => {
+            plaintext.erase(codex_home, AUTH_CREDENTIAL)?;
+            info!("moved auth.json into the configured credential_store");
+        }
         Err(e) => {
-            return Err(e);
+            warn!(
+                "auth.json is still stored in plaintext: failed to move it into the configured credential_store: {e}"
+            );
         }
+    }
+    Ok(Some(auth_dot_json))
+}
+
+/// Replace the auth.json kept in `credential_store`.
+pub fn save_auth_dot_json(
+    codex_home: &Path,
+    credential_store: &CredentialStore,
+    auth_dot_json: &AuthDotJson,
+) -> std::io::Result<()> {
+    // The plaintext file stays pretty-printed as it always was; other stores
+    // get a single line, which credential helpers require.
+    let contents = match credential_store {
+        CredentialStore::File => serde_json::to_string_pretty(auth_dot_json)?,
+        _ => serde_json::to_string(auth_dot_json)?,
+    };
+    credential_store.save(codex_home, AUTH_CREDENTIAL, &contents)
+}
+
+fn load_auth(
+    codex_home: &Path,
+    credential_store: &CredentialStore,
+) -> std::io::Result<Option<CodexAuth>> {
+    let client = crate::default_client::create_client();
+    let Some(auth_dot_json) = load_auth_dot_json(codex_home, credential_store)? else {
+        return Ok(None);
     };
 
     let AuthDotJson {
@@ -245,7 +308,8 @@ fn load_auth(codex_home: &Path) -> std::io::Result<Option<CodexAuth>> {
     Ok(Some(CodexAuth {
         api_key: None,
         mode: AuthMode::ChatGPT,
-        auth_file,
+        codex_home: codex_home.to_path_buf(),
+        credential_store: credential_store.clone(),
         auth_dot_json: Arc::new(Mutex::new(Some(AuthDotJson {
             openai_api_key: None,
             tokens,
@@ -267,29 +331,19 @@ pub fn try_read_auth_json(auth_file: &Path) -> std::io::Result<AuthDotJson> {
 }
 
 pub fn write_auth_json(auth_file: &Path, auth_dot_json: &AuthDotJson) -> std::io::Result<()> {
-    if let Some(parent) = auth_file.parent() {
-        std::fs::create_dir_all(parent)?;
-    }
     let json_data = serde_json::to_string_pretty(auth_dot_json)?;
-    let mut options = OpenOptions::new();
-    options.truncate(true).write(true).create(true);
-    #[cfg(unix)]
-    {
-        options.mode(0o600);
-    }
-    let mut file = options.open(auth_file)?;
-    file.write_all(json_data.as_bytes())?;
-    file.flush()?;
-    Ok(())
+    write_private_file(auth_file, json_data.as_bytes())
 }
 
 async fn update_tokens(
-    auth_file: &Path,
+    codex_home: &Path,
+    credential_store: &CredentialStore,
     id_token: String,
     access_token: Option<String>,
     refresh_token: Option<String>,
 ) -> std::io::Result<AuthDotJson> {
-    let mut auth_dot_json = try_read_auth_json(auth_file)?;
+    let mut auth_dot_json = load_auth_dot_json(codex_home, credential_store)?
+        .ok_or_else(|| std::io::Error::other("Token data is not available."))?;
 
     let tokens = auth_dot_json.tokens.get_or_insert_with(TokenData::default);
     tokens.id_token = parse_id_token(&id_token).map_err(std::io::Error::other)?;
@@ -300,7 +354,7 @@ async fn update_tokens(
         tokens.refresh_token = refresh_token;
     }
     auth_dot_json.last_refresh = Some(Utc::now());
-    write_auth_json(auth_file, &auth_dot_json)?;
+    save_auth_dot_json(codex_home, credential_store, &auth_dot_json)?;
     Ok(auth_dot_json)
 }
 
@@ -430,13 +484,35 @@ mod tests {
         )
         .unwrap();
 
-        super::login_with_api_key(dir.path(), "sk-new").expect("login_with_api_key should succeed");
+        super::login_with_api_key(dir.path(), "sk-new", &CredentialStore::File)
+            .expect("login_with_api_key should succeed");
 
         let auth = super::try_read_auth_json(&auth_path).expect("auth.json should parse");
         assert_eq!(auth.openai_api_key.as_deref(), Some("sk-new"));
         assert!(auth.tokens.is_none(), "tokens should be cleared");
     }
 
+    #[test]
+    fn switching_credential_store_moves_plaintext_auth_json() {
+        let codex_home = tempdir().unwrap();
+        super::login_with_api_key(codex_home.path(), "sk-plain", &CredentialStore::File)
+            .expect("login_with_api_key should succeed");
+        std::fs::write(codex_home.path().join("credentials.key"), "hunter2").unwrap();
+        let encrypted = CredentialStore::EncryptedFile {
+            key_file: Some(PathBuf::from("credentials.key")),
+            passphrase_env: None,
+        };
+
+        for _ in 0..2 {
+            let auth = load_auth_dot_json(codex_home.path(), &encrypted)
+                .unwrap()
+                .expect("still logged in");
+            assert_eq!(auth.openai_api_key.as_deref(), Some("sk-plain"));
+        }
+        assert!(!codex_home.path().join("auth.json").exists());
+        assert!(codex_home.path().join("auth.json.enc").exists());
+    }
+
     #[tokio::test]
     async fn pro_account_with_no_api_key_uses_chatgpt_auth() {
         let codex_home = tempdir().unwrap();
@@ -453,9 +529,10 @@ mod tests {
             api_key,
             mode,
             auth_dot_json,
-            auth_file: _,
             ..
-        } = super::load_auth(codex_home.path()).unwrap().unwrap();
+        } = super::load_auth(codex_home.path(), &CredentialStore::File)
+            .unwrap()
+            .unwrap();
         assert_eq!(None, api_key);
         assert_eq!(AuthMode::ChatGPT, mode);
 
@@ -494,7 +571,9 @@ mod tests {
         )
         .unwrap();
 
-        let auth = super::load_auth(dir.path()).unwrap().unwrap();
+        let auth = super::load_auth(dir.path(), &CredentialStore::File)
+            .unwrap()
+            .unwrap();
         assert_eq!(auth.mode, AuthMode::ApiKey);
         assert_eq!(auth.api_key, Some("sk-test-key".to_string()));
 
@@ -511,7 +590,7 @@ mod tests {
         };
         write_auth_json(&get_auth_file(dir.path()), &auth_dot_json)?;
         assert!(dir.path().join("auth.json").exists());
-        let removed = logout(dir.path())?;
+        let removed = logout(dir.path(), &CredentialStore::File)?;
         assert!(removed);
         assert!(!dir.path().join("auth.json").exists());
         Ok(())
@@ -576,6 +655,7 @@ mod tests {
 #[derive(Debug)]
 pub struct AuthManager {
     codex_home: PathBuf,
+    credential_store: CredentialStore,
     inner: RwLock<CachedAuth>,
 }
 
@@ -584,10 +664,13 @@ impl AuthManager {
     /// preferred auth method. Errors loading auth are swallowed; `auth()` will
     /// simply return `None` in that case so callers can treat it as an
     /// unauthenticated state.
-    pub fn new(codex_home: PathBuf) -> Self {
-        let auth = CodexAuth::from_codex_home(&codex_home).ok().flatten();
+    pub fn new(codex_home: PathBuf, credential_store: CredentialStore) -> Self {
+        let auth = CodexAuth::from_codex_home(&codex_home, &credential_store)
+            .ok()
+            .flatten();
         Self {
             codex_home,
+            credential_store,
             inner: RwLock::new(CachedAuth { auth }),
         }
     }
@@ -597,6 +680,7 @@ impl AuthManager {
         let cached = CachedAuth { auth: Some(auth) };
         Arc::new(Self {
             codex_home: PathBuf::new(),
+            credential_store: CredentialStore::default(),
             inner: RwLock::new(cached),
         })
     }
@@ -609,7 +693,9 @@ impl AuthManager {
     /// Force a reload of the auth information from auth.json. Returns
     /// whether the auth value changed.
     pub fn reload(&self) -> bool {
-        let new_auth = CodexAuth::from_codex_home(&self.codex_home).ok().flatten();
+        let new_auth = CodexAuth::from_codex_home(&self.codex_home, &self.credential_store)
+            .ok()
+            .flatten();
         if let Ok(mut guard) = self.inner.write() {
             let changed = !AuthManager::auths_equal(&guard.auth, &new_auth);
             guard.auth = new_auth;
@@ -628,8 +714,8 @@ impl AuthManager {
     }
 
     /// Convenience constructor returning an `Arc` wrapper.
-    pub fn shared(codex_home: PathBuf) -> Arc<Self> {
-        Arc::new(Self::new(codex_home))
+    pub fn shared(codex_home: PathBuf, credential_store: CredentialStore) -> Arc<Self> {
+        Arc::new(Self::new(codex_home, credential_store))
     }
 
     /// Attempt to refresh the current auth token (if any). On success, reload
@@ -649,12 +735,12 @@ impl AuthManager {
         }
     }
 
-    /// Log out by deleting the on‑disk auth.json (if present). Returns Ok(true)
-    /// if a file was removed, Ok(false) if no auth file existed. On success,
+    /// Log out by deleting the stored auth.json (if present). Returns Ok(true)
+    /// if it was removed, Ok(false) if none existed. On success,
     /// reloads the in‑memory auth cache so callers immediately observe the
     /// unauthenticated state.
     pub fn logout(&self) -> std::io::Result<bool> {
-        let removed = super::auth::logout(&self.codex_home)?;
+        let removed = super::auth::logout(&self.codex_home, &self.credential_store)?;
         // Always reload to clear any cached auth (even if file absent).
         self.reload();
         Ok(removed)
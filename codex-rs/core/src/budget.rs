@@ -0,0 +1,212 @@
+//! Session budgets: what token usage costs according to `model_prices`, and
+//! whether a session has reached one of its `[budget]` limits.
+
+use std::fmt;
+use std::time::Duration;
+
+use codex_protocol::num_format::format_with_separators;
+
+use crate::config_types::ModelPrice;
+use crate::config_types::SessionBudget;
+use crate::protocol::TokenUsage;
+use crate::protocol::TokenUsageInfo;
+
+/// Cost of `usage` in US dollars.
+pub(crate) fn usage_cost_usd(usage: &TokenUsage, price: &ModelPrice) -> f64 {
+    let cached_input_per_million = price
+        .cached_input_per_million
+        .unwrap_or(price.input_per_million);
+    let micro_dollars = usage.non_cached_input() as f64 * price.input_per_million
+        + usage.cached_input() as f64 * cached_input_per_million
+        + usage.output_tokens as f64 * price.output_per_million;
+    micro_dollars / 1_000_000.0
+}
+
+/// A `[budget]` limit the session has reached.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum BudgetExceeded {
+    Tokens { used: u64, limit: u64 },
+    Cost { spent: f64, limit: f64 },
+    Turns { limit: u64 },
+    Duration { limit: Duration },
+}
+
+impl fmt::Display for BudgetExceeded {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "Session budget exhausted: ")?;
+        match self {
+            BudgetExceeded::Tokens { used, limit } => write!(
+                f,
+                "used {} tokens of max_tokens_per_session = {}",
+                format_with_separators(*used),
+                format_with_separators(*limit)
+            ),
+            BudgetExceeded::Cost { spent, limit } => {
+                write!(f, "spent ${spent:.2} of max_cost_usd = ${limit:.2}")
+            }
+            BudgetExceeded::Turns { limit } => {
+                write!(f, "made max_turns = {limit} requests to the model")
+            }
+            BudgetExceeded::Duration { limit } => {
+                write!(f, "ran for max_duration_secs = {} seconds", limit.as_secs())
+            }
+        }
+    }
+}
+
+/// Returns the first limit in `budget` that the session has reached, given
+/// its token usage so far, how many requests it has made to the model and how
+/// long it has been running.
+pub(crate) fn check_budget(
+    budget: &SessionBudget,
+    token_info: Option<&TokenUsageInfo>,
+    turns: u64,
+    elapsed: Duration,
+) -> Option<BudgetExceeded> {
+    if let Some(limit) = budget.max_tokens_per_session {
+        let used = token_info
+            .map(|info| info.total_token_usage.blended_total())
+            .unwrap_or_default();
+        if used >= limit {
+            return Some(BudgetExceeded::Tokens { used, limit });
+        }
+    }
+    if let Some(limit) = budget.max_cost_usd {
+        let spent = token_info
+            .and_then(|info| info.total_cost_usd)
+            .unwrap_or_default();
+        if spent >= limit {
+            return Some(BudgetExceeded::Cost { spent, limit });
+        }
+    }
+    if let Some(limit) = budget.max_turns
+        && turns >= limit
+    {
+        return Some(BudgetExceeded::Turns { limit });
+    }
+    if let Some(limit) = budget.max_duration_secs.map(Duration::from_secs)
+        && elapsed >= limit
+    {
+        return Some(BudgetExceeded::Duration { limit });
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn usage(input: u64, cached: u64, output: u64) -> TokenUsage {
+        TokenUsage {
+            input_tokens: input,
+            cached_input_tokens: cached,
+            output_tokens: output,
+            reasoning_output_tokens: 0,
+            total_tokens: input + output,
+        }
+    }
+
+    fn info(usage: TokenUsage, total_cost_usd: Option<f64>) -> TokenUsageInfo {
+        TokenUsageInfo {
+            total_token_usage: usage.clone(),
+            last_token_usage: usage,
+            model_context_window: None,
+            total_cost_usd,
+        }
+    }
+
+    #[test]
+    fn cost_prices_cached_input_separately() {
+        let price = ModelPrice {
+            input_per_million: 2.0,
+            cached_input_per_million: Some(0.5),
+            output_per_million: 10.0,
+        };
+        let cost = usage_cost_usd(&usage(1_000_000, 400_000, 100_000), &price);
+        assert!((cost - (1.2 + 0.2 + 1.0)).abs() < 1e-9, "cost was {cost}");
+
+        let uncached_price = ModelPrice {
+            cached_input_per_million: None,
+            ..price
+        };
+        let cost = usage_cost_usd(&usage(1_000_000, 400_000, 0), &uncached_price);
+        assert!((cost - 2.0).abs() < 1e-9, "cost was {cost}");
+    }
+
+    #[test]
+    fn no_limits_never_exceeded() {
+        let token_info = info(usage(10_000_000, 0, 0), Some(100.0));
+        assert_eq!(
+            check_budget(
+                &SessionBudget::default(),
+                Some(&token_info),
+                1_000,
+                Duration::from_secs(86_400)
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn reports_the_limit_that_was_reached() {
+        let budget = SessionBudget {
+            max_tokens_per_session: Some(1_000),
+            max_cost_usd: Some(1.0),
+            max_turns: Some(3),
+            max_duration_secs: Some(60),
+        };
+        let within = info(usage(500, 0, 100), Some(0.5));
+        assert_eq!(
+            check_budget(&budget, Some(&within), 2, Duration::from_secs(59)),
+            None
+        );
+
+        let many_tokens = info(usage(1_500, 600, 100), Some(0.5));
+        assert_eq!(
+            check_budget(&budget, Some(&many_tokens), 0, Duration::ZERO),
+            Some(BudgetExceeded::Tokens {
+                used: 1_000,
+                limit: 1_000
+            })
+        );
+        let expensive = info(usage(10, 0, 10), Some(1.25));
+        assert_eq!(
+            check_budget(&budget, Some(&expensive), 0, Duration::ZERO),
+            Some(BudgetExceeded::Cost {
+                spent: 1.25,
+                limit: 1.0
+            })
+        );
+        assert_eq!(
+            check_budget(&budget, None, 3, Duration::ZERO),
+            Some(BudgetExceeded::Turns { limit: 3 })
+        );
+        assert_eq!(
+            check_budget(&budget, None, 0, Duration::from_secs(60)),
+            Some(BudgetExceeded::Duration {
+                limit: Duration::from_secs(60)
+            })
+        );
+    }
+
+    #[test]
+    fn message_names_the_config_key() {
+        assert_eq!(
+            BudgetExceeded::Cost {
+                spent: 5.031,
+                limit: 5.0
+            }
+            .to_string(),
+            "Session budget exhausted: spent $5.03 of max_cost_usd = $5.00"
+        );
+        assert_eq!(
+            BudgetExceeded::Tokens {
+                used: 2_000_123,
+                limit: 2_000_000
+            }
+            .to_string(),
+            "Session budget exhausted: used 2,000,123 tokens of max_tokens_per_session = 2,000,000"
+        );
+    }
+}
@@ -0,0 +1,295 @@
+//! Record-and-replay of model provider traffic (`--record-cassette` and
+//! `--replay-cassette`).
+//!
+//! A cassette is a directory. Every streaming request sent to the provider is
+//! keyed by a hash of its JSON body; recording stores the body as
+//! `<key>.request.json` and the raw SSE response as `<key>.sse`. Replaying
+//! serves `<key>.sse` instead of contacting the provider and fails with
+//! [`CodexErr::CassetteMiss`] when the request was never recorded.
+//!
+//! Fields that change from one run to the next without affecting the response
+//! (see [`VOLATILE_REQUEST_FIELDS`]) are left out of the key, and values that
+//! differ between runs inside the conversation itself (see
+//! [`volatile_text_patterns`]) are replaced with fixed placeholders, so that
+//! later turns of a session still match their recording.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use bytes::Bytes;
+use futures::Stream;
+use futures::StreamExt;
+use regex_lite::Regex;
+use serde_json::Value;
+use sha1::Digest;
+use sha1::Sha1;
+use tracing::info;
+use tracing::warn;
+
+use crate::error::CodexErr;
+use crate::error::Result;
+use crate::model_provider_info::WireApi;
+
+/// Top-level request fields that are excluded from the cassette key.
+const VOLATILE_REQUEST_FIELDS: &[&str] = &["prompt_cache_key"];
+
+/// Patterns for text that varies between runs of the same session, with
+/// their replacement: how long a command took (the `duration_seconds` of
+/// shell outputs and the `Wall time` of exec sessions) and the working
+/// directory in the environment context.
+fn volatile_text_patterns() -> &'static [(Regex, &'static str)] {
+    static PATTERNS: OnceLock<Vec<(Regex, &'static str)>> = OnceLock::new();
+
+    #[expect(clippy::unwrap_used)]
+    PATTERNS.get_or_init(|| {
+        vec![
+            (
+                Regex::new(r#""duration_seconds":\s*[0-9.eE+-]+"#).unwrap(),
+                r#""duration_seconds":0"#,
+            ),
+            (
+                Regex::new(r"Wall time: [0-9.]+ seconds").unwrap(),
+                "Wall time: 0 seconds",
+            ),
+            (Regex::new(r"<cwd>[^<]*</cwd>").unwrap(), "<cwd></cwd>"),
+        ]
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CassetteMode {
+    /// Forward requests to the provider and store every exchange.
+    Record,
+    /// Serve stored exchanges; never contact the provider.
+    Replay,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cassette {
+    pub dir: PathBuf,
+    pub mode: CassetteMode,
+}
+
+impl Cassette {
+    pub fn record(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Record,
+        }
+    }
+
+    pub fn replay(dir: impl Into<PathBuf>) -> Self {
+        Self {
+            dir: dir.into(),
+            mode: CassetteMode::Replay,
+        }
+    }
+
+    pub fn is_replay(&self) -> bool {
+        self.mode == CassetteMode::Replay
+    }
+
+    /// The recorded SSE stream for `payload`, or [`CodexErr::CassetteMiss`].
+    pub(crate) fn replay_stream(
+        &self,
+        wire_api: WireApi,
+        payload: &Value,
+    ) -> Result<impl Stream<Item = Result<Bytes>> + Unpin + use<>> {
+        let key = request_key(wire_api, payload);
+        let path = self.sse_path(&key);
+        let contents = match std::fs::read(&path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(CodexErr::CassetteMiss {
+                    key,
+                    dir: self.dir.clone(),
+                });
+            }
+            Err(e) => return Err(e.into()),
+        };
+        info!("Replaying {} from cassette", path.display());
+        Ok(futures::stream::iter(vec![Ok(Bytes::from(contents))]))
+    }
+
+    /// Store `payload` and pass `stream` through, copying every chunk into the
+    /// cassette as it arrives.
+    pub(crate) fn record_stream<S>(
+        &self,
+        wire_api: WireApi,
+        payload: &Value,
+        stream: S,
+    ) -> Result<impl Stream<Item = Result<Bytes>> + Unpin + use<S>>
+    where
+        S: Stream<Item = Result<Bytes>> + Unpin,
+    {
+        let key = request_key(wire_api, payload);
+        std::fs::create_dir_all(&self.dir)?;
+        let request = serde_json::to_vec_pretty(&cassette_request(wire_api, payload))?;
+        std::fs::write(self.request_path(&key), request)?;
+
+        let path = self.sse_path(&key);
+        info!("Recording {} to cassette", path.display());
+        let mut file = std::fs::File::create(&path)?;
+        Ok(stream.inspect(move |chunk| {
+            if let Ok(bytes) = chunk
+                && let Err(e) = file.write_all(bytes)
+            {
+                warn!("failed to record to {}: {e}", path.display());
+            }
+        }))
+    }
+
+    fn request_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.request.json"))
+    }
+
+    fn sse_path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.sse"))
+    }
+}
+
+/// The part of a request that identifies it in a cassette.
+fn cassette_request(wire_api: WireApi, payload: &Value) -> Value {
+    let mut body = canonicalize(payload);
+    if let Value::Object(map) = &mut body {
+        for field in VOLATILE_REQUEST_FIELDS {
+            map.remove(*field);
+        }
+    }
+    let wire_api = match wire_api {
+        WireApi::Responses => "responses",
+        WireApi::Chat => "chat",
+    };
+    serde_json::json!({ "wire_api": wire_api, "body": body })
+}
+
+/// Hash of the request, used as the file name of its recording.
+pub(crate) fn request_key(wire_api: WireApi, payload: &Value) -> String {
+    let canonical = cassette_request(wire_api, payload).to_string();
+    let mut hasher = Sha1::new();
+    hasher.update(canonical.as_bytes());
+    format!("{:x}", hasher.finalize())
+}
+
+/// `value` with object keys in sorted order, so the key does not depend on
+/// the order in which fields were inserted, and with volatile text replaced.
+fn canonicalize(value: &Value) -> Value {
+    match value {
+        Value::Object(map) => {
+            let sorted: BTreeMap<&String, Value> =
+                map.iter().map(|(k, v)| (k, canonicalize(v))).collect();
+            Value::Object(sorted.into_iter().map(|(k, v)| (k.clone(), v)).collect())
+        }
+        Value::Array(items) => Value::Array(items.iter().map(canonicalize).collect()),
+        Value::String(text) => Value::String(normalize_volatile_text(text)),
+        other => other.clone(),
+    }
+}
+
+fn normalize_volatile_text(text: &str) -> String {
+    volatile_text_patterns()
+        .iter()
+        .fold(text.to_string(), |text, (pattern, replacement)| {
+            pattern.replace_all(&text, *replacement).into_owned()
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::TryStreamExt;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+
+    #[test]
+    fn key_ignores_field_order_and_volatile_fields() {
+        let a = json!({
+            "model": "gpt-5",
+            "input": [{"role": "user", "content": "hi"}],
+            "prompt_cache_key": "conversation-1",
+        });
+        let b = json!({
+            "prompt_cache_key": "conversation-2",
+            "input": [{"content": "hi", "role": "user"}],
+            "model": "gpt-5",
+        });
+        assert_eq!(
+            request_key(WireApi::Responses, &a),
+            request_key(WireApi::Responses, &b)
+        );
+        assert_ne!(
+            request_key(WireApi::Responses, &a),
+            request_key(WireApi::Chat, &a)
+        );
+        assert_ne!(
+            request_key(WireApi::Responses, &a),
+            request_key(WireApi::Responses, &json!({"model": "gpt-5"}))
+        );
+    }
+
+    #[test]
+    fn key_ignores_command_durations_and_cwd() {
+        let turn = |cwd: &str, duration: &str| {
+            json!({
+                "input": [
+                    {
+                        "type": "message",
+                        "role": "user",
+                        "content": [{
+                            "type": "input_text",
+                            "text": format!("<environment_context>\n  <cwd>{cwd}</cwd>\n</environment_context>"),
+                        }],
+                    },
+                    {
+                        "type": "function_call_output",
+                        "call_id": "call-1",
+                        "output": format!(
+                            r#"{{"output":"hi\n","metadata":{{"exit_code":0,"duration_seconds":{duration}}}}}"#
+                        ),
+                    },
+                ],
+            })
+        };
+        assert_eq!(
+            request_key(WireApi::Responses, &turn("/tmp/record", "0.1")),
+            request_key(WireApi::Responses, &turn("/tmp/replay", "1.5"))
+        );
+        assert_eq!(
+            normalize_volatile_text("Chunk ID: 1\nWall time: 2.013 seconds\nProcess exited"),
+            "Chunk ID: 1\nWall time: 0 seconds\nProcess exited"
+        );
+    }
+
+    #[tokio::test]
+    async fn recorded_stream_is_replayed() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        let payload = json!({"model": "gpt-5", "input": []});
+        let chunks: Vec<Result<Bytes>> = vec![
+            Ok(Bytes::from_static(b"event: response.created\n")),
+            Ok(Bytes::from_static(b"data: {}\n\n")),
+        ];
+
+        let recorder = Cassette::record(dir.path());
+        let recorded: Vec<Bytes> = recorder
+            .record_stream(WireApi::Responses, &payload, futures::stream::iter(chunks))
+            .expect("start recording")
+            .try_collect()
+            .await
+            .expect("record stream");
+        assert_eq!(recorded.concat(), b"event: response.created\ndata: {}\n\n");
+
+        let player = Cassette::replay(dir.path());
+        let replayed: Vec<Bytes> = player
+            .replay_stream(WireApi::Responses, &payload)
+            .expect("cassette hit")
+            .try_collect()
+            .await
+            .expect("replay stream");
+        assert_eq!(replayed.concat(), recorded.concat());
+
+        let miss = player.replay_stream(WireApi::Chat, &payload);
+        assert!(matches!(miss, Err(CodexErr::CassetteMiss { .. })));
+    }
+}
@@ -1,12 +1,14 @@
 use std::time::Duration;
 
 use crate::ModelProviderInfo;
+use crate::cassette::Cassette;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
 use crate::client_common::ResponseStream;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::model_family::ModelFamily;
+use crate::model_provider_info::WireApi;
 use crate::openai_tools::create_tools_json_for_chat_completions_api;
 use crate::util::backoff;
 use bytes::Bytes;
@@ -35,6 +37,7 @@ pub(crate) async fn stream_chat_completions(
     client: &reqwest::Client,
     provider: &ModelProviderInfo,
     otel_event_manager: &OtelEventManager,
+    cassette: Option<&Cassette>,
 ) -> Result<ResponseStream> {
     if prompt.output_schema.is_some() {
         return Err(CodexErr::UnsupportedOperation(
@@ -275,12 +278,15 @@ pub(crate) async fn stream_chat_completions(
     }
 
     let tools_json = create_tools_json_for_chat_completions_api(&prompt.tools)?;
-    let payload = json!({
+    let mut payload = json!({
         "model": model_family.slug,
         "messages": messages,
         "stream": true,
         "tools": tools_json,
     });
+    if let Some(max_tokens) = prompt.max_output_tokens {
+        payload["max_tokens"] = json!(max_tokens);
+    }
 
     debug!(
         "POST to {}: {}",
@@ -288,6 +294,20 @@ pub(crate) async fn stream_chat_completions(
         serde_json::to_string_pretty(&payload).unwrap_or_default()
     );
 
+    if let Some(cassette) = cassette
+        && cassette.is_replay()
+    {
+        let stream = cassette.replay_stream(WireApi::Chat, &payload)?;
+        let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+        tokio::spawn(process_chat_sse(
+            stream,
+            tx_event,
+            provider.stream_idle_timeout(),
+            otel_event_manager.clone(),
+        ));
+        return Ok(ResponseStream { rx_event });
+    }
+
     let mut attempt = 0;
     let max_retries = provider.request_max_retries();
     loop {
@@ -308,12 +328,25 @@ pub(crate) async fn stream_chat_completions(
             Ok(resp) if resp.status().is_success() => {
                 let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
                 let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
-                tokio::spawn(process_chat_sse(
-                    stream,
-                    tx_event,
-                    provider.stream_idle_timeout(),
-                    otel_event_manager.clone(),
-                ));
+                match cassette {
+                    Some(cassette) => {
+                        let stream = cassette.record_stream(WireApi::Chat, &payload, stream)?;
+                        tokio::spawn(process_chat_sse(
+                            stream,
+                            tx_event,
+                            provider.stream_idle_timeout(),
+                            otel_event_manager.clone(),
+                        ));
+                    }
+                    None => {
+                        tokio::spawn(process_chat_sse(
+                            stream,
+                            tx_event,
+                            provider.stream_idle_timeout(),
+                            otel_event_manager.clone(),
+                        ));
+                    }
+                }
                 return Ok(ResponseStream { rx_event });
             }
             Ok(res) => {
@@ -586,9 +619,10 @@ async fn process_chat_sse<S>(
 
                         let _ = tx_event.send(Ok(ResponseEvent::OutputItemDone(item))).await;
                     }
-                    "stop" => {
-                        // Regular turn without tool-call. Emit the final assistant message
-                        // as a single OutputItemDone so non-delta consumers see the result.
+                    "stop" | "length" => {
+                        // Regular turn without tool-call, possibly cut off at `max_tokens`.
+                        // Emit the final assistant message as a single OutputItemDone so
+                        // non-delta consumers see the result.
                         if !assistant_text.is_empty() {
                             let item = ResponseItem::Message {
                                 role: "assistant".to_string(),
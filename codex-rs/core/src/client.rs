@@ -40,7 +40,7 @@ use crate::flags::CODEX_RS_SSE_FIXTURE;
 use crate::model_family::ModelFamily;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::WireApi;
-use crate::openai_model_info::get_model_info;
+use crate::openai_model_info::get_configured_model_info;
 use crate::openai_tools::create_tools_json_for_responses_api;
 use crate::protocol::RateLimitSnapshot;
 use crate::protocol::RateLimitWindow;
@@ -107,14 +107,16 @@ impl ModelClient {
     }
 
     pub fn get_model_context_window(&self) -> Option<u64> {
-        self.config
-            .model_context_window
-            .or_else(|| get_model_info(&self.config.model_family).map(|info| info.context_window))
+        self.config.model_context_window.or_else(|| {
+            get_configured_model_info(&self.config.model_family, &self.config.models)
+                .map(|info| info.context_window)
+        })
     }
 
     pub fn get_auto_compact_token_limit(&self) -> Option<i64> {
         self.config.model_auto_compact_token_limit.or_else(|| {
-            get_model_info(&self.config.model_family).and_then(|info| info.auto_compact_token_limit)
+            get_configured_model_info(&self.config.model_family, &self.config.models)
+                .and_then(|info| info.auto_compact_token_limit)
         })
     }
 
@@ -132,6 +134,7 @@ impl ModelClient {
                     &self.client,
                     &self.provider,
                     &self.otel_event_manager,
+                    self.config.cassette.as_ref(),
                 )
                 .await?;
 
@@ -233,6 +236,7 @@ impl ModelClient {
             include,
             prompt_cache_key: Some(self.conversation_id.to_string()),
             text,
+            max_output_tokens: prompt.max_output_tokens,
         };
 
         let mut payload_json = serde_json::to_value(&payload)?;
@@ -240,6 +244,20 @@ impl ModelClient {
             attach_item_ids(&mut payload_json, &input_with_instructions);
         }
 
+        if let Some(cassette) = &self.config.cassette
+            && cassette.is_replay()
+        {
+            let stream = cassette.replay_stream(WireApi::Responses, &payload_json)?;
+            let (tx_event, rx_event) = mpsc::channel::<Result<ResponseEvent>>(1600);
+            tokio::spawn(process_sse(
+                stream,
+                tx_event,
+                self.provider.stream_idle_timeout(),
+                self.otel_event_manager.clone(),
+            ));
+            return Ok(ResponseStream { rx_event });
+        }
+
         let max_attempts = self.provider.request_max_retries();
         for attempt in 0..=max_attempts {
             match self
@@ -333,12 +351,29 @@ impl ModelClient {
 
                 // spawn task to process SSE
                 let stream = resp.bytes_stream().map_err(CodexErr::Reqwest);
-                tokio::spawn(process_sse(
-                    stream,
-                    tx_event,
-                    self.provider.stream_idle_timeout(),
-                    self.otel_event_manager.clone(),
-                ));
+                let idle_timeout = self.provider.stream_idle_timeout();
+                let otel_event_manager = self.otel_event_manager.clone();
+                match &self.config.cassette {
+                    Some(cassette) => {
+                        let stream = cassette
+                            .record_stream(WireApi::Responses, payload_json, stream)
+                            .map_err(StreamAttemptError::Fatal)?;
+                        tokio::spawn(process_sse(
+                            stream,
+                            tx_event,
+                            idle_timeout,
+                            otel_event_manager,
+                        ));
+                    }
+                    None => {
+                        tokio::spawn(process_sse(
+                            stream,
+                            tx_event,
+                            idle_timeout,
+                            otel_event_manager,
+                        ));
+                    }
+                }
 
                 Ok(ResponseStream { rx_event })
             }
@@ -442,6 +477,11 @@ impl ModelClient {
     pub fn get_auth_manager(&self) -> Option<Arc<AuthManager>> {
         self.auth_manager.clone()
     }
+
+    /// Returns the configuration this client was built from.
+    pub(crate) fn get_config(&self) -> Arc<Config> {
+        Arc::clone(&self.config)
+    }
 }
 
 enum StreamAttemptError {
@@ -794,8 +834,10 @@ async fn process_sse<S>(
                     }
                 }
             }
-            // Final response completed – includes array of output items & id
-            "response.completed" => {
+            // Final response completed – includes array of output items & id.
+            // A response cut short, e.g. at `max_output_tokens`, ends the same
+            // way.
+            "response.completed" | "response.incomplete" => {
                 if let Some(resp_val) = event.response {
                     match serde_json::from_value::<ResponseCompleted>(resp_val) {
                         Ok(r) => {
@@ -4,6 +4,8 @@ use std::fmt::Debug;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::sync::atomic::AtomicU64;
+use std::time::Duration;
+use std::time::Instant;
 
 use crate::AuthManager;
 use crate::client_common::REVIEW_PROMPT;
@@ -15,8 +17,10 @@ use crate::user_notification::UserNotifier;
 use async_channel::Receiver;
 use async_channel::Sender;
 use codex_apply_patch::ApplyPatchAction;
+use codex_apply_patch::ApplyPatchError;
 use codex_apply_patch::MaybeApplyPatchVerified;
 use codex_apply_patch::maybe_parse_apply_patch_verified;
+use codex_mcp_client::ToolCallNotificationHandler;
 use codex_protocol::ConversationId;
 use codex_protocol::protocol::ConversationPathResponseEvent;
 use codex_protocol::protocol::ExitedReviewModeEvent;
@@ -32,6 +36,7 @@ use serde::Serialize;
 use serde_json;
 use serde_json::Value;
 use tokio::sync::Mutex;
+use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tracing::debug;
 use tracing::error;
@@ -44,6 +49,8 @@ use crate::apply_patch;
 use crate::apply_patch::ApplyPatchExec;
 use crate::apply_patch::InternalApplyPatchInvocation;
 use crate::apply_patch::convert_apply_patch_to_protocol;
+use crate::budget;
+use crate::budget::BudgetExceeded;
 use crate::client::ModelClient;
 use crate::client_common::Prompt;
 use crate::client_common::ResponseEvent;
@@ -56,6 +63,7 @@ use crate::error::Result as CodexResult;
 use crate::error::SandboxErr;
 use crate::exec::ExecParams;
 use crate::exec::ExecToolCallOutput;
+use crate::exec::SandboxType;
 use crate::exec::StdoutStream;
 #[cfg(test)]
 use crate::exec::StreamOutput;
@@ -69,10 +77,15 @@ use crate::executor::ExecutionMode;
 use crate::executor::Executor;
 use crate::executor::ExecutorConfig;
 use crate::executor::normalize_exec_result;
+use crate::mcp_connection_manager::ClientStartErrors;
 use crate::mcp_connection_manager::McpConnectionManager;
+use crate::mcp_connection_manager::McpServerRequest;
+use crate::mcp_connection_manager::mcp_roots;
+use crate::mcp_tool_call::MCP_TOOL_OUTPUT_MAX_BYTES;
+use crate::mcp_tool_call::content_to_text;
 use crate::mcp_tool_call::handle_mcp_tool_call;
-use crate::model_family::find_family_for_model;
-use crate::openai_model_info::get_model_info;
+use crate::model_family::find_family_for_configured_model;
+use crate::openai_model_info::get_configured_model_info;
 use crate::openai_tools::ApplyPatchToolArgs;
 use crate::openai_tools::ToolsConfig;
 use crate::openai_tools::ToolsConfigParams;
@@ -96,6 +109,7 @@ use crate::protocol::ExecCommandEndEvent;
 use crate::protocol::FileChange;
 use crate::protocol::InputItem;
 use crate::protocol::ListCustomPromptsResponseEvent;
+use crate::protocol::McpElicitationAction;
 use crate::protocol::Op;
 use crate::protocol::PatchApplyBeginEvent;
 use crate::protocol::PatchApplyEndEvent;
@@ -112,12 +126,17 @@ use crate::protocol::TurnDiffEvent;
 use crate::protocol::WebSearchBeginEvent;
 use crate::rollout::RolloutRecorder;
 use crate::rollout::RolloutRecorderParams;
+use crate::screenshot::CAPTURE_SCREENSHOT_TOOL_NAME;
+use crate::screenshot::handle_capture_screenshot;
 use crate::shell;
 use crate::state::ActiveTurn;
 use crate::state::SessionServices;
+use crate::sub_agent::SPAWN_AGENT_TOOL_NAME;
+use crate::sub_agent::handle_spawn_agent;
 use crate::tasks::CompactTask;
 use crate::tasks::RegularTask;
 use crate::tasks::ReviewTask;
+use crate::truncate::truncate_middle;
 use crate::turn_diff_tracker::TurnDiffTracker;
 use crate::unified_exec::UnifiedExecSessionManager;
 use crate::user_instructions::UserInstructions;
@@ -136,8 +155,10 @@ use codex_protocol::models::ShellToolCallParams;
 use codex_protocol::protocol::InitialHistory;
 
 pub mod compact;
+mod mcp_server_requests;
 use self::compact::build_compacted_history;
 use self::compact::collect_user_messages;
+use self::mcp_server_requests::handle_mcp_server_request;
 
 /// The high-level interface to the Codex system.
 /// It operates as a queue pair where you send submissions and receive events.
@@ -157,6 +178,9 @@ pub struct CodexSpawnOk {
 
 pub(crate) const INITIAL_SUBMIT_ID: &str = "";
 pub(crate) const SUBMISSION_CHANNEL_CAPACITY: usize = 64;
+/// Sampling and elicitation requests from MCP servers waiting to be picked up
+/// by the submission loop.
+const MCP_SERVER_REQUEST_CHANNEL_CAPACITY: usize = 16;
 
 // Model-formatting limits: clients get full streams; oonly content sent to the model is truncated.
 pub(crate) const MODEL_FORMAT_MAX_BYTES: usize = 10 * 1024; // 10 KiB
@@ -193,7 +217,7 @@ impl Codex {
         };
 
         // Generate a unique ID for the lifetime of this Codex session.
-        let (session, turn_context) = Session::new(
+        let (session, turn_context, mcp_server_requests) = Session::new(
             configure_session,
             config.clone(),
             auth_manager.clone(),
@@ -208,7 +232,13 @@ impl Codex {
         let conversation_id = session.conversation_id;
 
         // This task will run until Op::Shutdown is received.
-        tokio::spawn(submission_loop(session, turn_context, config, rx_sub));
+        tokio::spawn(submission_loop(
+            session,
+            turn_context,
+            config,
+            rx_sub,
+            mcp_server_requests,
+        ));
         let codex = Codex {
             next_id: AtomicU64::new(0),
             tx_sub,
@@ -333,7 +363,7 @@ impl Session {
         auth_manager: Arc<AuthManager>,
         tx_event: Sender<Event>,
         initial_history: InitialHistory,
-    ) -> anyhow::Result<(Arc<Self>, TurnContext)> {
+    ) -> anyhow::Result<(Arc<Self>, TurnContext, mpsc::Receiver<McpServerRequest>)> {
         let ConfigureSession {
             provider,
             model,
@@ -376,9 +406,16 @@ impl Session {
         // - load history metadata
         let rollout_fut = RolloutRecorder::new(&config, rollout_params);
 
+        let (tx_mcp_server_request, rx_mcp_server_request) =
+            mpsc::channel(MCP_SERVER_REQUEST_CHANNEL_CAPACITY);
         let mcp_fut = McpConnectionManager::new(
             config.mcp_servers.clone(),
+            config.overridden_mcp_servers.clone(),
             config.use_experimental_use_rmcp_client,
+            config.codex_home.clone(),
+            config.credential_store.clone(),
+            mcp_roots(&cwd, &sandbox_policy),
+            tx_mcp_server_request,
         );
         let default_shell_fut = shell::default_user_shell();
         let history_meta_fut = crate::message_history::history_metadata(&config);
@@ -421,6 +458,40 @@ impl Session {
             }
         }
 
+        if config.budget.max_cost_usd.is_some() && !config.model_prices.contains_key(&config.model)
+        {
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                    message: format!(
+                        "budget.max_cost_usd is set but model_prices has no entry for `{}`, so its usage will not count toward the limit.",
+                        config.model
+                    ),
+                }),
+            });
+        }
+
+        if !config.sandbox_resource_limits.is_empty()
+            && (crate::safety::get_platform_sandbox() != Some(SandboxType::LinuxSeccomp)
+                || matches!(config.sandbox_policy, SandboxPolicy::DangerFullAccess))
+        {
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                    message: "sandbox_resource_limits only apply to commands run in the Linux sandbox, so they are ignored in this session.".to_string(),
+                }),
+            });
+        } else if config.sandbox_resource_limits.max_rss_mb.is_some()
+            && !crate::resource_limits::memory_limit_enforceable()
+        {
+            post_session_configured_error_events.push(Event {
+                id: INITIAL_SUBMIT_ID.to_owned(),
+                msg: EventMsg::BackgroundEvent(BackgroundEventEvent {
+                    message: "sandbox_resource_limits.max_rss_mb is not enforced because no delegated cgroup v2 scope is available.".to_string(),
+                }),
+            });
+        }
+
         let otel_event_manager = OtelEventManager::new(
             conversation_id,
             config.model.as_str(),
@@ -464,6 +535,8 @@ impl Session {
                 include_web_search_request: config.tools_web_search_request,
                 use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
                 include_view_image_tool: config.include_view_image_tool,
+                include_screenshot_tool: config.screenshot.is_some(),
+                include_spawn_agent_tool: config.include_spawn_agent_tool,
                 experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
             }),
             user_instructions,
@@ -483,11 +556,19 @@ impl Session {
             rollout: Mutex::new(Some(rollout_recorder)),
             user_shell: default_shell,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
-            executor: Executor::new(ExecutorConfig::new(
-                turn_context.sandbox_policy.clone(),
-                turn_context.cwd.clone(),
-                config.codex_linux_sandbox_exe.clone(),
-            )),
+            answers_mcp_server_requests: config.answers_mcp_server_requests,
+            apply_patch_fuzzy_threshold: config.apply_patch_fuzzy_threshold,
+            model_prices: config.model_prices.clone(),
+            budget: config.budget,
+            started_at: Instant::now(),
+            executor: Executor::new(
+                ExecutorConfig::new(
+                    turn_context.sandbox_policy.clone(),
+                    turn_context.cwd.clone(),
+                    config.codex_linux_sandbox_exe.clone(),
+                )
+                .with_resource_limits(config.sandbox_resource_limits),
+            ),
         };
 
         let sess = Arc::new(Session {
@@ -522,7 +603,7 @@ impl Session {
             sess.send_event(event).await;
         }
 
-        Ok((sess, turn_context))
+        Ok((sess, turn_context, rx_mcp_server_request))
     }
 
     fn next_internal_sub_id(&self) -> String {
@@ -768,15 +849,93 @@ impl Session {
         {
             let mut state = self.state.lock().await;
             if let Some(token_usage) = token_usage {
+                let cost_usd = self
+                    .services
+                    .model_prices
+                    .get(&turn_context.client.get_model())
+                    .map(|price| budget::usage_cost_usd(token_usage, price));
                 state.update_token_info_from_usage(
                     token_usage,
                     turn_context.client.get_model_context_window(),
+                    cost_usd,
                 );
             }
         }
         self.send_token_count_event(sub_id).await;
     }
 
+    /// Counts a request to the model against `budget.max_turns`, unless the
+    /// session has already reached one of its `[budget]` limits.
+    async fn start_model_turn_within_budget(&self) -> Result<(), BudgetExceeded> {
+        let mut state = self.state.lock().await;
+        if let Some(exceeded) = budget::check_budget(
+            &self.services.budget,
+            state.token_info.as_ref(),
+            state.model_turns,
+            self.services.started_at.elapsed(),
+        ) {
+            return Err(exceeded);
+        }
+        state.model_turns += 1;
+        Ok(())
+    }
+
+    /// The task checks `budget.max_duration_secs` before each request to the
+    /// model, but a long-running command or a stalled stream could keep it
+    /// going well past the limit, so also abort it once the time is up.
+    pub(crate) fn arm_duration_budget_timer(self: &Arc<Self>, sub_id: String) {
+        let Some(limit) = self
+            .services
+            .budget
+            .max_duration_secs
+            .map(Duration::from_secs)
+        else {
+            return;
+        };
+        let remaining = limit.saturating_sub(self.services.started_at.elapsed());
+        let session = Arc::downgrade(self);
+        tokio::spawn(async move {
+            tokio::time::sleep(remaining).await;
+            if let Some(session) = session.upgrade() {
+                session
+                    .abort_task_over_budget(&sub_id, BudgetExceeded::Duration { limit })
+                    .await;
+            }
+        });
+    }
+
+    async fn abort_task_over_budget(self: &Arc<Self>, sub_id: &str, exceeded: BudgetExceeded) {
+        let still_running = self
+            .active_turn
+            .lock()
+            .await
+            .as_ref()
+            .is_some_and(|turn| turn.tasks.contains_key(sub_id));
+        if !still_running {
+            return;
+        }
+        self.send_event(Event {
+            id: sub_id.to_string(),
+            msg: EventMsg::Error(ErrorEvent {
+                message: exceeded.to_string(),
+            }),
+        })
+        .await;
+        self.abort_all_tasks(TurnAbortReason::BudgetExceeded).await;
+    }
+
+    /// Makes the task report a `TurnAborted` event with `reason` instead of
+    /// `TaskComplete` once it returns.
+    async fn end_task_with_abort(&self, sub_id: &str, reason: TurnAbortReason) {
+        let mut state = self.state.lock().await;
+        state.task_abort = Some((sub_id.to_string(), reason));
+    }
+
+    pub(crate) async fn take_task_abort(&self, sub_id: &str) -> Option<TurnAbortReason> {
+        let mut state = self.state.lock().await;
+        state.take_task_abort(sub_id)
+    }
+
     async fn update_rate_limits(&self, sub_id: &str, new_rate_limits: RateLimitSnapshot) {
         {
             let mut state = self.state.lock().await;
@@ -878,6 +1037,7 @@ impl Session {
             duration,
             exit_code,
             timed_out: _,
+            resource_limit_exceeded: _,
         } = output;
         // Send full stdout/stderr to clients; do not truncate.
         let stdout = stdout.text.clone();
@@ -1022,15 +1182,38 @@ impl Session {
         }
     }
 
+    /// Restart MCP servers that have exited and re-list tools that changed
+    /// and, with `reload_config`, start servers added to `config.toml` since
+    /// it was last read. Servers are (re)started in the background; those
+    /// that failed to start are reported as background events.
+    async fn refresh_mcp_servers(&self, sub_id: &str, reload_config: bool) {
+        let manager = &self.services.mcp_connection_manager;
+        let mut errors = ClientStartErrors::new();
+        if reload_config {
+            errors.extend(manager.reload_global_servers().await);
+        }
+        errors.extend(manager.refresh().await);
+        for (server_name, err) in errors {
+            let message = format!("MCP client for `{server_name}` failed to start: {err:#}");
+            warn!("{message}");
+            self.send_event(Event {
+                id: sub_id.to_string(),
+                msg: EventMsg::BackgroundEvent(BackgroundEventEvent { message }),
+            })
+            .await;
+        }
+    }
+
     pub async fn call_tool(
         &self,
         server: &str,
         tool: &str,
         arguments: Option<serde_json::Value>,
+        on_notification: Option<ToolCallNotificationHandler>,
     ) -> anyhow::Result<CallToolResult> {
         self.services
             .mcp_connection_manager
-            .call_tool(server, tool, arguments)
+            .call_tool(server, tool, arguments, on_notification)
             .await
     }
 
@@ -1093,11 +1276,28 @@ async fn submission_loop(
     turn_context: TurnContext,
     config: Arc<Config>,
     rx_sub: Receiver<Submission>,
+    mut mcp_server_requests: mpsc::Receiver<McpServerRequest>,
 ) {
     // Wrap once to avoid cloning TurnContext for each task.
     let mut turn_context = Arc::new(turn_context);
     // To break out of this loop, send Op::Shutdown.
-    while let Ok(sub) = rx_sub.recv().await {
+    loop {
+        let sub = tokio::select! {
+            sub = rx_sub.recv() => match sub {
+                Ok(sub) => sub,
+                Err(_) => break,
+            },
+            Some(request) = mcp_server_requests.recv() => {
+                // Answered on its own task: it may wait for the user, whose
+                // decision arrives as a submission.
+                tokio::spawn(handle_mcp_server_request(
+                    Arc::clone(&sess),
+                    Arc::clone(&turn_context),
+                    request,
+                ));
+                continue;
+            }
+        };
         debug!(?sub, "Submission");
         match sub.op {
             Op::Interrupt => {
@@ -1117,8 +1317,8 @@ async fn submission_loop(
 
                 // Effective model + family
                 let (effective_model, effective_family) = if let Some(ref m) = model {
-                    let fam =
-                        find_family_for_model(m).unwrap_or_else(|| config.model_family.clone());
+                    let fam = find_family_for_configured_model(m, &config.models)
+                        .unwrap_or_else(|| config.model_family.clone());
                     (m.clone(), fam)
                 } else {
                     (prev.client.get_model(), prev.client.get_model_family())
@@ -1134,7 +1334,9 @@ async fn submission_loop(
                 let mut updated_config = (*config).clone();
                 updated_config.model = effective_model.clone();
                 updated_config.model_family = effective_family.clone();
-                if let Some(model_info) = get_model_info(&effective_family) {
+                if let Some(model_info) =
+                    get_configured_model_info(&effective_family, &config.models)
+                {
                     updated_config.model_context_window = Some(model_info.context_window);
                 }
 
@@ -1166,6 +1368,8 @@ async fn submission_loop(
                     include_web_search_request: config.tools_web_search_request,
                     use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
                     include_view_image_tool: config.include_view_image_tool,
+                    include_screenshot_tool: config.screenshot.is_some(),
+                    include_spawn_agent_tool: config.include_spawn_agent_tool,
                     experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
                 });
 
@@ -1184,6 +1388,10 @@ async fn submission_loop(
 
                 // Install the new persistent context for subsequent tasks/turns.
                 turn_context = Arc::new(new_turn_context);
+                sess.services
+                    .mcp_connection_manager
+                    .set_roots(mcp_roots(&turn_context.cwd, &turn_context.sandbox_policy))
+                    .await;
 
                 // Optionally persist changes to model / effort
                 if cwd.is_some() || approval_policy.is_some() || sandbox_policy.is_some() {
@@ -1230,14 +1438,16 @@ async fn submission_loop(
                     let auth_manager = turn_context.client.get_auth_manager();
 
                     // Derive a model family for the requested model; fall back to the session's.
-                    let model_family = find_family_for_model(&model)
+                    let model_family = find_family_for_configured_model(&model, &config.models)
                         .unwrap_or_else(|| config.model_family.clone());
 
                     // Create a per‑turn Config clone with the requested model/family.
                     let mut per_turn_config = (*config).clone();
                     per_turn_config.model = model.clone();
                     per_turn_config.model_family = model_family.clone();
-                    if let Some(model_info) = get_model_info(&model_family) {
+                    if let Some(model_info) =
+                        get_configured_model_info(&model_family, &config.models)
+                    {
                         per_turn_config.model_context_window = Some(model_info.context_window);
                     }
 
@@ -1269,6 +1479,8 @@ async fn submission_loop(
                             use_streamable_shell_tool: config
                                 .use_experimental_streamable_shell_tool,
                             include_view_image_tool: config.include_view_image_tool,
+                            include_screenshot_tool: config.screenshot.is_some(),
+                            include_spawn_agent_tool: config.include_spawn_agent_tool,
                             experimental_unified_exec_tool: config
                                 .use_experimental_unified_exec_tool,
                         }),
@@ -1303,6 +1515,10 @@ async fn submission_loop(
 
                     // Install the new persistent context for subsequent tasks/turns.
                     turn_context = Arc::new(fresh_turn_context);
+                    sess.services
+                        .mcp_connection_manager
+                        .set_roots(mcp_roots(&turn_context.cwd, &turn_context.sandbox_policy))
+                        .await;
 
                     // no current task, spawn a new one with the per-turn context
                     sess.spawn_task(Arc::clone(&turn_context), sub.id, items, RegularTask)
@@ -1321,6 +1537,23 @@ async fn submission_loop(
                 }
                 other => sess.notify_approval(&id, other).await,
             },
+            Op::McpSamplingApproval { id, decision } => {
+                sess.notify_mcp_sampling_approval(&id, decision).await;
+                if decision == ReviewDecision::Abort {
+                    sess.interrupt_task().await;
+                }
+            }
+            Op::McpElicitationResponse {
+                id,
+                action,
+                content,
+            } => {
+                let result = mcp_types::ElicitResult {
+                    action: action.to_string(),
+                    content: content.filter(|_| action == McpElicitationAction::Accept),
+                };
+                sess.resolve_mcp_elicitation(&id, result).await;
+            }
             Op::AddToHistory { text } => {
                 let id = sess.conversation_id;
                 let config = config.clone();
@@ -1365,18 +1598,70 @@ async fn submission_loop(
                     sess_clone.send_event(event).await;
                 });
             }
+            Op::SearchHistoryRequest {
+                query,
+                log_id,
+                max_results,
+            } => {
+                let config = config.clone();
+                let sess_clone = sess.clone();
+                let sub_id = sub.id.clone();
+
+                tokio::spawn(async move {
+                    // Scanning the history file is blocking file IO + locking.
+                    let search_query = query.clone();
+                    let matches = tokio::task::spawn_blocking(move || {
+                        crate::message_history::search(log_id, &search_query, max_results, &config)
+                    })
+                    .await
+                    .unwrap_or_default();
+
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::SearchHistoryResponse(
+                            crate::protocol::SearchHistoryResponseEvent {
+                                query,
+                                log_id,
+                                matches: matches
+                                    .into_iter()
+                                    .map(|(offset, e)| {
+                                        codex_protocol::message_history::HistoryMatch {
+                                            offset,
+                                            entry: codex_protocol::message_history::HistoryEntry {
+                                                conversation_id: e.session_id,
+                                                ts: e.ts,
+                                                text: e.text,
+                                            },
+                                        }
+                                    })
+                                    .collect(),
+                            },
+                        ),
+                    };
+
+                    sess_clone.send_event(event).await;
+                });
+            }
             Op::ListMcpTools => {
                 let sub_id = sub.id.clone();
+                let sess_clone = sess.clone();
 
-                // This is a cheap lookup from the connection manager's cache.
-                let tools = sess.services.mcp_connection_manager.list_all_tools();
-                let event = Event {
-                    id: sub_id,
-                    msg: EventMsg::McpListToolsResponse(
-                        crate::protocol::McpListToolsResponseEvent { tools },
-                    ),
-                };
-                sess.send_event(event).await;
+                // Refreshing may (re)start servers, so keep it off the
+                // submission loop.
+                tokio::spawn(async move {
+                    sess_clone.refresh_mcp_servers(&sub_id, true).await;
+                    let manager = &sess_clone.services.mcp_connection_manager;
+                    let event = Event {
+                        id: sub_id,
+                        msg: EventMsg::McpListToolsResponse(
+                            crate::protocol::McpListToolsResponseEvent {
+                                tools: manager.list_all_tools(),
+                                servers: manager.server_statuses(),
+                            },
+                        ),
+                    };
+                    sess_clone.send_event(event).await;
+                });
             }
             Op::ListCustomPrompts => {
                 let sub_id = sub.id.clone();
@@ -1492,7 +1777,7 @@ async fn spawn_review_thread(
     review_request: ReviewRequest,
 ) {
     let model = config.review_model.clone();
-    let review_model_family = find_family_for_model(&model)
+    let review_model_family = find_family_for_configured_model(&model, &config.models)
         .unwrap_or_else(|| parent_turn_context.client.get_model_family());
     let tools_config = ToolsConfig::new(&ToolsConfigParams {
         model_family: &review_model_family,
@@ -1501,6 +1786,8 @@ async fn spawn_review_thread(
         include_web_search_request: false,
         use_streamable_shell_tool: false,
         include_view_image_tool: false,
+        include_screenshot_tool: false,
+        include_spawn_agent_tool: false,
         experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
     });
 
@@ -1516,7 +1803,7 @@ async fn spawn_review_thread(
     per_turn_config.model_family = model_family.clone();
     per_turn_config.model_reasoning_effort = Some(ReasoningEffortConfig::Low);
     per_turn_config.model_reasoning_summary = ReasoningSummaryConfig::Detailed;
-    if let Some(model_info) = get_model_info(&model_family) {
+    if let Some(model_info) = get_configured_model_info(&model_family, &config.models) {
         per_turn_config.model_context_window = Some(model_info.context_window);
     }
 
@@ -1624,8 +1911,28 @@ pub(crate) async fn run_task(
     // many turns, from the perspective of the user, it is a single turn.
     let mut turn_diff_tracker = TurnDiffTracker::new();
     let mut auto_compact_recently_attempted = false;
+    // `config.toml` is read again once per task, not before every request.
+    let mut reload_mcp_config = true;
 
     loop {
+        if let Err(exceeded) = sess.start_model_turn_within_budget().await {
+            sess.send_event(Event {
+                id: sub_id.clone(),
+                msg: EventMsg::Error(ErrorEvent {
+                    message: exceeded.to_string(),
+                }),
+            })
+            .await;
+            sess.end_task_with_abort(&sub_id, TurnAbortReason::BudgetExceeded)
+                .await;
+            break;
+        }
+
+        // Pick up MCP servers added, restarted, or with changed tools since
+        // the previous request before the tool list is built.
+        sess.refresh_mcp_servers(&sub_id, std::mem::take(&mut reload_mcp_config))
+            .await;
+
         // Note that pending_input would be something like a message the user
         // submitted through the UI while the model was running. Though the UI
         // may support this, the model might not.
@@ -1911,6 +2218,7 @@ async fn run_turn(
         tools,
         base_instructions_override: turn_context.base_instructions.clone(),
         output_schema: turn_context.final_output_json_schema.clone(),
+        max_output_tokens: None,
     };
 
     let mut retries = 0;
@@ -1927,6 +2235,7 @@ async fn run_turn(
                 return Err(CodexErr::UsageLimitReached(e));
             }
             Err(CodexErr::UsageNotIncluded) => return Err(CodexErr::UsageNotIncluded),
+            Err(e @ CodexErr::CassetteMiss { .. }) => return Err(e),
             Err(e) => {
                 // Use the configured provider-specific stream retry budget.
                 let max_retries = turn_context.client.get_provider().stream_max_retries();
@@ -2505,6 +2814,20 @@ async fn handle_function_call(
             .await
         }
         "update_plan" => handle_update_plan(sess, arguments, sub_id, call_id).await,
+        SPAWN_AGENT_TOOL_NAME => {
+            handle_spawn_agent(sess, turn_context, sub_id, call_id, arguments).await
+        }
+        CAPTURE_SCREENSHOT_TOOL_NAME => {
+            handle_capture_screenshot(
+                sess,
+                turn_context,
+                turn_diff_tracker,
+                sub_id,
+                call_id,
+                arguments,
+            )
+            .await
+        }
         EXEC_COMMAND_TOOL_NAME => {
             // TODO(mbolin): Sandbox check.
             let exec_params: ExecCommandParams = serde_json::from_str(&arguments).map_err(|e| {
@@ -2617,6 +2940,32 @@ async fn handle_container_exec_with_params(
     turn_diff_tracker: &mut TurnDiffTracker,
     sub_id: String,
     call_id: String,
+) -> Result<String, FunctionCallError> {
+    handle_container_exec_with_sandbox_policy(
+        tool_name,
+        params,
+        turn_context.sandbox_policy.clone(),
+        sess,
+        turn_context,
+        turn_diff_tracker,
+        sub_id,
+        call_id,
+    )
+    .await
+}
+
+/// Like [`handle_container_exec_with_params`], but runs the command under
+/// `sandbox_policy` instead of the turn's policy.
+#[allow(clippy::too_many_arguments)]
+pub(crate) async fn handle_container_exec_with_sandbox_policy(
+    tool_name: &str,
+    params: ExecParams,
+    sandbox_policy: SandboxPolicy,
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    sub_id: String,
+    call_id: String,
 ) -> Result<String, FunctionCallError> {
     let otel_event_manager = turn_context.client.get_otel_event_manager();
 
@@ -2630,7 +2979,11 @@ async fn handle_container_exec_with_params(
     }
 
     // check if this was a patch, and apply it if so
-    let apply_patch_exec = match maybe_parse_apply_patch_verified(&params.command, &params.cwd) {
+    let apply_patch_exec = match maybe_parse_apply_patch_verified(
+        &params.command,
+        &params.cwd,
+        sess.services.apply_patch_fuzzy_threshold,
+    ) {
         MaybeApplyPatchVerified::Body(changes) => {
             match apply_patch::apply_patch(sess, turn_context, &sub_id, &call_id, changes).await {
                 InternalApplyPatchInvocation::Output(item) => return item,
@@ -2643,9 +2996,13 @@ async fn handle_container_exec_with_params(
             // It looks like an invocation of `apply_patch`, but we
             // could not resolve it into a patch that would apply
             // cleanly. Return to model for resample.
-            return Err(FunctionCallError::RespondToModel(format!(
-                "error: {parse_error:#?}"
-            )));
+            let message = match parse_error {
+                // Already written for the model, including the closest
+                // match in the file, so keep its line breaks.
+                ApplyPatchError::ComputeReplacements(message) => format!("error: {message}"),
+                parse_error => format!("error: {parse_error:#?}"),
+            };
+            return Err(FunctionCallError::RespondToModel(message));
         }
         MaybeApplyPatchVerified::ShellParseError(error) => {
             trace!("Failed to parse shell command, {error:?}");
@@ -2683,10 +3040,9 @@ async fn handle_container_exec_with_params(
         None => ExecutionMode::Shell,
     };
 
-    sess.services.executor.update_environment(
-        turn_context.sandbox_policy.clone(),
-        turn_context.cwd.clone(),
-    );
+    sess.services
+        .executor
+        .update_environment(sandbox_policy, turn_context.cwd.clone());
 
     let prepared_exec = PreparedExec::new(
         exec_command_context,
@@ -2720,9 +3076,11 @@ async fn handle_container_exec_with_params(
             }
         }
         Err(ExecError::Function(err)) => Err(err),
-        Err(ExecError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output }))) => Err(
-            FunctionCallError::RespondToModel(format_exec_output(&output)),
-        ),
+        Err(ExecError::Codex(CodexErr::Sandbox(
+            SandboxErr::Timeout { output } | SandboxErr::ResourceLimitExceeded { output, .. },
+        ))) => Err(FunctionCallError::RespondToModel(format_exec_output(
+            &output,
+        ))),
         Err(ExecError::Codex(err)) => Err(FunctionCallError::RespondToModel(format!(
             "execution error: {err:?}"
         ))),
@@ -2746,6 +3104,9 @@ fn format_exec_output_str(exec_output: &ExecToolCallOutput) -> String {
             exec_output.duration.as_millis()
         ) + s;
         s = &prefixed_str;
+    } else if let Some(limit) = exec_output.resource_limit_exceeded {
+        prefixed_str = format!("command exceeded the sandbox {limit}\n") + s;
+        s = &prefixed_str;
     }
 
     let total_lines = s.lines().count();
@@ -2907,24 +3268,30 @@ fn convert_call_tool_result_to_function_call_output_payload(
     } = call_tool_result;
 
     // In terms of what to send back to the model, we prefer structured_content,
-    // if available, and fallback to content, otherwise.
-    let mut is_success = is_error != &Some(true);
-    let content = if let Some(structured_content) = structured_content
-        && structured_content != &serde_json::Value::Null
-        && let Ok(serialized_structured_content) = serde_json::to_string(&structured_content)
-    {
-        serialized_structured_content
-    } else {
-        match serde_json::to_string(&content) {
-            Ok(serialized_content) => serialized_content,
-            Err(err) => {
-                // If we could not serialize either content or structured_content to
-                // JSON, flag this as an error.
-                is_success = false;
-                err.to_string()
+    // if available, and fallback to content, otherwise. Images in content are
+    // attached to the next request separately (see `handle_mcp_tool_call`).
+    // Truncating JSON would leave the model with invalid JSON, so structured
+    // content that is too large also falls back to the text content.
+    let is_success = is_error != &Some(true);
+    let structured_content = structured_content
+        .as_ref()
+        .filter(|structured_content| *structured_content != &serde_json::Value::Null)
+        .and_then(|structured_content| serde_json::to_string(structured_content).ok());
+    let content = match structured_content {
+        Some(structured_content) if structured_content.len() <= MCP_TOOL_OUTPUT_MAX_BYTES => {
+            structured_content
+        }
+        Some(structured_content) => {
+            let text = content_to_text(content);
+            if text.is_empty() {
+                structured_content
+            } else {
+                text
             }
         }
+        None => content_to_text(content),
     };
+    let (content, _) = truncate_middle(&content, MCP_TOOL_OUTPUT_MAX_BYTES);
 
     FunctionCallOutputPayload {
         content,
@@ -3001,6 +3368,7 @@ mod tests {
     use crate::protocol::CompactedItem;
     use crate::protocol::InitialHistory;
     use crate::protocol::ResumedHistory;
+    use crate::resource_limits::ResourceLimit;
     use crate::state::TaskKind;
     use crate::tasks::SessionTask;
     use crate::tasks::SessionTaskContext;
@@ -3009,7 +3377,11 @@ mod tests {
     use codex_protocol::models::ResponseItem;
 
     use mcp_types::ContentBlock;
+    use mcp_types::EmbeddedResource;
+    use mcp_types::EmbeddedResourceResource;
+    use mcp_types::ResourceLink;
     use mcp_types::TextContent;
+    use mcp_types::TextResourceContents;
     use pretty_assertions::assert_eq;
     use serde::Deserialize;
     use serde_json::json;
@@ -3098,6 +3470,7 @@ mod tests {
             aggregated_output: StreamOutput::new(full),
             duration: StdDuration::from_secs(1),
             timed_out: false,
+            resource_limit_exceeded: None,
         };
 
         let out = format_exec_output_str(&exec);
@@ -3141,6 +3514,7 @@ mod tests {
             aggregated_output: StreamOutput::new(full.clone()),
             duration: StdDuration::from_secs(1),
             timed_out: false,
+            resource_limit_exceeded: None,
         };
 
         let out = format_exec_output_str(&exec);
@@ -3172,6 +3546,7 @@ mod tests {
             aggregated_output: StreamOutput::new("Command output".to_string()),
             duration: StdDuration::from_secs(1),
             timed_out: true,
+            resource_limit_exceeded: None,
         };
 
         let out = format_exec_output_str(&exec);
@@ -3182,6 +3557,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn includes_resource_limit_message() {
+        let exec = ExecToolCallOutput {
+            exit_code: 153,
+            stdout: StreamOutput::new(String::new()),
+            stderr: StreamOutput::new(String::new()),
+            aggregated_output: StreamOutput::new("Command output".to_string()),
+            duration: StdDuration::from_secs(1),
+            timed_out: false,
+            resource_limit_exceeded: Some(ResourceLimit::FileSize),
+        };
+
+        let out = format_exec_output_str(&exec);
+
+        assert_eq!(
+            out,
+            "command exceeded the sandbox file size limit\nCommand output"
+        );
+    }
+
     #[test]
     fn falls_back_to_content_when_structured_is_null() {
         let ctr = CallToolResult {
@@ -3192,14 +3587,83 @@ mod tests {
 
         let got = convert_call_tool_result_to_function_call_output_payload(&ctr);
         let expected = FunctionCallOutputPayload {
-            content: serde_json::to_string(&vec![text_block("hello"), text_block("world")])
-                .unwrap(),
+            content: "hello\nworld".to_string(),
             success: Some(true),
         };
 
         assert_eq!(expected, got);
     }
 
+    #[test]
+    fn inlines_embedded_text_resources_and_describes_links() {
+        let ctr = CallToolResult {
+            content: vec![
+                text_block("see notes"),
+                ContentBlock::EmbeddedResource(EmbeddedResource {
+                    annotations: None,
+                    resource: EmbeddedResourceResource::TextResourceContents(
+                        TextResourceContents {
+                            mime_type: Some("text/markdown".to_string()),
+                            text: "# Notes\nship it".to_string(),
+                            uri: "file:///notes.md".to_string(),
+                        },
+                    ),
+                    r#type: "resource".to_string(),
+                }),
+                ContentBlock::ResourceLink(ResourceLink {
+                    annotations: None,
+                    description: None,
+                    mime_type: None,
+                    name: "styles.md".to_string(),
+                    size: None,
+                    title: None,
+                    r#type: "resource_link".to_string(),
+                    uri: "file:///styles.md".to_string(),
+                }),
+            ],
+            is_error: None,
+            structured_content: None,
+        };
+
+        let got = convert_call_tool_result_to_function_call_output_payload(&ctr);
+
+        assert_eq!(
+            got.content,
+            "see notes\n[resource: file:///notes.md]\n# Notes\nship it\n\
+             [resource link: styles.md <file:///styles.md>]"
+        );
+    }
+
+    #[test]
+    fn truncates_large_tool_output() {
+        let ctr = CallToolResult {
+            content: vec![text_block(&"x".repeat(MCP_TOOL_OUTPUT_MAX_BYTES * 2))],
+            is_error: None,
+            structured_content: None,
+        };
+
+        let got = convert_call_tool_result_to_function_call_output_payload(&ctr);
+
+        // `truncate_middle` follows the marker with a newline.
+        assert!(got.content.len() <= MCP_TOOL_OUTPUT_MAX_BYTES + 1);
+        assert!(got.content.contains("tokens truncated"), "{}", got.content);
+    }
+
+    #[test]
+    fn large_structured_content_falls_back_to_text() {
+        let ctr = CallToolResult {
+            content: vec![text_block("summary of the rows")],
+            is_error: None,
+            structured_content: Some(json!({
+                "rows": vec!["x".repeat(100); MCP_TOOL_OUTPUT_MAX_BYTES / 50],
+            })),
+        };
+
+        let got = convert_call_tool_result_to_function_call_output_payload(&ctr);
+
+        assert_eq!(got.content, "summary of the rows");
+    }
+
     #[test]
     fn success_flag_reflects_is_error_true() {
         let ctr = CallToolResult {
@@ -3227,7 +3691,7 @@ mod tests {
 
         let got = convert_call_tool_result_to_function_call_output_payload(&ctr);
         let expected = FunctionCallOutputPayload {
-            content: serde_json::to_string(&vec![text_block("alpha")]).unwrap(),
+            content: "alpha".to_string(),
             success: Some(true),
         };
 
@@ -3282,6 +3746,8 @@ mod tests {
             include_web_search_request: config.tools_web_search_request,
             use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
             include_view_image_tool: config.include_view_image_tool,
+            include_screenshot_tool: config.screenshot.is_some(),
+            include_spawn_agent_tool: config.include_spawn_agent_tool,
             experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
         });
         let turn_context = TurnContext {
@@ -3304,6 +3770,11 @@ mod tests {
             rollout: Mutex::new(None),
             user_shell: shell::Shell::Unknown,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            answers_mcp_server_requests: config.answers_mcp_server_requests,
+            apply_patch_fuzzy_threshold: config.apply_patch_fuzzy_threshold,
+            model_prices: config.model_prices.clone(),
+            budget: config.budget,
+            started_at: Instant::now(),
             executor: Executor::new(ExecutorConfig::new(
                 turn_context.sandbox_policy.clone(),
                 turn_context.cwd.clone(),
@@ -3355,6 +3826,8 @@ mod tests {
             include_web_search_request: config.tools_web_search_request,
             use_streamable_shell_tool: config.use_experimental_streamable_shell_tool,
             include_view_image_tool: config.include_view_image_tool,
+            include_screenshot_tool: config.screenshot.is_some(),
+            include_spawn_agent_tool: config.include_spawn_agent_tool,
             experimental_unified_exec_tool: config.use_experimental_unified_exec_tool,
         });
         let turn_context = Arc::new(TurnContext {
@@ -3377,6 +3850,11 @@ mod tests {
             rollout: Mutex::new(None),
             user_shell: shell::Shell::Unknown,
             show_raw_agent_reasoning: config.show_raw_agent_reasoning,
+            answers_mcp_server_requests: config.answers_mcp_server_requests,
+            apply_patch_fuzzy_threshold: config.apply_patch_fuzzy_threshold,
+            model_prices: config.model_prices.clone(),
+            budget: config.budget,
+            started_at: Instant::now(),
             executor: Executor::new(ExecutorConfig::new(
                 config.sandbox_policy.clone(),
                 config.cwd.clone(),
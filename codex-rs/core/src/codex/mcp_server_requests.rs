@@ -0,0 +1,560 @@
+//! Answering `sampling/createMessage` and `elicitation/create` requests from
+//! MCP servers.
+//!
+//! Sampling runs the server's messages through the session's model once the
+//! user approves it; elicitation shows the server's form to the user. Both
+//! wait for an `Op` from the UI, so neither is possible when
+//! `approval_policy` is `never` or the frontend does not answer them
+//! (`Config::answers_mcp_server_requests`). A request the server stops
+//! waiting for is dropped.
+
+use std::sync::Arc;
+use std::sync::atomic::Ordering;
+
+use codex_protocol::models::ContentItem;
+use codex_protocol::models::ResponseItem;
+use futures::prelude::*;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::CreateMessageResult;
+use mcp_types::CreateMessageResultContent;
+use mcp_types::ElicitRequestParams;
+use mcp_types::ElicitResult;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::Role;
+use mcp_types::SamplingMessageContent;
+use mcp_types::TextContent;
+use tokio::sync::oneshot;
+use tracing::warn;
+
+use super::Session;
+use super::TurnContext;
+use crate::Prompt;
+use crate::client_common::ResponseEvent;
+use crate::mcp_connection_manager::McpServerRequest;
+use crate::mcp_connection_manager::McpServerRequestKind;
+use crate::protocol::AskForApproval;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::McpElicitationAction;
+use crate::protocol::McpElicitationRequestEvent;
+use crate::protocol::McpSamplingRequestEvent;
+use crate::protocol::ReviewDecision;
+
+/// Error code the MCP specification suggests for sampling requests the user
+/// rejected.
+const USER_REJECTED_ERROR_CODE: i64 = -1;
+const INTERNAL_ERROR_CODE: i64 = -32603;
+
+fn request_error(code: i64, message: impl Into<String>) -> JSONRPCErrorError {
+    JSONRPCErrorError {
+        code,
+        data: None,
+        message: message.into(),
+    }
+}
+
+/// Answer `request` and send the result back to the server.
+pub(super) async fn handle_mcp_server_request(
+    sess: Arc<Session>,
+    turn_context: Arc<TurnContext>,
+    request: McpServerRequest,
+) {
+    let McpServerRequest {
+        server_name,
+        kind,
+        mut respond_to,
+    } = request;
+    let (id, answer) = match kind {
+        McpServerRequestKind::CreateMessage(params) => {
+            let id = sess.next_mcp_request_id("sampling");
+            let answer = create_message(&sess, &turn_context, id.clone(), server_name, params)
+                .map_ok(serde_json::Value::from)
+                .boxed();
+            (id, answer)
+        }
+        McpServerRequestKind::Elicit(params) => {
+            let id = sess.next_mcp_request_id("elicitation");
+            let answer = elicit(&sess, &turn_context, id.clone(), server_name, params)
+                .map_ok(serde_json::Value::from)
+                .boxed();
+            (id, answer)
+        }
+    };
+    let result = tokio::select! {
+        result = answer => result,
+        () = respond_to.closed() => {
+            warn!("MCP server stopped waiting for request {id}");
+            sess.forget_mcp_request(&id).await;
+            return;
+        }
+    };
+    if respond_to.send(result).is_err() {
+        warn!("MCP server went away before its request was answered");
+    }
+}
+
+impl Session {
+    fn next_mcp_request_id(&self, kind: &str) -> String {
+        let id = self.next_internal_sub_id.fetch_add(1, Ordering::SeqCst);
+        format!("mcp-{kind}-{id}")
+    }
+
+    /// Deliver the user's decision on a pending sampling request.
+    pub(super) async fn notify_mcp_sampling_approval(&self, id: &str, decision: ReviewDecision) {
+        let entry = self.state.lock().await.pending_mcp_sampling.remove(id);
+        match entry {
+            Some(tx) => {
+                tx.send(decision).ok();
+            }
+            None => warn!("No pending MCP sampling request found for id: {id}"),
+        }
+    }
+
+    /// Drop the pending sampling or elicitation request `id`, which will not
+    /// be answered.
+    async fn forget_mcp_request(&self, id: &str) {
+        let mut state = self.state.lock().await;
+        state.pending_mcp_sampling.remove(id);
+        state.pending_mcp_elicitations.remove(id);
+    }
+
+    /// Deliver the user's answer to a pending elicitation request.
+    pub(super) async fn resolve_mcp_elicitation(&self, id: &str, result: ElicitResult) {
+        let entry = self.state.lock().await.pending_mcp_elicitations.remove(id);
+        match entry {
+            Some(tx) => {
+                tx.send(result).ok();
+            }
+            None => warn!("No pending MCP elicitation found for id: {id}"),
+        }
+    }
+}
+
+async fn create_message(
+    sess: &Session,
+    turn_context: &TurnContext,
+    id: String,
+    server_name: String,
+    params: CreateMessageRequestParams,
+) -> Result<CreateMessageResult, JSONRPCErrorError> {
+    if turn_context.approval_policy == AskForApproval::Never {
+        return Err(request_error(
+            USER_REJECTED_ERROR_CODE,
+            "sampling requires user approval, which is disabled in this session",
+        ));
+    }
+    if !sess.services.answers_mcp_server_requests {
+        return Err(request_error(
+            USER_REJECTED_ERROR_CODE,
+            "sampling requires user approval, which this client cannot ask for",
+        ));
+    }
+
+    let approved_for_session = sess
+        .state
+        .lock()
+        .await
+        .mcp_sampling_approved_servers
+        .contains(&server_name);
+    if !approved_for_session {
+        let (tx, rx) = oneshot::channel();
+        sess.state
+            .lock()
+            .await
+            .pending_mcp_sampling
+            .insert(id.clone(), tx);
+        sess.send_event(Event {
+            id: id.clone(),
+            msg: EventMsg::McpSamplingRequest(McpSamplingRequestEvent {
+                id: id.clone(),
+                server_name: server_name.clone(),
+                request: params.clone(),
+            }),
+        })
+        .await;
+        match rx.await.unwrap_or_default() {
+            ReviewDecision::Approved => {}
+            ReviewDecision::ApprovedForSession => {
+                sess.state
+                    .lock()
+                    .await
+                    .mcp_sampling_approved_servers
+                    .insert(server_name.clone());
+            }
+            ReviewDecision::Denied | ReviewDecision::Abort => {
+                return Err(request_error(
+                    USER_REJECTED_ERROR_CODE,
+                    "User rejected sampling request",
+                ));
+            }
+        }
+    }
+
+    let prompt = sampling_prompt(&params);
+    let mut stream = turn_context
+        .client
+        .clone()
+        .stream(&prompt)
+        .await
+        .map_err(|e| request_error(INTERNAL_ERROR_CODE, format!("sampling failed: {e}")))?;
+    let mut text = Vec::new();
+    let output_tokens;
+    loop {
+        let Some(event) = stream.next().await else {
+            return Err(request_error(
+                INTERNAL_ERROR_CODE,
+                "sampling failed: stream closed before response.completed",
+            ));
+        };
+        match event {
+            Ok(ResponseEvent::OutputItemDone(ResponseItem::Message { role, content, .. }))
+                if role == "assistant" =>
+            {
+                text.extend(content.into_iter().filter_map(|item| match item {
+                    ContentItem::OutputText { text } => Some(text),
+                    _ => None,
+                }));
+            }
+            Ok(ResponseEvent::RateLimits(snapshot)) => {
+                sess.update_rate_limits(&id, snapshot).await;
+            }
+            Ok(ResponseEvent::Completed { token_usage, .. }) => {
+                output_tokens = token_usage.as_ref().map(|usage| usage.output_tokens);
+                sess.update_token_usage_info(&id, turn_context, token_usage.as_ref())
+                    .await;
+                break;
+            }
+            Ok(_) => {}
+            Err(e) => {
+                return Err(request_error(
+                    INTERNAL_ERROR_CODE,
+                    format!("sampling failed: {e}"),
+                ));
+            }
+        }
+    }
+
+    let mut text = text.join("\n");
+    let stop_sequences = params.stop_sequences.as_deref().unwrap_or_default();
+    let stop_reason = if truncate_at_stop_sequence(&mut text, stop_sequences) {
+        "stopSequence"
+    } else if output_tokens
+        .zip(prompt.max_output_tokens)
+        .is_some_and(|(used, max)| used >= max)
+    {
+        "maxTokens"
+    } else {
+        "endTurn"
+    };
+
+    Ok(CreateMessageResult {
+        content: CreateMessageResultContent::TextContent(TextContent {
+            annotations: None,
+            text,
+            r#type: "text".to_string(),
+        }),
+        model: turn_context.client.get_model(),
+        role: Role::Assistant,
+        stop_reason: Some(stop_reason.to_string()),
+    })
+}
+
+/// Cut `text` before the earliest of `stop_sequences`, which the model API
+/// cannot be asked to stop at. Returns whether one was found.
+fn truncate_at_stop_sequence(text: &mut String, stop_sequences: &[String]) -> bool {
+    let end = stop_sequences
+        .iter()
+        .filter(|stop| !stop.is_empty())
+        .filter_map(|stop| text.find(stop.as_str()))
+        .min();
+    match end {
+        Some(end) => {
+            text.truncate(end);
+            true
+        }
+        None => false,
+    }
+}
+
+/// The prompt for a sampling request: the server's messages as conversation
+/// input, its system prompt, if any, in place of Codex's instructions and its
+/// `max_tokens` as the output limit. Audio is not supported by the model and
+/// is replaced with a note.
+fn sampling_prompt(params: &CreateMessageRequestParams) -> Prompt {
+    let input = params
+        .messages
+        .iter()
+        .map(|message| {
+            let (role, content) = match (&message.role, &message.content) {
+                (Role::User, SamplingMessageContent::TextContent(text)) => (
+                    "user",
+                    ContentItem::InputText {
+                        text: text.text.clone(),
+                    },
+                ),
+                (Role::Assistant, SamplingMessageContent::TextContent(text)) => (
+                    "assistant",
+                    ContentItem::OutputText {
+                        text: text.text.clone(),
+                    },
+                ),
+                (Role::User, SamplingMessageContent::ImageContent(image)) => (
+                    "user",
+                    ContentItem::InputImage {
+                        image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+                    },
+                ),
+                (Role::Assistant, SamplingMessageContent::ImageContent(image)) => (
+                    "assistant",
+                    ContentItem::OutputText {
+                        text: format!("[image ({}) omitted]", image.mime_type),
+                    },
+                ),
+                (role, SamplingMessageContent::AudioContent(audio)) => {
+                    let text = format!("[audio ({}) omitted]", audio.mime_type);
+                    match role {
+                        Role::User => ("user", ContentItem::InputText { text }),
+                        Role::Assistant => ("assistant", ContentItem::OutputText { text }),
+                    }
+                }
+            };
+            ResponseItem::Message {
+                id: None,
+                role: role.to_string(),
+                content: vec![content],
+            }
+        })
+        .collect();
+
+    Prompt {
+        input,
+        base_instructions_override: params.system_prompt.clone(),
+        max_output_tokens: u64::try_from(params.max_tokens).ok().filter(|&max| max > 0),
+        ..Default::default()
+    }
+}
+
+async fn elicit(
+    sess: &Session,
+    turn_context: &TurnContext,
+    id: String,
+    server_name: String,
+    params: ElicitRequestParams,
+) -> Result<ElicitResult, JSONRPCErrorError> {
+    if turn_context.approval_policy == AskForApproval::Never
+        || !sess.services.answers_mcp_server_requests
+    {
+        return Ok(ElicitResult {
+            action: McpElicitationAction::Decline.to_string(),
+            content: None,
+        });
+    }
+
+    let (tx, rx) = oneshot::channel();
+    sess.state
+        .lock()
+        .await
+        .pending_mcp_elicitations
+        .insert(id.clone(), tx);
+    sess.send_event(Event {
+        id: id.clone(),
+        msg: EventMsg::McpElicitationRequest(McpElicitationRequestEvent {
+            id,
+            server_name,
+            message: params.message,
+            requested_schema: params.requested_schema,
+        }),
+    })
+    .await;
+    Ok(rx.await.unwrap_or_else(|_| ElicitResult {
+        action: McpElicitationAction::Cancel.to_string(),
+        content: None,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::codex::tests::make_session_and_context;
+    use mcp_types::AudioContent;
+    use mcp_types::ElicitRequestParamsRequestedSchema;
+    use mcp_types::ImageContent;
+    use mcp_types::SamplingMessage;
+    use pretty_assertions::assert_eq;
+
+    fn text(text: &str) -> SamplingMessageContent {
+        SamplingMessageContent::TextContent(TextContent {
+            annotations: None,
+            text: text.to_string(),
+            r#type: "text".to_string(),
+        })
+    }
+
+    #[test]
+    fn sampling_prompt_maps_messages_and_system_prompt() {
+        let params = CreateMessageRequestParams {
+            include_context: None,
+            max_tokens: 200,
+            messages: vec![
+                SamplingMessage {
+                    content: text("What is in this file?"),
+                    role: Role::User,
+                },
+                SamplingMessage {
+                    content: SamplingMessageContent::ImageContent(ImageContent {
+                        annotations: None,
+                        data: "AAAA".to_string(),
+                        mime_type: "image/png".to_string(),
+                        r#type: "image".to_string(),
+                    }),
+                    role: Role::User,
+                },
+                SamplingMessage {
+                    content: text("A list of names."),
+                    role: Role::Assistant,
+                },
+                SamplingMessage {
+                    content: SamplingMessageContent::AudioContent(AudioContent {
+                        annotations: None,
+                        data: "AAAA".to_string(),
+                        mime_type: "audio/wav".to_string(),
+                        r#type: "audio".to_string(),
+                    }),
+                    role: Role::User,
+                },
+            ],
+            metadata: None,
+            model_preferences: None,
+            stop_sequences: None,
+            system_prompt: Some("Answer briefly.".to_string()),
+            temperature: None,
+        };
+
+        let prompt = sampling_prompt(&params);
+
+        let message = |role: &str, content: ContentItem| ResponseItem::Message {
+            id: None,
+            role: role.to_string(),
+            content: vec![content],
+        };
+        assert_eq!(
+            prompt.input,
+            vec![
+                message(
+                    "user",
+                    ContentItem::InputText {
+                        text: "What is in this file?".to_string(),
+                    }
+                ),
+                message(
+                    "user",
+                    ContentItem::InputImage {
+                        image_url: "data:image/png;base64,AAAA".to_string(),
+                    }
+                ),
+                message(
+                    "assistant",
+                    ContentItem::OutputText {
+                        text: "A list of names.".to_string(),
+                    }
+                ),
+                message(
+                    "user",
+                    ContentItem::InputText {
+                        text: "[audio (audio/wav) omitted]".to_string(),
+                    }
+                ),
+            ]
+        );
+        assert_eq!(
+            prompt.base_instructions_override.as_deref(),
+            Some("Answer briefly.")
+        );
+        assert_eq!(prompt.max_output_tokens, Some(200));
+        assert!(prompt.tools.is_empty());
+    }
+
+    #[test]
+    fn truncates_at_the_earliest_stop_sequence() {
+        let stops = ["END".to_string(), "\n\n".to_string(), String::new()];
+
+        let mut text = "first\n\nsecond END third".to_string();
+        assert!(truncate_at_stop_sequence(&mut text, &stops));
+        assert_eq!(text, "first");
+
+        let mut text = "no stop here".to_string();
+        assert!(!truncate_at_stop_sequence(&mut text, &stops));
+        assert_eq!(text, "no stop here");
+    }
+
+    fn elicit_request(
+        respond_to: oneshot::Sender<Result<serde_json::Value, JSONRPCErrorError>>,
+    ) -> McpServerRequest {
+        McpServerRequest {
+            server_name: "docs".to_string(),
+            kind: McpServerRequestKind::Elicit(ElicitRequestParams {
+                message: "Which page?".to_string(),
+                requested_schema: ElicitRequestParamsRequestedSchema {
+                    properties: serde_json::json!({}),
+                    required: None,
+                    r#type: "object".to_string(),
+                },
+            }),
+            respond_to,
+        }
+    }
+
+    #[tokio::test]
+    async fn declines_elicitation_when_the_frontend_cannot_answer() {
+        let (session, turn_context) = make_session_and_context();
+        assert!(!session.services.answers_mcp_server_requests);
+        let (tx, rx) = oneshot::channel();
+
+        handle_mcp_server_request(
+            Arc::new(session),
+            Arc::new(turn_context),
+            elicit_request(tx),
+        )
+        .await;
+
+        let result = rx.await.expect("answered").expect("not an error");
+        assert_eq!(result["action"], "decline");
+    }
+
+    #[tokio::test]
+    async fn forgets_requests_the_server_stopped_waiting_for() {
+        let (mut session, turn_context) = make_session_and_context();
+        session.services.answers_mcp_server_requests = true;
+        let session = Arc::new(session);
+        let (tx, rx) = oneshot::channel();
+        let handler = tokio::spawn(handle_mcp_server_request(
+            Arc::clone(&session),
+            Arc::new(turn_context),
+            elicit_request(tx),
+        ));
+        while session
+            .state
+            .lock()
+            .await
+            .pending_mcp_elicitations
+            .is_empty()
+        {
+            tokio::task::yield_now().await;
+        }
+
+        drop(rx);
+
+        tokio::time::timeout(std::time::Duration::from_secs(5), handler)
+            .await
+            .expect("handler finished")
+            .expect("handler did not panic");
+        assert!(
+            session
+                .state
+                .lock()
+                .await
+                .pending_mcp_elicitations
+                .is_empty()
+        );
+    }
+}
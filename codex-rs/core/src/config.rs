@@ -1,30 +1,48 @@
+use crate::cassette::Cassette;
+use crate::config_layers::ConfigLayer;
+use crate::config_layers::ConfigLayers;
+use crate::config_layers::load_project_config_as_toml;
+use crate::config_layers::merge_toml_values;
+use crate::config_layers::project_root_for_config;
 use crate::config_profile::ConfigProfile;
+use crate::config_profile::resolve_config_profile;
 use crate::config_types::DEFAULT_OTEL_ENVIRONMENT;
 use crate::config_types::History;
 use crate::config_types::McpServerConfig;
 use crate::config_types::McpServerTransportConfig;
+use crate::config_types::ModelDefinition;
+use crate::config_types::ModelDefinitionToml;
+use crate::config_types::ModelPrice;
 use crate::config_types::Notifications;
 use crate::config_types::OtelConfig;
 use crate::config_types::OtelConfigToml;
 use crate::config_types::OtelExporterKind;
 use crate::config_types::ReasoningSummaryFormat;
+use crate::config_types::SandboxResourceLimits;
 use crate::config_types::SandboxWorkspaceWrite;
+use crate::config_types::ScreenshotConfig;
+use crate::config_types::ScreenshotToml;
+use crate::config_types::SessionBudget;
 use crate::config_types::ShellEnvironmentPolicy;
 use crate::config_types::ShellEnvironmentPolicyToml;
+use crate::config_types::SubAgentsConfig;
+use crate::config_types::SubAgentsToml;
 use crate::config_types::Tui;
 use crate::config_types::UriBasedFileOpener;
+use crate::credential_store::CredentialStore;
 use crate::git_info::resolve_root_git_project_for_trust;
 use crate::model_family::ModelFamily;
 use crate::model_family::derive_default_model_family;
-use crate::model_family::find_family_for_model;
+use crate::model_family::find_family_for_configured_model;
 use crate::model_provider_info::ModelProviderInfo;
 use crate::model_provider_info::built_in_model_providers;
-use crate::openai_model_info::get_model_info;
+use crate::openai_model_info::get_configured_model_info;
 use crate::protocol::AskForApproval;
 use crate::protocol::SandboxPolicy;
 use anyhow::Context;
 use codex_app_server_protocol::Tools;
 use codex_app_server_protocol::UserSavedConfig;
+use codex_apply_patch::DEFAULT_FUZZY_MATCH_THRESHOLD;
 use codex_protocol::config_types::ReasoningEffort;
 use codex_protocol::config_types::ReasoningSummary;
 use codex_protocol::config_types::SandboxMode;
@@ -33,8 +51,10 @@ use dirs::home_dir;
 use serde::Deserialize;
 use std::collections::BTreeMap;
 use std::collections::HashMap;
+use std::collections::HashSet;
 use std::path::Path;
 use std::path::PathBuf;
+use std::time::Duration;
 use tempfile::NamedTempFile;
 use toml::Value as TomlValue;
 use toml_edit::Array as TomlArray;
@@ -53,6 +73,10 @@ pub(crate) const PROJECT_DOC_MAX_BYTES: usize = 32 * 1024; // 32 KiB
 
 pub(crate) const CONFIG_TOML_FILE: &str = "config.toml";
 
+/// How long `codex exec` waits for the approver to answer a request before
+/// denying it.
+const DEFAULT_APPROVER_TIMEOUT: Duration = Duration::from_secs(60);
+
 /// Application configuration loaded from disk and merged with overrides.
 #[derive(Debug, Clone, PartialEq)]
 pub struct Config {
@@ -84,6 +108,18 @@ pub struct Config {
 
     pub sandbox_policy: SandboxPolicy,
 
+    /// Resource limits for commands run under the Linux sandbox.
+    pub sandbox_resource_limits: SandboxResourceLimits,
+
+    /// Token prices by model slug, used to track the cost of a session.
+    pub model_prices: HashMap<String, ModelPrice>,
+
+    /// Models declared under `[models.<slug>]`, keyed by slug.
+    pub models: HashMap<String, ModelDefinition>,
+
+    /// Limits that end a session's task once reached.
+    pub budget: SessionBudget,
+
     pub shell_environment_policy: ShellEnvironmentPolicy,
 
     /// When `true`, `AgentReasoning` events emitted by the backend will be
@@ -123,6 +159,15 @@ pub struct Config {
     /// If unset the feature is disabled.
     pub notify: Option<Vec<String>>,
 
+    /// Command that answers approval requests in `codex exec`, as argv
+    /// tokens. Each request is written to its stdin as JSON and it replies
+    /// with `{"decision": ...}` on stdout.
+    pub approver: Option<Vec<String>>,
+
+    /// How long to wait for `approver` to answer before the approver is
+    /// killed and the request denied.
+    pub approver_timeout: Duration,
+
     /// TUI notifications preference. When set, the TUI will send OSC 9 notifications on approvals
     /// and turn completions when not focused.
     pub tui_notifications: Notifications,
@@ -135,6 +180,12 @@ pub struct Config {
     /// Definition for MCP servers that Codex can reach out to for tool calls.
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// MCP servers whose entry is not taken from `config.toml` as is: those
+    /// the active profile excludes or defines, and those set by a trusted
+    /// project config or a `-c` override. Changes to `config.toml` made
+    /// during a session leave these servers alone.
+    pub overridden_mcp_servers: HashSet<String>,
+
     /// Combined provider map (defaults merged with user-defined overrides).
     pub model_providers: HashMap<String, ModelProviderInfo>,
 
@@ -145,6 +196,9 @@ pub struct Config {
     /// overridden by the `CODEX_HOME` environment variable).
     pub codex_home: PathBuf,
 
+    /// Where the login and MCP server bearer tokens are kept.
+    pub credential_store: CredentialStore,
+
     /// Settings that govern if and what will be written to `~/.codex/history.jsonl`.
     pub history: History,
 
@@ -182,6 +236,10 @@ pub struct Config {
     /// model family's default preference.
     pub include_apply_patch_tool: bool,
 
+    /// Minimum similarity between a patch hunk and a region of the file for
+    /// `apply_patch` to apply the hunk there when it does not match exactly.
+    pub apply_patch_fuzzy_threshold: f64,
+
     pub tools_web_search_request: bool,
 
     pub use_experimental_streamable_shell_tool: bool,
@@ -196,9 +254,35 @@ pub struct Config {
     /// Include the `view_image` tool that lets the agent attach a local image path to context.
     pub include_view_image_tool: bool,
 
+    /// Include the `spawn_agent` tool that delegates tasks to child sessions.
+    pub include_spawn_agent_tool: bool,
+
+    /// Limits applied to sub-agents started through `spawn_agent`.
+    pub sub_agents: SubAgentsConfig,
+
+    /// Renderer for the `capture_screenshot` tool. The tool is only offered
+    /// when this is set.
+    pub screenshot: Option<ScreenshotConfig>,
+
+    /// Run each session in a dedicated git worktree on its own branch instead
+    /// of editing the user's checkout directly.
+    pub worktree: bool,
+
+    /// Record model traffic to, or replay it from, a cassette directory
+    /// (`--record-cassette` / `--replay-cassette`). Not read from config.toml.
+    pub cassette: Option<Cassette>,
+
+    /// Whether the frontend shows sampling and elicitation requests from MCP
+    /// servers to the user and answers them. When unset they are rejected
+    /// without waiting. Not read from config.toml.
+    pub answers_mcp_server_requests: bool,
+
     /// The active profile name used to derive this `Config` (if any).
     pub active_profile: Option<String>,
 
+    /// Which layer (project, user or CLI) supplied each configured value.
+    pub config_layers: ConfigLayers,
+
     /// When true, disables burst-paste detection for typed input entirely.
     /// All characters are inserted as they are received, and no buffering
     /// or placeholder replacement will occur for fast keypress bursts.
@@ -213,7 +297,8 @@ impl Config {
     /// **in between** the values parsed from `config.toml` and the
     /// strongly-typed overrides specified via [`ConfigOverrides`].
     ///
-    /// The precedence order is therefore: `config.toml` < `-c` overrides <
+    /// The precedence order is therefore: trusted project
+    /// `.codex/config.toml` < `config.toml` < `-c` overrides <
     /// `ConfigOverrides`.
     pub fn load_with_cli_overrides(
         cli_overrides: Vec<(String, TomlValue)>,
@@ -224,24 +309,85 @@ impl Config {
         // `Config` instance.
         let codex_home = find_codex_home()?;
 
-        // Step 1: parse `config.toml` into a generic JSON value.
-        let mut root_value = load_config_as_toml(&codex_home)?;
+        // Steps 1-4: layer the project, user and `-c` TOML values.
+        let (cfg, mut layers) =
+            load_layered_config_toml(&codex_home, cli_overrides, overrides.cwd.clone())?;
 
-        // Step 2: apply the `-c` overrides.
-        for (path, value) in cli_overrides.into_iter() {
-            apply_toml_override(&mut root_value, &path, value);
+        // Strongly-typed overrides come from CLI flags as well.
+        if overrides.model.is_some() {
+            layers.record_key("model", ConfigLayer::Cli);
+        }
+        if overrides.model_provider.is_some() {
+            layers.record_key("model_provider", ConfigLayer::Cli);
+        }
+        if overrides.approval_policy.is_some() {
+            layers.record_key("approval_policy", ConfigLayer::Cli);
+        }
+        if overrides.sandbox_mode.is_some() {
+            layers.record_key("sandbox_mode", ConfigLayer::Cli);
         }
 
-        // Step 3: deserialize into `ConfigToml` so that Serde can enforce the
-        // correct types.
-        let cfg: ConfigToml = root_value.try_into().map_err(|e| {
-            tracing::error!("Failed to deserialize overridden config: {e}");
-            std::io::Error::new(std::io::ErrorKind::InvalidData, e)
-        })?;
+        // Step 5: merge with the strongly-typed overrides.
+        let mut config = Self::load_from_base_config_with_overrides(cfg, overrides, codex_home)?;
+        for layer in [ConfigLayer::Project, ConfigLayer::Cli] {
+            config.overridden_mcp_servers.extend(
+                layers
+                    .settings_from(layer)
+                    .iter()
+                    .filter_map(|setting| setting.strip_prefix("mcp_servers."))
+                    .map(str::to_string),
+            );
+        }
+        config.config_layers = layers;
+        Ok(config)
+    }
+}
 
-        // Step 4: merge with the strongly-typed overrides.
-        Self::load_from_base_config_with_overrides(cfg, overrides, codex_home)
+/// Read the user config, apply the `-c` overrides and, when the project at
+/// `cwd` is trusted, merge its `.codex/config.toml` beneath both. Also returns
+/// the layer each value came from.
+fn load_layered_config_toml(
+    codex_home: &Path,
+    cli_overrides: Vec<(String, TomlValue)>,
+    cwd: Option<PathBuf>,
+) -> std::io::Result<(ConfigToml, ConfigLayers)> {
+    let mut layers = ConfigLayers::default();
+
+    // Step 1: parse `config.toml` into a generic JSON value.
+    let mut root_value = load_config_as_toml(codex_home)?;
+    layers.record(ConfigLayer::User, &root_value);
+
+    // Step 2: apply the `-c` overrides.
+    let mut cli_value = TomlValue::Table(Default::default());
+    for (path, value) in cli_overrides.into_iter() {
+        apply_toml_override(&mut cli_value, &path, value.clone());
+        apply_toml_override(&mut root_value, &path, value);
     }
+    layers.record(ConfigLayer::Cli, &cli_value);
+
+    // Step 3: deserialize into `ConfigToml` so that Serde can enforce the
+    // correct types.
+    let mut cfg = deserialize_config_toml(root_value.clone())?;
+
+    // Step 4: once the project is trusted, slide its `.codex/config.toml`
+    // in beneath the user config.
+    let resolved_cwd = resolve_cwd(cwd)?;
+    if cfg.is_cwd_trusted(&resolved_cwd) {
+        let project_root = project_root_for_config(&resolved_cwd);
+        if let Some((path, mut project_value)) = load_project_config_as_toml(&project_root)? {
+            tracing::info!("Loading project config from {}", path.display());
+            let mut project_layers = ConfigLayers::default();
+            project_layers.record(ConfigLayer::Project, &project_value);
+            project_layers.merge_from(layers);
+            layers = project_layers;
+            layers.project_config = Some(path);
+
+            merge_toml_values(&mut project_value, root_value);
+            cfg = deserialize_config_toml(project_value)?;
+        }
+    }
+
+    Ok((cfg, layers))
 }
 
 pub fn load_config_as_toml_with_cli_overrides(
@@ -254,12 +400,14 @@ pub fn load_config_as_toml_with_cli_overrides(
         apply_toml_override(&mut root_value, &path, value);
     }
 
-    let cfg: ConfigToml = root_value.try_into().map_err(|e| {
+    deserialize_config_toml(root_value)
+}
+
+fn deserialize_config_toml(root_value: TomlValue) -> std::io::Result<ConfigToml> {
+    root_value.try_into().map_err(|e| {
         tracing::error!("Failed to deserialize overridden config: {e}");
         std::io::Error::new(std::io::ErrorKind::InvalidData, e)
-    })?;
-
-    Ok(cfg)
+    })
 }
 
 /// Read `CODEX_HOME/config.toml` and return it as a generic TOML value. Returns
@@ -299,6 +447,20 @@ pub fn load_global_mcp_servers(
         .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
 }
 
+/// The `credential_store` configured in `CODEX_HOME/config.toml`, for callers
+/// that need credentials without loading a full [`Config`].
+pub fn load_global_credential_store(codex_home: &Path) -> std::io::Result<CredentialStore> {
+    let root_value = load_config_as_toml(codex_home)?;
+    let Some(store_value) = root_value.get("credential_store") else {
+        return Ok(CredentialStore::default());
+    };
+
+    store_value
+        .clone()
+        .try_into()
+        .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+}
+
 pub fn write_global_mcp_servers(
     codex_home: &Path,
     servers: &BTreeMap<String, McpServerConfig>,
@@ -347,11 +509,18 @@ pub fn write_global_mcp_servers(
                         entry["env"] = TomlItem::Table(env_table);
                     }
                 }
-                McpServerTransportConfig::StreamableHttp { url, bearer_token } => {
+                McpServerTransportConfig::StreamableHttp {
+                    url,
+                    bearer_token,
+                    bearer_token_env_var,
+                } => {
                     entry["url"] = toml_edit::value(url.clone());
                     if let Some(token) = bearer_token {
                         entry["bearer_token"] = toml_edit::value(token.clone());
                     }
+                    if let Some(env_var) = bearer_token_env_var {
+                        entry["bearer_token_env_var"] = toml_edit::value(env_var.clone());
+                    }
                 }
             }
 
@@ -577,6 +746,25 @@ pub async fn persist_model_selection(
     Ok(())
 }
 
+/// The session's working directory: `cwd` resolved against the current
+/// directory, or the current directory itself when unset.
+fn resolve_cwd(cwd: Option<PathBuf>) -> std::io::Result<PathBuf> {
+    match cwd {
+        None => {
+            tracing::info!("cwd not set, using current dir");
+            std::env::current_dir()
+        }
+        Some(p) if p.is_absolute() => Ok(p),
+        Some(p) => {
+            // Resolve relative path against the current working directory.
+            tracing::info!("cwd is relative, resolving against current dir");
+            let mut current = std::env::current_dir()?;
+            current.push(p);
+            Ok(current)
+        }
+    }
+}
+
 /// Apply a single dotted-path override onto a TOML value.
 fn apply_toml_override(root: &mut TomlValue, path: &str, value: TomlValue) {
     use toml::value::Table;
@@ -652,10 +840,37 @@ pub struct ConfigToml {
     /// Sandbox configuration to apply if `sandbox` is `WorkspaceWrite`.
     pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
 
+    /// Resource limits for commands run under the Linux sandbox.
+    pub sandbox_resource_limits: Option<SandboxResourceLimits>,
+
+    /// Token prices by model slug, in US dollars per million tokens.
+    #[serde(default)]
+    pub model_prices: HashMap<String, ModelPrice>,
+
+    /// Models Codex has no built-in knowledge of, or overrides for built-in
+    /// ones, keyed by slug.
+    #[serde(default)]
+    pub models: HashMap<String, ModelDefinitionToml>,
+
+    /// Limits that end a session's task once reached.
+    pub budget: Option<SessionBudget>,
+
+    /// Minimum similarity, from 0.0 to 1.0, for `apply_patch` to apply a hunk
+    /// whose lines do not match the file exactly. 1.0 disables fuzzy matching.
+    pub apply_patch_fuzzy_threshold: Option<f64>,
+
     /// Optional external command to spawn for end-user notifications.
     #[serde(default)]
     pub notify: Option<Vec<String>>,
 
+    /// External command that answers approval requests in `codex exec`.
+    #[serde(default)]
+    pub approver: Option<Vec<String>>,
+
+    /// Seconds to wait for `approver` before denying the request. Defaults
+    /// to 60.
+    pub approver_timeout_sec: Option<u64>,
+
     /// System instructions.
     pub instructions: Option<String>,
 
@@ -663,6 +878,10 @@ pub struct ConfigToml {
     #[serde(default)]
     pub mcp_servers: HashMap<String, McpServerConfig>,
 
+    /// Where the login and MCP server bearer tokens are kept. Defaults to
+    /// plaintext files in `CODEX_HOME`.
+    pub credential_store: Option<CredentialStore>,
+
     /// User-defined provider entries that extend/override the built-in list.
     #[serde(default)]
     pub model_providers: HashMap<String, ModelProviderInfo>,
@@ -729,6 +948,15 @@ pub struct ConfigToml {
 
     /// OTEL configuration.
     pub otel: Option<crate::config_types::OtelConfigToml>,
+
+    /// Limits for sub-agents started through the `spawn_agent` tool.
+    pub sub_agents: Option<SubAgentsToml>,
+
+    /// Headless renderer used by the `capture_screenshot` tool.
+    pub screenshot: Option<ScreenshotToml>,
+
+    /// Run each session in a dedicated git worktree (same as `--worktree`).
+    pub worktree: Option<bool>,
 }
 
 impl From<ConfigToml> for UserSavedConfig {
@@ -767,6 +995,10 @@ pub struct ToolsToml {
     /// Enable the `view_image` tool that lets the agent attach local images.
     #[serde(default)]
     pub view_image: Option<bool>,
+
+    /// Enable the `spawn_agent` tool that delegates tasks to child sessions.
+    #[serde(default)]
+    pub spawn_agent: Option<bool>,
 }
 
 impl From<ToolsToml> for Tools {
@@ -837,19 +1069,42 @@ impl ConfigToml {
         let profile = override_profile.or_else(|| self.profile.clone());
 
         match profile {
-            Some(key) => {
-                if let Some(profile) = self.profiles.get(key.as_str()) {
-                    return Ok(profile.clone());
-                }
-
-                Err(std::io::Error::new(
-                    std::io::ErrorKind::NotFound,
-                    format!("config profile `{key}` not found"),
-                ))
-            }
+            Some(key) => resolve_config_profile(&self.profiles, &key),
             None => Ok(ConfigProfile::default()),
         }
     }
+
+    /// Apply the parts of `profile` that replace or extend whole sections of
+    /// the config rather than single values.
+    fn apply_profile_sections(&mut self, profile: &ConfigProfile) {
+        if let Some(sandbox_mode) = profile.sandbox_mode {
+            self.sandbox_mode = Some(sandbox_mode);
+        }
+        if let Some(sandbox_workspace_write) = &profile.sandbox_workspace_write {
+            self.sandbox_workspace_write = Some(sandbox_workspace_write.clone());
+        }
+        for name in &profile.exclude_mcp_servers {
+            self.mcp_servers.remove(name);
+        }
+        self.mcp_servers.extend(profile.mcp_servers.clone());
+        if let Some(tools) = &profile.tools {
+            let base = self.tools.take().unwrap_or_default();
+            self.tools = Some(ToolsToml {
+                web_search: tools.web_search.or(base.web_search),
+                view_image: tools.view_image.or(base.view_image),
+                spawn_agent: tools.spawn_agent.or(base.spawn_agent),
+            });
+        }
+        if let Some(shell_environment_policy) = &profile.shell_environment_policy {
+            self.shell_environment_policy = shell_environment_policy.clone();
+        }
+        if let Some(notify) = &profile.notify {
+            self.notify = Some(notify.clone());
+        }
+        if let Some(project_doc_max_bytes) = profile.project_doc_max_bytes {
+            self.project_doc_max_bytes = Some(project_doc_max_bytes);
+        }
+    }
 }
 
 /// Optional overrides for user configuration (e.g., from CLI flags).
@@ -875,7 +1130,7 @@ impl Config {
     /// Meant to be used exclusively for tests: `load_with_overrides()` should
     /// be used in all other cases.
     pub fn load_from_base_config_with_overrides(
-        cfg: ConfigToml,
+        mut cfg: ConfigToml,
         overrides: ConfigOverrides,
         codex_home: PathBuf,
     ) -> std::io::Result<Self> {
@@ -904,18 +1159,16 @@ impl Config {
             .or(cfg.profile.as_ref())
             .cloned();
         let config_profile = match active_profile_name.as_ref() {
-            Some(key) => cfg
-                .profiles
-                .get(key)
-                .ok_or_else(|| {
-                    std::io::Error::new(
-                        std::io::ErrorKind::NotFound,
-                        format!("config profile `{key}` not found"),
-                    )
-                })?
-                .clone(),
+            Some(key) => resolve_config_profile(&cfg.profiles, key)?,
             None => ConfigProfile::default(),
         };
+        cfg.apply_profile_sections(&config_profile);
+        let overridden_mcp_servers = config_profile
+            .exclude_mcp_servers
+            .iter()
+            .chain(config_profile.mcp_servers.keys())
+            .cloned()
+            .collect();
 
         let sandbox_policy = cfg.derive_sandbox_policy(sandbox_mode);
 
@@ -941,27 +1194,22 @@ impl Config {
 
         let shell_environment_policy = cfg.shell_environment_policy.into();
 
-        let resolved_cwd = {
-            use std::env;
-
-            match cwd {
-                None => {
-                    tracing::info!("cwd not set, using current dir");
-                    env::current_dir()?
-                }
-                Some(p) if p.is_absolute() => p,
-                Some(p) => {
-                    // Resolve relative path against the current working directory.
-                    tracing::info!("cwd is relative, resolving against current dir");
-                    let mut current = env::current_dir()?;
-                    current.push(p);
-                    current
-                }
-            }
-        };
+        let resolved_cwd = resolve_cwd(cwd)?;
 
         let history = cfg.history.unwrap_or_default();
 
+        let apply_patch_fuzzy_threshold = cfg
+            .apply_patch_fuzzy_threshold
+            .unwrap_or(DEFAULT_FUZZY_MATCH_THRESHOLD);
+        if !(0.0..=1.0).contains(&apply_patch_fuzzy_threshold) {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!(
+                    "apply_patch_fuzzy_threshold must be between 0.0 and 1.0, got {apply_patch_fuzzy_threshold}"
+                ),
+            ));
+        }
+
         let tools_web_search_request = override_tools_web_search_request
             .or(cfg.tools.as_ref().and_then(|t| t.web_search))
             .unwrap_or(false);
@@ -970,13 +1218,28 @@ impl Config {
             .or(cfg.tools.as_ref().and_then(|t| t.view_image))
             .unwrap_or(true);
 
+        let include_spawn_agent_tool = cfg
+            .tools
+            .as_ref()
+            .and_then(|t| t.spawn_agent)
+            .unwrap_or(false);
+
         let model = model
             .or(config_profile.model)
             .or(cfg.model)
             .unwrap_or_else(default_model);
 
-        let mut model_family =
-            find_family_for_model(&model).unwrap_or_else(|| derive_default_model_family(&model));
+        let models = cfg
+            .models
+            .into_iter()
+            .map(|(slug, definition)| {
+                Self::load_model_definition(definition, &resolved_cwd)
+                    .map(|definition| (slug, definition))
+            })
+            .collect::<std::io::Result<HashMap<_, _>>>()?;
+
+        let mut model_family = find_family_for_configured_model(&model, &models)
+            .unwrap_or_else(|| derive_default_model_family(&model));
 
         if let Some(supports_reasoning_summaries) = cfg.model_supports_reasoning_summaries {
             model_family.supports_reasoning_summaries = supports_reasoning_summaries;
@@ -985,14 +1248,14 @@ impl Config {
             model_family.reasoning_summary_format = model_reasoning_summary_format;
         }
 
-        let openai_model_info = get_model_info(&model_family);
+        let openai_model_info = get_configured_model_info(&model_family, &models);
         let model_context_window = cfg
             .model_context_window
             .or_else(|| openai_model_info.as_ref().map(|info| info.context_window));
         let model_max_output_tokens = cfg.model_max_output_tokens.or_else(|| {
             openai_model_info
                 .as_ref()
-                .map(|info| info.max_output_tokens)
+                .and_then(|info| info.max_output_tokens)
         });
         let model_auto_compact_token_limit = cfg.model_auto_compact_token_limit.or_else(|| {
             openai_model_info
@@ -1031,14 +1294,25 @@ impl Config {
                 .or(cfg.approval_policy)
                 .unwrap_or_else(AskForApproval::default),
             sandbox_policy,
+            sandbox_resource_limits: cfg.sandbox_resource_limits.unwrap_or_default(),
+            model_prices: cfg.model_prices,
+            models,
+            budget: cfg.budget.unwrap_or_default(),
             shell_environment_policy,
             notify: cfg.notify,
+            approver: cfg.approver,
+            approver_timeout: cfg
+                .approver_timeout_sec
+                .map(Duration::from_secs)
+                .unwrap_or(DEFAULT_APPROVER_TIMEOUT),
             user_instructions,
             base_instructions,
             mcp_servers: cfg.mcp_servers,
+            overridden_mcp_servers,
             model_providers,
             project_doc_max_bytes: cfg.project_doc_max_bytes.unwrap_or(PROJECT_DOC_MAX_BYTES),
             codex_home,
+            credential_store: cfg.credential_store.unwrap_or_default(),
             history,
             file_opener: cfg.file_opener.unwrap_or(UriBasedFileOpener::VsCode),
             codex_linux_sandbox_exe,
@@ -1062,6 +1336,7 @@ impl Config {
                 .unwrap_or("https://chatgpt.com/backend-api/".to_string()),
             include_plan_tool: include_plan_tool.unwrap_or(false),
             include_apply_patch_tool: include_apply_patch_tool.unwrap_or(false),
+            apply_patch_fuzzy_threshold,
             tools_web_search_request,
             use_experimental_streamable_shell_tool: cfg
                 .experimental_use_exec_command_tool
@@ -1071,7 +1346,14 @@ impl Config {
                 .unwrap_or(false),
             use_experimental_use_rmcp_client: cfg.experimental_use_rmcp_client.unwrap_or(false),
             include_view_image_tool,
+            include_spawn_agent_tool,
+            sub_agents: cfg.sub_agents.unwrap_or_default().into(),
+            screenshot: cfg.screenshot.map(Into::into),
+            worktree: cfg.worktree.unwrap_or(false),
+            cassette: None,
+            answers_mcp_server_requests: false,
             active_profile: active_profile_name,
+            config_layers: ConfigLayers::default(),
             disable_paste_burst: cfg.disable_paste_burst.unwrap_or(false),
             tui_notifications: cfg
                 .tui
@@ -1095,6 +1377,42 @@ impl Config {
         Ok(config)
     }
 
+    /// Point the session at a worktree of the repository rooted at
+    /// `repo_root`: `cwd` keeps its offset inside the repository and writable
+    /// roots inside the original checkout are mapped into the worktree.
+    pub fn relocate_to_worktree(&mut self, repo_root: &Path, worktree_root: &Path) {
+        let relocate = |path: &Path| -> Option<PathBuf> {
+            path.strip_prefix(repo_root)
+                .ok()
+                .map(|relative| worktree_root.join(relative))
+        };
+        self.cwd = relocate(&self.cwd)
+            .or_else(|| {
+                let canonical = self.cwd.canonicalize().ok()?;
+                relocate(&canonical)
+            })
+            .unwrap_or_else(|| worktree_root.to_path_buf());
+        if let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = &mut self.sandbox_policy {
+            for root in writable_roots.iter_mut() {
+                if let Some(relocated) = relocate(root) {
+                    *root = relocated;
+                }
+            }
+        }
+    }
+
+    /// Switch the session to `model`, re-deriving its model family and, when
+    /// known, its context window and output limit.
+    pub fn set_model(&mut self, model: &str) {
+        self.model = model.to_string();
+        self.model_family = find_family_for_configured_model(model, &self.models)
+            .unwrap_or_else(|| derive_default_model_family(model));
+        if let Some(info) = get_configured_model_info(&self.model_family, &self.models) {
+            self.model_context_window = Some(info.context_window);
+            self.model_max_output_tokens = info.max_output_tokens;
+        }
+    }
+
     fn load_instructions(codex_dir: Option<&Path>) -> Option<String> {
         let mut p = match codex_dir {
             Some(p) => p.to_path_buf(),
@@ -1112,6 +1430,28 @@ impl Config {
         })
     }
 
+    fn load_model_definition(
+        definition: ModelDefinitionToml,
+        cwd: &Path,
+    ) -> std::io::Result<ModelDefinition> {
+        let base_instructions =
+            Self::get_base_instructions(definition.base_instructions_file.as_ref(), cwd)?;
+        Ok(ModelDefinition {
+            display_name: definition.display_name,
+            description: definition.description,
+            context_window: definition.context_window,
+            max_output_tokens: definition.max_output_tokens,
+            auto_compact_token_limit: definition.auto_compact_token_limit,
+            supports_reasoning_summaries: definition.supports_reasoning_summaries,
+            reasoning_summary_format: definition.reasoning_summary_format,
+            apply_patch_tool_type: definition.apply_patch_tool_type,
+            needs_special_apply_patch_instructions: definition
+                .needs_special_apply_patch_instructions,
+            uses_local_shell_tool: definition.uses_local_shell_tool,
+            base_instructions,
+        })
+    }
+
     fn get_base_instructions(
         path: Option<&PathBuf>,
         cwd: &Path,
@@ -1198,10 +1538,18 @@ pub fn log_dir(cfg: &Config) -> std::io::Result<PathBuf> {
     Ok(p)
 }
 
+/// Returns the path to the folder where session worktrees are created. Does
+/// not verify that the directory exists.
+pub fn worktrees_dir(cfg: &Config) -> PathBuf {
+    cfg.codex_home.join("worktrees")
+}
+
 #[cfg(test)]
 mod tests {
     use crate::config_types::HistoryPersistence;
     use crate::config_types::Notifications;
+    use crate::model_family::find_family_for_model;
+    use crate::tool_apply_patch::ApplyPatchToolType;
 
     use super::*;
     use pretty_assertions::assert_eq;
@@ -1310,6 +1658,152 @@ exclude_slash_tmp = true
         );
     }
 
+    #[test]
+    fn apply_patch_fuzzy_threshold_must_be_a_ratio() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let load = |threshold: f64| {
+            Config::load_from_base_config_with_overrides(
+                ConfigToml {
+                    apply_patch_fuzzy_threshold: Some(threshold),
+                    ..Default::default()
+                },
+                ConfigOverrides::default(),
+                codex_home.path().to_path_buf(),
+            )
+        };
+
+        assert_eq!(load(0.75)?.apply_patch_fuzzy_threshold, 0.75);
+        let err = load(75.0).expect_err("threshold above 1.0 should be rejected");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidInput);
+        Ok(())
+    }
+
+    #[test]
+    fn configured_model_sets_family_and_limits() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        std::fs::write(
+            codex_home.path().join("local-prompt.md"),
+            "You are a local model.\n",
+        )?;
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+model = "my-local-model"
+
+[models.my-local-model]
+display_name = "My local model"
+context_window = 32000
+max_output_tokens = 4096
+supports_reasoning_summaries = true
+apply_patch_tool_type = "function"
+base_instructions_file = "local-prompt.md"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(codex_home.path().to_path_buf()),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(config.model_context_window, Some(32_000));
+        assert_eq!(config.model_max_output_tokens, Some(4_096));
+        assert!(config.model_family.supports_reasoning_summaries);
+        assert_eq!(
+            config.model_family.apply_patch_tool_type,
+            Some(ApplyPatchToolType::Function)
+        );
+        assert_eq!(
+            config.model_family.base_instructions,
+            "You are a local model."
+        );
+        assert_eq!(
+            config.models["my-local-model"].display_name.as_deref(),
+            Some("My local model")
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn model_prices_and_budget_are_loaded() -> std::io::Result<()> {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[model_prices.gpt-5]
+input_per_million = 1.25
+cached_input_per_million = 0.125
+output_per_million = 10.0
+
+[budget]
+max_cost_usd = 5.0
+max_turns = 50
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let codex_home = TempDir::new()?;
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(
+            config.model_prices,
+            HashMap::from([(
+                "gpt-5".to_string(),
+                ModelPrice {
+                    input_per_million: 1.25,
+                    cached_input_per_million: Some(0.125),
+                    output_per_million: 10.0,
+                }
+            )])
+        );
+        assert_eq!(
+            config.budget,
+            SessionBudget {
+                max_tokens_per_session: None,
+                max_cost_usd: Some(5.0),
+                max_turns: Some(50),
+                max_duration_secs: None,
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn sandbox_resource_limits_are_loaded() -> std::io::Result<()> {
+        let cfg = toml::from_str::<ConfigToml>(
+            r#"
+[sandbox_resource_limits]
+max_rss_mb = 2048
+max_cpu_seconds = 600
+max_pids = 256
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+        let codex_home = TempDir::new()?;
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+
+        assert_eq!(
+            config.sandbox_resource_limits,
+            SandboxResourceLimits {
+                max_rss_mb: Some(2048),
+                max_cpu_seconds: Some(600),
+                max_pids: Some(256),
+                max_file_size_mb: None,
+            }
+        );
+        Ok(())
+    }
+
     #[test]
     fn load_global_mcp_servers_returns_empty_if_missing() -> anyhow::Result<()> {
         let codex_home = TempDir::new()?;
@@ -1448,6 +1942,7 @@ ZIG_VAR = "3"
                 transport: McpServerTransportConfig::StreamableHttp {
                     url: "https://example.com/mcp".to_string(),
                     bearer_token: Some("secret-token".to_string()),
+                    bearer_token_env_var: None,
                 },
                 startup_timeout_sec: Some(Duration::from_secs(2)),
                 tool_timeout_sec: None,
@@ -1470,7 +1965,9 @@ startup_timeout_sec = 2.0
         let loaded = load_global_mcp_servers(codex_home.path())?;
         let docs = loaded.get("docs").expect("docs entry");
         match &docs.transport {
-            McpServerTransportConfig::StreamableHttp { url, bearer_token } => {
+            McpServerTransportConfig::StreamableHttp {
+                url, bearer_token, ..
+            } => {
                 assert_eq!(url, "https://example.com/mcp");
                 assert_eq!(bearer_token.as_deref(), Some("secret-token"));
             }
@@ -1484,6 +1981,7 @@ startup_timeout_sec = 2.0
                 transport: McpServerTransportConfig::StreamableHttp {
                     url: "https://example.com/mcp".to_string(),
                     bearer_token: None,
+                    bearer_token_env_var: Some("DOCS_MCP_TOKEN".to_string()),
                 },
                 startup_timeout_sec: None,
                 tool_timeout_sec: None,
@@ -1496,15 +1994,21 @@ startup_timeout_sec = 2.0
             serialized,
             r#"[mcp_servers.docs]
 url = "https://example.com/mcp"
+bearer_token_env_var = "DOCS_MCP_TOKEN"
 "#
         );
 
         let loaded = load_global_mcp_servers(codex_home.path())?;
         let docs = loaded.get("docs").expect("docs entry");
         match &docs.transport {
-            McpServerTransportConfig::StreamableHttp { url, bearer_token } => {
+            McpServerTransportConfig::StreamableHttp {
+                url,
+                bearer_token,
+                bearer_token_env_var,
+            } => {
                 assert_eq!(url, "https://example.com/mcp");
                 assert!(bearer_token.is_none());
+                assert_eq!(bearer_token_env_var.as_deref(), Some("DOCS_MCP_TOKEN"));
             }
             other => panic!("unexpected transport {other:?}"),
         }
@@ -1804,14 +2308,22 @@ model_verbosity = "high"
                 model_provider: fixture.openai_provider.clone(),
                 approval_policy: AskForApproval::Never,
                 sandbox_policy: SandboxPolicy::new_read_only_policy(),
+                sandbox_resource_limits: SandboxResourceLimits::default(),
+                model_prices: HashMap::new(),
+                models: HashMap::new(),
+                budget: SessionBudget::default(),
                 shell_environment_policy: ShellEnvironmentPolicy::default(),
                 user_instructions: None,
                 notify: None,
+                approver: None,
+                approver_timeout: DEFAULT_APPROVER_TIMEOUT,
                 cwd: fixture.cwd(),
                 mcp_servers: HashMap::new(),
+                overridden_mcp_servers: HashSet::new(),
                 model_providers: fixture.model_provider_map.clone(),
                 project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
                 codex_home: fixture.codex_home(),
+                credential_store: CredentialStore::File,
                 history: History::default(),
                 file_opener: UriBasedFileOpener::VsCode,
                 codex_linux_sandbox_exe: None,
@@ -1824,12 +2336,20 @@ model_verbosity = "high"
                 base_instructions: None,
                 include_plan_tool: false,
                 include_apply_patch_tool: false,
+                apply_patch_fuzzy_threshold: DEFAULT_FUZZY_MATCH_THRESHOLD,
                 tools_web_search_request: false,
                 use_experimental_streamable_shell_tool: false,
                 use_experimental_unified_exec_tool: false,
                 use_experimental_use_rmcp_client: false,
                 include_view_image_tool: true,
+                include_spawn_agent_tool: false,
+                sub_agents: SubAgentsConfig::default(),
+                screenshot: None,
+                worktree: false,
+                cassette: None,
+                answers_mcp_server_requests: false,
                 active_profile: Some("o3".to_string()),
+                config_layers: ConfigLayers::default(),
                 disable_paste_burst: false,
                 tui_notifications: Default::default(),
                 otel: OtelConfig::default(),
@@ -1864,14 +2384,22 @@ model_verbosity = "high"
             model_provider: fixture.openai_chat_completions_provider.clone(),
             approval_policy: AskForApproval::UnlessTrusted,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_resource_limits: SandboxResourceLimits::default(),
+            model_prices: HashMap::new(),
+            models: HashMap::new(),
+            budget: SessionBudget::default(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            approver: None,
+            approver_timeout: DEFAULT_APPROVER_TIMEOUT,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            overridden_mcp_servers: HashSet::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
+            credential_store: CredentialStore::File,
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
@@ -1884,12 +2412,20 @@ model_verbosity = "high"
             base_instructions: None,
             include_plan_tool: false,
             include_apply_patch_tool: false,
+            apply_patch_fuzzy_threshold: DEFAULT_FUZZY_MATCH_THRESHOLD,
             tools_web_search_request: false,
             use_experimental_streamable_shell_tool: false,
             use_experimental_unified_exec_tool: false,
             use_experimental_use_rmcp_client: false,
             include_view_image_tool: true,
+            include_spawn_agent_tool: false,
+            sub_agents: SubAgentsConfig::default(),
+            screenshot: None,
+            worktree: false,
+            cassette: None,
+            answers_mcp_server_requests: false,
             active_profile: Some("gpt3".to_string()),
+            config_layers: ConfigLayers::default(),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
             otel: OtelConfig::default(),
@@ -1939,14 +2475,22 @@ model_verbosity = "high"
             model_provider: fixture.openai_provider.clone(),
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_resource_limits: SandboxResourceLimits::default(),
+            model_prices: HashMap::new(),
+            models: HashMap::new(),
+            budget: SessionBudget::default(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            approver: None,
+            approver_timeout: DEFAULT_APPROVER_TIMEOUT,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            overridden_mcp_servers: HashSet::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
+            credential_store: CredentialStore::File,
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
@@ -1959,12 +2503,20 @@ model_verbosity = "high"
             base_instructions: None,
             include_plan_tool: false,
             include_apply_patch_tool: false,
+            apply_patch_fuzzy_threshold: DEFAULT_FUZZY_MATCH_THRESHOLD,
             tools_web_search_request: false,
             use_experimental_streamable_shell_tool: false,
             use_experimental_unified_exec_tool: false,
             use_experimental_use_rmcp_client: false,
             include_view_image_tool: true,
+            include_spawn_agent_tool: false,
+            sub_agents: SubAgentsConfig::default(),
+            screenshot: None,
+            worktree: false,
+            cassette: None,
+            answers_mcp_server_requests: false,
             active_profile: Some("zdr".to_string()),
+            config_layers: ConfigLayers::default(),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
             otel: OtelConfig::default(),
@@ -2000,14 +2552,22 @@ model_verbosity = "high"
             model_provider: fixture.openai_provider.clone(),
             approval_policy: AskForApproval::OnFailure,
             sandbox_policy: SandboxPolicy::new_read_only_policy(),
+            sandbox_resource_limits: SandboxResourceLimits::default(),
+            model_prices: HashMap::new(),
+            models: HashMap::new(),
+            budget: SessionBudget::default(),
             shell_environment_policy: ShellEnvironmentPolicy::default(),
             user_instructions: None,
             notify: None,
+            approver: None,
+            approver_timeout: DEFAULT_APPROVER_TIMEOUT,
             cwd: fixture.cwd(),
             mcp_servers: HashMap::new(),
+            overridden_mcp_servers: HashSet::new(),
             model_providers: fixture.model_provider_map.clone(),
             project_doc_max_bytes: PROJECT_DOC_MAX_BYTES,
             codex_home: fixture.codex_home(),
+            credential_store: CredentialStore::File,
             history: History::default(),
             file_opener: UriBasedFileOpener::VsCode,
             codex_linux_sandbox_exe: None,
@@ -2020,12 +2580,20 @@ model_verbosity = "high"
             base_instructions: None,
             include_plan_tool: false,
             include_apply_patch_tool: false,
+            apply_patch_fuzzy_threshold: DEFAULT_FUZZY_MATCH_THRESHOLD,
             tools_web_search_request: false,
             use_experimental_streamable_shell_tool: false,
             use_experimental_unified_exec_tool: false,
             use_experimental_use_rmcp_client: false,
             include_view_image_tool: true,
+            include_spawn_agent_tool: false,
+            sub_agents: SubAgentsConfig::default(),
+            screenshot: None,
+            worktree: false,
+            cassette: None,
+            answers_mcp_server_requests: false,
             active_profile: Some("gpt5".to_string()),
+            config_layers: ConfigLayers::default(),
             disable_paste_burst: false,
             tui_notifications: Default::default(),
             otel: OtelConfig::default(),
@@ -2036,6 +2604,41 @@ model_verbosity = "high"
         Ok(())
     }
 
+    #[test]
+    fn relocate_to_worktree_maps_cwd_and_writable_roots() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let repo = PathBuf::from("/work/repo");
+        let cfg = ConfigToml {
+            sandbox_mode: Some(SandboxMode::WorkspaceWrite),
+            sandbox_workspace_write: Some(SandboxWorkspaceWrite {
+                writable_roots: vec![repo.join("target"), PathBuf::from("/opt/cache")],
+                ..Default::default()
+            }),
+            ..Default::default()
+        };
+        let mut config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides {
+                cwd: Some(repo.join("crates/app")),
+                ..Default::default()
+            },
+            codex_home.path().to_path_buf(),
+        )?;
+
+        let worktree = PathBuf::from("/wt/repo-session-1");
+        config.relocate_to_worktree(&repo, &worktree);
+
+        assert_eq!(config.cwd, worktree.join("crates/app"));
+        let SandboxPolicy::WorkspaceWrite { writable_roots, .. } = &config.sandbox_policy else {
+            panic!("expected workspace-write policy");
+        };
+        assert_eq!(
+            writable_roots,
+            &vec![worktree.join("target"), PathBuf::from("/opt/cache")]
+        );
+        Ok(())
+    }
+
     #[test]
     fn test_set_project_trusted_writes_explicit_tables() -> anyhow::Result<()> {
         let project_dir = Path::new("/some/path");
@@ -2131,6 +2734,162 @@ trust_level = "trusted"
 
         Ok(())
     }
+
+    fn write_project_config(project_dir: &Path, contents: &str) -> std::io::Result<()> {
+        let dir = project_dir.join(crate::config_layers::PROJECT_CONFIG_DIR);
+        std::fs::create_dir_all(&dir)?;
+        std::fs::write(dir.join(CONFIG_TOML_FILE), contents)
+    }
+
+    #[test]
+    fn test_trusted_project_config_is_layered_beneath_user_config() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let project = TempDir::new()?;
+        write_project_config(
+            project.path(),
+            r#"
+model = "o3"
+model_reasoning_effort = "high"
+
+[mcp_servers.docs]
+command = "docs-server"
+"#,
+        )?;
+        std::fs::write(
+            codex_home.path().join(CONFIG_TOML_FILE),
+            r#"model = "gpt-5""#,
+        )?;
+        set_project_trusted(codex_home.path(), project.path())?;
+
+        let (cfg, layers) = load_layered_config_toml(
+            codex_home.path(),
+            vec![(
+                "model_reasoning_effort".to_string(),
+                TomlValue::String("low".to_string()),
+            )],
+            Some(project.path().to_path_buf()),
+        )?;
+
+        assert_eq!(cfg.model.as_deref(), Some("gpt-5"));
+        assert_eq!(cfg.model_reasoning_effort, Some(ReasoningEffort::Low));
+        assert!(cfg.mcp_servers.contains_key("docs"));
+        assert_eq!(
+            layers.project_config,
+            Some(
+                project
+                    .path()
+                    .join(crate::config_layers::PROJECT_CONFIG_DIR)
+                    .join(CONFIG_TOML_FILE)
+            )
+        );
+        assert_eq!(layers.origin("model"), Some(ConfigLayer::User));
+        assert_eq!(
+            layers.origin("model_reasoning_effort"),
+            Some(ConfigLayer::Cli)
+        );
+        assert_eq!(
+            layers.origin("mcp_servers.docs"),
+            Some(ConfigLayer::Project)
+        );
+        assert_eq!(layers.origin("approval_policy"), None);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_untrusted_project_config_is_ignored() -> anyhow::Result<()> {
+        let codex_home = TempDir::new()?;
+        let project = TempDir::new()?;
+        write_project_config(project.path(), r#"model = "o3""#)?;
+
+        let (cfg, layers) = load_layered_config_toml(
+            codex_home.path(),
+            Vec::new(),
+            Some(project.path().to_path_buf()),
+        )?;
+
+        assert_eq!(cfg.model, None);
+        assert_eq!(layers, ConfigLayers::default());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_profile_overrides_config_sections() -> std::io::Result<()> {
+        let codex_home = TempDir::new()?;
+        let cfg: ConfigToml = toml::from_str(
+            r#"
+profile = "ci"
+notify = ["notify-send"]
+project_doc_max_bytes = 1024
+
+[tools]
+web_search = true
+
+[mcp_servers.docs]
+command = "docs-server"
+
+[mcp_servers.jira]
+command = "jira-server"
+
+[profiles.base]
+sandbox_mode = "workspace-write"
+exclude_mcp_servers = ["jira"]
+
+[profiles.base.sandbox_workspace_write]
+network_access = true
+
+[profiles.ci]
+extends = "base"
+notify = []
+project_doc_max_bytes = 0
+
+[profiles.ci.tools]
+view_image = false
+
+[profiles.ci.shell_environment_policy]
+inherit = "core"
+
+[profiles.ci.mcp_servers.browser]
+command = "browser-server"
+"#,
+        )
+        .expect("TOML deserialization should succeed");
+
+        let config = Config::load_from_base_config_with_overrides(
+            cfg,
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )?;
+
+        assert!(matches!(
+            config.sandbox_policy,
+            SandboxPolicy::WorkspaceWrite {
+                network_access: true,
+                ..
+            }
+        ));
+        let mut servers: Vec<&str> = config.mcp_servers.keys().map(String::as_str).collect();
+        servers.sort_unstable();
+        assert_eq!(servers, vec!["browser", "docs"]);
+        let mut overridden: Vec<&str> = config
+            .overridden_mcp_servers
+            .iter()
+            .map(String::as_str)
+            .collect();
+        overridden.sort_unstable();
+        assert_eq!(overridden, vec!["browser", "jira"]);
+        assert_eq!(config.notify, Some(Vec::new()));
+        assert_eq!(config.project_doc_max_bytes, 0);
+        assert!(config.tools_web_search_request);
+        assert!(!config.include_view_image_tool);
+        assert_eq!(
+            config.shell_environment_policy.inherit,
+            crate::config_types::ShellEnvironmentPolicyInherit::Core
+        );
+
+        Ok(())
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,375 @@
+//! Layered configuration: a repository's checked-in `.codex/config.toml`
+//! sits beneath the user's `$CODEX_HOME/config.toml`, which in turn sits
+//! beneath CLI overrides.
+//!
+//! The project layer is only read once the project has been trusted (see
+//! [`crate::config::set_project_trusted`]) and may only set the keys listed in
+//! [`PROJECT_CONFIG_KEYS`]. [`ConfigLayers`] remembers which layer supplied
+//! each value so that `/status` can explain where a setting came from.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use toml::Value as TomlValue;
+
+use crate::config::CONFIG_TOML_FILE;
+use crate::git_info::get_git_repo_root;
+
+/// Directory at the root of a repository that holds its Codex config.
+pub const PROJECT_CONFIG_DIR: &str = ".codex";
+
+/// Top-level keys a project config may set. Everything else (credentials,
+/// `notify`, `projects`, approval policy, ...) stays under the user's control
+/// and is ignored with a warning.
+pub const PROJECT_CONFIG_KEYS: &[&str] = &[
+    "model",
+    "review_model",
+    "model_provider",
+    "model_reasoning_effort",
+    "model_reasoning_summary",
+    "model_verbosity",
+    "mcp_servers",
+    "sandbox_workspace_write",
+    "shell_environment_policy",
+];
+
+/// The source of a configuration value, from lowest to highest precedence.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ConfigLayer {
+    /// `<repo>/.codex/config.toml`.
+    Project,
+    /// `$CODEX_HOME/config.toml`.
+    User,
+    /// `-c key=value` and flags such as `--model`.
+    Cli,
+}
+
+impl fmt::Display for ConfigLayer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigLayer::Project => f.write_str("project"),
+            ConfigLayer::User => f.write_str("user"),
+            ConfigLayer::Cli => f.write_str("cli"),
+        }
+    }
+}
+
+/// Which layer supplied each value of the effective configuration.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ConfigLayers {
+    /// The project config that was merged in, if any.
+    pub project_config: Option<PathBuf>,
+    /// Dotted path of every leaf value to the layer that set it last.
+    origins: BTreeMap<String, ConfigLayer>,
+}
+
+impl ConfigLayers {
+    /// Record every leaf of `value` as coming from `layer`, replacing what
+    /// lower layers set.
+    pub fn record(&mut self, layer: ConfigLayer, value: &TomlValue) {
+        let mut leaves = Vec::new();
+        collect_leaves(String::new(), value, &mut leaves);
+        for path in leaves {
+            self.record_key(path, layer);
+        }
+    }
+
+    pub fn record_key(&mut self, path: impl Into<String>, layer: ConfigLayer) {
+        self.origins.insert(path.into(), layer);
+    }
+
+    /// Lay the origins recorded in `higher` over these ones.
+    pub(crate) fn merge_from(&mut self, higher: ConfigLayers) {
+        self.origins.extend(higher.origins);
+        if higher.project_config.is_some() {
+            self.project_config = higher.project_config;
+        }
+    }
+
+    /// The layer that set `path` (a dotted key such as `model` or
+    /// `mcp_servers.docs`). For tables this is the highest layer that set any
+    /// value inside them. `None` means the built-in default is in effect.
+    pub fn origin(&self, path: &str) -> Option<ConfigLayer> {
+        let prefix = format!("{path}.");
+        self.origins
+            .iter()
+            .filter(|(key, _)| key.as_str() == path || key.starts_with(&prefix))
+            .map(|(_, layer)| *layer)
+            .max()
+    }
+
+    /// The settings whose effective value comes from `layer`, as top-level
+    /// keys except for `mcp_servers.<name>` and `profiles.<name>`.
+    pub fn settings_from(&self, layer: ConfigLayer) -> Vec<String> {
+        let mut settings: Vec<String> = Vec::new();
+        for (path, origin) in &self.origins {
+            if *origin != layer {
+                continue;
+            }
+            let mut segments = path.splitn(3, '.');
+            let setting = match (segments.next(), segments.next()) {
+                (Some(key @ ("mcp_servers" | "profiles")), Some(name)) => format!("{key}.{name}"),
+                (Some(key), _) => key.to_string(),
+                (None, _) => continue,
+            };
+            if !settings.contains(&setting) {
+                settings.push(setting);
+            }
+        }
+        settings
+    }
+
+    /// Like [`ConfigLayers::origin`] for a key that a profile can also set:
+    /// CLI overrides win, then `profiles.<profile>.<key>`, then `<key>`.
+    pub fn origin_with_profile(&self, profile: Option<&str>, key: &str) -> Option<ConfigLayer> {
+        let top_level = self.origin(key);
+        if top_level == Some(ConfigLayer::Cli) {
+            return top_level;
+        }
+        profile
+            .and_then(|profile| self.origin(&format!("profiles.{profile}.{key}")))
+            .or(top_level)
+    }
+}
+
+fn collect_leaves(path: String, value: &TomlValue, leaves: &mut Vec<String>) {
+    match value {
+        TomlValue::Table(table) if !table.is_empty() => {
+            for (key, value) in table {
+                let child = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{path}.{key}")
+                };
+                collect_leaves(child, value, leaves);
+            }
+        }
+        _ if !path.is_empty() => leaves.push(path),
+        _ => {}
+    }
+}
+
+/// Merge `overlay` into `base`: tables are merged key by key, any other value
+/// in `overlay` replaces the one in `base`.
+pub(crate) fn merge_toml_values(base: &mut TomlValue, overlay: TomlValue) {
+    match (base, overlay) {
+        (TomlValue::Table(base), TomlValue::Table(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_toml_values(existing, value),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// The directory whose `.codex/config.toml` applies to `cwd`: the enclosing
+/// git checkout, or `cwd` itself outside of git.
+pub fn project_root_for_config(cwd: &Path) -> PathBuf {
+    get_git_repo_root(cwd).unwrap_or_else(|| cwd.to_path_buf())
+}
+
+/// Read `<project_root>/.codex/config.toml`, keeping only the keys a project
+/// may set. Relative `sandbox_workspace_write.writable_roots` are resolved
+/// against `project_root`. Returns `None` when the file does not exist.
+pub(crate) fn load_project_config_as_toml(
+    project_root: &Path,
+) -> std::io::Result<Option<(PathBuf, TomlValue)>> {
+    let path = project_root.join(PROJECT_CONFIG_DIR).join(CONFIG_TOML_FILE);
+    let contents = match std::fs::read_to_string(&path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => {
+            tracing::error!("Failed to read {}: {e}", path.display());
+            return Err(e);
+        }
+    };
+    let value = toml::from_str::<TomlValue>(&contents).map_err(|e| {
+        tracing::error!("Failed to parse {}: {e}", path.display());
+        std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("{}: {e}", path.display()),
+        )
+    })?;
+
+    let TomlValue::Table(table) = value else {
+        return Ok(Some((path, TomlValue::Table(Default::default()))));
+    };
+    let mut allowed = toml::value::Table::new();
+    for (key, value) in table {
+        if PROJECT_CONFIG_KEYS.contains(&key.as_str()) {
+            allowed.insert(key, value);
+        } else {
+            tracing::warn!(
+                "Ignoring `{key}` in {}: it can only be set in the user config",
+                path.display()
+            );
+        }
+    }
+    if let Some(TomlValue::Array(roots)) = allowed
+        .get_mut("sandbox_workspace_write")
+        .and_then(|section| section.get_mut("writable_roots"))
+    {
+        for root in roots.iter_mut() {
+            if let TomlValue::String(root) = root
+                && Path::new(root.as_str()).is_relative()
+            {
+                *root = project_root
+                    .join(root.as_str())
+                    .to_string_lossy()
+                    .to_string();
+            }
+        }
+    }
+
+    Ok(Some((path, TomlValue::Table(allowed))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn toml(s: &str) -> TomlValue {
+        toml::from_str(s).expect("valid toml")
+    }
+
+    #[test]
+    fn merge_prefers_overlay_and_keeps_sibling_keys() {
+        let mut base = toml(
+            r#"
+model = "o3"
+[shell_environment_policy]
+inherit = "core"
+exclude = ["AWS_*"]
+"#,
+        );
+        merge_toml_values(
+            &mut base,
+            toml(
+                r#"
+model = "gpt-5"
+[shell_environment_policy]
+inherit = "all"
+"#,
+            ),
+        );
+        assert_eq!(
+            base,
+            toml(
+                r#"
+model = "gpt-5"
+[shell_environment_policy]
+inherit = "all"
+exclude = ["AWS_*"]
+"#,
+            )
+        );
+    }
+
+    #[test]
+    fn origin_reports_highest_layer_under_a_table() {
+        let mut layers = ConfigLayers::default();
+        layers.record(
+            ConfigLayer::Project,
+            &toml(
+                r#"
+model = "o3"
+[mcp_servers.docs]
+command = "docs-server"
+"#,
+            ),
+        );
+        layers.record(
+            ConfigLayer::User,
+            &toml(
+                r#"
+[mcp_servers.docs]
+args = ["--verbose"]
+[profiles.fast]
+model = "gpt-5-mini"
+"#,
+            ),
+        );
+
+        assert_eq!(layers.origin("model"), Some(ConfigLayer::Project));
+        assert_eq!(layers.origin("mcp_servers.docs"), Some(ConfigLayer::User));
+        assert_eq!(layers.origin("mcp_servers.do"), None);
+        assert_eq!(layers.origin("approval_policy"), None);
+        assert_eq!(
+            layers.origin_with_profile(Some("fast"), "model"),
+            Some(ConfigLayer::User)
+        );
+
+        assert_eq!(
+            layers.settings_from(ConfigLayer::Project),
+            vec!["mcp_servers.docs".to_string(), "model".to_string()]
+        );
+        assert_eq!(
+            layers.settings_from(ConfigLayer::User),
+            vec!["mcp_servers.docs".to_string(), "profiles.fast".to_string()]
+        );
+
+        layers.record_key("model", ConfigLayer::Cli);
+        assert_eq!(
+            layers.origin_with_profile(Some("fast"), "model"),
+            Some(ConfigLayer::Cli)
+        );
+    }
+
+    #[test]
+    fn project_config_drops_user_only_keys() {
+        let project = tempfile::tempdir().expect("tempdir");
+        let dir = project.path().join(PROJECT_CONFIG_DIR);
+        std::fs::create_dir_all(&dir).expect("create .codex");
+        std::fs::write(
+            dir.join(CONFIG_TOML_FILE),
+            r#"
+model = "o3"
+approval_policy = "never"
+notify = ["evil"]
+[sandbox_workspace_write]
+writable_roots = ["build", "/tmp/cache"]
+[projects."/elsewhere"]
+trust_level = "trusted"
+"#,
+        )
+        .expect("write project config");
+
+        let (path, value) = load_project_config_as_toml(project.path())
+            .expect("load project config")
+            .expect("project config exists");
+        assert_eq!(path, dir.join(CONFIG_TOML_FILE));
+
+        let mut expected = toml::value::Table::new();
+        expected.insert("model".to_string(), TomlValue::String("o3".to_string()));
+        let mut sandbox = toml::value::Table::new();
+        sandbox.insert(
+            "writable_roots".to_string(),
+            TomlValue::Array(vec![
+                TomlValue::String(project.path().join("build").to_string_lossy().to_string()),
+                TomlValue::String("/tmp/cache".to_string()),
+            ]),
+        );
+        expected.insert(
+            "sandbox_workspace_write".to_string(),
+            TomlValue::Table(sandbox),
+        );
+        assert_eq!(value, TomlValue::Table(expected));
+    }
+
+    #[test]
+    fn missing_project_config_is_not_an_error() {
+        let project = tempfile::tempdir().expect("tempdir");
+        assert_eq!(
+            load_project_config_as_toml(project.path()).expect("load project config"),
+            None
+        );
+    }
+}
@@ -1,25 +1,135 @@
 use serde::Deserialize;
+use std::collections::HashMap;
 use std::path::PathBuf;
 
+use crate::config::ToolsToml;
+use crate::config_types::McpServerConfig;
+use crate::config_types::SandboxWorkspaceWrite;
+use crate::config_types::ShellEnvironmentPolicyToml;
 use crate::protocol::AskForApproval;
 use codex_protocol::config_types::ReasoningEffort;
 use codex_protocol::config_types::ReasoningSummary;
+use codex_protocol::config_types::SandboxMode;
 use codex_protocol::config_types::Verbosity;
 
 /// Collection of common configuration options that a user can define as a unit
 /// in `config.toml`.
 #[derive(Debug, Clone, Default, PartialEq, Deserialize)]
 pub struct ConfigProfile {
+    /// Name of another profile whose values this one starts from.
+    pub extends: Option<String>,
     pub model: Option<String>,
     /// The key in the `model_providers` map identifying the
     /// [`ModelProviderInfo`] to use.
     pub model_provider: Option<String>,
     pub approval_policy: Option<AskForApproval>,
+    pub sandbox_mode: Option<SandboxMode>,
+    /// Replaces the top-level `[sandbox_workspace_write]` table.
+    pub sandbox_workspace_write: Option<SandboxWorkspaceWrite>,
     pub model_reasoning_effort: Option<ReasoningEffort>,
     pub model_reasoning_summary: Option<ReasoningSummary>,
     pub model_verbosity: Option<Verbosity>,
     pub chatgpt_base_url: Option<String>,
     pub experimental_instructions_file: Option<PathBuf>,
+    /// Servers added to (or replacing those of the same name in) the
+    /// top-level `mcp_servers`.
+    #[serde(default)]
+    pub mcp_servers: HashMap<String, McpServerConfig>,
+    /// Names of top-level `mcp_servers` to leave out.
+    #[serde(default)]
+    pub exclude_mcp_servers: Vec<String>,
+    /// Merged field by field over the top-level `[tools]` table.
+    pub tools: Option<ToolsToml>,
+    /// Replaces the top-level `[shell_environment_policy]` table.
+    pub shell_environment_policy: Option<ShellEnvironmentPolicyToml>,
+    pub notify: Option<Vec<String>>,
+    pub project_doc_max_bytes: Option<usize>,
+}
+
+impl ConfigProfile {
+    /// Fill in the values this profile leaves unset from `parent`.
+    fn inherit(self, parent: ConfigProfile) -> ConfigProfile {
+        let mut mcp_servers = parent.mcp_servers;
+        mcp_servers.extend(self.mcp_servers);
+        let mut exclude_mcp_servers = parent.exclude_mcp_servers;
+        for name in self.exclude_mcp_servers {
+            if !exclude_mcp_servers.contains(&name) {
+                exclude_mcp_servers.push(name);
+            }
+        }
+        // A server the child defines itself is not excluded by its parent.
+        exclude_mcp_servers.retain(|name| !mcp_servers.contains_key(name));
+
+        let tools = match (self.tools, parent.tools) {
+            (Some(tools), Some(parent_tools)) => Some(ToolsToml {
+                web_search: tools.web_search.or(parent_tools.web_search),
+                view_image: tools.view_image.or(parent_tools.view_image),
+                spawn_agent: tools.spawn_agent.or(parent_tools.spawn_agent),
+            }),
+            (tools, parent_tools) => tools.or(parent_tools),
+        };
+
+        ConfigProfile {
+            extends: parent.extends,
+            model: self.model.or(parent.model),
+            model_provider: self.model_provider.or(parent.model_provider),
+            approval_policy: self.approval_policy.or(parent.approval_policy),
+            sandbox_mode: self.sandbox_mode.or(parent.sandbox_mode),
+            sandbox_workspace_write: self
+                .sandbox_workspace_write
+                .or(parent.sandbox_workspace_write),
+            model_reasoning_effort: self
+                .model_reasoning_effort
+                .or(parent.model_reasoning_effort),
+            model_reasoning_summary: self
+                .model_reasoning_summary
+                .or(parent.model_reasoning_summary),
+            model_verbosity: self.model_verbosity.or(parent.model_verbosity),
+            chatgpt_base_url: self.chatgpt_base_url.or(parent.chatgpt_base_url),
+            experimental_instructions_file: self
+                .experimental_instructions_file
+                .or(parent.experimental_instructions_file),
+            mcp_servers,
+            exclude_mcp_servers,
+            tools,
+            shell_environment_policy: self
+                .shell_environment_policy
+                .or(parent.shell_environment_policy),
+            notify: self.notify.or(parent.notify),
+            project_doc_max_bytes: self.project_doc_max_bytes.or(parent.project_doc_max_bytes),
+        }
+    }
+}
+
+/// Look up profile `name` in `profiles` and apply its `extends` chain, so the
+/// result holds every value the profile ends up setting.
+pub fn resolve_config_profile(
+    profiles: &HashMap<String, ConfigProfile>,
+    name: &str,
+) -> std::io::Result<ConfigProfile> {
+    let lookup = |key: &str| {
+        profiles.get(key).cloned().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("config profile `{key}` not found"),
+            )
+        })
+    };
+
+    let mut chain = vec![name.to_string()];
+    let mut resolved = lookup(name)?;
+    while let Some(parent) = resolved.extends.clone() {
+        if chain.contains(&parent) {
+            chain.push(parent);
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("config profiles extend each other: {}", chain.join(" -> ")),
+            ));
+        }
+        resolved = resolved.inherit(lookup(&parent)?);
+        chain.push(parent);
+    }
+    Ok(resolved)
 }
 
 impl From<ConfigProfile> for codex_app_server_protocol::Profile {
@@ -35,3 +145,82 @@ impl From<ConfigProfile> for codex_app_server_protocol::Profile {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn profiles(toml: &str) -> HashMap<String, ConfigProfile> {
+        toml::from_str(toml).expect("valid profiles")
+    }
+
+    #[test]
+    fn extends_chain_fills_unset_values() {
+        let profiles = profiles(
+            r#"
+[base]
+model = "o3"
+sandbox_mode = "workspace-write"
+notify = ["notify-send"]
+tools = { web_search = true, view_image = false }
+exclude_mcp_servers = ["docs", "jira"]
+
+[ci]
+extends = "base"
+approval_policy = "never"
+tools = { view_image = true }
+
+[ci.mcp_servers.docs]
+command = "docs-server"
+
+[nightly]
+extends = "ci"
+model = "gpt-5-codex"
+"#,
+        );
+
+        let nightly = resolve_config_profile(&profiles, "nightly").expect("resolve nightly");
+        assert_eq!(nightly.model.as_deref(), Some("gpt-5-codex"));
+        assert_eq!(nightly.approval_policy, Some(AskForApproval::Never));
+        assert_eq!(nightly.sandbox_mode, Some(SandboxMode::WorkspaceWrite));
+        assert_eq!(nightly.notify, Some(vec!["notify-send".to_string()]));
+        assert_eq!(
+            nightly.tools,
+            Some(ToolsToml {
+                web_search: Some(true),
+                view_image: Some(true),
+                spawn_agent: None,
+            })
+        );
+        assert!(nightly.mcp_servers.contains_key("docs"));
+        assert_eq!(nightly.exclude_mcp_servers, vec!["jira".to_string()]);
+        assert_eq!(nightly.extends, None);
+    }
+
+    #[test]
+    fn missing_and_cyclic_parents_are_errors() {
+        let profiles = profiles(
+            r#"
+[a]
+extends = "b"
+
+[b]
+extends = "a"
+
+[orphan]
+extends = "nope"
+"#,
+        );
+
+        let err = resolve_config_profile(&profiles, "a").expect_err("cycle");
+        assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+        assert_eq!(
+            err.to_string(),
+            "config profiles extend each other: a -> b -> a"
+        );
+
+        let err = resolve_config_profile(&profiles, "orphan").expect_err("missing parent");
+        assert_eq!(err.to_string(), "config profile `nope` not found");
+    }
+}
@@ -3,6 +3,7 @@
 // Note this file should generally be restricted to simple struct/enum
 // definitions that do not contain business logic.
 
+use crate::tool_apply_patch::ApplyPatchToolType;
 use serde::Deserializer;
 use std::collections::HashMap;
 use std::path::PathBuf;
@@ -15,6 +16,12 @@ use serde::de::Error as SerdeError;
 
 pub const DEFAULT_OTEL_ENVIRONMENT: &str = "dev";
 
+pub const DEFAULT_SUB_AGENT_MAX_CONCURRENCY: usize = 4;
+
+pub const DEFAULT_SCREENSHOT_WIDTH: u32 = 1280;
+pub const DEFAULT_SCREENSHOT_HEIGHT: u32 = 800;
+pub const DEFAULT_SCREENSHOT_TIMEOUT_MS: u64 = 30_000;
+
 #[derive(Serialize, Debug, Clone, PartialEq)]
 pub struct McpServerConfig {
     #[serde(flatten)]
@@ -48,6 +55,7 @@ impl<'de> Deserialize<'de> for McpServerConfig {
 
             url: Option<String>,
             bearer_token: Option<String>,
+            bearer_token_env_var: Option<String>,
 
             #[serde(default)]
             startup_timeout_sec: Option<f64>,
@@ -87,10 +95,16 @@ impl<'de> Deserialize<'de> for McpServerConfig {
                 env,
                 url,
                 bearer_token,
+                bearer_token_env_var,
                 ..
             } => {
                 throw_if_set("stdio", "url", url.as_ref())?;
                 throw_if_set("stdio", "bearer_token", bearer_token.as_ref())?;
+                throw_if_set(
+                    "stdio",
+                    "bearer_token_env_var",
+                    bearer_token_env_var.as_ref(),
+                )?;
                 McpServerTransportConfig::Stdio {
                     command,
                     args: args.unwrap_or_default(),
@@ -100,6 +114,7 @@ impl<'de> Deserialize<'de> for McpServerConfig {
             RawMcpServerConfig {
                 url: Some(url),
                 bearer_token,
+                bearer_token_env_var,
                 command,
                 args,
                 env,
@@ -108,7 +123,16 @@ impl<'de> Deserialize<'de> for McpServerConfig {
                 throw_if_set("streamable_http", "command", command.as_ref())?;
                 throw_if_set("streamable_http", "args", args.as_ref())?;
                 throw_if_set("streamable_http", "env", env.as_ref())?;
-                McpServerTransportConfig::StreamableHttp { url, bearer_token }
+                if bearer_token.is_some() && bearer_token_env_var.is_some() {
+                    return Err(SerdeError::custom(
+                        "set only one of bearer_token and bearer_token_env_var",
+                    ));
+                }
+                McpServerTransportConfig::StreamableHttp {
+                    url,
+                    bearer_token,
+                    bearer_token_env_var,
+                }
             }
             _ => return Err(SerdeError::custom("invalid transport")),
         };
@@ -140,6 +164,10 @@ pub enum McpServerTransportConfig {
         /// This should be used with caution because it lives on disk in clear text.
         #[serde(default, skip_serializing_if = "Option::is_none")]
         bearer_token: Option<String>,
+        /// Name of an environment variable holding the bearer token, as an
+        /// alternative to `bearer_token`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        bearer_token_env_var: Option<String>,
     },
 }
 
@@ -292,6 +320,81 @@ impl Default for Notifications {
     }
 }
 
+/// Limits for child sessions started through the `spawn_agent` tool, loaded
+/// from the `[sub_agents]` table.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct SubAgentsToml {
+    /// Maximum number of sub-agents that run at the same time.
+    pub max_concurrency: Option<usize>,
+
+    /// Tokens a single sub-agent may use before it is stopped.
+    pub max_tokens: Option<u64>,
+}
+
+/// Effective sub-agent limits after defaults are applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SubAgentsConfig {
+    pub max_concurrency: usize,
+    pub max_tokens: Option<u64>,
+}
+
+impl Default for SubAgentsConfig {
+    fn default() -> Self {
+        Self {
+            max_concurrency: DEFAULT_SUB_AGENT_MAX_CONCURRENCY,
+            max_tokens: None,
+        }
+    }
+}
+
+impl From<SubAgentsToml> for SubAgentsConfig {
+    fn from(toml: SubAgentsToml) -> Self {
+        Self {
+            max_concurrency: toml
+                .max_concurrency
+                .unwrap_or(DEFAULT_SUB_AGENT_MAX_CONCURRENCY)
+                .max(1),
+            max_tokens: toml.max_tokens,
+        }
+    }
+}
+
+/// Headless renderer behind the `capture_screenshot` tool, loaded from the
+/// `[screenshot]` table.
+#[derive(Deserialize, Debug, Clone, PartialEq)]
+pub struct ScreenshotToml {
+    /// Renderer argv. `{url}`, `{output}`, `{width}` and `{height}` are
+    /// replaced in every argument before it runs.
+    pub command: Vec<String>,
+
+    /// Default viewport size in pixels.
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+
+    /// How long the renderer may run before it is killed.
+    pub timeout_ms: Option<u64>,
+}
+
+/// Effective renderer settings after defaults are applied.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScreenshotConfig {
+    pub command: Vec<String>,
+    pub width: u32,
+    pub height: u32,
+    pub timeout_ms: u64,
+}
+
+impl From<ScreenshotToml> for ScreenshotConfig {
+    fn from(toml: ScreenshotToml) -> Self {
+        Self {
+            command: toml.command,
+            width: toml.width.unwrap_or(DEFAULT_SCREENSHOT_WIDTH),
+            height: toml.height.unwrap_or(DEFAULT_SCREENSHOT_HEIGHT),
+            timeout_ms: toml.timeout_ms.unwrap_or(DEFAULT_SCREENSHOT_TIMEOUT_MS),
+        }
+    }
+}
+
 /// Collection of settings that are specific to the TUI.
 #[derive(Deserialize, Debug, Clone, PartialEq, Default)]
 pub struct Tui {
@@ -313,6 +416,104 @@ pub struct SandboxWorkspaceWrite {
     pub exclude_slash_tmp: bool,
 }
 
+/// Resource limits applied to commands run under the Linux sandbox. Unset
+/// fields leave the corresponding resource unrestricted.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct SandboxResourceLimits {
+    /// Maximum resident memory of the command and its descendants, in MiB.
+    pub max_rss_mb: Option<u64>,
+    /// Maximum CPU time per process, in seconds.
+    pub max_cpu_seconds: Option<u64>,
+    /// Maximum number of processes/threads the command may run at once.
+    pub max_pids: Option<u64>,
+    /// Largest file the command may create or grow, in MiB.
+    pub max_file_size_mb: Option<u64>,
+}
+
+impl SandboxResourceLimits {
+    pub fn is_empty(&self) -> bool {
+        self == &Self::default()
+    }
+}
+
+impl std::str::FromStr for SandboxResourceLimits {
+    type Err = serde_json::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        serde_json::from_str(s)
+    }
+}
+
+/// Price of a model's tokens, in US dollars per million tokens.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq)]
+pub struct ModelPrice {
+    pub input_per_million: f64,
+    /// Price of input tokens served from the prompt cache. Defaults to
+    /// `input_per_million`.
+    pub cached_input_per_million: Option<f64>,
+    pub output_per_million: f64,
+}
+
+/// Limits that stop a session once it has consumed too much. Unset fields are
+/// unlimited.
+#[derive(Deserialize, Debug, Clone, Copy, PartialEq, Default)]
+pub struct SessionBudget {
+    /// Maximum tokens (non-cached input plus output) across the session.
+    pub max_tokens_per_session: Option<u64>,
+    /// Maximum cost of the session in US dollars, based on `model_prices`.
+    pub max_cost_usd: Option<f64>,
+    /// Maximum number of requests made to the model.
+    pub max_turns: Option<u64>,
+    /// Maximum wall-clock time since the session started, in seconds.
+    pub max_duration_secs: Option<u64>,
+}
+
+/// A `[models.<slug>]` entry describing a model Codex has no built-in
+/// knowledge of, or overriding what it knows about a built-in one. Unset
+/// fields keep the built-in value, or the defaults for unknown models.
+#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+pub struct ModelDefinitionToml {
+    /// Name shown in the `/model` picker. Defaults to the slug.
+    pub display_name: Option<String>,
+    /// Short description shown next to the name in the `/model` picker.
+    pub description: Option<String>,
+    /// Size of the context window in tokens.
+    pub context_window: Option<u64>,
+    /// Maximum number of output tokens the model can generate.
+    pub max_output_tokens: Option<u64>,
+    /// Token threshold at which conversation history is compacted.
+    pub auto_compact_token_limit: Option<i64>,
+    /// Whether the `reasoning` field can be set in requests to the model.
+    pub supports_reasoning_summaries: Option<bool>,
+    pub reasoning_summary_format: Option<ReasoningSummaryFormat>,
+    /// How the `apply_patch` tool is offered to the model, if as a tool.
+    pub apply_patch_tool_type: Option<ApplyPatchToolType>,
+    /// Whether the model needs instructions on using `apply_patch` from the
+    /// shell.
+    pub needs_special_apply_patch_instructions: Option<bool>,
+    /// Whether the model expects the native `local_shell` tool.
+    pub uses_local_shell_tool: Option<bool>,
+    /// File holding the base instructions to send to the model instead of
+    /// the built-in prompt.
+    pub base_instructions_file: Option<PathBuf>,
+}
+
+/// A `[models.<slug>]` entry with its base instructions file read.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct ModelDefinition {
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub context_window: Option<u64>,
+    pub max_output_tokens: Option<u64>,
+    pub auto_compact_token_limit: Option<i64>,
+    pub supports_reasoning_summaries: Option<bool>,
+    pub reasoning_summary_format: Option<ReasoningSummaryFormat>,
+    pub apply_patch_tool_type: Option<ApplyPatchToolType>,
+    pub needs_special_apply_patch_instructions: Option<bool>,
+    pub uses_local_shell_tool: Option<bool>,
+    pub base_instructions: Option<String>,
+}
+
 impl From<SandboxWorkspaceWrite> for codex_app_server_protocol::SandboxSettings {
     fn from(sandbox_workspace_write: SandboxWorkspaceWrite) -> Self {
         Self {
@@ -324,7 +525,7 @@ impl From<SandboxWorkspaceWrite> for codex_app_server_protocol::SandboxSettings
     }
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Default)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Default)]
 #[serde(rename_all = "kebab-case")]
 pub enum ShellEnvironmentPolicyInherit {
     /// "Core" environment variables for the platform. On UNIX, this would
@@ -506,7 +707,8 @@ mod tests {
             cfg.transport,
             McpServerTransportConfig::StreamableHttp {
                 url: "https://example.com/mcp".to_string(),
-                bearer_token: None
+                bearer_token: None,
+                bearer_token_env_var: None,
             }
         );
     }
@@ -525,11 +727,41 @@ mod tests {
             cfg.transport,
             McpServerTransportConfig::StreamableHttp {
                 url: "https://example.com/mcp".to_string(),
-                bearer_token: Some("secret".to_string())
+                bearer_token: Some("secret".to_string()),
+                bearer_token_env_var: None,
             }
         );
     }
 
+    #[test]
+    fn deserialize_streamable_http_server_config_with_bearer_token_env_var() {
+        let cfg: McpServerConfig = toml::from_str(
+            r#"
+            url = "https://example.com/mcp"
+            bearer_token_env_var = "EXAMPLE_MCP_TOKEN"
+        "#,
+        )
+        .expect("should deserialize http config");
+
+        assert_eq!(
+            cfg.transport,
+            McpServerTransportConfig::StreamableHttp {
+                url: "https://example.com/mcp".to_string(),
+                bearer_token: None,
+                bearer_token_env_var: Some("EXAMPLE_MCP_TOKEN".to_string()),
+            }
+        );
+
+        toml::from_str::<McpServerConfig>(
+            r#"
+            url = "https://example.com/mcp"
+            bearer_token = "secret"
+            bearer_token_env_var = "EXAMPLE_MCP_TOKEN"
+        "#,
+        )
+        .expect_err("should reject bearer_token with bearer_token_env_var");
+    }
+
     #[test]
     fn deserialize_rejects_command_and_url() {
         toml::from_str::<McpServerConfig>(
@@ -0,0 +1,630 @@
+//! Where Codex keeps secrets: the ChatGPT/API key login (`auth.json`) and
+//! bearer tokens for MCP servers.
+//!
+//! Selected with the `[credential_store]` table in `config.toml`:
+//!
+//! - `backend = "file"` (default): plaintext files under `CODEX_HOME`, readable
+//!   only by the user.
+//! - `backend = "encrypted-file"`: the same files, encrypted with AES-256-GCM
+//!   under a key derived from a passphrase (`passphrase_env`) or key file
+//!   (`key_file`).
+//! - `backend = "helper"`: an external command speaking a git-credential style
+//!   protocol, see [`CredentialStore::Helper`].
+
+use std::io;
+use std::io::Write;
+use std::num::NonZeroU32;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Child;
+use std::process::Command;
+use std::process::ExitStatus;
+use std::process::Stdio;
+use std::time::Duration;
+use std::time::Instant;
+
+use base64::Engine;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use ring::aead;
+use ring::pbkdf2;
+use ring::rand::SecureRandom;
+use ring::rand::SystemRandom;
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Name of the credential holding the contents of `auth.json`.
+pub const AUTH_CREDENTIAL: &str = "auth";
+
+/// Environment variable read for the passphrase of an `encrypted-file` store
+/// when neither `passphrase_env` nor `key_file` is set.
+pub const DEFAULT_PASSPHRASE_ENV_VAR: &str = "CODEX_CREDENTIALS_PASSPHRASE";
+
+/// Directory under `CODEX_HOME` for credentials other than `auth.json`.
+const CREDENTIALS_DIR: &str = "credentials";
+
+const ENCRYPTED_FILE_EXTENSION: &str = "enc";
+const PBKDF2_ITERATIONS: u32 = 600_000;
+const SALT_LEN: usize = 16;
+
+/// How long a credential helper may take before it is killed.
+const HELPER_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Name of the credential holding the bearer token for MCP server `server`.
+pub fn mcp_server_credential(server: &str) -> String {
+    format!("mcp-server/{server}")
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(tag = "backend", rename_all = "kebab-case")]
+pub enum CredentialStore {
+    /// Plaintext files in `CODEX_HOME` with mode `0600`.
+    #[default]
+    File,
+
+    /// Files in `CODEX_HOME`, encrypted with a key derived from a passphrase.
+    EncryptedFile {
+        /// File whose contents are the passphrase. Relative paths are
+        /// resolved against `CODEX_HOME`.
+        key_file: Option<PathBuf>,
+        /// Environment variable holding the passphrase. Defaults to
+        /// [`DEFAULT_PASSPHRASE_ENV_VAR`] when `key_file` is not set.
+        passphrase_env: Option<String>,
+    },
+
+    /// An external program, run as `command... get|store|erase`. It reads
+    /// `key=value` lines terminated by an empty line on stdin: `name=<credential>`
+    /// and, for `store`, `secret=<value>`. For `get` it prints
+    /// `secret=<value>` if it has the credential and nothing otherwise.
+    Helper { command: Vec<String> },
+}
+
+impl CredentialStore {
+    /// The secret stored under `name`, or `None` if there is none.
+    pub fn load(&self, codex_home: &Path, name: &str) -> io::Result<Option<String>> {
+        match self {
+            CredentialStore::File => read_optional(&credential_path(codex_home, name)),
+            CredentialStore::EncryptedFile { .. } => {
+                let path = encrypted_credential_path(codex_home, name);
+                let Some(contents) = read_optional(&path)? else {
+                    return Ok(None);
+                };
+                let passphrase = self.passphrase(codex_home)?;
+                decrypt(&passphrase, name, &contents)
+                    .map(Some)
+                    .map_err(|e| io::Error::new(e.kind(), format!("{}: {e}", path.display())))
+            }
+            CredentialStore::Helper { command } => {
+                let output = run_helper(command, "get", &[("name", name)], HELPER_TIMEOUT)?;
+                Ok(output.lines().find_map(|line| {
+                    line.strip_prefix("secret=")
+                        .filter(|secret| !secret.is_empty())
+                        .map(str::to_string)
+                }))
+            }
+        }
+    }
+
+    /// Store `secret` under `name`, replacing any previous value.
+    pub fn save(&self, codex_home: &Path, name: &str, secret: &str) -> io::Result<()> {
+        match self {
+            CredentialStore::File => {
+                write_private_file(&credential_path(codex_home, name), secret.as_bytes())
+            }
+            CredentialStore::EncryptedFile { .. } => {
+                let passphrase = self.passphrase(codex_home)?;
+                let contents = encrypt(&passphrase, name, secret)?;
+                write_private_file(
+                    &encrypted_credential_path(codex_home, name),
+                    contents.as_bytes(),
+                )
+            }
+            CredentialStore::Helper { command } => {
+                if secret.contains(['\n', '\r']) {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        "credential helper secrets must be a single line",
+                    ));
+                }
+                run_helper(
+                    command,
+                    "store",
+                    &[("name", name), ("secret", secret)],
+                    HELPER_TIMEOUT,
+                )
+                .map(|_| ())
+            }
+        }
+    }
+
+    /// Remove the secret stored under `name`. Returns whether there was one;
+    /// credential helpers are always assumed to have had it.
+    pub fn erase(&self, codex_home: &Path, name: &str) -> io::Result<bool> {
+        let path = match self {
+            CredentialStore::File => credential_path(codex_home, name),
+            CredentialStore::EncryptedFile { .. } => encrypted_credential_path(codex_home, name),
+            CredentialStore::Helper { command } => {
+                return run_helper(command, "erase", &[("name", name)], HELPER_TIMEOUT)
+                    .map(|_| true);
+            }
+        };
+        match std::fs::remove_file(&path) {
+            Ok(()) => Ok(true),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(false),
+            Err(e) => Err(e),
+        }
+    }
+
+    fn passphrase(&self, codex_home: &Path) -> io::Result<String> {
+        let CredentialStore::EncryptedFile {
+            key_file,
+            passphrase_env,
+        } = self
+        else {
+            return Err(io::Error::other("credential store is not encrypted"));
+        };
+        let passphrase = match (key_file, passphrase_env) {
+            (Some(_), Some(_)) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "credential_store: set only one of `key_file` and `passphrase_env`",
+                ));
+            }
+            (Some(key_file), None) => {
+                let path = codex_home.join(key_file);
+                std::fs::read_to_string(&path)
+                    .map_err(|e| {
+                        io::Error::new(
+                            e.kind(),
+                            format!("failed to read credential key file {}: {e}", path.display()),
+                        )
+                    })?
+                    .trim_end_matches(['\n', '\r'])
+                    .to_string()
+            }
+            (None, env_var) => {
+                let env_var = env_var.as_deref().unwrap_or(DEFAULT_PASSPHRASE_ENV_VAR);
+                std::env::var(env_var).map_err(|_| {
+                    io::Error::new(
+                        io::ErrorKind::NotFound,
+                        format!(
+                            "credential_store: set {env_var} to the passphrase of the encrypted credential store"
+                        ),
+                    )
+                })?
+            }
+        };
+        if passphrase.is_empty() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidInput,
+                "credential_store: the passphrase is empty",
+            ));
+        }
+        Ok(passphrase)
+    }
+}
+
+/// `auth.json` for the login, `credentials/<name>` for everything else.
+fn credential_path(codex_home: &Path, name: &str) -> PathBuf {
+    if name == AUTH_CREDENTIAL {
+        return crate::auth::get_auth_file(codex_home);
+    }
+    codex_home
+        .join(CREDENTIALS_DIR)
+        .join(credential_file_name(name))
+}
+
+/// `name` with every byte other than ASCII letters, digits, `-` and `_`
+/// percent-encoded, so distinct names never share a file.
+fn credential_file_name(name: &str) -> String {
+    let mut file_name = String::with_capacity(name.len());
+    for byte in name.bytes() {
+        if byte.is_ascii_alphanumeric() || byte == b'-' || byte == b'_' {
+            file_name.push(char::from(byte));
+        } else {
+            file_name.push_str(&format!("%{byte:02X}"));
+        }
+    }
+    file_name
+}
+
+fn encrypted_credential_path(codex_home: &Path, name: &str) -> PathBuf {
+    let mut path = credential_path(codex_home, name).into_os_string();
+    path.push(".");
+    path.push(ENCRYPTED_FILE_EXTENSION);
+    PathBuf::from(path)
+}
+
+fn read_optional(path: &Path) -> io::Result<Option<String>> {
+    match std::fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e),
+    }
+}
+
+/// Write `contents` to `path`, creating parent directories, with mode `0600`
+/// on Unix.
+pub(crate) fn write_private_file(path: &Path, contents: &[u8]) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    let mut options = std::fs::OpenOptions::new();
+    options.truncate(true).write(true).create(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    let mut file = options.open(path)?;
+    file.write_all(contents)?;
+    file.flush()?;
+    Ok(())
+}
+
+/// On-disk format of an `encrypted-file` credential.
+#[derive(Debug, Deserialize, Serialize)]
+struct EncryptedCredential {
+    version: u32,
+    kdf: String,
+    iterations: u32,
+    salt: String,
+    nonce: String,
+    ciphertext: String,
+}
+
+fn derive_key(passphrase: &str, salt: &[u8], iterations: u32) -> io::Result<aead::LessSafeKey> {
+    let iterations = NonZeroU32::new(iterations)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid iteration count"))?;
+    let mut key = [0u8; 32];
+    pbkdf2::derive(
+        pbkdf2::PBKDF2_HMAC_SHA256,
+        iterations,
+        salt,
+        passphrase.as_bytes(),
+        &mut key,
+    );
+    let key = aead::UnboundKey::new(&aead::AES_256_GCM, &key)
+        .map_err(|_| io::Error::other("failed to create encryption key"))?;
+    Ok(aead::LessSafeKey::new(key))
+}
+
+/// Encrypt `secret`, binding it to `name` so files cannot be swapped.
+fn encrypt(passphrase: &str, name: &str, secret: &str) -> io::Result<String> {
+    let rng = SystemRandom::new();
+    let mut salt = [0u8; SALT_LEN];
+    let mut nonce = [0u8; aead::NONCE_LEN];
+    rng.fill(&mut salt)
+        .and_then(|()| rng.fill(&mut nonce))
+        .map_err(|_| io::Error::other("failed to generate random bytes"))?;
+
+    let key = derive_key(passphrase, &salt, PBKDF2_ITERATIONS)?;
+    let mut in_out = secret.as_bytes().to_vec();
+    key.seal_in_place_append_tag(
+        aead::Nonce::assume_unique_for_key(nonce),
+        aead::Aad::from(name.as_bytes()),
+        &mut in_out,
+    )
+    .map_err(|_| io::Error::other("failed to encrypt credential"))?;
+
+    let encrypted = EncryptedCredential {
+        version: 1,
+        kdf: "pbkdf2-sha256".to_string(),
+        iterations: PBKDF2_ITERATIONS,
+        salt: BASE64.encode(salt),
+        nonce: BASE64.encode(nonce),
+        ciphertext: BASE64.encode(in_out),
+    };
+    Ok(serde_json::to_string_pretty(&encrypted)?)
+}
+
+fn decrypt(passphrase: &str, name: &str, contents: &str) -> io::Result<String> {
+    let invalid = |message: &str| io::Error::new(io::ErrorKind::InvalidData, message.to_string());
+    let encrypted: EncryptedCredential = serde_json::from_str(contents)?;
+    if encrypted.version != 1 || encrypted.kdf != "pbkdf2-sha256" {
+        return Err(invalid("unsupported encrypted credential format"));
+    }
+    let decode = |value: &str| BASE64.decode(value).map_err(|_| invalid("invalid base64"));
+    let salt = decode(&encrypted.salt)?;
+    let nonce: [u8; aead::NONCE_LEN] = decode(&encrypted.nonce)?
+        .try_into()
+        .map_err(|_| invalid("invalid nonce"))?;
+    let mut in_out = decode(&encrypted.ciphertext)?;
+
+    let key = derive_key(passphrase, &salt, encrypted.iterations)?;
+    let plaintext = key
+        .open_in_place(
+            aead::Nonce::assume_unique_for_key(nonce),
+            aead::Aad::from(name.as_bytes()),
+            &mut in_out,
+        )
+        .map_err(|_| invalid("failed to decrypt credential; is the passphrase correct?"))?;
+    String::from_utf8(plaintext.to_vec()).map_err(|_| invalid("credential is not valid UTF-8"))
+}
+
+/// Run a credential helper and return its stdout. A helper that is still
+/// running after `timeout` (e.g. waiting for an unlock prompt nobody sees) is
+/// killed.
+fn run_helper(
+    command: &[String],
+    action: &str,
+    fields: &[(&str, &str)],
+    timeout: Duration,
+) -> io::Result<String> {
+    let Some((program, args)) = command.split_first() else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "credential_store: `command` must not be empty",
+        ));
+    };
+    let mut child = Command::new(program)
+        .args(args)
+        .arg(action)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            io::Error::new(
+                e.kind(),
+                format!("failed to run credential helper `{program}`: {e}"),
+            )
+        })?;
+
+    let mut input = String::new();
+    for (key, value) in fields {
+        input.push_str(&format!("{key}={value}\n"));
+    }
+    input.push('\n');
+    if let Some(mut stdin) = child.stdin.take() {
+        stdin.write_all(input.as_bytes())?;
+    }
+
+    // Read the pipes on their own threads so a chatty helper cannot block on
+    // a full pipe while we wait for it to exit.
+    let stdout = read_pipe_in_background(child.stdout.take());
+    let stderr = read_pipe_in_background(child.stderr.take());
+    let Some(status) = wait_with_timeout(&mut child, timeout)? else {
+        return Err(io::Error::new(
+            io::ErrorKind::TimedOut,
+            format!(
+                "credential helper `{program} {action}` did not finish within {} seconds",
+                timeout.as_secs()
+            ),
+        ));
+    };
+    let stdout = stdout.join().unwrap_or_default();
+    if !status.success() {
+        let stderr = stderr.join().unwrap_or_default();
+        let stderr = String::from_utf8_lossy(&stderr);
+        return Err(io::Error::other(format!(
+            "credential helper `{program} {action}` failed ({status}): {}",
+            stderr.trim()
+        )));
+    }
+    String::from_utf8(stdout).map_err(|_| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            "credential helper output is not valid UTF-8",
+        )
+    })
+}
+
+fn read_pipe_in_background(
+    pipe: Option<impl io::Read + Send + 'static>,
+) -> std::thread::JoinHandle<Vec<u8>> {
+    std::thread::spawn(move || {
+        let mut contents = Vec::new();
+        if let Some(mut pipe) = pipe {
+            let _ = pipe.read_to_end(&mut contents);
+        }
+        contents
+    })
+}
+
+/// Waits for `child` to exit, killing it once `timeout` has passed. Returns
+/// `None` if it had to be killed.
+fn wait_with_timeout(child: &mut Child, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Ok(None);
+        }
+        std::thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+
+    #[test]
+    fn file_store_keeps_auth_in_auth_json() {
+        let codex_home = tempdir().unwrap();
+        let store = CredentialStore::File;
+
+        assert_eq!(
+            store.load(codex_home.path(), AUTH_CREDENTIAL).unwrap(),
+            None
+        );
+        store
+            .save(
+                codex_home.path(),
+                AUTH_CREDENTIAL,
+                "{\"OPENAI_API_KEY\":\"sk\"}",
+            )
+            .unwrap();
+        assert_eq!(
+            std::fs::read_to_string(codex_home.path().join("auth.json")).unwrap(),
+            "{\"OPENAI_API_KEY\":\"sk\"}"
+        );
+
+        let docs = mcp_server_credential("docs");
+        store.save(codex_home.path(), &docs, "token").unwrap();
+        assert!(
+            codex_home
+                .path()
+                .join("credentials")
+                .join("mcp-server%2Fdocs")
+                .exists()
+        );
+        assert_eq!(
+            store.load(codex_home.path(), &docs).unwrap().as_deref(),
+            Some("token")
+        );
+        assert!(store.erase(codex_home.path(), &docs).unwrap());
+        assert!(!store.erase(codex_home.path(), &docs).unwrap());
+    }
+
+    #[test]
+    fn credential_file_names_are_distinct() {
+        assert_eq!(credential_file_name("mcp-server/docs"), "mcp-server%2Fdocs");
+        assert_ne!(
+            credential_file_name("mcp-server/a-b"),
+            credential_file_name("mcp-server/a.b")
+        );
+        assert_ne!(
+            credential_file_name("mcp-server/a%2Fb"),
+            credential_file_name("mcp-server/a/b")
+        );
+        assert_eq!(credential_file_name("é"), "%C3%A9");
+    }
+
+    #[test]
+    fn encrypted_file_store_round_trips_with_key_file() {
+        let codex_home = tempdir().unwrap();
+        std::fs::write(codex_home.path().join("credentials.key"), "hunter2\n").unwrap();
+        let store = CredentialStore::EncryptedFile {
+            key_file: Some(PathBuf::from("credentials.key")),
+            passphrase_env: None,
+        };
+
+        store
+            .save(
+                codex_home.path(),
+                AUTH_CREDENTIAL,
+                "{\"OPENAI_API_KEY\":\"sk-secret\"}",
+            )
+            .unwrap();
+        assert!(!codex_home.path().join("auth.json").exists());
+        let on_disk = std::fs::read_to_string(codex_home.path().join("auth.json.enc")).unwrap();
+        assert!(!on_disk.contains("sk-secret"));
+        assert_eq!(
+            store
+                .load(codex_home.path(), AUTH_CREDENTIAL)
+                .unwrap()
+                .as_deref(),
+            Some("{\"OPENAI_API_KEY\":\"sk-secret\"}")
+        );
+
+        std::fs::write(codex_home.path().join("credentials.key"), "wrong").unwrap();
+        let err = store.load(codex_home.path(), AUTH_CREDENTIAL).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn helper_store_speaks_get_store_erase() {
+        let codex_home = tempdir().unwrap();
+        let script = codex_home.path().join("helper.sh");
+        let db = codex_home.path().join("db");
+        // Keeps `name=...` / `secret=...` pairs in a file, one credential per
+        // line.
+        std::fs::write(
+            &script,
+            format!(
+                r#"#!/bin/sh
+db={db}
+while IFS= read -r line && [ -n "$line" ]; do
+  case "$line" in
+    name=*) name="${{line#name=}}" ;;
+    secret=*) secret="${{line#secret=}}" ;;
+  esac
+done
+touch "$db"
+case "$1" in
+  get) grep "^$name " "$db" | sed "s/^[^ ]* /secret=/" ;;
+  store) grep -v "^$name " "$db" > "$db.tmp"; echo "$name $secret" >> "$db.tmp"; mv "$db.tmp" "$db" ;;
+  erase) grep -v "^$name " "$db" > "$db.tmp"; mv "$db.tmp" "$db" ;;
+esac
+"#,
+                db = db.display()
+            ),
+        )
+        .unwrap();
+        let store = CredentialStore::Helper {
+            command: vec!["sh".to_string(), script.display().to_string()],
+        };
+        let docs = mcp_server_credential("docs");
+
+        assert_eq!(store.load(codex_home.path(), &docs).unwrap(), None);
+        store.save(codex_home.path(), &docs, "token-1").unwrap();
+        store.save(codex_home.path(), &docs, "token-2").unwrap();
+        assert_eq!(
+            store.load(codex_home.path(), &docs).unwrap().as_deref(),
+            Some("token-2")
+        );
+        assert!(store.erase(codex_home.path(), &docs).unwrap());
+        assert_eq!(store.load(codex_home.path(), &docs).unwrap(), None);
+
+        let err = store
+            .save(codex_home.path(), &docs, "two\nlines")
+            .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn helper_that_hangs_is_killed() {
+        let command = vec!["sh".to_string(), "-c".to_string(), "sleep 30".to_string()];
+        let started = Instant::now();
+        let err = run_helper(
+            &command,
+            "get",
+            &[("name", "auth")],
+            Duration::from_millis(200),
+        )
+        .unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::TimedOut);
+        assert!(started.elapsed() < Duration::from_secs(10));
+    }
+
+    #[test]
+    fn parses_backend_tables() {
+        #[derive(Deserialize)]
+        struct Wrapper {
+            credential_store: CredentialStore,
+        }
+        let parse = |toml: &str| {
+            toml::from_str::<Wrapper>(toml)
+                .expect("valid credential_store")
+                .credential_store
+        };
+
+        assert_eq!(
+            parse("credential_store = { backend = \"file\" }"),
+            CredentialStore::File
+        );
+        assert_eq!(
+            parse("[credential_store]\nbackend = \"encrypted-file\"\npassphrase_env = \"PASS\""),
+            CredentialStore::EncryptedFile {
+                key_file: None,
+                passphrase_env: Some("PASS".to_string()),
+            }
+        );
+        assert_eq!(
+            parse(
+                "[credential_store]\nbackend = \"helper\"\ncommand = [\"pass-helper\", \"--codex\"]"
+            ),
+            CredentialStore::Helper {
+                command: vec!["pass-helper".to_string(), "--codex".to_string()],
+            }
+        );
+    }
+}
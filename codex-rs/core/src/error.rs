@@ -1,4 +1,5 @@
 use crate::exec::ExecToolCallOutput;
+use crate::resource_limits::ResourceLimit;
 use crate::token_data::KnownPlan;
 use crate::token_data::PlanType;
 use codex_protocol::ConversationId;
@@ -6,6 +7,7 @@ use codex_protocol::protocol::RateLimitSnapshot;
 use reqwest::StatusCode;
 use serde_json;
 use std::io;
+use std::path::PathBuf;
 use std::time::Duration;
 use thiserror::Error;
 use tokio::task::JoinError;
@@ -35,6 +37,19 @@ pub enum SandboxErr {
     #[error("command timed out")]
     Timeout { output: Box<ExecToolCallOutput> },
 
+    /// Command ran into one of the configured `sandbox_resource_limits`.
+    ///
+    /// A CPU time or file size breach inside a shell is inferred from the
+    /// shell exiting with 128 + `SIGXCPU`/`SIGXFSZ` (152/153), since the
+    /// sandbox helper `exec`s the command and cannot observe the signal. While
+    /// the matching limit is set, a command that exits with 152 or 153 on its
+    /// own is therefore reported as a breach too.
+    #[error("command exceeded the sandbox {limit}")]
+    ResourceLimitExceeded {
+        limit: ResourceLimit,
+        output: Box<ExecToolCallOutput>,
+    },
+
     /// Command was killed by a signal
     #[error("command was killed by a signal")]
     Signal(i32),
@@ -42,6 +57,11 @@ pub enum SandboxErr {
     /// Error from linux landlock
     #[error("Landlock was not able to fully enforce all sandbox rules")]
     LandlockRestrict,
+
+    /// Error protecting read-only subpaths with bind mounts on linux
+    #[cfg(target_os = "linux")]
+    #[error("failed to mount read-only subpaths: {0}")]
+    ReadOnlyMount(std::io::Error),
 }
 
 #[derive(Error, Debug)]
@@ -108,6 +128,13 @@ pub enum CodexErr {
     #[error("unsupported operation: {0}")]
     UnsupportedOperation(String),
 
+    /// `--replay-cassette` was given a request that was never recorded.
+    #[error(
+        "no recorded response for request {key} in cassette {}; record it again with --record-cassette",
+        .dir.display()
+    )]
+    CassetteMiss { key: String, dir: PathBuf },
+
     // -----------------------------------------------------------------
     // Automatic conversions for common external error types
     // -----------------------------------------------------------------
@@ -260,6 +287,9 @@ pub fn get_error_message_ui(e: &CodexErr) -> String {
             "error: command timed out after {} ms",
             output.duration.as_millis()
         ),
+        CodexErr::Sandbox(SandboxErr::ResourceLimitExceeded { limit, .. }) => {
+            format!("error: command exceeded the sandbox {limit}")
+        }
         _ => e.to_string(),
     }
 }
@@ -14,7 +14,9 @@ use tokio::io::AsyncRead;
 use tokio::io::AsyncReadExt;
 use tokio::io::BufReader;
 use tokio::process::Child;
+use tracing::warn;
 
+use crate::config_types::SandboxResourceLimits;
 use crate::error::CodexErr;
 use crate::error::Result;
 use crate::error::SandboxErr;
@@ -24,6 +26,7 @@ use crate::protocol::EventMsg;
 use crate::protocol::ExecCommandOutputDeltaEvent;
 use crate::protocol::ExecOutputStream;
 use crate::protocol::SandboxPolicy;
+use crate::resource_limits::ResourceLimit;
 use crate::seatbelt::spawn_command_under_seatbelt;
 use crate::spawn::StdioPolicy;
 use crate::spawn::spawn_child_async;
@@ -85,11 +88,22 @@ pub async fn process_exec_tool_call(
     sandbox_policy: &SandboxPolicy,
     sandbox_cwd: &Path,
     codex_linux_sandbox_exe: &Option<PathBuf>,
+    resource_limits: &SandboxResourceLimits,
     stdout_stream: Option<StdoutStream>,
 ) -> Result<ExecToolCallOutput> {
     let start = Instant::now();
 
     let timeout_duration = params.timeout_duration();
+    // Only `codex-linux-sandbox` enforces the limits; anything else that kills
+    // a command with e.g. SIGXCPU is not a breach of ours.
+    let enforced_limits = if sandbox_type == SandboxType::LinuxSeccomp {
+        *resource_limits
+    } else {
+        if !resource_limits.is_empty() {
+            warn!("sandbox_resource_limits are not enforced for {sandbox_type:?} commands");
+        }
+        SandboxResourceLimits::default()
+    };
 
     let raw_output_result: std::result::Result<RawExecToolCallOutput, CodexErr> = match sandbox_type
     {
@@ -129,12 +143,31 @@ pub async fn process_exec_tool_call(
                 command_cwd,
                 sandbox_policy,
                 sandbox_cwd,
+                resource_limits,
                 StdioPolicy::RedirectForShellTool,
                 env,
             )
             .await?;
-
-            consume_truncated_output(child, timeout_duration, stdout_stream).await
+            let helper_pid = child.id();
+
+            let mut raw_output =
+                consume_truncated_output(child, timeout_duration, stdout_stream).await;
+            if let Some(pid) = helper_pid {
+                let breach = tokio::task::spawn_blocking(move || {
+                    crate::resource_limits::collect_cgroup_scope(pid)
+                })
+                .await
+                .ok()
+                .flatten();
+                if let Ok(raw_output) = raw_output.as_mut() {
+                    raw_output.resource_limit_exceeded = breach.or_else(|| {
+                        raw_output.exit_status.code().and_then(|exit_code| {
+                            ResourceLimit::from_exit_code(exit_code, &enforced_limits)
+                        })
+                    });
+                }
+            }
+            raw_output
         }
     };
     let duration = start.elapsed();
@@ -142,19 +175,27 @@ pub async fn process_exec_tool_call(
         Ok(raw_output) => {
             #[allow(unused_mut)]
             let mut timed_out = raw_output.timed_out;
+            #[allow(unused_mut)]
+            let mut resource_limit_exceeded = raw_output.resource_limit_exceeded;
+            let mut exit_code = raw_output.exit_status.code().unwrap_or(-1);
 
             #[cfg(target_family = "unix")]
             {
                 if let Some(signal) = raw_output.exit_status.signal() {
                     if signal == TIMEOUT_CODE {
                         timed_out = true;
+                    } else if let Some(limit) = resource_limit_exceeded
+                        .or_else(|| ResourceLimit::from_signal(signal, &enforced_limits))
+                    {
+                        // e.g. SIGKILL from the cgroup OOM killer, or SIGXCPU.
+                        resource_limit_exceeded = Some(limit);
+                        exit_code = EXIT_CODE_SIGNAL_BASE + signal;
                     } else {
                         return Err(CodexErr::Sandbox(SandboxErr::Signal(signal)));
                     }
                 }
             }
 
-            let mut exit_code = raw_output.exit_status.code().unwrap_or(-1);
             if timed_out {
                 exit_code = EXEC_TIMEOUT_EXIT_CODE;
             }
@@ -169,6 +210,7 @@ pub async fn process_exec_tool_call(
                 aggregated_output,
                 duration,
                 timed_out,
+                resource_limit_exceeded,
             };
 
             if timed_out {
@@ -177,6 +219,18 @@ pub async fn process_exec_tool_call(
                 }));
             }
 
+            // A command that hits e.g. the process limit usually notices the
+            // failed fork and exits on its own; only blame the limit if the
+            // command failed.
+            if exit_code != 0
+                && let Some(limit) = resource_limit_exceeded
+            {
+                return Err(CodexErr::Sandbox(SandboxErr::ResourceLimitExceeded {
+                    limit,
+                    output: Box::new(exec_output),
+                }));
+            }
+
             if exit_code != 0 && is_likely_sandbox_denied(sandbox_type, exit_code) {
                 return Err(CodexErr::Sandbox(SandboxErr::Denied {
                     output: Box::new(exec_output),
@@ -224,6 +278,7 @@ struct RawExecToolCallOutput {
     pub stderr: StreamOutput<Vec<u8>>,
     pub aggregated_output: StreamOutput<Vec<u8>>,
     pub timed_out: bool,
+    pub resource_limit_exceeded: Option<ResourceLimit>,
 }
 
 impl StreamOutput<String> {
@@ -257,6 +312,8 @@ pub struct ExecToolCallOutput {
     pub aggregated_output: StreamOutput<String>,
     pub duration: Duration,
     pub timed_out: bool,
+    /// Set when the command ran into one of the `sandbox_resource_limits`.
+    pub resource_limit_exceeded: Option<ResourceLimit>,
 }
 
 async fn exec(
@@ -367,6 +424,7 @@ async fn consume_truncated_output(
         stderr,
         aggregated_output,
         timed_out,
+        resource_limit_exceeded: None,
     })
 }
 
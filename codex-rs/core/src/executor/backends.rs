@@ -7,6 +7,7 @@ use crate::CODEX_APPLY_PATCH_ARG1;
 use crate::apply_patch::ApplyPatchExec;
 use crate::exec::ExecParams;
 use crate::function_tool::FunctionCallError;
+use codex_apply_patch::FUZZY_MATCH_THRESHOLD_ENV_VAR;
 
 pub(crate) enum ExecutionMode {
     Shell,
@@ -84,7 +85,12 @@ impl ExecutionBackend for ApplyPatchBackend {
                     timeout_ms: params.timeout_ms,
                     // Run apply_patch with a minimal environment for determinism and to
                     // avoid leaking host environment variables into the patch process.
-                    env: HashMap::new(),
+                    // The fuzzy match threshold is passed along so the patch applies
+                    // exactly as it was verified.
+                    env: HashMap::from([(
+                        FUZZY_MATCH_THRESHOLD_ENV_VAR.to_string(),
+                        exec.action.fuzzy_threshold().to_string(),
+                    )]),
                     with_escalated_permissions: params.with_escalated_permissions,
                     justification: params.justification,
                 })
@@ -8,6 +8,7 @@ use super::backends::backend_for_mode;
 use super::cache::ApprovalCache;
 use crate::codex::ExecCommandContext;
 use crate::codex::Session;
+use crate::config_types::SandboxResourceLimits;
 use crate::error::CodexErr;
 use crate::error::SandboxErr;
 use crate::error::get_error_message_ui;
@@ -31,6 +32,7 @@ pub(crate) struct ExecutorConfig {
     pub(crate) sandbox_policy: SandboxPolicy,
     pub(crate) sandbox_cwd: PathBuf,
     codex_linux_sandbox_exe: Option<PathBuf>,
+    resource_limits: SandboxResourceLimits,
 }
 
 impl ExecutorConfig {
@@ -43,8 +45,14 @@ impl ExecutorConfig {
             sandbox_policy,
             sandbox_cwd,
             codex_linux_sandbox_exe,
+            resource_limits: SandboxResourceLimits::default(),
         }
     }
+
+    pub(crate) fn with_resource_limits(mut self, resource_limits: SandboxResourceLimits) -> Self {
+        self.resource_limits = resource_limits;
+        self
+    }
 }
 
 /// Coordinates sandbox selection, backend-specific preparation, and command
@@ -136,6 +144,10 @@ impl Executor {
             Err(CodexErr::Sandbox(SandboxErr::Timeout { output })) => {
                 Err(CodexErr::Sandbox(SandboxErr::Timeout { output }).into())
             }
+            // Retrying without the sandbox would also lift the limits.
+            Err(CodexErr::Sandbox(SandboxErr::ResourceLimitExceeded { limit, output })) => {
+                Err(CodexErr::Sandbox(SandboxErr::ResourceLimitExceeded { limit, output }).into())
+            }
             Err(CodexErr::Sandbox(error)) => {
                 if sandbox_decision.escalate_on_failure {
                     self.retry_without_sandbox(
@@ -230,6 +242,7 @@ impl Executor {
             &config.sandbox_policy,
             &config.sandbox_cwd,
             &config.codex_linux_sandbox_exe,
+            &config.resource_limits,
             stdout_stream,
         )
         .await
@@ -289,12 +302,12 @@ pub(crate) fn normalize_exec_result(
             borrowed: Some(output),
             synthetic: None,
         },
-        Err(ExecError::Codex(CodexErr::Sandbox(SandboxErr::Timeout { output }))) => {
-            NormalizedExecOutput {
-                borrowed: Some(output.as_ref()),
-                synthetic: None,
-            }
-        }
+        Err(ExecError::Codex(CodexErr::Sandbox(
+            SandboxErr::Timeout { output } | SandboxErr::ResourceLimitExceeded { output, .. },
+        ))) => NormalizedExecOutput {
+            borrowed: Some(output.as_ref()),
+            synthetic: None,
+        },
         Err(err) => {
             let message = match err {
                 ExecError::Function(FunctionCallError::RespondToModel(msg)) => msg.clone(),
@@ -307,6 +320,7 @@ pub(crate) fn normalize_exec_result(
                 aggregated_output: StreamOutput::new(message),
                 duration: Duration::default(),
                 timed_out: false,
+                resource_limit_exceeded: None,
             };
             NormalizedExecOutput {
                 borrowed: None,
@@ -323,6 +337,7 @@ mod tests {
     use crate::error::EnvVarError;
     use crate::error::SandboxErr;
     use crate::exec::StreamOutput;
+    use crate::resource_limits::ResourceLimit;
     use pretty_assertions::assert_eq;
 
     fn make_output(text: &str) -> ExecToolCallOutput {
@@ -333,6 +348,7 @@ mod tests {
             aggregated_output: StreamOutput::new(text.to_string()),
             duration: Duration::from_millis(123),
             timed_out: false,
+            resource_limit_exceeded: None,
         }
     }
 
@@ -358,6 +374,21 @@ mod tests {
         );
     }
 
+    #[test]
+    fn normalize_resource_limit_borrows_embedded_output() {
+        let out = make_output("partial output");
+        let err = CodexErr::Sandbox(SandboxErr::ResourceLimitExceeded {
+            limit: ResourceLimit::Memory,
+            output: Box::new(out),
+        });
+        let result: Result<ExecToolCallOutput, ExecError> = Err(ExecError::Codex(err));
+        let normalized = normalize_exec_result(&result);
+        assert_eq!(
+            normalized.event_output().aggregated_output.text,
+            "partial output"
+        );
+    }
+
     #[test]
     fn normalize_function_error_synthesizes_payload() {
         let err = FunctionCallError::RespondToModel("boom".to_string());
@@ -8,6 +8,8 @@
 mod apply_patch;
 pub mod auth;
 pub mod bash;
+mod budget;
+pub mod cassette;
 mod chat_completions;
 mod client;
 mod client_common;
@@ -18,9 +20,11 @@ pub use codex_conversation::CodexConversation;
 mod command_safety;
 pub mod config;
 pub mod config_edit;
+pub mod config_layers;
 pub mod config_profile;
 pub mod config_types;
 mod conversation_history;
+pub mod credential_store;
 pub mod custom_prompts;
 mod environment_context;
 pub mod error;
@@ -32,6 +36,7 @@ mod flags;
 pub mod git_info;
 pub mod landlock;
 mod mcp_connection_manager;
+pub mod mcp_oauth;
 mod mcp_tool_call;
 mod message_history;
 mod model_provider_info;
@@ -59,11 +64,14 @@ mod openai_model_info;
 mod openai_tools;
 pub mod plan_tool;
 pub mod project_doc;
+pub mod resource_limits;
 mod rollout;
 pub(crate) mod safety;
+mod screenshot;
 pub mod seatbelt;
 pub mod shell;
 pub mod spawn;
+mod sub_agent;
 pub mod terminal;
 mod tool_apply_patch;
 pub mod turn_diff_tracker;
@@ -5,31 +5,76 @@
 //! helpers to query the available tools across *all* servers and returns them
 //! in a single aggregated map using the fully-qualified tool name
 //! `"<server><MCP_TOOL_NAME_DELIMITER><tool>"` as the key.
+//!
+//! Servers are supervised for the lifetime of the session: each has an
+//! [`McpServerStatus`], servers whose connection is lost are restarted with
+//! backoff, and [`McpConnectionManager::refresh`] and
+//! [`McpConnectionManager::reload_global_servers`] pick up tool list changes
+//! and `config.toml` edits between turns.
+//!
+//! Servers may also send requests to Codex. `roots/list` is answered here from
+//! the roots set with [`McpConnectionManager::set_roots`];
+//! `sampling/createMessage` and `elicitation/create` need the model or the
+//! user and are forwarded to the session as [`McpServerRequest`]s.
 
+use std::collections::BTreeMap;
 use std::collections::HashMap;
 use std::collections::HashSet;
 use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
+use std::sync::PoisonError;
+use std::sync::RwLock;
+use std::sync::RwLockReadGuard;
+use std::sync::RwLockWriteGuard;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
+use chrono::DateTime;
+use chrono::Utc;
 use codex_mcp_client::McpClient;
+use codex_mcp_client::ServerRequestHandler;
+use codex_mcp_client::ToolCallNotificationHandler;
 use codex_rmcp_client::RmcpClient;
 use mcp_types::ClientCapabilities;
+use mcp_types::ClientCapabilitiesRoots;
+use mcp_types::CreateMessageRequest;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::ElicitRequest;
+use mcp_types::ElicitRequestParams;
 use mcp_types::Implementation;
+use mcp_types::JSONRPCErrorError;
+use mcp_types::ListRootsRequest;
+use mcp_types::ListRootsResult;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::Root;
 use mcp_types::Tool;
 
+use serde::de::DeserializeOwned;
 use serde_json::json;
 use sha1::Digest;
 use sha1::Sha1;
+use tokio::sync::mpsc;
+use tokio::sync::oneshot;
+use tokio::sync::watch;
+use tokio::task::JoinHandle;
 use tokio::task::JoinSet;
 use tracing::info;
 use tracing::warn;
 
+use crate::config::load_global_mcp_servers;
 use crate::config_types::McpServerConfig;
 use crate::config_types::McpServerTransportConfig;
+use crate::credential_store::CredentialStore;
+use crate::credential_store::mcp_server_credential;
+use crate::mcp_oauth::expires_soon;
+use crate::mcp_oauth::oauth_bearer_token;
+use crate::protocol::McpServerStatus;
+use crate::protocol::SandboxPolicy;
 
 /// Delimiter used to separate the server name from the tool name in a fully
 /// qualified tool name.
@@ -45,10 +90,188 @@ const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
 /// Default timeout for individual tool calls.
 const DEFAULT_TOOL_TIMEOUT: Duration = Duration::from_secs(60);
 
+/// Delay before restarting a server whose restart failed; doubled for every
+/// further failure up to [`MAX_RESTART_BACKOFF`].
+const INITIAL_RESTART_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_RESTART_BACKOFF: Duration = Duration::from_secs(60);
+
+/// Failed restarts after which a server is left stopped until its config
+/// changes.
+const MAX_RESTART_ATTEMPTS: u32 = 5;
+
+/// JSON-RPC error codes used when answering requests from servers.
+const METHOD_NOT_FOUND_ERROR_CODE: i64 = -32601;
+const INVALID_PARAMS_ERROR_CODE: i64 = -32602;
+const INTERNAL_ERROR_CODE: i64 = -32603;
+
 /// Map that holds a startup error for every MCP server that could **not** be
 /// spawned successfully.
 pub type ClientStartErrors = HashMap<String, anyhow::Error>;
 
+/// A request from an MCP server that the session must answer, either by
+/// sampling the model or by asking the user.
+pub(crate) struct McpServerRequest {
+    pub(crate) server_name: String,
+    pub(crate) kind: McpServerRequestKind,
+    /// Receives the JSON result (or error) that is sent back to the server.
+    pub(crate) respond_to: oneshot::Sender<Result<serde_json::Value, JSONRPCErrorError>>,
+}
+
+pub(crate) enum McpServerRequestKind {
+    /// `sampling/createMessage`
+    CreateMessage(CreateMessageRequestParams),
+    /// `elicitation/create`
+    Elicit(ElicitRequestParams),
+}
+
+/// The roots advertised to MCP servers: the working directory followed by
+/// the other roots the sandbox lets Codex write to.
+pub(crate) fn mcp_roots(cwd: &Path, sandbox_policy: &SandboxPolicy) -> Vec<Root> {
+    let mut paths = vec![cwd.to_path_buf()];
+    for writable_root in sandbox_policy.get_writable_roots_with_cwd(cwd) {
+        if !paths.contains(&writable_root.root) {
+            paths.push(writable_root.root);
+        }
+    }
+    paths
+        .into_iter()
+        .filter_map(|path| {
+            let uri = url::Url::from_directory_path(&path).ok()?;
+            Some(Root {
+                name: path
+                    .file_name()
+                    .map(|name| name.to_string_lossy().into_owned()),
+                uri: uri.to_string(),
+            })
+        })
+        .collect()
+}
+
+fn request_error(code: i64, message: impl Into<String>) -> JSONRPCErrorError {
+    JSONRPCErrorError {
+        code,
+        data: None,
+        message: message.into(),
+    }
+}
+
+fn parse_request_params<P: DeserializeOwned>(
+    method: &str,
+    params: Option<serde_json::Value>,
+) -> Result<P, JSONRPCErrorError> {
+    serde_json::from_value(params.unwrap_or_default()).map_err(|e| {
+        request_error(
+            INVALID_PARAMS_ERROR_CODE,
+            format!("invalid params for {method}: {e}"),
+        )
+    })
+}
+
+/// Build the handler that answers requests from `server_name`: `roots/list`
+/// from `roots`, everything the session has to answer through `requests`.
+fn server_request_handler(
+    server_name: String,
+    roots: Arc<RwLock<Vec<Root>>>,
+    requests: mpsc::Sender<McpServerRequest>,
+) -> ServerRequestHandler {
+    Arc::new(move |method: String, params: Option<serde_json::Value>| {
+        let server_name = server_name.clone();
+        let roots = Arc::clone(&roots);
+        let requests = requests.clone();
+        Box::pin(async move {
+            let kind = if method == ListRootsRequest::METHOD {
+                let roots = roots.read().unwrap_or_else(PoisonError::into_inner).clone();
+                return Ok(ListRootsResult { roots }.into());
+            } else if method == CreateMessageRequest::METHOD {
+                McpServerRequestKind::CreateMessage(parse_request_params(&method, params)?)
+            } else if method == ElicitRequest::METHOD {
+                McpServerRequestKind::Elicit(parse_request_params(&method, params)?)
+            } else {
+                return Err(request_error(
+                    METHOD_NOT_FOUND_ERROR_CODE,
+                    format!("method not supported: {method}"),
+                ));
+            };
+
+            let (respond_to, response) = oneshot::channel();
+            requests
+                .send(McpServerRequest {
+                    server_name,
+                    kind,
+                    respond_to,
+                })
+                .await
+                .map_err(|_| request_error(INTERNAL_ERROR_CODE, "the Codex session has ended"))?;
+            response.await.map_err(|_| {
+                request_error(INTERNAL_ERROR_CODE, "the request was dropped by Codex")
+            })?
+        })
+    })
+}
+
+/// A bearer token and, for OAuth access tokens, when it expires.
+struct BearerToken {
+    token: String,
+    /// Whether the token comes from `codex mcp login` and can be refreshed.
+    oauth: bool,
+    expires_at: Option<DateTime<Utc>>,
+}
+
+impl From<String> for BearerToken {
+    fn from(token: String) -> Self {
+        Self {
+            token,
+            oauth: false,
+            expires_at: None,
+        }
+    }
+}
+
+/// The bearer token for streamable HTTP server `server_name`, in order of
+/// preference: `bearer_token` from the config, the `bearer_token_env_var`
+/// environment variable, the OAuth login from `codex mcp login` (refreshed if
+/// needed or if `refresh_oauth` is set), then the token saved with
+/// `codex mcp set-token`.
+async fn resolve_bearer_token(
+    server_name: &str,
+    bearer_token: Option<String>,
+    bearer_token_env_var: Option<String>,
+    codex_home: PathBuf,
+    credential_store: CredentialStore,
+    refresh_oauth: bool,
+) -> Result<Option<BearerToken>> {
+    if let Some(token) = bearer_token {
+        return Ok(Some(token.into()));
+    }
+    if let Some(env_var) = bearer_token_env_var {
+        return match std::env::var(&env_var) {
+            Ok(token) if !token.is_empty() => Ok(Some(token.into())),
+            _ => Err(anyhow!(
+                "environment variable `{env_var}` (bearer_token_env_var for `{server_name}`) is not set"
+            )),
+        };
+    }
+    if let Some(tokens) =
+        oauth_bearer_token(&codex_home, &credential_store, server_name, refresh_oauth)
+            .await
+            .with_context(|| format!("failed to load the OAuth login for `{server_name}`"))?
+    {
+        return Ok(Some(BearerToken {
+            token: tokens.access_token,
+            oauth: true,
+            expires_at: tokens.expires_at,
+        }));
+    }
+    let credential = mcp_server_credential(server_name);
+    let token =
+        tokio::task::spawn_blocking(move || credential_store.load(&codex_home, &credential))
+            .await?
+            .with_context(|| {
+                format!("failed to read the stored bearer token for `{server_name}`")
+            })?;
+    Ok(token.map(BearerToken::from))
+}
+
 fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     let mut used_names = HashSet::new();
     let mut qualified_tools = HashMap::new();
@@ -81,16 +304,67 @@ fn qualify_tools(tools: Vec<ToolInfo>) -> HashMap<String, ToolInfo> {
     qualified_tools
 }
 
+#[derive(Clone)]
 struct ToolInfo {
     server_name: String,
     tool_name: String,
     tool: Tool,
 }
 
+#[derive(Clone)]
 struct ManagedClient {
     client: McpClientAdapter,
     startup_timeout: Duration,
     tool_timeout: Option<Duration>,
+    /// When the OAuth access token the client connected with expires.
+    token_expires_at: Option<DateTime<Utc>>,
+}
+
+impl ManagedClient {
+    fn token_expires_soon(&self) -> bool {
+        self.token_expires_at
+            .is_some_and(|expires_at| expires_soon(expires_at, Utc::now()))
+    }
+}
+
+/// A configured server together with its connection, if it is running.
+struct ManagedServer {
+    config: McpServerConfig,
+    status: McpServerStatus,
+    /// Present while `status` is [`McpServerStatus::Ready`].
+    client: Option<ManagedClient>,
+    /// Tools reported by the server the last time they were listed.
+    tools: Vec<Tool>,
+    /// Failed restarts since the server was last ready.
+    restart_attempts: u32,
+    /// When the server should next be restarted. `None` if it is running or
+    /// will not be restarted automatically.
+    restart_at: Option<Instant>,
+    /// Whether a task is currently starting the server.
+    start_in_flight: bool,
+    /// Whether the next start must refresh the OAuth access token because
+    /// the server rejected it.
+    refresh_oauth_token: bool,
+}
+
+impl ManagedServer {
+    fn new(config: McpServerConfig) -> Self {
+        Self {
+            config,
+            status: McpServerStatus::Starting,
+            client: None,
+            tools: Vec::new(),
+            restart_attempts: 0,
+            restart_at: None,
+            start_in_flight: false,
+            refresh_oauth_token: false,
+        }
+    }
+
+    fn restart_due(&self, now: Instant) -> bool {
+        matches!(self.status, McpServerStatus::Failed { .. })
+            && self.restart_at.is_some_and(|at| at <= now)
+    }
 }
 
 #[derive(Clone)]
@@ -107,30 +381,47 @@ impl McpClientAdapter {
         env: Option<HashMap<String, String>>,
         params: mcp_types::InitializeRequestParams,
         startup_timeout: Duration,
+        request_handler: Option<ServerRequestHandler>,
     ) -> Result<Self> {
         info!(
             "new_stdio_client use_rmcp_client: {use_rmcp_client} program: {program:?} args: {args:?} env: {env:?} params: {params:?} startup_timeout: {startup_timeout:?}"
         );
         if use_rmcp_client {
             let client = Arc::new(RmcpClient::new_stdio_client(program, args, env).await?);
+            if let Some(handler) = request_handler {
+                client.set_server_request_handler(handler);
+            }
             client.initialize(params, Some(startup_timeout)).await?;
             Ok(McpClientAdapter::Rmcp(client))
         } else {
             let client = Arc::new(McpClient::new_stdio_client(program, args, env).await?);
+            if let Some(handler) = request_handler {
+                client.set_server_request_handler(handler);
+            }
             client.initialize(params, Some(startup_timeout)).await?;
             Ok(McpClientAdapter::Legacy(client))
         }
     }
 
+    /// Connect to a streamable HTTP server. On failure, also returns whether
+    /// the server rejected `bearer_token`.
     async fn new_streamable_http_client(
         url: String,
         bearer_token: Option<String>,
         params: mcp_types::InitializeRequestParams,
         startup_timeout: Duration,
-    ) -> Result<Self> {
-        let client = Arc::new(RmcpClient::new_streamable_http_client(url, bearer_token)?);
-        client.initialize(params, Some(startup_timeout)).await?;
-        Ok(McpClientAdapter::Rmcp(client))
+        request_handler: Option<ServerRequestHandler>,
+    ) -> std::result::Result<Self, (anyhow::Error, bool)> {
+        let client = Arc::new(
+            RmcpClient::new_streamable_http_client(url, bearer_token).map_err(|e| (e, false))?,
+        );
+        if let Some(handler) = request_handler {
+            client.set_server_request_handler(handler);
+        }
+        match client.initialize(params, Some(startup_timeout)).await {
+            Ok(_) => Ok(McpClientAdapter::Rmcp(client)),
+            Err(e) => Err((e, client.is_unauthorized())),
+        }
     }
 
     async fn list_tools(
@@ -149,25 +440,119 @@ impl McpClientAdapter {
         name: String,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+        on_notification: Option<ToolCallNotificationHandler>,
     ) -> Result<mcp_types::CallToolResult> {
         match self {
-            McpClientAdapter::Legacy(client) => client.call_tool(name, arguments, timeout).await,
-            McpClientAdapter::Rmcp(client) => client.call_tool(name, arguments, timeout).await,
+            McpClientAdapter::Legacy(client) => {
+                client
+                    .call_tool(name, arguments, timeout, on_notification)
+                    .await
+            }
+            McpClientAdapter::Rmcp(client) => {
+                client
+                    .call_tool(name, arguments, timeout, on_notification)
+                    .await
+            }
+        }
+    }
+
+    async fn is_closed(&self) -> bool {
+        match self {
+            McpClientAdapter::Legacy(client) => client.is_closed(),
+            McpClientAdapter::Rmcp(client) => client.is_closed().await,
+        }
+    }
+
+    fn is_same(&self, other: &McpClientAdapter) -> bool {
+        match (self, other) {
+            (McpClientAdapter::Legacy(a), McpClientAdapter::Legacy(b)) => Arc::ptr_eq(a, b),
+            (McpClientAdapter::Rmcp(a), McpClientAdapter::Rmcp(b)) => Arc::ptr_eq(a, b),
+            _ => false,
+        }
+    }
+
+    fn is_unauthorized(&self) -> bool {
+        match self {
+            McpClientAdapter::Legacy(_) => false,
+            McpClientAdapter::Rmcp(client) => client.is_unauthorized(),
+        }
+    }
+
+    fn take_tools_list_changed(&self) -> bool {
+        match self {
+            McpClientAdapter::Legacy(client) => client.take_tools_list_changed(),
+            McpClientAdapter::Rmcp(client) => client.take_tools_list_changed(),
+        }
+    }
+
+    async fn notify_roots_list_changed(&self) -> Result<()> {
+        match self {
+            McpClientAdapter::Legacy(client) => client.notify_roots_list_changed().await,
+            McpClientAdapter::Rmcp(client) => client.notify_roots_list_changed().await,
         }
     }
 }
 
-/// A thin wrapper around a set of running [`McpClient`] instances.
 #[derive(Default)]
-pub(crate) struct McpConnectionManager {
-    /// Server-name -> client instance.
+struct ManagerState {
+    /// Server-name -> server.
     ///
     /// The server name originates from the keys of the `mcp_servers` map in
     /// the user configuration.
-    clients: HashMap<String, ManagedClient>,
+    servers: HashMap<String, ManagedServer>,
 
-    /// Fully qualified tool name -> tool instance.
+    /// Fully qualified tool name -> tool instance, for every ready server.
     tools: HashMap<String, ToolInfo>,
+
+    /// `mcp_servers` from `CODEX_HOME/config.toml` as last read, used to
+    /// detect servers added, changed or removed while the session runs.
+    global_servers: Option<BTreeMap<String, McpServerConfig>>,
+
+    /// Servers that failed to start in the background, not yet reported by
+    /// [`McpConnectionManager::refresh`].
+    start_errors: ClientStartErrors,
+}
+
+impl ManagerState {
+    fn requalify_tools(&mut self) {
+        let tools = self
+            .servers
+            .iter()
+            .flat_map(|(server_name, server)| {
+                server.tools.iter().map(|tool| ToolInfo {
+                    server_name: server_name.clone(),
+                    tool_name: tool.name.clone(),
+                    tool: tool.clone(),
+                })
+            })
+            .collect();
+        self.tools = qualify_tools(tools);
+    }
+}
+
+/// Owns one MCP client per configured server and keeps them running.
+///
+/// Servers whose connection is lost are restarted with backoff, tools are
+/// re-listed when a server sends `notifications/tools/list_changed`, and
+/// servers added to or removed from `config.toml` while the session runs are
+/// started or stopped. See [`McpConnectionManager::refresh`].
+#[derive(Default)]
+pub(crate) struct McpConnectionManager {
+    use_rmcp_client: bool,
+    codex_home: PathBuf,
+    credential_store: CredentialStore,
+    /// Servers that `config.toml` changes do not affect; see
+    /// `Config::overridden_mcp_servers`.
+    overridden_servers: HashSet<String>,
+    state: Arc<RwLock<ManagerState>>,
+    /// Bumped whenever a server start finishes, for callers waiting on a
+    /// start that is in flight.
+    starts_finished: watch::Sender<u64>,
+    /// Returned to servers for `roots/list`.
+    roots: Arc<RwLock<Vec<Root>>>,
+    /// Where requests the session must answer are sent. Without it servers
+    /// are not given a request handler and their requests are rejected.
+    server_requests: Option<mpsc::Sender<McpServerRequest>>,
 }
 
 impl McpConnectionManager {
@@ -179,227 +564,756 @@ impl McpConnectionManager {
     ///
     /// Servers that fail to start are reported in `ClientStartErrors`: the
     /// user should be informed about these errors.
+    ///
+    /// Streamable HTTP servers without a `bearer_token` or
+    /// `bearer_token_env_var` in their config use the OAuth login or token
+    /// saved for them in `credential_store`, if any.
+    ///
+    /// Servers are offered `roots` and may send sampling and elicitation
+    /// requests, which arrive on the receiver of `server_requests`.
     pub async fn new(
         mcp_servers: HashMap<String, McpServerConfig>,
+        overridden_servers: HashSet<String>,
         use_rmcp_client: bool,
+        codex_home: PathBuf,
+        credential_store: CredentialStore,
+        roots: Vec<Root>,
+        server_requests: mpsc::Sender<McpServerRequest>,
     ) -> Result<(Self, ClientStartErrors)> {
-        // Early exit if no servers are configured.
-        if mcp_servers.is_empty() {
-            return Ok((Self::default(), ClientStartErrors::default()));
-        }
-
-        // Launch all configured servers concurrently.
-        let mut join_set = JoinSet::new();
-        let mut errors = ClientStartErrors::new();
-
-        for (server_name, cfg) in mcp_servers {
-            // Validate server name before spawning
-            if !is_valid_mcp_server_name(&server_name) {
-                let error = anyhow::anyhow!(
-                    "invalid server name '{server_name}': must match pattern ^[a-zA-Z0-9_-]+$"
-                );
-                errors.insert(server_name, error);
-                continue;
-            }
-
-            if matches!(
-                cfg.transport,
-                McpServerTransportConfig::StreamableHttp { .. }
-            ) && !use_rmcp_client
-            {
-                info!(
-                    "skipping MCP server `{}` configured with url because rmcp client is disabled",
-                    server_name
-                );
-                continue;
-            }
-
-            let startup_timeout = cfg.startup_timeout_sec.unwrap_or(DEFAULT_STARTUP_TIMEOUT);
-            let tool_timeout = cfg.tool_timeout_sec.unwrap_or(DEFAULT_TOOL_TIMEOUT);
-
-            let use_rmcp_client_flag = use_rmcp_client;
-            join_set.spawn(async move {
-                let McpServerConfig { transport, .. } = cfg;
-                let params = mcp_types::InitializeRequestParams {
-                    capabilities: ClientCapabilities {
-                        experimental: None,
-                        roots: None,
-                        sampling: None,
-                        // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
-                        // indicates this should be an empty object.
-                        elicitation: Some(json!({})),
-                    },
-                    client_info: Implementation {
-                        name: "codex-mcp-client".to_owned(),
-                        version: env!("CARGO_PKG_VERSION").to_owned(),
-                        title: Some("Codex".into()),
-                        // This field is used by Codex when it is an MCP
-                        // server: it should not be used when Codex is
-                        // an MCP client.
-                        user_agent: None,
-                    },
-                    protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
-                };
-
-                let client = match transport {
-                    McpServerTransportConfig::Stdio { command, args, env } => {
-                        let command_os: OsString = command.into();
-                        let args_os: Vec<OsString> = args.into_iter().map(Into::into).collect();
-                        McpClientAdapter::new_stdio_client(
-                            use_rmcp_client_flag,
-                            command_os,
-                            args_os,
-                            env,
-                            params.clone(),
-                            startup_timeout,
-                        )
-                        .await
-                    }
-                    McpServerTransportConfig::StreamableHttp { url, bearer_token } => {
-                        McpClientAdapter::new_streamable_http_client(
-                            url,
-                            bearer_token,
-                            params,
-                            startup_timeout,
-                        )
-                        .await
-                    }
-                }
-                .map(|c| (c, startup_timeout));
-
-                ((server_name, tool_timeout), client)
-            });
-        }
-
-        let mut clients: HashMap<String, ManagedClient> = HashMap::with_capacity(join_set.len());
-
-        while let Some(res) = join_set.join_next().await {
-            let ((server_name, tool_timeout), client_res) = match res {
-                Ok(result) => result,
+        let global_servers =
+            match load_reloadable_servers(codex_home.clone(), &overridden_servers).await {
+                Ok(servers) => Some(servers),
                 Err(e) => {
-                    warn!("Task panic when starting MCP server: {e:#}");
-                    continue;
+                    warn!("failed to read mcp_servers from config.toml: {e:#}");
+                    None
                 }
             };
 
-            match client_res {
-                Ok((client, startup_timeout)) => {
-                    clients.insert(
-                        server_name,
-                        ManagedClient {
-                            client,
-                            startup_timeout,
-                            tool_timeout: Some(tool_timeout),
-                        },
-                    );
+        let mut errors = ClientStartErrors::new();
+        let mut servers = HashMap::new();
+        for (server_name, cfg) in mcp_servers {
+            match should_manage_server(&server_name, &cfg, use_rmcp_client) {
+                Ok(true) => {
+                    servers.insert(server_name, ManagedServer::new(cfg));
                 }
+                Ok(false) => {}
                 Err(e) => {
                     errors.insert(server_name, e);
                 }
             }
         }
 
-        let all_tools = match list_all_tools(&clients).await {
-            Ok(tools) => tools,
-            Err(e) => {
-                warn!("Failed to list tools from some MCP servers: {e:#}");
-                Vec::new()
-            }
+        let names = servers.keys().cloned().collect();
+        let manager = Self {
+            use_rmcp_client,
+            codex_home,
+            credential_store,
+            overridden_servers,
+            state: Arc::new(RwLock::new(ManagerState {
+                servers,
+                tools: HashMap::new(),
+                global_servers,
+                start_errors: ClientStartErrors::new(),
+            })),
+            starts_finished: watch::Sender::default(),
+            roots: Arc::new(RwLock::new(roots)),
+            server_requests: Some(server_requests),
         };
+        errors.extend(manager.start_servers(names).await);
 
-        let tools = qualify_tools(all_tools);
-
-        Ok((Self { clients, tools }, errors))
+        Ok((manager, errors))
     }
 
     /// Returns a single map that contains **all** tools. Each key is the
     /// fully-qualified name for the tool.
     pub fn list_all_tools(&self) -> HashMap<String, Tool> {
-        self.tools
+        self.read_state()
+            .tools
             .iter()
             .map(|(name, tool)| (name.clone(), tool.tool.clone()))
             .collect()
     }
 
+    /// The status of every configured server.
+    pub fn server_statuses(&self) -> HashMap<String, McpServerStatus> {
+        self.read_state()
+            .servers
+            .iter()
+            .map(|(name, server)| (name.clone(), server.status.clone()))
+            .collect()
+    }
+
     /// Invoke the tool indicated by the (server, tool) pair.
+    ///
+    /// If the server's connection has been lost or its OAuth access token is
+    /// about to expire it is restarted first, unless it is still waiting out
+    /// the backoff from earlier failed restarts. If the server rejects the
+    /// access token, the token is refreshed and the call is retried once on a
+    /// new connection. `on_notification` receives the server's progress and
+    /// log notifications while the call runs. Dropping the returned future
+    /// cancels the call.
     pub async fn call_tool(
         &self,
         server: &str,
         tool: &str,
         arguments: Option<serde_json::Value>,
+        on_notification: Option<ToolCallNotificationHandler>,
     ) -> Result<mcp_types::CallToolResult> {
-        let managed = self
-            .clients
-            .get(server)
-            .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
-        let client = managed.client.clone();
-        let timeout = managed.tool_timeout;
-
-        client
-            .call_tool(tool.to_string(), arguments, timeout)
-            .await
-            .with_context(|| format!("tool call failed for `{server}/{tool}`"))
+        let mut managed = self.running_client(server).await?;
+        let mut result = managed
+            .client
+            .call_tool(
+                tool.to_string(),
+                arguments.clone(),
+                managed.tool_timeout,
+                on_notification.clone(),
+            )
+            .await;
+        if result.is_err() && managed.client.is_unauthorized() {
+            self.schedule_restart(
+                server,
+                &managed.client,
+                "the server rejected the access token",
+                true,
+            );
+            managed = self.running_client(server).await?;
+            result = managed
+                .client
+                .call_tool(
+                    tool.to_string(),
+                    arguments,
+                    managed.tool_timeout,
+                    on_notification,
+                )
+                .await;
+        }
+        if result.is_err() && managed.client.is_closed().await {
+            self.mark_exited(server, &managed.client);
+        }
+        result.with_context(|| format!("tool call failed for `{server}/{tool}`"))
     }
 
     pub fn parse_tool_name(&self, tool_name: &str) -> Option<(String, String)> {
-        self.tools
+        self.read_state()
+            .tools
             .get(tool_name)
             .map(|tool| (tool.server_name.clone(), tool.tool_name.clone()))
     }
-}
 
-/// Query every server for its available tools and return a single map that
-/// contains **all** tools. Each key is the fully-qualified name for the tool.
-async fn list_all_tools(clients: &HashMap<String, ManagedClient>) -> Result<Vec<ToolInfo>> {
-    let mut join_set = JoinSet::new();
-
-    // Spawn one task per server so we can query them concurrently. This
-    // keeps the overall latency roughly at the slowest server instead of
-    // the cumulative latency.
-    for (server_name, managed_client) in clients {
-        let server_name_cloned = server_name.clone();
-        let client_clone = managed_client.client.clone();
-        let startup_timeout = managed_client.startup_timeout;
-        join_set.spawn(async move {
-            let res = client_clone.list_tools(None, Some(startup_timeout)).await;
-            (server_name_cloned, res)
-        });
+    /// Bring the servers up to date between model requests: restart servers
+    /// whose connection was lost once their backoff has elapsed, in the
+    /// background, and re-list the tools of servers that sent
+    /// `notifications/tools/list_changed`.
+    ///
+    /// Returns the servers that failed to start in the background since the
+    /// previous call.
+    pub async fn refresh(&self) -> ClientStartErrors {
+        let running: Vec<(String, McpClientAdapter)> = self
+            .read_state()
+            .servers
+            .iter()
+            .filter_map(|(name, server)| {
+                let client = server.client.as_ref()?;
+                Some((name.clone(), client.client.clone()))
+            })
+            .collect();
+        for (name, client) in running {
+            if client.is_closed().await {
+                self.mark_exited(&name, &client);
+            }
+        }
+
+        let now = Instant::now();
+        let due = self
+            .read_state()
+            .servers
+            .iter()
+            .filter(|(_, server)| server.restart_due(now))
+            .map(|(name, _)| name.clone())
+            .collect();
+        self.start_servers_in_background(due);
+
+        self.relist_changed_tools().await;
+        std::mem::take(&mut self.write_state().start_errors)
     }
 
-    let mut aggregated: Vec<ToolInfo> = Vec::with_capacity(join_set.len());
+    /// Replace the roots returned by `roots/list` and, if they changed, send
+    /// `notifications/roots/list_changed` to every running server.
+    pub async fn set_roots(&self, roots: Vec<Root>) {
+        {
+            let mut current = self.roots.write().unwrap_or_else(PoisonError::into_inner);
+            if *current == roots {
+                return;
+            }
+            *current = roots;
+        }
 
-    while let Some(join_res) = join_set.join_next().await {
-        let (server_name, list_result) = if let Ok(result) = join_res {
-            result
-        } else {
-            warn!("Task panic when listing tools for MCP server: {join_res:#?}");
-            continue;
+        let running: Vec<(String, McpClientAdapter)> = self
+            .read_state()
+            .servers
+            .iter()
+            .filter_map(|(name, server)| {
+                let client = server.client.as_ref()?;
+                Some((name.clone(), client.client.clone()))
+            })
+            .collect();
+        for (name, client) in running {
+            if let Err(e) = client.notify_roots_list_changed().await {
+                warn!("failed to notify MCP server `{name}` of changed roots: {e:#}");
+            }
+        }
+    }
+
+    /// Apply changes to `mcp_servers` in `CODEX_HOME/config.toml` made since
+    /// it was last read, e.g. by `codex mcp add` or `codex mcp remove`.
+    ///
+    /// Servers added or changed there are (re)started in the background and
+    /// removed ones are stopped. Servers that were not touched in the file
+    /// keep the configuration this session started with, and servers the
+    /// session's profile, project config or `-c` overrides exclude or define
+    /// are never affected.
+    ///
+    /// Returns the servers that cannot be started; failures while starting
+    /// are returned by the next [`refresh`](Self::refresh).
+    pub async fn reload_global_servers(&self) -> ClientStartErrors {
+        let mut errors = ClientStartErrors::new();
+        let Some(previous) = self.read_state().global_servers.clone() else {
+            return errors;
         };
 
-        let list_result = if let Ok(result) = list_result {
-            result
-        } else {
-            warn!("Failed to list tools for MCP server '{server_name}': {list_result:#?}");
-            continue;
+        let current =
+            match load_reloadable_servers(self.codex_home.clone(), &self.overridden_servers).await {
+                Ok(servers) => servers,
+                Err(e) => {
+                    warn!("failed to reload mcp_servers from config.toml: {e:#}");
+                    return errors;
+                }
+            };
+        if current == previous {
+            return errors;
+        }
+
+        let mut to_start = Vec::new();
+        {
+            let mut state = self.write_state();
+            for name in previous.keys() {
+                if !current.contains_key(name) && state.servers.remove(name).is_some() {
+                    info!("MCP server `{name}` was removed from config.toml; stopping it");
+                }
+            }
+            for (name, cfg) in &current {
+                if previous.get(name) == Some(cfg) {
+                    continue;
+                }
+                match should_manage_server(name, cfg, self.use_rmcp_client) {
+                    Ok(true) => {
+                        info!(
+                            "MCP server `{name}` was added or changed in config.toml; starting it"
+                        );
+                        state
+                            .servers
+                            .insert(name.clone(), ManagedServer::new(cfg.clone()));
+                        to_start.push(name.clone());
+                    }
+                    Ok(false) => {}
+                    Err(e) => {
+                        errors.insert(name.clone(), e);
+                    }
+                }
+            }
+            state.global_servers = Some(current);
+            state.requalify_tools();
+        }
+
+        self.start_servers_in_background(to_start);
+        errors
+    }
+
+    /// The client for `server`, restarting the server first if its
+    /// connection was lost or its OAuth access token is about to expire and
+    /// a restart is due, and waiting for a start that is already in flight.
+    async fn running_client(&self, server: &str) -> Result<ManagedClient> {
+        let client = {
+            let state = self.read_state();
+            let managed = state
+                .servers
+                .get(server)
+                .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
+            managed.client.clone()
+        };
+        if let Some(client) = client {
+            if client.client.is_closed().await {
+                self.mark_exited(server, &client.client);
+            } else if client.token_expires_soon() {
+                self.schedule_restart(server, &client.client, "the access token expired", false);
+            } else {
+                return Ok(client);
+            }
+        }
+
+        let restart_due = self
+            .read_state()
+            .servers
+            .get(server)
+            .is_some_and(|managed| managed.restart_due(Instant::now()));
+        let mut starts_finished = self.starts_finished.subscribe();
+        if restart_due {
+            self.start_servers(vec![server.to_string()]).await;
+        }
+
+        loop {
+            {
+                let state = self.read_state();
+                let managed = state
+                    .servers
+                    .get(server)
+                    .ok_or_else(|| anyhow!("unknown MCP server '{server}'"))?;
+                match (&managed.client, &managed.status) {
+                    (Some(client), _) => return Ok(client.clone()),
+                    (None, McpServerStatus::Failed { error }) => {
+                        return Err(anyhow!("MCP server `{server}` is not running: {error}"));
+                    }
+                    (None, _) if managed.start_in_flight => {}
+                    (None, _) => return Err(anyhow!("MCP server `{server}` is still starting")),
+                }
+            }
+            if starts_finished.changed().await.is_err() {
+                return Err(anyhow!("MCP server `{server}` is still starting"));
+            }
+        }
+    }
+
+    /// Record that `client`, the connection to `server`, was lost and
+    /// schedule an immediate restart. Does nothing if the server has been
+    /// restarted since `client` was handed out.
+    fn mark_exited(&self, server: &str, client: &McpClientAdapter) {
+        self.schedule_restart(server, client, "connection to the server was lost", false);
+    }
+
+    /// Drop `client`, the connection to `server`, and schedule an immediate
+    /// restart, refreshing the OAuth access token first if `refresh_oauth`
+    /// is set. Does nothing if the server has been restarted since `client`
+    /// was handed out.
+    fn schedule_restart(
+        &self,
+        server: &str,
+        client: &McpClientAdapter,
+        reason: &str,
+        refresh_oauth: bool,
+    ) {
+        let mut state = self.write_state();
+        let Some(managed) = state.servers.get_mut(server) else {
+            return;
+        };
+        if !managed
+            .client
+            .as_ref()
+            .is_some_and(|current| current.client.is_same(client))
+        {
+            return;
+        }
+        managed.client = None;
+        warn!("restarting MCP server `{server}`: {reason}");
+        managed.status = McpServerStatus::Failed {
+            error: reason.to_string(),
+        };
+        // The tools stay listed while the server restarts; calls to them wait
+        // for the restart.
+        managed.restart_at = Some(Instant::now());
+        managed.refresh_oauth_token |= refresh_oauth;
+    }
+
+    /// Start (or restart) the named servers concurrently and record the
+    /// outcome. Servers that are already starting are skipped.
+    async fn start_servers(&self, names: Vec<String>) -> ClientStartErrors {
+        let pending = self.spawn_starts(names);
+        finish_starts(&self.state, &self.starts_finished, pending, false).await
+    }
+
+    /// Like [`start_servers`](Self::start_servers), but without waiting for
+    /// the servers: failures are kept for the next [`refresh`](Self::refresh).
+    fn start_servers_in_background(&self, names: Vec<String>) {
+        let pending = self.spawn_starts(names);
+        if pending.is_empty() {
+            return;
+        }
+        let state = Arc::clone(&self.state);
+        let starts_finished = self.starts_finished.clone();
+        tokio::spawn(async move {
+            finish_starts(&state, &starts_finished, pending, true).await;
+        });
+    }
+
+    /// Mark the named servers as starting and spawn a task connecting to each.
+    fn spawn_starts(&self, names: Vec<String>) -> Vec<PendingStart> {
+        let mut pending = Vec::new();
+        let mut state = self.write_state();
+        for name in names {
+            let Some(managed) = state.servers.get_mut(&name) else {
+                continue;
+            };
+            if managed.start_in_flight {
+                continue;
+            }
+            managed.start_in_flight = true;
+            let restarting = managed.restart_at.take().is_some();
+            let refresh_oauth = std::mem::take(&mut managed.refresh_oauth_token);
+            managed.status = McpServerStatus::Starting;
+            managed.client = None;
+            if !restarting {
+                managed.tools.clear();
+            }
+            let cfg = managed.config.clone();
+            let request_handler = self.server_requests.clone().map(|requests| {
+                server_request_handler(name.clone(), Arc::clone(&self.roots), requests)
+            });
+            let handle = tokio::spawn(start_server(
+                name.clone(),
+                cfg.clone(),
+                self.use_rmcp_client,
+                self.codex_home.clone(),
+                self.credential_store.clone(),
+                refresh_oauth,
+                request_handler,
+            ));
+            pending.push(PendingStart {
+                name,
+                cfg,
+                restarting,
+                handle,
+            });
+        }
+        state.requalify_tools();
+        pending
+    }
+
+    /// Re-list the tools of every ready server that reported a change.
+    async fn relist_changed_tools(&self) {
+        let changed: Vec<(String, ManagedClient)> = self
+            .read_state()
+            .servers
+            .iter()
+            .filter_map(|(name, server)| {
+                let client = server.client.as_ref()?;
+                client
+                    .client
+                    .take_tools_list_changed()
+                    .then(|| (name.clone(), client.clone()))
+            })
+            .collect();
+        if changed.is_empty() {
+            return;
+        }
+
+        // Spawn one task per server so we can query them concurrently. This
+        // keeps the overall latency roughly at the slowest server instead of
+        // the cumulative latency.
+        let mut join_set = JoinSet::new();
+        for (name, managed) in changed {
+            join_set.spawn(async move {
+                let result = managed
+                    .client
+                    .list_tools(None, Some(managed.startup_timeout))
+                    .await;
+                (name, result)
+            });
+        }
+
+        while let Some(join_res) = join_set.join_next().await {
+            let (name, result) = match join_res {
+                Ok(result) => result,
+                Err(e) => {
+                    warn!("Task panic when listing tools for MCP server: {e:#}");
+                    continue;
+                }
+            };
+            match result {
+                Ok(result) => {
+                    info!(
+                        "MCP server `{name}` changed its tools; now has {}",
+                        result.tools.len()
+                    );
+                    let mut state = self.write_state();
+                    if let Some(managed) = state.servers.get_mut(&name)
+                        && managed.client.is_some()
+                    {
+                        managed.tools = result.tools;
+                        state.requalify_tools();
+                    }
+                }
+                Err(e) => {
+                    warn!("Failed to list tools for MCP server '{name}': {e:#}");
+                }
+            }
+        }
+    }
+
+    fn read_state(&self) -> RwLockReadGuard<'_, ManagerState> {
+        self.state.read().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn write_state(&self) -> RwLockWriteGuard<'_, ManagerState> {
+        self.state.write().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// A server start spawned by [`McpConnectionManager::spawn_starts`].
+struct PendingStart {
+    name: String,
+    /// The configuration the server is being started with.
+    cfg: McpServerConfig,
+    restarting: bool,
+    handle: JoinHandle<Result<(ManagedClient, Vec<Tool>)>>,
+}
+
+/// Wait for `pending` starts and record their outcome in `state`, notifying
+/// `starts_finished` after each one. Failures are returned or, for starts in
+/// the `background`, kept in `state` for the next refresh.
+async fn finish_starts(
+    state: &RwLock<ManagerState>,
+    starts_finished: &watch::Sender<u64>,
+    pending: Vec<PendingStart>,
+    background: bool,
+) -> ClientStartErrors {
+    let mut errors = ClientStartErrors::new();
+    for PendingStart {
+        name,
+        cfg,
+        restarting,
+        handle,
+    } in pending
+    {
+        let result = match handle.await {
+            Ok(result) => result,
+            Err(e) => Err(anyhow!("task panicked while starting MCP server: {e}")),
         };
 
-        for tool in list_result.tools {
-            let tool_info = ToolInfo {
-                server_name: server_name.clone(),
-                tool_name: tool.name.clone(),
-                tool,
+        if let Some(e) = record_start(state, &name, &cfg, restarting, result) {
+            if background {
+                warn!("MCP server `{name}` failed to start: {e:#}");
+                state
+                    .write()
+                    .unwrap_or_else(PoisonError::into_inner)
+                    .start_errors
+                    .insert(name, e);
+            } else {
+                errors.insert(name, e);
+            }
+        }
+        starts_finished.send_modify(|finished| *finished += 1);
+    }
+    errors
+}
+
+/// Record the outcome of starting `name` with `cfg`. Returns the error if the
+/// start failed.
+fn record_start(
+    state: &RwLock<ManagerState>,
+    name: &str,
+    cfg: &McpServerConfig,
+    restarting: bool,
+    result: Result<(ManagedClient, Vec<Tool>)>,
+) -> Option<anyhow::Error> {
+    let mut state = state.write().unwrap_or_else(PoisonError::into_inner);
+    let managed = state.servers.get_mut(name)?;
+    if managed.config != *cfg {
+        // The server was reconfigured while it was starting; the newer start
+        // owns it now.
+        return None;
+    }
+    managed.start_in_flight = false;
+    let error = match result {
+        Ok((client, tools)) => {
+            managed.status = McpServerStatus::Ready;
+            managed.client = Some(client);
+            managed.tools = tools;
+            managed.restart_attempts = 0;
+            None
+        }
+        Err(e) => {
+            managed.status = McpServerStatus::Failed {
+                error: format!("{e:#}"),
             };
-            aggregated.push(tool_info);
+            managed.tools.clear();
+            if restarting {
+                managed.restart_attempts += 1;
+                if managed.restart_attempts < MAX_RESTART_ATTEMPTS {
+                    managed.restart_at =
+                        Some(Instant::now() + restart_backoff(managed.restart_attempts));
+                } else {
+                    warn!(
+                        "giving up on MCP server `{name}` after {MAX_RESTART_ATTEMPTS} failed restarts"
+                    );
+                }
+            }
+            Some(e)
         }
+    };
+    state.requalify_tools();
+    error
+}
+
+/// `mcp_servers` from `CODEX_HOME/config.toml`, without `overridden` ones.
+async fn load_reloadable_servers(
+    codex_home: PathBuf,
+    overridden: &HashSet<String>,
+) -> Result<BTreeMap<String, McpServerConfig>> {
+    let mut servers =
+        tokio::task::spawn_blocking(move || load_global_mcp_servers(&codex_home)).await??;
+    servers.retain(|name, _| !overridden.contains(name));
+    Ok(servers)
+}
+
+/// Whether a server from the config should be started: `Err` for invalid
+/// names, `Ok(false)` for servers that cannot be used in this session.
+fn should_manage_server(
+    server_name: &str,
+    cfg: &McpServerConfig,
+    use_rmcp_client: bool,
+) -> Result<bool> {
+    if !is_valid_mcp_server_name(server_name) {
+        return Err(anyhow!(
+            "invalid server name '{server_name}': must match pattern ^[a-zA-Z0-9_-]+$"
+        ));
+    }
+
+    if matches!(
+        cfg.transport,
+        McpServerTransportConfig::StreamableHttp { .. }
+    ) && !use_rmcp_client
+    {
+        info!(
+            "skipping MCP server `{}` configured with url because rmcp client is disabled",
+            server_name
+        );
+        return Ok(false);
     }
 
+    Ok(true)
+}
+
+/// Delay before the `attempt`-th restart of a server whose previous restart
+/// failed: doubled every time, up to [`MAX_RESTART_BACKOFF`].
+fn restart_backoff(attempt: u32) -> Duration {
+    let exponent = attempt.saturating_sub(1).min(16);
+    INITIAL_RESTART_BACKOFF
+        .saturating_mul(1 << exponent)
+        .min(MAX_RESTART_BACKOFF)
+}
+
+/// Connect to `server_name` and list its tools.
+async fn start_server(
+    server_name: String,
+    cfg: McpServerConfig,
+    use_rmcp_client: bool,
+    codex_home: PathBuf,
+    credential_store: CredentialStore,
+    refresh_oauth: bool,
+    request_handler: Option<ServerRequestHandler>,
+) -> Result<(ManagedClient, Vec<Tool>)> {
+    let startup_timeout = cfg.startup_timeout_sec.unwrap_or(DEFAULT_STARTUP_TIMEOUT);
+    let tool_timeout = cfg.tool_timeout_sec.unwrap_or(DEFAULT_TOOL_TIMEOUT);
+    let params = mcp_types::InitializeRequestParams {
+        capabilities: ClientCapabilities {
+            experimental: None,
+            roots: Some(ClientCapabilitiesRoots {
+                list_changed: Some(true),
+            }),
+            sampling: Some(json!({})),
+            // https://modelcontextprotocol.io/specification/2025-06-18/client/elicitation#capabilities
+            // indicates this should be an empty object.
+            elicitation: Some(json!({})),
+        },
+        client_info: Implementation {
+            name: "codex-mcp-client".to_owned(),
+            version: env!("CARGO_PKG_VERSION").to_owned(),
+            title: Some("Codex".into()),
+            // This field is used by Codex when it is an MCP
+            // server: it should not be used when Codex is
+            // an MCP client.
+            user_agent: None,
+        },
+        protocol_version: mcp_types::MCP_SCHEMA_VERSION.to_owned(),
+    };
+
+    let mut token_expires_at = None;
+    let client = match cfg.transport {
+        McpServerTransportConfig::Stdio { command, args, env } => {
+            let command_os: OsString = command.into();
+            let args_os: Vec<OsString> = args.into_iter().map(Into::into).collect();
+            McpClientAdapter::new_stdio_client(
+                use_rmcp_client,
+                command_os,
+                args_os,
+                env,
+                params,
+                startup_timeout,
+                request_handler,
+            )
+            .await?
+        }
+        McpServerTransportConfig::StreamableHttp {
+            url,
+            bearer_token,
+            bearer_token_env_var,
+        } => {
+            let mut refresh_oauth = refresh_oauth;
+            loop {
+                let token = resolve_bearer_token(
+                    &server_name,
+                    bearer_token.clone(),
+                    bearer_token_env_var.clone(),
+                    codex_home.clone(),
+                    credential_store.clone(),
+                    refresh_oauth,
+                )
+                .await?;
+                let oauth = token.as_ref().is_some_and(|token| token.oauth);
+                token_expires_at = token.as_ref().and_then(|token| token.expires_at);
+                match McpClientAdapter::new_streamable_http_client(
+                    url.clone(),
+                    token.map(|token| token.token),
+                    params.clone(),
+                    startup_timeout,
+                    request_handler.clone(),
+                )
+                .await
+                {
+                    Ok(client) => break client,
+                    // A token revoked before it expired: refresh it once.
+                    Err((_, true)) if oauth && !refresh_oauth => {
+                        warn!(
+                            "MCP server `{server_name}` rejected its access token; refreshing it"
+                        );
+                        refresh_oauth = true;
+                    }
+                    Err((e, _)) => return Err(e),
+                }
+            }
+        }
+    };
+
+    let tools = match client.list_tools(None, Some(startup_timeout)).await {
+        Ok(result) => result.tools,
+        Err(e) => {
+            warn!("Failed to list tools for MCP server '{server_name}': {e:#}");
+            Vec::new()
+        }
+    };
     info!(
-        "aggregated {} tools from {} servers",
-        aggregated.len(),
-        clients.len()
+        "MCP server `{server_name}` is ready with {} tools",
+        tools.len()
     );
 
-    Ok(aggregated)
+    Ok((
+        ManagedClient {
+            client,
+            startup_timeout,
+            tool_timeout: Some(tool_timeout),
+            token_expires_at,
+        },
+        tools,
+    ))
 }
 
 fn is_valid_mcp_server_name(server_name: &str) -> bool {
@@ -412,7 +1326,10 @@ fn is_valid_mcp_server_name(server_name: &str) -> bool {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::config::write_global_mcp_servers;
     use mcp_types::ToolInputSchema;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
 
     fn create_test_tool(server_name: &str, tool_name: &str) -> ToolInfo {
         ToolInfo {
@@ -495,4 +1412,222 @@ mod tests {
             "my_server__yet_another_e1c3987bd9c50b826cbe1687966f79f0c602d19ca"
         );
     }
+
+    fn stdio_server(command: &str) -> McpServerConfig {
+        McpServerConfig {
+            transport: McpServerTransportConfig::Stdio {
+                command: command.to_string(),
+                args: Vec::new(),
+                env: None,
+            },
+            startup_timeout_sec: None,
+            tool_timeout_sec: None,
+        }
+    }
+
+    fn sorted_names<V>(map: HashMap<String, V>) -> Vec<String> {
+        let mut names: Vec<String> = map.into_keys().collect();
+        names.sort();
+        names
+    }
+
+    /// Wait until no server start is in flight.
+    async fn wait_for_starts(manager: &McpConnectionManager) {
+        let mut starts_finished = manager.starts_finished.subscribe();
+        while manager
+            .read_state()
+            .servers
+            .values()
+            .any(|server| server.start_in_flight)
+        {
+            starts_finished.changed().await.unwrap();
+        }
+    }
+
+    #[test]
+    fn restart_backoff_doubles_up_to_max() {
+        assert_eq!(restart_backoff(1), Duration::from_secs(1));
+        assert_eq!(restart_backoff(2), Duration::from_secs(2));
+        assert_eq!(restart_backoff(4), Duration::from_secs(8));
+        assert_eq!(restart_backoff(10), MAX_RESTART_BACKOFF);
+    }
+
+    #[tokio::test]
+    async fn reload_global_servers_applies_config_toml_changes() {
+        let codex_home = tempdir().unwrap();
+        // The command does not exist, so every server fails to start; that is
+        // enough to observe which servers the manager is tracking.
+        let missing = codex_home.path().join("missing-mcp-server");
+        let server = stdio_server(&missing.to_string_lossy());
+        write_global_mcp_servers(
+            codex_home.path(),
+            &BTreeMap::from([
+                ("kept".to_string(), server.clone()),
+                ("removed".to_string(), server.clone()),
+            ]),
+        )
+        .unwrap();
+
+        let (manager, errors) = McpConnectionManager::new(
+            HashMap::from([
+                ("kept".to_string(), server.clone()),
+                ("removed".to_string(), server.clone()),
+                ("session_only".to_string(), server.clone()),
+            ]),
+            HashSet::new(),
+            false,
+            codex_home.path().to_path_buf(),
+            CredentialStore::File,
+            Vec::new(),
+            mpsc::channel(1).0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(
+            sorted_names(errors),
+            vec!["kept", "removed", "session_only"]
+        );
+        assert!(matches!(
+            manager.server_statuses()["kept"],
+            McpServerStatus::Failed { .. }
+        ));
+
+        // Nothing changed on disk yet.
+        assert!(manager.reload_global_servers().await.is_empty());
+
+        write_global_mcp_servers(
+            codex_home.path(),
+            &BTreeMap::from([
+                ("added".to_string(), server.clone()),
+                ("kept".to_string(), server.clone()),
+            ]),
+        )
+        .unwrap();
+        // `added` is started in the background and its failure reported by
+        // the next refresh.
+        assert!(manager.reload_global_servers().await.is_empty());
+        assert_eq!(
+            sorted_names(manager.server_statuses()),
+            vec!["added", "kept", "session_only"]
+        );
+        wait_for_starts(&manager).await;
+        assert_eq!(sorted_names(manager.refresh().await), vec!["added"]);
+
+        // Servers that failed their first start are not restarted.
+        assert!(manager.refresh().await.is_empty());
+        wait_for_starts(&manager).await;
+        assert!(manager.refresh().await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn reload_global_servers_leaves_overridden_servers_alone() {
+        let codex_home = tempdir().unwrap();
+        let missing = codex_home.path().join("missing-mcp-server");
+        let server = stdio_server(&missing.to_string_lossy());
+        write_global_mcp_servers(
+            codex_home.path(),
+            &BTreeMap::from([("excluded".to_string(), server.clone())]),
+        )
+        .unwrap();
+
+        // The active profile excludes `excluded` and defines `pinned` itself.
+        let (manager, errors) = McpConnectionManager::new(
+            HashMap::from([("pinned".to_string(), server.clone())]),
+            HashSet::from(["excluded".to_string(), "pinned".to_string()]),
+            false,
+            codex_home.path().to_path_buf(),
+            CredentialStore::File,
+            Vec::new(),
+            mpsc::channel(1).0,
+        )
+        .await
+        .unwrap();
+        assert_eq!(sorted_names(errors), vec!["pinned"]);
+
+        let changed = stdio_server(&codex_home.path().join("other").to_string_lossy());
+        write_global_mcp_servers(
+            codex_home.path(),
+            &BTreeMap::from([
+                ("excluded".to_string(), changed.clone()),
+                ("pinned".to_string(), changed),
+            ]),
+        )
+        .unwrap();
+        assert!(manager.reload_global_servers().await.is_empty());
+        assert_eq!(sorted_names(manager.server_statuses()), vec!["pinned"]);
+        assert!(!manager.read_state().servers["pinned"].start_in_flight);
+    }
+
+    #[test]
+    fn mcp_roots_lists_cwd_then_other_writable_roots() {
+        let cwd = tempdir().unwrap();
+        let extra = tempdir().unwrap();
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![extra.path().to_path_buf(), cwd.path().to_path_buf()],
+            network_access: false,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: true,
+        };
+
+        let uris: Vec<String> = mcp_roots(cwd.path(), &policy)
+            .into_iter()
+            .map(|root| root.uri)
+            .collect();
+        assert_eq!(
+            uris,
+            vec![
+                url::Url::from_directory_path(cwd.path())
+                    .unwrap()
+                    .to_string(),
+                url::Url::from_directory_path(extra.path())
+                    .unwrap()
+                    .to_string(),
+            ]
+        );
+        assert_eq!(mcp_roots(cwd.path(), &SandboxPolicy::ReadOnly).len(), 1);
+    }
+
+    #[tokio::test]
+    async fn server_request_handler_answers_roots_and_forwards_sampling() {
+        let roots = Arc::new(RwLock::new(vec![Root {
+            name: Some("project".to_string()),
+            uri: "file:///project/".to_string(),
+        }]));
+        let (tx, mut rx) = mpsc::channel(1);
+        let handler = server_request_handler("docs".to_string(), roots, tx);
+
+        let result = handler(ListRootsRequest::METHOD.to_string(), None)
+            .await
+            .unwrap();
+        assert_eq!(
+            result,
+            json!({ "roots": [{ "name": "project", "uri": "file:///project/" }] })
+        );
+
+        let err = handler("resources/unknown".to_string(), None)
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, METHOD_NOT_FOUND_ERROR_CODE);
+
+        let err = handler(CreateMessageRequest::METHOD.to_string(), Some(json!({})))
+            .await
+            .unwrap_err();
+        assert_eq!(err.code, INVALID_PARAMS_ERROR_CODE);
+
+        let pending = tokio::spawn(handler(
+            CreateMessageRequest::METHOD.to_string(),
+            Some(json!({
+                "maxTokens": 100,
+                "messages": [{ "role": "user", "content": { "type": "text", "text": "hi" } }],
+            })),
+        ));
+        let request = rx.recv().await.unwrap();
+        assert_eq!(request.server_name, "docs");
+        let McpServerRequestKind::CreateMessage(params) = request.kind else {
+            panic!("expected a sampling request");
+        };
+        assert_eq!(params.max_tokens, 100);
+        request.respond_to.send(Ok(json!({ "ok": true }))).unwrap();
+        assert_eq!(pending.await.unwrap().unwrap(), json!({ "ok": true }));
+    }
 }
@@ -0,0 +1,327 @@
+//! OAuth tokens for streamable HTTP MCP servers.
+//!
+//! `codex mcp login <server>` (see `codex-login`) runs the authorization-code
+//! flow and saves an [`McpOAuthTokens`] in the credential store. When Codex
+//! connects to the server it loads the tokens with [`oauth_bearer_token`],
+//! refreshing them first if the access token is about to expire or the server
+//! rejected it.
+
+use std::io;
+use std::path::Path;
+
+use chrono::DateTime;
+use chrono::Duration;
+use chrono::Utc;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::credential_store::CredentialStore;
+
+/// Access tokens expiring within this window are refreshed before use.
+const REFRESH_MARGIN_SECS: i64 = 60;
+
+/// Name of the credential holding the OAuth tokens for MCP server `server`.
+pub fn mcp_server_oauth_credential(server: &str) -> String {
+    format!("mcp-oauth/{server}")
+}
+
+/// Tokens obtained by `codex mcp login`, along with what is needed to refresh
+/// them.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct McpOAuthTokens {
+    /// Client id issued by dynamic registration or supplied by the user.
+    pub client_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+    pub token_endpoint: String,
+    /// The MCP server URL, sent as the RFC 8707 `resource` parameter.
+    pub resource: String,
+    pub access_token: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub refresh_token: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+}
+
+/// Successful response from an OAuth token endpoint (RFC 6749 section 5.1).
+#[derive(Debug, Clone, Deserialize)]
+pub struct TokenResponse {
+    pub access_token: String,
+    #[serde(default)]
+    pub expires_in: Option<i64>,
+    #[serde(default)]
+    pub refresh_token: Option<String>,
+    #[serde(default)]
+    pub scope: Option<String>,
+}
+
+impl McpOAuthTokens {
+    pub fn load(
+        codex_home: &Path,
+        credential_store: &CredentialStore,
+        server: &str,
+    ) -> io::Result<Option<Self>> {
+        match credential_store.load(codex_home, &mcp_server_oauth_credential(server))? {
+            Some(contents) => Ok(Some(serde_json::from_str(&contents)?)),
+            None => Ok(None),
+        }
+    }
+
+    pub fn save(
+        &self,
+        codex_home: &Path,
+        credential_store: &CredentialStore,
+        server: &str,
+    ) -> io::Result<()> {
+        let contents = serde_json::to_string(self)?;
+        credential_store.save(codex_home, &mcp_server_oauth_credential(server), &contents)
+    }
+
+    pub fn erase(
+        codex_home: &Path,
+        credential_store: &CredentialStore,
+        server: &str,
+    ) -> io::Result<bool> {
+        credential_store.erase(codex_home, &mcp_server_oauth_credential(server))
+    }
+
+    /// Replace the tokens with those in `response`. The refresh token and
+    /// scope are kept when the server does not send new ones.
+    pub fn apply_token_response(&mut self, response: TokenResponse, now: DateTime<Utc>) {
+        self.access_token = response.access_token;
+        self.expires_at = response
+            .expires_in
+            .map(|expires_in| now + Duration::seconds(expires_in));
+        if let Some(refresh_token) = response.refresh_token {
+            self.refresh_token = Some(refresh_token);
+        }
+        if let Some(scope) = response.scope {
+            self.scope = Some(scope);
+        }
+    }
+
+    fn needs_refresh(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| expires_soon(expires_at, now))
+    }
+
+    /// Exchange the refresh token for a new access token.
+    pub async fn refresh(&mut self, client: &reqwest::Client) -> io::Result<()> {
+        let Some(refresh_token) = self.refresh_token.clone() else {
+            return Err(io::Error::other(
+                "the MCP access token expired and no refresh token is available; run `codex mcp login` again",
+            ));
+        };
+        let mut form = vec![
+            ("grant_type", "refresh_token"),
+            ("refresh_token", refresh_token.as_str()),
+            ("client_id", self.client_id.as_str()),
+            ("resource", self.resource.as_str()),
+        ];
+        if let Some(client_secret) = &self.client_secret {
+            form.push(("client_secret", client_secret.as_str()));
+        }
+        let response = request_tokens(client, &self.token_endpoint, &form).await?;
+        self.apply_token_response(response, Utc::now());
+        Ok(())
+    }
+}
+
+/// Whether an access token expiring at `expires_at` should be refreshed
+/// before it is used at `now`.
+pub fn expires_soon(expires_at: DateTime<Utc>, now: DateTime<Utc>) -> bool {
+    expires_at - Duration::seconds(REFRESH_MARGIN_SECS) <= now
+}
+
+/// POST `form` to `token_endpoint` and parse the token response.
+pub async fn request_tokens(
+    client: &reqwest::Client,
+    token_endpoint: &str,
+    form: &[(&str, &str)],
+) -> io::Result<TokenResponse> {
+    let response = client
+        .post(token_endpoint)
+        .header("Accept", "application/json")
+        .form(form)
+        .send()
+        .await
+        .map_err(io::Error::other)?;
+
+    let status = response.status();
+    if !status.is_success() {
+        let body = response.text().await.unwrap_or_default();
+        return Err(io::Error::other(format!(
+            "token endpoint returned status {status}: {}",
+            body.trim()
+        )));
+    }
+    response.json().await.map_err(io::Error::other)
+}
+
+/// The tokens saved for `server` by `codex mcp login`, refreshed and saved
+/// again if the access token is about to expire or `force_refresh` is set,
+/// e.g. because the server rejected it. `Ok(None)` if the server has no OAuth
+/// login.
+pub async fn oauth_bearer_token(
+    codex_home: &Path,
+    credential_store: &CredentialStore,
+    server: &str,
+    force_refresh: bool,
+) -> io::Result<Option<McpOAuthTokens>> {
+    let load = {
+        let codex_home = codex_home.to_path_buf();
+        let credential_store = credential_store.clone();
+        let server = server.to_string();
+        move || McpOAuthTokens::load(&codex_home, &credential_store, &server)
+    };
+    let Some(mut tokens) = tokio::task::spawn_blocking(load)
+        .await
+        .map_err(io::Error::other)??
+    else {
+        return Ok(None);
+    };
+
+    if force_refresh || tokens.needs_refresh(Utc::now()) {
+        let client = crate::default_client::create_client();
+        tokens.refresh(&client).await?;
+        let codex_home = codex_home.to_path_buf();
+        let credential_store = credential_store.clone();
+        let server = server.to_string();
+        let saved = tokens.clone();
+        tokio::task::spawn_blocking(move || saved.save(&codex_home, &credential_store, &server))
+            .await
+            .map_err(io::Error::other)??;
+    }
+    Ok(Some(tokens))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use tempfile::tempdir;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::body_string_contains;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    fn tokens(token_endpoint: String, expires_at: Option<DateTime<Utc>>) -> McpOAuthTokens {
+        McpOAuthTokens {
+            client_id: "client-1".to_string(),
+            client_secret: None,
+            token_endpoint,
+            resource: "https://mcp.example.com/mcp".to_string(),
+            access_token: "access-old".to_string(),
+            refresh_token: Some("refresh-1".to_string()),
+            expires_at,
+            scope: Some("read".to_string()),
+        }
+    }
+
+    #[tokio::test]
+    async fn returns_unexpired_token_without_refreshing() {
+        let codex_home = tempdir().unwrap();
+        let store = CredentialStore::File;
+        tokens(
+            "http://127.0.0.1:9/token".to_string(),
+            Some(Utc::now() + Duration::hours(1)),
+        )
+        .save(codex_home.path(), &store, "docs")
+        .unwrap();
+
+        let token = oauth_bearer_token(codex_home.path(), &store, "docs", false)
+            .await
+            .unwrap()
+            .map(|tokens| tokens.access_token);
+        assert_eq!(token.as_deref(), Some("access-old"));
+        assert_eq!(
+            oauth_bearer_token(codex_home.path(), &store, "other", false)
+                .await
+                .unwrap(),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn refreshes_expired_token_and_saves_it() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("grant_type=refresh_token"))
+            .and(body_string_contains("refresh_token=refresh-1"))
+            .and(body_string_contains("client_id=client-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "access-new",
+                "token_type": "Bearer",
+                "expires_in": 3600,
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let codex_home = tempdir().unwrap();
+        let store = CredentialStore::File;
+        tokens(
+            format!("{}/token", server.uri()),
+            Some(Utc::now() - Duration::minutes(5)),
+        )
+        .save(codex_home.path(), &store, "docs")
+        .unwrap();
+
+        let token = oauth_bearer_token(codex_home.path(), &store, "docs", false)
+            .await
+            .unwrap()
+            .map(|tokens| tokens.access_token);
+        assert_eq!(token.as_deref(), Some("access-new"));
+
+        let saved = McpOAuthTokens::load(codex_home.path(), &store, "docs")
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved.access_token, "access-new");
+        // The server did not rotate the refresh token, so the old one is kept.
+        assert_eq!(saved.refresh_token.as_deref(), Some("refresh-1"));
+        assert_eq!(saved.scope.as_deref(), Some("read"));
+        assert!(saved.expires_at.unwrap() > Utc::now() + Duration::minutes(59));
+    }
+
+    #[tokio::test]
+    async fn refreshes_rejected_token_before_it_expires() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/token"))
+            .and(body_string_contains("refresh_token=refresh-1"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "access_token": "access-new",
+                "token_type": "Bearer",
+                "refresh_token": "refresh-2",
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let codex_home = tempdir().unwrap();
+        let store = CredentialStore::File;
+        tokens(
+            format!("{}/token", server.uri()),
+            Some(Utc::now() + Duration::hours(1)),
+        )
+        .save(codex_home.path(), &store, "docs")
+        .unwrap();
+
+        let refreshed = oauth_bearer_token(codex_home.path(), &store, "docs", true)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(refreshed.access_token, "access-new");
+        assert_eq!(refreshed.expires_at, None);
+        let saved = McpOAuthTokens::load(codex_home.path(), &store, "docs")
+            .unwrap()
+            .unwrap();
+        assert_eq!(saved, refreshed);
+        assert_eq!(saved.refresh_token.as_deref(), Some("refresh-2"));
+    }
+}
@@ -1,18 +1,33 @@
+use std::sync::Arc;
 use std::time::Instant;
 
+use codex_mcp_client::ToolCallNotificationHandler;
+use mcp_types::ContentBlock;
+use mcp_types::EmbeddedResourceResource;
+use mcp_types::ServerNotification;
+use tokio::sync::mpsc;
 use tracing::error;
+use tracing::warn;
 
 use crate::codex::Session;
 use crate::protocol::Event;
 use crate::protocol::EventMsg;
+use crate::protocol::InputItem;
 use crate::protocol::McpInvocation;
 use crate::protocol::McpToolCallBeginEvent;
 use crate::protocol::McpToolCallEndEvent;
+use crate::protocol::McpToolCallProgressEvent;
 use codex_protocol::models::FunctionCallOutputPayload;
 use codex_protocol::models::ResponseInputItem;
 
+/// MCP tool output sent to the model is truncated in the middle beyond this
+/// many bytes.
+pub(crate) const MCP_TOOL_OUTPUT_MAX_BYTES: usize = 32 * 1024;
+
 /// Handles the specified tool call dispatches the appropriate
-/// `McpToolCallBegin` and `McpToolCallEnd` events to the `Session`.
+/// `McpToolCallBegin`, `McpToolCallProgress` and `McpToolCallEnd` events to
+/// the `Session`. If the turn is interrupted while the call runs, dropping
+/// this future tells the server to cancel it.
 pub(crate) async fn handle_mcp_tool_call(
     sess: &Session,
     sub_id: &str,
@@ -53,12 +68,35 @@ pub(crate) async fn handle_mcp_tool_call(
     });
     notify_mcp_tool_call_event(sess, sub_id, tool_call_begin_event).await;
 
+    // The client calls the handler from its reader task, so updates are
+    // queued here and sent as events while we wait for the result.
+    let (progress_tx, mut progress_rx) = mpsc::unbounded_channel();
+    let progress_call_id = call_id.clone();
+    let on_notification: ToolCallNotificationHandler = Arc::new(move |notification| {
+        if let Some(event) = progress_event(&progress_call_id, notification) {
+            let _ = progress_tx.send(event);
+        }
+    });
+
     let start = Instant::now();
     // Perform the tool call.
-    let result = sess
-        .call_tool(&server, &tool_name, arguments_value.clone())
-        .await
-        .map_err(|e| format!("tool call error: {e}"));
+    let call = sess.call_tool(
+        &server,
+        &tool_name,
+        arguments_value.clone(),
+        Some(on_notification),
+    );
+    tokio::pin!(call);
+    let result = loop {
+        tokio::select! {
+            result = &mut call => break result,
+            Some(event) = progress_rx.recv() => {
+                notify_mcp_tool_call_event(sess, sub_id, EventMsg::McpToolCallProgress(event))
+                    .await;
+            }
+        }
+    }
+    .map_err(|e| format!("tool call error: {e}"));
     let tool_call_end_event = EventMsg::McpToolCallEnd(McpToolCallEndEvent {
         call_id: call_id.clone(),
         invocation,
@@ -68,9 +106,119 @@ pub(crate) async fn handle_mcp_tool_call(
 
     notify_mcp_tool_call_event(sess, sub_id, tool_call_end_event.clone()).await;
 
+    // Like `view_image`, images returned by the tool are attached to the next
+    // request rather than sent to the model as base64 text.
+    if let Ok(call_tool_result) = &result {
+        let images = image_inputs(&call_tool_result.content);
+        if !images.is_empty() && sess.inject_input(images).await.is_err() {
+            warn!("unable to attach images from `{server}/{tool_name}` (no active task)");
+        }
+    }
+
     ResponseInputItem::McpToolCallOutput { call_id, result }
 }
 
+/// The text of an MCP tool result for the model. Text blocks and embedded
+/// text resources are included as is; other blocks are replaced by a short
+/// description. Images are sent separately, see [`image_inputs`].
+pub(crate) fn content_to_text(content: &[ContentBlock]) -> String {
+    content
+        .iter()
+        .map(|block| match block {
+            ContentBlock::TextContent(text) => text.text.clone(),
+            ContentBlock::ImageContent(image) => {
+                format!("[image ({}) attached below]", image.mime_type)
+            }
+            ContentBlock::AudioContent(audio) => {
+                format!("[audio ({}) omitted]", audio.mime_type)
+            }
+            ContentBlock::ResourceLink(link) => {
+                let name = link.title.as_deref().unwrap_or(&link.name);
+                match &link.description {
+                    Some(description) => {
+                        format!("[resource link: {name} <{}>] {description}", link.uri)
+                    }
+                    None => format!("[resource link: {name} <{}>]", link.uri),
+                }
+            }
+            ContentBlock::EmbeddedResource(resource) => match &resource.resource {
+                EmbeddedResourceResource::TextResourceContents(text) => {
+                    format!("[resource: {}]\n{}", text.uri, text.text)
+                }
+                EmbeddedResourceResource::BlobResourceContents(blob) => {
+                    let mime_type = blob.mime_type.as_deref().unwrap_or("unknown type");
+                    if is_image_mime_type(blob.mime_type.as_deref()) {
+                        format!("[resource: {} ({mime_type}) attached below]", blob.uri)
+                    } else {
+                        format!("[resource: {} ({mime_type}) omitted]", blob.uri)
+                    }
+                }
+            },
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Image blocks and embedded image resources in `content`, as inputs for
+/// the next model request.
+pub(crate) fn image_inputs(content: &[ContentBlock]) -> Vec<InputItem> {
+    content
+        .iter()
+        .filter_map(|block| match block {
+            ContentBlock::ImageContent(image) => Some(InputItem::Image {
+                image_url: format!("data:{};base64,{}", image.mime_type, image.data),
+            }),
+            ContentBlock::EmbeddedResource(resource) => match &resource.resource {
+                EmbeddedResourceResource::BlobResourceContents(blob)
+                    if is_image_mime_type(blob.mime_type.as_deref()) =>
+                {
+                    Some(InputItem::Image {
+                        image_url: format!(
+                            "data:{};base64,{}",
+                            blob.mime_type.as_deref().unwrap_or_default(),
+                            blob.blob
+                        ),
+                    })
+                }
+                _ => None,
+            },
+            _ => None,
+        })
+        .collect()
+}
+
+/// The event for a progress or log notification received during the call.
+fn progress_event(
+    call_id: &str,
+    notification: ServerNotification,
+) -> Option<McpToolCallProgressEvent> {
+    match notification {
+        ServerNotification::ProgressNotification(progress) => Some(McpToolCallProgressEvent {
+            call_id: call_id.to_string(),
+            progress: Some(progress.progress),
+            total: progress.total,
+            message: progress.message,
+        }),
+        ServerNotification::LoggingMessageNotification(log) => {
+            let text = match log.data {
+                serde_json::Value::String(text) => text,
+                data => data.to_string(),
+            };
+            Some(McpToolCallProgressEvent {
+                call_id: call_id.to_string(),
+                progress: None,
+                total: None,
+                message: Some(text),
+            })
+        }
+        _ => None,
+    }
+}
+
+fn is_image_mime_type(mime_type: Option<&str>) -> bool {
+    mime_type.is_some_and(|mime_type| mime_type.starts_with("image/"))
+}
+
 async fn notify_mcp_tool_call_event(sess: &Session, sub_id: &str, event: EventMsg) {
     sess.send_event(Event {
         id: sub_id.to_string(),
@@ -78,3 +226,100 @@ async fn notify_mcp_tool_call_event(sess: &Session, sub_id: &str, event: EventMs
     })
     .await;
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mcp_types::BlobResourceContents;
+    use mcp_types::EmbeddedResource;
+    use mcp_types::ImageContent;
+    use mcp_types::LoggingLevel;
+    use mcp_types::LoggingMessageNotificationParams;
+    use mcp_types::ProgressNotificationParams;
+    use mcp_types::ProgressToken;
+    use mcp_types::TextContent;
+    use pretty_assertions::assert_eq;
+
+    fn image_block() -> ContentBlock {
+        ContentBlock::ImageContent(ImageContent {
+            annotations: None,
+            data: "iVBORw0KGgo=".to_string(),
+            mime_type: "image/png".to_string(),
+            r#type: "image".to_string(),
+        })
+    }
+
+    fn blob_block(mime_type: &str) -> ContentBlock {
+        ContentBlock::EmbeddedResource(EmbeddedResource {
+            annotations: None,
+            resource: EmbeddedResourceResource::BlobResourceContents(BlobResourceContents {
+                blob: "AAAA".to_string(),
+                mime_type: Some(mime_type.to_string()),
+                uri: "file:///chart".to_string(),
+            }),
+            r#type: "resource".to_string(),
+        })
+    }
+
+    #[test]
+    fn progress_event_maps_progress_and_log_messages() {
+        let progress = ServerNotification::ProgressNotification(ProgressNotificationParams {
+            message: Some("indexing".to_string()),
+            progress: 3.0,
+            progress_token: ProgressToken::Integer(7),
+            total: Some(10.0),
+        });
+        let event = progress_event("call-1", progress).expect("progress event");
+        assert_eq!(
+            (event.call_id.as_str(), event.progress, event.total),
+            ("call-1", Some(3.0), Some(10.0))
+        );
+        assert_eq!(event.message.as_deref(), Some("indexing"));
+
+        let log =
+            ServerNotification::LoggingMessageNotification(LoggingMessageNotificationParams {
+                data: serde_json::json!({ "rows": 42 }),
+                level: LoggingLevel::Info,
+                logger: None,
+            });
+        let event = progress_event("call-1", log).expect("log event");
+        assert_eq!((event.progress, event.total), (None, None));
+        assert_eq!(event.message.as_deref(), Some(r#"{"rows":42}"#));
+
+        let changed = ServerNotification::ToolListChangedNotification(None);
+        assert!(progress_event("call-1", changed).is_none());
+    }
+
+    #[test]
+    fn image_inputs_collects_images_and_image_resources() {
+        let content = vec![
+            ContentBlock::TextContent(TextContent {
+                annotations: None,
+                text: "chart below".to_string(),
+                r#type: "text".to_string(),
+            }),
+            image_block(),
+            blob_block("image/jpeg"),
+            blob_block("application/pdf"),
+        ];
+
+        assert_eq!(
+            image_inputs(&content),
+            vec![
+                InputItem::Image {
+                    image_url: "data:image/png;base64,iVBORw0KGgo=".to_string(),
+                },
+                InputItem::Image {
+                    image_url: "data:image/jpeg;base64,AAAA".to_string(),
+                },
+            ]
+        );
+        assert_eq!(
+            content_to_text(&content),
+            "chart below\n\
+             [image (image/png) attached below]\n\
+             [resource: file:///chart (image/jpeg) attached below]\n\
+             [resource: file:///chart (application/pdf) omitted]"
+        );
+    }
+}
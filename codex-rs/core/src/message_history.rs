@@ -190,6 +190,112 @@ pub(crate) async fn history_metadata(config: &Config) -> (u64, usize) {
 pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<HistoryEntry> {
     use std::io::BufRead;
     use std::io::BufReader;
+
+    with_shared_lock(log_id, config, |file| {
+        let reader = BufReader::new(file);
+        for (idx, line_res) in reader.lines().enumerate() {
+            let line = match line_res {
+                Ok(l) => l,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to read line from history file");
+                    return None;
+                }
+            };
+
+            if idx == offset {
+                match serde_json::from_str::<HistoryEntry>(&line) {
+                    Ok(entry) => return Some(entry),
+                    Err(e) => {
+                        tracing::warn!(error = %e, "failed to parse history entry");
+                        return None;
+                    }
+                }
+            }
+        }
+        // Not found at requested offset.
+        None
+    })
+    .flatten()
+}
+
+/// Fallback stub for non-Unix systems: currently always returns `None`.
+#[cfg(not(unix))]
+pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<HistoryEntry> {
+    let _ = (log_id, offset, config);
+    None
+}
+
+/// Scan the whole history file identified by `log_id` and return up to
+/// `max_results` entries whose text contains the characters of `query` in
+/// order (case-insensitive), paired with their zero-based offsets. Results are
+/// ordered newest first and identical texts are only reported once, at their
+/// most recent offset. An empty `query` matches every entry.
+///
+/// The filter is intentionally loose: callers are expected to rank the
+/// returned entries with a proper fuzzy scorer.
+#[cfg(unix)]
+pub(crate) fn search(
+    log_id: u64,
+    query: &str,
+    max_results: usize,
+    config: &Config,
+) -> Vec<(usize, HistoryEntry)> {
+    use std::collections::HashSet;
+    use std::io::BufRead;
+    use std::io::BufReader;
+
+    let needle: Vec<char> = query.to_lowercase().chars().collect();
+    let matches = with_shared_lock(log_id, config, |file| {
+        let mut matches = Vec::new();
+        for (idx, line_res) in BufReader::new(file).lines().enumerate() {
+            let line = match line_res {
+                Ok(l) => l,
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to read line from history file");
+                    break;
+                }
+            };
+            match serde_json::from_str::<HistoryEntry>(&line) {
+                Ok(entry) if contains_subsequence(&entry.text, &needle) => {
+                    matches.push((idx, entry));
+                }
+                Ok(_) => {}
+                Err(e) => {
+                    tracing::warn!(error = %e, "failed to parse history entry");
+                }
+            }
+        }
+        matches
+    })
+    .unwrap_or_default();
+
+    let mut seen = HashSet::new();
+    matches
+        .into_iter()
+        .rev()
+        .filter(|(_, entry)| seen.insert(entry.text.clone()))
+        .take(max_results)
+        .collect()
+}
+
+/// Fallback stub for non-Unix systems: currently always returns no matches.
+#[cfg(not(unix))]
+pub(crate) fn search(
+    log_id: u64,
+    query: &str,
+    max_results: usize,
+    config: &Config,
+) -> Vec<(usize, HistoryEntry)> {
+    let _ = (log_id, query, max_results, config);
+    Vec::new()
+}
+
+/// Open the history file, verify that it is still the file identified by
+/// `log_id` and run `read` while holding a shared advisory lock on it. Returns
+/// `None` when the file cannot be opened, does not match `log_id`, or the lock
+/// cannot be acquired after a few attempts.
+#[cfg(unix)]
+fn with_shared_lock<T>(log_id: u64, config: &Config, read: impl FnOnce(&File) -> T) -> Option<T> {
     use std::os::unix::fs::MetadataExt;
 
     let path = history_filepath(config);
@@ -216,33 +322,8 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
     // Open & lock file for reading using a shared lock.
     // Retry a few times to avoid indefinite blocking.
     for _ in 0..MAX_RETRIES {
-        let lock_result = file.try_lock_shared();
-
-        match lock_result {
-            Ok(()) => {
-                let reader = BufReader::new(&file);
-                for (idx, line_res) in reader.lines().enumerate() {
-                    let line = match line_res {
-                        Ok(l) => l,
-                        Err(e) => {
-                            tracing::warn!(error = %e, "failed to read line from history file");
-                            return None;
-                        }
-                    };
-
-                    if idx == offset {
-                        match serde_json::from_str::<HistoryEntry>(&line) {
-                            Ok(entry) => return Some(entry),
-                            Err(e) => {
-                                tracing::warn!(error = %e, "failed to parse history entry");
-                                return None;
-                            }
-                        }
-                    }
-                }
-                // Not found at requested offset.
-                return None;
-            }
+        match file.try_lock_shared() {
+            Ok(()) => return Some(read(&file)),
             Err(std::fs::TryLockError::WouldBlock) => {
                 std::thread::sleep(RETRY_SLEEP);
             }
@@ -256,11 +337,17 @@ pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<Hist
     None
 }
 
-/// Fallback stub for non-Unix systems: currently always returns `None`.
-#[cfg(not(unix))]
-pub(crate) fn lookup(log_id: u64, offset: usize, config: &Config) -> Option<HistoryEntry> {
-    let _ = (log_id, offset, config);
-    None
+#[cfg(unix)]
+/// Case-insensitive check that every character of `needle` (already
+/// lowercased) appears in `haystack` in order.
+fn contains_subsequence(haystack: &str, needle: &[char]) -> bool {
+    let mut remaining = needle.iter().peekable();
+    for ch in haystack.chars().flat_map(char::to_lowercase) {
+        if remaining.peek().is_some_and(|&&next| next == ch) {
+            remaining.next();
+        }
+    }
+    remaining.peek().is_none()
 }
 
 /// On Unix systems ensure the file permissions are `0o600` (rw-------). If the
@@ -284,3 +371,66 @@ async fn ensure_owner_only_permissions(_file: &File) -> Result<()> {
     // For now, on non-Unix, simply succeed.
     Ok(())
 }
+
+#[cfg(test)]
+#[cfg(unix)]
+mod tests {
+    use super::*;
+    use crate::config::ConfigOverrides;
+    use crate::config::ConfigToml;
+    use pretty_assertions::assert_eq;
+    use tempfile::TempDir;
+
+    fn make_config(codex_home: &TempDir) -> Config {
+        Config::load_from_base_config_with_overrides(
+            ConfigToml::default(),
+            ConfigOverrides::default(),
+            codex_home.path().to_path_buf(),
+        )
+        .expect("defaults for test should always succeed")
+    }
+
+    #[tokio::test]
+    async fn search_returns_newest_unique_matches() {
+        let codex_home = TempDir::new().unwrap();
+        let config = make_config(&codex_home);
+        let conversation_id = ConversationId::new();
+        for text in [
+            "fix the flaky test",
+            "write docs",
+            "Fix The Build",
+            "fix the flaky test",
+        ] {
+            append_entry(text, &conversation_id, &config).await.unwrap();
+        }
+        let (log_id, count) = history_metadata(&config).await;
+        assert_eq!(count, 4);
+
+        let texts = |matches: Vec<(usize, HistoryEntry)>| {
+            matches
+                .into_iter()
+                .map(|(offset, entry)| (offset, entry.text))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(
+            texts(search(log_id, "ftb", 10, &config)),
+            vec![(2, "Fix The Build".to_string())]
+        );
+        assert_eq!(
+            texts(search(log_id, "fix", 10, &config)),
+            vec![
+                (3, "fix the flaky test".to_string()),
+                (2, "Fix The Build".to_string()),
+            ]
+        );
+        assert_eq!(
+            texts(search(log_id, "", 2, &config)),
+            vec![
+                (3, "fix the flaky test".to_string()),
+                (2, "Fix The Build".to_string()),
+            ]
+        );
+        assert!(search(log_id + 1, "fix", 10, &config).is_empty());
+    }
+}
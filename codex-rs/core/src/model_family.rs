@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use crate::config_types::ModelDefinition;
 use crate::config_types::ReasoningSummaryFormat;
 use crate::tool_apply_patch::ApplyPatchToolType;
 
@@ -117,6 +120,40 @@ pub fn find_family_for_model(slug: &str) -> Option<ModelFamily> {
     }
 }
 
+/// Returns the `ModelFamily` for `slug` with any `[models.<slug>]` entry in
+/// `models` applied. A configured slug always resolves, starting from the
+/// defaults for unknown models when it matches no built-in family.
+pub fn find_family_for_configured_model(
+    slug: &str,
+    models: &HashMap<String, ModelDefinition>,
+) -> Option<ModelFamily> {
+    let Some(definition) = models.get(slug) else {
+        return find_family_for_model(slug);
+    };
+    let mut mf = find_family_for_model(slug).unwrap_or_else(|| derive_default_model_family(slug));
+    if let Some(supports_reasoning_summaries) = definition.supports_reasoning_summaries {
+        mf.supports_reasoning_summaries = supports_reasoning_summaries;
+    }
+    if let Some(reasoning_summary_format) = &definition.reasoning_summary_format {
+        mf.reasoning_summary_format = reasoning_summary_format.clone();
+    }
+    if let Some(apply_patch_tool_type) = &definition.apply_patch_tool_type {
+        mf.apply_patch_tool_type = Some(apply_patch_tool_type.clone());
+    }
+    if let Some(needs_special_apply_patch_instructions) =
+        definition.needs_special_apply_patch_instructions
+    {
+        mf.needs_special_apply_patch_instructions = needs_special_apply_patch_instructions;
+    }
+    if let Some(uses_local_shell_tool) = definition.uses_local_shell_tool {
+        mf.uses_local_shell_tool = uses_local_shell_tool;
+    }
+    if let Some(base_instructions) = &definition.base_instructions {
+        mf.base_instructions = base_instructions.clone();
+    }
+    Some(mf)
+}
+
 pub fn derive_default_model_family(model: &str) -> ModelFamily {
     ModelFamily {
         slug: model.to_string(),
@@ -129,3 +166,59 @@ pub fn derive_default_model_family(model: &str) -> ModelFamily {
         base_instructions: BASE_INSTRUCTIONS.to_string(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tool_apply_patch::ApplyPatchToolType;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn configured_model_overrides_builtin_family() {
+        let models = HashMap::from([(
+            "gpt-5".to_string(),
+            ModelDefinition {
+                apply_patch_tool_type: Some(ApplyPatchToolType::Freeform),
+                ..Default::default()
+            },
+        )]);
+
+        let family = find_family_for_configured_model("gpt-5", &models).expect("gpt-5 family");
+        assert_eq!(family.family, "gpt-5");
+        assert!(family.supports_reasoning_summaries);
+        assert_eq!(
+            family.apply_patch_tool_type,
+            Some(ApplyPatchToolType::Freeform)
+        );
+    }
+
+    #[test]
+    fn configured_model_without_builtin_family() {
+        assert_eq!(
+            find_family_for_configured_model("my-local-model", &HashMap::new()),
+            None
+        );
+
+        let models = HashMap::from([(
+            "my-local-model".to_string(),
+            ModelDefinition {
+                supports_reasoning_summaries: Some(true),
+                uses_local_shell_tool: Some(true),
+                base_instructions: Some("You are a local model.".to_string()),
+                ..Default::default()
+            },
+        )]);
+
+        let family =
+            find_family_for_configured_model("my-local-model", &models).expect("configured");
+        assert_eq!(
+            family,
+            ModelFamily {
+                supports_reasoning_summaries: true,
+                uses_local_shell_tool: true,
+                base_instructions: "You are a local model.".to_string(),
+                ..derive_default_model_family("my-local-model")
+            }
+        );
+    }
+}
@@ -1,3 +1,6 @@
+use std::collections::HashMap;
+
+use crate::config_types::ModelDefinition;
 use crate::model_family::ModelFamily;
 
 /// Metadata about a model, particularly OpenAI models.
@@ -11,7 +14,7 @@ pub(crate) struct ModelInfo {
     pub(crate) context_window: u64,
 
     /// Maximum number of output tokens that can be generated for the model.
-    pub(crate) max_output_tokens: u64,
+    pub(crate) max_output_tokens: Option<u64>,
 
     /// Token threshold where we should automatically compact conversation history. This considers
     /// input tokens + output tokens of this turn.
@@ -22,7 +25,7 @@ impl ModelInfo {
     const fn new(context_window: u64, max_output_tokens: u64) -> Self {
         Self {
             context_window,
-            max_output_tokens,
+            max_output_tokens: Some(max_output_tokens),
             auto_compact_token_limit: None,
         }
     }
@@ -64,7 +67,7 @@ pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
 
         _ if slug.starts_with("gpt-5-codex") => Some(ModelInfo {
             context_window: 272_000,
-            max_output_tokens: 128_000,
+            max_output_tokens: Some(128_000),
             auto_compact_token_limit: Some(350_000),
         }),
 
@@ -75,3 +78,28 @@ pub(crate) fn get_model_info(model_family: &ModelFamily) -> Option<ModelInfo> {
         _ => None,
     }
 }
+
+/// Like [`get_model_info`], but with the limits from a `[models.<slug>]`
+/// entry in `models` taking precedence over the built-in ones. Returns `None`
+/// when the context window is unknown.
+pub(crate) fn get_configured_model_info(
+    model_family: &ModelFamily,
+    models: &HashMap<String, ModelDefinition>,
+) -> Option<ModelInfo> {
+    let builtin = get_model_info(model_family);
+    let Some(definition) = models.get(&model_family.slug) else {
+        return builtin;
+    };
+    let context_window = definition
+        .context_window
+        .or_else(|| builtin.as_ref().map(|info| info.context_window))?;
+    Some(ModelInfo {
+        context_window,
+        max_output_tokens: definition
+            .max_output_tokens
+            .or_else(|| builtin.as_ref().and_then(|info| info.max_output_tokens)),
+        auto_compact_token_limit: definition
+            .auto_compact_token_limit
+            .or_else(|| builtin.and_then(|info| info.auto_compact_token_limit)),
+    })
+}
@@ -67,6 +67,8 @@ pub(crate) struct ToolsConfig {
     pub apply_patch_tool_type: Option<ApplyPatchToolType>,
     pub web_search_request: bool,
     pub include_view_image_tool: bool,
+    pub include_screenshot_tool: bool,
+    pub include_spawn_agent_tool: bool,
     pub experimental_unified_exec_tool: bool,
 }
 
@@ -77,6 +79,8 @@ pub(crate) struct ToolsConfigParams<'a> {
     pub(crate) include_web_search_request: bool,
     pub(crate) use_streamable_shell_tool: bool,
     pub(crate) include_view_image_tool: bool,
+    pub(crate) include_screenshot_tool: bool,
+    pub(crate) include_spawn_agent_tool: bool,
     pub(crate) experimental_unified_exec_tool: bool,
 }
 
@@ -89,6 +93,8 @@ impl ToolsConfig {
             include_web_search_request,
             use_streamable_shell_tool,
             include_view_image_tool,
+            include_screenshot_tool,
+            include_spawn_agent_tool,
             experimental_unified_exec_tool,
         } = params;
         let shell_type = if *use_streamable_shell_tool {
@@ -117,6 +123,8 @@ impl ToolsConfig {
             apply_patch_tool_type,
             web_search_request: *include_web_search_request,
             include_view_image_tool: *include_view_image_tool,
+            include_screenshot_tool: *include_screenshot_tool,
+            include_spawn_agent_tool: *include_spawn_agent_tool,
             experimental_unified_exec_tool: *experimental_unified_exec_tool,
         }
     }
@@ -526,6 +534,14 @@ pub(crate) fn get_openai_tools(
     if config.include_view_image_tool {
         tools.push(create_view_image_tool());
     }
+
+    if config.include_screenshot_tool {
+        tools.push(crate::screenshot::create_capture_screenshot_tool());
+    }
+
+    if config.include_spawn_agent_tool {
+        tools.push(crate::sub_agent::create_spawn_agent_tool());
+    }
     if let Some(mcp_tools) = mcp_tools {
         // Ensure deterministic ordering to maximize prompt cache hits.
         let mut entries: Vec<(String, mcp_types::Tool)> = mcp_tools.into_iter().collect();
@@ -587,6 +603,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_screenshot_tool: false,
+            include_spawn_agent_tool: false,
             experimental_unified_exec_tool: true,
         });
         let tools = get_openai_tools(&config, Some(HashMap::new()));
@@ -607,6 +625,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_screenshot_tool: false,
+            include_spawn_agent_tool: false,
             experimental_unified_exec_tool: true,
         });
         let tools = get_openai_tools(&config, Some(HashMap::new()));
@@ -617,6 +637,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_get_openai_tools_spawn_agent() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            include_plan_tool: false,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_view_image_tool: true,
+            include_screenshot_tool: false,
+            include_spawn_agent_tool: true,
+            experimental_unified_exec_tool: true,
+        });
+        let tools = get_openai_tools(&config, Some(HashMap::new()));
+
+        assert_eq_tool_names(&tools, &["unified_exec", "view_image", "spawn_agent"]);
+    }
+
+    #[test]
+    fn test_get_openai_tools_capture_screenshot() {
+        let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
+        let config = ToolsConfig::new(&ToolsConfigParams {
+            model_family: &model_family,
+            include_plan_tool: false,
+            include_apply_patch_tool: false,
+            include_web_search_request: false,
+            use_streamable_shell_tool: false,
+            include_view_image_tool: true,
+            include_screenshot_tool: true,
+            include_spawn_agent_tool: false,
+            experimental_unified_exec_tool: true,
+        });
+        let tools = get_openai_tools(&config, Some(HashMap::new()));
+
+        assert_eq_tool_names(
+            &tools,
+            &["unified_exec", "view_image", "capture_screenshot"],
+        );
+    }
+
     #[test]
     fn test_get_openai_tools_mcp_tools() {
         let model_family = find_family_for_model("o3").expect("o3 should be a valid model family");
@@ -627,6 +688,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_screenshot_tool: false,
+            include_spawn_agent_tool: false,
             experimental_unified_exec_tool: true,
         });
         let tools = get_openai_tools(
@@ -731,6 +794,8 @@ mod tests {
             include_web_search_request: false,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_screenshot_tool: false,
+            include_spawn_agent_tool: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -807,6 +872,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_screenshot_tool: false,
+            include_spawn_agent_tool: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -868,6 +935,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_screenshot_tool: false,
+            include_spawn_agent_tool: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -924,6 +993,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_screenshot_tool: false,
+            include_spawn_agent_tool: false,
             experimental_unified_exec_tool: true,
         });
 
@@ -983,6 +1054,8 @@ mod tests {
             include_web_search_request: true,
             use_streamable_shell_tool: false,
             include_view_image_tool: true,
+            include_screenshot_tool: false,
+            include_spawn_agent_tool: false,
             experimental_unified_exec_tool: true,
         });
 
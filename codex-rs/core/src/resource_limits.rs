@@ -0,0 +1,224 @@
+//! Shared helpers for the `[sandbox_resource_limits]` enforced by
+//! `codex-linux-sandbox`.
+//!
+//! CPU time and file size are enforced with rlimits, which deliver a distinct
+//! signal when exceeded. Memory and process counts are enforced with a
+//! per-command cgroup v2 scope when one can be created (falling back to
+//! rlimits otherwise); breaches of those are read back from the scope's event
+//! counters once the command exits.
+
+use std::fmt;
+#[cfg(target_os = "linux")]
+use std::path::Path;
+#[cfg(target_os = "linux")]
+use std::path::PathBuf;
+
+use crate::config_types::SandboxResourceLimits;
+
+// Same values on Linux and macOS; hardcoded so this also compiles on Windows.
+/// Delivered when a process exceeds `RLIMIT_CPU`.
+const SIGXCPU: i32 = 24;
+/// Delivered when a process exceeds `RLIMIT_FSIZE`.
+const SIGXFSZ: i32 = 25;
+/// Shells report a child killed by a signal as 128 + signal.
+const EXIT_CODE_SIGNAL_BASE: i32 = 128;
+
+/// The resource limit a sandboxed command ran into.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ResourceLimit {
+    Memory,
+    CpuTime,
+    Processes,
+    FileSize,
+}
+
+impl ResourceLimit {
+    /// Maps the signal that terminated a command to the limit that raised it.
+    /// Programs can also raise `SIGXCPU`/`SIGXFSZ` themselves or inherit their
+    /// own rlimits, so only trust the signal when the matching limit is
+    /// configured.
+    pub(crate) fn from_signal(signal: i32, limits: &SandboxResourceLimits) -> Option<Self> {
+        match signal {
+            SIGXCPU if limits.max_cpu_seconds.is_some() => Some(Self::CpuTime),
+            SIGXFSZ if limits.max_file_size_mb.is_some() => Some(Self::FileSize),
+            _ => None,
+        }
+    }
+
+    /// Maps the exit code of a command to the limit that killed it. A breach
+    /// inside `bash -c` kills a child of the shell, which then exits with
+    /// 128 + signal. This cannot be told apart from a command exiting with
+    /// that code itself; see [`SandboxErr::ResourceLimitExceeded`].
+    ///
+    /// [`SandboxErr::ResourceLimitExceeded`]: crate::error::SandboxErr::ResourceLimitExceeded
+    pub(crate) fn from_exit_code(exit_code: i32, limits: &SandboxResourceLimits) -> Option<Self> {
+        Self::from_signal(exit_code - EXIT_CODE_SIGNAL_BASE, limits)
+    }
+}
+
+impl fmt::Display for ResourceLimit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Memory => "memory limit",
+            Self::CpuTime => "CPU time limit",
+            Self::Processes => "process limit",
+            Self::FileSize => "file size limit",
+        };
+        f.write_str(name)
+    }
+}
+
+#[cfg(target_os = "linux")]
+const CGROUP2_MOUNTS: [&str; 2] = ["/sys/fs/cgroup", "/sys/fs/cgroup/unified"];
+
+/// Path of the cgroup v2 scope used for the sandbox helper with the given
+/// pid, or `None` when cgroup v2 is not available.
+///
+/// The scope is a sibling of the caller's cgroup (cgroup v2 does not allow
+/// processes in inner nodes), so the helper and Codex compute the same path:
+/// the helper inherits Codex's cgroup when it is spawned.
+#[cfg(target_os = "linux")]
+pub fn cgroup_scope_path(pid: u32) -> Option<PathBuf> {
+    let mount = CGROUP2_MOUNTS
+        .iter()
+        .map(Path::new)
+        .find(|mount| mount.join("cgroup.controllers").is_file())?;
+    let contents = std::fs::read_to_string("/proc/self/cgroup").ok()?;
+    let relative = contents.lines().find_map(|line| line.strip_prefix("0::"))?;
+    let current = mount.join(relative.trim_start_matches('/'));
+    let parent = if current == mount {
+        current.as_path()
+    } else {
+        current.parent()?
+    };
+    Some(parent.join(format!("codex-exec-{pid}")))
+}
+
+/// Whether `max_rss_mb` can be enforced: the sandbox helper needs to create
+/// a cgroup v2 scope with the memory controller next to Codex's cgroup.
+/// Probed with a scope named after Codex's own pid, which is removed again.
+#[cfg(target_os = "linux")]
+pub fn memory_limit_enforceable() -> bool {
+    let Some(scope) = cgroup_scope_path(std::process::id()) else {
+        return false;
+    };
+    if std::fs::create_dir(&scope).is_err() {
+        return false;
+    }
+    // `memory.max` only exists when the memory controller is delegated.
+    let enforceable = std::fs::write(scope.join("memory.max"), "max").is_ok();
+    let _ = std::fs::remove_dir(&scope);
+    enforceable
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn memory_limit_enforceable() -> bool {
+    false
+}
+
+/// Tears down the cgroup scope of the sandbox helper with the given pid,
+/// killing anything the command left running, and reports whether the
+/// command ran into the memory or process limit.
+#[cfg(target_os = "linux")]
+pub fn collect_cgroup_scope(pid: u32) -> Option<ResourceLimit> {
+    let scope = cgroup_scope_path(pid)?;
+    if !scope.is_dir() {
+        return None;
+    }
+
+    let breach = if read_event_counter(&scope.join("memory.events"), "oom_kill") > 0 {
+        Some(ResourceLimit::Memory)
+    } else if read_event_counter(&scope.join("pids.events"), "max") > 0 {
+        Some(ResourceLimit::Processes)
+    } else {
+        None
+    };
+
+    // `cgroup.kill` needs Linux 5.14; on older kernels leftover background
+    // processes keep the scope alive and the rmdir below fails harmlessly.
+    let _ = std::fs::write(scope.join("cgroup.kill"), "1");
+    for _ in 0..10 {
+        if std::fs::remove_dir(&scope).is_ok() {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(10));
+    }
+
+    breach
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn collect_cgroup_scope(_pid: u32) -> Option<ResourceLimit> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn read_event_counter(path: &Path, key: &str) -> u64 {
+    std::fs::read_to_string(path)
+        .ok()
+        .and_then(|contents| parse_event_counter(&contents, key))
+        .unwrap_or(0)
+}
+
+#[cfg(target_os = "linux")]
+fn parse_event_counter(contents: &str, key: &str) -> Option<u64> {
+    contents.lines().find_map(|line| {
+        let (name, value) = line.split_once(' ')?;
+        if name == key {
+            value.trim().parse().ok()
+        } else {
+            None
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn maps_rlimit_signals_only_for_configured_limits() {
+        let limits = SandboxResourceLimits {
+            max_cpu_seconds: Some(10),
+            max_file_size_mb: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            ResourceLimit::from_signal(SIGXCPU, &limits),
+            Some(ResourceLimit::CpuTime)
+        );
+        assert_eq!(
+            ResourceLimit::from_signal(SIGXFSZ, &limits),
+            Some(ResourceLimit::FileSize)
+        );
+        assert_eq!(ResourceLimit::from_signal(9, &limits), None);
+
+        let unlimited = SandboxResourceLimits::default();
+        assert_eq!(ResourceLimit::from_signal(SIGXCPU, &unlimited), None);
+        assert_eq!(ResourceLimit::from_signal(SIGXFSZ, &unlimited), None);
+    }
+
+    #[test]
+    fn maps_shell_exit_codes_only_for_configured_limits() {
+        let limits = SandboxResourceLimits {
+            max_file_size_mb: Some(1),
+            ..Default::default()
+        };
+        assert_eq!(
+            ResourceLimit::from_exit_code(153, &limits),
+            Some(ResourceLimit::FileSize)
+        );
+        assert_eq!(ResourceLimit::from_exit_code(152, &limits), None);
+        assert_eq!(ResourceLimit::from_exit_code(1, &limits), None);
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn parses_cgroup_event_counters() {
+        let memory_events = "low 0\nhigh 0\nmax 12\noom 1\noom_kill 1\noom_group_kill 0\n";
+        assert_eq!(parse_event_counter(memory_events, "oom_kill"), Some(1));
+        assert_eq!(parse_event_counter(memory_events, "max"), Some(12));
+        assert_eq!(parse_event_counter("max 0\n", "oom_kill"), None);
+    }
+}
@@ -0,0 +1,382 @@
+//! The `capture_screenshot` tool: render a local page with the headless
+//! renderer configured in `[screenshot]` and attach the PNG to the
+//! conversation.
+//!
+//! Only pages on this machine can be captured: local files (relative paths
+//! resolve against the turn's working directory) and `http(s)` URLs whose
+//! host is `localhost` or a loopback address. The model picks the target, so
+//! the renderer runs like a shell command: under the session's sandbox and
+//! approval policy, with only its output directory made writable. A headless
+//! browser loads whatever the page references, so the tool refuses to run
+//! when the sandbox blocks network access.
+
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use url::Url;
+use uuid::Uuid;
+
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::codex::handle_container_exec_with_sandbox_policy;
+use crate::config_types::ScreenshotConfig;
+use crate::exec::ExecParams;
+use crate::exec_env::create_env;
+use crate::function_tool::FunctionCallError;
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::InputItem;
+use crate::protocol::SandboxPolicy;
+use crate::protocol::ScreenshotCapturedEvent;
+use crate::turn_diff_tracker::TurnDiffTracker;
+
+pub(crate) const CAPTURE_SCREENSHOT_TOOL_NAME: &str = "capture_screenshot";
+
+/// Largest viewport the model may ask for, in either dimension.
+const MAX_VIEWPORT_SIZE: u32 = 4096;
+
+pub(crate) fn create_capture_screenshot_tool() -> OpenAiTool {
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "target".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Local HTML file path, or an http(s) URL on localhost, to render".to_string(),
+            ),
+        },
+    );
+    properties.insert(
+        "width".to_string(),
+        JsonSchema::Number {
+            description: Some("Viewport width in pixels".to_string()),
+        },
+    );
+    properties.insert(
+        "height".to_string(),
+        JsonSchema::Number {
+            description: Some("Viewport height in pixels".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: CAPTURE_SCREENSHOT_TOOL_NAME.to_string(),
+        description: "Render a local HTML file or a page served on localhost in a headless browser and attach a PNG screenshot to the conversation context for this turn."
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["target".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct CaptureScreenshotArgs {
+    target: String,
+    #[serde(default)]
+    width: Option<u32>,
+    #[serde(default)]
+    height: Option<u32>,
+}
+
+pub(crate) async fn handle_capture_screenshot(
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    sub_id: String,
+    call_id: String,
+    arguments: String,
+) -> Result<String, FunctionCallError> {
+    let args: CaptureScreenshotArgs = serde_json::from_str(&arguments).map_err(|e| {
+        FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e:?}"))
+    })?;
+    let config = turn_context.client.get_config();
+    let Some(renderer) = config.screenshot.as_ref() else {
+        return Err(FunctionCallError::RespondToModel(
+            "no screenshot renderer is configured".to_string(),
+        ));
+    };
+    if !turn_context.sandbox_policy.has_full_network_access() {
+        return Err(FunctionCallError::RespondToModel(
+            "screenshots cannot be captured because the sandbox blocks network access".to_string(),
+        ));
+    }
+
+    let url = resolve_target(&args.target, &turn_context.cwd)
+        .map_err(FunctionCallError::RespondToModel)?;
+    let width = viewport_size(args.width, renderer.width)?;
+    let height = viewport_size(args.height, renderer.height)?;
+
+    // One directory per capture, so the sandbox only opens up this one.
+    let output_dir = config
+        .codex_home
+        .join("screenshots")
+        .join(Uuid::new_v4().to_string());
+    std::fs::create_dir_all(&output_dir).map_err(|e| {
+        FunctionCallError::RespondToModel(format!("failed to create {}: {e}", output_dir.display()))
+    })?;
+    let output = output_dir.join("screenshot.png");
+    let result = async {
+        render(
+            sess,
+            turn_context,
+            turn_diff_tracker,
+            renderer,
+            &url,
+            &output_dir,
+            &output,
+            width,
+            height,
+            sub_id.clone(),
+            call_id.clone(),
+        )
+        .await?;
+        // The image is read when it is attached, so it can be deleted below.
+        sess.inject_input(vec![InputItem::LocalImage {
+            path: output.clone(),
+        }])
+        .await
+        .map_err(|_| {
+            FunctionCallError::RespondToModel(
+                "unable to attach screenshot (no active task)".to_string(),
+            )
+        })
+    }
+    .await;
+    let _ = std::fs::remove_dir_all(&output_dir);
+    result?;
+
+    sess.send_event(Event {
+        id: sub_id,
+        msg: EventMsg::ScreenshotCaptured(ScreenshotCapturedEvent {
+            call_id,
+            url: url.to_string(),
+            width,
+            height,
+        }),
+    })
+    .await;
+
+    Ok(format!("attached screenshot of {url} ({width}x{height})"))
+}
+
+fn viewport_size(requested: Option<u32>, default: u32) -> Result<u32, FunctionCallError> {
+    match requested {
+        None => Ok(default),
+        Some(size) if (1..=MAX_VIEWPORT_SIZE).contains(&size) => Ok(size),
+        Some(size) => Err(FunctionCallError::RespondToModel(format!(
+            "viewport size {size} is out of range (1-{MAX_VIEWPORT_SIZE})"
+        ))),
+    }
+}
+
+/// The URL to render for `target`: a localhost URL as given, or a file path
+/// (relative to `cwd`) turned into a `file://` URL.
+fn resolve_target(target: &str, cwd: &Path) -> Result<Url, String> {
+    let target = target.trim();
+    if target.starts_with("http://") || target.starts_with("https://") {
+        let url = Url::parse(target).map_err(|e| format!("invalid URL {target}: {e}"))?;
+        let is_local = match url.host() {
+            Some(url::Host::Domain(domain)) => domain.eq_ignore_ascii_case("localhost"),
+            Some(url::Host::Ipv4(ip)) => ip.is_loopback(),
+            Some(url::Host::Ipv6(ip)) => ip.is_loopback(),
+            None => false,
+        };
+        if !is_local {
+            return Err(format!(
+                "only pages served on localhost can be captured, not {target}"
+            ));
+        }
+        return Ok(url);
+    }
+
+    let path = PathBuf::from(target.strip_prefix("file://").unwrap_or(target));
+    let path = cwd.join(path);
+    let path = path
+        .canonicalize()
+        .map_err(|e| format!("cannot read {}: {e}", path.display()))?;
+    if !path.is_file() {
+        return Err(format!("{} is not a file", path.display()));
+    }
+    Url::from_file_path(&path).map_err(|()| format!("cannot build a URL for {}", path.display()))
+}
+
+/// `command` with the placeholders of every argument filled in.
+fn renderer_argv(
+    command: &[String],
+    url: &Url,
+    output: &Path,
+    width: u32,
+    height: u32,
+) -> Vec<String> {
+    let output = output.to_string_lossy();
+    command
+        .iter()
+        .map(|arg| {
+            arg.replace("{url}", url.as_str())
+                .replace("{output}", &output)
+                .replace("{width}", &width.to_string())
+                .replace("{height}", &height.to_string())
+        })
+        .collect()
+}
+
+/// The turn's sandbox policy with `output_dir` made writable for the
+/// renderer.
+fn renderer_sandbox_policy(policy: &SandboxPolicy, output_dir: &Path) -> SandboxPolicy {
+    match policy {
+        SandboxPolicy::WorkspaceWrite {
+            writable_roots,
+            network_access,
+            exclude_tmpdir_env_var,
+            exclude_slash_tmp,
+        } => {
+            let mut writable_roots = writable_roots.clone();
+            writable_roots.push(output_dir.to_path_buf());
+            SandboxPolicy::WorkspaceWrite {
+                writable_roots,
+                network_access: *network_access,
+                exclude_tmpdir_env_var: *exclude_tmpdir_env_var,
+                exclude_slash_tmp: *exclude_slash_tmp,
+            }
+        }
+        SandboxPolicy::DangerFullAccess | SandboxPolicy::ReadOnly => policy.clone(),
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+async fn render(
+    sess: &Session,
+    turn_context: &TurnContext,
+    turn_diff_tracker: &mut TurnDiffTracker,
+    renderer: &ScreenshotConfig,
+    url: &Url,
+    output_dir: &Path,
+    output: &Path,
+    width: u32,
+    height: u32,
+    sub_id: String,
+    call_id: String,
+) -> Result<(), FunctionCallError> {
+    let command = renderer_argv(&renderer.command, url, output, width, height);
+    if command.is_empty() {
+        return Err(FunctionCallError::RespondToModel(
+            "the screenshot renderer command is empty".to_string(),
+        ));
+    }
+    let params = ExecParams {
+        command,
+        cwd: turn_context.cwd.clone(),
+        timeout_ms: Some(renderer.timeout_ms),
+        env: create_env(&turn_context.shell_environment_policy),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+    handle_container_exec_with_sandbox_policy(
+        CAPTURE_SCREENSHOT_TOOL_NAME,
+        params,
+        renderer_sandbox_policy(&turn_context.sandbox_policy, output_dir),
+        sess,
+        turn_context,
+        turn_diff_tracker,
+        sub_id,
+        call_id,
+    )
+    .await?;
+
+    match std::fs::metadata(output) {
+        Ok(metadata) if metadata.len() > 0 => Ok(()),
+        _ => Err(FunctionCallError::RespondToModel(format!(
+            "screenshot renderer did not write {}",
+            output.display()
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn only_local_urls_are_accepted() {
+        let cwd = std::env::temp_dir();
+        assert_eq!(
+            resolve_target("http://localhost:5173/app", &cwd).map(String::from),
+            Ok("http://localhost:5173/app".to_string())
+        );
+        assert!(resolve_target("http://127.0.0.1:8000/", &cwd).is_ok());
+        assert!(resolve_target("https://[::1]/", &cwd).is_ok());
+        assert!(resolve_target("https://example.com/", &cwd).is_err());
+        assert!(resolve_target("http://localhost.example.com/", &cwd).is_err());
+    }
+
+    #[test]
+    fn files_are_resolved_against_cwd() {
+        let dir = tempfile::tempdir().expect("tempdir");
+        std::fs::write(dir.path().join("index.html"), "<h1>hi</h1>").expect("write html");
+
+        let url = resolve_target("index.html", dir.path()).expect("resolve file");
+        assert_eq!(url.scheme(), "file");
+        assert_eq!(
+            url.to_file_path().expect("file path"),
+            dir.path()
+                .join("index.html")
+                .canonicalize()
+                .expect("canonicalize")
+        );
+        assert!(resolve_target("missing.html", dir.path()).is_err());
+    }
+
+    #[test]
+    fn placeholders_are_filled_in() {
+        let command = vec![
+            "chromium".to_string(),
+            "--headless".to_string(),
+            "--window-size={width},{height}".to_string(),
+            "--screenshot={output}".to_string(),
+            "{url}".to_string(),
+        ];
+        let url = Url::parse("http://localhost:3000/").expect("url");
+        assert_eq!(
+            renderer_argv(&command, &url, Path::new("/tmp/shot.png"), 800, 600),
+            vec![
+                "chromium",
+                "--headless",
+                "--window-size=800,600",
+                "--screenshot=/tmp/shot.png",
+                "http://localhost:3000/",
+            ]
+        );
+    }
+
+    #[test]
+    fn renderer_may_only_write_its_output_directory() {
+        let output_dir = Path::new("/home/user/.codex/screenshots/1234");
+        let policy = SandboxPolicy::WorkspaceWrite {
+            writable_roots: vec![PathBuf::from("/data")],
+            network_access: true,
+            exclude_tmpdir_env_var: true,
+            exclude_slash_tmp: false,
+        };
+        assert_eq!(
+            renderer_sandbox_policy(&policy, output_dir),
+            SandboxPolicy::WorkspaceWrite {
+                writable_roots: vec![PathBuf::from("/data"), output_dir.to_path_buf()],
+                network_access: true,
+                exclude_tmpdir_env_var: true,
+                exclude_slash_tmp: false,
+            }
+        );
+        assert_eq!(
+            renderer_sandbox_policy(&SandboxPolicy::DangerFullAccess, output_dir),
+            SandboxPolicy::DangerFullAccess
+        );
+    }
+}
@@ -1,4 +1,9 @@
+use std::collections::HashMap;
+use std::time::Instant;
+
 use crate::RolloutRecorder;
+use crate::config_types::ModelPrice;
+use crate::config_types::SessionBudget;
 use crate::exec_command::ExecSessionManager;
 use crate::executor::Executor;
 use crate::mcp_connection_manager::McpConnectionManager;
@@ -14,5 +19,12 @@ pub(crate) struct SessionServices {
     pub(crate) rollout: Mutex<Option<RolloutRecorder>>,
     pub(crate) user_shell: crate::shell::Shell,
     pub(crate) show_raw_agent_reasoning: bool,
+    /// Whether the frontend answers sampling and elicitation requests from
+    /// MCP servers.
+    pub(crate) answers_mcp_server_requests: bool,
+    pub(crate) apply_patch_fuzzy_threshold: f64,
+    pub(crate) model_prices: HashMap<String, ModelPrice>,
+    pub(crate) budget: SessionBudget,
+    pub(crate) started_at: Instant,
     pub(crate) executor: Executor,
 }
@@ -1,11 +1,18 @@
 //! Session-wide mutable state.
 
+use std::collections::HashMap;
+use std::collections::HashSet;
+
 use codex_protocol::models::ResponseItem;
+use mcp_types::ElicitResult;
+use tokio::sync::oneshot;
 
 use crate::conversation_history::ConversationHistory;
 use crate::protocol::RateLimitSnapshot;
+use crate::protocol::ReviewDecision;
 use crate::protocol::TokenUsage;
 use crate::protocol::TokenUsageInfo;
+use crate::protocol::TurnAbortReason;
 
 /// Persistent, session-scoped state previously stored directly on `Session`.
 #[derive(Default)]
@@ -13,6 +20,18 @@ pub(crate) struct SessionState {
     pub(crate) history: ConversationHistory,
     pub(crate) token_info: Option<TokenUsageInfo>,
     pub(crate) latest_rate_limits: Option<RateLimitSnapshot>,
+    /// Requests made to the model so far, counted against `budget.max_turns`.
+    pub(crate) model_turns: u64,
+    /// A task that ended itself early, and why, so that its completion is
+    /// reported as an abort.
+    pub(crate) task_abort: Option<(String, TurnAbortReason)>,
+    /// MCP sampling requests waiting for the user's decision, by request id.
+    pub(crate) pending_mcp_sampling: HashMap<String, oneshot::Sender<ReviewDecision>>,
+    /// MCP elicitation requests waiting for the user's answer, by request id.
+    pub(crate) pending_mcp_elicitations: HashMap<String, oneshot::Sender<ElicitResult>>,
+    /// MCP servers the user allowed to sample the model for the rest of the
+    /// session.
+    pub(crate) mcp_sampling_approved_servers: HashSet<String>,
 }
 
 impl SessionState {
@@ -46,12 +65,16 @@ impl SessionState {
         &mut self,
         usage: &TokenUsage,
         model_context_window: Option<u64>,
+        cost_usd: Option<f64>,
     ) {
         self.token_info = TokenUsageInfo::new_or_append(
             &self.token_info,
             &Some(usage.clone()),
             model_context_window,
         );
+        if let (Some(info), Some(cost_usd)) = (self.token_info.as_mut(), cost_usd) {
+            info.add_cost_usd(cost_usd);
+        }
     }
 
     pub(crate) fn set_rate_limits(&mut self, snapshot: RateLimitSnapshot) {
@@ -64,5 +87,16 @@ impl SessionState {
         (self.token_info.clone(), self.latest_rate_limits.clone())
     }
 
+    // Task abort helpers
+    pub(crate) fn take_task_abort(&mut self, sub_id: &str) -> Option<TurnAbortReason> {
+        match self.task_abort.take() {
+            Some((id, reason)) if id == sub_id => Some(reason),
+            other => {
+                self.task_abort = other;
+                None
+            }
+        }
+    }
+
     // Pending input/approval moved to TurnState.
 }
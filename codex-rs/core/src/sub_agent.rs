@@ -0,0 +1,520 @@
+//! The `spawn_agent` tool: delegate self-contained tasks to child sessions.
+//!
+//! Each sub-agent is a separate [`Codex`] instance seeded with the task as
+//! its only user message. Children inherit the parent's model and working
+//! directory but run with a restricted tool set, never ask for approval and
+//! cannot spawn further agents. Their progress is forwarded to the parent as
+//! `SubAgent*` events and the final message of every child is returned to
+//! the parent model as the tool output.
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use futures::StreamExt;
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::AuthManager;
+use crate::codex::Codex;
+use crate::codex::CodexSpawnOk;
+use crate::codex::Session;
+use crate::codex::TurnContext;
+use crate::config::Config;
+use crate::function_tool::FunctionCallError;
+use crate::openai_tools::JsonSchema;
+use crate::openai_tools::OpenAiTool;
+use crate::openai_tools::ResponsesApiTool;
+use crate::protocol::AskForApproval;
+use crate::protocol::Event;
+use crate::protocol::EventMsg;
+use crate::protocol::InputItem;
+use crate::protocol::Op;
+use crate::protocol::ReviewDecision;
+use crate::protocol::SandboxPolicy;
+use crate::protocol::SubAgentBeginEvent;
+use crate::protocol::SubAgentEndEvent;
+use crate::protocol::SubAgentProgressEvent;
+use crate::protocol::SubAgentStatus;
+use crate::protocol::TokenUsage;
+use codex_protocol::protocol::InitialHistory;
+
+pub(crate) const SPAWN_AGENT_TOOL_NAME: &str = "spawn_agent";
+
+/// Longest progress message forwarded to the parent.
+const PROGRESS_MAX_CHARS: usize = 120;
+
+const SUB_AGENT_PREAMBLE: &str = "You are a sub-agent working on behalf of another Codex agent. \
+Complete the task below on your own: you cannot ask the user questions or request approval \
+for commands. When you are done, reply with a concise summary of what you did, the files you \
+changed and anything the parent agent needs to know.";
+
+pub(crate) fn create_spawn_agent_tool() -> OpenAiTool {
+    let mut agent_properties = BTreeMap::new();
+    agent_properties.insert(
+        "task".to_string(),
+        JsonSchema::String {
+            description: Some(
+                "Self-contained description of the work; the sub-agent does not see this conversation"
+                    .to_string(),
+            ),
+        },
+    );
+    agent_properties.insert(
+        "label".to_string(),
+        JsonSchema::String {
+            description: Some("Short name used when reporting progress".to_string()),
+        },
+    );
+    agent_properties.insert(
+        "read_only".to_string(),
+        JsonSchema::Boolean {
+            description: Some(
+                "Run the sub-agent in a read-only sandbox, e.g. for investigation tasks"
+                    .to_string(),
+            ),
+        },
+    );
+
+    let mut properties = BTreeMap::new();
+    properties.insert(
+        "agents".to_string(),
+        JsonSchema::Array {
+            items: Box::new(JsonSchema::Object {
+                properties: agent_properties,
+                required: Some(vec!["task".to_string()]),
+                additional_properties: Some(false),
+            }),
+            description: Some("One entry per sub-agent to start".to_string()),
+        },
+    );
+
+    OpenAiTool::Function(ResponsesApiTool {
+        name: SPAWN_AGENT_TOOL_NAME.to_string(),
+        description: r#"Delegate independent tasks to sub-agents that run concurrently in child sessions.
+Each sub-agent starts from a fresh conversation in the current working directory. It can run shell commands and edit files inside the sandbox but cannot request approval or spawn agents of its own.
+Pass several entries in `agents` to run them in parallel. The call returns once every sub-agent has finished, with each one's final message."#
+            .to_string(),
+        strict: false,
+        parameters: JsonSchema::Object {
+            properties,
+            required: Some(vec!["agents".to_string()]),
+            additional_properties: Some(false),
+        },
+    })
+}
+
+#[derive(Debug, Deserialize)]
+struct SpawnAgentArgs {
+    agents: Vec<SubAgentTask>,
+}
+
+#[derive(Debug, Deserialize)]
+struct SubAgentTask {
+    task: String,
+    #[serde(default)]
+    label: Option<String>,
+    #[serde(default)]
+    read_only: bool,
+}
+
+/// Per-agent entry of the JSON returned to the parent model.
+#[derive(Debug, Serialize)]
+struct SubAgentOutcome {
+    agent: String,
+    status: SubAgentStatus,
+    final_message: Option<String>,
+    total_tokens: u64,
+}
+
+pub(crate) async fn handle_spawn_agent(
+    sess: &Session,
+    turn_context: &TurnContext,
+    sub_id: String,
+    call_id: String,
+    arguments: String,
+) -> Result<String, FunctionCallError> {
+    let args: SpawnAgentArgs = serde_json::from_str(&arguments).map_err(|e| {
+        FunctionCallError::RespondToModel(format!("failed to parse function arguments: {e:?}"))
+    })?;
+    if args.agents.is_empty() {
+        return Err(FunctionCallError::RespondToModel(
+            "`agents` must contain at least one task".to_string(),
+        ));
+    }
+    let auth_manager = turn_context.client.get_auth_manager().ok_or_else(|| {
+        FunctionCallError::RespondToModel(
+            "sub-agents are not available in this session".to_string(),
+        )
+    })?;
+
+    let base_config = turn_context.client.get_config();
+    let limits = base_config.sub_agents.clone();
+    let runs = args.agents.into_iter().enumerate().map(|(idx, task)| {
+        let label = task
+            .label
+            .as_deref()
+            .map(str::trim)
+            .filter(|label| !label.is_empty())
+            .map(str::to_string)
+            .unwrap_or_else(|| format!("agent-{}", idx + 1));
+        let config = child_config(&base_config, turn_context, task.read_only);
+        let run = SubAgentRun {
+            sess,
+            sub_id: &sub_id,
+            call_id: &call_id,
+            label,
+            max_tokens: limits.max_tokens,
+        };
+        let auth_manager = auth_manager.clone();
+        async move { (idx, run.run(config, auth_manager, task.task).await) }
+    });
+    let mut outcomes: Vec<(usize, SubAgentOutcome)> = futures::stream::iter(runs)
+        .buffer_unordered(limits.max_concurrency)
+        .collect()
+        .await;
+    outcomes.sort_by_key(|(idx, _)| *idx);
+    let outcomes: Vec<SubAgentOutcome> = outcomes.into_iter().map(|(_, o)| o).collect();
+
+    serde_json::to_string(&outcomes).map_err(|e| {
+        FunctionCallError::RespondToModel(format!("failed to serialize sub-agent results: {e}"))
+    })
+}
+
+/// Configuration for a child session: same model, reasoning settings and cwd
+/// as the parent turn, no MCP servers, no approvals and no nested
+/// `spawn_agent`.
+fn child_config(base: &Config, turn_context: &TurnContext, read_only: bool) -> Config {
+    let mut config = base.clone();
+    config.model = turn_context.client.get_model();
+    config.model_family = turn_context.client.get_model_family();
+    config.model_reasoning_effort = turn_context.client.get_reasoning_effort();
+    config.model_reasoning_summary = turn_context.client.get_reasoning_summary();
+    config.cwd = turn_context.cwd.clone();
+    config.approval_policy = AskForApproval::Never;
+    config.sandbox_policy = child_sandbox_policy(&turn_context.sandbox_policy, read_only);
+    config.shell_environment_policy = turn_context.shell_environment_policy.clone();
+    config.mcp_servers.clear();
+    config.include_plan_tool = false;
+    config.include_view_image_tool = false;
+    config.screenshot = None;
+    config.include_spawn_agent_tool = false;
+    config.tools_web_search_request = false;
+    config.notify = None;
+    config
+}
+
+/// Children never get more access than the parent and never run without a
+/// sandbox, even when the parent does.
+fn child_sandbox_policy(parent: &SandboxPolicy, read_only: bool) -> SandboxPolicy {
+    if read_only {
+        return SandboxPolicy::new_read_only_policy();
+    }
+    match parent {
+        SandboxPolicy::DangerFullAccess => SandboxPolicy::new_workspace_write_policy(),
+        other => other.clone(),
+    }
+}
+
+struct SubAgentRun<'a> {
+    sess: &'a Session,
+    sub_id: &'a str,
+    call_id: &'a str,
+    label: String,
+    max_tokens: Option<u64>,
+}
+
+impl SubAgentRun<'_> {
+    async fn run(
+        self,
+        config: Config,
+        auth_manager: Arc<AuthManager>,
+        task: String,
+    ) -> SubAgentOutcome {
+        self.send(EventMsg::SubAgentBegin(SubAgentBeginEvent {
+            call_id: self.call_id.to_string(),
+            agent: self.label.clone(),
+            task: task.clone(),
+        }))
+        .await;
+
+        let (status, final_message, token_usage) =
+            match Codex::spawn(config, auth_manager, InitialHistory::New).await {
+                Ok(CodexSpawnOk { codex, .. }) => self.drive(ChildSession::new(codex), task).await,
+                Err(e) => {
+                    self.progress(format!("failed to start: {e}")).await;
+                    (SubAgentStatus::Failed, None, TokenUsage::default())
+                }
+            };
+
+        self.send(EventMsg::SubAgentEnd(SubAgentEndEvent {
+            call_id: self.call_id.to_string(),
+            agent: self.label.clone(),
+            status,
+            final_message: final_message.clone(),
+            token_usage: token_usage.clone(),
+        }))
+        .await;
+
+        SubAgentOutcome {
+            agent: self.label,
+            status,
+            final_message,
+            total_tokens: token_usage.total_tokens,
+        }
+    }
+
+    /// Submit the task and pump child events until the child's turn ends.
+    async fn drive(
+        &self,
+        mut child: ChildSession,
+        task: String,
+    ) -> (SubAgentStatus, Option<String>, TokenUsage) {
+        let mut token_usage = TokenUsage::default();
+        let mut budget_exceeded = false;
+        let mut last_error: Option<String> = None;
+
+        let input = vec![InputItem::Text {
+            text: format!("{SUB_AGENT_PREAMBLE}\n\nTask: {task}"),
+        }];
+        if let Err(e) = child.submit(Op::UserInput { items: input }).await {
+            self.progress(format!("failed to submit task: {e}")).await;
+            return (SubAgentStatus::Failed, None, token_usage);
+        }
+
+        let (status, final_message) = loop {
+            let event = match child.next_event().await {
+                Ok(event) => event,
+                Err(e) => break (SubAgentStatus::Failed, Some(e.to_string())),
+            };
+            match event.msg {
+                EventMsg::TaskComplete(ev) => {
+                    let status = if budget_exceeded {
+                        SubAgentStatus::BudgetExceeded
+                    } else if ev.last_agent_message.is_none() && last_error.is_some() {
+                        SubAgentStatus::Failed
+                    } else {
+                        SubAgentStatus::Completed
+                    };
+                    break (status, ev.last_agent_message.or(last_error));
+                }
+                EventMsg::TurnAborted(_) => {
+                    let status = if budget_exceeded {
+                        SubAgentStatus::BudgetExceeded
+                    } else {
+                        SubAgentStatus::Interrupted
+                    };
+                    break (status, last_error);
+                }
+                EventMsg::ShutdownComplete => break (SubAgentStatus::Interrupted, last_error),
+                EventMsg::TokenCount(ev) => {
+                    if let Some(info) = ev.info {
+                        token_usage = info.total_token_usage;
+                    }
+                    if let Some(max_tokens) = self.max_tokens
+                        && !budget_exceeded
+                        && token_usage.total_tokens >= max_tokens
+                    {
+                        budget_exceeded = true;
+                        self.progress(format!("stopping: token budget of {max_tokens} used up"))
+                            .await;
+                        let _ = child.submit(Op::Interrupt).await;
+                    }
+                }
+                EventMsg::ExecCommandBegin(ev) => {
+                    self.progress(format!("running `{}`", describe_command(&ev.command)))
+                        .await;
+                }
+                EventMsg::PatchApplyBegin(ev) => {
+                    let mut files: Vec<String> = ev
+                        .changes
+                        .keys()
+                        .map(|path| path.display().to_string())
+                        .collect();
+                    files.sort();
+                    self.progress(format!("editing {}", files.join(", "))).await;
+                }
+                EventMsg::Error(ev) => {
+                    self.progress(format!("error: {}", ev.message)).await;
+                    last_error = Some(ev.message);
+                }
+                // Children run with `AskForApproval::Never`; refuse anything
+                // that still asks so the child cannot block forever.
+                EventMsg::ExecApprovalRequest(_) => {
+                    let _ = child
+                        .submit(Op::ExecApproval {
+                            id: event.id,
+                            decision: ReviewDecision::Denied,
+                        })
+                        .await;
+                }
+                EventMsg::ApplyPatchApprovalRequest(_) => {
+                    let _ = child
+                        .submit(Op::PatchApproval {
+                            id: event.id,
+                            decision: ReviewDecision::Denied,
+                        })
+                        .await;
+                }
+                _ => {}
+            }
+        };
+
+        child.shutdown().await;
+        (status, final_message, token_usage)
+    }
+
+    async fn progress(&self, message: String) {
+        self.send(EventMsg::SubAgentProgress(SubAgentProgressEvent {
+            call_id: self.call_id.to_string(),
+            agent: self.label.clone(),
+            message: truncate_progress(&message),
+        }))
+        .await;
+    }
+
+    async fn send(&self, msg: EventMsg) {
+        self.sess
+            .send_event(Event {
+                id: self.sub_id.to_string(),
+                msg,
+            })
+            .await;
+    }
+}
+
+/// Owns a child [`Codex`] and shuts it down if the parent turn is dropped
+/// (e.g. interrupted) before the child finished.
+struct ChildSession {
+    codex: Option<Codex>,
+}
+
+impl ChildSession {
+    fn new(codex: Codex) -> Self {
+        Self { codex: Some(codex) }
+    }
+
+    async fn submit(&self, op: Op) -> crate::error::Result<String> {
+        match &self.codex {
+            Some(codex) => codex.submit(op).await,
+            None => Err(crate::error::CodexErr::InternalAgentDied),
+        }
+    }
+
+    async fn next_event(&self) -> crate::error::Result<Event> {
+        match &self.codex {
+            Some(codex) => codex.next_event().await,
+            None => Err(crate::error::CodexErr::InternalAgentDied),
+        }
+    }
+
+    async fn shutdown(&mut self) {
+        if let Some(codex) = self.codex.take() {
+            let _ = codex.submit(Op::Shutdown).await;
+        }
+    }
+}
+
+impl Drop for ChildSession {
+    fn drop(&mut self) {
+        if let Some(codex) = self.codex.take()
+            && let Ok(handle) = tokio::runtime::Handle::try_current()
+        {
+            handle.spawn(async move {
+                let _ = codex.submit(Op::Shutdown).await;
+            });
+        }
+    }
+}
+
+/// Human-readable form of a command, unwrapping `bash -lc <script>`.
+fn describe_command(command: &[String]) -> String {
+    match command {
+        [shell, flag, script] if flag == "-lc" && shell.ends_with("sh") => script.clone(),
+        _ => shlex::try_join(command.iter().map(String::as_str))
+            .unwrap_or_else(|_| command.join(" ")),
+    }
+}
+
+fn truncate_progress(message: &str) -> String {
+    let first_line = message.lines().next().unwrap_or_default();
+    if first_line.chars().count() <= PROGRESS_MAX_CHARS {
+        return first_line.to_string();
+    }
+    let truncated: String = first_line.chars().take(PROGRESS_MAX_CHARS - 1).collect();
+    format!("{truncated}…")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::ModelClient;
+    use crate::model_family::find_family_for_model;
+    use codex_protocol::ConversationId;
+    use codex_protocol::config_types::ReasoningEffort;
+    use codex_protocol::config_types::ReasoningSummary;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn children_are_never_unsandboxed() {
+        let workspace_write = SandboxPolicy::new_workspace_write_policy();
+        assert_eq!(
+            child_sandbox_policy(&SandboxPolicy::DangerFullAccess, false),
+            workspace_write
+        );
+        assert_eq!(
+            child_sandbox_policy(&workspace_write, false),
+            workspace_write
+        );
+        assert_eq!(
+            child_sandbox_policy(&workspace_write, true),
+            SandboxPolicy::new_read_only_policy()
+        );
+        assert_eq!(
+            child_sandbox_policy(&SandboxPolicy::new_read_only_policy(), false),
+            SandboxPolicy::new_read_only_policy()
+        );
+    }
+
+    #[test]
+    fn children_use_the_model_of_the_parent_turn() {
+        let (_session, mut turn_context) = crate::codex::make_session_and_context();
+        let session_config = turn_context.client.get_config();
+        let mut turn_config = (*session_config).clone();
+        turn_config.model = "o3".to_string();
+        turn_config.model_family =
+            find_family_for_model("o3").expect("o3 should have a model family");
+        turn_context.client = ModelClient::new(
+            Arc::new(turn_config),
+            None,
+            turn_context.client.get_otel_event_manager(),
+            turn_context.client.get_provider(),
+            Some(ReasoningEffort::High),
+            ReasoningSummary::Detailed,
+            ConversationId::default(),
+        );
+
+        let config = child_config(&session_config, &turn_context, false);
+        assert_eq!(config.model, "o3");
+        assert_eq!(config.model_family.slug, "o3");
+        assert_eq!(config.model_reasoning_effort, Some(ReasoningEffort::High));
+        assert_eq!(config.model_reasoning_summary, ReasoningSummary::Detailed);
+    }
+
+    #[test]
+    fn progress_messages_are_single_short_lines() {
+        let command = vec![
+            "bash".to_string(),
+            "-lc".to_string(),
+            "cargo test".to_string(),
+        ];
+        assert_eq!(describe_command(&command), "cargo test");
+        let command = vec!["rg".to_string(), "foo bar".to_string()];
+        assert_eq!(describe_command(&command), "rg 'foo bar'");
+
+        assert_eq!(truncate_progress("first\nsecond"), "first");
+        let long = "x".repeat(PROGRESS_MAX_CHARS + 10);
+        let truncated = truncate_progress(&long);
+        assert_eq!(truncated.chars().count(), PROGRESS_MAX_CHARS);
+        assert!(truncated.ends_with('…'));
+    }
+}
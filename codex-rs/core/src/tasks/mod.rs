@@ -90,7 +90,9 @@ impl Session {
             kind: task_kind,
             task,
         };
-        self.register_new_active_task(sub_id, running_task).await;
+        self.register_new_active_task(sub_id.clone(), running_task)
+            .await;
+        self.arm_duration_budget_timer(sub_id);
     }
 
     pub async fn abort_all_tasks(self: &Arc<Self>, reason: TurnAbortReason) {
@@ -111,10 +113,11 @@ impl Session {
             *active = None;
         }
         drop(active);
-        let event = Event {
-            id: sub_id,
-            msg: EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }),
+        let msg = match self.take_task_abort(&sub_id).await {
+            Some(reason) => EventMsg::TurnAborted(TurnAbortedEvent { reason }),
+            None => EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }),
         };
+        let event = Event { id: sub_id, msg };
         self.send_event(event).await;
     }
 
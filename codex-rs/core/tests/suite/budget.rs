@@ -0,0 +1,127 @@
+use std::time::Duration;
+use std::time::Instant;
+
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::TurnAbortReason;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::sse;
+use core_test_support::responses::sse_response;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use core_test_support::wait_for_event_with_timeout;
+use pretty_assertions::assert_eq;
+use wiremock::Mock;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn max_turns_aborts_the_task_before_the_next_request() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    // The response asks for a tool call, so the task would normally send a
+    // second request with its output.
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_function_call("call-1", "no_such_tool", "{}"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|config| config.budget.max_turns = Some(1))
+        .build(&server)
+        .await
+        .expect("create new conversation");
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::Error(error) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::Error(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(
+        error.message,
+        "Session budget exhausted: made max_turns = 1 requests to the model"
+    );
+
+    let EventMsg::TurnAborted(aborted) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(aborted.reason, TurnAbortReason::BudgetExceeded);
+
+    let requests = server.received_requests().await.unwrap_or_default();
+    assert_eq!(requests.len(), 1);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn max_duration_interrupts_a_task_that_is_still_running() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    // The model takes far longer to answer than the session may run.
+    Mock::given(method("POST"))
+        .and(path("/v1/responses"))
+        .respond_with(
+            sse_response(sse(vec![ev_completed("resp-1")])).set_delay(Duration::from_secs(60)),
+        )
+        .mount(&server)
+        .await;
+
+    let TestCodex { codex, .. } = test_codex()
+        .with_config(|config| config.budget.max_duration_secs = Some(1))
+        .build(&server)
+        .await
+        .expect("create new conversation");
+
+    let started = Instant::now();
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "hello".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let EventMsg::Error(error) = wait_for_event_with_timeout(
+        &codex,
+        |ev| matches!(ev, EventMsg::Error(_)),
+        Duration::from_secs(10),
+    )
+    .await
+    else {
+        unreachable!()
+    };
+    assert_eq!(
+        error.message,
+        "Session budget exhausted: ran for max_duration_secs = 1 seconds"
+    );
+
+    let EventMsg::TurnAborted(aborted) =
+        wait_for_event(&codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await
+    else {
+        unreachable!()
+    };
+    assert_eq!(aborted.reason, TurnAbortReason::BudgetExceeded);
+    assert!(started.elapsed() < Duration::from_secs(30));
+}
@@ -5,6 +5,7 @@ use std::path::PathBuf;
 use std::time::Duration;
 
 use async_channel::Receiver;
+use codex_core::config_types::SandboxResourceLimits;
 use codex_core::error::CodexErr;
 use codex_core::error::SandboxErr;
 use codex_core::exec::ExecParams;
@@ -67,6 +68,7 @@ async fn test_exec_stdout_stream_events_echo() {
         &policy,
         cwd.as_path(),
         &None,
+        &SandboxResourceLimits::default(),
         Some(stdout_stream),
     )
     .await;
@@ -119,6 +121,7 @@ async fn test_exec_stderr_stream_events_echo() {
         &policy,
         cwd.as_path(),
         &None,
+        &SandboxResourceLimits::default(),
         Some(stdout_stream),
     )
     .await;
@@ -174,6 +177,7 @@ async fn test_aggregated_output_interleaves_in_order() {
         &policy,
         cwd.as_path(),
         &None,
+        &SandboxResourceLimits::default(),
         None,
     )
     .await
@@ -212,6 +216,7 @@ async fn test_exec_timeout_returns_partial_output() {
         &policy,
         cwd.as_path(),
         &None,
+        &SandboxResourceLimits::default(),
         None,
     )
     .await;
@@ -2,6 +2,7 @@
 
 #[cfg(not(target_os = "windows"))]
 mod abort_tasks;
+mod budget;
 mod cli_stream;
 mod client;
 mod compact;
@@ -17,7 +18,9 @@ mod prompt_caching;
 mod review;
 mod rmcp_client;
 mod rollout_list_find;
+mod screenshot;
 mod seatbelt;
 mod stream_error_allows_next_turn;
 mod stream_no_completed;
+mod sub_agent;
 mod user_notification;
@@ -4,16 +4,20 @@ use std::time::Duration;
 
 use codex_core::config_types::McpServerConfig;
 use codex_core::config_types::McpServerTransportConfig;
+use codex_core::credential_store::CredentialStore;
+use codex_core::mcp_oauth::McpOAuthTokens;
 
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::EventMsg;
 use codex_core::protocol::InputItem;
+use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::SandboxPolicy;
 use codex_protocol::config_types::ReasoningSummary;
 use core_test_support::responses;
 use core_test_support::responses::mount_sse_once_match;
 use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
 use core_test_support::test_codex::test_codex;
 use core_test_support::wait_for_event;
 use core_test_support::wait_for_event_with_timeout;
@@ -24,7 +28,13 @@ use tokio::process::Child;
 use tokio::process::Command;
 use tokio::time::Instant;
 use tokio::time::sleep;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
 use wiremock::matchers::any;
+use wiremock::matchers::body_string_contains;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
 
 #[tokio::test(flavor = "multi_thread", worker_threads = 1)]
 async fn stdio_server_round_trip() -> anyhow::Result<()> {
@@ -232,6 +242,7 @@ async fn streamable_http_tool_call_round_trip() -> anyhow::Result<()> {
                     transport: McpServerTransportConfig::StreamableHttp {
                         url: server_url,
                         bearer_token: None,
+                        bearer_token_env_var: None,
                     },
                     startup_timeout_sec: Some(Duration::from_secs(10)),
                     tool_timeout_sec: None,
@@ -328,6 +339,415 @@ async fn streamable_http_tool_call_round_trip() -> anyhow::Result<()> {
     Ok(())
 }
 
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn streamable_http_refreshes_rejected_oauth_token() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = responses::start_mock_server().await;
+
+    let call_id = "call-789";
+    let server_name = "rmcp_oauth";
+    let tool_name = format!("{server_name}__echo");
+
+    mount_sse_once_match(
+        &server,
+        any(),
+        responses::sse(vec![
+            serde_json::json!({
+                "type": "response.created",
+                "response": {"id": "resp-1"}
+            }),
+            responses::ev_function_call(call_id, &tool_name, "{\"message\":\"ping\"}"),
+            responses::ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    mount_sse_once_match(
+        &server,
+        any(),
+        responses::sse(vec![
+            responses::ev_assistant_message("msg-1", "done"),
+            responses::ev_completed("resp-2"),
+        ]),
+    )
+    .await;
+
+    let token_server = MockServer::start().await;
+    Mock::given(method("POST"))
+        .and(path("/token"))
+        .and(body_string_contains("grant_type=refresh_token"))
+        .and(body_string_contains("refresh_token=refresh-1"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+            "access_token": "access-2",
+            "token_type": "Bearer",
+            "expires_in": 3600,
+        })))
+        .expect(1)
+        .mount(&token_server)
+        .await;
+
+    let rmcp_http_server_bin = CargoBuild::new()
+        .package("codex-rmcp-client")
+        .bin("test_streamable_http_server")
+        .run()?
+        .path()
+        .to_string_lossy()
+        .into_owned();
+
+    let listener = TcpListener::bind("127.0.0.1:0")?;
+    let port = listener.local_addr()?.port();
+    drop(listener);
+    let bind_addr = format!("127.0.0.1:{port}");
+    let server_url = format!("http://{bind_addr}/mcp");
+
+    // The server accepts whatever token is in this file.
+    let token_dir = tempfile::tempdir()?;
+    let token_file = token_dir.path().join("token");
+    std::fs::write(&token_file, "access-1")?;
+    let mut http_server_child = Command::new(&rmcp_http_server_bin)
+        .kill_on_drop(true)
+        .env("MCP_STREAMABLE_HTTP_BIND_ADDR", &bind_addr)
+        .env("MCP_EXPECT_BEARER_TOKEN_FILE", &token_file)
+        .spawn()?;
+
+    wait_for_streamable_http_server(&mut http_server_child, &bind_addr, Duration::from_secs(5))
+        .await?;
+
+    let tokens = McpOAuthTokens {
+        client_id: "client-1".to_string(),
+        client_secret: None,
+        token_endpoint: format!("{}/token", token_server.uri()),
+        resource: server_url.clone(),
+        access_token: "access-1".to_string(),
+        refresh_token: Some("refresh-1".to_string()),
+        expires_at: None,
+        scope: None,
+    };
+    let fixture = test_codex()
+        .with_config(move |config| {
+            config.use_experimental_use_rmcp_client = true;
+            tokens
+                .save(&config.codex_home, &config.credential_store, server_name)
+                .expect("save OAuth tokens");
+            config.mcp_servers.insert(
+                server_name.to_string(),
+                McpServerConfig {
+                    transport: McpServerTransportConfig::StreamableHttp {
+                        url: server_url,
+                        bearer_token: None,
+                        bearer_token_env_var: None,
+                    },
+                    startup_timeout_sec: Some(Duration::from_secs(10)),
+                    tool_timeout_sec: None,
+                },
+            );
+        })
+        .build(&server)
+        .await?;
+    let session_model = fixture.session_configured.model.clone();
+
+    // The server revokes the token the session connected with.
+    std::fs::write(&token_file, "access-2")?;
+
+    fixture
+        .codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text {
+                text: "call the rmcp echo tool".into(),
+            }],
+            final_output_json_schema: None,
+            cwd: fixture.cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: session_model,
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+        .await?;
+
+    let end_event = wait_for_event_with_timeout(
+        &fixture.codex,
+        |ev| matches!(ev, EventMsg::McpToolCallEnd(_)),
+        Duration::from_secs(20),
+    )
+    .await;
+    let EventMsg::McpToolCallEnd(end) = end_event else {
+        unreachable!("event guard guarantees McpToolCallEnd");
+    };
+    let result = end
+        .result
+        .as_ref()
+        .expect("the call should succeed with the refreshed token");
+    assert_eq!(result.is_error, Some(false));
+
+    wait_for_event(&fixture.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    token_server.verify().await;
+    let saved = McpOAuthTokens::load(fixture.home.path(), &CredentialStore::File, server_name)?
+        .expect("tokens are still saved");
+    assert_eq!(saved.access_token, "access-2");
+
+    let _ = http_server_child.kill().await;
+    Ok(())
+}
+
+/// A test stdio server named `rmcp`, started with the rmcp client.
+async fn stdio_server_fixture(server: &MockServer) -> anyhow::Result<TestCodex> {
+    stdio_server_fixture_with(server, true, None).await
+}
+
+async fn stdio_server_fixture_with(
+    server: &MockServer,
+    use_rmcp_client: bool,
+    env: Option<HashMap<String, String>>,
+) -> anyhow::Result<TestCodex> {
+    let rmcp_test_server_bin = CargoBuild::new()
+        .package("codex-rmcp-client")
+        .bin("test_stdio_server")
+        .run()?
+        .path()
+        .to_string_lossy()
+        .into_owned();
+
+    test_codex()
+        .with_config(move |config| {
+            config.use_experimental_use_rmcp_client = use_rmcp_client;
+            config.mcp_servers.insert(
+                "rmcp".to_string(),
+                McpServerConfig {
+                    transport: McpServerTransportConfig::Stdio {
+                        command: rmcp_test_server_bin,
+                        args: Vec::new(),
+                        env,
+                    },
+                    startup_timeout_sec: Some(Duration::from_secs(10)),
+                    tool_timeout_sec: None,
+                },
+            );
+        })
+        .build(server)
+        .await
+}
+
+async fn submit_turn(fixture: &TestCodex, text: &str) -> anyhow::Result<()> {
+    fixture
+        .codex
+        .submit(Op::UserTurn {
+            items: vec![InputItem::Text { text: text.into() }],
+            final_output_json_schema: None,
+            cwd: fixture.cwd.path().to_path_buf(),
+            approval_policy: AskForApproval::Never,
+            sandbox_policy: SandboxPolicy::DangerFullAccess,
+            model: fixture.session_configured.model.clone(),
+            effort: None,
+            summary: ReasoningSummary::Auto,
+        })
+        .await?;
+    Ok(())
+}
+
+async fn wait_for_tool_call_end(fixture: &TestCodex) -> McpToolCallEndEvent {
+    let end_event = wait_for_event_with_timeout(
+        &fixture.codex,
+        |ev| matches!(ev, EventMsg::McpToolCallEnd(_)),
+        Duration::from_secs(20),
+    )
+    .await;
+    let EventMsg::McpToolCallEnd(end) = end_event else {
+        unreachable!("event guard guarantees McpToolCallEnd");
+    };
+    end
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn stdio_server_is_restarted_after_exiting() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = responses::start_mock_server().await;
+    for (call_id, tool, arguments) in [
+        ("call-exit", "rmcp__exit", "{}"),
+        ("call-echo", "rmcp__echo", "{\"message\":\"ping\"}"),
+    ] {
+        mount_sse_once_match(
+            &server,
+            any(),
+            responses::sse(vec![
+                responses::ev_function_call(call_id, tool, arguments),
+                responses::ev_completed("resp-call"),
+            ]),
+        )
+        .await;
+        mount_sse_once_match(
+            &server,
+            any(),
+            responses::sse(vec![
+                responses::ev_assistant_message("msg", "done"),
+                responses::ev_completed("resp-done"),
+            ]),
+        )
+        .await;
+    }
+
+    let fixture = stdio_server_fixture(&server).await?;
+
+    submit_turn(&fixture, "stop the server").await?;
+    let end = wait_for_tool_call_end(&fixture).await;
+    assert_eq!(end.invocation.tool, "exit");
+    assert!(end.result.is_err(), "the server exited without replying");
+    wait_for_event(&fixture.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    submit_turn(&fixture, "call the echo tool").await?;
+    let end = wait_for_tool_call_end(&fixture).await;
+    assert_eq!(end.invocation.tool, "echo");
+    let result = end
+        .result
+        .as_ref()
+        .expect("the restarted server should answer");
+    assert_eq!(
+        result
+            .structured_content
+            .as_ref()
+            .and_then(|content| content["echo"].as_str()),
+        Some("ECHOING: ping")
+    );
+    wait_for_event(&fixture.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn tools_added_by_list_changed_are_offered_to_the_model() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = responses::start_mock_server().await;
+    mount_sse_once_match(
+        &server,
+        any(),
+        responses::sse(vec![
+            responses::ev_function_call("call-add", "rmcp__add_tool", "{}"),
+            responses::ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+    mount_sse_once_match(
+        &server,
+        any(),
+        responses::sse(vec![
+            responses::ev_assistant_message("msg-1", "added"),
+            responses::ev_completed("resp-2"),
+        ]),
+    )
+    .await;
+    // Only answered if the next turn offers the new tool.
+    mount_sse_once_match(
+        &server,
+        body_string_contains("rmcp__echo_again"),
+        responses::sse(vec![
+            responses::ev_function_call(
+                "call-echo-again",
+                "rmcp__echo_again",
+                "{\"message\":\"pong\"}",
+            ),
+            responses::ev_completed("resp-3"),
+        ]),
+    )
+    .await;
+    mount_sse_once_match(
+        &server,
+        any(),
+        responses::sse(vec![
+            responses::ev_assistant_message("msg-2", "done"),
+            responses::ev_completed("resp-4"),
+        ]),
+    )
+    .await;
+
+    let fixture = stdio_server_fixture(&server).await?;
+
+    submit_turn(&fixture, "add a tool").await?;
+    let end = wait_for_tool_call_end(&fixture).await;
+    assert_eq!(end.invocation.tool, "add_tool");
+    assert!(end.result.is_ok(), "{:?}", end.result);
+    wait_for_event(&fixture.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    submit_turn(&fixture, "call the new tool").await?;
+    let end = wait_for_tool_call_end(&fixture).await;
+    assert_eq!(end.invocation.tool, "echo_again");
+    let result = end.result.as_ref().expect("echo_again should succeed");
+    assert_eq!(
+        result
+            .structured_content
+            .as_ref()
+            .and_then(|content| content["echo"].as_str()),
+        Some("ECHOING: pong")
+    );
+    wait_for_event(&fixture.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    Ok(())
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn interrupt_cancels_rmcp_tool_call() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    interrupt_cancels_tool_call(true).await
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 1)]
+async fn interrupt_cancels_legacy_tool_call() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    interrupt_cancels_tool_call(false).await
+}
+
+/// Interrupt a turn while the `wait` tool runs and check that the server
+/// received `notifications/cancelled` for the call.
+async fn interrupt_cancels_tool_call(use_rmcp_client: bool) -> anyhow::Result<()> {
+    let server = responses::start_mock_server().await;
+    mount_sse_once_match(
+        &server,
+        any(),
+        responses::sse(vec![
+            responses::ev_function_call("call-wait", "rmcp__wait", "{}"),
+            responses::ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+
+    let cancelled_dir = tempfile::tempdir()?;
+    let cancelled_file = cancelled_dir.path().join("cancelled");
+    let env = HashMap::from([(
+        "MCP_CANCELLED_FILE".to_string(),
+        cancelled_file.to_string_lossy().into_owned(),
+    )]);
+    let fixture = stdio_server_fixture_with(&server, use_rmcp_client, Some(env)).await?;
+
+    submit_turn(&fixture, "wait").await?;
+    wait_for_event_with_timeout(
+        &fixture.codex,
+        |ev| matches!(ev, EventMsg::McpToolCallBegin(_)),
+        Duration::from_secs(20),
+    )
+    .await;
+    fixture.codex.submit(Op::Interrupt).await?;
+    wait_for_event(&fixture.codex, |ev| matches!(ev, EventMsg::TurnAborted(_))).await;
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        if let Ok(reason) = std::fs::read_to_string(&cancelled_file) {
+            assert_eq!(reason, "request cancelled by the client");
+            break;
+        }
+        assert!(
+            Instant::now() < deadline,
+            "the server never received notifications/cancelled"
+        );
+        sleep(Duration::from_millis(50)).await;
+    }
+
+    Ok(())
+}
+
 async fn wait_for_streamable_http_server(
     server_child: &mut Child,
     address: &str,
@@ -0,0 +1,171 @@
+#![allow(clippy::unwrap_used)]
+
+use codex_core::config_types::ScreenshotConfig;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::TestCodex;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+use wiremock::MockServer;
+
+/// Writes a few bytes to `{output}`; only the `.png` extension matters for
+/// attaching it.
+fn fake_renderer() -> ScreenshotConfig {
+    ScreenshotConfig {
+        command: vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            "printf 'not really a png' > \"$0\"".to_string(),
+            "{output}".to_string(),
+        ],
+        width: 800,
+        height: 600,
+        timeout_ms: 10_000,
+    }
+}
+
+async fn mount_capture_then_done(server: &MockServer) {
+    let args = json!({ "target": "index.html" }).to_string();
+    mount_sse_sequence(
+        server,
+        vec![
+            sse(vec![
+                ev_function_call("call-shot", "capture_screenshot", &args),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("msg-1", "Looks fine"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+}
+
+async fn start_turn(test: &TestCodex) {
+    std::fs::write(test.cwd.path().join("index.html"), "<h1>hi</h1>").unwrap();
+    test.codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "check the page".into(),
+            }],
+        })
+        .await
+        .unwrap();
+}
+
+/// The second request, which carries the tool output and any attached image.
+async fn followup_input(server: &MockServer) -> Vec<Value> {
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 2);
+    requests[1].body_json::<Value>().unwrap()["input"]
+        .as_array()
+        .unwrap()
+        .clone()
+}
+
+fn tool_output(input: &[Value]) -> String {
+    input
+        .iter()
+        .find(|item| item["type"] == "function_call_output" && item["call_id"] == "call-shot")
+        .and_then(|item| item["output"].as_str())
+        .unwrap()
+        .to_string()
+}
+
+fn screenshots_left(test: &TestCodex) -> usize {
+    std::fs::read_dir(test.home.path().join("screenshots"))
+        .map(Iterator::count)
+        .unwrap_or(0)
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn refuses_to_capture_when_sandbox_blocks_network() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    mount_capture_then_done(&server).await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.screenshot = Some(fake_renderer());
+            config.sandbox_policy = SandboxPolicy::ReadOnly;
+            config.approval_policy = AskForApproval::Never;
+        })
+        .build(&server)
+        .await
+        .unwrap();
+    start_turn(&test).await;
+    wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let input = followup_input(&server).await;
+    assert_eq!(
+        tool_output(&input),
+        "screenshots cannot be captured because the sandbox blocks network access"
+    );
+    assert_eq!(screenshots_left(&test), 0);
+}
+
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn renders_as_a_command_and_deletes_the_screenshot_once_attached() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    mount_capture_then_done(&server).await;
+
+    let test = test_codex()
+        .with_config(|config| {
+            config.screenshot = Some(fake_renderer());
+            config.sandbox_policy = SandboxPolicy::DangerFullAccess;
+            config.approval_policy = AskForApproval::Never;
+        })
+        .build(&server)
+        .await
+        .unwrap();
+    start_turn(&test).await;
+
+    // The renderer runs through the same path as shell commands.
+    let EventMsg::ExecCommandBegin(begin) = wait_for_event(&test.codex, |ev| {
+        matches!(ev, EventMsg::ExecCommandBegin(_))
+    })
+    .await
+    else {
+        unreachable!("predicate only matches ExecCommandBegin");
+    };
+    assert_eq!(begin.call_id, "call-shot");
+    assert_eq!(begin.command[0], "sh");
+    wait_for_event(&test.codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let input = followup_input(&server).await;
+    let output = tool_output(&input);
+    assert!(
+        output.starts_with("attached screenshot of file://")
+            && output.ends_with("/index.html (800x600)"),
+        "unexpected tool output: {output}"
+    );
+    let attached = input.iter().any(|item| {
+        item["content"].as_array().is_some_and(|content| {
+            content.iter().any(|part| {
+                part["type"] == "input_image"
+                    && part["image_url"]
+                        .as_str()
+                        .is_some_and(|url| url.starts_with("data:image/png;base64,"))
+            })
+        })
+    });
+    assert!(attached, "screenshot was not attached: {input:?}");
+    assert_eq!(screenshots_left(&test), 0);
+}
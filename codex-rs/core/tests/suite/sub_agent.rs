@@ -0,0 +1,122 @@
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SubAgentStatus;
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_completed_with_tokens;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex::test_codex;
+use core_test_support::wait_for_event;
+use pretty_assertions::assert_eq;
+use serde_json::Value;
+use serde_json::json;
+
+/// The parent calls `spawn_agent`; the child session runs against the same
+/// provider and its final message is returned as the tool output.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn spawn_agent_returns_child_final_message() {
+    skip_if_no_network!();
+
+    let server = start_mock_server().await;
+    let args = json!({
+        "agents": [{ "task": "Summarize the README", "label": "docs", "read_only": true }]
+    })
+    .to_string();
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_function_call("call-spawn", "spawn_agent", &args),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("child-msg", "README looks good"),
+                ev_completed_with_tokens("resp-child", 42),
+            ]),
+            sse(vec![
+                ev_assistant_message("parent-msg", "All done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let codex = test_codex()
+        .with_config(|config| config.include_spawn_agent_tool = true)
+        .build(&server)
+        .await
+        .unwrap()
+        .codex;
+
+    codex
+        .submit(Op::UserInput {
+            items: vec![InputItem::Text {
+                text: "delegate the docs review".into(),
+            }],
+        })
+        .await
+        .unwrap();
+
+    let begin = wait_for_event(&codex, |ev| matches!(ev, EventMsg::SubAgentBegin(_))).await;
+    let EventMsg::SubAgentBegin(begin) = begin else {
+        unreachable!("predicate only matches SubAgentBegin");
+    };
+    assert_eq!(begin.agent, "docs");
+    assert_eq!(begin.task, "Summarize the README");
+
+    let end = wait_for_event(&codex, |ev| matches!(ev, EventMsg::SubAgentEnd(_))).await;
+    let EventMsg::SubAgentEnd(end) = end else {
+        unreachable!("predicate only matches SubAgentEnd");
+    };
+    assert_eq!(end.status, SubAgentStatus::Completed);
+    assert_eq!(end.final_message.as_deref(), Some("README looks good"));
+    assert_eq!(end.token_usage.total_tokens, 42);
+
+    wait_for_event(&codex, |ev| matches!(ev, EventMsg::TaskComplete(_))).await;
+
+    let requests = server.received_requests().await.unwrap();
+    assert_eq!(requests.len(), 3);
+
+    let tool_names = |body: &Value| -> Vec<String> {
+        body["tools"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .filter_map(|tool| tool["name"].as_str().map(str::to_string))
+            .collect()
+    };
+    let parent_body = requests[0].body_json::<Value>().unwrap();
+    assert!(tool_names(&parent_body).contains(&"spawn_agent".to_string()));
+
+    // The child sees only its task and cannot spawn agents of its own.
+    let child_body = requests[1].body_json::<Value>().unwrap();
+    assert!(!tool_names(&child_body).contains(&"spawn_agent".to_string()));
+    let child_input = child_body["input"].to_string();
+    assert!(child_input.contains("Summarize the README"));
+    assert!(!child_input.contains("delegate the docs review"));
+
+    let followup = requests[2].body_json::<Value>().unwrap();
+    let output = followup["input"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|item| item["type"] == "function_call_output" && item["call_id"] == "call-spawn")
+        .and_then(|item| item["output"].as_str())
+        .unwrap()
+        .to_string();
+    let output: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(
+        output,
+        json!([{
+            "agent": "docs",
+            "status": "completed",
+            "final_message": "README looks good",
+            "total_tokens": 42,
+        }])
+    );
+}
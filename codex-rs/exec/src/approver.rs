@@ -0,0 +1,120 @@
+//! Answers approval requests with an external program (`--approver` or
+//! `approver` in config.toml).
+//!
+//! The program is run once per request. It receives the `approval.requested`
+//! event as JSON on stdin and must print `{"decision": "<decision>"}` on
+//! stdout, where `<decision>` is one of `approved`, `approved_for_session`,
+//! `denied` or `abort`. Anything else, including a non-zero exit status or
+//! not answering within `approver_timeout_sec`, denies the request.
+
+use std::process::Stdio;
+use std::time::Duration;
+
+use anyhow::Context;
+use codex_core::CodexConversation;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use codex_core::protocol::ReviewDecision;
+use serde::Deserialize;
+use tokio::io::AsyncWriteExt;
+use tokio::process::Command;
+use tracing::info;
+use tracing::warn;
+
+use crate::event_processor_with_jsonl_output::approval_requested_event;
+use crate::exec_events::ApprovalRequestedEvent;
+use crate::exec_events::ThreadEvent;
+
+#[derive(Debug, Deserialize)]
+struct ApproverReply {
+    decision: ReviewDecision,
+}
+
+pub(crate) struct Approver {
+    program: String,
+    args: Vec<String>,
+    timeout: Duration,
+}
+
+impl Approver {
+    /// `argv` is the command split into tokens; `None` when it is empty.
+    pub(crate) fn new(argv: Vec<String>, timeout: Duration) -> Option<Self> {
+        let mut argv = argv.into_iter();
+        let program = argv.next()?;
+        Some(Self {
+            program,
+            args: argv.collect(),
+            timeout,
+        })
+    }
+
+    /// If `event` asks for an approval, ask the approver and submit its
+    /// decision. Returns whether the event was an approval request.
+    pub(crate) async fn answer(
+        &self,
+        conversation: &CodexConversation,
+        event: &Event,
+    ) -> anyhow::Result<bool> {
+        let Some(request) = approval_requested_event(event) else {
+            return Ok(false);
+        };
+        let decision = self.decide(request).await;
+        let id = event.id.clone();
+        let op = match event.msg {
+            EventMsg::ApplyPatchApprovalRequest(_) => Op::PatchApproval { id, decision },
+            _ => Op::ExecApproval { id, decision },
+        };
+        conversation.submit(op).await?;
+        Ok(true)
+    }
+
+    async fn decide(&self, request: ApprovalRequestedEvent) -> ReviewDecision {
+        match self.run(request).await {
+            Ok(decision) => {
+                info!("Approver decided {decision}");
+                decision
+            }
+            Err(e) => {
+                warn!("Approver failed, denying the request: {e:#}");
+                ReviewDecision::Denied
+            }
+        }
+    }
+
+    /// Dropping the child on timeout kills it, since it is spawned with
+    /// `kill_on_drop`.
+    async fn run(&self, request: ApprovalRequestedEvent) -> anyhow::Result<ReviewDecision> {
+        match tokio::time::timeout(self.timeout, self.ask(request)).await {
+            Ok(decision) => decision,
+            Err(_) => anyhow::bail!(
+                "approver did not answer within {} seconds",
+                self.timeout.as_secs()
+            ),
+        }
+    }
+
+    async fn ask(&self, request: ApprovalRequestedEvent) -> anyhow::Result<ReviewDecision> {
+        let payload = serde_json::to_vec(&ThreadEvent::ApprovalRequested(request))?;
+        let mut child = Command::new(&self.program)
+            .args(&self.args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::inherit())
+            .kill_on_drop(true)
+            .spawn()
+            .with_context(|| format!("failed to spawn approver `{}`", self.program))?;
+        if let Some(mut stdin) = child.stdin.take() {
+            stdin.write_all(&payload).await?;
+            stdin.write_all(b"\n").await?;
+        }
+
+        let output = child.wait_with_output().await?;
+        if !output.status.success() {
+            anyhow::bail!("approver exited with {}", output.status);
+        }
+        let reply: ApproverReply =
+            serde_json::from_slice(&output.stdout).context("approver reply is not valid JSON")?;
+        Ok(reply.decision)
+    }
+}
@@ -67,16 +67,50 @@ pub struct Cli {
     #[arg(long = "json", alias = "experimental-json", default_value_t = false)]
     pub json: bool,
 
+    /// Command that answers approval requests, e.g. `--approver ./approve.sh`.
+    /// Each request is written to its stdin as JSON and it replies with
+    /// `{"decision": "approved"}` (or `approved_for_session`, `denied`,
+    /// `abort`) on stdout. Overrides `approver` from config.toml.
+    #[arg(long = "approver", value_name = "CMD")]
+    pub approver: Option<String>,
+
+    /// How stdin is read. `jsonl` keeps the session open and reads user turns,
+    /// turn context overrides and approval decisions from stdin, one JSON
+    /// object per line. Implies `--json`.
+    #[arg(long = "input-format", value_enum, default_value_t = InputFormat::Text)]
+    pub input_format: InputFormat,
+
     /// Whether to include the plan tool in the conversation.
     #[arg(long = "include-plan-tool", default_value_t = false)]
     pub include_plan_tool: bool,
 
+    /// Run the session in a new git worktree on its own branch. Changes are
+    /// committed to that branch when the run finishes.
+    #[arg(long = "worktree", default_value_t = false)]
+    pub worktree: bool,
+
+    /// Record every model request and its streamed response into DIR so the
+    /// session can be replayed later with `--replay-cassette`.
+    #[arg(
+        long = "record-cassette",
+        value_name = "DIR",
+        conflicts_with = "replay_cassette"
+    )]
+    pub record_cassette: Option<PathBuf>,
+
+    /// Serve model responses from a cassette recorded with
+    /// `--record-cassette` instead of contacting the provider. Fails on any
+    /// request that was not recorded.
+    #[arg(long = "replay-cassette", value_name = "DIR")]
+    pub replay_cassette: Option<PathBuf>,
+
     /// Specifies file where the last message from the agent should be written.
     #[arg(long = "output-last-message")]
     pub last_message_file: Option<PathBuf>,
 
     /// Initial instructions for the agent. If not provided as an argument (or
-    /// if `-` is used), instructions are read from stdin.
+    /// if `-` is used), instructions are read from stdin. Optional with
+    /// `--input-format jsonl`.
     #[arg(value_name = "PROMPT")]
     pub prompt: Option<String>,
 }
@@ -111,3 +145,13 @@ pub enum Color {
     #[default]
     Auto,
 }
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, ValueEnum)]
+#[value(rename_all = "kebab-case")]
+pub enum InputFormat {
+    /// Read a single prompt.
+    #[default]
+    Text,
+    /// Read a stream of `ThreadInput` messages as JSONL.
+    Jsonl,
+}
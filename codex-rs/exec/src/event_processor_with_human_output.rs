@@ -7,20 +7,29 @@ use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentDeltaEvent;
 use codex_core::protocol::AgentReasoningRawContentEvent;
+use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::BackgroundEventEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::FileChange;
+use codex_core::protocol::McpElicitationRequestEvent;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::McpSamplingRequestEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
+use codex_core::protocol::ScreenshotCapturedEvent;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::SubAgentBeginEvent;
+use codex_core::protocol::SubAgentEndEvent;
+use codex_core::protocol::SubAgentProgressEvent;
+use codex_core::protocol::SubAgentStatus;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TurnAbortReason;
 use codex_core::protocol::TurnDiffEvent;
@@ -192,11 +201,12 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             }
             EventMsg::TokenCount(ev) => {
                 if let Some(usage_info) = ev.info {
-                    ts_println!(
-                        self,
-                        "tokens used: {}",
-                        format_with_separators(usage_info.total_token_usage.blended_total())
-                    );
+                    let tokens =
+                        format_with_separators(usage_info.total_token_usage.blended_total());
+                    match usage_info.total_cost_usd {
+                        Some(cost) => ts_println!(self, "tokens used: {tokens} (${cost:.2})"),
+                        None => ts_println!(self, "tokens used: {tokens}"),
+                    }
                 }
             }
             EventMsg::AgentMessageDelta(AgentMessageDeltaEvent { delta }) => {
@@ -340,6 +350,12 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                     format_mcp_invocation(&invocation).style(self.bold),
                 );
             }
+            EventMsg::McpToolCallProgress(ev) => {
+                let summary = ev.summary();
+                if !summary.is_empty() {
+                    println!("{}", summary.style(self.dimmed));
+                }
+            }
             EventMsg::McpToolCallEnd(tool_call_end_event) => {
                 let is_success = tool_call_end_event.is_success();
                 let McpToolCallEndEvent {
@@ -495,11 +511,50 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 ts_println!(self, "{}", "turn diff:".style(self.magenta));
                 println!("{unified_diff}");
             }
-            EventMsg::ExecApprovalRequest(_) => {
-                // Should we exit?
+            EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent { command, cwd, .. }) => {
+                ts_println!(
+                    self,
+                    "{} {} in {}",
+                    "approval requested".style(self.magenta),
+                    escape_command(&command).style(self.bold),
+                    cwd.to_string_lossy(),
+                );
+            }
+            EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+                changes, ..
+            }) => {
+                let mut paths: Vec<String> = changes
+                    .keys()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .collect();
+                paths.sort();
+                ts_println!(
+                    self,
+                    "{} apply_patch to {}",
+                    "approval requested".style(self.magenta),
+                    paths.join(", ").style(self.bold),
+                );
             }
-            EventMsg::ApplyPatchApprovalRequest(_) => {
-                // Should we exit?
+            EventMsg::McpSamplingRequest(McpSamplingRequestEvent { server_name, .. }) => {
+                ts_println!(
+                    self,
+                    "{} {} wants to sample the model",
+                    "approval requested".style(self.magenta),
+                    server_name.style(self.bold),
+                );
+            }
+            EventMsg::McpElicitationRequest(McpElicitationRequestEvent {
+                server_name,
+                message,
+                ..
+            }) => {
+                ts_println!(
+                    self,
+                    "{} {} asks: {}",
+                    "input requested".style(self.magenta),
+                    server_name.style(self.bold),
+                    message,
+                );
             }
             EventMsg::AgentReasoning(agent_reasoning_event) => {
                 if self.show_agent_reasoning {
@@ -574,6 +629,9 @@ impl EventProcessor for EventProcessorWithHumanOutput {
             EventMsg::GetHistoryEntryResponse(_) => {
                 // Currently ignored in exec output.
             }
+            EventMsg::SearchHistoryResponse(_) => {
+                // Currently ignored in exec output.
+            }
             EventMsg::McpListToolsResponse(_) => {
                 // Currently ignored in exec output.
             }
@@ -590,12 +648,67 @@ impl EventProcessor for EventProcessorWithHumanOutput {
                 TurnAbortReason::ReviewEnded => {
                     ts_println!(self, "task aborted: review ended");
                 }
+                TurnAbortReason::BudgetExceeded => {
+                    ts_println!(self, "task aborted: session budget exhausted");
+                    return CodexStatus::InitiateShutdown;
+                }
             },
             EventMsg::ShutdownComplete => return CodexStatus::Shutdown,
             EventMsg::ConversationPath(_) => {}
             EventMsg::UserMessage(_) => {}
             EventMsg::EnteredReviewMode(_) => {}
             EventMsg::ExitedReviewMode(_) => {}
+            EventMsg::SubAgentBegin(SubAgentBeginEvent { agent, task, .. }) => {
+                ts_println!(
+                    self,
+                    "{} {}\n{}",
+                    "sub-agent".style(self.magenta),
+                    agent.style(self.bold),
+                    task.style(self.dimmed)
+                );
+            }
+            EventMsg::SubAgentProgress(SubAgentProgressEvent { agent, message, .. }) => {
+                ts_println!(
+                    self,
+                    "{} {}",
+                    format!("[{agent}]").style(self.dimmed),
+                    message.style(self.dimmed)
+                );
+            }
+            EventMsg::SubAgentEnd(SubAgentEndEvent {
+                agent,
+                status,
+                final_message,
+                token_usage,
+                ..
+            }) => {
+                let outcome = match status {
+                    SubAgentStatus::Completed => "finished".style(self.green),
+                    SubAgentStatus::Failed => "failed".style(self.red),
+                    SubAgentStatus::BudgetExceeded => "ran out of token budget".style(self.red),
+                    SubAgentStatus::Interrupted => "interrupted".style(self.dimmed),
+                };
+                ts_println!(
+                    self,
+                    "{} {} {outcome} ({} tokens)",
+                    "sub-agent".style(self.magenta),
+                    agent.style(self.bold),
+                    token_usage.total_tokens
+                );
+                if let Some(message) = final_message {
+                    println!("{}", message.style(self.dimmed));
+                }
+            }
+            EventMsg::ScreenshotCaptured(ScreenshotCapturedEvent {
+                url, width, height, ..
+            }) => {
+                ts_println!(
+                    self,
+                    "{} {} ({width}x{height})",
+                    "screenshot".style(self.magenta),
+                    url.style(self.bold)
+                );
+            }
         }
         CodexStatus::Running
     }
@@ -5,15 +5,20 @@ use std::sync::atomic::AtomicU64;
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
 use crate::event_processor::handle_last_message;
+use crate::exec_events::ApprovalRequestDetails;
+use crate::exec_events::ApprovalRequestedEvent;
 use crate::exec_events::AssistantMessageItem;
+use crate::exec_events::CommandApprovalRequest;
 use crate::exec_events::CommandExecutionItem;
 use crate::exec_events::CommandExecutionStatus;
+use crate::exec_events::FileChangeApprovalRequest;
 use crate::exec_events::FileChangeItem;
 use crate::exec_events::FileUpdateChange;
 use crate::exec_events::ItemCompletedEvent;
 use crate::exec_events::ItemStartedEvent;
 use crate::exec_events::ItemUpdatedEvent;
 use crate::exec_events::McpToolCallItem;
+use crate::exec_events::McpToolCallProgress;
 use crate::exec_events::McpToolCallStatus;
 use crate::exec_events::PatchApplyStatus;
 use crate::exec_events::PatchChangeKind;
@@ -35,18 +40,23 @@ use codex_core::plan_tool::StepStatus;
 use codex_core::plan_tool::UpdatePlanArgs;
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningEvent;
+use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::McpToolCallProgressEvent;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::SessionConfiguredEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TaskStartedEvent;
+use codex_core::protocol::TurnAbortReason;
+use codex_core::protocol::TurnAbortedEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use tracing::error;
 use tracing::warn;
@@ -81,6 +91,7 @@ struct RunningMcpToolCall {
     server: String,
     tool: String,
     item_id: String,
+    progress: Option<McpToolCallProgress>,
 }
 
 impl EventProcessorWithJsonOutput {
@@ -105,9 +116,16 @@ impl EventProcessorWithJsonOutput {
             EventMsg::ExecCommandBegin(ev) => self.handle_exec_command_begin(ev),
             EventMsg::ExecCommandEnd(ev) => self.handle_exec_command_end(ev),
             EventMsg::McpToolCallBegin(ev) => self.handle_mcp_tool_call_begin(ev),
+            EventMsg::McpToolCallProgress(ev) => self.handle_mcp_tool_call_progress(ev),
             EventMsg::McpToolCallEnd(ev) => self.handle_mcp_tool_call_end(ev),
             EventMsg::PatchApplyBegin(ev) => self.handle_patch_apply_begin(ev),
             EventMsg::PatchApplyEnd(ev) => self.handle_patch_apply_end(ev),
+            EventMsg::ExecApprovalRequest(_) | EventMsg::ApplyPatchApprovalRequest(_) => {
+                approval_requested_event(event)
+                    .map(ThreadEvent::ApprovalRequested)
+                    .into_iter()
+                    .collect()
+            }
             EventMsg::WebSearchBegin(_) => Vec::new(),
             EventMsg::WebSearchEnd(ev) => self.handle_web_search_end(ev),
             EventMsg::TokenCount(ev) => {
@@ -118,6 +136,10 @@ impl EventProcessorWithJsonOutput {
             }
             EventMsg::TaskStarted(ev) => self.handle_task_started(ev),
             EventMsg::TaskComplete(_) => self.handle_task_complete(),
+            EventMsg::TurnAborted(TurnAbortedEvent {
+                reason: TurnAbortReason::BudgetExceeded,
+            }) => self.handle_budget_exceeded(),
+            EventMsg::TurnAborted(_) => self.handle_turn_interrupted(),
             EventMsg::Error(ev) => {
                 let error = ThreadErrorEvent {
                     message: ev.message.clone(),
@@ -184,16 +206,7 @@ impl EventProcessorWithJsonOutput {
     fn handle_exec_command_begin(&mut self, ev: &ExecCommandBeginEvent) -> Vec<ThreadEvent> {
         let item_id = self.get_next_item_id();
 
-        let command_string = match shlex::try_join(ev.command.iter().map(String::as_str)) {
-            Ok(command_string) => command_string,
-            Err(e) => {
-                warn!(
-                    call_id = ev.call_id,
-                    "Failed to stringify command: {e:?}; skipping item.started"
-                );
-                ev.command.join(" ")
-            }
-        };
+        let command_string = command_to_string(&ev.call_id, &ev.command);
 
         self.running_commands.insert(
             ev.call_id.clone(),
@@ -227,6 +240,7 @@ impl EventProcessorWithJsonOutput {
                 server: server.clone(),
                 tool: tool.clone(),
                 item_id: item_id.clone(),
+                progress: None,
             },
         );
 
@@ -236,12 +250,37 @@ impl EventProcessorWithJsonOutput {
                 server,
                 tool,
                 status: McpToolCallStatus::InProgress,
+                progress: None,
             }),
         };
 
         vec![ThreadEvent::ItemStarted(ItemStartedEvent { item })]
     }
 
+    fn handle_mcp_tool_call_progress(&mut self, ev: &McpToolCallProgressEvent) -> Vec<ThreadEvent> {
+        let Some(running) = self.running_mcp_tool_calls.get_mut(&ev.call_id) else {
+            return Vec::new();
+        };
+        let progress = McpToolCallProgress {
+            progress: ev.progress,
+            total: ev.total,
+            message: ev.message.clone(),
+        };
+        running.progress = Some(progress.clone());
+
+        let item = ThreadItem {
+            id: running.item_id.clone(),
+            details: ThreadItemDetails::McpToolCall(McpToolCallItem {
+                server: running.server.clone(),
+                tool: running.tool.clone(),
+                status: McpToolCallStatus::InProgress,
+                progress: Some(progress),
+            }),
+        };
+
+        vec![ThreadEvent::ItemUpdated(ItemUpdatedEvent { item })]
+    }
+
     fn handle_mcp_tool_call_end(&mut self, ev: &McpToolCallEndEvent) -> Vec<ThreadEvent> {
         let status = if ev.is_success() {
             McpToolCallStatus::Completed
@@ -249,20 +288,27 @@ impl EventProcessorWithJsonOutput {
             McpToolCallStatus::Failed
         };
 
-        let (server, tool, item_id) = match self.running_mcp_tool_calls.remove(&ev.call_id) {
-            Some(running) => (running.server, running.tool, running.item_id),
-            None => {
-                warn!(
-                    call_id = ev.call_id,
-                    "Received McpToolCallEnd without begin; synthesizing new item"
-                );
-                (
-                    ev.invocation.server.clone(),
-                    ev.invocation.tool.clone(),
-                    self.get_next_item_id(),
-                )
-            }
-        };
+        let (server, tool, item_id, progress) =
+            match self.running_mcp_tool_calls.remove(&ev.call_id) {
+                Some(running) => (
+                    running.server,
+                    running.tool,
+                    running.item_id,
+                    running.progress,
+                ),
+                None => {
+                    warn!(
+                        call_id = ev.call_id,
+                        "Received McpToolCallEnd without begin; synthesizing new item"
+                    );
+                    (
+                        ev.invocation.server.clone(),
+                        ev.invocation.tool.clone(),
+                        self.get_next_item_id(),
+                        None,
+                    )
+                }
+            };
 
         let item = ThreadItem {
             id: item_id,
@@ -270,6 +316,7 @@ impl EventProcessorWithJsonOutput {
                 server,
                 tool,
                 status,
+                progress,
             }),
         };
 
@@ -283,14 +330,6 @@ impl EventProcessorWithJsonOutput {
         Vec::new()
     }
 
-    fn map_change_kind(&self, kind: &FileChange) -> PatchChangeKind {
-        match kind {
-            FileChange::Add { .. } => PatchChangeKind::Add,
-            FileChange::Delete { .. } => PatchChangeKind::Delete,
-            FileChange::Update { .. } => PatchChangeKind::Update,
-        }
-    }
-
     fn handle_patch_apply_end(&mut self, ev: &PatchApplyEndEvent) -> Vec<ThreadEvent> {
         if let Some(running_patch_apply) = self.running_patch_applies.remove(&ev.call_id) {
             let status = if ev.success {
@@ -302,14 +341,7 @@ impl EventProcessorWithJsonOutput {
                 id: self.get_next_item_id(),
 
                 details: ThreadItemDetails::FileChange(FileChangeItem {
-                    changes: running_patch_apply
-                        .changes
-                        .iter()
-                        .map(|(path, change)| FileUpdateChange {
-                            path: path.to_str().unwrap_or("").to_string(),
-                            kind: self.map_change_kind(change),
-                        })
-                        .collect(),
+                    changes: map_changes(&running_patch_apply.changes),
                     status,
                 }),
             };
@@ -387,6 +419,26 @@ impl EventProcessorWithJsonOutput {
         vec![ThreadEvent::TurnStarted(TurnStartedEvent {})]
     }
 
+    /// The task was stopped by a `[budget]` limit, which always fails the turn.
+    fn handle_budget_exceeded(&mut self) -> Vec<ThreadEvent> {
+        if self.last_critical_error.is_none() {
+            self.last_critical_error = Some(ThreadErrorEvent {
+                message: "session budget exhausted".to_string(),
+            });
+        }
+        self.handle_task_complete()
+    }
+
+    /// The turn was interrupted (or replaced) before it completed.
+    fn handle_turn_interrupted(&mut self) -> Vec<ThreadEvent> {
+        if self.last_critical_error.is_none() {
+            self.last_critical_error = Some(ThreadErrorEvent {
+                message: "turn interrupted".to_string(),
+            });
+        }
+        self.handle_task_complete()
+    }
+
     fn handle_task_complete(&mut self) -> Vec<ThreadEvent> {
         let usage = if let Some(u) = &self.last_total_token_usage {
             Usage {
@@ -420,6 +472,68 @@ impl EventProcessorWithJsonOutput {
     }
 }
 
+/// The `approval.requested` event for an `ExecApprovalRequest` or
+/// `ApplyPatchApprovalRequest`. `id` is the id of the submission that is
+/// waiting, which `Op::ExecApproval`/`Op::PatchApproval` expect back.
+pub fn approval_requested_event(event: &Event) -> Option<ApprovalRequestedEvent> {
+    let details = match &event.msg {
+        EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+            call_id,
+            command,
+            cwd,
+            reason,
+        }) => ApprovalRequestDetails::Command(CommandApprovalRequest {
+            command: command_to_string(call_id, command),
+            cwd: cwd.to_string_lossy().into_owned(),
+            reason: reason.clone(),
+        }),
+        EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+            changes,
+            reason,
+            ..
+        }) => ApprovalRequestDetails::FileChange(FileChangeApprovalRequest {
+            changes: map_changes(changes),
+            reason: reason.clone(),
+        }),
+        _ => return None,
+    };
+    Some(ApprovalRequestedEvent {
+        id: event.id.clone(),
+        details,
+    })
+}
+
+fn map_change_kind(kind: &FileChange) -> PatchChangeKind {
+    match kind {
+        FileChange::Add { .. } => PatchChangeKind::Add,
+        FileChange::Delete { .. } => PatchChangeKind::Delete,
+        FileChange::Update { .. } => PatchChangeKind::Update,
+    }
+}
+
+fn map_changes(changes: &HashMap<PathBuf, FileChange>) -> Vec<FileUpdateChange> {
+    changes
+        .iter()
+        .map(|(path, change)| FileUpdateChange {
+            path: path.to_str().unwrap_or("").to_string(),
+            kind: map_change_kind(change),
+        })
+        .collect()
+}
+
+fn command_to_string(call_id: &str, command: &[String]) -> String {
+    match shlex::try_join(command.iter().map(String::as_str)) {
+        Ok(command_string) => command_string,
+        Err(e) => {
+            warn!(
+                call_id,
+                "Failed to stringify command: {e:?}; joining with spaces"
+            );
+            command.join(" ")
+        }
+    }
+}
+
 impl EventProcessor for EventProcessorWithJsonOutput {
     fn print_config_summary(&mut self, _: &Config, _: &str, ev: &SessionConfiguredEvent) {
         self.process_event(Event {
@@ -443,13 +557,17 @@ impl EventProcessor for EventProcessorWithJsonOutput {
 
         let Event { msg, .. } = event;
 
-        if let EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) = msg {
-            if let Some(output_file) = self.last_message_path.as_deref() {
-                handle_last_message(last_agent_message.as_deref(), output_file);
+        match msg {
+            EventMsg::TaskComplete(TaskCompleteEvent { last_agent_message }) => {
+                if let Some(output_file) = self.last_message_path.as_deref() {
+                    handle_last_message(last_agent_message.as_deref(), output_file);
+                }
+                CodexStatus::InitiateShutdown
             }
-            CodexStatus::InitiateShutdown
-        } else {
-            CodexStatus::Running
+            EventMsg::TurnAborted(TurnAbortedEvent {
+                reason: TurnAbortReason::BudgetExceeded,
+            }) => CodexStatus::InitiateShutdown,
+            _ => CodexStatus::Running,
         }
     }
 }
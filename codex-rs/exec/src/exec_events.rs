@@ -3,7 +3,7 @@ use serde::Serialize;
 use ts_rs::TS;
 
 /// Top-level events emitted on the Codex Exec thread stream.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 #[serde(tag = "type")]
 pub enum ThreadEvent {
     #[serde(rename = "thread.started")]
@@ -20,6 +20,8 @@ pub enum ThreadEvent {
     ItemUpdated(ItemUpdatedEvent),
     #[serde(rename = "item.completed")]
     ItemCompleted(ItemCompletedEvent),
+    #[serde(rename = "approval.requested")]
+    ApprovalRequested(ApprovalRequestedEvent),
     #[serde(rename = "error")]
     Error(ThreadErrorEvent),
 }
@@ -50,21 +52,57 @@ pub struct Usage {
     pub output_tokens: u64,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct ItemStartedEvent {
     pub item: ThreadItem,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct ItemCompletedEvent {
     pub item: ThreadItem,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct ItemUpdatedEvent {
     pub item: ThreadItem,
 }
 
+/// The agent is waiting for a decision before it continues. Only emitted when
+/// the approval policy asks for approvals, i.e. with `--input-format jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub struct ApprovalRequestedEvent {
+    /// Identifier to send back in the `approval` input that answers this
+    /// request.
+    pub id: String,
+    #[serde(flatten)]
+    pub details: ApprovalRequestDetails,
+}
+
+/// What the agent is asking to do.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(tag = "approval_type", rename_all = "snake_case")]
+pub enum ApprovalRequestDetails {
+    Command(CommandApprovalRequest),
+    FileChange(FileChangeApprovalRequest),
+}
+
+/// Approval request for running a command.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub struct CommandApprovalRequest {
+    pub command: String,
+    pub cwd: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
+/// Approval request for applying a patch.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub struct FileChangeApprovalRequest {
+    pub changes: Vec<FileUpdateChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+}
+
 /// Fatal error emitted by the stream.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
 pub struct ThreadErrorEvent {
@@ -72,7 +110,7 @@ pub struct ThreadErrorEvent {
 }
 
 /// Canonical representation of a thread item and its domain-specific payload.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct ThreadItem {
     pub id: String,
     #[serde(flatten)]
@@ -80,7 +118,7 @@ pub struct ThreadItem {
 }
 
 /// Typed payloads for each supported thread item type.
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 #[serde(tag = "item_type", rename_all = "snake_case")]
 pub enum ThreadItemDetails {
     AssistantMessage(AssistantMessageItem),
@@ -169,11 +207,26 @@ pub enum McpToolCallStatus {
     Failed,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
 pub struct McpToolCallItem {
     pub server: String,
     pub tool: String,
     pub status: McpToolCallStatus,
+    /// Latest progress reported by the server while the call ran.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<McpToolCallProgress>,
+}
+
+/// Progress or log message from `notifications/progress` or
+/// `notifications/message`.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, TS)]
+pub struct McpToolCallProgress {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
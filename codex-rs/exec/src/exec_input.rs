@@ -0,0 +1,57 @@
+use std::path::PathBuf;
+
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::ReviewDecision;
+use codex_core::protocol::SandboxPolicy;
+use codex_protocol::config_types::ReasoningEffort;
+use codex_protocol::config_types::ReasoningSummary;
+use serde::Deserialize;
+use serde::Serialize;
+use ts_rs::TS;
+
+/// Messages read from stdin by `codex exec --input-format jsonl`, one JSON
+/// object per line.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ThreadInput {
+    /// Start a new turn, or add to the running one.
+    UserTurn(UserTurnInput),
+    /// Change the settings used by subsequent turns.
+    OverrideTurnContext(OverrideTurnContextInput),
+    /// Answer an `approval.requested` event.
+    Approval(ApprovalInput),
+    /// Abort the running turn.
+    Interrupt,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub struct UserTurnInput {
+    pub text: String,
+    /// Local images to attach to the message.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub images: Vec<PathBuf>,
+}
+
+/// Fields that are omitted keep their current value.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub struct OverrideTurnContextInput {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cwd: Option<PathBuf>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval_policy: Option<AskForApproval>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sandbox_policy: Option<SandboxPolicy>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub effort: Option<ReasoningEffort>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub summary: Option<ReasoningSummary>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, TS)]
+pub struct ApprovalInput {
+    /// The `id` of the `approval.requested` event being answered.
+    pub id: String,
+    pub decision: ReviewDecision,
+}
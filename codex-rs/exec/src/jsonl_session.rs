@@ -0,0 +1,300 @@
+//! Driver for `codex exec --input-format jsonl`: keeps the conversation open
+//! and feeds it the [`ThreadInput`] messages read from stdin while the
+//! resulting [`ThreadEvent`]s are printed to stdout.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use codex_core::CodexConversation;
+use codex_core::protocol::AskForApproval;
+use codex_core::protocol::Event;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::InputItem;
+use codex_core::protocol::Op;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::protocol::TurnAbortReason;
+use codex_core::protocol::TurnAbortedEvent;
+use codex_protocol::config_types::ReasoningEffort;
+use codex_protocol::config_types::ReasoningSummary;
+use serde_json::Value;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::BufReader;
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::sync::mpsc::unbounded_channel;
+use tracing::error;
+
+use crate::approver::Approver;
+use crate::event_processor::CodexStatus;
+use crate::event_processor::EventProcessor;
+use crate::exec_events::ThreadErrorEvent;
+use crate::exec_events::ThreadEvent;
+use crate::exec_input::ApprovalInput;
+use crate::exec_input::OverrideTurnContextInput;
+use crate::exec_input::ThreadInput;
+use crate::exec_input::UserTurnInput;
+
+/// Settings sent with every `Op::UserTurn`, updated by
+/// `override_turn_context` inputs.
+pub(crate) struct TurnSettings {
+    pub cwd: PathBuf,
+    pub approval_policy: AskForApproval,
+    pub sandbox_policy: SandboxPolicy,
+    pub model: String,
+    pub effort: Option<ReasoningEffort>,
+    pub summary: ReasoningSummary,
+    pub output_schema: Option<Value>,
+}
+
+#[derive(Debug, Default)]
+pub(crate) struct SessionOutcome {
+    pub error_seen: bool,
+    pub budget_exceeded: bool,
+}
+
+#[derive(Debug, Clone, Copy)]
+enum ApprovalKind {
+    Exec,
+    Patch,
+}
+
+struct JsonlSession {
+    conversation: Arc<CodexConversation>,
+    settings: TurnSettings,
+    /// Approval requests that have not been answered yet, keyed by the id
+    /// reported in `approval.requested`.
+    pending_approvals: HashMap<String, ApprovalKind>,
+    task_running: bool,
+}
+
+/// Run the session until stdin is closed and the last turn has finished, or
+/// until a `[budget]` limit stops it.
+pub(crate) async fn run_jsonl_session(
+    conversation: Arc<CodexConversation>,
+    mut events: UnboundedReceiver<Event>,
+    event_processor: &mut dyn EventProcessor,
+    settings: TurnSettings,
+    approver: Option<Approver>,
+    prompt: Option<String>,
+) -> anyhow::Result<SessionOutcome> {
+    let mut session = JsonlSession {
+        conversation,
+        settings,
+        pending_approvals: HashMap::new(),
+        task_running: false,
+    };
+    if let Some(text) = prompt {
+        session
+            .submit_user_turn(UserTurnInput {
+                text,
+                images: Vec::new(),
+            })
+            .await?;
+    }
+
+    let mut inputs = spawn_stdin_reader();
+    let mut input_closed = false;
+    let mut shutdown_requested = false;
+    let mut outcome = SessionOutcome::default();
+    loop {
+        tokio::select! {
+            event = events.recv() => {
+                let Some(event) = event else {
+                    break;
+                };
+                match &event.msg {
+                    EventMsg::Error(_) => outcome.error_seen = true,
+                    EventMsg::TurnAborted(TurnAbortedEvent {
+                        reason: TurnAbortReason::BudgetExceeded,
+                    }) => outcome.budget_exceeded = true,
+                    EventMsg::ExecApprovalRequest(_) | EventMsg::ApplyPatchApprovalRequest(_) => {
+                        // With an approver the request is answered right away;
+                        // otherwise it waits for an `approval` input.
+                        let answered = match &approver {
+                            Some(approver) => {
+                                approver.answer(&session.conversation, &event).await?
+                            }
+                            None => false,
+                        };
+                        if !answered {
+                            let kind = match event.msg {
+                                EventMsg::ApplyPatchApprovalRequest(_) => ApprovalKind::Patch,
+                                _ => ApprovalKind::Exec,
+                            };
+                            session.pending_approvals.insert(event.id.clone(), kind);
+                        }
+                    }
+                    EventMsg::TaskComplete(_) | EventMsg::TurnAborted(_) => {
+                        session.task_running = false;
+                        session.pending_approvals.clear();
+                    }
+                    _ => {}
+                }
+                // `InitiateShutdown` only marks the end of a turn here: the
+                // session stays open until stdin is closed.
+                if let CodexStatus::Shutdown = event_processor.process_event(event) {
+                    break;
+                }
+            }
+            line = inputs.recv(), if !input_closed => match line {
+                Some(line) => session.handle_input_line(&line).await?,
+                None => input_closed = true,
+            },
+        }
+
+        if !shutdown_requested
+            && (outcome.budget_exceeded || (input_closed && !session.task_running))
+        {
+            session.conversation.submit(Op::Shutdown).await?;
+            shutdown_requested = true;
+        }
+    }
+
+    Ok(outcome)
+}
+
+impl JsonlSession {
+    async fn handle_input_line(&mut self, line: &str) -> anyhow::Result<()> {
+        if line.trim().is_empty() {
+            return Ok(());
+        }
+        let input = match serde_json::from_str::<ThreadInput>(line) {
+            Ok(input) => input,
+            Err(err) => {
+                print_error(format!("invalid input: {err}"));
+                return Ok(());
+            }
+        };
+
+        match input {
+            ThreadInput::UserTurn(turn) => self.submit_user_turn(turn).await?,
+            ThreadInput::OverrideTurnContext(overrides) => {
+                self.override_turn_context(overrides).await?
+            }
+            ThreadInput::Approval(approval) => self.submit_approval(approval).await?,
+            ThreadInput::Interrupt => {
+                self.conversation.submit(Op::Interrupt).await?;
+            }
+        }
+        Ok(())
+    }
+
+    async fn submit_user_turn(&mut self, turn: UserTurnInput) -> anyhow::Result<()> {
+        let UserTurnInput { text, images } = turn;
+        let mut items = vec![InputItem::Text { text }];
+        items.extend(
+            images
+                .into_iter()
+                .map(|path| InputItem::LocalImage { path }),
+        );
+
+        let settings = &self.settings;
+        self.conversation
+            .submit(Op::UserTurn {
+                items,
+                cwd: settings.cwd.clone(),
+                approval_policy: settings.approval_policy,
+                sandbox_policy: settings.sandbox_policy.clone(),
+                model: settings.model.clone(),
+                effort: settings.effort,
+                summary: settings.summary,
+                final_output_json_schema: settings.output_schema.clone(),
+            })
+            .await?;
+        self.task_running = true;
+        Ok(())
+    }
+
+    async fn override_turn_context(
+        &mut self,
+        overrides: OverrideTurnContextInput,
+    ) -> anyhow::Result<()> {
+        let OverrideTurnContextInput {
+            cwd,
+            approval_policy,
+            sandbox_policy,
+            model,
+            effort,
+            summary,
+        } = overrides;
+
+        // `Op::UserTurn` carries the full turn context, so keep a copy that
+        // reflects the overrides for the turns that follow.
+        let settings = &mut self.settings;
+        if let Some(cwd) = &cwd {
+            settings.cwd = cwd.clone();
+        }
+        if let Some(approval_policy) = approval_policy {
+            settings.approval_policy = approval_policy;
+        }
+        if let Some(sandbox_policy) = &sandbox_policy {
+            settings.sandbox_policy = sandbox_policy.clone();
+        }
+        if let Some(model) = &model {
+            settings.model = model.clone();
+        }
+        if let Some(effort) = effort {
+            settings.effort = Some(effort);
+        }
+        if let Some(summary) = summary {
+            settings.summary = summary;
+        }
+
+        self.conversation
+            .submit(Op::OverrideTurnContext {
+                cwd,
+                approval_policy,
+                sandbox_policy,
+                model,
+                effort: effort.map(Some),
+                summary,
+            })
+            .await?;
+        Ok(())
+    }
+
+    async fn submit_approval(&mut self, approval: ApprovalInput) -> anyhow::Result<()> {
+        let ApprovalInput { id, decision } = approval;
+        let op = match self.pending_approvals.remove(&id) {
+            Some(ApprovalKind::Exec) => Op::ExecApproval { id, decision },
+            Some(ApprovalKind::Patch) => Op::PatchApproval { id, decision },
+            None => {
+                print_error(format!("no pending approval with id {id}"));
+                return Ok(());
+            }
+        };
+        self.conversation.submit(op).await?;
+        Ok(())
+    }
+}
+
+/// Problems with the input are reported on stdout so that they reach the
+/// script driving the session.
+fn print_error(message: String) {
+    match serde_json::to_string(&ThreadEvent::Error(ThreadErrorEvent { message })) {
+        Ok(line) => println!("{line}"),
+        Err(e) => error!("Failed to serialize event: {e:?}"),
+    }
+}
+
+fn spawn_stdin_reader() -> UnboundedReceiver<String> {
+    let (tx, rx) = unbounded_channel();
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(tokio::io::stdin()).lines();
+        loop {
+            match lines.next_line().await {
+                Ok(Some(line)) => {
+                    if tx.send(line).is_err() {
+                        break;
+                    }
+                }
+                Ok(None) => break,
+                Err(e) => {
+                    error!("Failed to read stdin: {e:?}");
+                    break;
+                }
+            }
+        }
+    });
+    rx
+}
@@ -1,16 +1,21 @@
+mod approver;
 mod cli;
 mod event_processor;
 mod event_processor_with_human_output;
 pub mod event_processor_with_jsonl_output;
 pub mod exec_events;
+pub mod exec_input;
+mod jsonl_session;
 
 pub use cli::Cli;
 use codex_core::AuthManager;
 use codex_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use codex_core::ConversationManager;
 use codex_core::NewConversation;
+use codex_core::cassette::Cassette;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
+use codex_core::config::worktrees_dir;
 use codex_core::git_info::get_git_repo_root;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::Event;
@@ -18,6 +23,14 @@ use codex_core::protocol::EventMsg;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::Op;
 use codex_core::protocol::TaskCompleteEvent;
+use codex_core::protocol::TurnAbortReason;
+use codex_core::protocol::TurnAbortedEvent;
+use codex_git_tooling::CreateWorktreeOptions;
+use codex_git_tooling::SessionWorktree;
+use codex_git_tooling::WorktreeDisposition;
+use codex_git_tooling::create_session_worktree;
+use codex_git_tooling::finish_session_worktree;
+use codex_git_tooling::worktree_has_changes;
 use codex_ollama::DEFAULT_OSS_MODEL;
 use codex_protocol::config_types::SandboxMode;
 use event_processor_with_human_output::EventProcessorWithHumanOutput;
@@ -33,12 +46,21 @@ use tracing::info;
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::prelude::*;
 
+use crate::approver::Approver;
 use crate::cli::Command as ExecCommand;
+use crate::cli::InputFormat;
 use crate::event_processor::CodexStatus;
 use crate::event_processor::EventProcessor;
+use crate::jsonl_session::SessionOutcome;
+use crate::jsonl_session::TurnSettings;
+use crate::jsonl_session::run_jsonl_session;
 use codex_core::default_client::set_default_originator;
 use codex_core::find_conversation_path_by_id_str;
 
+/// Exit status of `codex exec` when the session stopped because it reached one
+/// of its `[budget]` limits.
+pub const BUDGET_EXCEEDED_EXIT_CODE: i32 = 3;
+
 pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> anyhow::Result<()> {
     if let Err(err) = set_default_originator("codex_exec") {
         tracing::warn!(?err, "Failed to set codex exec originator override {err:?}");
@@ -56,14 +78,22 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         skip_git_repo_check,
         color,
         last_message_file,
-        json: json_mode,
+        json,
+        input_format,
+        approver: approver_cli_arg,
         sandbox_mode: sandbox_mode_cli_arg,
         prompt,
         output_schema: output_schema_path,
         include_plan_tool,
+        worktree,
+        record_cassette,
+        replay_cassette,
         config_overrides,
     } = cli;
 
+    let jsonl_input = input_format == InputFormat::Jsonl;
+    let json_mode = json || jsonl_input;
+
     // Determine the prompt source (parent or subcommand) and read from stdin if needed.
     let prompt_arg = match &command {
         // Allow prompt before the subcommand by falling back to the parent-level prompt
@@ -73,7 +103,10 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     };
 
     let prompt = match prompt_arg {
-        Some(p) if p != "-" => p,
+        // In JSONL mode stdin carries the session's inputs, so the prompt
+        // argument, if any, is only the first turn.
+        p if jsonl_input => p.filter(|p| p != "-"),
+        Some(p) if p != "-" => Some(p),
         // Either `-` was passed or no positional arg.
         maybe_dash => {
             // When no arg (None) **and** stdin is a TTY, bail out early – unless the
@@ -102,7 +135,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
                 eprintln!("No prompt provided via stdin.");
                 std::process::exit(1);
             }
-            buffer
+            Some(buffer)
         }
     };
 
@@ -160,9 +193,8 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         model,
         review_model: None,
         config_profile,
-        // This CLI is intended to be headless and has no affordances for asking
-        // the user for approval.
-        approval_policy: Some(AskForApproval::Never),
+        // Decided below, once it is known whether approvals can be answered.
+        approval_policy: None,
         sandbox_mode,
         cwd: cwd.map(|p| p.canonicalize().unwrap_or(p)),
         model_provider,
@@ -183,7 +215,31 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     };
 
-    let config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)?;
+    let mut config = Config::load_with_cli_overrides(cli_kv_overrides, overrides)?;
+
+    if let Some(approver) = approver_cli_arg {
+        match shlex::split(&approver) {
+            Some(argv) => config.approver = Some(argv),
+            None => {
+                eprintln!("Invalid --approver command: {approver}");
+                std::process::exit(1);
+            }
+        }
+    }
+    config.cassette = match (record_cassette, replay_cassette) {
+        (Some(dir), _) => Some(Cassette::record(dir)),
+        (None, Some(dir)) => Some(Cassette::replay(dir)),
+        (None, None) => None,
+    };
+    let approver = config
+        .approver
+        .clone()
+        .and_then(|argv| Approver::new(argv, config.approver_timeout));
+    if !jsonl_input && approver.is_none() {
+        // Without JSONL input or an approver this CLI is headless and has no
+        // affordances for asking the user for approval.
+        config.approval_policy = AskForApproval::Never;
+    }
 
     let otel = codex_core::otel_init::build_provider(&config, env!("CARGO_PKG_VERSION"));
 
@@ -219,11 +275,24 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     };
 
     if oss {
-        codex_ollama::ensure_oss_ready(&config)
+        codex_ollama::ensure_oss_ready(&mut config)
             .await
             .map_err(|e| anyhow::anyhow!("OSS setup failed: {e}"))?;
     }
 
+    // Created after the setup steps that may `process::exit`, so the guard's
+    // `Drop` runs on every path out of this function. The git repository
+    // check below always passes inside the worktree.
+    let session_worktree = if worktree || config.worktree {
+        let worktrees_dir = worktrees_dir(&config);
+        let worktree =
+            create_session_worktree(&CreateWorktreeOptions::new(&config.cwd, &worktrees_dir))?;
+        config.relocate_to_worktree(worktree.repo_root(), worktree.path());
+        ExecWorktree(Some(worktree))
+    } else {
+        ExecWorktree(None)
+    };
+
     let default_cwd = config.cwd.to_path_buf();
     let default_approval_policy = config.approval_policy;
     let default_sandbox_policy = config.sandbox_policy.clone();
@@ -236,8 +305,10 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         std::process::exit(1);
     }
 
-    let conversation_manager =
-        ConversationManager::new(AuthManager::shared(config.codex_home.clone()));
+    let conversation_manager = ConversationManager::new(AuthManager::shared(
+        config.codex_home.clone(),
+        config.credential_store.clone(),
+    ));
 
     // Handle resume subcommand by resolving a rollout path and using explicit resume API.
     let NewConversation {
@@ -252,7 +323,7 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
                 .resume_conversation_from_rollout(
                     config.clone(),
                     path,
-                    AuthManager::shared(config.codex_home.clone()),
+                    AuthManager::shared(config.codex_home.clone(), config.credential_store.clone()),
                 )
                 .await?
         } else {
@@ -267,7 +338,11 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     };
     // Print the effective configuration and prompt so users can see what Codex
     // is using.
-    event_processor.print_config_summary(&config, &prompt, &session_configured);
+    event_processor.print_config_summary(
+        &config,
+        prompt.as_deref().unwrap_or_default(),
+        &session_configured,
+    );
 
     info!("Codex initialized with event: {session_configured:?}");
 
@@ -332,7 +407,30 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
         }
     }
 
+    if jsonl_input {
+        let settings = TurnSettings {
+            cwd: default_cwd,
+            approval_policy: default_approval_policy,
+            sandbox_policy: default_sandbox_policy,
+            model: default_model,
+            effort: default_effort,
+            summary: default_summary,
+            output_schema,
+        };
+        let outcome = run_jsonl_session(
+            conversation,
+            rx,
+            event_processor.as_mut(),
+            settings,
+            approver,
+            prompt,
+        )
+        .await?;
+        return finish_exec(session_worktree, outcome);
+    }
+
     // Send the prompt.
+    let prompt = prompt.unwrap_or_default();
     let items: Vec<InputItem> = vec![InputItem::Text { text: prompt }];
     let initial_prompt_task_id = conversation
         .submit(Op::UserTurn {
@@ -351,10 +449,17 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
     // Run the loop until the task is complete.
     // Track whether a fatal error was reported by the server so we can
     // exit with a non-zero status for automation-friendly signaling.
-    let mut error_seen = false;
+    let mut outcome = SessionOutcome::default();
     while let Some(event) = rx.recv().await {
-        if matches!(event.msg, EventMsg::Error(_)) {
-            error_seen = true;
+        match &event.msg {
+            EventMsg::Error(_) => outcome.error_seen = true,
+            EventMsg::TurnAborted(TurnAbortedEvent {
+                reason: TurnAbortReason::BudgetExceeded,
+            }) => outcome.budget_exceeded = true,
+            _ => {}
+        }
+        if let Some(approver) = &approver {
+            approver.answer(&conversation, &event).await?;
         }
         let shutdown: CodexStatus = event_processor.process_event(event);
         match shutdown {
@@ -367,13 +472,63 @@ pub async fn run_main(cli: Cli, codex_linux_sandbox_exe: Option<PathBuf>) -> any
             }
         }
     }
-    if error_seen {
+    finish_exec(session_worktree, outcome)
+}
+
+fn finish_exec(session_worktree: ExecWorktree, outcome: SessionOutcome) -> anyhow::Result<()> {
+    session_worktree.finish()?;
+    if outcome.budget_exceeded {
+        std::process::exit(BUDGET_EXCEEDED_EXIT_CODE);
+    }
+    if outcome.error_seen {
         std::process::exit(1);
     }
 
     Ok(())
 }
 
+/// The session worktree, if any. It is finished with `finish_exec_worktree`
+/// on every exit path: explicitly once the session ends, or on drop when an
+/// error returns early.
+struct ExecWorktree(Option<SessionWorktree>);
+
+impl ExecWorktree {
+    fn finish(mut self) -> anyhow::Result<()> {
+        match self.0.take() {
+            Some(worktree) => finish_exec_worktree(&worktree),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Drop for ExecWorktree {
+    fn drop(&mut self) {
+        if let Some(worktree) = self.0.take()
+            && let Err(err) = finish_exec_worktree(&worktree)
+        {
+            eprintln!(
+                "Failed to clean up worktree {}: {err}",
+                worktree.path().display()
+            );
+        }
+    }
+}
+
+/// `codex exec` cannot ask what to do with the session worktree, so keep the
+/// branch whenever the agent changed something and drop it otherwise.
+fn finish_exec_worktree(worktree: &SessionWorktree) -> anyhow::Result<()> {
+    if worktree_has_changes(worktree)? {
+        finish_session_worktree(worktree, WorktreeDisposition::Keep)?;
+        eprintln!(
+            "Changes from this session were committed to branch {}",
+            worktree.branch()
+        );
+    } else {
+        finish_session_worktree(worktree, WorktreeDisposition::Discard)?;
+    }
+    Ok(())
+}
+
 async fn resolve_resume_path(
     config: &Config,
     args: &crate::cli::ResumeArgs,
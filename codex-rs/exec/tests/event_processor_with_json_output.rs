@@ -1,26 +1,37 @@
 use codex_core::protocol::AgentMessageEvent;
 use codex_core::protocol::AgentReasoningEvent;
+use codex_core::protocol::ApplyPatchApprovalRequestEvent;
 use codex_core::protocol::ErrorEvent;
 use codex_core::protocol::Event;
 use codex_core::protocol::EventMsg;
+use codex_core::protocol::ExecApprovalRequestEvent;
 use codex_core::protocol::ExecCommandBeginEvent;
 use codex_core::protocol::ExecCommandEndEvent;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpInvocation;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::McpToolCallProgressEvent;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::PatchApplyEndEvent;
 use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::protocol::TurnAbortReason;
+use codex_core::protocol::TurnAbortedEvent;
 use codex_core::protocol::WebSearchEndEvent;
 use codex_exec::event_processor_with_jsonl_output::EventProcessorWithJsonOutput;
+use codex_exec::exec_events::ApprovalRequestDetails;
+use codex_exec::exec_events::ApprovalRequestedEvent;
 use codex_exec::exec_events::AssistantMessageItem;
+use codex_exec::exec_events::CommandApprovalRequest;
 use codex_exec::exec_events::CommandExecutionItem;
 use codex_exec::exec_events::CommandExecutionStatus;
+use codex_exec::exec_events::FileChangeApprovalRequest;
+use codex_exec::exec_events::FileUpdateChange;
 use codex_exec::exec_events::ItemCompletedEvent;
 use codex_exec::exec_events::ItemStartedEvent;
 use codex_exec::exec_events::ItemUpdatedEvent;
 use codex_exec::exec_events::McpToolCallItem;
+use codex_exec::exec_events::McpToolCallProgress;
 use codex_exec::exec_events::McpToolCallStatus;
 use codex_exec::exec_events::PatchApplyStatus;
 use codex_exec::exec_events::PatchChangeKind;
@@ -260,6 +271,7 @@ fn mcp_tool_call_begin_and_end_emit_item_events() {
                     server: "server_a".to_string(),
                     tool: "tool_x".to_string(),
                     status: McpToolCallStatus::InProgress,
+                    progress: None,
                 }),
             },
         })]
@@ -288,6 +300,77 @@ fn mcp_tool_call_begin_and_end_emit_item_events() {
                     server: "server_a".to_string(),
                     tool: "tool_x".to_string(),
                     status: McpToolCallStatus::Completed,
+                    progress: None,
+                }),
+            },
+        })]
+    );
+}
+
+#[test]
+fn mcp_tool_call_progress_emits_item_updated() {
+    let mut ep = EventProcessorWithJsonOutput::new(None);
+    let invocation = McpInvocation {
+        server: "server_a".to_string(),
+        tool: "tool_x".to_string(),
+        arguments: None,
+    };
+    ep.collect_thread_events(&event(
+        "m1",
+        EventMsg::McpToolCallBegin(McpToolCallBeginEvent {
+            call_id: "call-1".to_string(),
+            invocation: invocation.clone(),
+        }),
+    ));
+
+    let progress = event(
+        "m2",
+        EventMsg::McpToolCallProgress(McpToolCallProgressEvent {
+            call_id: "call-1".to_string(),
+            progress: Some(2.0),
+            total: Some(4.0),
+            message: Some("halfway".to_string()),
+        }),
+    );
+    let expected_progress = McpToolCallProgress {
+        progress: Some(2.0),
+        total: Some(4.0),
+        message: Some("halfway".to_string()),
+    };
+    assert_eq!(
+        ep.collect_thread_events(&progress),
+        vec![ThreadEvent::ItemUpdated(ItemUpdatedEvent {
+            item: ThreadItem {
+                id: "item_0".to_string(),
+                details: ThreadItemDetails::McpToolCall(McpToolCallItem {
+                    server: "server_a".to_string(),
+                    tool: "tool_x".to_string(),
+                    status: McpToolCallStatus::InProgress,
+                    progress: Some(expected_progress.clone()),
+                }),
+            },
+        })]
+    );
+
+    let end = event(
+        "m3",
+        EventMsg::McpToolCallEnd(McpToolCallEndEvent {
+            call_id: "call-1".to_string(),
+            invocation,
+            duration: Duration::from_secs(1),
+            result: Err("interrupted".to_string()),
+        }),
+    );
+    assert_eq!(
+        ep.collect_thread_events(&end),
+        vec![ThreadEvent::ItemCompleted(ItemCompletedEvent {
+            item: ThreadItem {
+                id: "item_0".to_string(),
+                details: ThreadItemDetails::McpToolCall(McpToolCallItem {
+                    server: "server_a".to_string(),
+                    tool: "tool_x".to_string(),
+                    status: McpToolCallStatus::Failed,
+                    progress: Some(expected_progress),
                 }),
             },
         })]
@@ -331,6 +414,7 @@ fn mcp_tool_call_failure_sets_failed_status() {
                     server: "server_b".to_string(),
                     tool: "tool_y".to_string(),
                     status: McpToolCallStatus::Failed,
+                    progress: None,
                 }),
             },
         })]
@@ -791,6 +875,7 @@ fn task_complete_produces_turn_completed_with_usage() {
         total_token_usage: usage.clone(),
         last_token_usage: usage,
         model_context_window: None,
+        total_cost_usd: None,
     };
     let token_count_event = event(
         "e1",
@@ -820,3 +905,94 @@ fn task_complete_produces_turn_completed_with_usage() {
         })]
     );
 }
+
+#[test]
+fn exec_approval_request_produces_approval_requested() {
+    let mut ep = EventProcessorWithJsonOutput::new(None);
+    let out = ep.collect_thread_events(&event(
+        "sub-1",
+        EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+            call_id: "call-1".to_string(),
+            command: vec!["rm".to_string(), "-rf".to_string(), "build dir".to_string()],
+            cwd: PathBuf::from("/tmp/project"),
+            reason: Some("outside the sandbox".to_string()),
+        }),
+    ));
+    assert_eq!(
+        out,
+        vec![ThreadEvent::ApprovalRequested(ApprovalRequestedEvent {
+            id: "sub-1".to_string(),
+            details: ApprovalRequestDetails::Command(CommandApprovalRequest {
+                command: "rm -rf 'build dir'".to_string(),
+                cwd: "/tmp/project".to_string(),
+                reason: Some("outside the sandbox".to_string()),
+            }),
+        })]
+    );
+
+    let json = serde_json::to_value(&out[0]).unwrap();
+    assert_eq!(
+        json,
+        serde_json::json!({
+            "type": "approval.requested",
+            "id": "sub-1",
+            "approval_type": "command",
+            "command": "rm -rf 'build dir'",
+            "cwd": "/tmp/project",
+            "reason": "outside the sandbox",
+        })
+    );
+}
+
+#[test]
+fn apply_patch_approval_request_produces_approval_requested() {
+    let mut ep = EventProcessorWithJsonOutput::new(None);
+    let mut changes = std::collections::HashMap::new();
+    changes.insert(
+        PathBuf::from("a/added.txt"),
+        FileChange::Add {
+            content: "+hello".to_string(),
+        },
+    );
+    let out = ep.collect_thread_events(&event(
+        "sub-2",
+        EventMsg::ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent {
+            call_id: "call-2".to_string(),
+            changes,
+            reason: None,
+            grant_root: None,
+        }),
+    ));
+    assert_eq!(
+        out,
+        vec![ThreadEvent::ApprovalRequested(ApprovalRequestedEvent {
+            id: "sub-2".to_string(),
+            details: ApprovalRequestDetails::FileChange(FileChangeApprovalRequest {
+                changes: vec![FileUpdateChange {
+                    path: "a/added.txt".to_string(),
+                    kind: PatchChangeKind::Add,
+                }],
+                reason: None,
+            }),
+        })]
+    );
+}
+
+#[test]
+fn interrupted_turn_produces_turn_failed() {
+    let mut ep = EventProcessorWithJsonOutput::new(None);
+    let out = ep.collect_thread_events(&event(
+        "e1",
+        EventMsg::TurnAborted(TurnAbortedEvent {
+            reason: TurnAbortReason::Interrupted,
+        }),
+    ));
+    assert_eq!(
+        out,
+        vec![ThreadEvent::TurnFailed(TurnFailedEvent {
+            error: ThreadErrorEvent {
+                message: "turn interrupted".to_string(),
+            },
+        })]
+    );
+}
@@ -0,0 +1,132 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::os::unix::fs::PermissionsExt;
+use std::time::Duration;
+use std::time::Instant;
+
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex_exec::test_codex_exec;
+use serde_json::Value;
+
+/// Commands that need approval are sent to the `--approver` program, and the
+/// command runs once it approves.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn approver_decides_command_approval() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let test = test_codex_exec();
+    let request_path = test.home_path().join("approval_request.json");
+    let approver_path = test.home_path().join("approver.sh");
+    std::fs::write(
+        &approver_path,
+        format!(
+            "#!/bin/sh\ncat > '{}'\necho '{{\"decision\": \"approved\"}}'\n",
+            request_path.display()
+        ),
+    )?;
+    std::fs::set_permissions(&approver_path, std::fs::Permissions::from_mode(0o755))?;
+
+    let args = serde_json::json!({
+        "command": ["touch", "approved.txt"],
+        "workdir": test.cwd_path(),
+    })
+    .to_string();
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_function_call("call-1", "shell", &args),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("m1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("-s")
+        .arg("danger-full-access")
+        .arg("-c")
+        .arg("approval_policy=\"untrusted\"")
+        .arg("--approver")
+        .arg(&approver_path)
+        .arg("touch a file")
+        .assert()
+        .success();
+
+    assert!(test.cwd_path().join("approved.txt").exists());
+    let request: Value = serde_json::from_str(&std::fs::read_to_string(&request_path)?)?;
+    assert_eq!(request["type"], "approval.requested");
+    assert_eq!(request["approval_type"], "command");
+    assert_eq!(request["command"], "touch approved.txt");
+
+    Ok(())
+}
+
+/// An approver that does not answer in time is killed and the request is
+/// denied.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn approver_timeout_denies_the_request() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let test = test_codex_exec();
+    let approver_path = test.home_path().join("approver.sh");
+    std::fs::write(
+        &approver_path,
+        "#!/bin/sh\nexec sleep 60\necho '{\"decision\": \"approved\"}'\n",
+    )?;
+    std::fs::set_permissions(&approver_path, std::fs::Permissions::from_mode(0o755))?;
+
+    let args = serde_json::json!({
+        "command": ["touch", "approved.txt"],
+        "workdir": test.cwd_path(),
+    })
+    .to_string();
+    let server = start_mock_server().await;
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_function_call("call-1", "shell", &args),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("m1", "done"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    let started = Instant::now();
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("-s")
+        .arg("danger-full-access")
+        .arg("-c")
+        .arg("approval_policy=\"untrusted\"")
+        .arg("-c")
+        .arg("approver_timeout_sec=1")
+        .arg("--approver")
+        .arg(&approver_path)
+        .arg("touch a file")
+        .assert()
+        .success();
+
+    assert!(started.elapsed() < Duration::from_secs(30));
+    assert!(!test.cwd_path().join("approved.txt").exists());
+
+    Ok(())
+}
@@ -0,0 +1,128 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use core_test_support::responses::ev_assistant_message;
+use core_test_support::responses::ev_completed;
+use core_test_support::responses::ev_function_call;
+use core_test_support::responses::mount_sse_once;
+use core_test_support::responses::mount_sse_sequence;
+use core_test_support::responses::sse;
+use core_test_support::responses::start_mock_server;
+use core_test_support::skip_if_no_network;
+use core_test_support::test_codex_exec::test_codex_exec;
+use predicates::str::contains;
+
+/// A session recorded with `--record-cassette` replays without the provider,
+/// and a request that was never recorded fails instead of going online.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn replay_serves_recorded_responses() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let test = test_codex_exec();
+    let cassette = test.home_path().join("cassette");
+    let server = start_mock_server().await;
+    mount_sse_once(
+        &server,
+        sse(vec![
+            ev_assistant_message("m1", "recorded answer"),
+            ev_completed("resp-1"),
+        ]),
+    )
+    .await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--record-cassette")
+        .arg(&cassette)
+        .arg("say something")
+        .assert()
+        .success()
+        .stdout(contains("recorded answer"));
+
+    let recorded: Vec<String> = std::fs::read_dir(&cassette)?
+        .map(|entry| entry.unwrap().file_name().to_string_lossy().to_string())
+        .collect();
+    assert_eq!(
+        recorded.len(),
+        2,
+        "expected request and SSE files: {recorded:?}"
+    );
+    assert!(recorded.iter().any(|name| name.ends_with(".request.json")));
+    assert!(recorded.iter().any(|name| name.ends_with(".sse")));
+
+    // The mock expects exactly one request, so replaying must not reach it.
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--replay-cassette")
+        .arg(&cassette)
+        .arg("say something")
+        .assert()
+        .success()
+        .stdout(contains("recorded answer"));
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--replay-cassette")
+        .arg(&cassette)
+        .arg("say something else")
+        .assert()
+        .code(1)
+        .stdout(contains("no recorded response for request"));
+
+    Ok(())
+}
+
+/// Later turns of a session replay too, even though the shell output they
+/// send back carries a different duration and the replay runs in another
+/// working directory.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn replay_serves_turns_after_a_shell_call() -> anyhow::Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let test = test_codex_exec();
+    let cassette = test.home_path().join("cassette");
+    let args = serde_json::json!({ "command": ["sh", "-c", "sleep 0.3; echo hi"] }).to_string();
+    let server = start_mock_server().await;
+    // Expects exactly two requests, so replaying must not reach it.
+    mount_sse_sequence(
+        &server,
+        vec![
+            sse(vec![
+                ev_function_call("call-1", "shell", &args),
+                ev_completed("resp-1"),
+            ]),
+            sse(vec![
+                ev_assistant_message("m1", "recorded answer"),
+                ev_completed("resp-2"),
+            ]),
+        ],
+    )
+    .await;
+
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("-s")
+        .arg("danger-full-access")
+        .arg("--record-cassette")
+        .arg(&cassette)
+        .arg("run a command")
+        .assert()
+        .success()
+        .stdout(contains("recorded answer"));
+
+    let replay_cwd = tempfile::tempdir()?;
+    test.cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("-s")
+        .arg("danger-full-access")
+        .arg("-C")
+        .arg(replay_cwd.path())
+        .arg("--replay-cassette")
+        .arg(&cassette)
+        .arg("run a command")
+        .assert()
+        .success()
+        .stdout(contains("recorded answer"));
+
+    Ok(())
+}
@@ -0,0 +1,72 @@
+#![cfg(not(target_os = "windows"))]
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use core_test_support::responses;
+use core_test_support::test_codex_exec::test_codex_exec;
+use serde_json::Value;
+use wiremock::matchers::any;
+
+/// With `--input-format jsonl` the turns come from stdin; malformed lines are
+/// reported on stdout without ending the session.
+#[tokio::test(flavor = "multi_thread", worker_threads = 2)]
+async fn exec_reads_user_turns_from_jsonl_stdin() -> anyhow::Result<()> {
+    let test = test_codex_exec();
+
+    let server = responses::start_mock_server().await;
+    let body = responses::sse(vec![
+        serde_json::json!({
+            "type": "response.created",
+            "response": {"id": "resp1"}
+        }),
+        responses::ev_assistant_message("m1", "fixture hello"),
+        responses::ev_completed("resp1"),
+    ]);
+    responses::mount_sse_once_match(&server, any(), body).await;
+
+    let output = test
+        .cmd_with_server(&server)
+        .arg("--skip-git-repo-check")
+        .arg("--input-format")
+        .arg("jsonl")
+        .write_stdin("not json\n{\"type\":\"user_turn\",\"text\":\"tell me a joke\"}\n")
+        .assert()
+        .success()
+        .get_output()
+        .stdout
+        .clone();
+
+    let events: Vec<Value> = String::from_utf8(output)?
+        .lines()
+        .map(serde_json::from_str)
+        .collect::<Result<_, _>>()?;
+    let types: Vec<&str> = events
+        .iter()
+        .filter_map(|event| event["type"].as_str())
+        .collect();
+    assert_eq!(
+        types,
+        vec![
+            "thread.started",
+            "error",
+            "turn.started",
+            "item.completed",
+            "turn.completed",
+        ]
+    );
+    assert!(
+        events[1]["message"]
+            .as_str()
+            .is_some_and(|message| message.starts_with("invalid input")),
+        "unexpected error event: {}",
+        events[1]
+    );
+    assert_eq!(events[3]["item"]["text"], "fixture hello");
+
+    let requests = server
+        .received_requests()
+        .await
+        .expect("failed to capture requests");
+    assert_eq!(requests.len(), 1, "expected exactly one request");
+
+    Ok(())
+}
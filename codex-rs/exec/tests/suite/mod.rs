@@ -1,5 +1,8 @@
 // Aggregates all former standalone integration tests as modules.
 mod apply_patch;
+mod approver;
+mod cassette;
+mod input_format_jsonl;
 mod output_schema;
 mod resume;
 mod sandbox;
@@ -40,6 +40,7 @@ async fn spawn_command_under_sandbox(
     stdio_policy: StdioPolicy,
     env: HashMap<String, String>,
 ) -> std::io::Result<Child> {
+    use codex_core::config_types::SandboxResourceLimits;
     use codex_core::landlock::spawn_command_under_linux_sandbox;
     let codex_linux_sandbox_exe = assert_cmd::cargo::cargo_bin("codex-exec");
     spawn_command_under_linux_sandbox(
@@ -48,6 +49,7 @@ async fn spawn_command_under_sandbox(
         command_cwd,
         sandbox_policy,
         sandbox_cwd,
+        &SandboxResourceLimits::default(),
         stdio_policy,
         env,
     )
@@ -4,6 +4,7 @@ mod errors;
 mod ghost_commits;
 mod operations;
 mod platform;
+mod worktrees;
 
 pub use errors::GitToolingError;
 pub use ghost_commits::CreateGhostCommitOptions;
@@ -11,6 +12,17 @@ pub use ghost_commits::create_ghost_commit;
 pub use ghost_commits::restore_ghost_commit;
 pub use ghost_commits::restore_to_commit;
 pub use platform::create_symlink;
+pub use worktrees::CreateWorktreeOptions;
+pub use worktrees::PruneReport;
+pub use worktrees::SESSION_BRANCH_PREFIX;
+pub use worktrees::SessionWorktree;
+pub use worktrees::WorktreeDisposition;
+pub use worktrees::WorktreeEntry;
+pub use worktrees::create_session_worktree;
+pub use worktrees::finish_session_worktree;
+pub use worktrees::list_worktrees;
+pub use worktrees::prune_worktrees;
+pub use worktrees::worktree_has_changes;
 
 /// Details of a ghost commit created from a repository state.
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -33,5 +33,25 @@ pub fn create_symlink(
     Ok(())
 }
 
+/// Whether a process with this id is still running.
+#[cfg(unix)]
+pub(crate) fn process_is_running(pid: u32) -> bool {
+    let pid = match libc::pid_t::try_from(pid) {
+        Ok(pid) if pid > 0 => pid,
+        _ => return false,
+    };
+    // Signal 0 only checks that the process exists; EPERM means it exists
+    // but belongs to another user.
+    let rc = unsafe { libc::kill(pid, 0) };
+    rc == 0 || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Whether a process with this id is still running. Windows has no cheap
+/// check here, so every process is assumed to still be running.
+#[cfg(windows)]
+pub(crate) fn process_is_running(_pid: u32) -> bool {
+    true
+}
+
 #[cfg(not(any(unix, windows)))]
 compile_error!("codex-git-tooling symlink support is only implemented for Unix and Windows");
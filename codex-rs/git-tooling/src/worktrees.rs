@@ -0,0 +1,806 @@
+use std::ffi::OsString;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::SystemTime;
+use std::time::UNIX_EPOCH;
+
+use crate::GitToolingError;
+use crate::operations::ensure_git_repository;
+use crate::operations::resolve_head;
+use crate::operations::resolve_repository_root;
+use crate::operations::run_git_for_status;
+use crate::operations::run_git_for_stdout;
+use crate::platform::process_is_running;
+
+/// Prefix for branches created for session worktrees. `list_worktrees` and
+/// `prune_worktrees` only consider worktrees checked out on such a branch.
+pub const SESSION_BRANCH_PREFIX: &str = "codex/";
+
+/// Commit message used when pending worktree changes are committed on exit.
+const SESSION_COMMIT_MESSAGE: &str = "Codex session changes";
+
+/// Prefix of the `git worktree lock` reason recorded while a session uses its
+/// worktree. The owning process id follows it.
+const SESSION_LOCK_REASON_PREFIX: &str = "in use by codex process ";
+
+/// Options to control session worktree creation.
+pub struct CreateWorktreeOptions<'a> {
+    pub repo_path: &'a Path,
+    pub worktrees_dir: &'a Path,
+    pub branch: Option<&'a str>,
+}
+
+impl<'a> CreateWorktreeOptions<'a> {
+    /// Creates options for a worktree of `repo_path` placed under `worktrees_dir`.
+    pub fn new(repo_path: &'a Path, worktrees_dir: &'a Path) -> Self {
+        Self {
+            repo_path,
+            worktrees_dir,
+            branch: None,
+        }
+    }
+
+    /// Uses a specific branch name instead of a generated `codex/session-*` one.
+    pub fn branch(mut self, branch: &'a str) -> Self {
+        self.branch = Some(branch);
+        self
+    }
+}
+
+/// A git worktree created for a single agent session.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SessionWorktree {
+    repo_root: PathBuf,
+    path: PathBuf,
+    branch: String,
+    base_commit: String,
+}
+
+impl SessionWorktree {
+    /// Root of the checkout the worktree was created from.
+    pub fn repo_root(&self) -> &Path {
+        &self.repo_root
+    }
+
+    /// Directory containing the worktree checkout.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Branch checked out in the worktree.
+    pub fn branch(&self) -> &str {
+        &self.branch
+    }
+
+    /// Commit the branch was created from.
+    pub fn base_commit(&self) -> &str {
+        &self.base_commit
+    }
+}
+
+/// What to do with a session worktree once the session is over.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorktreeDisposition {
+    /// Commit pending changes and merge the branch into the original checkout.
+    Merge,
+    /// Commit pending changes and keep the branch (e.g. to open a PR).
+    Keep,
+    /// Drop the worktree, the branch and every change made in it.
+    Discard,
+}
+
+/// A codex-managed worktree as reported by `git worktree list`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorktreeEntry {
+    pub path: PathBuf,
+    pub branch: String,
+    pub head: String,
+    /// The worktree directory is missing; `git worktree prune` will drop it.
+    pub prunable: bool,
+    /// Reason given to `git worktree lock`, if the worktree is locked.
+    pub locked: Option<String>,
+}
+
+impl WorktreeEntry {
+    /// Whether a session may still be using the worktree. Locks taken by a
+    /// Codex process that has exited are stale; any other lock is respected.
+    pub fn in_use(&self) -> bool {
+        match self.locked.as_deref() {
+            None => false,
+            Some(reason) => session_lock_owner(reason).is_none_or(process_is_running),
+        }
+    }
+}
+
+/// Outcome of `prune_worktrees`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PruneReport {
+    /// Worktrees whose checkout was removed.
+    pub removed: Vec<WorktreeEntry>,
+    /// Worktrees left in place because they have uncommitted changes.
+    pub skipped_dirty: Vec<WorktreeEntry>,
+    /// Worktrees left in place because they are locked by a running session
+    /// or by hand.
+    pub skipped_in_use: Vec<WorktreeEntry>,
+    /// Branches kept because they contain commits not merged into `HEAD`.
+    pub kept_branches: Vec<String>,
+}
+
+/// Create a new worktree on a fresh branch starting at the repository's `HEAD`.
+/// The worktree stays locked to this process until `finish_session_worktree`
+/// so that `prune_worktrees` leaves it alone while the session runs.
+pub fn create_session_worktree(
+    options: &CreateWorktreeOptions<'_>,
+) -> Result<SessionWorktree, GitToolingError> {
+    ensure_git_repository(options.repo_path)?;
+
+    let repo_root = resolve_repository_root(options.repo_path)?;
+    let Some(base_commit) = resolve_head(repo_root.as_path())? else {
+        return Err(GitToolingError::MissingHead { path: repo_root });
+    };
+
+    let repo_name = repo_root
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "repo".to_string());
+    let (branch, path) = match options.branch {
+        Some(branch) => {
+            let dir_name = format!("{repo_name}-{}", branch.replace('/', "-"));
+            (branch.to_string(), options.worktrees_dir.join(dir_name))
+        }
+        None => unused_session_name(repo_root.as_path(), options.worktrees_dir, &repo_name)?,
+    };
+
+    std::fs::create_dir_all(options.worktrees_dir)?;
+    run_git_for_status(
+        repo_root.as_path(),
+        vec![
+            OsString::from("worktree"),
+            OsString::from("add"),
+            OsString::from("-b"),
+            OsString::from(&branch),
+            path.as_os_str().to_os_string(),
+            OsString::from(&base_commit),
+        ],
+        None,
+    )?;
+    run_git_for_status(
+        repo_root.as_path(),
+        vec![
+            OsString::from("worktree"),
+            OsString::from("lock"),
+            OsString::from("--reason"),
+            OsString::from(format!(
+                "{SESSION_LOCK_REASON_PREFIX}{}",
+                std::process::id()
+            )),
+            path.as_os_str().to_os_string(),
+        ],
+        None,
+    )?;
+
+    // Canonicalize so the path matches what git reports (e.g. /private/var on macOS).
+    let path = path.canonicalize().unwrap_or(path);
+    Ok(SessionWorktree {
+        repo_root,
+        path,
+        branch,
+        base_commit,
+    })
+}
+
+/// Returns true when the worktree has uncommitted changes or commits on top
+/// of its base commit.
+pub fn worktree_has_changes(worktree: &SessionWorktree) -> Result<bool, GitToolingError> {
+    if is_dirty(worktree.path())? {
+        return Ok(true);
+    }
+    Ok(commits_ahead(worktree.path(), worktree.base_commit(), "HEAD")? > 0)
+}
+
+/// Wrap up a session worktree according to `disposition`. The worktree
+/// checkout is removed in every case; only `Keep` leaves the branch behind.
+pub fn finish_session_worktree(
+    worktree: &SessionWorktree,
+    disposition: WorktreeDisposition,
+) -> Result<(), GitToolingError> {
+    let repo_root = worktree.repo_root();
+    unlock_worktree(repo_root, worktree.path())?;
+    match disposition {
+        WorktreeDisposition::Discard => {
+            remove_worktree(repo_root, worktree.path())?;
+            delete_branch(repo_root, worktree.branch(), true)?;
+        }
+        WorktreeDisposition::Keep => {
+            commit_pending_changes(worktree.path())?;
+            remove_worktree(repo_root, worktree.path())?;
+        }
+        WorktreeDisposition::Merge => {
+            commit_pending_changes(worktree.path())?;
+            remove_worktree(repo_root, worktree.path())?;
+            if commits_ahead(repo_root, "HEAD", worktree.branch())? > 0 {
+                let merge = run_git_for_status(
+                    repo_root,
+                    vec![
+                        OsString::from("merge"),
+                        OsString::from("--no-edit"),
+                        OsString::from(worktree.branch()),
+                    ],
+                    Some(commit_identity_env(repo_root).as_slice()),
+                );
+                if let Err(err) = merge {
+                    // Leave the original checkout untouched; the branch still
+                    // holds the session's work.
+                    let _ = run_git_for_status(
+                        repo_root,
+                        vec![OsString::from("merge"), OsString::from("--abort")],
+                        None,
+                    );
+                    return Err(err);
+                }
+            }
+            delete_branch(repo_root, worktree.branch(), true)?;
+        }
+    }
+    Ok(())
+}
+
+/// List the codex-managed worktrees of the repository containing `repo_path`.
+pub fn list_worktrees(repo_path: &Path) -> Result<Vec<WorktreeEntry>, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+    let output = run_git_for_stdout(
+        repo_path,
+        vec![
+            OsString::from("worktree"),
+            OsString::from("list"),
+            OsString::from("--porcelain"),
+        ],
+        None,
+    )?;
+    Ok(parse_worktree_list(&output))
+}
+
+/// Remove codex-managed worktrees. Worktrees a running session still uses are
+/// always skipped; worktrees with uncommitted changes are skipped unless
+/// `force` is set. Branches are only deleted once they are fully merged into
+/// `HEAD`.
+pub fn prune_worktrees(repo_path: &Path, force: bool) -> Result<PruneReport, GitToolingError> {
+    ensure_git_repository(repo_path)?;
+    let repo_root = resolve_repository_root(repo_path)?;
+    run_git_for_status(
+        repo_root.as_path(),
+        vec![OsString::from("worktree"), OsString::from("prune")],
+        None,
+    )?;
+
+    let mut report = PruneReport::default();
+    for entry in list_worktrees(repo_root.as_path())? {
+        if entry.in_use() {
+            report.skipped_in_use.push(entry);
+            continue;
+        }
+        if !force && is_dirty(&entry.path)? {
+            report.skipped_dirty.push(entry);
+            continue;
+        }
+        if entry.locked.is_some() {
+            // Stale lock left behind by a session that did not exit cleanly.
+            unlock_worktree(repo_root.as_path(), &entry.path)?;
+        }
+        remove_worktree(repo_root.as_path(), &entry.path)?;
+        if delete_branch(repo_root.as_path(), &entry.branch, false).is_err() {
+            report.kept_branches.push(entry.branch.clone());
+        }
+        report.removed.push(entry);
+    }
+    Ok(report)
+}
+
+fn parse_worktree_list(output: &str) -> Vec<WorktreeEntry> {
+    let mut entries = Vec::new();
+    for block in output.split("\n\n") {
+        let mut path = None;
+        let mut head = String::new();
+        let mut branch = None;
+        let mut prunable = false;
+        let mut locked = None;
+        for line in block.lines() {
+            if let Some(value) = line.strip_prefix("worktree ") {
+                path = Some(PathBuf::from(value));
+            } else if let Some(value) = line.strip_prefix("HEAD ") {
+                head = value.to_string();
+            } else if let Some(value) = line.strip_prefix("branch ") {
+                branch = Some(
+                    value
+                        .strip_prefix("refs/heads/")
+                        .unwrap_or(value)
+                        .to_string(),
+                );
+            } else if line == "prunable" || line.starts_with("prunable ") {
+                prunable = true;
+            } else if line == "locked" {
+                locked = Some(String::new());
+            } else if let Some(reason) = line.strip_prefix("locked ") {
+                locked = Some(reason.to_string());
+            }
+        }
+        if let (Some(path), Some(branch)) = (path, branch)
+            && branch.starts_with(SESSION_BRANCH_PREFIX)
+        {
+            entries.push(WorktreeEntry {
+                path,
+                branch,
+                head,
+                prunable,
+                locked,
+            });
+        }
+    }
+    entries
+}
+
+/// The process id recorded in a lock taken by `create_session_worktree`.
+fn session_lock_owner(reason: &str) -> Option<u32> {
+    reason
+        .strip_prefix(SESSION_LOCK_REASON_PREFIX)?
+        .parse()
+        .ok()
+}
+
+fn unused_session_name(
+    repo_root: &Path,
+    worktrees_dir: &Path,
+    repo_name: &str,
+) -> Result<(String, PathBuf), GitToolingError> {
+    let stamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|elapsed| elapsed.as_secs())
+        .unwrap_or_default();
+    let mut attempt = 0u32;
+    loop {
+        let suffix = if attempt == 0 {
+            format!("session-{stamp}")
+        } else {
+            format!("session-{stamp}-{attempt}")
+        };
+        let branch = format!("{SESSION_BRANCH_PREFIX}{suffix}");
+        let path = worktrees_dir.join(format!("{repo_name}-{suffix}"));
+        if !path.exists() && !branch_exists(repo_root, &branch)? {
+            return Ok((branch, path));
+        }
+        attempt += 1;
+    }
+}
+
+fn branch_exists(repo_root: &Path, branch: &str) -> Result<bool, GitToolingError> {
+    match run_git_for_status(
+        repo_root,
+        vec![
+            OsString::from("show-ref"),
+            OsString::from("--verify"),
+            OsString::from("--quiet"),
+            OsString::from(format!("refs/heads/{branch}")),
+        ],
+        None,
+    ) {
+        Ok(()) => Ok(true),
+        Err(GitToolingError::GitCommand { .. }) => Ok(false),
+        Err(err) => Err(err),
+    }
+}
+
+fn is_dirty(path: &Path) -> Result<bool, GitToolingError> {
+    if !path.exists() {
+        return Ok(false);
+    }
+    let status = run_git_for_stdout(
+        path,
+        vec![OsString::from("status"), OsString::from("--porcelain")],
+        None,
+    )?;
+    Ok(!status.is_empty())
+}
+
+fn commits_ahead(dir: &Path, base: &str, tip: &str) -> Result<u64, GitToolingError> {
+    let count = run_git_for_stdout(
+        dir,
+        vec![
+            OsString::from("rev-list"),
+            OsString::from("--count"),
+            OsString::from(format!("{base}..{tip}")),
+        ],
+        None,
+    )?;
+    Ok(count.parse().unwrap_or_default())
+}
+
+fn commit_pending_changes(path: &Path) -> Result<(), GitToolingError> {
+    if !is_dirty(path)? {
+        return Ok(());
+    }
+    run_git_for_status(
+        path,
+        vec![OsString::from("add"), OsString::from("--all")],
+        None,
+    )?;
+    run_git_for_status(
+        path,
+        vec![
+            OsString::from("commit"),
+            OsString::from("--no-verify"),
+            OsString::from("-m"),
+            OsString::from(SESSION_COMMIT_MESSAGE),
+        ],
+        Some(commit_identity_env(path).as_slice()),
+    )
+}
+
+/// Unlock the worktree at `path`; a worktree that is not locked is left as is.
+fn unlock_worktree(repo_root: &Path, path: &Path) -> Result<(), GitToolingError> {
+    match run_git_for_status(
+        repo_root,
+        vec![
+            OsString::from("worktree"),
+            OsString::from("unlock"),
+            path.as_os_str().to_os_string(),
+        ],
+        None,
+    ) {
+        Ok(()) | Err(GitToolingError::GitCommand { .. }) => Ok(()),
+        Err(err) => Err(err),
+    }
+}
+
+fn remove_worktree(repo_root: &Path, path: &Path) -> Result<(), GitToolingError> {
+    if !path.exists() {
+        return run_git_for_status(
+            repo_root,
+            vec![OsString::from("worktree"), OsString::from("prune")],
+            None,
+        );
+    }
+    run_git_for_status(
+        repo_root,
+        vec![
+            OsString::from("worktree"),
+            OsString::from("remove"),
+            OsString::from("--force"),
+            path.as_os_str().to_os_string(),
+        ],
+        None,
+    )
+}
+
+fn delete_branch(repo_root: &Path, branch: &str, force: bool) -> Result<(), GitToolingError> {
+    let flag = if force { "-D" } else { "-d" };
+    run_git_for_status(
+        repo_root,
+        vec![
+            OsString::from("branch"),
+            OsString::from(flag),
+            OsString::from(branch),
+        ],
+        None,
+    )
+}
+
+/// Falls back to a Codex identity when the user has not configured one, so
+/// committing the session's work never fails on a fresh machine.
+fn commit_identity_env(dir: &Path) -> Vec<(OsString, OsString)> {
+    let configured = |key: &str| {
+        run_git_for_stdout(
+            dir,
+            vec![OsString::from("config"), OsString::from(key)],
+            None,
+        )
+        .is_ok_and(|value| !value.is_empty())
+    };
+    if configured("user.name") && configured("user.email") {
+        return Vec::new();
+    }
+    vec![
+        (OsString::from("GIT_AUTHOR_NAME"), OsString::from("Codex")),
+        (
+            OsString::from("GIT_AUTHOR_EMAIL"),
+            OsString::from("codex@codex.local"),
+        ),
+        (
+            OsString::from("GIT_COMMITTER_NAME"),
+            OsString::from("Codex"),
+        ),
+        (
+            OsString::from("GIT_COMMITTER_EMAIL"),
+            OsString::from("codex@codex.local"),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use std::process::Command;
+    use std::process::Stdio;
+
+    fn run_git_in(repo_path: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .current_dir(repo_path)
+            .args(args)
+            .status()
+            .expect("git command");
+        assert!(status.success(), "git command failed: {args:?}");
+    }
+
+    fn run_git_stdout(repo_path: &Path, args: &[&str]) -> String {
+        let output = Command::new("git")
+            .current_dir(repo_path)
+            .args(args)
+            .output()
+            .expect("git command");
+        assert!(output.status.success(), "git command failed: {args:?}");
+        String::from_utf8_lossy(&output.stdout).trim().to_string()
+    }
+
+    /// Initializes a repository with one commit and a local identity.
+    fn init_repo_with_commit(repo: &Path) {
+        run_git_in(repo, &["init", "--initial-branch=main"]);
+        run_git_in(repo, &["config", "core.autocrlf", "false"]);
+        run_git_in(repo, &["config", "user.name", "Tester"]);
+        run_git_in(repo, &["config", "user.email", "test@example.com"]);
+        std::fs::write(repo.join("tracked.txt"), "initial\n").expect("write");
+        run_git_in(repo, &["add", "tracked.txt"]);
+        run_git_in(repo, &["commit", "-m", "init"]);
+    }
+
+    fn branch_list(repo: &Path) -> String {
+        run_git_stdout(repo, &["branch", "--list", "codex/*"])
+    }
+
+    /// Re-lock `worktree` as if its session had crashed: the recorded owner is
+    /// a process that has already exited.
+    fn abandon(repo: &Path, worktree: &SessionWorktree) {
+        let mut child = Command::new("git")
+            .arg("--version")
+            .stdout(Stdio::null())
+            .spawn()
+            .expect("spawn git");
+        let pid = child.id();
+        child.wait().expect("wait for git");
+        let path = worktree.path().to_str().expect("utf8");
+        run_git_in(repo, &["worktree", "unlock", path]);
+        run_git_in(
+            repo,
+            &[
+                "worktree",
+                "lock",
+                "--reason",
+                &format!("{SESSION_LOCK_REASON_PREFIX}{pid}"),
+                path,
+            ],
+        );
+    }
+
+    #[test]
+    fn create_session_worktree_checks_out_new_branch() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(&repo)?;
+        init_repo_with_commit(&repo);
+        let worktrees = temp.path().join("worktrees");
+
+        let worktree = create_session_worktree(&CreateWorktreeOptions::new(&repo, &worktrees))?;
+
+        assert!(worktree.branch().starts_with("codex/session-"));
+        assert!(worktree.path().starts_with(worktrees.canonicalize()?));
+        assert_eq!(
+            std::fs::read_to_string(worktree.path().join("tracked.txt"))?,
+            "initial\n"
+        );
+        assert_eq!(
+            run_git_stdout(worktree.path(), &["rev-parse", "--abbrev-ref", "HEAD"]),
+            worktree.branch()
+        );
+        assert!(!worktree_has_changes(&worktree)?);
+
+        std::fs::write(worktree.path().join("new.txt"), "new\n")?;
+        assert!(worktree_has_changes(&worktree)?);
+        Ok(())
+    }
+
+    #[test]
+    fn create_session_worktree_requires_commit() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        run_git_in(temp.path(), &["init", "--initial-branch=main"]);
+        let err = create_session_worktree(&CreateWorktreeOptions::new(
+            temp.path(),
+            &temp.path().join("worktrees"),
+        ))
+        .unwrap_err();
+        assert!(matches!(err, GitToolingError::MissingHead { .. }));
+        Ok(())
+    }
+
+    #[test]
+    fn merge_brings_changes_into_checkout() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(&repo)?;
+        init_repo_with_commit(&repo);
+        let worktrees = temp.path().join("worktrees");
+        let worktree = create_session_worktree(
+            &CreateWorktreeOptions::new(&repo, &worktrees).branch("codex/merge-me"),
+        )?;
+
+        std::fs::write(worktree.path().join("tracked.txt"), "changed\n")?;
+        finish_session_worktree(&worktree, WorktreeDisposition::Merge)?;
+
+        assert_eq!(
+            std::fs::read_to_string(repo.join("tracked.txt"))?,
+            "changed\n"
+        );
+        assert!(!worktree.path().exists());
+        assert_eq!(branch_list(&repo), "");
+        Ok(())
+    }
+
+    #[test]
+    fn keep_commits_changes_on_branch() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(&repo)?;
+        init_repo_with_commit(&repo);
+        let worktrees = temp.path().join("worktrees");
+        let worktree = create_session_worktree(
+            &CreateWorktreeOptions::new(&repo, &worktrees).branch("codex/keep-me"),
+        )?;
+
+        std::fs::write(worktree.path().join("new.txt"), "new\n")?;
+        finish_session_worktree(&worktree, WorktreeDisposition::Keep)?;
+
+        assert!(!worktree.path().exists());
+        assert!(!repo.join("new.txt").exists());
+        assert_eq!(
+            run_git_stdout(&repo, &["show", "codex/keep-me:new.txt"]),
+            "new"
+        );
+        assert_eq!(
+            run_git_stdout(&repo, &["log", "-1", "--format=%s", "codex/keep-me"]),
+            SESSION_COMMIT_MESSAGE
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn discard_drops_worktree_and_branch() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(&repo)?;
+        init_repo_with_commit(&repo);
+        let worktrees = temp.path().join("worktrees");
+        let worktree = create_session_worktree(&CreateWorktreeOptions::new(&repo, &worktrees))?;
+
+        std::fs::write(worktree.path().join("tracked.txt"), "changed\n")?;
+        finish_session_worktree(&worktree, WorktreeDisposition::Discard)?;
+
+        assert!(!worktree.path().exists());
+        assert_eq!(branch_list(&repo), "");
+        assert_eq!(
+            std::fs::read_to_string(repo.join("tracked.txt"))?,
+            "initial\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn list_and_prune_only_touch_codex_worktrees() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(&repo)?;
+        init_repo_with_commit(&repo);
+        let worktrees = temp.path().join("worktrees");
+        let clean = create_session_worktree(
+            &CreateWorktreeOptions::new(&repo, &worktrees).branch("codex/clean"),
+        )?;
+        let dirty = create_session_worktree(
+            &CreateWorktreeOptions::new(&repo, &worktrees).branch("codex/dirty"),
+        )?;
+        std::fs::write(dirty.path().join("scratch.txt"), "wip\n")?;
+        abandon(&repo, &clean);
+        abandon(&repo, &dirty);
+        let other = temp.path().join("other");
+        run_git_in(
+            &repo,
+            &[
+                "worktree",
+                "add",
+                "-b",
+                "feature",
+                other.to_str().expect("utf8"),
+            ],
+        );
+
+        let listed = list_worktrees(&repo)?;
+        let branches: Vec<&str> = listed.iter().map(|entry| entry.branch.as_str()).collect();
+        assert_eq!(branches, vec!["codex/clean", "codex/dirty"]);
+
+        let report = prune_worktrees(&repo, false)?;
+        assert_eq!(report.removed.len(), 1);
+        assert_eq!(report.removed[0].branch, "codex/clean");
+        assert_eq!(report.skipped_dirty.len(), 1);
+        assert!(report.kept_branches.is_empty());
+        assert!(!clean.path().exists());
+        assert!(dirty.path().exists());
+        assert!(other.exists());
+
+        let report = prune_worktrees(&repo, true)?;
+        assert_eq!(report.removed.len(), 1);
+        assert!(!dirty.path().exists());
+        assert_eq!(branch_list(&repo), "");
+        Ok(())
+    }
+
+    #[test]
+    fn prune_skips_worktrees_of_running_sessions() -> Result<(), GitToolingError> {
+        let temp = tempfile::tempdir()?;
+        let repo = temp.path().join("repo");
+        std::fs::create_dir_all(&repo)?;
+        init_repo_with_commit(&repo);
+        let worktrees = temp.path().join("worktrees");
+        let worktree = create_session_worktree(&CreateWorktreeOptions::new(&repo, &worktrees))?;
+
+        let listed = list_worktrees(&repo)?;
+        assert_eq!(
+            listed[0].locked,
+            Some(format!(
+                "{SESSION_LOCK_REASON_PREFIX}{}",
+                std::process::id()
+            ))
+        );
+        assert!(listed[0].in_use());
+
+        let report = prune_worktrees(&repo, true)?;
+        assert!(report.removed.is_empty());
+        assert_eq!(report.skipped_in_use.len(), 1);
+        assert!(worktree.path().exists());
+
+        finish_session_worktree(&worktree, WorktreeDisposition::Discard)?;
+        assert!(!worktree.path().exists());
+        assert_eq!(branch_list(&repo), "");
+        Ok(())
+    }
+
+    #[test]
+    fn parse_worktree_list_reads_porcelain_blocks() {
+        let output = "worktree /repo\nHEAD aaa\nbranch refs/heads/main\n\n\
+                      worktree /wt/one\nHEAD bbb\nbranch refs/heads/codex/one\n\n\
+                      worktree /wt/gone\nHEAD ccc\nbranch refs/heads/codex/gone\nprunable gitdir file points to non-existent location\n\n\
+                      worktree /wt/locked\nHEAD eee\nbranch refs/heads/codex/locked\nlocked in use by codex process 42\n\n\
+                      worktree /wt/detached\nHEAD ddd\ndetached";
+        assert_eq!(
+            parse_worktree_list(output),
+            vec![
+                WorktreeEntry {
+                    path: PathBuf::from("/wt/one"),
+                    branch: "codex/one".to_string(),
+                    head: "bbb".to_string(),
+                    prunable: false,
+                    locked: None,
+                },
+                WorktreeEntry {
+                    path: PathBuf::from("/wt/gone"),
+                    branch: "codex/gone".to_string(),
+                    head: "ccc".to_string(),
+                    prunable: true,
+                    locked: None,
+                },
+                WorktreeEntry {
+                    path: PathBuf::from("/wt/locked"),
+                    branch: "codex/locked".to_string(),
+                    head: "eee".to_string(),
+                    prunable: false,
+                    locked: Some("in use by codex process 42".to_string()),
+                },
+            ]
+        );
+    }
+}
@@ -7,6 +7,8 @@ use codex_core::error::Result;
 use codex_core::error::SandboxErr;
 use codex_core::protocol::SandboxPolicy;
 
+use crate::mount_namespace::mount_read_only_subpaths;
+
 use landlock::ABI;
 use landlock::Access;
 use landlock::AccessFs;
@@ -36,8 +38,16 @@ pub(crate) fn apply_sandbox_policy_to_current_thread(
     }
 
     if !sandbox_policy.has_full_disk_write_access() {
-        let writable_roots = sandbox_policy
-            .get_writable_roots_with_cwd(cwd)
+        let writable_roots = sandbox_policy.get_writable_roots_with_cwd(cwd);
+        let read_only_subpaths: Vec<PathBuf> = writable_roots
+            .iter()
+            .flat_map(|writable_root| writable_root.read_only_subpaths.iter().cloned())
+            .collect();
+        // Must happen before Landlock is enforced: afterwards this thread can
+        // no longer mount anything.
+        mount_read_only_subpaths(&read_only_subpaths)?;
+
+        let writable_roots = writable_roots
             .into_iter()
             .map(|writable_root| writable_root.root)
             .collect();
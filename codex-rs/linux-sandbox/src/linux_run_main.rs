@@ -3,9 +3,14 @@ use std::ffi::CString;
 use std::path::PathBuf;
 
 use crate::landlock::apply_sandbox_policy_to_current_thread;
+use crate::resource_limits::apply_resource_limits;
 
 #[derive(Debug, Parser)]
 pub struct LandlockCommand {
+    /// JSON-encoded `SandboxResourceLimits` to apply to the command.
+    #[arg(long = "resource-limits")]
+    pub resource_limits: Option<codex_core::config_types::SandboxResourceLimits>,
+
     /// It is possible that the cwd used in the context of the sandbox policy
     /// is different from the cwd of the process to spawn.
     pub sandbox_policy_cwd: PathBuf,
@@ -19,11 +24,18 @@ pub struct LandlockCommand {
 
 pub fn run_main() -> ! {
     let LandlockCommand {
+        resource_limits,
         sandbox_policy_cwd,
         sandbox_policy,
         command,
     } = LandlockCommand::parse();
 
+    if let Some(resource_limits) = resource_limits
+        && let Err(e) = apply_resource_limits(&resource_limits)
+    {
+        panic!("error applying resource limits: {e:?}");
+    }
+
     if let Err(e) = apply_sandbox_policy_to_current_thread(&sandbox_policy, &sandbox_policy_cwd) {
         panic!("error running landlock: {e:?}");
     }
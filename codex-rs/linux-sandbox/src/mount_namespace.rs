@@ -0,0 +1,180 @@
+use std::ffi::CString;
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+use std::path::PathBuf;
+
+use codex_core::error::CodexErr;
+use codex_core::error::Result;
+use codex_core::error::SandboxErr;
+
+/// Landlock can only grant access to whole subtrees, so it cannot keep e.g.
+/// `.git` read-only inside a writable workspace. Instead, move this process
+/// into a new user + mount namespace and cover each protected subpath with a
+/// read-only bind mount. The mounts are private to the namespace, so the rest
+/// of the system is unaffected.
+///
+/// Must run while the process is still single-threaded (a requirement of
+/// `unshare(CLONE_NEWUSER)`) and before Landlock is enforced, since a
+/// Landlock-restricted thread may no longer change its mount topology.
+///
+/// Hosts that forbid unprivileged user namespaces (Docker's default seccomp
+/// profile, Ubuntu's AppArmor restriction) cannot protect the subpaths, so
+/// the command is refused rather than run with them writable. Codex handles
+/// that like any other sandbox denial, e.g. by asking to rerun the command
+/// outside the sandbox.
+pub(crate) fn mount_read_only_subpaths(read_only_subpaths: &[PathBuf]) -> Result<()> {
+    if read_only_subpaths.is_empty() {
+        return Ok(());
+    }
+    match enter_user_and_mount_namespace() {
+        Ok(()) => {}
+        Err(NamespaceError::Unavailable(err)) => {
+            let subpaths = read_only_subpaths
+                .iter()
+                .map(|subpath| subpath.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ");
+            return Err(CodexErr::Sandbox(SandboxErr::ReadOnlyMount(
+                io::Error::new(
+                    err.kind(),
+                    format!(
+                        "cannot keep {subpaths} read-only because user namespaces are unavailable: {err}"
+                    ),
+                ),
+            )));
+        }
+        Err(NamespaceError::Failed(err)) => {
+            return Err(CodexErr::Sandbox(SandboxErr::ReadOnlyMount(err)));
+        }
+    }
+    read_only_subpaths
+        .iter()
+        .try_for_each(|subpath| bind_mount_read_only(subpath))
+        .map_err(|err| CodexErr::Sandbox(SandboxErr::ReadOnlyMount(err)))
+}
+
+enum NamespaceError {
+    /// The host does not let this process set up a user + mount namespace.
+    Unavailable(io::Error),
+    Failed(io::Error),
+}
+
+/// `unshare(CLONE_NEWUSER)` fails with `EPERM` when seccomp or a security
+/// module forbids it, `EINVAL` when the kernel lacks user namespaces and
+/// `ENOSPC` when `user.max_user_namespaces` is 0. Under Ubuntu's AppArmor
+/// restriction `unshare` succeeds, but the new namespace has no capabilities,
+/// so the first mount fails with `EPERM` instead.
+fn user_namespaces_unavailable(err: &io::Error) -> bool {
+    matches!(
+        err.raw_os_error(),
+        Some(libc::EPERM | libc::EINVAL | libc::ENOSPC)
+    )
+}
+
+fn enter_user_and_mount_namespace() -> std::result::Result<(), NamespaceError> {
+    let uid = unsafe { libc::geteuid() };
+    let gid = unsafe { libc::getegid() };
+
+    if unsafe { libc::unshare(libc::CLONE_NEWUSER | libc::CLONE_NEWNS) } != 0 {
+        let err = io::Error::last_os_error();
+        return Err(if user_namespaces_unavailable(&err) {
+            NamespaceError::Unavailable(err)
+        } else {
+            NamespaceError::Failed(err)
+        });
+    }
+
+    // Process hardening may have cleared the dumpable flag, which makes
+    // /proc/self/{uid,gid}_map owned by root and unwritable. Restore it while
+    // the maps are written.
+    let was_dumpable = unsafe { libc::prctl(libc::PR_GET_DUMPABLE, 0, 0, 0, 0) };
+    if was_dumpable == 0 {
+        unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 1, 0, 0, 0) };
+    }
+    let maps = write_id_maps(uid, gid);
+    if was_dumpable == 0 {
+        unsafe { libc::prctl(libc::PR_SET_DUMPABLE, 0, 0, 0, 0) };
+    }
+    maps.map_err(NamespaceError::Failed)?;
+
+    // Keep our mounts from propagating back into the parent namespace. The
+    // ids are mapped onto themselves, so staying in the namespace is harmless
+    // if mounting turns out to be forbidden.
+    mount(None, Path::new("/"), libc::MS_REC | libc::MS_PRIVATE).map_err(|err| {
+        if err.kind() == io::ErrorKind::PermissionDenied {
+            NamespaceError::Unavailable(err)
+        } else {
+            NamespaceError::Failed(err)
+        }
+    })
+}
+
+/// Map the caller's ids onto themselves so file ownership and permission
+/// checks look the same inside the namespace.
+fn write_id_maps(uid: libc::uid_t, gid: libc::gid_t) -> io::Result<()> {
+    std::fs::write("/proc/self/setgroups", "deny")?;
+    std::fs::write("/proc/self/uid_map", format!("{uid} {uid} 1"))?;
+    std::fs::write("/proc/self/gid_map", format!("{gid} {gid} 1"))
+}
+
+fn bind_mount_read_only(path: &Path) -> io::Result<()> {
+    mount(Some(path), path, libc::MS_BIND | libc::MS_REC)?;
+
+    // Inside a user namespace, a remount must keep the flags that are locked
+    // on the underlying mount (nosuid, nodev, ...) or it fails with EPERM.
+    let mut flags = libc::MS_BIND | libc::MS_REMOUNT | libc::MS_RDONLY;
+    let locked_flags = statvfs_flags(path)?;
+    for (st_flag, ms_flag) in [
+        (libc::ST_NOSUID, libc::MS_NOSUID),
+        (libc::ST_NODEV, libc::MS_NODEV),
+        (libc::ST_NOEXEC, libc::MS_NOEXEC),
+        (libc::ST_NOATIME, libc::MS_NOATIME),
+        (libc::ST_NODIRATIME, libc::MS_NODIRATIME),
+        (libc::ST_RELATIME, libc::MS_RELATIME),
+    ] {
+        if locked_flags & st_flag != 0 {
+            flags |= ms_flag;
+        }
+    }
+    mount(None, path, flags)
+}
+
+fn mount(source: Option<&Path>, target: &Path, flags: libc::c_ulong) -> io::Result<()> {
+    let source = source.map(path_to_cstring).transpose()?;
+    let target = path_to_cstring(target)?;
+    let source_ptr = source
+        .as_ref()
+        .map_or(std::ptr::null(), |source| source.as_ptr());
+    let rc = unsafe {
+        libc::mount(
+            source_ptr,
+            target.as_ptr(),
+            std::ptr::null(),
+            flags,
+            std::ptr::null(),
+        )
+    };
+    if rc != 0 {
+        let err = io::Error::last_os_error();
+        return Err(io::Error::new(
+            err.kind(),
+            format!("mount {}: {err}", target.to_string_lossy()),
+        ));
+    }
+    Ok(())
+}
+
+fn statvfs_flags(path: &Path) -> io::Result<libc::c_ulong> {
+    let path = path_to_cstring(path)?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    if unsafe { libc::statvfs(path.as_ptr(), &mut stat) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(stat.f_flag)
+}
+
+fn path_to_cstring(path: &Path) -> io::Result<CString> {
+    CString::new(path.as_os_str().as_bytes())
+        .map_err(|err| io::Error::new(io::ErrorKind::InvalidInput, err))
+}
@@ -0,0 +1,113 @@
+use std::io;
+use std::os::unix::fs::MetadataExt;
+
+use codex_core::config_types::SandboxResourceLimits;
+use codex_core::resource_limits::cgroup_scope_path;
+
+const BYTES_PER_MB: u64 = 1024 * 1024;
+
+#[cfg(target_env = "gnu")]
+type RlimitResource = libc::__rlimit_resource_t;
+#[cfg(not(target_env = "gnu"))]
+type RlimitResource = libc::c_int;
+
+/// Applies `limits` to this process so the command it `exec`s inherits them.
+///
+/// CPU time and file size always use rlimits: the kernel signals the
+/// offending process with `SIGXCPU`/`SIGXFSZ`, which Codex reports as a limit
+/// breach. Memory and process counts are enforced by moving this process into
+/// a fresh cgroup v2 scope when the caller's cgroup is delegated to us, since
+/// that covers every descendant and records breaches in `memory.events` /
+/// `pids.events`. Without cgroups the process count falls back to
+/// `RLIMIT_NPROC`, which is coarser and does not report breaches, and the
+/// memory limit is skipped (Codex warns about that once per session): the
+/// only rlimit alternative, `RLIMIT_AS`, caps address space, which runtimes
+/// such as the JVM, Node and Go reserve far beyond what they use.
+///
+/// Must run before entering the user namespace, since cgroup delegation is
+/// checked against our uid in the initial namespace.
+pub(crate) fn apply_resource_limits(limits: &SandboxResourceLimits) -> io::Result<()> {
+    let pids_in_scope = enter_cgroup_scope(limits);
+
+    if let Some(seconds) = limits.max_cpu_seconds {
+        // The soft limit delivers SIGXCPU; the hard limit a second later
+        // SIGKILLs a command that ignores it.
+        set_rlimit(libc::RLIMIT_CPU, seconds, seconds.saturating_add(1))?;
+    }
+    if let Some(mb) = limits.max_file_size_mb {
+        let bytes = mb.saturating_mul(BYTES_PER_MB);
+        set_rlimit(libc::RLIMIT_FSIZE, bytes, bytes)?;
+    }
+    if let Some(pids) = limits.max_pids
+        && !pids_in_scope
+    {
+        // RLIMIT_NPROC counts every task owned by the user, not just ours.
+        let uid = unsafe { libc::getuid() };
+        let limit = count_user_tasks(uid).saturating_add(pids);
+        set_rlimit(libc::RLIMIT_NPROC, limit, limit)?;
+    }
+    Ok(())
+}
+
+/// Returns whether the cgroup scope enforces `max_pids`. Best effort: any
+/// failure leaves this process where it was and the limits to the rlimit
+/// fallbacks.
+fn enter_cgroup_scope(limits: &SandboxResourceLimits) -> bool {
+    if limits.max_rss_mb.is_none() && limits.max_pids.is_none() {
+        return false;
+    }
+    let Some(scope_path) = cgroup_scope_path(std::process::id()) else {
+        return false;
+    };
+    if std::fs::create_dir(&scope_path).is_err() {
+        return false;
+    }
+
+    let memory = limits.max_rss_mb.is_some_and(|mb| {
+        let bytes = mb.saturating_mul(BYTES_PER_MB);
+        let applied = std::fs::write(scope_path.join("memory.max"), bytes.to_string()).is_ok();
+        if applied {
+            // Otherwise the command could swap instead of hitting the limit.
+            let _ = std::fs::write(scope_path.join("memory.swap.max"), "0");
+        }
+        applied
+    });
+    let pids = limits
+        .max_pids
+        .is_some_and(|pids| std::fs::write(scope_path.join("pids.max"), pids.to_string()).is_ok());
+
+    // Writing 0 moves the writing process.
+    let joined = (memory || pids) && std::fs::write(scope_path.join("cgroup.procs"), "0").is_ok();
+    if !joined {
+        let _ = std::fs::remove_dir(&scope_path);
+        return false;
+    }
+    pids
+}
+
+fn set_rlimit(resource: RlimitResource, soft: u64, hard: u64) -> io::Result<()> {
+    let limit = libc::rlimit {
+        rlim_cur: soft,
+        rlim_max: hard,
+    };
+    if unsafe { libc::setrlimit(resource, &limit) } != 0 {
+        return Err(io::Error::last_os_error());
+    }
+    Ok(())
+}
+
+fn count_user_tasks(uid: libc::uid_t) -> u64 {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return 0;
+    };
+    entries
+        .flatten()
+        .filter(|entry| entry.file_name().to_string_lossy().parse::<u32>().is_ok())
+        .filter(|entry| entry.metadata().is_ok_and(|metadata| metadata.uid() == uid))
+        .map(|entry| {
+            std::fs::read_dir(entry.path().join("task"))
+                .map(|tasks| tasks.count() as u64)
+                .unwrap_or(1)
+        })
+        .sum()
+}
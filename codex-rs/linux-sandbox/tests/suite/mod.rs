@@ -1,2 +1,4 @@
 // Aggregates all former standalone integration tests as modules.
 mod landlock;
+mod read_only_subpaths;
+mod resource_limits;
@@ -0,0 +1,343 @@
+#![cfg(target_os = "linux")]
+
+//! Mirrors the Seatbelt tests in `core/tests/suite/seatbelt.rs`: the `.git`
+//! folder of a writable Git repository root must stay read-only.
+
+use std::collections::BTreeMap;
+use std::collections::HashMap;
+use std::os::unix::process::CommandExt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::process::Command;
+
+use codex_core::config_types::SandboxResourceLimits;
+use codex_core::landlock::spawn_command_under_linux_sandbox;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::spawn::StdioPolicy;
+use seccompiler::BpfProgram;
+use seccompiler::SeccompAction;
+use seccompiler::SeccompFilter;
+use seccompiler::TargetArch;
+use seccompiler::apply_filter;
+use tempfile::TempDir;
+
+struct TestScenario {
+    repo_parent: PathBuf,
+    file_outside_repo: PathBuf,
+    repo_root: PathBuf,
+    file_in_repo_root: PathBuf,
+    file_in_dot_git_dir: PathBuf,
+}
+
+struct TestExpectations {
+    file_outside_repo_is_writable: bool,
+    file_in_repo_root_is_writable: bool,
+    file_in_dot_git_dir_is_writable: bool,
+}
+
+impl TestScenario {
+    async fn run_test(&self, policy: &SandboxPolicy, expectations: TestExpectations) {
+        assert_eq!(
+            touch(&self.file_outside_repo, policy).await,
+            expectations.file_outside_repo_is_writable
+        );
+        assert_eq!(
+            self.file_outside_repo.exists(),
+            expectations.file_outside_repo_is_writable
+        );
+
+        assert_eq!(
+            touch(&self.file_in_repo_root, policy).await,
+            expectations.file_in_repo_root_is_writable
+        );
+        assert_eq!(
+            self.file_in_repo_root.exists(),
+            expectations.file_in_repo_root_is_writable
+        );
+
+        assert_eq!(
+            touch(&self.file_in_dot_git_dir, policy).await,
+            expectations.file_in_dot_git_dir_is_writable
+        );
+        assert_eq!(
+            self.file_in_dot_git_dir.exists(),
+            expectations.file_in_dot_git_dir_is_writable
+        );
+    }
+}
+
+/// If the parent of the repository is the writable root, `.git` is not
+/// protected (the user opted out of the Git safety net).
+#[tokio::test]
+async fn if_parent_of_repo_is_writable_then_dot_git_folder_is_writable() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let test_scenario = create_test_scenario(&tmp);
+    let policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![test_scenario.repo_parent.clone()],
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+    };
+
+    test_scenario
+        .run_test(
+            &policy,
+            TestExpectations {
+                file_outside_repo_is_writable: true,
+                file_in_repo_root_is_writable: true,
+                file_in_dot_git_dir_is_writable: true,
+            },
+        )
+        .await;
+}
+
+/// When the writable root is the root of a Git repository, the `.git` folder
+/// is bind-mounted read-only.
+#[tokio::test]
+async fn if_git_repo_is_writable_root_then_dot_git_folder_is_read_only() {
+    if !user_namespaces_available().await {
+        eprintln!("skipping test: user namespaces are unavailable on this host");
+        return;
+    }
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let test_scenario = create_test_scenario(&tmp);
+    let policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![test_scenario.repo_root.clone()],
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+    };
+
+    test_scenario
+        .run_test(
+            &policy,
+            TestExpectations {
+                file_outside_repo_is_writable: false,
+                file_in_repo_root_is_writable: true,
+                file_in_dot_git_dir_is_writable: false,
+            },
+        )
+        .await;
+}
+
+/// Under DangerFullAccess, all writes should be permitted anywhere on disk,
+/// including inside the .git folder.
+#[tokio::test]
+async fn danger_full_access_allows_all_writes() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let test_scenario = create_test_scenario(&tmp);
+    let policy = SandboxPolicy::DangerFullAccess;
+
+    test_scenario
+        .run_test(
+            &policy,
+            TestExpectations {
+                file_outside_repo_is_writable: true,
+                file_in_repo_root_is_writable: true,
+                file_in_dot_git_dir_is_writable: true,
+            },
+        )
+        .await;
+}
+
+/// Under ReadOnly, writes should not be permitted anywhere on disk.
+#[tokio::test]
+async fn read_only_forbids_all_writes() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let test_scenario = create_test_scenario(&tmp);
+    let policy = SandboxPolicy::ReadOnly;
+
+    test_scenario
+        .run_test(
+            &policy,
+            TestExpectations {
+                file_outside_repo_is_writable: false,
+                file_in_repo_root_is_writable: false,
+                file_in_dot_git_dir_is_writable: false,
+            },
+        )
+        .await;
+}
+
+/// Existing files under `.git` cannot be rewritten either, so hooks and refs
+/// stay intact.
+#[tokio::test]
+async fn existing_dot_git_files_cannot_be_modified() {
+    if !user_namespaces_available().await {
+        eprintln!("skipping test: user namespaces are unavailable on this host");
+        return;
+    }
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let test_scenario = create_test_scenario(&tmp);
+    let head = test_scenario.repo_root.join(".git").join("HEAD");
+    std::fs::write(&head, "ref: refs/heads/main\n").expect("should be able to write HEAD");
+    let policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![test_scenario.repo_root.clone()],
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+    };
+
+    let succeeded = run_under_sandbox(
+        vec![
+            "bash".to_string(),
+            "-c".to_string(),
+            format!("echo tampered > {}", head.to_string_lossy()),
+        ],
+        &policy,
+    )
+    .await;
+
+    assert!(!succeeded);
+    assert_eq!(
+        std::fs::read_to_string(&head).expect("should be able to read HEAD"),
+        "ref: refs/heads/main\n"
+    );
+}
+
+/// Without unprivileged user namespaces (Docker's default seccomp profile,
+/// Ubuntu's AppArmor restriction) `.git` cannot be bind-mounted read-only, so
+/// commands that could write to a Git repository root are refused instead of
+/// running with `.git` writable.
+#[test]
+fn refuses_to_run_when_user_namespaces_are_unavailable() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let test_scenario = create_test_scenario(&tmp);
+    let policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![test_scenario.repo_root.clone()],
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+    };
+
+    let (succeeded, stderr) =
+        touch_without_user_namespaces(&test_scenario.file_in_dot_git_dir, &policy);
+    assert!(!succeeded);
+    assert!(!test_scenario.file_in_dot_git_dir.exists());
+    assert!(
+        stderr.contains("user namespaces are unavailable"),
+        "stderr: {stderr}"
+    );
+
+    let (succeeded, _) = touch_without_user_namespaces(&test_scenario.file_in_repo_root, &policy);
+    assert!(!succeeded);
+    assert!(!test_scenario.file_in_repo_root.exists());
+
+    // Without a `.git` to protect there is nothing to mount, so the command
+    // runs under Landlock alone.
+    let policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![test_scenario.repo_parent.clone()],
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+    };
+    let (succeeded, stderr) =
+        touch_without_user_namespaces(&test_scenario.file_outside_repo, &policy);
+    assert!(succeeded, "stderr: {stderr}");
+    assert!(test_scenario.file_outside_repo.exists());
+}
+
+#[expect(clippy::expect_used)]
+fn create_test_scenario(tmp: &TempDir) -> TestScenario {
+    let repo_parent = tmp.path().to_path_buf();
+    let repo_root = repo_parent.join("repo");
+    let dot_git_dir = repo_root.join(".git");
+
+    std::fs::create_dir(&repo_root).expect("should be able to create repo root");
+    std::fs::create_dir(&dot_git_dir).expect("should be able to create .git dir");
+
+    TestScenario {
+        file_outside_repo: repo_parent.join("outside.txt"),
+        repo_parent,
+        file_in_repo_root: repo_root.join("repo_file.txt"),
+        repo_root,
+        file_in_dot_git_dir: dot_git_dir.join("dot_git_file.txt"),
+    }
+}
+
+/// Whether the sandbox can bind-mount `.git` read-only on this host. Without
+/// unprivileged user namespaces it refuses such commands instead, as
+/// `refuses_to_run_when_user_namespaces_are_unavailable` checks.
+#[expect(clippy::expect_used)]
+async fn user_namespaces_available() -> bool {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let test_scenario = create_test_scenario(&tmp);
+    let policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![test_scenario.repo_root.clone()],
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+    };
+    run_under_sandbox(vec!["true".to_string()], &policy).await
+}
+
+/// Note that `path` must be absolute.
+async fn touch(path: &Path, policy: &SandboxPolicy) -> bool {
+    assert!(path.is_absolute(), "Path must be absolute: {path:?}");
+    run_under_sandbox(
+        vec!["touch".to_string(), path.to_string_lossy().to_string()],
+        policy,
+    )
+    .await
+}
+
+#[expect(clippy::expect_used)]
+async fn run_under_sandbox(command: Vec<String>, policy: &SandboxPolicy) -> bool {
+    let command_cwd = std::env::current_dir().expect("getcwd");
+    let sandbox_cwd = command_cwd.clone();
+    let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
+    let mut child = spawn_command_under_linux_sandbox(
+        sandbox_program,
+        command,
+        command_cwd,
+        policy,
+        sandbox_cwd.as_path(),
+        &SandboxResourceLimits::default(),
+        StdioPolicy::RedirectForShellTool,
+        HashMap::new(),
+    )
+    .await
+    .expect("should be able to spawn command under linux sandbox");
+    child
+        .wait()
+        .await
+        .expect("should be able to wait for child process")
+        .success()
+}
+
+/// Runs `touch path` under the sandbox helper with `unshare` failing with
+/// `EPERM`, as it does under Docker's default seccomp profile. Returns whether
+/// the command succeeded and the helper's stderr.
+#[expect(clippy::expect_used)]
+fn touch_without_user_namespaces(path: &Path, policy: &SandboxPolicy) -> (bool, String) {
+    let filter: BpfProgram = SeccompFilter::new(
+        BTreeMap::from([(libc::SYS_unshare, vec![])]),
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::EPERM as u32),
+        TargetArch::try_from(std::env::consts::ARCH).expect("supported architecture"),
+    )
+    .expect("should be able to build seccomp filter")
+    .try_into()
+    .expect("should be able to compile seccomp filter");
+
+    let cwd = std::env::current_dir().expect("getcwd");
+    let mut command = Command::new(env!("CARGO_BIN_EXE_codex-linux-sandbox"));
+    command
+        .arg(&cwd)
+        .arg(serde_json::to_string(policy).expect("serialize policy"))
+        .arg("--")
+        .arg("touch")
+        .arg(path);
+    // SAFETY: only async-signal-safe syscalls run between fork and exec.
+    unsafe {
+        command
+            .pre_exec(move || apply_filter(&filter).map_err(|_| std::io::Error::last_os_error()));
+    }
+    let output = command
+        .output()
+        .expect("should be able to run codex-linux-sandbox");
+    (
+        output.status.success(),
+        String::from_utf8_lossy(&output.stderr).into_owned(),
+    )
+}
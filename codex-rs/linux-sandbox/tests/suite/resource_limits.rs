@@ -0,0 +1,155 @@
+#![cfg(target_os = "linux")]
+
+//! `[sandbox_resource_limits]` breaches must surface as
+//! `SandboxErr::ResourceLimitExceeded` rather than a generic failure.
+
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+use codex_core::config_types::SandboxResourceLimits;
+use codex_core::error::CodexErr;
+use codex_core::error::Result;
+use codex_core::error::SandboxErr;
+use codex_core::exec::ExecParams;
+use codex_core::exec::ExecToolCallOutput;
+use codex_core::exec::SandboxType;
+use codex_core::exec::process_exec_tool_call;
+use codex_core::protocol::SandboxPolicy;
+use codex_core::resource_limits::ResourceLimit;
+use tempfile::TempDir;
+
+#[expect(clippy::expect_used)]
+async fn run_with_limits(
+    command: &[&str],
+    tmp: &TempDir,
+    resource_limits: SandboxResourceLimits,
+) -> Result<ExecToolCallOutput> {
+    let cwd = tmp.path().to_path_buf();
+    let params = ExecParams {
+        command: command.iter().copied().map(str::to_owned).collect(),
+        cwd: cwd.clone(),
+        timeout_ms: Some(10_000),
+        env: HashMap::from([(
+            "PATH".to_string(),
+            std::env::var("PATH").expect("PATH should be set"),
+        )]),
+        with_escalated_permissions: None,
+        justification: None,
+    };
+    let sandbox_policy = SandboxPolicy::WorkspaceWrite {
+        writable_roots: vec![cwd.clone()],
+        network_access: false,
+        exclude_tmpdir_env_var: true,
+        exclude_slash_tmp: true,
+    };
+    let sandbox_program = env!("CARGO_BIN_EXE_codex-linux-sandbox");
+    process_exec_tool_call(
+        params,
+        SandboxType::LinuxSeccomp,
+        &sandbox_policy,
+        cwd.as_path(),
+        &Some(PathBuf::from(sandbox_program)),
+        &resource_limits,
+        None,
+    )
+    .await
+}
+
+fn file_size_limit() -> SandboxResourceLimits {
+    SandboxResourceLimits {
+        max_file_size_mb: Some(1),
+        ..Default::default()
+    }
+}
+
+#[tokio::test]
+async fn file_size_limit_breach_is_reported() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let result = run_with_limits(
+        &["dd", "if=/dev/zero", "of=big.bin", "bs=1M", "count=2"],
+        &tmp,
+        file_size_limit(),
+    )
+    .await;
+
+    match result {
+        Err(CodexErr::Sandbox(SandboxErr::ResourceLimitExceeded { limit, output })) => {
+            assert_eq!(limit, ResourceLimit::FileSize);
+            assert_eq!(
+                output.resource_limit_exceeded,
+                Some(ResourceLimit::FileSize)
+            );
+        }
+        other => panic!("expected a file size limit breach, got: {other:?}"),
+    }
+    let written = std::fs::metadata(tmp.path().join("big.bin"))
+        .expect("dd should have created the file")
+        .len();
+    assert_eq!(written, 1024 * 1024);
+}
+
+/// Shells report a child killed by `SIGXFSZ` as exit code 153.
+#[tokio::test]
+async fn file_size_limit_breach_inside_shell_is_reported() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let result = run_with_limits(
+        &[
+            "bash",
+            "-c",
+            "dd if=/dev/zero of=big.bin bs=1M count=2 && echo done",
+        ],
+        &tmp,
+        file_size_limit(),
+    )
+    .await;
+
+    match result {
+        Err(CodexErr::Sandbox(SandboxErr::ResourceLimitExceeded { limit, output })) => {
+            assert_eq!(limit, ResourceLimit::FileSize);
+            assert_eq!(output.exit_code, 153);
+        }
+        other => panic!("expected a file size limit breach, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn cpu_time_limit_breach_is_reported() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let result = run_with_limits(
+        &["bash", "-c", "while :; do :; done"],
+        &tmp,
+        SandboxResourceLimits {
+            max_cpu_seconds: Some(1),
+            ..Default::default()
+        },
+    )
+    .await;
+
+    match result {
+        Err(CodexErr::Sandbox(SandboxErr::ResourceLimitExceeded { limit, .. })) => {
+            assert_eq!(limit, ResourceLimit::CpuTime);
+        }
+        other => panic!("expected a CPU time limit breach, got: {other:?}"),
+    }
+}
+
+#[tokio::test]
+async fn commands_within_limits_succeed() {
+    let tmp = TempDir::new().expect("should be able to create temp dir");
+    let output = run_with_limits(
+        &["bash", "-c", "echo hello > small.txt && cat small.txt"],
+        &tmp,
+        SandboxResourceLimits {
+            max_rss_mb: Some(512),
+            max_cpu_seconds: Some(10),
+            max_pids: Some(64),
+            max_file_size_mb: Some(1),
+        },
+    )
+    .await
+    .expect("command within limits should succeed");
+
+    assert_eq!(output.exit_code, 0);
+    assert_eq!(output.stdout.text, "hello\n");
+    assert_eq!(output.resource_limit_exceeded, None);
+}
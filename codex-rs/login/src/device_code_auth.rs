@@ -186,6 +186,7 @@ pub async fn run_device_code_login(opts: ServerOptions) -> std::io::Result<()> {
 
     crate::server::persist_tokens_async(
         &opts.codex_home,
+        &opts.credential_store,
         None,
         tokens.id_token,
         tokens.access_token,
@@ -1,8 +1,11 @@
 mod device_code_auth;
+mod mcp_oauth;
 mod pkce;
 mod server;
 
 pub use device_code_auth::run_device_code_login;
+pub use mcp_oauth::McpLoginOptions;
+pub use mcp_oauth::run_mcp_login_server;
 pub use server::LoginServer;
 pub use server::ServerOptions;
 pub use server::ShutdownHandle;
@@ -16,8 +19,11 @@ pub use codex_core::auth::AuthDotJson;
 pub use codex_core::auth::CLIENT_ID;
 pub use codex_core::auth::OPENAI_API_KEY_ENV_VAR;
 pub use codex_core::auth::get_auth_file;
+pub use codex_core::auth::load_auth_dot_json;
 pub use codex_core::auth::login_with_api_key;
 pub use codex_core::auth::logout;
+pub use codex_core::auth::save_auth_dot_json;
 pub use codex_core::auth::try_read_auth_json;
 pub use codex_core::auth::write_auth_json;
+pub use codex_core::credential_store::CredentialStore;
 pub use codex_core::token_data::TokenData;
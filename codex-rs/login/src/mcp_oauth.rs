@@ -0,0 +1,548 @@
+//! `codex mcp login`: the OAuth 2.1 authorization-code flow with PKCE for
+//! streamable HTTP MCP servers.
+//! https://modelcontextprotocol.io/specification/2025-06-18/basic/authorization
+//!
+//! The authorization server is found through the MCP server's protected
+//! resource metadata (RFC 9728), located by the `resource_metadata` parameter
+//! of the `WWW-Authenticate` header the server answers an unauthenticated
+//! request with or else at its well-known URL, and the authorization server's
+//! own metadata (RFC 8414), falling back to `/authorize`, `/token` and
+//! `/register` on the server's origin. Unless a
+//! client id is supplied, Codex registers itself with dynamic client
+//! registration (RFC 7591).
+
+use std::io;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+use chrono::Utc;
+use codex_core::credential_store::CredentialStore;
+use codex_core::mcp_oauth::McpOAuthTokens;
+use codex_core::mcp_oauth::request_tokens;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use tiny_http::Response;
+use url::Url;
+
+use crate::pkce::PkceCodes;
+use crate::pkce::generate_pkce;
+use crate::server::HandledRequest;
+use crate::server::LoginServer;
+use crate::server::bind_server;
+use crate::server::generate_state;
+use crate::server::serve_callbacks;
+
+/// Port for the local callback server. Redirect URIs are registered per
+/// login, so any free port works.
+const DEFAULT_PORT: u16 = 0;
+const CALLBACK_PATH: &str = "/callback";
+
+#[derive(Debug, Clone)]
+pub struct McpLoginOptions {
+    pub codex_home: PathBuf,
+    /// Where the resulting tokens are stored.
+    pub credential_store: CredentialStore,
+    pub server_name: String,
+    pub server_url: String,
+    /// Client id registered ahead of time, for authorization servers without
+    /// dynamic client registration.
+    pub client_id: Option<String>,
+    /// Scopes to request. Defaults to the scopes the server advertises.
+    pub scopes: Vec<String>,
+    pub port: u16,
+    pub open_browser: bool,
+}
+
+impl McpLoginOptions {
+    pub fn new(codex_home: PathBuf, server_name: String, server_url: String) -> Self {
+        Self {
+            codex_home,
+            credential_store: CredentialStore::default(),
+            server_name,
+            server_url,
+            client_id: None,
+            scopes: Vec::new(),
+            port: DEFAULT_PORT,
+            open_browser: true,
+        }
+    }
+}
+
+/// OAuth protected resource metadata (RFC 9728).
+#[derive(Debug, Deserialize)]
+struct ProtectedResourceMetadata {
+    #[serde(default)]
+    authorization_servers: Vec<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+}
+
+/// OAuth authorization server metadata (RFC 8414).
+#[derive(Debug, Deserialize)]
+struct AuthorizationServerMetadata {
+    authorization_endpoint: String,
+    token_endpoint: String,
+    #[serde(default)]
+    registration_endpoint: Option<String>,
+    #[serde(default)]
+    scopes_supported: Vec<String>,
+    #[serde(default)]
+    code_challenge_methods_supported: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ClientRegistration {
+    client_id: String,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+/// Discover the authorization server for `opts.server_url`, register a client
+/// if needed and start the local callback server. The browser is opened on
+/// the returned [`LoginServer::auth_url`] when `opts.open_browser` is set.
+pub async fn run_mcp_login_server(opts: McpLoginOptions) -> io::Result<LoginServer> {
+    let client = reqwest::Client::new();
+    let server_url = Url::parse(&opts.server_url).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid MCP server url {}: {e}", opts.server_url),
+        )
+    })?;
+    let (metadata, advertised_scopes) = discover_authorization_server(&client, &server_url).await?;
+
+    let server = bind_server(opts.port)?;
+    let actual_port = match server.server_addr().to_ip() {
+        Some(addr) => addr.port(),
+        None => {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                "Unable to determine the server port",
+            ));
+        }
+    };
+    let redirect_uri = format!("http://localhost:{actual_port}{CALLBACK_PATH}");
+
+    let registration = match &opts.client_id {
+        Some(client_id) => ClientRegistration {
+            client_id: client_id.clone(),
+            client_secret: None,
+        },
+        None => {
+            let Some(registration_endpoint) = &metadata.registration_endpoint else {
+                return Err(io::Error::other(format!(
+                    "the authorization server for `{}` does not support dynamic client registration; pass --client-id",
+                    opts.server_name
+                )));
+            };
+            register_client(&client, registration_endpoint, &redirect_uri).await?
+        }
+    };
+
+    let scopes = if opts.scopes.is_empty() {
+        advertised_scopes
+    } else {
+        opts.scopes.clone()
+    };
+    let scope = (!scopes.is_empty()).then(|| scopes.join(" "));
+
+    let pkce = generate_pkce();
+    let state = generate_state();
+    let auth_url = build_authorize_url(
+        &metadata.authorization_endpoint,
+        &registration.client_id,
+        &redirect_uri,
+        &pkce,
+        &state,
+        &opts.server_url,
+        scope.as_deref(),
+    )?;
+
+    if opts.open_browser {
+        let _ = webbrowser::open(&auth_url);
+    }
+
+    let callback = Arc::new(Callback {
+        opts,
+        client,
+        token_endpoint: metadata.token_endpoint,
+        registration,
+        redirect_uri,
+        pkce,
+        state,
+        scope,
+    });
+    let handler = move |url_raw: String| {
+        let callback = callback.clone();
+        async move { callback.handle(&url_raw).await }
+    };
+
+    Ok(serve_callbacks(server, auth_url, actual_port, handler))
+}
+
+/// The authorization server metadata for `server_url` and the scopes it
+/// advertises.
+async fn discover_authorization_server(
+    client: &reqwest::Client,
+    server_url: &Url,
+) -> io::Result<(AuthorizationServerMetadata, Vec<String>)> {
+    let resource_metadata_urls = match resource_metadata_url(client, server_url).await? {
+        Some(url) => vec![url],
+        None => well_known_urls(server_url, "oauth-protected-resource"),
+    };
+    let resource: Option<ProtectedResourceMetadata> =
+        fetch_metadata(client, resource_metadata_urls).await?;
+
+    let issuer = match resource
+        .as_ref()
+        .and_then(|resource| resource.authorization_servers.first())
+    {
+        Some(issuer) => Url::parse(issuer).map_err(|e| {
+            io::Error::other(format!("invalid authorization server url {issuer}: {e}"))
+        })?,
+        None => origin(server_url),
+    };
+
+    let mut candidates = well_known_urls(&issuer, "oauth-authorization-server");
+    candidates.push(with_path(
+        &issuer,
+        &format!(
+            "{}/.well-known/openid-configuration",
+            issuer.path().trim_end_matches('/')
+        ),
+    ));
+    let metadata = match fetch_metadata::<AuthorizationServerMetadata>(client, candidates).await? {
+        Some(metadata) => metadata,
+        None => {
+            // Servers without metadata use these default endpoints.
+            let base = origin(&issuer);
+            AuthorizationServerMetadata {
+                authorization_endpoint: with_path(&base, "/authorize").to_string(),
+                token_endpoint: with_path(&base, "/token").to_string(),
+                registration_endpoint: Some(with_path(&base, "/register").to_string()),
+                scopes_supported: Vec::new(),
+                code_challenge_methods_supported: None,
+            }
+        }
+    };
+
+    if let Some(methods) = &metadata.code_challenge_methods_supported
+        && !methods.iter().any(|method| method == "S256")
+    {
+        return Err(io::Error::other(
+            "the authorization server does not support PKCE with S256",
+        ));
+    }
+
+    let scopes = match resource {
+        Some(resource) if !resource.scopes_supported.is_empty() => resource.scopes_supported,
+        _ => metadata.scopes_supported.clone(),
+    };
+    Ok((metadata, scopes))
+}
+
+/// The protected resource metadata URL the server names in the
+/// `WWW-Authenticate` header of its `401 Unauthorized` answer to an
+/// unauthenticated request (RFC 9728 section 5.1).
+async fn resource_metadata_url(
+    client: &reqwest::Client,
+    server_url: &Url,
+) -> io::Result<Option<Url>> {
+    let resp = client
+        .post(server_url.clone())
+        .header("Accept", "application/json, text/event-stream")
+        .json(&serde_json::json!({
+            "jsonrpc": "2.0",
+            "id": 0,
+            "method": "initialize",
+            "params": {
+                "protocolVersion": mcp_types::MCP_SCHEMA_VERSION,
+                "capabilities": {},
+                "clientInfo": {"name": "codex-mcp-client", "version": env!("CARGO_PKG_VERSION")},
+            },
+        }))
+        .send()
+        .await
+        .map_err(io::Error::other)?;
+    if resp.status() != reqwest::StatusCode::UNAUTHORIZED {
+        return Ok(None);
+    }
+    let Some(url) = resp
+        .headers()
+        .get_all(reqwest::header::WWW_AUTHENTICATE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find_map(|value| auth_param(value, "resource_metadata"))
+    else {
+        return Ok(None);
+    };
+    server_url
+        .join(&url)
+        .map(Some)
+        .map_err(|e| io::Error::other(format!("invalid resource_metadata url {url}: {e}")))
+}
+
+/// The value of auth-param `name` in a `WWW-Authenticate` header value, as a
+/// token or a quoted string.
+fn auth_param(header: &str, name: &str) -> Option<String> {
+    let mut rest = header;
+    while let Some(index) = rest.find('=') {
+        let key = rest[..index].rsplit([' ', ',']).next().unwrap_or_default();
+        let value = rest[index + 1..].trim_start();
+        let (parsed, remainder) = match value.strip_prefix('"') {
+            Some(quoted) => {
+                let mut parsed = String::new();
+                let mut chars = quoted.char_indices();
+                let mut end = quoted.len();
+                while let Some((i, c)) = chars.next() {
+                    match c {
+                        '\\' => {
+                            if let Some((_, escaped)) = chars.next() {
+                                parsed.push(escaped);
+                            }
+                        }
+                        '"' => {
+                            end = i + 1;
+                            break;
+                        }
+                        c => parsed.push(c),
+                    }
+                }
+                (parsed, &quoted[end..])
+            }
+            None => {
+                let end = value.find([',', ' ']).unwrap_or(value.len());
+                (value[..end].to_string(), &value[end..])
+            }
+        };
+        if key.eq_ignore_ascii_case(name) {
+            return Some(parsed);
+        }
+        rest = remainder;
+    }
+    None
+}
+
+/// The first of `urls` that serves metadata, or `None` if none does.
+async fn fetch_metadata<T: DeserializeOwned>(
+    client: &reqwest::Client,
+    urls: Vec<Url>,
+) -> io::Result<Option<T>> {
+    for url in urls {
+        let resp = client
+            .get(url.clone())
+            .header("Accept", "application/json")
+            .send()
+            .await
+            .map_err(io::Error::other)?;
+        if !resp.status().is_success() {
+            continue;
+        }
+        let metadata = resp
+            .json()
+            .await
+            .map_err(|e| io::Error::other(format!("invalid metadata at {url}: {e}")))?;
+        return Ok(Some(metadata));
+    }
+    Ok(None)
+}
+
+/// `/.well-known/<suffix>` URLs for `base`: with the path of `base` appended
+/// (RFC 8414 section 3.1, RFC 9728 section 3.1), then at the root.
+fn well_known_urls(base: &Url, suffix: &str) -> Vec<Url> {
+    let path = base.path().trim_end_matches('/');
+    let mut urls = Vec::new();
+    if !path.is_empty() {
+        urls.push(with_path(base, &format!("/.well-known/{suffix}{path}")));
+    }
+    urls.push(with_path(base, &format!("/.well-known/{suffix}")));
+    urls
+}
+
+fn origin(url: &Url) -> Url {
+    with_path(url, "/")
+}
+
+fn with_path(url: &Url, path: &str) -> Url {
+    let mut url = url.clone();
+    url.set_path(path);
+    url.set_query(None);
+    url.set_fragment(None);
+    url
+}
+
+async fn register_client(
+    client: &reqwest::Client,
+    registration_endpoint: &str,
+    redirect_uri: &str,
+) -> io::Result<ClientRegistration> {
+    let resp = client
+        .post(registration_endpoint)
+        .json(&serde_json::json!({
+            "client_name": "Codex",
+            "redirect_uris": [redirect_uri],
+            "grant_types": ["authorization_code", "refresh_token"],
+            "response_types": ["code"],
+            "token_endpoint_auth_method": "none",
+        }))
+        .send()
+        .await
+        .map_err(io::Error::other)?;
+
+    if !resp.status().is_success() {
+        return Err(io::Error::other(format!(
+            "client registration failed with status {}",
+            resp.status()
+        )));
+    }
+    resp.json().await.map_err(io::Error::other)
+}
+
+fn build_authorize_url(
+    authorization_endpoint: &str,
+    client_id: &str,
+    redirect_uri: &str,
+    pkce: &PkceCodes,
+    state: &str,
+    resource: &str,
+    scope: Option<&str>,
+) -> io::Result<String> {
+    let mut url = Url::parse(authorization_endpoint).map_err(|e| {
+        io::Error::other(format!(
+            "invalid authorization endpoint {authorization_endpoint}: {e}"
+        ))
+    })?;
+    {
+        let mut query = url.query_pairs_mut();
+        query
+            .append_pair("response_type", "code")
+            .append_pair("client_id", client_id)
+            .append_pair("redirect_uri", redirect_uri)
+            .append_pair("code_challenge", &pkce.code_challenge)
+            .append_pair("code_challenge_method", "S256")
+            .append_pair("state", state)
+            .append_pair("resource", resource);
+        if let Some(scope) = scope {
+            query.append_pair("scope", scope);
+        }
+    }
+    Ok(url.to_string())
+}
+
+/// Everything needed to finish the login when the browser is redirected back.
+struct Callback {
+    opts: McpLoginOptions,
+    client: reqwest::Client,
+    token_endpoint: String,
+    registration: ClientRegistration,
+    redirect_uri: String,
+    pkce: PkceCodes,
+    state: String,
+    scope: Option<String>,
+}
+
+impl Callback {
+    async fn handle(&self, url_raw: &str) -> HandledRequest {
+        let parsed_url = match Url::parse(&format!("http://localhost{url_raw}")) {
+            Ok(u) => u,
+            Err(e) => {
+                eprintln!("URL parse error: {e}");
+                return HandledRequest::Response(
+                    Response::from_string("Bad Request").with_status_code(400),
+                );
+            }
+        };
+
+        match parsed_url.path() {
+            CALLBACK_PATH => {
+                let params: std::collections::HashMap<String, String> =
+                    parsed_url.query_pairs().into_owned().collect();
+                if params.get("state") != Some(&self.state) {
+                    return HandledRequest::Response(
+                        Response::from_string("State mismatch").with_status_code(400),
+                    );
+                }
+                if let Some(error) = params.get("error") {
+                    let message = match params.get("error_description") {
+                        Some(description) => format!("{error}: {description}"),
+                        None => error.clone(),
+                    };
+                    return exit_with_error(format!("Authorization failed: {message}"));
+                }
+                let code = match params.get("code") {
+                    Some(c) if !c.is_empty() => c,
+                    _ => {
+                        return HandledRequest::Response(
+                            Response::from_string("Missing authorization code")
+                                .with_status_code(400),
+                        );
+                    }
+                };
+
+                match self.exchange_and_save(code).await {
+                    Ok(()) => HandledRequest::ResponseAndExit {
+                        response: Response::from_string(format!(
+                            "Signed in to MCP server '{}'. You can close this window and return to Codex.",
+                            self.opts.server_name
+                        )),
+                        result: Ok(()),
+                    },
+                    Err(err) => {
+                        eprintln!("MCP login error: {err}");
+                        exit_with_error(format!("Login failed: {err}"))
+                    }
+                }
+            }
+            "/cancel" => HandledRequest::ResponseAndExit {
+                response: Response::from_string("Login cancelled"),
+                result: Err(io::Error::new(
+                    io::ErrorKind::Interrupted,
+                    "Login cancelled",
+                )),
+            },
+            _ => HandledRequest::Response(Response::from_string("Not Found").with_status_code(404)),
+        }
+    }
+
+    async fn exchange_and_save(&self, code: &str) -> io::Result<()> {
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", self.redirect_uri.as_str()),
+            ("client_id", self.registration.client_id.as_str()),
+            ("code_verifier", self.pkce.code_verifier.as_str()),
+            ("resource", self.opts.server_url.as_str()),
+        ];
+        if let Some(client_secret) = &self.registration.client_secret {
+            form.push(("client_secret", client_secret.as_str()));
+        }
+        let response = request_tokens(&self.client, &self.token_endpoint, &form).await?;
+
+        let mut tokens = McpOAuthTokens {
+            client_id: self.registration.client_id.clone(),
+            client_secret: self.registration.client_secret.clone(),
+            token_endpoint: self.token_endpoint.clone(),
+            resource: self.opts.server_url.clone(),
+            access_token: String::new(),
+            refresh_token: None,
+            expires_at: None,
+            scope: self.scope.clone(),
+        };
+        tokens.apply_token_response(response, Utc::now());
+
+        let codex_home = self.opts.codex_home.clone();
+        let credential_store = self.opts.credential_store.clone();
+        let server_name = self.opts.server_name.clone();
+        tokio::task::spawn_blocking(move || {
+            tokens.save(&codex_home, &credential_store, &server_name)
+        })
+        .await
+        .map_err(|e| io::Error::other(format!("persist task failed: {e}")))?
+    }
+}
+
+fn exit_with_error(message: String) -> HandledRequest {
+    HandledRequest::ResponseAndExit {
+        response: Response::from_string(message.clone()).with_status_code(400),
+        result: Err(io::Error::other(message)),
+    }
+}
@@ -1,3 +1,4 @@
+use std::future::Future;
 use std::io::Cursor;
 use std::io::Read;
 use std::io::Write;
@@ -15,7 +16,8 @@ use crate::pkce::generate_pkce;
 use base64::Engine;
 use chrono::Utc;
 use codex_core::auth::AuthDotJson;
-use codex_core::auth::get_auth_file;
+use codex_core::auth::save_auth_dot_json;
+use codex_core::credential_store::CredentialStore;
 use codex_core::default_client::originator;
 use codex_core::token_data::TokenData;
 use codex_core::token_data::parse_id_token;
@@ -32,6 +34,8 @@ const DEFAULT_PORT: u16 = 1455;
 #[derive(Debug, Clone)]
 pub struct ServerOptions {
     pub codex_home: PathBuf,
+    /// Where the resulting login is stored.
+    pub credential_store: CredentialStore,
     pub client_id: String,
     pub issuer: String,
     pub port: u16,
@@ -43,6 +47,7 @@ impl ServerOptions {
     pub fn new(codex_home: PathBuf, client_id: String) -> Self {
         Self {
             codex_home,
+            credential_store: CredentialStore::default(),
             client_id,
             issuer: DEFAULT_ISSUER.to_string(),
             port: DEFAULT_PORT,
@@ -100,8 +105,6 @@ pub fn run_login_server(opts: ServerOptions) -> io::Result<LoginServer> {
             ));
         }
     };
-    let server = Arc::new(server);
-
     let redirect_uri = format!("http://localhost:{actual_port}/auth/callback");
     let auth_url = build_authorize_url(&opts.issuer, &opts.client_id, &redirect_uri, &pkce, &state);
 
@@ -109,6 +112,31 @@ pub fn run_login_server(opts: ServerOptions) -> io::Result<LoginServer> {
         let _ = webbrowser::open(&auth_url);
     }
 
+    let handler = move |url_raw: String| {
+        let opts = opts.clone();
+        let redirect_uri = redirect_uri.clone();
+        let pkce = pkce.clone();
+        let state = state.clone();
+        async move { process_request(&url_raw, &opts, &redirect_uri, &pkce, actual_port, &state).await }
+    };
+
+    Ok(serve_callbacks(server, auth_url, actual_port, handler))
+}
+
+/// Answer requests to the local callback server with `handle_request` until it
+/// returns [`HandledRequest::ResponseAndExit`] or the login is cancelled.
+pub(crate) fn serve_callbacks<H, Fut>(
+    server: Server,
+    auth_url: String,
+    actual_port: u16,
+    handle_request: H,
+) -> LoginServer
+where
+    H: Fn(String) -> Fut + Send + 'static,
+    Fut: Future<Output = HandledRequest> + Send + 'static,
+{
+    let server = Arc::new(server);
+
     // Map blocking reads from server.recv() to an async channel.
     let (tx, mut rx) = tokio::sync::mpsc::channel::<Request>(16);
     let _server_handle = {
@@ -140,8 +168,7 @@ pub fn run_login_server(opts: ServerOptions) -> io::Result<LoginServer> {
                         };
 
                         let url_raw = req.url().to_string();
-                        let response =
-                            process_request(&url_raw, &opts, &redirect_uri, &pkce, actual_port, &state).await;
+                        let response = handle_request(url_raw).await;
 
                         let exit_result = match response {
                             HandledRequest::Response(response) => {
@@ -173,15 +200,15 @@ pub fn run_login_server(opts: ServerOptions) -> io::Result<LoginServer> {
         })
     };
 
-    Ok(LoginServer {
+    LoginServer {
         auth_url,
         actual_port,
         server_handle,
         shutdown_handle: ShutdownHandle { shutdown_notify },
-    })
+    }
 }
 
-enum HandledRequest {
+pub(crate) enum HandledRequest {
     Response(Response<Cursor<Vec<u8>>>),
     RedirectWithHeader(Header),
     ResponseAndExit {
@@ -237,6 +264,7 @@ async fn process_request(
                         .ok();
                     if let Err(err) = persist_tokens_async(
                         &opts.codex_home,
+                        &opts.credential_store,
                         api_key.clone(),
                         tokens.id_token.clone(),
                         tokens.access_token.clone(),
@@ -325,7 +353,7 @@ fn build_authorize_url(
     format!("{issuer}/oauth/authorize?{qs}")
 }
 
-fn generate_state() -> String {
+pub(crate) fn generate_state() -> String {
     let mut bytes = [0u8; 32];
     rand::rng().fill_bytes(&mut bytes);
     base64::engine::general_purpose::URL_SAFE_NO_PAD.encode(bytes)
@@ -348,7 +376,7 @@ fn send_cancel_request(port: u16) -> io::Result<()> {
     Ok(())
 }
 
-fn bind_server(port: u16) -> io::Result<Server> {
+pub(crate) fn bind_server(port: u16) -> io::Result<Server> {
     let bind_address = format!("127.0.0.1:{port}");
     let mut cancel_attempted = false;
     let mut attempts = 0;
@@ -445,6 +473,7 @@ pub(crate) async fn exchange_code_for_tokens(
 
 pub(crate) async fn persist_tokens_async(
     codex_home: &Path,
+    credential_store: &CredentialStore,
     api_key: Option<String>,
     id_token: String,
     access_token: String,
@@ -452,14 +481,8 @@ pub(crate) async fn persist_tokens_async(
 ) -> io::Result<()> {
     // Reuse existing synchronous logic but run it off the async runtime.
     let codex_home = codex_home.to_path_buf();
+    let credential_store = credential_store.clone();
     tokio::task::spawn_blocking(move || {
-        let auth_file = get_auth_file(&codex_home);
-        if let Some(parent) = auth_file.parent()
-            && !parent.exists()
-        {
-            std::fs::create_dir_all(parent).map_err(io::Error::other)?;
-        }
-
         let mut tokens = TokenData {
             id_token: parse_id_token(&id_token).map_err(io::Error::other)?,
             access_token,
@@ -477,7 +500,7 @@ pub(crate) async fn persist_tokens_async(
             tokens: Some(tokens),
             last_refresh: Some(Utc::now()),
         };
-        codex_core::auth::write_auth_json(&auth_file, &auth)
+        save_auth_dot_json(&codex_home, &credential_store, &auth)
     })
     .await
     .map_err(|e| io::Error::other(format!("persist task failed: {e}")))?
@@ -7,6 +7,7 @@ use std::time::Duration;
 
 use anyhow::Result;
 use base64::Engine;
+use codex_login::CredentialStore;
 use codex_login::ServerOptions;
 use codex_login::run_login_server;
 use core_test_support::skip_if_no_network;
@@ -108,6 +109,7 @@ async fn end_to_end_login_flow_persists_auth_json() -> Result<()> {
 
     let opts = ServerOptions {
         codex_home: server_home,
+        credential_store: CredentialStore::default(),
         client_id: codex_login::CLIENT_ID.to_string(),
         issuer,
         port: 0,
@@ -161,6 +163,7 @@ async fn creates_missing_codex_home_dir() -> Result<()> {
     let server_home = codex_home.clone();
     let opts = ServerOptions {
         codex_home: server_home,
+        credential_store: CredentialStore::default(),
         client_id: codex_login::CLIENT_ID.to_string(),
         issuer,
         port: 0,
@@ -197,6 +200,7 @@ async fn cancels_previous_login_server_when_port_is_in_use() -> Result<()> {
 
     let first_opts = ServerOptions {
         codex_home: first_codex_home,
+        credential_store: CredentialStore::default(),
         client_id: codex_login::CLIENT_ID.to_string(),
         issuer: issuer.clone(),
         port: 0,
@@ -215,6 +219,7 @@ async fn cancels_previous_login_server_when_port_is_in_use() -> Result<()> {
 
     let second_opts = ServerOptions {
         codex_home: second_codex_home,
+        credential_store: CredentialStore::default(),
         client_id: codex_login::CLIENT_ID.to_string(),
         issuer,
         port: login_port,
@@ -0,0 +1,244 @@
+#![allow(clippy::unwrap_used)]
+
+use std::collections::HashMap;
+
+use anyhow::Result;
+use codex_core::mcp_oauth::McpOAuthTokens;
+use codex_login::CredentialStore;
+use codex_login::McpLoginOptions;
+use codex_login::run_mcp_login_server;
+use core_test_support::skip_if_no_network;
+use pretty_assertions::assert_eq;
+use serde_json::json;
+use tempfile::tempdir;
+use url::Url;
+use wiremock::Mock;
+use wiremock::MockServer;
+use wiremock::ResponseTemplate;
+use wiremock::matchers::body_string_contains;
+use wiremock::matchers::method;
+use wiremock::matchers::path;
+
+/// Serve protected resource metadata for `/mcp` pointing at an authorization
+/// server under `/auth` on the same mock, with dynamic client registration.
+async fn mount_authorization_server(server: &MockServer) {
+    let uri = server.uri();
+    Mock::given(method("GET"))
+        .and(path("/.well-known/oauth-protected-resource/mcp"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "resource": format!("{uri}/mcp"),
+            "authorization_servers": [format!("{uri}/auth")],
+            "scopes_supported": ["mcp.read", "mcp.write"],
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/.well-known/oauth-authorization-server/auth"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "issuer": format!("{uri}/auth"),
+            "authorization_endpoint": format!("{uri}/auth/authorize"),
+            "token_endpoint": format!("{uri}/auth/token"),
+            "registration_endpoint": format!("{uri}/auth/register"),
+            "code_challenge_methods_supported": ["S256"],
+        })))
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/auth/register"))
+        .and(body_string_contains(
+            "\"token_endpoint_auth_method\":\"none\"",
+        ))
+        .respond_with(ResponseTemplate::new(201).set_body_json(json!({
+            "client_id": "client-abc",
+        })))
+        .expect(1)
+        .mount(server)
+        .await;
+    Mock::given(method("POST"))
+        .and(path("/auth/token"))
+        .and(body_string_contains("grant_type=authorization_code"))
+        .and(body_string_contains("code=code-123"))
+        .and(body_string_contains("client_id=client-abc"))
+        .and(body_string_contains("code_verifier="))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "access_token": "mcp-access",
+            "token_type": "Bearer",
+            "expires_in": 3600,
+            "refresh_token": "mcp-refresh",
+        })))
+        .expect(1)
+        .mount(server)
+        .await;
+}
+
+fn query(url: &str) -> HashMap<String, String> {
+    Url::parse(url)
+        .unwrap()
+        .query_pairs()
+        .into_owned()
+        .collect()
+}
+
+#[tokio::test]
+async fn mcp_login_registers_client_and_stores_tokens() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    let server = MockServer::start().await;
+    mount_authorization_server(&server).await;
+    let uri = server.uri();
+    let codex_home = tempdir()?;
+
+    let mut opts = McpLoginOptions::new(
+        codex_home.path().to_path_buf(),
+        "docs".to_string(),
+        format!("{uri}/mcp"),
+    );
+    opts.open_browser = false;
+    let login = run_mcp_login_server(opts).await?;
+
+    assert!(
+        login
+            .auth_url
+            .starts_with(&format!("{uri}/auth/authorize?"))
+    );
+    let params = query(&login.auth_url);
+    assert_eq!(params["client_id"], "client-abc");
+    assert_eq!(params["code_challenge_method"], "S256");
+    assert_eq!(params["resource"], format!("{uri}/mcp"));
+    assert_eq!(params["scope"], "mcp.read mcp.write");
+    assert_eq!(
+        params["redirect_uri"],
+        format!("http://localhost:{}/callback", login.actual_port)
+    );
+
+    // Simulate the browser being redirected back.
+    let callback = format!("http://127.0.0.1:{}/callback", login.actual_port);
+    let client = reqwest::Client::new();
+    let resp = client
+        .get(format!("{callback}?code=code-123&state=wrong-state"))
+        .send()
+        .await?;
+    assert_eq!(resp.status(), 400);
+    let resp = client
+        .get(format!(
+            "{callback}?code=code-123&state={}",
+            params["state"]
+        ))
+        .send()
+        .await?;
+    assert!(resp.status().is_success());
+    login.block_until_done().await?;
+
+    let tokens = McpOAuthTokens::load(codex_home.path(), &CredentialStore::File, "docs")?
+        .expect("tokens stored");
+    assert_eq!(tokens.client_id, "client-abc");
+    assert_eq!(tokens.token_endpoint, format!("{uri}/auth/token"));
+    assert_eq!(tokens.resource, format!("{uri}/mcp"));
+    assert_eq!(tokens.access_token, "mcp-access");
+    assert_eq!(tokens.refresh_token.as_deref(), Some("mcp-refresh"));
+    assert_eq!(tokens.scope.as_deref(), Some("mcp.read mcp.write"));
+    assert!(tokens.expires_at.is_some());
+    Ok(())
+}
+
+#[tokio::test]
+async fn mcp_login_falls_back_to_default_endpoints_and_reports_denial() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    // No metadata at all: the default endpoints on the server's origin are used.
+    let server = MockServer::start().await;
+    let uri = server.uri();
+    let codex_home = tempdir()?;
+
+    let mut opts = McpLoginOptions::new(
+        codex_home.path().to_path_buf(),
+        "docs".to_string(),
+        format!("{uri}/mcp"),
+    );
+    opts.open_browser = false;
+    opts.client_id = Some("preregistered".to_string());
+    let login = run_mcp_login_server(opts).await?;
+
+    assert!(login.auth_url.starts_with(&format!("{uri}/authorize?")));
+    let params = query(&login.auth_url);
+    assert_eq!(params["client_id"], "preregistered");
+    assert!(!params.contains_key("scope"));
+
+    let resp = reqwest::Client::new()
+        .get(format!(
+            "http://127.0.0.1:{}/callback?error=access_denied&state={}",
+            login.actual_port, params["state"]
+        ))
+        .send()
+        .await?;
+    assert_eq!(resp.status(), 400);
+
+    let err = login.block_until_done().await.unwrap_err();
+    assert!(err.to_string().contains("access_denied"), "{err}");
+    assert_eq!(
+        McpOAuthTokens::load(codex_home.path(), &CredentialStore::File, "docs")?,
+        None
+    );
+    Ok(())
+}
+
+#[tokio::test]
+async fn mcp_login_follows_resource_metadata_from_www_authenticate() -> Result<()> {
+    skip_if_no_network!(Ok(()));
+
+    // The metadata is only served where the 401 points, not at the
+    // well-known URL.
+    let server = MockServer::start().await;
+    let uri = server.uri();
+    Mock::given(method("POST"))
+        .and(path("/mcp"))
+        .respond_with(ResponseTemplate::new(401).insert_header(
+            "WWW-Authenticate",
+            format!(
+                "Bearer realm=\"mcp\", resource_metadata=\"{uri}/metadata/docs\", error=\"invalid_token\""
+            )
+            .as_str(),
+        ))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/metadata/docs"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "resource": format!("{uri}/mcp"),
+            "authorization_servers": [format!("{uri}/issuer")],
+            "scopes_supported": ["docs.read"],
+        })))
+        .expect(1)
+        .mount(&server)
+        .await;
+    Mock::given(method("GET"))
+        .and(path("/.well-known/oauth-authorization-server/issuer"))
+        .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+            "issuer": format!("{uri}/issuer"),
+            "authorization_endpoint": format!("{uri}/issuer/authorize"),
+            "token_endpoint": format!("{uri}/issuer/token"),
+        })))
+        .mount(&server)
+        .await;
+    let codex_home = tempdir()?;
+
+    let mut opts = McpLoginOptions::new(
+        codex_home.path().to_path_buf(),
+        "docs".to_string(),
+        format!("{uri}/mcp"),
+    );
+    opts.open_browser = false;
+    opts.client_id = Some("preregistered".to_string());
+    let login = run_mcp_login_server(opts).await?;
+
+    assert!(
+        login
+            .auth_url
+            .starts_with(&format!("{uri}/issuer/authorize?"))
+    );
+    let params = query(&login.auth_url);
+    assert_eq!(params["scope"], "docs.read");
+    login.cancel();
+    Ok(())
+}
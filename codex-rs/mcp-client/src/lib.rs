@@ -1,3 +1,7 @@
 mod mcp_client;
+pub mod shared;
 
 pub use mcp_client::McpClient;
+pub use shared::ServerRequestFuture;
+pub use shared::ServerRequestHandler;
+pub use shared::ToolCallNotificationHandler;
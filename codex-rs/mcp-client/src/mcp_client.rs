@@ -6,6 +6,11 @@
 //!   2. Sending MCP requests and pairing them with their corresponding
 //!      responses.
 //!   3. Offering a convenience helper for the common `tools/list` request.
+//!   4. Answering requests the server sends to the client through a
+//!      [`ServerRequestHandler`].
+//!   5. Reporting `notifications/progress` and `notifications/message` to the
+//!      caller of a tool call, and sending `notifications/cancelled` for
+//!      requests the caller stops waiting for.
 //!
 //! The crate hides all JSON‐RPC framing details behind a typed API. Users
 //! interact with the [`ModelContextProtocolRequest`] trait from `mcp-types` to
@@ -14,6 +19,11 @@
 use std::collections::HashMap;
 use std::ffi::OsString;
 use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::OnceLock;
+use std::sync::PoisonError;
+use std::sync::atomic::AtomicBool;
 use std::sync::atomic::AtomicI64;
 use std::sync::atomic::Ordering;
 use std::time::Duration;
@@ -23,10 +33,14 @@ use anyhow::Result;
 use anyhow::anyhow;
 use mcp_types::CallToolRequest;
 use mcp_types::CallToolRequestParams;
+use mcp_types::CancelledNotification;
+use mcp_types::CancelledNotificationParams;
 use mcp_types::InitializeRequest;
 use mcp_types::InitializeRequestParams;
 use mcp_types::InitializedNotification;
 use mcp_types::JSONRPC_VERSION;
+use mcp_types::JSONRPCError;
+use mcp_types::JSONRPCErrorError;
 use mcp_types::JSONRPCMessage;
 use mcp_types::JSONRPCNotification;
 use mcp_types::JSONRPCRequest;
@@ -34,16 +48,23 @@ use mcp_types::JSONRPCResponse;
 use mcp_types::ListToolsRequest;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
+use mcp_types::LoggingMessageNotification;
 use mcp_types::ModelContextProtocolNotification;
 use mcp_types::ModelContextProtocolRequest;
+use mcp_types::PingRequest;
+use mcp_types::ProgressNotification;
+use mcp_types::ProgressToken;
 use mcp_types::RequestId;
+use mcp_types::RootsListChangedNotification;
+use mcp_types::ServerNotification;
+use mcp_types::ToolListChangedNotification;
 use serde::Serialize;
 use serde::de::DeserializeOwned;
+use serde_json::json;
 use tokio::io::AsyncBufReadExt;
 use tokio::io::AsyncWriteExt;
 use tokio::io::BufReader;
 use tokio::process::Command;
-use tokio::sync::Mutex;
 use tokio::sync::mpsc;
 use tokio::sync::oneshot;
 use tokio::time;
@@ -52,13 +73,32 @@ use tracing::error;
 use tracing::info;
 use tracing::warn;
 
+use crate::shared::InFlightRequest;
+use crate::shared::ServerRequestHandler;
+use crate::shared::ToolCallHandlers;
+use crate::shared::ToolCallNotificationHandler;
+use crate::shared::ToolCallRegistration;
+use crate::shared::dispatch_tool_call_notification;
+
 /// Capacity of the bounded channels used for transporting messages between the
 /// client API and the IO tasks.
 const CHANNEL_CAPACITY: usize = 128;
 
+/// JSON-RPC error code for requests whose method the client does not
+/// implement.
+const METHOD_NOT_FOUND_ERROR_CODE: i64 = -32601;
+
 /// Internal representation of a pending request sender.
 type PendingSender = oneshot::Sender<JSONRPCMessage>;
 
+/// Requests awaiting their reply, keyed by request id. A std mutex, so that
+/// a request dropped mid-flight can always remove itself.
+type PendingRequests = Arc<Mutex<HashMap<i64, PendingSender>>>;
+
+fn lock_pending(pending: &PendingRequests) -> MutexGuard<'_, HashMap<i64, PendingSender>> {
+    pending.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
 /// A running MCP client instance.
 pub struct McpClient {
     /// Retain this child process until the client is dropped. The Tokio runtime
@@ -72,10 +112,24 @@ pub struct McpClient {
 
     /// Map of `request.id -> oneshot::Sender` used to dispatch responses back
     /// to the originating caller.
-    pending: Arc<Mutex<HashMap<i64, PendingSender>>>,
+    pending: PendingRequests,
 
     /// Monotonically increasing counter used to generate request IDs.
     id_counter: AtomicI64,
+
+    /// Set once the server's STDOUT is closed, which usually means the
+    /// process has exited.
+    closed: Arc<AtomicBool>,
+
+    /// Set when the server sends `notifications/tools/list_changed`.
+    tools_list_changed: Arc<AtomicBool>,
+
+    /// Answers requests from the server other than `ping`. Requests that
+    /// arrive while it is unset are rejected as unsupported.
+    server_request_handler: Arc<OnceLock<ServerRequestHandler>>,
+
+    /// Notification handlers of the tool calls currently in flight.
+    tool_call_handlers: ToolCallHandlers,
 }
 
 impl McpClient {
@@ -110,7 +164,11 @@ impl McpClient {
             .ok_or_else(|| std::io::Error::other("failed to capture child stdout"))?;
 
         let (outgoing_tx, mut outgoing_rx) = mpsc::channel::<JSONRPCMessage>(CHANNEL_CAPACITY);
-        let pending: Arc<Mutex<HashMap<i64, PendingSender>>> = Arc::new(Mutex::new(HashMap::new()));
+        let pending: PendingRequests = Arc::new(Mutex::new(HashMap::new()));
+        let closed = Arc::new(AtomicBool::new(false));
+        let tools_list_changed = Arc::new(AtomicBool::new(false));
+        let server_request_handler: Arc<OnceLock<ServerRequestHandler>> = Arc::new(OnceLock::new());
+        let tool_call_handlers: ToolCallHandlers = Arc::new(Mutex::new(HashMap::new()));
 
         // Spawn writer task. It listens on the `outgoing_rx` channel and
         // writes messages to the child's STDIN.
@@ -141,6 +199,11 @@ impl McpClient {
         // STDOUT and dispatches responses to the pending map.
         let reader_handle = {
             let pending = pending.clone();
+            let closed = closed.clone();
+            let tools_list_changed = tools_list_changed.clone();
+            let server_request_handler = server_request_handler.clone();
+            let tool_call_handlers = tool_call_handlers.clone();
+            let outgoing_tx = outgoing_tx.clone();
             let mut lines = BufReader::new(stdout).lines();
 
             tokio::spawn(async move {
@@ -148,25 +211,38 @@ impl McpClient {
                     debug!("MCP message from server: {line}");
                     match serde_json::from_str::<JSONRPCMessage>(&line) {
                         Ok(JSONRPCMessage::Response(resp)) => {
-                            Self::dispatch_response(resp, &pending).await;
+                            Self::dispatch_response(resp, &pending);
                         }
                         Ok(JSONRPCMessage::Error(err)) => {
-                            Self::dispatch_error(err, &pending).await;
+                            Self::dispatch_error(err, &pending);
                         }
-                        Ok(JSONRPCMessage::Notification(JSONRPCNotification { .. })) => {
-                            // For now we only log server-initiated notifications.
+                        Ok(JSONRPCMessage::Notification(notification)) => {
+                            if notification.method == ToolListChangedNotification::METHOD {
+                                tools_list_changed.store(true, Ordering::SeqCst);
+                            }
+                            Self::dispatch_tool_call_notification(
+                                notification,
+                                &tool_call_handlers,
+                            );
                             info!("<- notification: {}", line);
                         }
-                        Ok(other) => {
-                            // Batch responses and requests are currently not
-                            // expected from the server – log and ignore.
-                            info!("<- unhandled message: {:?}", other);
+                        Ok(JSONRPCMessage::Request(request)) => {
+                            Self::dispatch_server_request(
+                                request,
+                                server_request_handler.get().cloned(),
+                                outgoing_tx.clone(),
+                            );
                         }
                         Err(e) => {
                             error!("failed to deserialize JSONRPCMessage: {e}; line = {}", line)
                         }
                     }
                 }
+
+                // The server is gone: fail any requests still waiting for a
+                // reply instead of letting them run into their timeout.
+                closed.store(true, Ordering::SeqCst);
+                lock_pending(&pending).clear();
             })
         };
 
@@ -181,6 +257,10 @@ impl McpClient {
             outgoing_tx,
             pending,
             id_counter: AtomicI64::new(1),
+            closed,
+            tools_list_changed,
+            server_request_handler,
+            tool_call_handlers,
         })
     }
 
@@ -188,7 +268,9 @@ impl McpClient {
     ///
     /// If `timeout` is `None` the call waits indefinitely. If `Some(duration)`
     /// is supplied and no response is received within the given period, a
-    /// timeout error is returned.
+    /// timeout error is returned. Unless the request is `initialize`, the
+    /// server is sent `notifications/cancelled` if the request times out or
+    /// the returned future is dropped before a reply arrives.
     pub async fn send_request<R>(
         &self,
         params: R::Params,
@@ -199,10 +281,6 @@ impl McpClient {
         R::Params: Serialize,
         R::Result: DeserializeOwned,
     {
-        // Create a new unique ID.
-        let id = self.id_counter.fetch_add(1, Ordering::SeqCst);
-        let request_id = RequestId::Integer(id);
-
         // Serialize params -> JSON. For many request types `Params` is
         // `Option<T>` and `None` should be encoded as *absence* of the field.
         let params_json = serde_json::to_value(&params)?;
@@ -211,12 +289,29 @@ impl McpClient {
         } else {
             Some(params_json)
         };
+        let id = self.id_counter.fetch_add(1, Ordering::SeqCst);
+        let result = self
+            .send_request_json(id, R::METHOD, params_field, timeout)
+            .await?;
+        let typed: R::Result = serde_json::from_value(result)?;
+        Ok(typed)
+    }
 
+    /// Send a request with the given `id` and raw JSON params and await the
+    /// raw JSON result.
+    async fn send_request_json(
+        &self,
+        id: i64,
+        method: &str,
+        params: Option<serde_json::Value>,
+        timeout: Option<Duration>,
+    ) -> Result<serde_json::Value> {
+        let request_id = RequestId::Integer(id);
         let jsonrpc_request = JSONRPCRequest {
             id: request_id.clone(),
             jsonrpc: JSONRPC_VERSION.to_string(),
-            method: R::METHOD.to_string(),
-            params: params_field,
+            method: method.to_string(),
+            params,
         };
 
         let message = JSONRPCMessage::Request(jsonrpc_request);
@@ -226,9 +321,13 @@ impl McpClient {
 
         // Register in pending map *before* sending the message so a race where
         // the response arrives immediately cannot be lost.
-        {
-            let mut guard = self.pending.lock().await;
-            guard.insert(id, tx);
+        lock_pending(&self.pending).insert(id, tx);
+
+        // The reader task clears `pending` when the server goes away, so a
+        // request registered after that would never be answered.
+        if self.is_closed() {
+            lock_pending(&self.pending).remove(&id);
+            return Err(anyhow!("connection to MCP server closed"));
         }
 
         // Send to writer task.
@@ -238,37 +337,42 @@ impl McpClient {
             ));
         }
 
+        // Until the reply arrives, dropping this forgets the request and tells
+        // the server to stop working on it. The spec forbids cancelling
+        // `initialize`.
+        let mut in_flight = (method != InitializeRequest::METHOD).then(|| {
+            let pending = Arc::clone(&self.pending);
+            let outgoing_tx = self.outgoing_tx.clone();
+            InFlightRequest::new(move |reason| cancel_request(id, &pending, &outgoing_tx, reason))
+        });
+
         // Await the response, optionally bounded by a timeout.
-        let msg = match timeout {
-            Some(duration) => {
-                match time::timeout(duration, rx).await {
-                    Ok(Ok(msg)) => msg,
-                    Ok(Err(_)) => {
-                        // Channel closed without a reply – remove the pending entry.
-                        let mut guard = self.pending.lock().await;
-                        guard.remove(&id);
-                        return Err(anyhow!(
-                            "response channel closed before a reply was received"
-                        ));
-                    }
-                    Err(_) => {
-                        // Timed out. Remove the pending entry so we don't leak.
-                        let mut guard = self.pending.lock().await;
-                        guard.remove(&id);
-                        return Err(anyhow!("request timed out"));
+        let received = match timeout {
+            Some(duration) => match time::timeout(duration, rx).await {
+                Ok(received) => received,
+                Err(_) => {
+                    if let Some(in_flight) = in_flight.as_mut() {
+                        in_flight.set_reason("request timed out");
                     }
+                    lock_pending(&self.pending).remove(&id);
+                    return Err(anyhow!("request timed out"));
                 }
-            }
-            None => rx
-                .await
-                .map_err(|_| anyhow!("response channel closed before a reply was received"))?,
+            },
+            None => rx.await,
+        };
+        if let Some(in_flight) = in_flight.as_mut() {
+            in_flight.finish();
+        }
+        let Ok(msg) = received else {
+            // Channel closed without a reply – remove the pending entry.
+            lock_pending(&self.pending).remove(&id);
+            return Err(anyhow!(
+                "response channel closed before a reply was received"
+            ));
         };
 
         match msg {
-            JSONRPCMessage::Response(JSONRPCResponse { result, .. }) => {
-                let typed: R::Result = serde_json::from_value(result)?;
-                Ok(typed)
-            }
+            JSONRPCMessage::Response(JSONRPCResponse { result, .. }) => Ok(result),
             JSONRPCMessage::Error(err) => Err(anyhow!(format!(
                 "server returned JSON-RPC error: code = {}, message = {}",
                 err.error.code, err.error.message
@@ -307,6 +411,15 @@ impl McpClient {
             .with_context(|| format!("failed to send notification `{method}` to writer task"))
     }
 
+    /// Install the handler that answers requests from the server. Must be
+    /// called before [`initialize`](Self::initialize) so that requests sent
+    /// right after the handshake are not rejected; later calls are ignored.
+    pub fn set_server_request_handler(&self, handler: ServerRequestHandler) {
+        if self.server_request_handler.set(handler).is_err() {
+            warn!("server request handler already set; ignoring");
+        }
+    }
+
     /// Negotiates the initialization with the MCP server. Sends an `initialize`
     /// request with the specified `initialize_params` and then the
     /// `notifications/initialized` notification once the response has been
@@ -334,22 +447,123 @@ impl McpClient {
     }
 
     /// Convenience wrapper around `tools/call`.
+    ///
+    /// With `on_notification`, the request carries a progress token and the
+    /// handler receives the server's progress and log notifications until the
+    /// call returns.
     pub async fn call_tool(
         &self,
         name: String,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+        on_notification: Option<ToolCallNotificationHandler>,
     ) -> Result<mcp_types::CallToolResult> {
         let params = CallToolRequestParams { name, arguments };
         debug!("MCP tool call: {params:?}");
-        self.send_request::<CallToolRequest>(params, timeout).await
+        let Some(on_notification) = on_notification else {
+            return self.send_request::<CallToolRequest>(params, timeout).await;
+        };
+
+        // Use the request id as the progress token; both are unique per client.
+        let id = self.id_counter.fetch_add(1, Ordering::SeqCst);
+        let progress_token = ProgressToken::Integer(id);
+        let mut params_json = serde_json::to_value(&params)?;
+        if let Some(obj) = params_json.as_object_mut() {
+            obj.insert("_meta".to_string(), json!({ "progressToken": id }));
+        }
+        let _registration =
+            ToolCallRegistration::new(&self.tool_call_handlers, progress_token, on_notification);
+        let result = self
+            .send_request_json(id, CallToolRequest::METHOD, Some(params_json), timeout)
+            .await?;
+        Ok(serde_json::from_value(result)?)
     }
 
-    /// Internal helper: route a JSON-RPC *response* object to the pending map.
-    async fn dispatch_response(
-        resp: JSONRPCResponse,
-        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
+    /// Whether the connection to the server has been lost, e.g. because the
+    /// server process exited.
+    pub fn is_closed(&self) -> bool {
+        self.closed.load(Ordering::SeqCst)
+    }
+
+    /// Whether the server reported a change to its tools since the last call.
+    pub fn take_tools_list_changed(&self) -> bool {
+        self.tools_list_changed.swap(false, Ordering::SeqCst)
+    }
+
+    /// Tell the server that the roots returned by `roots/list` changed.
+    pub async fn notify_roots_list_changed(&self) -> Result<()> {
+        self.send_notification::<RootsListChangedNotification>(None)
+            .await
+    }
+
+    /// Internal helper: pass progress and log notifications to the handlers
+    /// of the tool calls in flight.
+    fn dispatch_tool_call_notification(
+        notification: JSONRPCNotification,
+        handlers: &ToolCallHandlers,
     ) {
+        let JSONRPCNotification { method, params, .. } = notification;
+        let is_tool_call_notification =
+            method == ProgressNotification::METHOD || method == LoggingMessageNotification::METHOD;
+        if !is_tool_call_notification {
+            return;
+        }
+        let notification = match serde_json::from_value::<ServerNotification>(json!({
+            "method": method,
+            "params": params,
+        })) {
+            Ok(notification) => notification,
+            Err(e) => {
+                warn!("failed to parse `{method}` notification: {e}");
+                return;
+            }
+        };
+        dispatch_tool_call_notification(handlers, notification);
+    }
+
+    /// Internal helper: answer a request from the server on a separate task
+    /// so that a slow handler (e.g. one waiting for the user) does not block
+    /// the reader.
+    fn dispatch_server_request(
+        request: JSONRPCRequest,
+        handler: Option<ServerRequestHandler>,
+        outgoing_tx: mpsc::Sender<JSONRPCMessage>,
+    ) {
+        let JSONRPCRequest {
+            id, method, params, ..
+        } = request;
+        tokio::spawn(async move {
+            let result = if method == PingRequest::METHOD {
+                Ok(json!({}))
+            } else if let Some(handler) = handler {
+                handler(method, params).await
+            } else {
+                Err(JSONRPCErrorError {
+                    code: METHOD_NOT_FOUND_ERROR_CODE,
+                    data: None,
+                    message: format!("method not supported: {method}"),
+                })
+            };
+            let message = match result {
+                Ok(result) => JSONRPCMessage::Response(JSONRPCResponse {
+                    id,
+                    jsonrpc: JSONRPC_VERSION.to_string(),
+                    result,
+                }),
+                Err(error) => JSONRPCMessage::Error(JSONRPCError {
+                    error,
+                    id,
+                    jsonrpc: JSONRPC_VERSION.to_string(),
+                }),
+            };
+            if outgoing_tx.send(message).await.is_err() {
+                warn!("failed to answer server request - writer channel closed");
+            }
+        });
+    }
+
+    /// Internal helper: route a JSON-RPC *response* object to the pending map.
+    fn dispatch_response(resp: JSONRPCResponse, pending: &PendingRequests) {
         let id = match resp.id {
             RequestId::Integer(i) => i,
             RequestId::String(_) => {
@@ -360,10 +574,7 @@ impl McpClient {
             }
         };
 
-        let tx_opt = {
-            let mut guard = pending.lock().await;
-            guard.remove(&id)
-        };
+        let tx_opt = lock_pending(pending).remove(&id);
         if let Some(tx) = tx_opt {
             // Ignore send errors – the receiver might have been dropped.
             let _ = tx.send(JSONRPCMessage::Response(resp));
@@ -373,25 +584,51 @@ impl McpClient {
     }
 
     /// Internal helper: route a JSON-RPC *error* object to the pending map.
-    async fn dispatch_error(
-        err: mcp_types::JSONRPCError,
-        pending: &Arc<Mutex<HashMap<i64, PendingSender>>>,
-    ) {
+    fn dispatch_error(err: mcp_types::JSONRPCError, pending: &PendingRequests) {
         let id = match err.id {
             RequestId::Integer(i) => i,
             RequestId::String(_) => return, // see comment above
         };
 
-        let tx_opt = {
-            let mut guard = pending.lock().await;
-            guard.remove(&id)
-        };
+        let tx_opt = lock_pending(pending).remove(&id);
         if let Some(tx) = tx_opt {
             let _ = tx.send(JSONRPCMessage::Error(err));
         }
     }
 }
 
+/// Forget request `id`, which the caller stopped waiting for, and tell the
+/// server to stop working on it.
+fn cancel_request(
+    id: i64,
+    pending: &PendingRequests,
+    outgoing_tx: &mpsc::Sender<JSONRPCMessage>,
+    reason: &'static str,
+) {
+    lock_pending(pending).remove(&id);
+    let params = CancelledNotificationParams {
+        reason: Some(reason.to_string()),
+        request_id: RequestId::Integer(id),
+    };
+    let notification = match serde_json::to_value(params) {
+        Ok(params) => JSONRPCMessage::Notification(JSONRPCNotification {
+            jsonrpc: JSONRPC_VERSION.to_string(),
+            method: CancelledNotification::METHOD.to_string(),
+            params: Some(params),
+        }),
+        Err(e) => {
+            error!("failed to serialize cancellation: {e}");
+            return;
+        }
+    };
+    if outgoing_tx.try_send(notification).is_err() {
+        warn!(
+            id,
+            "failed to send notifications/cancelled - writer channel full or closed"
+        );
+    }
+}
+
 impl Drop for McpClient {
     fn drop(&mut self) {
         // Even though we have already tagged this process with
@@ -0,0 +1,207 @@
+//! Plumbing shared with `codex-rmcp-client`: answering requests from the
+//! server, routing the notifications of tool calls in flight, and cancelling
+//! requests the caller stopped waiting for. Everything that depends on the
+//! transport stays with each client.
+
+use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::PoisonError;
+
+use mcp_types::JSONRPCErrorError;
+use mcp_types::ProgressToken;
+use mcp_types::ServerNotification;
+
+/// Future returned by a [`ServerRequestHandler`].
+pub type ServerRequestFuture =
+    Pin<Box<dyn Future<Output = Result<serde_json::Value, JSONRPCErrorError>> + Send>>;
+
+/// Answers requests the server sends to the client, such as `roots/list`,
+/// `sampling/createMessage` or `elicitation/create`. Called with the method
+/// and params of the request; resolves to its result or a JSON-RPC error.
+pub type ServerRequestHandler =
+    Arc<dyn Fn(String, Option<serde_json::Value>) -> ServerRequestFuture + Send + Sync>;
+
+/// Receives the notifications that concern one tool call while it runs:
+/// `notifications/progress` for the call's progress token, and every
+/// `notifications/message` the server sends in the meantime.
+pub type ToolCallNotificationHandler = Arc<dyn Fn(ServerNotification) + Send + Sync>;
+
+/// Handlers of the tool calls in flight, keyed by their progress token.
+pub type ToolCallHandlers = Arc<Mutex<HashMap<ProgressToken, ToolCallNotificationHandler>>>;
+
+/// Pass `notification` to the tool calls it concerns: the call with its
+/// progress token for `notifications/progress`, every call otherwise.
+pub fn dispatch_tool_call_notification(
+    handlers: &ToolCallHandlers,
+    notification: ServerNotification,
+) {
+    let handlers: Vec<ToolCallNotificationHandler> = {
+        let handlers = handlers.lock().unwrap_or_else(PoisonError::into_inner);
+        match &notification {
+            ServerNotification::ProgressNotification(progress) => handlers
+                .get(&progress.progress_token)
+                .cloned()
+                .into_iter()
+                .collect(),
+            _ => handlers.values().cloned().collect(),
+        }
+    };
+    for handler in handlers {
+        handler(notification.clone());
+    }
+}
+
+/// Keeps a tool call's notification handler registered while the call runs.
+pub struct ToolCallRegistration<'a> {
+    handlers: &'a ToolCallHandlers,
+    token: ProgressToken,
+}
+
+impl<'a> ToolCallRegistration<'a> {
+    pub fn new(
+        handlers: &'a ToolCallHandlers,
+        token: ProgressToken,
+        handler: ToolCallNotificationHandler,
+    ) -> Self {
+        handlers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .insert(token.clone(), handler);
+        Self { handlers, token }
+    }
+}
+
+impl Drop for ToolCallRegistration<'_> {
+    fn drop(&mut self) {
+        self.handlers
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .remove(&self.token);
+    }
+}
+
+/// A request awaiting its reply. If it is dropped before it is
+/// [`finish`](Self::finish)ed, e.g. because it timed out or the caller's task
+/// was aborted, `cancel` is called with the reason so that the transport can
+/// forget the request and send `notifications/cancelled`.
+pub struct InFlightRequest<F: FnOnce(&'static str)> {
+    cancel: Option<F>,
+    reason: &'static str,
+}
+
+impl<F: FnOnce(&'static str)> InFlightRequest<F> {
+    pub fn new(cancel: F) -> Self {
+        Self {
+            cancel: Some(cancel),
+            reason: "request cancelled by the client",
+        }
+    }
+
+    /// Report `reason` instead of a cancellation by the client.
+    pub fn set_reason(&mut self, reason: &'static str) {
+        self.reason = reason;
+    }
+
+    /// The reply arrived, so there is nothing left to cancel.
+    pub fn finish(&mut self) {
+        self.cancel = None;
+    }
+}
+
+impl<F: FnOnce(&'static str)> Drop for InFlightRequest<F> {
+    fn drop(&mut self) {
+        if let Some(cancel) = self.cancel.take() {
+            cancel(self.reason);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::atomic::Ordering;
+
+    use mcp_types::LoggingLevel;
+    use mcp_types::LoggingMessageNotificationParams;
+    use mcp_types::ProgressNotificationParams;
+
+    fn counting_handler(count: &Arc<AtomicUsize>) -> ToolCallNotificationHandler {
+        let count = Arc::clone(count);
+        Arc::new(move |_| {
+            count.fetch_add(1, Ordering::SeqCst);
+        })
+    }
+
+    #[test]
+    fn progress_goes_to_its_call_and_log_messages_to_every_call() {
+        let handlers: ToolCallHandlers = Arc::default();
+        let first = Arc::new(AtomicUsize::new(0));
+        let second = Arc::new(AtomicUsize::new(0));
+        let _first = ToolCallRegistration::new(
+            &handlers,
+            ProgressToken::Integer(1),
+            counting_handler(&first),
+        );
+        let second_registration = ToolCallRegistration::new(
+            &handlers,
+            ProgressToken::Integer(2),
+            counting_handler(&second),
+        );
+
+        dispatch_tool_call_notification(
+            &handlers,
+            ServerNotification::ProgressNotification(ProgressNotificationParams {
+                message: None,
+                progress: 1.0,
+                progress_token: ProgressToken::Integer(1),
+                total: None,
+            }),
+        );
+        dispatch_tool_call_notification(
+            &handlers,
+            ServerNotification::LoggingMessageNotification(LoggingMessageNotificationParams {
+                data: serde_json::json!("working"),
+                level: LoggingLevel::Info,
+                logger: None,
+            }),
+        );
+        assert_eq!(first.load(Ordering::SeqCst), 2);
+        assert_eq!(second.load(Ordering::SeqCst), 1);
+
+        drop(second_registration);
+        assert!(
+            !handlers
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .contains_key(&ProgressToken::Integer(2))
+        );
+    }
+
+    #[test]
+    fn in_flight_request_is_cancelled_only_if_unfinished() {
+        let reasons = Mutex::new(Vec::new());
+        let record = |reason| {
+            reasons
+                .lock()
+                .unwrap_or_else(PoisonError::into_inner)
+                .push(reason)
+        };
+
+        drop(InFlightRequest::new(record));
+        let mut timed_out = InFlightRequest::new(record);
+        timed_out.set_reason("request timed out");
+        drop(timed_out);
+        let mut answered = InFlightRequest::new(record);
+        answered.finish();
+        drop(answered);
+
+        assert_eq!(
+            *reasons.lock().unwrap_or_else(PoisonError::into_inner),
+            vec!["request cancelled by the client", "request timed out"]
+        );
+    }
+}
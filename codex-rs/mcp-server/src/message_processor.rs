@@ -52,7 +52,8 @@ impl MessageProcessor {
         config: Arc<Config>,
     ) -> Self {
         let outgoing = Arc::new(outgoing);
-        let auth_manager = AuthManager::shared(config.codex_home.clone());
+        let auth_manager =
+            AuthManager::shared(config.codex_home.clone(), config.credential_store.clone());
         let conversation_manager = Arc::new(ConversationManager::new(auth_manager));
         Self {
             outgoing,
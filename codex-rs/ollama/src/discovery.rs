@@ -0,0 +1,302 @@
+//! Discovery of local model servers that speak the OpenAI API: Ollama,
+//! LM Studio, vLLM and llama.cpp's `llama-server`.
+
+use std::io;
+
+use codex_core::ModelProviderInfo;
+use codex_core::WireApi;
+use serde_json::Value as JsonValue;
+
+use crate::url::base_url_to_host_root;
+
+/// Base URL of the built-in `oss` provider when neither `CODEX_OSS_BASE_URL`
+/// nor `CODEX_OSS_PORT` is set.
+const DEFAULT_OLLAMA_BASE_URL: &str = "http://localhost:11434/v1";
+
+/// Where the other supported servers listen by default, probed in this order
+/// when the `oss` provider still points at the default Ollama URL.
+const DEFAULT_LOCAL_SERVER_BASE_URLS: &[&str] = &[
+    // LM Studio
+    "http://localhost:1234/v1",
+    // vLLM
+    "http://localhost:8000/v1",
+    // llama.cpp `llama-server`
+    "http://localhost:8080/v1",
+];
+
+pub(crate) const NO_LOCAL_SERVER_ERROR: &str = "No local model server detected. Start Ollama (`ollama serve`), LM Studio, vLLM or llama.cpp's `llama-server`, or set CODEX_OSS_BASE_URL to the OpenAI-compatible endpoint of your server (e.g. http://localhost:8000/v1).";
+
+/// The kind of server answering on a local OpenAI-compatible endpoint.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LocalServerKind {
+    Ollama,
+    LmStudio,
+    Vllm,
+    LlamaCpp,
+    /// Some other server exposing `/v1/models`.
+    OpenAiCompatible,
+}
+
+impl LocalServerKind {
+    pub fn display_name(self) -> &'static str {
+        match self {
+            LocalServerKind::Ollama => "Ollama",
+            LocalServerKind::LmStudio => "LM Studio",
+            LocalServerKind::Vllm => "vLLM",
+            LocalServerKind::LlamaCpp => "llama.cpp",
+            LocalServerKind::OpenAiCompatible => "local OpenAI-compatible server",
+        }
+    }
+
+    /// Identify the server from the `owned_by` field its `/v1/models`
+    /// endpoint reports for its models.
+    fn from_owned_by(owned_by: Option<&str>) -> Self {
+        match owned_by {
+            Some("library") | Some("ollama") => LocalServerKind::Ollama,
+            Some("vllm") => LocalServerKind::Vllm,
+            Some("llamacpp") => LocalServerKind::LlamaCpp,
+            Some("organization_owner") => LocalServerKind::LmStudio,
+            _ => LocalServerKind::OpenAiCompatible,
+        }
+    }
+}
+
+/// A local model server found by [`discover_local_server`].
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalServer {
+    pub kind: LocalServerKind,
+    /// OpenAI-compatible root of the server, e.g. "http://localhost:8000/v1".
+    pub base_url: String,
+    /// Responses when the server implements `/v1/responses`, Chat otherwise.
+    pub wire_api: WireApi,
+    /// Model ids listed by `/v1/models`.
+    pub models: Vec<String>,
+}
+
+impl LocalServer {
+    /// `provider` pointed at this server. Ollama keeps the provider's wire API
+    /// so that overrides of the built-in `oss` provider in config.toml still
+    /// apply.
+    pub fn provider(&self, provider: &ModelProviderInfo) -> ModelProviderInfo {
+        let mut provider = provider.clone();
+        provider.base_url = Some(self.base_url.clone());
+        if self.kind != LocalServerKind::Ollama {
+            provider.name = self.kind.display_name().to_string();
+            provider.wire_api = self.wire_api;
+        }
+        provider
+    }
+}
+
+/// Find the local model server behind `base_url`, the base URL of the `oss`
+/// provider. When that is the default Ollama URL and nothing answers there,
+/// the default ports of LM Studio, vLLM and llama.cpp are tried as well.
+pub async fn discover_local_server(base_url: &str) -> io::Result<LocalServer> {
+    let base_url = base_url.trim_end_matches('/');
+    let mut candidates = vec![base_url];
+    if base_url == DEFAULT_OLLAMA_BASE_URL {
+        candidates.extend_from_slice(DEFAULT_LOCAL_SERVER_BASE_URLS);
+    }
+
+    let client = reqwest::Client::builder()
+        .connect_timeout(std::time::Duration::from_secs(2))
+        .build()
+        .unwrap_or_else(|_| reqwest::Client::new());
+    for candidate in candidates {
+        if let Some(server) = probe_local_server(&client, candidate).await {
+            return Ok(server);
+        }
+    }
+    Err(io::Error::other(NO_LOCAL_SERVER_ERROR))
+}
+
+async fn probe_local_server(client: &reqwest::Client, base_url: &str) -> Option<LocalServer> {
+    let host_root = base_url_to_host_root(base_url);
+    let base_url = format!("{host_root}/v1");
+
+    let resp = match client.get(format!("{base_url}/models")).send().await {
+        Ok(resp) if resp.status().is_success() => resp,
+        Ok(resp) => {
+            tracing::debug!("{base_url}/models returned HTTP {}", resp.status());
+            return None;
+        }
+        Err(err) => {
+            tracing::debug!("No local model server at {base_url}: {err}");
+            return None;
+        }
+    };
+    let body = resp.json::<JsonValue>().await.unwrap_or(JsonValue::Null);
+    let entries = body
+        .get("data")
+        .and_then(JsonValue::as_array)
+        .cloned()
+        .unwrap_or_default();
+    let models = entries
+        .iter()
+        .filter_map(|entry| entry.get("id").and_then(JsonValue::as_str))
+        .map(str::to_string)
+        .collect();
+
+    let kind = if is_ollama(client, &host_root).await {
+        LocalServerKind::Ollama
+    } else {
+        LocalServerKind::from_owned_by(
+            entries
+                .first()
+                .and_then(|entry| entry.get("owned_by"))
+                .and_then(JsonValue::as_str),
+        )
+    };
+    let wire_api =
+        if kind != LocalServerKind::Ollama && serves_responses_api(client, &base_url).await {
+            WireApi::Responses
+        } else {
+            WireApi::Chat
+        };
+
+    Some(LocalServer {
+        kind,
+        base_url,
+        wire_api,
+        models,
+    })
+}
+
+/// Ollama is the only one of these servers with a native `/api/tags`.
+async fn is_ollama(client: &reqwest::Client, host_root: &str) -> bool {
+    matches!(
+        client.get(format!("{host_root}/api/tags")).send().await,
+        Ok(resp) if resp.status().is_success()
+    )
+}
+
+/// `/v1/responses` only accepts POST, so a server that implements it rejects
+/// a GET with something other than 404. This avoids running the model just to
+/// find out.
+async fn serves_responses_api(client: &reqwest::Client, base_url: &str) -> bool {
+    matches!(
+        client.get(format!("{base_url}/responses")).send().await,
+        Ok(resp) if resp.status() != reqwest::StatusCode::NOT_FOUND
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::Mock;
+    use wiremock::MockServer;
+    use wiremock::ResponseTemplate;
+    use wiremock::matchers::method;
+    use wiremock::matchers::path;
+
+    fn network_disabled(test: &str) -> bool {
+        if std::env::var(codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR).is_ok() {
+            tracing::info!(
+                "{} set; skipping {test}",
+                codex_core::spawn::CODEX_SANDBOX_NETWORK_DISABLED_ENV_VAR
+            );
+            return true;
+        }
+        false
+    }
+
+    async fn mount_models(server: &MockServer, owned_by: &str, ids: &[&str]) {
+        let data: Vec<JsonValue> = ids
+            .iter()
+            .map(|id| serde_json::json!({"id": id, "object": "model", "owned_by": owned_by}))
+            .collect();
+        Mock::given(method("GET"))
+            .and(path("/v1/models"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({"object": "list", "data": data})),
+            )
+            .mount(server)
+            .await;
+    }
+
+    #[tokio::test]
+    async fn detects_vllm_with_responses_api() {
+        if network_disabled("detects_vllm_with_responses_api") {
+            return;
+        }
+
+        let server = MockServer::start().await;
+        mount_models(&server, "vllm", &["Qwen/Qwen3-Coder-30B-A3B-Instruct"]).await;
+        Mock::given(method("GET"))
+            .and(path("/v1/responses"))
+            .respond_with(ResponseTemplate::new(405))
+            .mount(&server)
+            .await;
+
+        let local = discover_local_server(&format!("{}/v1", server.uri()))
+            .await
+            .expect("discover vLLM");
+        assert_eq!(
+            local,
+            LocalServer {
+                kind: LocalServerKind::Vllm,
+                base_url: format!("{}/v1", server.uri()),
+                wire_api: WireApi::Responses,
+                models: vec!["Qwen/Qwen3-Coder-30B-A3B-Instruct".to_string()],
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn detects_llama_cpp_with_chat_api() {
+        if network_disabled("detects_llama_cpp_with_chat_api") {
+            return;
+        }
+
+        let server = MockServer::start().await;
+        mount_models(&server, "llamacpp", &["devstral-small.gguf"]).await;
+
+        let local = discover_local_server(&server.uri())
+            .await
+            .expect("discover llama.cpp");
+        assert_eq!(local.kind, LocalServerKind::LlamaCpp);
+        assert_eq!(local.wire_api, WireApi::Chat);
+        assert_eq!(local.models, vec!["devstral-small.gguf".to_string()]);
+
+        let provider = local.provider(&codex_core::create_oss_provider_with_base_url(
+            DEFAULT_OLLAMA_BASE_URL,
+        ));
+        assert_eq!(provider.name, "llama.cpp");
+        assert_eq!(provider.base_url, Some(format!("{}/v1", server.uri())));
+    }
+
+    #[tokio::test]
+    async fn detects_ollama_from_native_api() {
+        if network_disabled("detects_ollama_from_native_api") {
+            return;
+        }
+
+        let server = MockServer::start().await;
+        mount_models(&server, "library", &["gpt-oss:20b"]).await;
+        Mock::given(method("GET"))
+            .and(path("/api/tags"))
+            .respond_with(ResponseTemplate::new(200))
+            .mount(&server)
+            .await;
+
+        let local = discover_local_server(&format!("{}/v1/", server.uri()))
+            .await
+            .expect("discover Ollama");
+        assert_eq!(local.kind, LocalServerKind::Ollama);
+        assert_eq!(local.wire_api, WireApi::Chat);
+    }
+
+    #[tokio::test]
+    async fn reports_missing_server() {
+        if network_disabled("reports_missing_server") {
+            return;
+        }
+
+        let server = MockServer::start().await;
+        let err = discover_local_server(&format!("{}/v1", server.uri()))
+            .await
+            .expect_err("no server should be found");
+        assert_eq!(err.to_string(), NO_LOCAL_SERVER_ERROR);
+    }
+}
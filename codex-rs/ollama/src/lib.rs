@@ -1,10 +1,16 @@
 mod client;
+mod discovery;
 mod parser;
 mod pull;
 mod url;
 
 pub use client::OllamaClient;
+use codex_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use codex_core::config::Config;
+use codex_core::config_types::ModelDefinition;
+pub use discovery::LocalServer;
+pub use discovery::LocalServerKind;
+pub use discovery::discover_local_server;
 pub use pull::CliProgressReporter;
 pub use pull::PullEvent;
 pub use pull::PullProgressReporter;
@@ -15,9 +21,69 @@ pub const DEFAULT_OSS_MODEL: &str = "gpt-oss:20b";
 
 /// Prepare the local OSS environment when `--oss` is selected.
 ///
-/// - Ensures a local Ollama server is reachable.
-/// - Checks if the model exists locally and pulls it if missing.
-pub async fn ensure_oss_ready(config: &Config) -> std::io::Result<()> {
+/// - Finds the local model server (Ollama, LM Studio, vLLM or llama.cpp) and
+///   points the `oss` provider at it with the wire API it supports.
+/// - With Ollama, checks if the model exists locally and pulls it if missing.
+///   Other servers cannot pull models, so when `-m` was not provided the first
+///   model they serve is used instead of the default OSS model.
+/// - Lists the server's models in the `/model` picker.
+pub async fn ensure_oss_ready(config: &mut Config) -> std::io::Result<()> {
+    // Look the provider up from the Config so that overrides of the built-in
+    // `oss` provider in config.toml are taken into account.
+    let oss_provider = config
+        .model_providers
+        .get(BUILT_IN_OSS_MODEL_PROVIDER_ID)
+        .cloned()
+        .ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::NotFound,
+                format!("Built-in provider {BUILT_IN_OSS_MODEL_PROVIDER_ID} not found"),
+            )
+        })?;
+    let server =
+        discover_local_server(oss_provider.base_url.as_deref().unwrap_or_default()).await?;
+    let provider = server.provider(&oss_provider);
+    config.model_provider = provider.clone();
+    config
+        .model_providers
+        .insert(BUILT_IN_OSS_MODEL_PROVIDER_ID.to_string(), provider);
+
+    let mut models = server.models.clone();
+    if server.kind == LocalServerKind::Ollama {
+        pull_model_if_missing(config).await?;
+        if !models.contains(&config.model) {
+            models.push(config.model.clone());
+        }
+    }
+    register_local_models(config, &server, &models);
+
+    if !models.contains(&config.model) {
+        match models.first() {
+            Some(first) if config.model == DEFAULT_OSS_MODEL => {
+                let first = first.clone();
+                config.set_model(&first);
+            }
+            _ => {
+                let available = if models.is_empty() {
+                    "none".to_string()
+                } else {
+                    models.join(", ")
+                };
+                return Err(std::io::Error::new(
+                    std::io::ErrorKind::NotFound,
+                    format!(
+                        "{} does not serve model `{}`. Available models: {available}",
+                        server.kind.display_name(),
+                        config.model,
+                    ),
+                ));
+            }
+        }
+    }
+    Ok(())
+}
+
+async fn pull_model_if_missing(config: &Config) -> std::io::Result<()> {
     // Only download when the requested model is the default OSS model (or when -m is not provided).
     let model = config.model.as_ref();
 
@@ -42,3 +108,17 @@ pub async fn ensure_oss_ready(config: &Config) -> std::io::Result<()> {
 
     Ok(())
 }
+
+/// Add the server's models to `config.models`, unless config.toml already
+/// describes them, so that the `/model` picker offers them.
+fn register_local_models(config: &mut Config, server: &LocalServer, models: &[String]) {
+    for model in models {
+        config
+            .models
+            .entry(model.clone())
+            .or_insert_with(|| ModelDefinition {
+                description: Some(format!("served by {}", server.kind.display_name())),
+                ..Default::default()
+            });
+    }
+}
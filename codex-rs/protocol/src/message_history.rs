@@ -8,3 +8,10 @@ pub struct HistoryEntry {
     pub ts: u64,
     pub text: String,
 }
+
+/// A history entry together with its zero-based offset in the history log.
+#[derive(Serialize, Deserialize, Debug, Clone, TS)]
+pub struct HistoryMatch {
+    pub offset: usize,
+    pub entry: HistoryEntry,
+}
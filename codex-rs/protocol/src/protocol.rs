@@ -15,12 +15,15 @@ use crate::config_types::ReasoningEffort as ReasoningEffortConfig;
 use crate::config_types::ReasoningSummary as ReasoningSummaryConfig;
 use crate::custom_prompts::CustomPrompt;
 use crate::message_history::HistoryEntry;
+use crate::message_history::HistoryMatch;
 use crate::models::ContentItem;
 use crate::models::ResponseItem;
 use crate::num_format::format_with_separators;
 use crate::parse_command::ParsedCommand;
 use crate::plan_tool::UpdatePlanArgs;
 use mcp_types::CallToolResult;
+use mcp_types::CreateMessageRequestParams;
+use mcp_types::ElicitRequestParamsRequestedSchema;
 use mcp_types::Tool as McpTool;
 use serde::Deserialize;
 use serde::Serialize;
@@ -143,6 +146,25 @@ pub enum Op {
         decision: ReviewDecision,
     },
 
+    /// Approve an MCP server's request to sample the model.
+    McpSamplingApproval {
+        /// The `id` of the `McpSamplingRequestEvent` being answered.
+        id: String,
+        /// The user's decision in response to the request.
+        decision: ReviewDecision,
+    },
+
+    /// Answer an MCP server's request for input from the user.
+    McpElicitationResponse {
+        /// The `id` of the `McpElicitationRequestEvent` being answered.
+        id: String,
+        action: McpElicitationAction,
+        /// The submitted values keyed by property name; only sent with
+        /// `McpElicitationAction::Accept`.
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        content: Option<Value>,
+    },
+
     /// Append an entry to the persistent cross-session message history.
     ///
     /// Note the entry is not guaranteed to be logged if the user has
@@ -155,6 +177,15 @@ pub enum Op {
     /// Request a single history entry identified by `log_id` + `offset`.
     GetHistoryEntryRequest { offset: usize, log_id: u64 },
 
+    /// Search the whole history log identified by `log_id` for entries that
+    /// match `query`, returning at most `max_results` of them at once.
+    /// Reply is delivered via `EventMsg::SearchHistoryResponse`.
+    SearchHistoryRequest {
+        query: String,
+        log_id: u64,
+        max_results: usize,
+    },
+
     /// Request the full in-memory conversation transcript for the current session.
     /// Reply is delivered via `EventMsg::ConversationHistory`.
     GetPath,
@@ -463,6 +494,9 @@ pub enum EventMsg {
 
     McpToolCallBegin(McpToolCallBeginEvent),
 
+    /// Progress or a log message from the MCP server while the tool call runs.
+    McpToolCallProgress(McpToolCallProgressEvent),
+
     McpToolCallEnd(McpToolCallEndEvent),
 
     WebSearchBegin(WebSearchBeginEvent),
@@ -481,6 +515,14 @@ pub enum EventMsg {
 
     ApplyPatchApprovalRequest(ApplyPatchApprovalRequestEvent),
 
+    /// An MCP server asked to sample the model (`sampling/createMessage`).
+    /// Answer with `Op::McpSamplingApproval`.
+    McpSamplingRequest(McpSamplingRequestEvent),
+
+    /// An MCP server asked the user for input (`elicitation/create`). Answer
+    /// with `Op::McpElicitationResponse`.
+    McpElicitationRequest(McpElicitationRequestEvent),
+
     BackgroundEvent(BackgroundEventEvent),
 
     /// Notification that a model stream experienced an error or disconnect
@@ -499,6 +541,9 @@ pub enum EventMsg {
     /// Response to GetHistoryEntryRequest.
     GetHistoryEntryResponse(GetHistoryEntryResponseEvent),
 
+    /// Response to SearchHistoryRequest.
+    SearchHistoryResponse(SearchHistoryResponseEvent),
+
     /// List of MCP tools available to the agent.
     McpListToolsResponse(McpListToolsResponseEvent),
 
@@ -519,6 +564,19 @@ pub enum EventMsg {
 
     /// Exited review mode with an optional final result to apply.
     ExitedReviewMode(ExitedReviewModeEvent),
+
+    /// A child session started by the `spawn_agent` tool began its task.
+    SubAgentBegin(SubAgentBeginEvent),
+
+    /// Progress reported by a running child session.
+    SubAgentProgress(SubAgentProgressEvent),
+
+    /// A child session finished; carries its final message.
+    SubAgentEnd(SubAgentEndEvent),
+
+    /// The `capture_screenshot` tool attached a rendered page to the
+    /// conversation.
+    ScreenshotCaptured(ScreenshotCapturedEvent),
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -557,6 +615,10 @@ pub struct TokenUsageInfo {
     pub total_token_usage: TokenUsage,
     pub last_token_usage: TokenUsage,
     pub model_context_window: Option<u64>,
+    /// Running cost of the session in US dollars, counting only usage of
+    /// models with a configured price.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total_cost_usd: Option<f64>,
 }
 
 impl TokenUsageInfo {
@@ -575,6 +637,7 @@ impl TokenUsageInfo {
                 total_token_usage: TokenUsage::default(),
                 last_token_usage: TokenUsage::default(),
                 model_context_window,
+                total_cost_usd: None,
             },
         };
         if let Some(last) = last {
@@ -587,6 +650,10 @@ impl TokenUsageInfo {
         self.total_token_usage.add_assign(last);
         self.last_token_usage = last.clone();
     }
+
+    pub fn add_cost_usd(&mut self, cost_usd: f64) {
+        self.total_cost_usd = Some(self.total_cost_usd.unwrap_or_default() + cost_usd);
+    }
 }
 
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
@@ -828,6 +895,38 @@ pub struct McpToolCallBeginEvent {
     pub invocation: McpInvocation,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct McpToolCallProgressEvent {
+    /// Identifier of the McpToolCallBegin this update belongs to.
+    pub call_id: String,
+    /// Progress so far, from `notifications/progress`. Increases with every
+    /// update but need not be a fraction of `total`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub progress: Option<f64>,
+    /// Total amount of work, if the server knows it.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub total: Option<f64>,
+    /// Progress message, or the text of a `notifications/message` log entry.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+impl McpToolCallProgressEvent {
+    /// One-line description such as `3/10 indexing files`.
+    pub fn summary(&self) -> String {
+        let amount = match (self.progress, self.total) {
+            (Some(progress), Some(total)) => Some(format!("{progress}/{total}")),
+            (Some(progress), None) => Some(progress.to_string()),
+            (None, _) => None,
+        };
+        [amount.as_deref(), self.message.as_deref()]
+            .into_iter()
+            .flatten()
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct McpToolCallEndEvent {
     /// Identifier for the corresponding McpToolCallBegin that finished.
@@ -1166,11 +1265,73 @@ pub struct GetHistoryEntryResponseEvent {
     pub entry: Option<HistoryEntry>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SearchHistoryResponseEvent {
+    /// The query this response answers, echoed so clients can drop stale
+    /// results.
+    pub query: String,
+    pub log_id: u64,
+    /// Matching entries, newest first.
+    pub matches: Vec<HistoryMatch>,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct McpSamplingRequestEvent {
+    /// Identifier to pass back in `Op::McpSamplingApproval`.
+    pub id: String,
+    /// The MCP server making the request.
+    pub server_name: String,
+    /// The messages, system prompt and limits the server wants sampled.
+    pub request: CreateMessageRequestParams,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct McpElicitationRequestEvent {
+    /// Identifier to pass back in `Op::McpElicitationResponse`.
+    pub id: String,
+    /// The MCP server making the request.
+    pub server_name: String,
+    /// What the server is asking the user for.
+    pub message: String,
+    /// The fields to fill in: a flat JSON Schema object whose properties are
+    /// strings, numbers, booleans or enums.
+    pub requested_schema: ElicitRequestParamsRequestedSchema,
+}
+
+/// How the user answered an elicitation request.
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, Display, TS)]
+#[serde(rename_all = "snake_case")]
+#[strum(serialize_all = "snake_case")]
+pub enum McpElicitationAction {
+    /// The user submitted the form.
+    Accept,
+    /// The user explicitly refused to provide the information.
+    Decline,
+    /// The user dismissed the request without choosing.
+    Cancel,
+}
+
 /// Response payload for `Op::ListMcpTools`.
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct McpListToolsResponseEvent {
     /// Fully qualified tool name -> tool definition.
     pub tools: std::collections::HashMap<String, McpTool>,
+    /// Server name -> connection status, for every configured server.
+    #[serde(default)]
+    pub servers: std::collections::HashMap<String, McpServerStatus>,
+}
+
+/// Connection status of a configured MCP server.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize, TS)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum McpServerStatus {
+    /// The server is being started or restarted.
+    Starting,
+    /// The server is connected and its tools are available.
+    Ready,
+    /// The server could not be started or has exited. Codex retries it with
+    /// backoff.
+    Failed { error: String },
 }
 
 /// Response payload for `Op::ListCustomPrompts`.
@@ -1250,6 +1411,52 @@ pub struct Chunk {
     pub inserted_lines: Vec<String>,
 }
 
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SubAgentBeginEvent {
+    /// Identifier of the `spawn_agent` call that started the sub-agent.
+    pub call_id: String,
+    /// Short label distinguishing sub-agents of the same call.
+    pub agent: String,
+    pub task: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SubAgentProgressEvent {
+    pub call_id: String,
+    pub agent: String,
+    /// One-line description of what the sub-agent is doing.
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct SubAgentEndEvent {
+    pub call_id: String,
+    pub agent: String,
+    pub status: SubAgentStatus,
+    /// Last assistant message of the sub-agent, returned to the parent.
+    pub final_message: Option<String>,
+    pub token_usage: TokenUsage,
+}
+
+#[derive(Debug, Clone, Deserialize, Serialize, TS)]
+pub struct ScreenshotCapturedEvent {
+    pub call_id: String,
+    /// Page that was rendered, a `file://` or local `http(s)://` URL.
+    pub url: String,
+    pub width: u32,
+    pub height: u32,
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, Serialize, PartialEq, Eq, TS)]
+#[serde(rename_all = "snake_case")]
+pub enum SubAgentStatus {
+    Completed,
+    Failed,
+    /// Stopped after using up its token budget.
+    BudgetExceeded,
+    Interrupted,
+}
+
 #[derive(Debug, Clone, Deserialize, Serialize, TS)]
 pub struct TurnAbortedEvent {
     pub reason: TurnAbortReason,
@@ -1261,6 +1468,8 @@ pub enum TurnAbortReason {
     Interrupted,
     Replaced,
     ReviewEnded,
+    /// The session reached one of its configured `[budget]` limits.
+    BudgetExceeded,
 }
 
 #[cfg(test)]
@@ -1305,6 +1514,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn mcp_tool_call_progress_summary() {
+        let progress = |progress, total, message: Option<&str>| McpToolCallProgressEvent {
+            call_id: "call-1".to_string(),
+            progress,
+            total,
+            message: message.map(str::to_string),
+        };
+        assert_eq!(
+            progress(Some(3.0), Some(10.0), Some("indexing")).summary(),
+            "3/10 indexing"
+        );
+        assert_eq!(progress(Some(0.5), None, None).summary(), "0.5");
+        assert_eq!(
+            progress(None, None, Some("connected")).summary(),
+            "connected"
+        );
+    }
+
     #[test]
     fn vec_u8_as_base64_serialization_and_deserialization() -> Result<()> {
         let event = ExecCommandOutputDeltaEvent {
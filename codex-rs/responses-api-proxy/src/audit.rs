@@ -0,0 +1,131 @@
+use std::fs::File;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use anyhow::Context;
+use anyhow::Result;
+use serde::Serialize;
+use serde_json::Value;
+
+use crate::usage::TokenUsage;
+
+/// One line of the audit log, describing a single proxied (or rejected)
+/// request.
+#[derive(Debug, Clone, Serialize)]
+pub(crate) struct AuditEntry {
+    /// RFC 3339 time at which the request arrived.
+    pub timestamp: String,
+    /// Name of the client from the `--clients` file, if clients are used.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    pub method: String,
+    pub path: String,
+    pub status: u16,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub upstream_key: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub model: Option<String>,
+    pub request_bytes: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub usage: Option<TokenUsage>,
+    pub duration_ms: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Only recorded with `--audit-log-bodies`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub request_body: Option<Value>,
+}
+
+/// Append-only JSONL file of [`AuditEntry`]s.
+#[derive(Debug)]
+pub(crate) struct AuditLog {
+    file: Mutex<File>,
+}
+
+impl AuditLog {
+    pub fn open(path: &Path) -> Result<Self> {
+        let mut options = OpenOptions::new();
+        options.create(true).append(true);
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::OpenOptionsExt;
+            options.mode(0o600);
+        }
+        let file = options
+            .open(path)
+            .with_context(|| format!("opening audit log {}", path.display()))?;
+        Ok(Self {
+            file: Mutex::new(file),
+        })
+    }
+
+    pub fn append(&self, entry: &AuditEntry) {
+        let mut line = match serde_json::to_vec(entry) {
+            Ok(line) => line,
+            Err(e) => {
+                eprintln!("failed to serialize audit entry: {e}");
+                return;
+            }
+        };
+        line.push(b'\n');
+        // A single write per entry keeps lines from concurrent requests whole.
+        let mut file = match self.file.lock() {
+            Ok(file) => file,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        if let Err(e) = file.write_all(&line).and_then(|()| file.flush()) {
+            eprintln!("failed to write audit log: {e}");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn appends_one_json_line_per_entry() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("audit.jsonl");
+        std::fs::write(&path, "{\"existing\":true}\n").unwrap();
+
+        let log = AuditLog::open(&path).unwrap();
+        let entry = AuditEntry {
+            timestamp: "2025-01-01T00:00:00.000Z".to_string(),
+            client: Some("alice".to_string()),
+            method: "POST".to_string(),
+            path: "/v1/responses".to_string(),
+            status: 200,
+            upstream_key: Some("default".to_string()),
+            model: Some("gpt-5".to_string()),
+            request_bytes: 42,
+            usage: Some(TokenUsage {
+                input_tokens: 1,
+                output_tokens: 2,
+                total_tokens: 3,
+            }),
+            duration_ms: 5,
+            error: None,
+            request_body: None,
+        };
+        log.append(&entry);
+        log.append(&AuditEntry {
+            status: 429,
+            usage: None,
+            error: Some("token quota exceeded".to_string()),
+            ..entry
+        });
+
+        let contents = std::fs::read_to_string(&path).unwrap();
+        let lines: Vec<&str> = contents.lines().collect();
+        assert_eq!(lines.len(), 3);
+        assert_eq!(
+            lines[1],
+            r#"{"timestamp":"2025-01-01T00:00:00.000Z","client":"alice","method":"POST","path":"/v1/responses","status":200,"upstream_key":"default","model":"gpt-5","request_bytes":42,"usage":{"input_tokens":1,"output_tokens":2,"total_tokens":3},"duration_ms":5}"#
+        );
+        assert!(lines[2].contains(r#""status":429"#));
+        assert!(lines[2].contains(r#""error":"token quota exceeded""#));
+    }
+}
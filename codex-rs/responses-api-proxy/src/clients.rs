@@ -0,0 +1,202 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+
+use anyhow::Context;
+use anyhow::Result;
+use anyhow::anyhow;
+use serde::Deserialize;
+
+use crate::read_api_key::DEFAULT_UPSTREAM_KEY;
+use crate::read_api_key::UpstreamKeys;
+
+/// Contents of the `--clients` file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClientsFile {
+    #[serde(default)]
+    clients: BTreeMap<String, ClientToml>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ClientToml {
+    /// Bearer token the client sends in its `Authorization` header.
+    token: String,
+    /// Name of the upstream key (read from stdin) used for this client.
+    #[serde(default = "default_upstream_key")]
+    upstream_key: String,
+    /// Total tokens the client may use while the proxy is running.
+    token_quota: Option<u64>,
+}
+
+fn default_upstream_key() -> String {
+    DEFAULT_UPSTREAM_KEY.to_string()
+}
+
+/// A caller of the proxy, identified by its bearer token.
+#[derive(Debug)]
+pub(crate) struct Client {
+    pub name: String,
+    pub upstream_key: String,
+    pub token_quota: Option<u64>,
+    token: String,
+    used_tokens: AtomicU64,
+}
+
+impl Client {
+    pub fn used_tokens(&self) -> u64 {
+        self.used_tokens.load(Ordering::Relaxed)
+    }
+
+    pub fn quota_exceeded(&self) -> bool {
+        self.token_quota
+            .is_some_and(|quota| self.used_tokens() >= quota)
+    }
+
+    pub fn record_usage(&self, tokens: u64) {
+        self.used_tokens.fetch_add(tokens, Ordering::Relaxed);
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct Clients {
+    clients: Vec<Client>,
+}
+
+impl Clients {
+    /// Load the `--clients` file and check that every client refers to one of
+    /// `upstream_keys`.
+    pub fn load(path: &Path, upstream_keys: &UpstreamKeys) -> Result<Self> {
+        let contents =
+            std::fs::read_to_string(path).with_context(|| format!("reading {}", path.display()))?;
+        let clients = Self::from_toml(&contents, upstream_keys)
+            .with_context(|| format!("loading {}", path.display()))?;
+        Ok(clients)
+    }
+
+    fn from_toml(contents: &str, upstream_keys: &UpstreamKeys) -> Result<Self> {
+        let file: ClientsFile = toml::from_str(contents)?;
+        let mut clients: Vec<Client> = Vec::with_capacity(file.clients.len());
+        for (name, client) in file.clients {
+            if client.token.is_empty() {
+                return Err(anyhow!("client `{name}` has an empty token"));
+            }
+            if let Some(other) = clients.iter().find(|other| other.token == client.token) {
+                return Err(anyhow!(
+                    "clients `{}` and `{name}` use the same token",
+                    other.name
+                ));
+            }
+            if !upstream_keys.contains_key(&client.upstream_key) {
+                return Err(anyhow!(
+                    "client `{name}` uses upstream key `{}`, which was not provided on stdin",
+                    client.upstream_key
+                ));
+            }
+            clients.push(Client {
+                name,
+                upstream_key: client.upstream_key,
+                token_quota: client.token_quota,
+                token: client.token,
+                used_tokens: AtomicU64::new(0),
+            });
+        }
+        if clients.is_empty() {
+            return Err(anyhow!("no clients are defined"));
+        }
+        Ok(Self { clients })
+    }
+
+    /// The client whose token is presented in `authorization` (the value of
+    /// the incoming `Authorization` header).
+    pub fn authenticate(&self, authorization: Option<&str>) -> Option<&Client> {
+        let token = authorization?.strip_prefix("Bearer ")?;
+        self.clients
+            .iter()
+            .find(|client| constant_time_eq(client.token.as_bytes(), token.as_bytes()))
+    }
+}
+
+/// Compare tokens without short-circuiting on the first differing byte.
+fn constant_time_eq(a: &[u8], b: &[u8]) -> bool {
+    a.len() == b.len() && a.iter().zip(b).fold(0u8, |acc, (x, y)| acc | (x ^ y)) == 0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upstream_keys(names: &[&str]) -> UpstreamKeys {
+        names
+            .iter()
+            .map(|name| (name.to_string(), "Bearer sk-test"))
+            .collect()
+    }
+
+    #[test]
+    fn authenticates_clients_by_token() {
+        let clients = Clients::from_toml(
+            r#"
+[clients.alice]
+token = "alice-token"
+token_quota = 100
+
+[clients.bob]
+token = "bob-token"
+upstream_key = "team-b"
+"#,
+            &upstream_keys(&["default", "team-b"]),
+        )
+        .unwrap();
+
+        let alice = clients.authenticate(Some("Bearer alice-token")).unwrap();
+        assert_eq!(alice.name, "alice");
+        assert_eq!(alice.upstream_key, "default");
+        let bob = clients.authenticate(Some("Bearer bob-token")).unwrap();
+        assert_eq!(bob.upstream_key, "team-b");
+
+        assert!(clients.authenticate(Some("Bearer alice-toke")).is_none());
+        assert!(clients.authenticate(Some("alice-token")).is_none());
+        assert!(clients.authenticate(None).is_none());
+
+        assert!(!alice.quota_exceeded());
+        alice.record_usage(60);
+        alice.record_usage(40);
+        assert_eq!(alice.used_tokens(), 100);
+        assert!(alice.quota_exceeded());
+        bob.record_usage(1_000_000);
+        assert!(!bob.quota_exceeded());
+    }
+
+    #[test]
+    fn rejects_unknown_upstream_keys_and_shared_tokens() {
+        let err = Clients::from_toml(
+            r#"
+[clients.alice]
+token = "t"
+upstream_key = "team-b"
+"#,
+            &upstream_keys(&["default"]),
+        )
+        .unwrap_err();
+        assert!(err.to_string().contains("upstream key `team-b`"), "{err}");
+
+        let err = Clients::from_toml(
+            r#"
+[clients.alice]
+token = "t"
+
+[clients.bob]
+token = "t"
+"#,
+            &upstream_keys(&["default"]),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "clients `alice` and `bob` use the same token"
+        );
+    }
+}
@@ -7,18 +7,21 @@ use std::path::Path;
 use std::path::PathBuf;
 use std::sync::Arc;
 use std::time::Duration;
+use std::time::Instant;
 
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
+use chrono::SecondsFormat;
+use chrono::Utc;
 use clap::Parser;
 use reqwest::blocking::Client;
 use reqwest::header::AUTHORIZATION;
-use reqwest::header::HOST;
 use reqwest::header::HeaderMap;
 use reqwest::header::HeaderName;
 use reqwest::header::HeaderValue;
 use serde::Serialize;
+use serde_json::Value;
 use tiny_http::Header;
 use tiny_http::Method;
 use tiny_http::Request;
@@ -26,8 +29,21 @@ use tiny_http::Response;
 use tiny_http::Server;
 use tiny_http::StatusCode;
 
+mod audit;
+mod clients;
 mod read_api_key;
-use read_api_key::read_auth_header_from_stdin;
+mod usage;
+use audit::AuditEntry;
+use audit::AuditLog;
+use clients::Clients;
+use read_api_key::DEFAULT_UPSTREAM_KEY;
+use read_api_key::UpstreamKeys;
+use read_api_key::read_auth_headers_from_stdin;
+use usage::MeteredBody;
+use usage::TokenUsage;
+use usage::UsageScanner;
+
+const DEFAULT_UPSTREAM_URL: &str = "https://api.openai.com/v1";
 
 /// CLI arguments for the proxy.
 #[derive(Debug, Clone, Parser)]
@@ -44,6 +60,23 @@ pub struct Args {
     /// Enable HTTP shutdown endpoint at GET /shutdown
     #[arg(long)]
     pub http_shutdown: bool,
+
+    /// Base URL that `/v1/<endpoint>` requests are forwarded to.
+    #[arg(long, value_name = "URL", default_value = DEFAULT_UPSTREAM_URL)]
+    pub upstream_url: String,
+
+    /// TOML file of clients, each with its own bearer token, upstream key and
+    /// optional token quota. Without it, every caller shares the default key.
+    #[arg(long, value_name = "FILE")]
+    pub clients: Option<PathBuf>,
+
+    /// Append a JSON line describing every request to this file.
+    #[arg(long, value_name = "FILE")]
+    pub audit_log: Option<PathBuf>,
+
+    /// Include request bodies in the audit log.
+    #[arg(long, requires = "audit_log")]
+    pub audit_log_bodies: bool,
 }
 
 #[derive(Serialize)]
@@ -51,9 +84,41 @@ struct ServerInfo {
     port: u16,
 }
 
+/// Everything a request handler needs, shared across connection threads.
+struct ProxyState {
+    http: Client,
+    /// `--upstream-url` without a trailing `/`.
+    upstream_url: String,
+    upstream_keys: UpstreamKeys,
+    clients: Option<Clients>,
+    audit_log: Option<AuditLog>,
+    audit_log_bodies: bool,
+}
+
+impl ProxyState {
+    fn audit(&self, entry: &AuditEntry) {
+        if let Some(audit_log) = &self.audit_log {
+            audit_log.append(entry);
+        }
+    }
+}
+
 /// Entry point for the library main, for parity with other crates.
 pub fn run_main(args: Args) -> Result<()> {
-    let auth_header = read_auth_header_from_stdin()?;
+    let upstream_keys = read_auth_headers_from_stdin()?;
+
+    let clients = match args.clients.as_deref() {
+        Some(path) => Some(Clients::load(path, &upstream_keys)?),
+        None if upstream_keys.contains_key(DEFAULT_UPSTREAM_KEY) => None,
+        None => {
+            return Err(anyhow!(
+                "without --clients, stdin must provide a single key or a `{DEFAULT_UPSTREAM_KEY}=` key"
+            ));
+        }
+    };
+    let upstream_url = reqwest::Url::parse(&args.upstream_url)
+        .with_context(|| format!("invalid --upstream-url {}", args.upstream_url))?;
+    let audit_log = args.audit_log.as_deref().map(AuditLog::open).transpose()?;
 
     let (listener, bound_addr) = bind_listener(args.port)?;
     if let Some(path) = args.server_info.as_ref() {
@@ -61,26 +126,31 @@ pub fn run_main(args: Args) -> Result<()> {
     }
     let server = Server::from_listener(listener, None)
         .map_err(|err| anyhow!("creating HTTP server: {err}"))?;
-    let client = Arc::new(
-        Client::builder()
+    let state = Arc::new(ProxyState {
+        http: Client::builder()
             // Disable reqwest's 30s default so long-lived response streams keep flowing.
             .timeout(None::<Duration>)
             .build()
             .context("building reqwest client")?,
-    );
+        upstream_url: upstream_url.as_str().trim_end_matches('/').to_string(),
+        upstream_keys,
+        clients,
+        audit_log,
+        audit_log_bodies: args.audit_log_bodies,
+    });
 
     eprintln!("responses-api-proxy listening on {bound_addr}");
 
     let http_shutdown = args.http_shutdown;
     for request in server.incoming_requests() {
-        let client = client.clone();
+        let state = state.clone();
         std::thread::spawn(move || {
             if http_shutdown && request.method() == &Method::Get && request.url() == "/shutdown" {
                 let _ = request.respond(Response::new_empty(StatusCode(200)));
                 std::process::exit(0);
             }
 
-            if let Err(e) = forward_request(&client, auth_header, request) {
+            if let Err(e) = forward_request(&state, request) {
                 eprintln!("forwarding error: {e}");
             }
         });
@@ -112,30 +182,185 @@ fn write_server_info(path: &Path, port: u16) -> Result<()> {
     Ok(())
 }
 
-fn forward_request(client: &Client, auth_header: &'static str, mut req: Request) -> Result<()> {
-    // Only allow POST /v1/responses exactly, no query string.
-    let method = req.method().clone();
-    let url_path = req.url().to_string();
-    let allow = method == Method::Post && url_path == "/v1/responses";
+/// The upstream path (relative to `--upstream-url`) for an allowed request:
+/// `POST /v1/responses`, `POST /v1/chat/completions`, `GET /v1/models` and
+/// `GET /v1/models/<model>`, without query strings.
+fn upstream_path(method: &Method, url: &str) -> Option<String> {
+    let path = url.strip_prefix("/v1/")?;
+    let allow = match method {
+        Method::Post => matches!(path, "responses" | "chat/completions"),
+        Method::Get => {
+            path == "models"
+                || path.strip_prefix("models/").is_some_and(|model| {
+                    !model.is_empty()
+                        && model.bytes().all(|b| {
+                            b.is_ascii_alphanumeric() || matches!(b, b'-' | b'_' | b'.' | b':')
+                        })
+                })
+        }
+        _ => false,
+    };
+    allow.then(|| path.to_string())
+}
+
+/// Chat Completions streams only report usage when the request sets
+/// `stream_options.include_usage`, so a client with a quota could otherwise
+/// stream for free. Turns it on and returns whether `body` was changed.
+fn request_stream_usage(path: &str, body: &mut Value) -> bool {
+    if path != "chat/completions" || body.get("stream").and_then(Value::as_bool) != Some(true) {
+        return false;
+    }
+    let Some(request) = body.as_object_mut() else {
+        return false;
+    };
+    let stream_options = request
+        .entry("stream_options")
+        .or_insert_with(|| Value::Object(Default::default()));
+    if !stream_options.is_object() {
+        *stream_options = Value::Object(Default::default());
+    }
+    if stream_options.get("include_usage") == Some(&Value::Bool(true)) {
+        return false;
+    }
+    stream_options["include_usage"] = Value::Bool(true);
+    true
+}
+
+/// Respond with an OpenAI-style JSON error and record it in the audit log.
+fn reject(
+    state: &ProxyState,
+    req: Request,
+    mut entry: AuditEntry,
+    started: Instant,
+    status: u16,
+    error_type: &str,
+    message: String,
+) {
+    let body = serde_json::json!({
+        "error": { "type": error_type, "message": message },
+    });
+    entry.status = status;
+    entry.error = Some(message);
+    entry.duration_ms = started.elapsed().as_millis() as u64;
+    state.audit(&entry);
 
-    if !allow {
-        let resp = Response::new_empty(StatusCode(403));
-        let _ = req.respond(resp);
+    let mut response = Response::from_string(body.to_string()).with_status_code(status);
+    if let Ok(header) = Header::from_bytes(&b"content-type"[..], &b"application/json"[..]) {
+        response.add_header(header);
+    }
+    let _ = req.respond(response);
+}
+
+fn forward_request(state: &ProxyState, mut req: Request) -> Result<()> {
+    let started = Instant::now();
+    let mut entry = AuditEntry {
+        timestamp: Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true),
+        client: None,
+        method: req.method().to_string(),
+        path: req.url().to_string(),
+        status: 0,
+        upstream_key: None,
+        model: None,
+        request_bytes: 0,
+        usage: None,
+        duration_ms: 0,
+        error: None,
+        request_body: None,
+    };
+
+    let Some(path) = upstream_path(req.method(), req.url()) else {
+        let message = format!("{} {} is not allowed", entry.method, entry.path);
+        reject(state, req, entry, started, 403, "forbidden", message);
+        return Ok(());
+    };
+
+    // With `--clients`, callers must present one of the configured tokens.
+    let client = match &state.clients {
+        Some(clients) => {
+            let authorization = req
+                .headers()
+                .iter()
+                .find(|header| header.field.equiv("authorization"))
+                .map(|header| header.value.as_str());
+            let Some(client) = clients.authenticate(authorization) else {
+                let message = "missing or unknown client token".to_string();
+                reject(
+                    state,
+                    req,
+                    entry,
+                    started,
+                    401,
+                    "invalid_request_error",
+                    message,
+                );
+                return Ok(());
+            };
+            entry.client = Some(client.name.clone());
+            Some(client)
+        }
+        None => None,
+    };
+    if let Some(client) = client
+        && let Some(quota) = client.token_quota
+        && client.quota_exceeded()
+    {
+        // Codex stops retrying when it sees `usage_limit_reached`.
+        let message = format!(
+            "client `{}` has used {} of its {quota} token quota",
+            client.name,
+            client.used_tokens()
+        );
+        reject(
+            state,
+            req,
+            entry,
+            started,
+            429,
+            "usage_limit_reached",
+            message,
+        );
         return Ok(());
     }
 
+    let key_name = client.map_or(DEFAULT_UPSTREAM_KEY, |client| client.upstream_key.as_str());
+    let auth_header = *state
+        .upstream_keys
+        .get(key_name)
+        .with_context(|| format!("upstream key `{key_name}` is missing"))?;
+    entry.upstream_key = Some(key_name.to_string());
+
     // Read request body
     let mut body = Vec::new();
     let mut reader = req.as_reader();
     std::io::Read::read_to_end(&mut reader, &mut body)?;
+    entry.request_bytes = body.len();
+    let mut json_body = serde_json::from_slice::<Value>(&body).ok();
+    entry.model = json_body
+        .as_ref()
+        .and_then(|json| json.get("model"))
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    if client.is_some_and(|client| client.token_quota.is_some())
+        && let Some(json) = json_body.as_mut()
+        && request_stream_usage(&path, json)
+    {
+        body = serde_json::to_vec(json).context("serializing request body")?;
+    }
+    if state.audit_log_bodies && !body.is_empty() {
+        entry.request_body = Some(
+            json_body.unwrap_or_else(|| Value::String(String::from_utf8_lossy(&body).into_owned())),
+        );
+    }
 
     // Build headers for upstream, forwarding everything from the incoming
-    // request except Authorization (we replace it below).
+    // request except Authorization (we replace it below), Host (which
+    // reqwest derives from the upstream URL) and Content-Length (the body may
+    // have been rewritten above).
     let mut headers = HeaderMap::new();
     for header in req.headers() {
         let name_ascii = header.field.as_str();
         let lower = name_ascii.to_ascii_lowercase();
-        if lower.as_str() == "authorization" || lower.as_str() == "host" {
+        if matches!(lower.as_str(), "authorization" | "host" | "content-length") {
             continue;
         }
 
@@ -154,15 +379,25 @@ fn forward_request(client: &Client, auth_header: &'static str, mut req: Request)
     auth_header_value.set_sensitive(true);
     headers.insert(AUTHORIZATION, auth_header_value);
 
-    headers.insert(HOST, HeaderValue::from_static("api.openai.com"));
-
-    let upstream = "https://api.openai.com/v1/responses";
-    let upstream_resp = client
-        .post(upstream)
+    let method = match req.method() {
+        Method::Get => reqwest::Method::GET,
+        _ => reqwest::Method::POST,
+    };
+    let upstream = format!("{}/{path}", state.upstream_url);
+    let upstream_resp = match state
+        .http
+        .request(method, upstream)
         .headers(headers)
         .body(body)
         .send()
-        .context("forwarding request to upstream")?;
+    {
+        Ok(resp) => resp,
+        Err(e) => {
+            let message = "forwarding request to upstream failed".to_string();
+            reject(state, req, entry, started, 502, "upstream_error", message);
+            return Err(e).context("forwarding request to upstream");
+        }
+    };
 
     // We have to create an adapter between a `reqwest::blocking::Response`
     // and a `tiny_http::Response`. Fortunately, `reqwest::blocking::Response`
@@ -192,10 +427,31 @@ fn forward_request(client: &Client, auth_header: &'static str, mut req: Request)
         }
     });
 
+    // Streams are scanned line by line for `response.completed` (or a Chat
+    // Completions `usage` chunk); plain JSON bodies are parsed once read.
+    let is_json = upstream_resp
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("application/json"));
+    entry.status = status.as_u16();
+    let body = MeteredBody::new(
+        upstream_resp,
+        UsageScanner::new(is_json),
+        move |usage: Option<TokenUsage>| {
+            if let (Some(client), Some(usage)) = (client, usage) {
+                client.record_usage(usage.total_tokens);
+            }
+            entry.usage = usage;
+            entry.duration_ms = started.elapsed().as_millis() as u64;
+            state.audit(&entry);
+        },
+    );
+
     let response = Response::new(
         StatusCode(status.as_u16()),
         response_headers,
-        upstream_resp,
+        body,
         content_length,
         None,
     );
@@ -203,3 +459,51 @@ fn forward_request(client: &Client, auth_header: &'static str, mut req: Request)
     let _ = req.respond(response);
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn only_allowed_endpoints_are_forwarded() {
+        assert_eq!(
+            upstream_path(&Method::Post, "/v1/responses"),
+            Some("responses".to_string())
+        );
+        assert_eq!(
+            upstream_path(&Method::Post, "/v1/chat/completions"),
+            Some("chat/completions".to_string())
+        );
+        assert_eq!(
+            upstream_path(&Method::Get, "/v1/models/gpt-5"),
+            Some("models/gpt-5".to_string())
+        );
+        assert_eq!(upstream_path(&Method::Get, "/v1/responses"), None);
+        assert_eq!(upstream_path(&Method::Post, "/v1/files"), None);
+        assert_eq!(upstream_path(&Method::Post, "/v1/responses?x=1"), None);
+        assert_eq!(upstream_path(&Method::Get, "/v1/models/../files"), None);
+        assert_eq!(upstream_path(&Method::Get, "/v1/models/"), None);
+        assert_eq!(upstream_path(&Method::Post, "/responses"), None);
+    }
+
+    #[test]
+    fn streamed_chat_completions_request_usage() {
+        let mut body = json!({"model": "gpt-5", "stream": true});
+        assert!(request_stream_usage("chat/completions", &mut body));
+        assert_eq!(
+            body,
+            json!({"model": "gpt-5", "stream": true, "stream_options": {"include_usage": true}})
+        );
+        assert!(!request_stream_usage("chat/completions", &mut body));
+
+        let mut opted_out = json!({"stream": true, "stream_options": {"include_usage": false}});
+        assert!(request_stream_usage("chat/completions", &mut opted_out));
+        assert_eq!(opted_out["stream_options"]["include_usage"], json!(true));
+
+        let mut unstreamed = json!({"stream": false});
+        assert!(!request_stream_usage("chat/completions", &mut unstreamed));
+        let mut responses = json!({"stream": true});
+        assert!(!request_stream_usage("responses", &mut responses));
+    }
+}
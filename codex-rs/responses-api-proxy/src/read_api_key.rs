@@ -1,6 +1,7 @@
 use anyhow::Context;
 use anyhow::Result;
 use anyhow::anyhow;
+use std::collections::BTreeMap;
 use std::io::Read;
 use zeroize::Zeroize;
 
@@ -9,24 +10,31 @@ use zeroize::Zeroize;
 const BUFFER_SIZE: usize = 1024;
 const AUTH_HEADER_PREFIX: &[u8] = b"Bearer ";
 
-/// Reads the auth token from stdin and returns a static `Authorization` header
-/// value with the auth token used with `Bearer`. The header value is returned
-/// as a `&'static str` whose bytes are locked in memory to avoid accidental
-/// exposure.
-pub(crate) fn read_auth_header_from_stdin() -> Result<&'static str> {
-    read_auth_header_with(|buffer| std::io::stdin().read(buffer))
+/// Name of the upstream key used when stdin holds a single bare key.
+pub(crate) const DEFAULT_UPSTREAM_KEY: &str = "default";
+
+/// Upstream key name to its `Authorization` header value.
+pub(crate) type UpstreamKeys = BTreeMap<String, &'static str>;
+
+/// Reads the auth tokens from stdin and returns a static `Authorization`
+/// header value for each, using `Bearer`. Stdin holds either a single key,
+/// which is named [`DEFAULT_UPSTREAM_KEY`], or one `name=key` pair per line.
+/// The header values are returned as `&'static str`s whose bytes are locked in
+/// memory to avoid accidental exposure.
+pub(crate) fn read_auth_headers_from_stdin() -> Result<UpstreamKeys> {
+    read_auth_headers_with(|buffer| std::io::stdin().read(buffer))
 }
 
-fn read_auth_header_with<F>(read_fn: F) -> Result<&'static str>
+fn read_auth_headers_with<F>(read_fn: F) -> Result<UpstreamKeys>
 where
     F: FnOnce(&mut [u8]) -> std::io::Result<usize>,
 {
     // TAKE CARE WHEN MODIFYING THIS CODE!!!
     //
-    // This function goes to great lengths to avoid leaving the API key in
-    // memory longer than necessary and to avoid copying it around. We read
-    // directly into a stack buffer so the only heap allocation should be the
-    // one to create the String (with the exact size) for the header value,
+    // This function goes to great lengths to avoid leaving the API keys in
+    // memory longer than necessary and to avoid copying them around. We read
+    // directly into a stack buffer so the only heap allocations should be the
+    // ones to create the Strings (with the exact size) for the header values,
     // which we then immediately protect with mlock(2).
     let mut buf = [0u8; BUFFER_SIZE];
     buf[..AUTH_HEADER_PREFIX.len()].copy_from_slice(AUTH_HEADER_PREFIX);
@@ -54,24 +62,63 @@ where
         ));
     }
 
-    if let Err(err) = validate_auth_header_bytes(&buf[AUTH_HEADER_PREFIX.len()..total]) {
-        buf.zeroize();
-        return Err(err);
-    }
+    let result = if buf[AUTH_HEADER_PREFIX.len()..total].contains(&b'=') {
+        named_auth_headers(&buf[AUTH_HEADER_PREFIX.len()..total])
+    } else {
+        leak_auth_header(&buf[..total]).map(|header| {
+            let mut keys = UpstreamKeys::new();
+            keys.insert(DEFAULT_UPSTREAM_KEY.to_string(), header);
+            keys
+        })
+    };
+    buf.zeroize();
+    result
+}
 
-    let header_str = match std::str::from_utf8(&buf[..total]) {
-        Ok(value) => value,
-        Err(err) => {
-            // In theory, validate_auth_header_bytes() should have caught
-            // any invalid UTF-8 sequences, but just in case...
-            buf.zeroize();
-            return Err(err).context("reading Authorization header from stdin as UTF-8");
+/// Parse `name=key` lines, ignoring blank ones.
+fn named_auth_headers(input: &[u8]) -> Result<UpstreamKeys> {
+    let mut keys = UpstreamKeys::new();
+    for line in input.split(|byte| *byte == b'\n') {
+        let line = line.strip_suffix(b"\r").unwrap_or(line);
+        if line.is_empty() {
+            continue;
         }
-    };
+        let Some(separator) = line.iter().position(|byte| *byte == b'=') else {
+            return Err(anyhow!(
+                "each line of stdin must be `name=key` when more than one key is provided"
+            ));
+        };
+        let (name, key) = (&line[..separator], &line[separator + 1..]);
+        validate_key_name(name)?;
+        // validate_key_name() guarantees ASCII.
+        let name = String::from_utf8_lossy(name).into_owned();
+        if key.is_empty() {
+            return Err(anyhow!("upstream key `{name}` is empty"));
+        }
+        validate_auth_header_bytes(key)?;
+
+        let mut header = Vec::with_capacity(AUTH_HEADER_PREFIX.len() + key.len());
+        header.extend_from_slice(AUTH_HEADER_PREFIX);
+        header.extend_from_slice(key);
+        let leaked = leak_auth_header(&header);
+        header.zeroize();
+        if keys.insert(name.clone(), leaked?).is_some() {
+            return Err(anyhow!("upstream key `{name}` is provided more than once"));
+        }
+    }
+    Ok(keys)
+}
 
-    let header_value = String::from(header_str);
-    buf.zeroize();
+/// Copy `header` (`Bearer <key>`) into a leaked, mlock(2)ed `&'static str`.
+fn leak_auth_header(header: &[u8]) -> Result<&'static str> {
+    validate_auth_header_bytes(&header[AUTH_HEADER_PREFIX.len()..])?;
+
+    // In theory, validate_auth_header_bytes() should have caught any invalid
+    // UTF-8 sequences, but just in case...
+    let header_str =
+        std::str::from_utf8(header).context("reading Authorization header from stdin as UTF-8")?;
 
+    let header_value = String::from(header_str);
     let leaked: &'static mut str = header_value.leak();
     mlock_str(leaked);
 
@@ -135,11 +182,34 @@ fn validate_auth_header_bytes(key_bytes: &[u8]) -> Result<()> {
     ))
 }
 
+fn validate_key_name(name: &[u8]) -> Result<()> {
+    if !name.is_empty()
+        && name
+            .iter()
+            .all(|byte| byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'_'))
+    {
+        return Ok(());
+    }
+
+    Err(anyhow!(
+        "upstream key names may only contain ASCII letters, numbers, '-' or '_'"
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use std::io;
 
+    fn read_auth_header_with<F>(read_fn: F) -> Result<&'static str>
+    where
+        F: FnOnce(&mut [u8]) -> std::io::Result<usize>,
+    {
+        let keys = read_auth_headers_with(read_fn)?;
+        assert_eq!(keys.len(), 1);
+        Ok(keys[DEFAULT_UPSTREAM_KEY])
+    }
+
     #[test]
     fn reads_key_with_no_newlines() {
         let result = read_auth_header_with(|buf| {
@@ -221,4 +291,41 @@ mod tests {
             message.contains("OPENAI_API_KEY may only contain ASCII letters, numbers, '-' or '_'")
         );
     }
+
+    #[test]
+    fn reads_named_keys() {
+        let keys = read_auth_headers_with(|buf| {
+            let data = b"team-a=sk-aaa\r\n\nteam_b=sk-bbb\n";
+            buf[..data.len()].copy_from_slice(data);
+            Ok(data.len())
+        })
+        .unwrap();
+
+        assert_eq!(
+            keys.into_iter().collect::<Vec<_>>(),
+            vec![
+                ("team-a".to_string(), "Bearer sk-aaa"),
+                ("team_b".to_string(), "Bearer sk-bbb"),
+            ]
+        );
+    }
+
+    #[test]
+    fn errors_on_malformed_named_keys() {
+        for (data, expected) in [
+            (&b"a=sk-1\nsk-2"[..], "must be `name=key`"),
+            (&b"a=sk-1\na=sk-2"[..], "provided more than once"),
+            (&b"a b=sk-1"[..], "upstream key names may only contain"),
+            (&b"a="[..], "upstream key `a` is empty"),
+            (&b"a=sk!1"[..], "OPENAI_API_KEY may only contain"),
+        ] {
+            let err = read_auth_headers_with(|buf| {
+                buf[..data.len()].copy_from_slice(data);
+                Ok(data.len())
+            })
+            .unwrap_err();
+            let message = format!("{err:#}");
+            assert!(message.contains(expected), "{message}");
+        }
+    }
 }
@@ -0,0 +1,250 @@
+use std::io::Read;
+
+use serde::Serialize;
+use serde_json::Value;
+
+/// Longest SSE line that is inspected for usage. `response.completed` carries
+/// the whole response, so this is generous.
+const MAX_LINE_BYTES: usize = 16 * 1024 * 1024;
+
+/// Largest non-streaming JSON body that is inspected for usage.
+const MAX_JSON_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Token counts reported by the upstream for one request.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub(crate) struct TokenUsage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub total_tokens: u64,
+}
+
+impl TokenUsage {
+    /// Read a `usage` object from either the Responses API
+    /// (`input_tokens`/`output_tokens`) or Chat Completions
+    /// (`prompt_tokens`/`completion_tokens`).
+    fn from_value(usage: &Value) -> Option<Self> {
+        let field = |names: [&str; 2]| names.iter().find_map(|name| usage.get(name)?.as_u64());
+        let input_tokens = field(["input_tokens", "prompt_tokens"]);
+        let output_tokens = field(["output_tokens", "completion_tokens"]);
+        let total_tokens = usage.get("total_tokens").and_then(Value::as_u64);
+        if input_tokens.is_none() && output_tokens.is_none() && total_tokens.is_none() {
+            return None;
+        }
+        let input_tokens = input_tokens.unwrap_or_default();
+        let output_tokens = output_tokens.unwrap_or_default();
+        Some(Self {
+            input_tokens,
+            output_tokens,
+            total_tokens: total_tokens.unwrap_or(input_tokens + output_tokens),
+        })
+    }
+}
+
+/// The usage in a streamed event or a complete response body: the `usage` of
+/// `response.completed` for the Responses API, the top-level `usage`
+/// otherwise.
+fn usage_from_event(event: &Value) -> Option<TokenUsage> {
+    let usage = if event.get("type").and_then(Value::as_str) == Some("response.completed") {
+        event.get("response")?.get("usage")?
+    } else {
+        event.get("usage")?
+    };
+    TokenUsage::from_value(usage)
+}
+
+/// Finds the token usage in a response body as it streams past.
+#[derive(Debug)]
+pub(crate) struct UsageScanner {
+    /// Whether the body is a single JSON document rather than SSE.
+    json: bool,
+    buffer: Vec<u8>,
+    overflowed: bool,
+    usage: Option<TokenUsage>,
+}
+
+impl UsageScanner {
+    pub fn new(json: bool) -> Self {
+        Self {
+            json,
+            buffer: Vec::new(),
+            overflowed: false,
+            usage: None,
+        }
+    }
+
+    pub fn feed(&mut self, bytes: &[u8]) {
+        if self.json {
+            if self.buffer.len() + bytes.len() > MAX_JSON_BODY_BYTES {
+                self.overflowed = true;
+                self.buffer = Vec::new();
+            } else if !self.overflowed {
+                self.buffer.extend_from_slice(bytes);
+            }
+            return;
+        }
+
+        for &byte in bytes {
+            if byte == b'\n' {
+                if !self.overflowed {
+                    self.scan_line();
+                }
+                self.buffer.clear();
+                self.overflowed = false;
+            } else if self.buffer.len() < MAX_LINE_BYTES {
+                self.buffer.push(byte);
+            } else {
+                self.overflowed = true;
+            }
+        }
+    }
+
+    fn scan_line(&mut self) {
+        let line = self.buffer.strip_suffix(b"\r").unwrap_or(&self.buffer);
+        let Some(data) = line.strip_prefix(b"data:") else {
+            return;
+        };
+        if let Ok(event) = serde_json::from_slice::<Value>(data)
+            && let Some(usage) = usage_from_event(&event)
+        {
+            self.usage = Some(usage);
+        }
+    }
+
+    pub fn finish(mut self) -> Option<TokenUsage> {
+        if self.json {
+            if self.overflowed {
+                return None;
+            }
+            return serde_json::from_slice::<Value>(&self.buffer)
+                .ok()
+                .and_then(|body| usage_from_event(&body));
+        }
+        if !self.overflowed && !self.buffer.is_empty() {
+            self.scan_line();
+        }
+        self.usage
+    }
+}
+
+/// A response body that reports the usage it contained to `on_finish` once it
+/// has been read to the end (or dropped early).
+pub(crate) struct MeteredBody<R, F>
+where
+    F: FnOnce(Option<TokenUsage>),
+{
+    inner: R,
+    scanner: Option<UsageScanner>,
+    on_finish: Option<F>,
+}
+
+impl<R, F> MeteredBody<R, F>
+where
+    F: FnOnce(Option<TokenUsage>),
+{
+    pub fn new(inner: R, scanner: UsageScanner, on_finish: F) -> Self {
+        Self {
+            inner,
+            scanner: Some(scanner),
+            on_finish: Some(on_finish),
+        }
+    }
+}
+
+impl<R, F> Read for MeteredBody<R, F>
+where
+    R: Read,
+    F: FnOnce(Option<TokenUsage>),
+{
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let read = self.inner.read(buf)?;
+        if let Some(scanner) = self.scanner.as_mut() {
+            scanner.feed(&buf[..read]);
+        }
+        Ok(read)
+    }
+}
+
+impl<R, F> Drop for MeteredBody<R, F>
+where
+    F: FnOnce(Option<TokenUsage>),
+{
+    fn drop(&mut self) {
+        if let (Some(scanner), Some(on_finish)) = (self.scanner.take(), self.on_finish.take()) {
+            on_finish(scanner.finish());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+
+    fn metered(body: &str, json: bool) -> Option<TokenUsage> {
+        let seen = Arc::new(Mutex::new(None));
+        let seen_in_callback = seen.clone();
+        let mut reader = MeteredBody::new(body.as_bytes(), UsageScanner::new(json), move |usage| {
+            *seen_in_callback.lock().unwrap() = Some(usage)
+        });
+        // Read in small chunks so events straddle reads.
+        let mut chunk = [0u8; 7];
+        let mut out = Vec::new();
+        loop {
+            let read = reader.read(&mut chunk).unwrap();
+            if read == 0 {
+                break;
+            }
+            out.extend_from_slice(&chunk[..read]);
+        }
+        drop(reader);
+        assert_eq!(out, body.as_bytes());
+        seen.lock().unwrap().take().expect("on_finish called")
+    }
+
+    #[test]
+    fn reads_usage_from_response_completed() {
+        let body = concat!(
+            "event: response.created\n",
+            "data: {\"type\":\"response.created\",\"response\":{}}\n\n",
+            "event: response.completed\n",
+            "data: {\"type\":\"response.completed\",\"response\":{\"id\":\"r\",",
+            "\"usage\":{\"input_tokens\":12,\"output_tokens\":30,\"total_tokens\":42}}}\n\n",
+        );
+        assert_eq!(
+            metered(body, false),
+            Some(TokenUsage {
+                input_tokens: 12,
+                output_tokens: 30,
+                total_tokens: 42,
+            })
+        );
+    }
+
+    #[test]
+    fn reads_usage_from_chat_completions() {
+        let stream = concat!(
+            "data: {\"choices\":[{\"delta\":{\"content\":\"hi\"}}],\"usage\":null}\n\n",
+            "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":5,\"completion_tokens\":2,\"total_tokens\":7}}\n\n",
+            "data: [DONE]\n\n",
+        );
+        let expected = Some(TokenUsage {
+            input_tokens: 5,
+            output_tokens: 2,
+            total_tokens: 7,
+        });
+        assert_eq!(metered(stream, false), expected);
+
+        let body = r#"{"choices":[],"usage":{"prompt_tokens":5,"completion_tokens":2}}"#;
+        assert_eq!(metered(body, true), expected);
+    }
+
+    #[test]
+    fn no_usage_without_a_completed_event() {
+        assert_eq!(
+            metered("data: {\"type\":\"response.created\"}\n", false),
+            None
+        );
+        assert_eq!(metered(r#"{"data":[{"id":"gpt-5"}]}"#, true), None);
+    }
+}
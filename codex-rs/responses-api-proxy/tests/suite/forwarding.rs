@@ -0,0 +1,266 @@
+#![allow(clippy::expect_used, clippy::unwrap_used)]
+
+use std::io::Write;
+use std::path::Path;
+use std::process::Child;
+use std::process::Command;
+use std::process::Stdio;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+use std::time::Instant;
+
+use reqwest::blocking::Client;
+use reqwest::blocking::Response;
+use serde_json::Value;
+use serde_json::json;
+use tempfile::TempDir;
+use tiny_http::Header;
+
+const UPSTREAM_KEY: &str = "sk-upstream";
+
+const RESPONSES_STREAM: &str = concat!(
+    "event: response.completed\n",
+    "data: {\"type\":\"response.completed\",\"response\":{\"id\":\"r\",",
+    "\"usage\":{\"input_tokens\":12,\"output_tokens\":30,\"total_tokens\":42}}}\n\n",
+);
+
+const CHAT_STREAM: &str = concat!(
+    "data: {\"choices\":[{\"delta\":{\"content\":\"hi\"}}],\"usage\":null}\n\n",
+    "data: {\"choices\":[],\"usage\":{\"prompt_tokens\":40,\"completion_tokens\":20,\"total_tokens\":60}}\n\n",
+    "data: [DONE]\n\n",
+);
+
+/// A request as the upstream received it.
+#[derive(Debug, Clone)]
+struct UpstreamRequest {
+    path: String,
+    authorization: Option<String>,
+    body: Value,
+}
+
+/// Fake upstream that answers every request with a canned SSE stream.
+struct Upstream {
+    url: String,
+    requests: Arc<Mutex<Vec<UpstreamRequest>>>,
+}
+
+impl Upstream {
+    fn start() -> Self {
+        let server = tiny_http::Server::http("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/v1", server.server_addr());
+        let requests = Arc::new(Mutex::new(Vec::new()));
+        let recorded = requests.clone();
+        std::thread::spawn(move || {
+            for mut request in server.incoming_requests() {
+                let mut body = String::new();
+                request.as_reader().read_to_string(&mut body).unwrap();
+                let path = request.url().to_string();
+                let authorization = request
+                    .headers()
+                    .iter()
+                    .find(|header| header.field.equiv("authorization"))
+                    .map(|header| header.value.to_string());
+                let stream = if path.ends_with("/chat/completions") {
+                    CHAT_STREAM
+                } else {
+                    RESPONSES_STREAM
+                };
+                recorded.lock().unwrap().push(UpstreamRequest {
+                    path,
+                    authorization,
+                    body: serde_json::from_str(&body).unwrap_or(Value::Null),
+                });
+                let content_type =
+                    Header::from_bytes(&b"content-type"[..], &b"text/event-stream"[..]).unwrap();
+                let response = tiny_http::Response::from_string(stream).with_header(content_type);
+                let _ = request.respond(response);
+            }
+        });
+        Self { url, requests }
+    }
+
+    fn requests(&self) -> Vec<UpstreamRequest> {
+        self.requests.lock().unwrap().clone()
+    }
+}
+
+/// The proxy binary, killed on drop.
+struct Proxy {
+    child: Child,
+    base_url: String,
+    http: Client,
+    _dir: TempDir,
+}
+
+impl Proxy {
+    fn start(upstream: &Upstream, clients: Option<&str>) -> Self {
+        let dir = TempDir::new().unwrap();
+        let server_info = dir.path().join("server-info.json");
+        let mut command = Command::new(env!("CARGO_BIN_EXE_codex-responses-api-proxy"));
+        command
+            .arg("--server-info")
+            .arg(&server_info)
+            .arg("--upstream-url")
+            .arg(&upstream.url)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null());
+        if let Some(clients) = clients {
+            let clients_path = dir.path().join("clients.toml");
+            std::fs::write(&clients_path, clients).unwrap();
+            command.arg("--clients").arg(clients_path);
+        }
+        let mut child = command.spawn().unwrap();
+        let mut stdin = child.stdin.take().unwrap();
+        stdin.write_all(UPSTREAM_KEY.as_bytes()).unwrap();
+        drop(stdin);
+
+        let port = wait_for_port(&server_info);
+        Self {
+            child,
+            base_url: format!("http://127.0.0.1:{port}/v1"),
+            http: Client::new(),
+            _dir: dir,
+        }
+    }
+
+    fn post(&self, path: &str, token: Option<&str>, body: Value) -> Response {
+        let mut request = self
+            .http
+            .post(format!("{}/{path}", self.base_url))
+            .json(&body);
+        if let Some(token) = token {
+            request = request.bearer_auth(token);
+        }
+        request.send().unwrap()
+    }
+}
+
+impl Drop for Proxy {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+fn wait_for_port(server_info: &Path) -> u64 {
+    let deadline = Instant::now() + Duration::from_secs(10);
+    loop {
+        if let Ok(contents) = std::fs::read_to_string(server_info)
+            && let Ok(info) = serde_json::from_str::<Value>(&contents)
+            && let Some(port) = info["port"].as_u64()
+        {
+            return port;
+        }
+        assert!(Instant::now() < deadline, "proxy did not start");
+        std::thread::sleep(Duration::from_millis(20));
+    }
+}
+
+/// Reads the whole body, which is when the proxy records its usage, and gives
+/// the proxy a moment to finish the request.
+fn finish(response: Response) -> String {
+    let body = response.text().unwrap();
+    std::thread::sleep(Duration::from_millis(100));
+    body
+}
+
+fn error_type(response: Response) -> String {
+    let body: Value = response.json().unwrap();
+    body["error"]["type"].as_str().unwrap().to_string()
+}
+
+#[test]
+fn forwards_allowed_requests_with_the_upstream_key() {
+    let upstream = Upstream::start();
+    let proxy = Proxy::start(&upstream, None);
+
+    let response = proxy.post("responses", Some("ignored"), json!({"model": "gpt-5"}));
+    assert_eq!(response.status(), 200);
+    assert_eq!(finish(response), RESPONSES_STREAM);
+
+    let response = proxy.post("files", None, json!({}));
+    assert_eq!(response.status(), 403);
+    assert_eq!(error_type(response), "forbidden");
+
+    let requests = upstream.requests();
+    assert_eq!(requests.len(), 1, "{requests:?}");
+    assert_eq!(requests[0].path, "/v1/responses");
+    assert_eq!(
+        requests[0].authorization.as_deref(),
+        Some("Bearer sk-upstream")
+    );
+    assert_eq!(requests[0].body, json!({"model": "gpt-5"}));
+}
+
+#[test]
+fn rejects_unknown_client_tokens() {
+    let upstream = Upstream::start();
+    let proxy = Proxy::start(
+        &upstream,
+        Some("[clients.alice]\ntoken = \"alice-token\"\n"),
+    );
+
+    let response = proxy.post("responses", Some("mallory-token"), json!({}));
+    assert_eq!(response.status(), 401);
+    assert_eq!(error_type(response), "invalid_request_error");
+    let response = proxy.post("responses", None, json!({}));
+    assert_eq!(response.status(), 401);
+
+    let response = proxy.post("responses", Some("alice-token"), json!({}));
+    assert_eq!(response.status(), 200);
+    finish(response);
+    assert_eq!(upstream.requests().len(), 1);
+}
+
+#[test]
+fn quota_counts_usage_until_exhausted() {
+    let upstream = Upstream::start();
+    let proxy = Proxy::start(
+        &upstream,
+        Some("[clients.alice]\ntoken = \"alice-token\"\ntoken_quota = 50\n"),
+    );
+
+    // 42 of 50 tokens used: the next request still starts under the quota.
+    let response = proxy.post("responses", Some("alice-token"), json!({"stream": true}));
+    assert_eq!(response.status(), 200);
+    finish(response);
+    let response = proxy.post("responses", Some("alice-token"), json!({"stream": true}));
+    assert_eq!(response.status(), 200);
+    finish(response);
+
+    let response = proxy.post("responses", Some("alice-token"), json!({"stream": true}));
+    assert_eq!(response.status(), 429);
+    assert_eq!(error_type(response), "usage_limit_reached");
+    assert_eq!(upstream.requests().len(), 2);
+}
+
+#[test]
+fn streamed_chat_completions_of_clients_with_a_quota_report_usage() {
+    let upstream = Upstream::start();
+    let proxy = Proxy::start(
+        &upstream,
+        Some("[clients.alice]\ntoken = \"alice-token\"\ntoken_quota = 50\n"),
+    );
+
+    let response = proxy.post(
+        "chat/completions",
+        Some("alice-token"),
+        json!({"model": "gpt-5", "stream": true}),
+    );
+    assert_eq!(response.status(), 200);
+    finish(response);
+
+    let requests = upstream.requests();
+    assert_eq!(
+        requests[0].body,
+        json!({"model": "gpt-5", "stream": true, "stream_options": {"include_usage": true}})
+    );
+    // The 60 tokens from the usage chunk count against the quota.
+    let response = proxy.post(
+        "chat/completions",
+        Some("alice-token"),
+        json!({"model": "gpt-5", "stream": true}),
+    );
+    assert_eq!(response.status(), 429);
+}
@@ -0,0 +1,2 @@
+// Aggregates all former standalone integration tests as modules.
+mod forwarding;
@@ -1,12 +1,14 @@
 use std::borrow::Cow;
 use std::collections::HashMap;
 use std::sync::Arc;
+use std::sync::Mutex;
 
 use rmcp::ErrorData as McpError;
 use rmcp::ServiceExt;
 use rmcp::handler::server::ServerHandler;
 use rmcp::model::CallToolRequestParam;
 use rmcp::model::CallToolResult;
+use rmcp::model::CancelledNotificationParam;
 use rmcp::model::JsonObject;
 use rmcp::model::ListToolsResult;
 use rmcp::model::PaginatedRequestParam;
@@ -19,20 +21,44 @@ use tokio::task;
 
 #[derive(Clone)]
 struct TestToolServer {
-    tools: Arc<Vec<Tool>>,
+    tools: Arc<Mutex<Vec<Tool>>>,
 }
 pub fn stdio() -> (tokio::io::Stdin, tokio::io::Stdout) {
     (tokio::io::stdin(), tokio::io::stdout())
 }
 impl TestToolServer {
     fn new() -> Self {
-        let tools = vec![Self::echo_tool()];
+        let tools = vec![
+            Self::echo_tool("echo"),
+            Self::no_args_tool("exit", "Exit the server process without replying."),
+            Self::no_args_tool(
+                "add_tool",
+                "Add the `echo_again` tool and notify the client that the tools changed.",
+            ),
+            Self::no_args_tool("wait", "Wait until the client cancels the call."),
+        ];
         Self {
-            tools: Arc::new(tools),
+            tools: Arc::new(Mutex::new(tools)),
         }
     }
 
-    fn echo_tool() -> Tool {
+    fn no_args_tool(name: &'static str, description: &'static str) -> Tool {
+        #[expect(clippy::expect_used)]
+        let schema: JsonObject = serde_json::from_value(json!({
+            "type": "object",
+            "properties": {},
+            "additionalProperties": false
+        }))
+        .expect("tool schema should deserialize");
+
+        Tool::new(
+            Cow::Borrowed(name),
+            Cow::Borrowed(description),
+            Arc::new(schema),
+        )
+    }
+
+    fn echo_tool(name: &'static str) -> Tool {
         #[expect(clippy::expect_used)]
         let schema: JsonObject = serde_json::from_value(json!({
             "type": "object",
@@ -46,7 +72,7 @@ impl TestToolServer {
         .expect("echo tool schema should deserialize");
 
         Tool::new(
-            Cow::Borrowed("echo"),
+            Cow::Borrowed(name),
             Cow::Borrowed("Echo back the provided message and include environment data."),
             Arc::new(schema),
         )
@@ -76,22 +102,63 @@ impl ServerHandler for TestToolServer {
         _request: Option<PaginatedRequestParam>,
         _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
     ) -> impl std::future::Future<Output = Result<ListToolsResult, McpError>> + Send + '_ {
-        let tools = self.tools.clone();
+        let tools = self
+            .tools
+            .lock()
+            .unwrap_or_else(std::sync::PoisonError::into_inner)
+            .clone();
         async move {
             Ok(ListToolsResult {
-                tools: (*tools).clone(),
+                tools,
                 next_cursor: None,
             })
         }
     }
 
+    /// Record the reason of a cancellation in the file named by
+    /// `MCP_CANCELLED_FILE` so that tests can check it was sent.
+    async fn on_cancelled(
+        &self,
+        notification: CancelledNotificationParam,
+        _context: rmcp::service::NotificationContext<rmcp::service::RoleServer>,
+    ) {
+        if let Ok(path) = std::env::var("MCP_CANCELLED_FILE") {
+            let reason = notification.reason.unwrap_or_default();
+            if let Err(err) = std::fs::write(path, reason) {
+                eprintln!("failed to record cancellation: {err}");
+            }
+        }
+    }
+
     async fn call_tool(
         &self,
         request: CallToolRequestParam,
-        _context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
+        context: rmcp::service::RequestContext<rmcp::service::RoleServer>,
     ) -> Result<CallToolResult, McpError> {
         match request.name.as_ref() {
-            "echo" => {
+            "exit" => std::process::exit(0),
+            "add_tool" => {
+                self.tools
+                    .lock()
+                    .unwrap_or_else(std::sync::PoisonError::into_inner)
+                    .push(Self::echo_tool("echo_again"));
+                context
+                    .peer
+                    .notify_tool_list_changed()
+                    .await
+                    .map_err(|err| McpError::internal_error(err.to_string(), None))?;
+                Ok(CallToolResult {
+                    content: Vec::new(),
+                    structured_content: Some(json!({ "added": "echo_again" })),
+                    is_error: Some(false),
+                    meta: None,
+                })
+            }
+            "wait" => {
+                context.ct.cancelled().await;
+                Err(McpError::internal_error("cancelled", None))
+            }
+            "echo" | "echo_again" => {
                 let args: EchoArgs = match request.arguments {
                     Some(arguments) => serde_json::from_value(serde_json::Value::Object(
                         arguments.into_iter().collect(),
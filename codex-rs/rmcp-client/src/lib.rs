@@ -2,4 +2,7 @@ mod logging_client_handler;
 mod rmcp_client;
 mod utils;
 
+pub use codex_mcp_client::shared::ServerRequestFuture;
+pub use codex_mcp_client::shared::ServerRequestHandler;
+pub use codex_mcp_client::shared::ToolCallNotificationHandler;
 pub use rmcp_client::RmcpClient;
@@ -1,47 +1,152 @@
+use std::fmt;
+use std::sync::Arc;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
+
+use codex_mcp_client::shared::ServerRequestHandler;
+use codex_mcp_client::shared::ToolCallHandlers;
+use codex_mcp_client::shared::dispatch_tool_call_notification;
+use mcp_types::CreateMessageRequest;
+use mcp_types::ElicitRequest;
+use mcp_types::ListRootsRequest;
+use mcp_types::LoggingMessageNotification;
+use mcp_types::ModelContextProtocolNotification;
+use mcp_types::ModelContextProtocolRequest;
+use mcp_types::ProgressNotification;
+use mcp_types::ServerNotification;
 use rmcp::ClientHandler;
 use rmcp::RoleClient;
 use rmcp::model::CancelledNotificationParam;
 use rmcp::model::ClientInfo;
 use rmcp::model::CreateElicitationRequestParam;
 use rmcp::model::CreateElicitationResult;
-use rmcp::model::ElicitationAction;
+use rmcp::model::CreateMessageRequestParam;
+use rmcp::model::CreateMessageResult;
+use rmcp::model::ErrorCode;
+use rmcp::model::ListRootsResult;
 use rmcp::model::LoggingLevel;
 use rmcp::model::LoggingMessageNotificationParam;
 use rmcp::model::ProgressNotificationParam;
 use rmcp::model::ResourceUpdatedNotificationParam;
 use rmcp::service::NotificationContext;
 use rmcp::service::RequestContext;
+use serde::Serialize;
+use serde::de::DeserializeOwned;
 use tracing::debug;
 use tracing::error;
 use tracing::info;
 use tracing::warn;
 
-#[derive(Debug, Clone)]
+use crate::utils::convert_to_mcp;
+
+#[derive(Clone)]
 pub(crate) struct LoggingClientHandler {
     client_info: ClientInfo,
+    /// Set when the server sends `notifications/tools/list_changed`.
+    tools_list_changed: Arc<AtomicBool>,
+    /// Answers `roots/list`, `sampling/createMessage` and
+    /// `elicitation/create`. Without one those requests are rejected.
+    server_request_handler: Option<ServerRequestHandler>,
+    /// Receive progress and log notifications for the tool calls in flight.
+    tool_call_handlers: ToolCallHandlers,
+}
+
+impl fmt::Debug for LoggingClientHandler {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LoggingClientHandler")
+            .field("client_info", &self.client_info)
+            .field("tools_list_changed", &self.tools_list_changed)
+            .field(
+                "server_request_handler",
+                &self.server_request_handler.is_some(),
+            )
+            .finish()
+    }
 }
 
 impl LoggingClientHandler {
-    pub(crate) fn new(client_info: ClientInfo) -> Self {
-        Self { client_info }
+    pub(crate) fn new(
+        client_info: ClientInfo,
+        tools_list_changed: Arc<AtomicBool>,
+        server_request_handler: Option<ServerRequestHandler>,
+        tool_call_handlers: ToolCallHandlers,
+    ) -> Self {
+        Self {
+            client_info,
+            tools_list_changed,
+            server_request_handler,
+            tool_call_handlers,
+        }
+    }
+
+    /// Pass a progress or log notification to the handlers of the tool calls
+    /// it concerns: the call with the matching progress token, or every call
+    /// in flight for log messages.
+    fn notify_tool_calls<P: Serialize>(&self, method: &str, params: P) {
+        let notification = match convert_to_mcp::<_, ServerNotification>(serde_json::json!({
+            "method": method,
+            "params": params,
+        })) {
+            Ok(notification) => notification,
+            Err(err) => {
+                warn!("failed to convert `{method}` notification: {err}");
+                return;
+            }
+        };
+        dispatch_tool_call_notification(&self.tool_call_handlers, notification);
+    }
+
+    /// Pass a request from the server to the [`ServerRequestHandler`],
+    /// converting between the SDK types and JSON.
+    async fn forward<P, R>(&self, method: &str, params: Option<P>) -> Result<R, rmcp::ErrorData>
+    where
+        P: Serialize,
+        R: DeserializeOwned,
+    {
+        let Some(handler) = &self.server_request_handler else {
+            return Err(rmcp::ErrorData::new(
+                ErrorCode::METHOD_NOT_FOUND,
+                format!("method not supported: {method}"),
+                None,
+            ));
+        };
+        let params = params
+            .map(serde_json::to_value)
+            .transpose()
+            .map_err(|err| rmcp::ErrorData::internal_error(err.to_string(), None))?;
+        let result = handler(method.to_string(), params).await.map_err(|err| {
+            let code = i32::try_from(err.code).unwrap_or(ErrorCode::INTERNAL_ERROR.0);
+            rmcp::ErrorData::new(ErrorCode(code), err.message, err.data)
+        })?;
+        serde_json::from_value(result).map_err(|err| {
+            rmcp::ErrorData::internal_error(format!("invalid {method} result: {err}"), None)
+        })
     }
 }
 
 impl ClientHandler for LoggingClientHandler {
-    // TODO (CODEX-3571): support elicitations.
+    async fn list_roots(
+        &self,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<ListRootsResult, rmcp::ErrorData> {
+        self.forward::<(), _>(ListRootsRequest::METHOD, None).await
+    }
+
+    async fn create_message(
+        &self,
+        params: CreateMessageRequestParam,
+        _context: RequestContext<RoleClient>,
+    ) -> Result<CreateMessageResult, rmcp::ErrorData> {
+        self.forward(CreateMessageRequest::METHOD, Some(params))
+            .await
+    }
+
     async fn create_elicitation(
         &self,
         request: CreateElicitationRequestParam,
         _context: RequestContext<RoleClient>,
     ) -> Result<CreateElicitationResult, rmcp::ErrorData> {
-        info!(
-            "MCP server requested elicitation ({}). Elicitations are not supported yet. Declining.",
-            request.message
-        );
-        Ok(CreateElicitationResult {
-            action: ElicitationAction::Decline,
-            content: None,
-        })
+        self.forward(ElicitRequest::METHOD, Some(request)).await
     }
 
     async fn on_cancelled(
@@ -64,6 +169,7 @@ impl ClientHandler for LoggingClientHandler {
             "MCP server progress notification (token: {:?}, progress: {}, total: {:?}, message: {:?})",
             params.progress_token, params.progress, params.total, params.message
         );
+        self.notify_tool_calls(ProgressNotification::METHOD, params);
     }
 
     async fn on_resource_updated(
@@ -80,6 +186,7 @@ impl ClientHandler for LoggingClientHandler {
 
     async fn on_tool_list_changed(&self, _context: NotificationContext<RoleClient>) {
         info!("MCP server tool list changed");
+        self.tools_list_changed.store(true, Ordering::SeqCst);
     }
 
     async fn on_prompt_list_changed(&self, _context: NotificationContext<RoleClient>) {
@@ -95,6 +202,7 @@ impl ClientHandler for LoggingClientHandler {
         params: LoggingMessageNotificationParam,
         _context: NotificationContext<RoleClient>,
     ) {
+        self.notify_tool_calls(LoggingMessageNotification::METHOD, &params);
         let LoggingMessageNotificationParam {
             level,
             logger,
@@ -3,10 +3,18 @@ use std::ffi::OsString;
 use std::io;
 use std::process::Stdio;
 use std::sync::Arc;
+use std::sync::OnceLock;
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::Ordering;
 use std::time::Duration;
 
 use anyhow::Result;
 use anyhow::anyhow;
+use codex_mcp_client::shared::InFlightRequest;
+use codex_mcp_client::shared::ServerRequestHandler;
+use codex_mcp_client::shared::ToolCallHandlers;
+use codex_mcp_client::shared::ToolCallNotificationHandler;
+use codex_mcp_client::shared::ToolCallRegistration;
 use futures::FutureExt;
 use mcp_types::CallToolRequestParams;
 use mcp_types::CallToolResult;
@@ -14,15 +22,26 @@ use mcp_types::InitializeRequestParams;
 use mcp_types::InitializeResult;
 use mcp_types::ListToolsRequestParams;
 use mcp_types::ListToolsResult;
+use rmcp::model::CallToolRequest;
 use rmcp::model::CallToolRequestParam;
+use rmcp::model::CancelledNotificationParam;
+use rmcp::model::ClientRequest;
 use rmcp::model::InitializeRequestParam;
 use rmcp::model::PaginatedRequestParam;
+use rmcp::model::RequestId;
+use rmcp::model::ServerResult;
+use rmcp::service::ClientInitializeError;
+use rmcp::service::Peer;
+use rmcp::service::PeerRequestOptions;
 use rmcp::service::RoleClient;
 use rmcp::service::RunningService;
+use rmcp::service::ServiceError;
 use rmcp::service::{self};
+use rmcp::transport::DynamicTransportError;
 use rmcp::transport::StreamableHttpClientTransport;
 use rmcp::transport::child_process::TokioChildProcess;
 use rmcp::transport::streamable_http_client::StreamableHttpClientTransportConfig;
+use rmcp::transport::streamable_http_client::StreamableHttpError;
 use tokio::io::AsyncBufReadExt;
 use tokio::io::BufReader;
 use tokio::process::Command;
@@ -56,6 +75,10 @@ enum ClientState {
 /// https://github.com/modelcontextprotocol/rust-sdk
 pub struct RmcpClient {
     state: Mutex<ClientState>,
+    tools_list_changed: Arc<AtomicBool>,
+    server_request_handler: OnceLock<ServerRequestHandler>,
+    tool_call_handlers: ToolCallHandlers,
+    unauthorized: AtomicBool,
 }
 
 impl RmcpClient {
@@ -100,13 +123,18 @@ impl RmcpClient {
             state: Mutex::new(ClientState::Connecting {
                 transport: Some(PendingTransport::ChildProcess(transport)),
             }),
+            tools_list_changed: Arc::new(AtomicBool::new(false)),
+            server_request_handler: OnceLock::new(),
+            tool_call_handlers: Arc::default(),
+            unauthorized: AtomicBool::new(false),
         })
     }
 
     pub fn new_streamable_http_client(url: String, bearer_token: Option<String>) -> Result<Self> {
         let mut config = StreamableHttpClientTransportConfig::with_uri(url);
         if let Some(token) = bearer_token {
-            config = config.auth_header(format!("Bearer {token}"));
+            // The transport adds the `Bearer` scheme itself.
+            config = config.auth_header(token);
         }
 
         let transport = StreamableHttpClientTransport::from_config(config);
@@ -115,9 +143,22 @@ impl RmcpClient {
             state: Mutex::new(ClientState::Connecting {
                 transport: Some(PendingTransport::StreamableHttp(transport)),
             }),
+            tools_list_changed: Arc::new(AtomicBool::new(false)),
+            server_request_handler: OnceLock::new(),
+            tool_call_handlers: Arc::default(),
+            unauthorized: AtomicBool::new(false),
         })
     }
 
+    /// Install the handler that answers requests from the server. Must be
+    /// called before [`initialize`](Self::initialize); later calls are
+    /// ignored.
+    pub fn set_server_request_handler(&self, handler: ServerRequestHandler) {
+        if self.server_request_handler.set(handler).is_err() {
+            warn!("server request handler already set; ignoring");
+        }
+    }
+
     /// Perform the initialization handshake with the MCP server.
     /// https://modelcontextprotocol.io/specification/2025-06-18/basic/lifecycle#initialization
     pub async fn initialize(
@@ -138,7 +179,12 @@ impl RmcpClient {
         };
 
         let client_info = convert_to_rmcp::<_, InitializeRequestParam>(params.clone())?;
-        let client_handler = LoggingClientHandler::new(client_info);
+        let client_handler = LoggingClientHandler::new(
+            client_info,
+            Arc::clone(&self.tools_list_changed),
+            self.server_request_handler.get().cloned(),
+            Arc::clone(&self.tool_call_handlers),
+        );
         let service_future = match transport {
             PendingTransport::ChildProcess(transport) => {
                 service::serve_client(client_handler.clone(), transport).boxed()
@@ -148,15 +194,20 @@ impl RmcpClient {
             }
         };
 
-        let service = match timeout {
+        let result = match timeout {
             Some(duration) => time::timeout(duration, service_future)
                 .await
-                .map_err(|_| anyhow!("timed out handshaking with MCP server after {duration:?}"))?
-                .map_err(|err| anyhow!("handshaking with MCP server failed: {err}"))?,
-            None => service_future
-                .await
-                .map_err(|err| anyhow!("handshaking with MCP server failed: {err}"))?,
+                .map_err(|_| anyhow!("timed out handshaking with MCP server after {duration:?}"))?,
+            None => service_future.await,
         };
+        let service = result.map_err(|err| {
+            if let ClientInitializeError::TransportError { error, .. } = &err
+                && is_unauthorized_error(error)
+            {
+                self.unauthorized.store(true, Ordering::SeqCst);
+            }
+            anyhow!("handshaking with MCP server failed: {err}")
+        })?;
 
         let initialize_result_rmcp = service
             .peer()
@@ -184,23 +235,110 @@ impl RmcpClient {
             .map(convert_to_rmcp::<_, PaginatedRequestParam>)
             .transpose()?;
 
-        let fut = service.list_tools(rmcp_params);
+        let fut = async {
+            let result = service.list_tools(rmcp_params).await;
+            self.record_auth_failure(&result);
+            result
+        };
         let result = run_with_timeout(fut, timeout, "tools/list").await?;
         convert_to_mcp(result)
     }
 
+    /// Call a tool. With `on_notification`, the handler receives the
+    /// server's progress and log notifications until the call returns. If the
+    /// call times out or the returned future is dropped before the server
+    /// replies, the server is sent `notifications/cancelled`.
     pub async fn call_tool(
         &self,
         name: String,
         arguments: Option<serde_json::Value>,
         timeout: Option<Duration>,
+        on_notification: Option<ToolCallNotificationHandler>,
     ) -> Result<CallToolResult> {
         let service = self.service().await?;
         let params = CallToolRequestParams { arguments, name };
         let rmcp_params: CallToolRequestParam = convert_to_rmcp(params)?;
-        let fut = service.call_tool(rmcp_params);
-        let rmcp_result = run_with_timeout(fut, timeout, "tools/call").await?;
-        convert_call_tool_result(rmcp_result)
+        let request = ClientRequest::CallToolRequest(CallToolRequest {
+            method: Default::default(),
+            params: rmcp_params,
+            extensions: Default::default(),
+        });
+        let handle = service
+            .send_request_with_option(request, PeerRequestOptions::no_options())
+            .await
+            .map_err(|err| anyhow!("tools/call failed: {err}"))?;
+
+        let mut in_flight = {
+            let peer = handle.peer.clone();
+            let request_id = handle.id.clone();
+            InFlightRequest::new(move |reason| cancel_request(peer, request_id, reason))
+        };
+        let _registration = match on_notification {
+            Some(handler) => Some(ToolCallRegistration::new(
+                &self.tool_call_handlers,
+                convert_to_mcp(handle.progress_token.clone())?,
+                handler,
+            )),
+            None => None,
+        };
+
+        let response = handle.await_response();
+        let result = match timeout {
+            Some(duration) => match time::timeout(duration, response).await {
+                Ok(result) => result,
+                Err(_) => {
+                    in_flight.set_reason("request timed out");
+                    return Err(anyhow!("timed out awaiting tools/call after {duration:?}"));
+                }
+            },
+            None => response.await,
+        };
+        in_flight.finish();
+        self.record_auth_failure(&result);
+        match result.map_err(|err| anyhow!("tools/call failed: {err}"))? {
+            ServerResult::CallToolResult(rmcp_result) => convert_call_tool_result(rmcp_result),
+            other => Err(anyhow!("unexpected response to tools/call: {other:?}")),
+        }
+    }
+
+    /// Whether the connection to the server has been lost, e.g. because the
+    /// server process exited. A client that has not finished initializing is
+    /// not considered closed.
+    pub async fn is_closed(&self) -> bool {
+        let guard = self.state.lock().await;
+        match &*guard {
+            ClientState::Ready { service } => service.peer().is_transport_closed(),
+            ClientState::Connecting { .. } => false,
+        }
+    }
+
+    /// Whether the server rejected the bearer token with `401 Unauthorized`.
+    /// The token of a running client cannot be replaced; connect again with a
+    /// new one.
+    pub fn is_unauthorized(&self) -> bool {
+        self.unauthorized.load(Ordering::SeqCst)
+    }
+
+    /// Whether the server reported a change to its tools since the last call.
+    pub fn take_tools_list_changed(&self) -> bool {
+        self.tools_list_changed.swap(false, Ordering::SeqCst)
+    }
+
+    /// Tell the server that the roots returned by `roots/list` changed.
+    pub async fn notify_roots_list_changed(&self) -> Result<()> {
+        let service = self.service().await?;
+        service
+            .notify_roots_list_changed()
+            .await
+            .map_err(|err| anyhow!("notifications/roots/list_changed failed: {err}"))
+    }
+
+    fn record_auth_failure<T>(&self, result: &Result<T, ServiceError>) {
+        if let Err(ServiceError::TransportSend(err)) = result
+            && is_unauthorized_error(err)
+        {
+            self.unauthorized.store(true, Ordering::SeqCst);
+        }
     }
 
     async fn service(&self) -> Result<Arc<RunningService<RoleClient, LoggingClientHandler>>> {
@@ -211,3 +349,34 @@ impl RmcpClient {
         }
     }
 }
+
+/// Whether `err` is the streamable HTTP transport reporting `401 Unauthorized`.
+fn is_unauthorized_error(err: &DynamicTransportError) -> bool {
+    match err
+        .error
+        .downcast_ref::<StreamableHttpError<reqwest::Error>>()
+    {
+        Some(StreamableHttpError::AuthRequired(_)) => true,
+        Some(StreamableHttpError::Client(err)) => {
+            err.status() == Some(reqwest::StatusCode::UNAUTHORIZED)
+        }
+        _ => false,
+    }
+}
+
+/// Tell the server to stop working on `request_id`, which the caller stopped
+/// waiting for.
+fn cancel_request(peer: Peer<RoleClient>, request_id: RequestId, reason: &'static str) {
+    let Ok(runtime) = tokio::runtime::Handle::try_current() else {
+        return;
+    };
+    let params = CancelledNotificationParam {
+        request_id,
+        reason: Some(reason.to_string()),
+    };
+    runtime.spawn(async move {
+        if let Err(err) = peer.notify_cancelled(params).await {
+            warn!("failed to send notifications/cancelled: {err}");
+        }
+    });
+}
@@ -1,7 +1,9 @@
 use crate::app_backtrack::BacktrackState;
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
+use crate::app_tabs::TabsState;
 use crate::chatwidget::ChatWidget;
+use crate::diff_viewer::parse_unified_diff;
 use crate::file_search::FileSearchManager;
 use crate::history_cell::HistoryCell;
 use crate::pager_overlay::Overlay;
@@ -13,9 +15,10 @@ use codex_core::AuthManager;
 use codex_core::ConversationManager;
 use codex_core::config::Config;
 use codex_core::config::persist_model_selection;
-use codex_core::model_family::find_family_for_model;
+use codex_core::model_family::find_family_for_configured_model;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
+use codex_git_tooling::SessionWorktree;
 use codex_protocol::ConversationId;
 use color_eyre::eyre::Result;
 use color_eyre::eyre::WrapErr;
@@ -38,6 +41,8 @@ use tokio::sync::mpsc::unbounded_channel;
 pub struct AppExitInfo {
     pub token_usage: TokenUsage,
     pub conversation_id: Option<ConversationId>,
+    /// Worktree the session ran in when `--worktree` was used.
+    pub session_worktree: Option<SessionWorktree>,
 }
 
 pub(crate) struct App {
@@ -54,7 +59,7 @@ pub(crate) struct App {
 
     pub(crate) transcript_cells: Vec<Arc<dyn HistoryCell>>,
 
-    // Pager overlay state (Transcript or Static like Diff)
+    // Pager overlay state (Transcript, Static or the Diff viewer)
     pub(crate) overlay: Option<Overlay>,
     pub(crate) deferred_history_lines: Vec<Line<'static>>,
     has_emitted_history_lines: bool,
@@ -66,6 +71,9 @@ pub(crate) struct App {
 
     // Esc-backtracking state grouped
     pub(crate) backtrack: crate::app_backtrack::BacktrackState,
+
+    /// Conversation tabs; the active one is `chat_widget`/`transcript_cells`.
+    pub(crate) tabs: TabsState,
 }
 
 impl App {
@@ -86,12 +94,14 @@ impl App {
 
         let enhanced_keys_supported = tui.enhanced_keys_supported();
 
+        let tabs = TabsState::default();
+        let tab_event_tx = app_event_tx.for_tab(tabs.active_id());
         let chat_widget = match resume_selection {
             ResumeSelection::StartFresh | ResumeSelection::Exit => {
                 let init = crate::chatwidget::ChatWidgetInit {
                     config: config.clone(),
                     frame_requester: tui.frame_requester(),
-                    app_event_tx: app_event_tx.clone(),
+                    app_event_tx: tab_event_tx.clone(),
                     initial_prompt: initial_prompt.clone(),
                     initial_images: initial_images.clone(),
                     enhanced_keys_supported,
@@ -113,7 +123,7 @@ impl App {
                 let init = crate::chatwidget::ChatWidgetInit {
                     config: config.clone(),
                     frame_requester: tui.frame_requester(),
-                    app_event_tx: app_event_tx.clone(),
+                    app_event_tx: tab_event_tx.clone(),
                     initial_prompt: initial_prompt.clone(),
                     initial_images: initial_images.clone(),
                     enhanced_keys_supported,
@@ -144,6 +154,7 @@ impl App {
             has_emitted_history_lines: false,
             commit_anim_running: Arc::new(AtomicBool::new(false)),
             backtrack: BacktrackState::default(),
+            tabs,
         };
 
         let tui_events = tui.event_stream();
@@ -163,6 +174,7 @@ impl App {
         Ok(AppExitInfo {
             token_usage: app.token_usage(),
             conversation_id: app.chat_widget.conversation_id(),
+            session_worktree: None,
         })
     }
 
@@ -210,18 +222,26 @@ impl App {
     }
 
     async fn handle_event(&mut self, tui: &mut tui::Tui, event: AppEvent) -> Result<bool> {
+        let event = match event {
+            AppEvent::Tab { tab, event } => match self.route_tab_event(tab, *event) {
+                Some(event) => event,
+                None => return Ok(true),
+            },
+            event => event,
+        };
         match event {
             AppEvent::NewSession => {
                 let init = crate::chatwidget::ChatWidgetInit {
                     config: self.config.clone(),
                     frame_requester: tui.frame_requester(),
-                    app_event_tx: self.app_event_tx.clone(),
+                    app_event_tx: self.active_tab_sender(),
                     initial_prompt: None,
                     initial_images: Vec::new(),
                     enhanced_keys_supported: self.enhanced_keys_supported,
                     auth_manager: self.auth_manager.clone(),
                 };
                 self.chat_widget = ChatWidget::new(init, self.server.clone());
+                self.refresh_tab_indicator();
                 tui.frame_requester().schedule_frame();
             }
             AppEvent::InsertHistoryCell(cell) => {
@@ -270,7 +290,7 @@ impl App {
                 self.commit_anim_running.store(false, Ordering::Release);
             }
             AppEvent::CommitTick => {
-                self.chat_widget.on_commit_tick();
+                self.on_commit_tick_all_tabs();
             }
             AppEvent::CodexEvent(event) => {
                 self.chat_widget.handle_codex_event(event);
@@ -287,15 +307,22 @@ impl App {
                 self.chat_widget.on_diff_complete();
                 // Enter alternate screen using TUI helper and build pager lines
                 let _ = tui.enter_alt_screen();
-                let pager_lines: Vec<ratatui::text::Line<'static>> = if text.trim().is_empty() {
-                    vec!["No changes detected.".italic().into()]
+                let files = parse_unified_diff(&text);
+                self.overlay = Some(if files.is_empty() {
+                    let pager_lines: Vec<ratatui::text::Line<'static>> = if text.trim().is_empty() {
+                        vec!["No changes detected.".italic().into()]
+                    } else {
+                        text.lines().map(ansi_escape_line).collect()
+                    };
+                    Overlay::new_static_with_title(pager_lines, "D I F F".to_string())
                 } else {
-                    text.lines().map(ansi_escape_line).collect()
-                };
-                self.overlay = Some(Overlay::new_static_with_title(
-                    pager_lines,
-                    "D I F F".to_string(),
-                ));
+                    Overlay::new_diff(files, "D I F F".to_string())
+                });
+                tui.frame_requester().schedule_frame();
+            }
+            AppEvent::OpenDiffViewer { title, files } => {
+                let _ = tui.enter_alt_screen();
+                self.overlay = Some(Overlay::new_diff(files, title));
                 tui.frame_requester().schedule_frame();
             }
             AppEvent::StartFileSearch(query) => {
@@ -312,7 +339,8 @@ impl App {
             AppEvent::UpdateModel(model) => {
                 self.chat_widget.set_model(&model);
                 self.config.model = model.clone();
-                if let Some(family) = find_family_for_model(&model) {
+                if let Some(family) = find_family_for_configured_model(&model, &self.config.models)
+                {
                     self.config.model_family = family;
                 }
             }
@@ -363,6 +391,31 @@ impl App {
             AppEvent::OpenReviewCustomPrompt => {
                 self.chat_widget.show_review_custom_prompt();
             }
+            AppEvent::Tab { .. } => {
+                tracing::warn!("ignoring nested tab event");
+            }
+            AppEvent::OpenTabsPopup => {
+                let tabs = self.tab_summaries();
+                self.chat_widget.open_tabs_popup(tabs);
+            }
+            AppEvent::NewTab => {
+                self.open_new_tab(tui);
+            }
+            AppEvent::ResumeInNewTab => {
+                self.resume_in_new_tab(tui).await;
+            }
+            AppEvent::SwitchTab(id) => {
+                self.switch_to_tab(tui, id);
+            }
+            AppEvent::OpenRenameTabPrompt => {
+                self.open_rename_tab_prompt();
+            }
+            AppEvent::RenameTab(name) => {
+                self.rename_active_tab(name);
+            }
+            AppEvent::CloseTab => {
+                self.close_active_tab(tui).await;
+            }
         }
         Ok(true)
     }
@@ -389,6 +442,16 @@ impl App {
                 self.overlay = Some(Overlay::new_transcript(self.transcript_cells.clone()));
                 tui.frame_requester().schedule_frame();
             }
+            // Alt+1..9 jumps straight to that conversation tab.
+            KeyEvent {
+                code: KeyCode::Char(c @ '1'..='9'),
+                modifiers: crossterm::event::KeyModifiers::ALT,
+                kind: KeyEventKind::Press,
+                ..
+            } => {
+                let index = c as usize - '1' as usize;
+                self.switch_to_tab_index(tui, index);
+            }
             // Esc primes/advances backtracking only in normal (not working) mode
             // with an empty composer. In any other state, forward Esc so the
             // active UI (e.g. status indicator, modals, popups) handles it.
@@ -437,7 +500,7 @@ impl App {
 }
 
 #[cfg(test)]
-mod tests {
+pub(crate) mod tests {
     use super::*;
     use crate::app_backtrack::BacktrackState;
     use crate::app_backtrack::user_count;
@@ -457,7 +520,7 @@ mod tests {
     use std::sync::Arc;
     use std::sync::atomic::AtomicBool;
 
-    fn make_test_app() -> App {
+    pub(crate) fn make_test_app() -> App {
         let (chat_widget, app_event_tx, _rx, _op_rx) = make_chatwidget_manual_with_sender();
         let config = chat_widget.config_ref().clone();
 
@@ -483,6 +546,7 @@ mod tests {
             enhanced_keys_supported: false,
             commit_anim_running: Arc::new(AtomicBool::new(false)),
             backtrack: BacktrackState::default(),
+            tabs: TabsState::default(),
         }
     }
 
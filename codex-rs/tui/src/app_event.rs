@@ -4,6 +4,8 @@ use codex_core::protocol::ConversationPathResponseEvent;
 use codex_core::protocol::Event;
 use codex_file_search::FileMatch;
 
+use crate::app_tabs::TabId;
+use crate::diff_viewer::FileDiff;
 use crate::history_cell::HistoryCell;
 
 use codex_core::protocol::AskForApproval;
@@ -41,6 +43,12 @@ pub(crate) enum AppEvent {
     /// Result of computing a `/diff` command.
     DiffResult(String),
 
+    /// Open the interactive diff viewer over the given files.
+    OpenDiffViewer {
+        title: String,
+        files: Vec<FileDiff>,
+    },
+
     InsertHistoryCell(Box<dyn HistoryCell>),
 
     StartCommitAnimation,
@@ -76,4 +84,32 @@ pub(crate) enum AppEvent {
 
     /// Open the custom prompt option from the review popup.
     OpenReviewCustomPrompt,
+
+    /// An event raised by the conversation in `tab`. Events from the active
+    /// tab are handled as usual; the rest update that tab in the background.
+    Tab {
+        tab: TabId,
+        event: Box<AppEvent>,
+    },
+
+    /// Show the `/tabs` popup listing the open conversations.
+    OpenTabsPopup,
+
+    /// Open a fresh conversation in a new tab and switch to it.
+    NewTab,
+
+    /// Pick a previous session and resume it in a new tab.
+    ResumeInNewTab,
+
+    /// Switch to the given tab.
+    SwitchTab(TabId),
+
+    /// Ask for a new name for the current tab.
+    OpenRenameTabPrompt,
+
+    /// Rename the current tab; an empty name restores the default title.
+    RenameTab(String),
+
+    /// Shut down the current tab's conversation and close it.
+    CloseTab,
 }
@@ -0,0 +1,549 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+
+use crate::app::App;
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+use crate::bottom_pane::TabIndicator;
+use crate::chatwidget::ChatWidget;
+use crate::chatwidget::ChatWidgetInit;
+use crate::history_cell::HistoryCell;
+use crate::history_cell::UserHistoryCell;
+use crate::resume_picker::ResumeSelection;
+use crate::resume_picker::run_resume_picker;
+use crate::text_formatting::truncate_text;
+use crate::tui;
+use codex_core::protocol::EventMsg;
+use codex_core::protocol::Op;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+
+/// Longest default tab title derived from the first prompt.
+const TAB_TITLE_MAX_GRAPHEMES: usize = 40;
+
+const DEFAULT_TAB_TITLE: &str = "new conversation";
+
+/// Stable identifier of a conversation tab. Ids are never reused, so events
+/// still in flight for a closed tab cannot be attributed to a newer one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub(crate) struct TabId(u64);
+
+/// Widget and transcript of a tab that is not currently displayed.
+struct ParkedTab {
+    chat_widget: ChatWidget,
+    transcript_cells: Vec<Arc<dyn HistoryCell>>,
+}
+
+struct ConversationTab {
+    id: TabId,
+    /// Name given via "Rename tab"; the first prompt is used otherwise.
+    name: Option<String>,
+    /// `None` for the active tab, whose state lives directly on `App`.
+    parked: Option<ParkedTab>,
+    /// Set when the tab received an approval request while in the background.
+    awaiting_approval: bool,
+}
+
+/// Aggregates the conversation tabs open in this UI session.
+///
+/// The active tab's `ChatWidget` and transcript stay in `App::chat_widget` and
+/// `App::transcript_cells` so the rest of the app is unaware of tabs; the
+/// others are parked here and swapped in on switch.
+pub(crate) struct TabsState {
+    tabs: Vec<ConversationTab>,
+    active: usize,
+    next_id: u64,
+    /// Tabs whose stream is currently animating. The shared commit animation
+    /// keeps running until all of them stop.
+    animating: HashSet<TabId>,
+}
+
+impl Default for TabsState {
+    fn default() -> Self {
+        Self {
+            tabs: vec![ConversationTab {
+                id: TabId(0),
+                name: None,
+                parked: None,
+                awaiting_approval: false,
+            }],
+            active: 0,
+            next_id: 1,
+            animating: HashSet::new(),
+        }
+    }
+}
+
+impl TabsState {
+    pub(crate) fn active_id(&self) -> TabId {
+        self.tabs[self.active].id
+    }
+
+    fn position(&self, id: TabId) -> Option<usize> {
+        self.tabs.iter().position(|tab| tab.id == id)
+    }
+
+    fn allocate_id(&mut self) -> TabId {
+        let id = TabId(self.next_id);
+        self.next_id += 1;
+        id
+    }
+}
+
+/// Row of the `/tabs` popup.
+pub(crate) struct TabSummary {
+    pub(crate) id: TabId,
+    pub(crate) title: String,
+    pub(crate) is_active: bool,
+    pub(crate) awaiting_approval: bool,
+    pub(crate) is_working: bool,
+}
+
+impl App {
+    /// Sender for widgets that belong to the active tab.
+    pub(crate) fn active_tab_sender(&self) -> AppEventSender {
+        self.app_event_tx.for_tab(self.tabs.active_id())
+    }
+
+    /// Resolve an event raised by `tab`. Returns the inner event when it
+    /// belongs to the active tab and should be handled as usual; background
+    /// tabs are updated in place and events from closed tabs are dropped.
+    pub(crate) fn route_tab_event(&mut self, tab: TabId, event: AppEvent) -> Option<AppEvent> {
+        match event {
+            AppEvent::StartCommitAnimation => {
+                if !self.tabs.animating.insert(tab) {
+                    return None;
+                }
+                return Some(AppEvent::StartCommitAnimation);
+            }
+            AppEvent::StopCommitAnimation => {
+                self.tabs.animating.remove(&tab);
+                return self
+                    .tabs
+                    .animating
+                    .is_empty()
+                    .then_some(AppEvent::StopCommitAnimation);
+            }
+            _ => {}
+        }
+
+        let idx = self.tabs.position(tab)?;
+        if idx == self.tabs.active {
+            return Some(event);
+        }
+
+        let entry = &mut self.tabs.tabs[idx];
+        let parked = entry.parked.as_mut()?;
+        match event {
+            AppEvent::CodexEvent(ev) => {
+                let needs_approval = matches!(
+                    ev.msg,
+                    EventMsg::ExecApprovalRequest(_)
+                        | EventMsg::ApplyPatchApprovalRequest(_)
+                        | EventMsg::McpSamplingRequest(_)
+                        | EventMsg::McpElicitationRequest(_)
+                );
+                parked.chat_widget.handle_codex_event(ev);
+                if needs_approval && !entry.awaiting_approval {
+                    entry.awaiting_approval = true;
+                    self.refresh_tab_indicator();
+                }
+            }
+            AppEvent::InsertHistoryCell(cell) => {
+                parked.transcript_cells.push(cell.into());
+            }
+            AppEvent::CodexOp(op) => parked.chat_widget.submit_op(op),
+            // Everything else only matters for the tab the user is looking at.
+            _ => {}
+        }
+        None
+    }
+
+    /// Advance stream animations for every tab, not just the visible one.
+    pub(crate) fn on_commit_tick_all_tabs(&mut self) {
+        self.chat_widget.on_commit_tick();
+        for parked in self.tabs.tabs.iter_mut().filter_map(|t| t.parked.as_mut()) {
+            parked.chat_widget.on_commit_tick();
+        }
+    }
+
+    pub(crate) fn tab_summaries(&self) -> Vec<TabSummary> {
+        self.tabs
+            .tabs
+            .iter()
+            .enumerate()
+            .map(|(idx, tab)| {
+                let is_active = idx == self.tabs.active;
+                let (cells, is_working) = match &tab.parked {
+                    Some(parked) => (
+                        parked.transcript_cells.as_slice(),
+                        parked.chat_widget.is_task_running(),
+                    ),
+                    None => (
+                        self.transcript_cells.as_slice(),
+                        self.chat_widget.is_task_running(),
+                    ),
+                };
+                TabSummary {
+                    id: tab.id,
+                    title: tab_title(tab.name.as_deref(), cells),
+                    is_active,
+                    awaiting_approval: tab.awaiting_approval,
+                    is_working,
+                }
+            })
+            .collect()
+    }
+
+    /// Swap the tab at `index` into `App`, parking the current one. Does not
+    /// touch the terminal; see `switch_to_tab_index`.
+    pub(crate) fn activate_tab(&mut self, index: usize) {
+        if index == self.tabs.active || index >= self.tabs.tabs.len() {
+            return;
+        }
+        let Some(incoming) = self.tabs.tabs[index].parked.take() else {
+            return;
+        };
+        let outgoing_widget = std::mem::replace(&mut self.chat_widget, incoming.chat_widget);
+        let outgoing_cells =
+            std::mem::replace(&mut self.transcript_cells, incoming.transcript_cells);
+
+        let previous = &mut self.tabs.tabs[self.tabs.active];
+        previous.awaiting_approval = outgoing_widget.is_awaiting_approval();
+        previous.parked = Some(ParkedTab {
+            chat_widget: outgoing_widget,
+            transcript_cells: outgoing_cells,
+        });
+
+        self.tabs.tabs[index].awaiting_approval = false;
+        self.tabs.active = index;
+        self.reset_backtrack_state();
+        self.refresh_tab_indicator();
+    }
+
+    /// Switch to the tab at `index` and replay its transcript into the
+    /// scrollback so the conversation reads top to bottom.
+    pub(crate) fn switch_to_tab_index(&mut self, tui: &mut tui::Tui, index: usize) {
+        if index == self.tabs.active || index >= self.tabs.tabs.len() {
+            return;
+        }
+        self.activate_tab(index);
+        let title = tab_title(
+            self.tabs.tabs[index].name.as_deref(),
+            &self.transcript_cells,
+        );
+        tui.insert_history_lines(vec![
+            Line::from(""),
+            Line::from(vec![
+                "── ".dim(),
+                format!("tab {}: ", index + 1).dim(),
+                title.bold(),
+                " ──".dim(),
+            ]),
+        ]);
+        self.render_transcript_once(tui);
+        tui.frame_requester().schedule_frame();
+    }
+
+    pub(crate) fn switch_to_tab(&mut self, tui: &mut tui::Tui, id: TabId) {
+        if let Some(index) = self.tabs.position(id) {
+            self.switch_to_tab_index(tui, index);
+        }
+    }
+
+    /// Insert `chat_widget` as a new tab right after the active one and
+    /// switch to it.
+    fn push_tab(&mut self, tui: &mut tui::Tui, id: TabId, chat_widget: ChatWidget) {
+        let index = self.tabs.active + 1;
+        self.tabs.tabs.insert(
+            index,
+            ConversationTab {
+                id,
+                name: None,
+                parked: Some(ParkedTab {
+                    chat_widget,
+                    transcript_cells: Vec::new(),
+                }),
+                awaiting_approval: false,
+            },
+        );
+        self.switch_to_tab_index(tui, index);
+    }
+
+    fn new_tab_init(&self, tui: &tui::Tui, id: TabId) -> ChatWidgetInit {
+        ChatWidgetInit {
+            config: self.config.clone(),
+            frame_requester: tui.frame_requester(),
+            app_event_tx: self.app_event_tx.for_tab(id),
+            initial_prompt: None,
+            initial_images: Vec::new(),
+            enhanced_keys_supported: self.enhanced_keys_supported,
+            auth_manager: self.auth_manager.clone(),
+        }
+    }
+
+    pub(crate) fn open_new_tab(&mut self, tui: &mut tui::Tui) {
+        let id = self.tabs.allocate_id();
+        let chat_widget = ChatWidget::new(self.new_tab_init(tui, id), self.server.clone());
+        self.push_tab(tui, id, chat_widget);
+    }
+
+    /// Let the user pick a previous session and continue it in a new tab.
+    pub(crate) async fn resume_in_new_tab(&mut self, tui: &mut tui::Tui) {
+        let selection = match run_resume_picker(tui, &self.config.codex_home).await {
+            Ok(selection) => selection,
+            Err(err) => {
+                self.chat_widget
+                    .add_error_message(format!("Failed to open the resume picker: {err}"));
+                return;
+            }
+        };
+        match selection {
+            ResumeSelection::Resume(path) => {
+                let resumed = match self
+                    .server
+                    .resume_conversation_from_rollout(
+                        self.config.clone(),
+                        path.clone(),
+                        self.auth_manager.clone(),
+                    )
+                    .await
+                {
+                    Ok(resumed) => resumed,
+                    Err(err) => {
+                        self.chat_widget.add_error_message(format!(
+                            "Failed to resume session from {}: {err}",
+                            path.display()
+                        ));
+                        return;
+                    }
+                };
+                let id = self.tabs.allocate_id();
+                let chat_widget = ChatWidget::new_from_existing(
+                    self.new_tab_init(tui, id),
+                    resumed.conversation,
+                    resumed.session_configured,
+                );
+                self.push_tab(tui, id, chat_widget);
+            }
+            ResumeSelection::StartFresh => self.open_new_tab(tui),
+            ResumeSelection::Exit => tui.frame_requester().schedule_frame(),
+        }
+    }
+
+    /// Shut down the active tab's conversation and switch to a neighbour.
+    pub(crate) async fn close_active_tab(&mut self, tui: &mut tui::Tui) {
+        if self.tabs.tabs.len() <= 1 {
+            self.chat_widget
+                .add_info_message("This is the only open tab.".to_string(), None);
+            return;
+        }
+        let closing = self.tabs.active;
+        let closing_id = self.tabs.active_id();
+        let conversation_id = self.chat_widget.conversation_id();
+        self.chat_widget.submit_op(Op::Shutdown);
+
+        let next = if closing + 1 < self.tabs.tabs.len() {
+            closing + 1
+        } else {
+            closing - 1
+        };
+        self.switch_to_tab_index(tui, next);
+        self.tabs.tabs.remove(closing);
+        if closing < self.tabs.active {
+            self.tabs.active -= 1;
+        }
+        if self.tabs.animating.remove(&closing_id) && self.tabs.animating.is_empty() {
+            self.app_event_tx.send(AppEvent::StopCommitAnimation);
+        }
+        if let Some(conversation_id) = conversation_id {
+            let _ = self.server.remove_conversation(&conversation_id).await;
+        }
+        self.refresh_tab_indicator();
+    }
+
+    /// Rename the active tab; an empty name restores the default title.
+    pub(crate) fn rename_active_tab(&mut self, name: String) {
+        let active = self.tabs.active;
+        self.tabs.tabs[active].name = (!name.is_empty()).then_some(name);
+    }
+
+    pub(crate) fn open_rename_tab_prompt(&mut self) {
+        let title = tab_title(
+            self.tabs.tabs[self.tabs.active].name.as_deref(),
+            &self.transcript_cells,
+        );
+        self.chat_widget.show_rename_tab_prompt(title);
+    }
+
+    /// Push the current tab summary to the footer. Nothing is shown while a
+    /// single tab is open.
+    pub(crate) fn refresh_tab_indicator(&mut self) {
+        let count = self.tabs.tabs.len();
+        let indicator = (count > 1).then(|| TabIndicator {
+            active: self.tabs.active + 1,
+            count,
+            awaiting_approval: self
+                .tabs
+                .tabs
+                .iter()
+                .position(|tab| tab.awaiting_approval)
+                .map(|idx| idx + 1),
+        });
+        self.chat_widget.set_tab_indicator(indicator);
+    }
+}
+
+/// Title shown for a tab: its custom name, else the first prompt sent in it.
+fn tab_title(name: Option<&str>, cells: &[Arc<dyn HistoryCell>]) -> String {
+    if let Some(name) = name {
+        return name.to_string();
+    }
+    cells
+        .iter()
+        .find_map(|cell| cell.as_any().downcast_ref::<UserHistoryCell>())
+        .and_then(|cell| cell.message.lines().find(|line| !line.trim().is_empty()))
+        .map(|line| truncate_text(line.trim(), TAB_TITLE_MAX_GRAPHEMES))
+        .unwrap_or_else(|| DEFAULT_TAB_TITLE.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::app::tests::make_test_app;
+    use crate::chatwidget::tests::make_chatwidget_manual_with_sender;
+    use codex_core::protocol::Event;
+    use codex_core::protocol::ExecApprovalRequestEvent;
+    use pretty_assertions::assert_eq;
+    use std::path::PathBuf;
+
+    /// Add a parked tab to `app` and return its id.
+    fn add_background_tab(app: &mut App) -> TabId {
+        let (chat_widget, _tx, _rx, _op_rx) = make_chatwidget_manual_with_sender();
+        let id = app.tabs.allocate_id();
+        app.tabs.tabs.push(ConversationTab {
+            id,
+            name: None,
+            parked: Some(ParkedTab {
+                chat_widget,
+                transcript_cells: Vec::new(),
+            }),
+            awaiting_approval: false,
+        });
+        id
+    }
+
+    fn user_cell(text: &str) -> Box<dyn HistoryCell> {
+        Box::new(UserHistoryCell {
+            message: text.to_string(),
+        })
+    }
+
+    fn approval_request() -> AppEvent {
+        AppEvent::CodexEvent(Event {
+            id: "sub-1".into(),
+            msg: EventMsg::ExecApprovalRequest(ExecApprovalRequestEvent {
+                call_id: "call-1".into(),
+                command: vec!["echo".into(), "hi".into()],
+                cwd: PathBuf::from("."),
+                reason: None,
+            }),
+        })
+    }
+
+    #[test]
+    fn background_events_update_the_parked_tab() {
+        let mut app = make_test_app();
+        let background = add_background_tab(&mut app);
+
+        assert!(
+            app.route_tab_event(background, AppEvent::InsertHistoryCell(user_cell("hello")))
+                .is_none()
+        );
+        assert!(
+            app.route_tab_event(background, approval_request())
+                .is_none()
+        );
+        assert!(app.transcript_cells.is_empty());
+
+        let summaries = app.tab_summaries();
+        assert_eq!(summaries.len(), 2);
+        assert_eq!(summaries[0].title, DEFAULT_TAB_TITLE);
+        assert_eq!(summaries[1].title, "hello");
+        assert!(summaries[1].awaiting_approval);
+        assert!(!summaries[1].is_active);
+
+        // Events for the active tab are handed back to the normal handler.
+        let active = app.tabs.active_id();
+        assert!(matches!(
+            app.route_tab_event(active, AppEvent::NewTab),
+            Some(AppEvent::NewTab)
+        ));
+    }
+
+    #[test]
+    fn activating_a_tab_swaps_widget_and_transcript() {
+        let mut app = make_test_app();
+        app.transcript_cells.push(user_cell("first tab").into());
+        let background = add_background_tab(&mut app);
+        app.route_tab_event(background, AppEvent::InsertHistoryCell(user_cell("second")));
+        app.route_tab_event(background, approval_request());
+
+        app.activate_tab(1);
+        assert_eq!(app.tabs.active_id(), background);
+        assert_eq!(app.transcript_cells.len(), 1);
+        assert!(app.chat_widget.is_awaiting_approval());
+        let summaries = app.tab_summaries();
+        assert_eq!(summaries[0].title, "first tab");
+        assert!(!summaries[1].awaiting_approval);
+
+        // The approval modal stays with its tab when switching away.
+        app.activate_tab(0);
+        assert!(!app.chat_widget.is_awaiting_approval());
+        assert!(app.tab_summaries()[1].awaiting_approval);
+    }
+
+    #[test]
+    fn commit_animation_stops_after_last_tab() {
+        let mut app = make_test_app();
+        let first = app.tabs.active_id();
+        let second = add_background_tab(&mut app);
+
+        assert!(matches!(
+            app.route_tab_event(first, AppEvent::StartCommitAnimation),
+            Some(AppEvent::StartCommitAnimation)
+        ));
+        assert!(matches!(
+            app.route_tab_event(second, AppEvent::StartCommitAnimation),
+            Some(AppEvent::StartCommitAnimation)
+        ));
+        assert!(
+            app.route_tab_event(first, AppEvent::StopCommitAnimation)
+                .is_none()
+        );
+        assert!(matches!(
+            app.route_tab_event(second, AppEvent::StopCommitAnimation),
+            Some(AppEvent::StopCommitAnimation)
+        ));
+    }
+
+    #[test]
+    fn renamed_tabs_use_their_name() {
+        let mut app = make_test_app();
+        app.transcript_cells
+            .push(user_cell("\n  a long first prompt that keeps going and going and going").into());
+        assert_eq!(
+            app.tab_summaries()[0].title,
+            "a long first prompt that keeps going ..."
+        );
+
+        app.rename_active_tab("refactor".to_string());
+        assert_eq!(app.tab_summaries()[0].title, "refactor");
+
+        app.rename_active_tab(String::new());
+        assert_eq!(
+            app.tab_summaries()[0].title,
+            "a long first prompt that keeps going ..."
+        );
+    }
+}
@@ -8,6 +8,7 @@ use crate::bottom_pane::list_selection_view::HeaderLine;
 use crate::bottom_pane::list_selection_view::ListSelectionView;
 use crate::bottom_pane::list_selection_view::SelectionItem;
 use crate::bottom_pane::list_selection_view::SelectionViewParams;
+use crate::diff_viewer::FileDiff;
 use crate::exec_command::strip_bash_lc_and_escape;
 use crate::history_cell;
 use crate::text_formatting::truncate_text;
@@ -17,6 +18,9 @@ use crossterm::event::KeyCode;
 use crossterm::event::KeyEvent;
 use crossterm::event::KeyEventKind;
 use crossterm::event::KeyModifiers;
+use mcp_types::Role;
+use mcp_types::SamplingMessage;
+use mcp_types::SamplingMessageContent;
 use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::Stylize;
@@ -34,6 +38,15 @@ pub(crate) enum ApprovalRequest {
         id: String,
         reason: Option<String>,
         grant_root: Option<PathBuf>,
+        /// Proposed changes, shown in the diff viewer on request.
+        files: Vec<FileDiff>,
+    },
+    McpSampling {
+        id: String,
+        server_name: String,
+        /// The conversation the server wants the model to continue.
+        messages: Vec<SamplingMessage>,
+        system_prompt: Option<String>,
     },
 }
 
@@ -93,9 +106,22 @@ impl ApprovalOverlay {
                 },
             );
         };
-        let (options, title) = match &state.variant {
-            ApprovalVariant::Exec { .. } => (exec_options(), "Allow command?".to_string()),
-            ApprovalVariant::ApplyPatch { .. } => (patch_options(), "Apply changes?".to_string()),
+        let (options, title, footer_hint) = match &state.variant {
+            ApprovalVariant::Exec { .. } => (
+                exec_options(),
+                "Allow command?".to_string(),
+                "Press Enter to confirm or Esc to cancel",
+            ),
+            ApprovalVariant::ApplyPatch { .. } => (
+                patch_options(),
+                "Apply changes?".to_string(),
+                "Press Enter to confirm, D to view the full diff, or Esc to cancel",
+            ),
+            ApprovalVariant::McpSampling { server_name, .. } => (
+                sampling_options(server_name),
+                format!("Let MCP server `{server_name}` use the model?"),
+                "Press Enter to confirm or Esc to cancel",
+            ),
         };
 
         let items = options
@@ -112,7 +138,7 @@ impl ApprovalOverlay {
 
         let params = SelectionViewParams {
             title,
-            footer_hint: Some("Press Enter to confirm or Esc to cancel".to_string()),
+            footer_hint: Some(footer_hint.to_string()),
             items,
             header: state.header.clone(),
             ..Default::default()
@@ -136,6 +162,9 @@ impl ApprovalOverlay {
                 (ApprovalVariant::ApplyPatch { id, .. }, decision) => {
                     self.handle_patch_decision(id, decision);
                 }
+                (ApprovalVariant::McpSampling { id, server_name }, decision) => {
+                    self.handle_sampling_decision(id, server_name, decision);
+                }
             }
         }
 
@@ -162,6 +191,33 @@ impl ApprovalOverlay {
         }));
     }
 
+    fn handle_sampling_decision(&self, id: &str, server_name: &str, decision: ReviewDecision) {
+        self.app_event_tx.send(AppEvent::InsertHistoryCell(Box::new(
+            history_cell::new_user_approval_decision(build_sampling_history_lines(
+                server_name,
+                decision,
+            )),
+        )));
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::McpSamplingApproval {
+                id: id.to_string(),
+                decision,
+            }));
+    }
+
+    fn open_diff_viewer(&self) -> bool {
+        let Some(ApprovalVariant::ApplyPatch { files, .. }) =
+            self.current.as_ref().map(|state| &state.variant)
+        else {
+            return false;
+        };
+        self.app_event_tx.send(AppEvent::OpenDiffViewer {
+            title: "P R O P O S E D   C H A N G E S".to_string(),
+            files: files.clone(),
+        });
+        true
+    }
+
     fn advance_queue(&mut self) {
         if let Some(next) = self.queue.pop() {
             self.set_current(next);
@@ -186,6 +242,9 @@ impl ApprovalOverlay {
             return false;
         }
         let lower = c.to_ascii_lowercase();
+        if lower == 'd' && !self.current_complete && self.open_diff_viewer() {
+            return true;
+        }
         if let Some(idx) = self
             .options
             .iter()
@@ -224,6 +283,9 @@ impl BottomPaneView for ApprovalOverlay {
                 ApprovalVariant::ApplyPatch { id, .. } => {
                     self.handle_patch_decision(id, ReviewDecision::Abort);
                 }
+                ApprovalVariant::McpSampling { id, server_name } => {
+                    self.handle_sampling_decision(id, server_name, ReviewDecision::Abort);
+                }
             }
         }
         self.queue.clear();
@@ -231,6 +293,10 @@ impl BottomPaneView for ApprovalOverlay {
         CancellationEvent::Handled
     }
 
+    fn is_awaiting_approval(&self) -> bool {
+        !self.done
+    }
+
     fn is_complete(&self) -> bool {
         self.done
     }
@@ -296,6 +362,7 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                 id,
                 reason,
                 grant_root,
+                files,
             } => {
                 let mut header = Vec::new();
                 if let Some(reason) = reason
@@ -318,7 +385,34 @@ impl From<ApprovalRequest> for ApprovalRequestState {
                     header.push(HeaderLine::Spacer);
                 }
                 Self {
-                    variant: ApprovalVariant::ApplyPatch { id },
+                    variant: ApprovalVariant::ApplyPatch { id, files },
+                    header,
+                }
+            }
+            ApprovalRequest::McpSampling {
+                id,
+                server_name,
+                messages,
+                system_prompt,
+            } => {
+                let mut header = Vec::new();
+                if let Some(system_prompt) = system_prompt
+                    && !system_prompt.is_empty()
+                {
+                    header.push(HeaderLine::Text {
+                        text: format!("System prompt: {}", truncate_text(&system_prompt, 200)),
+                        italic: true,
+                    });
+                }
+                header.extend(messages.iter().map(|message| HeaderLine::Text {
+                    text: sampling_message_summary(message),
+                    italic: false,
+                }));
+                if !header.is_empty() {
+                    header.push(HeaderLine::Spacer);
+                }
+                Self {
+                    variant: ApprovalVariant::McpSampling { id, server_name },
                     header,
                 }
             }
@@ -328,7 +422,8 @@ impl From<ApprovalRequest> for ApprovalRequestState {
 
 enum ApprovalVariant {
     Exec { id: String, command: Vec<String> },
-    ApplyPatch { id: String },
+    ApplyPatch { id: String, files: Vec<FileDiff> },
+    McpSampling { id: String, server_name: String },
 }
 
 #[derive(Clone)]
@@ -380,6 +475,78 @@ fn patch_options() -> Vec<ApprovalOption> {
     ]
 }
 
+fn sampling_options(server_name: &str) -> Vec<ApprovalOption> {
+    vec![
+        ApprovalOption {
+            label: "Approve".to_string(),
+            description: "(Y) Send this request to the model".to_string(),
+            decision: ReviewDecision::Approved,
+            shortcut: Some('y'),
+        },
+        ApprovalOption {
+            label: "Always approve this session".to_string(),
+            description: format!(
+                "(A) Let `{server_name}` use the model for the rest of the session"
+            ),
+            decision: ReviewDecision::ApprovedForSession,
+            shortcut: Some('a'),
+        },
+        ApprovalOption {
+            label: "Decline".to_string(),
+            description: "(N) Tell the server the request was rejected".to_string(),
+            decision: ReviewDecision::Denied,
+            shortcut: Some('n'),
+        },
+    ]
+}
+
+/// One line per message of a sampling request, e.g. `User: <text>`.
+fn sampling_message_summary(message: &SamplingMessage) -> String {
+    let role = match message.role {
+        Role::User => "User",
+        Role::Assistant => "Assistant",
+    };
+    let content = match &message.content {
+        SamplingMessageContent::TextContent(text) => truncate_text(&text.text, 200),
+        SamplingMessageContent::ImageContent(image) => format!("[image ({})]", image.mime_type),
+        SamplingMessageContent::AudioContent(audio) => format!("[audio ({})]", audio.mime_type),
+    };
+    format!("{role}: {content}")
+}
+
+fn build_sampling_history_lines(server_name: &str, decision: ReviewDecision) -> Vec<Line<'static>> {
+    let server = Span::from(server_name.to_string()).dim();
+    let line = match decision {
+        ReviewDecision::Approved => Line::from(vec![
+            "✔ ".green(),
+            "You ".into(),
+            "approved".bold(),
+            " ".into(),
+            server,
+            " to use the model".into(),
+            " this time".bold(),
+        ]),
+        ReviewDecision::ApprovedForSession => Line::from(vec![
+            "✔ ".green(),
+            "You ".into(),
+            "approved".bold(),
+            " ".into(),
+            server,
+            " to use the model".into(),
+            " for this session".bold(),
+        ]),
+        ReviewDecision::Denied | ReviewDecision::Abort => Line::from(vec![
+            "✗ ".red(),
+            "You ".into(),
+            "declined".bold(),
+            " to let ".into(),
+            server,
+            " use the model".into(),
+        ]),
+    };
+    vec![line]
+}
+
 fn build_exec_history_lines(
     command: Vec<String>,
     decision: ReviewDecision,
@@ -465,6 +632,7 @@ fn exec_snippet(command: &[String]) -> String {
 mod tests {
     use super::*;
     use crate::app_event::AppEvent;
+    use mcp_types::TextContent;
     use tokio::sync::mpsc::unbounded_channel;
 
     fn make_exec_request() -> ApprovalRequest {
@@ -534,6 +702,115 @@ mod tests {
         );
     }
 
+    #[test]
+    fn patch_shortcut_opens_diff_viewer_without_deciding() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let files = crate::diff_viewer::parse_unified_diff(
+            "--- a/foo.txt\n+++ b/foo.txt\n@@ -1 +1 @@\n-old\n+new\n",
+        );
+        let mut view = ApprovalOverlay::new(
+            ApprovalRequest::ApplyPatch {
+                id: "patch".into(),
+                reason: None,
+                grant_root: None,
+                files: files.clone(),
+            },
+            tx,
+        );
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('d'), KeyModifiers::NONE));
+
+        assert!(!view.is_complete());
+        let mut opened = None;
+        while let Ok(ev) = rx.try_recv() {
+            match ev {
+                AppEvent::OpenDiffViewer { files, .. } => opened = Some(files),
+                AppEvent::CodexOp(op) => panic!("unexpected op: {op:?}"),
+                _ => {}
+            }
+        }
+        assert_eq!(opened, Some(files));
+    }
+
+    fn sampling_text(role: Role, text: &str) -> SamplingMessage {
+        SamplingMessage {
+            content: SamplingMessageContent::TextContent(TextContent {
+                annotations: None,
+                text: text.into(),
+                r#type: "text".into(),
+            }),
+            role,
+        }
+    }
+
+    #[test]
+    fn sampling_header_shows_system_prompt_and_every_message() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let view = ApprovalOverlay::new(
+            ApprovalRequest::McpSampling {
+                id: "mcp-sampling-0".into(),
+                server_name: "docs".into(),
+                messages: vec![
+                    sampling_text(Role::User, "What is on the page?"),
+                    sampling_text(Role::Assistant, "A list of names."),
+                    sampling_text(Role::User, "Sort them."),
+                ],
+                system_prompt: Some("Answer briefly.".into()),
+            },
+            tx,
+        );
+        let mut buf = Buffer::empty(Rect::new(0, 0, 80, 12));
+        view.render(Rect::new(0, 0, 80, 12), &mut buf);
+
+        let rendered: Vec<String> = (0..buf.area.height)
+            .map(|row| {
+                (0..buf.area.width)
+                    .map(|col| buf[(col, row)].symbol().to_string())
+                    .collect()
+            })
+            .collect();
+        for expected in [
+            "System prompt: Answer briefly.",
+            "User: What is on the page?",
+            "Assistant: A list of names.",
+            "User: Sort them.",
+        ] {
+            assert!(
+                rendered.iter().any(|line| line.contains(expected)),
+                "expected header to include {expected:?}, got {rendered:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn sampling_decline_sends_denied_without_interrupting() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+        let mut view = ApprovalOverlay::new(
+            ApprovalRequest::McpSampling {
+                id: "mcp-sampling-0".into(),
+                server_name: "docs".into(),
+                messages: vec![sampling_text(Role::User, "Summarize the page")],
+                system_prompt: None,
+            },
+            tx,
+        );
+        view.handle_key_event(KeyEvent::new(KeyCode::Char('n'), KeyModifiers::NONE));
+
+        assert!(view.is_complete());
+        let mut answer = None;
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(Op::McpSamplingApproval { id, decision }) = ev {
+                answer = Some((id, decision));
+            }
+        }
+        assert_eq!(
+            answer,
+            Some(("mcp-sampling-0".to_string(), ReviewDecision::Denied))
+        );
+    }
+
     #[test]
     fn enter_sets_last_selected_index_without_dismissing() {
         let (tx_raw, mut rx) = unbounded_channel::<AppEvent>();
@@ -38,6 +38,12 @@ pub(crate) trait BottomPaneView {
         None
     }
 
+    /// Return `true` while the view is holding an approval request that the
+    /// user has not answered yet.
+    fn is_awaiting_approval(&self) -> bool {
+        false
+    }
+
     /// Try to handle approval request; return the original value if not
     /// consumed.
     fn try_consume_approval_request(
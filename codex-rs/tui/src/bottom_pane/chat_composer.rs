@@ -21,11 +21,14 @@ use super::command_popup::CommandPopup;
 use super::file_search_popup::FileSearchPopup;
 use super::footer::FooterMode;
 use super::footer::FooterProps;
+use super::footer::TabIndicator;
 use super::footer::esc_hint_mode;
 use super::footer::footer_height;
 use super::footer::render_footer;
 use super::footer::reset_mode_after_activity;
 use super::footer::toggle_shortcut_mode;
+use super::history_search_popup::HISTORY_SEARCH_MAX_RESULTS;
+use super::history_search_popup::HistorySearchPopup;
 use super::paste_burst::CharDecision;
 use super::paste_burst::PasteBurst;
 use crate::bottom_pane::paste_burst::FlushResult;
@@ -108,6 +111,7 @@ pub(crate) struct ChatComposer {
     custom_prompts: Vec<CustomPrompt>,
     footer_mode: FooterMode,
     footer_hint_override: Option<Vec<(String, String)>>,
+    tab_indicator: Option<TabIndicator>,
 }
 
 /// Popup state – at most one can be visible at any time.
@@ -115,6 +119,7 @@ enum ActivePopup {
     None,
     Command(CommandPopup),
     File(FileSearchPopup),
+    History(HistorySearchPopup),
 }
 
 const FOOTER_SPACING_HEIGHT: u16 = 0;
@@ -150,6 +155,7 @@ impl ChatComposer {
             custom_prompts: Vec::new(),
             footer_mode: FooterMode::ShortcutPrompt,
             footer_hint_override: None,
+            tab_indicator: None,
         };
         // Apply configuration via the setter to keep side-effects centralized.
         this.set_disable_paste_burst(disable_paste_burst);
@@ -170,6 +176,7 @@ impl ChatComposer {
                 ActivePopup::None => footer_total_height,
                 ActivePopup::Command(c) => c.calculate_required_height(width),
                 ActivePopup::File(c) => c.calculate_required_height(),
+                ActivePopup::History(c) => c.calculate_required_height(),
             }
     }
 
@@ -185,6 +192,7 @@ impl ChatComposer {
                 Constraint::Max(popup.calculate_required_height(area.width))
             }
             ActivePopup::File(popup) => Constraint::Max(popup.calculate_required_height()),
+            ActivePopup::History(popup) => Constraint::Max(popup.calculate_required_height()),
             ActivePopup::None => Constraint::Max(footer_total_height),
         };
         let mut area = area;
@@ -242,6 +250,24 @@ impl ChatComposer {
         true
     }
 
+    /// Integrate the bulk results of a reverse history search. Results are
+    /// dropped when the search popup has since been closed.
+    pub(crate) fn on_history_search_response(
+        &mut self,
+        query: String,
+        log_id: u64,
+        matches: Vec<(usize, String)>,
+    ) -> bool {
+        let Some(texts) = self.history.on_search_response(log_id, matches) else {
+            return false;
+        };
+        let ActivePopup::History(popup) = &mut self.active_popup else {
+            return false;
+        };
+        popup.set_persistent_entries(&query, texts);
+        true
+    }
+
     pub fn handle_paste(&mut self, pasted: String) -> bool {
         let char_count = pasted.chars().count();
         if char_count > LARGE_PASTE_CHAR_THRESHOLD {
@@ -381,9 +407,16 @@ impl ChatComposer {
         let result = match &mut self.active_popup {
             ActivePopup::Command(_) => self.handle_key_event_with_slash_popup(key_event),
             ActivePopup::File(_) => self.handle_key_event_with_file_popup(key_event),
+            ActivePopup::History(_) => self.handle_key_event_with_history_popup(key_event),
             ActivePopup::None => self.handle_key_event_without_popup(key_event),
         };
 
+        // The history search owns the keyboard until it is closed, so the
+        // composer text must not reopen the other popups underneath it.
+        if matches!(self.active_popup, ActivePopup::History(_)) {
+            return result;
+        }
+
         // Update (or hide/show) popup after processing the key.
         self.sync_command_popup();
         if matches!(self.active_popup, ActivePopup::Command(_)) {
@@ -679,6 +712,113 @@ impl ChatComposer {
         }
     }
 
+    /// Open the Ctrl+R reverse history search popup and request the most
+    /// recent persistent entries to seed it.
+    fn open_history_search(&mut self) {
+        self.active_popup =
+            ActivePopup::History(HistorySearchPopup::new(self.history.local_entries()));
+        self.history
+            .search("", HISTORY_SEARCH_MAX_RESULTS, &self.app_event_tx);
+    }
+
+    /// Handle key events when the reverse history search popup is visible.
+    fn handle_key_event_with_history_popup(&mut self, key_event: KeyEvent) -> (InputResult, bool) {
+        if key_event.kind == KeyEventKind::Release {
+            return (InputResult::None, false);
+        }
+        let ActivePopup::History(popup) = &mut self.active_popup else {
+            unreachable!();
+        };
+
+        match key_event {
+            KeyEvent {
+                code: KeyCode::Esc, ..
+            }
+            | KeyEvent {
+                code: KeyCode::Char('g'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                self.active_popup = ActivePopup::None;
+                (InputResult::None, true)
+            }
+            KeyEvent {
+                code: KeyCode::Up, ..
+            }
+            | KeyEvent {
+                code: KeyCode::Char('p'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                popup.move_up();
+                (InputResult::None, true)
+            }
+            KeyEvent {
+                code: KeyCode::Down,
+                ..
+            }
+            | KeyEvent {
+                code: KeyCode::Char('r' | 'n'),
+                modifiers: KeyModifiers::CONTROL,
+                ..
+            } => {
+                popup.move_down();
+                (InputResult::None, true)
+            }
+            KeyEvent {
+                code: KeyCode::Backspace,
+                ..
+            } => {
+                if popup.pop_char() {
+                    let query = popup.query().to_string();
+                    self.history
+                        .search(&query, HISTORY_SEARCH_MAX_RESULTS, &self.app_event_tx);
+                }
+                (InputResult::None, true)
+            }
+            KeyEvent {
+                code: KeyCode::Tab, ..
+            } => {
+                // Edit: load the entry into the composer without sending it.
+                if let Some(text) = popup.selected_text().map(str::to_string) {
+                    self.set_text_content(text);
+                    self.textarea.set_cursor(self.textarea.text().len());
+                }
+                self.active_popup = ActivePopup::None;
+                (InputResult::None, true)
+            }
+            KeyEvent {
+                code: KeyCode::Enter,
+                modifiers: KeyModifiers::NONE,
+                ..
+            } => {
+                // Accept: send the entry exactly as if it had been typed, so
+                // slash commands and custom prompts are dispatched as usual.
+                let selected = popup.selected_text().map(str::to_string);
+                self.active_popup = ActivePopup::None;
+                let Some(text) = selected else {
+                    return (InputResult::None, true);
+                };
+                self.set_text_content(text);
+                self.textarea.set_cursor(self.textarea.text().len());
+                self.sync_command_popup();
+                self.handle_key_event(key_event)
+            }
+            KeyEvent {
+                code: KeyCode::Char(ch),
+                modifiers,
+                ..
+            } if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                popup.push_char(ch);
+                let query = popup.query().to_string();
+                self.history
+                    .search(&query, HISTORY_SEARCH_MAX_RESULTS, &self.app_event_tx);
+                (InputResult::None, true)
+            }
+            _ => (InputResult::None, false),
+        }
+    }
+
     fn is_image_path(path: &str) -> bool {
         let lower = path.to_ascii_lowercase();
         lower.ends_with(".png") || lower.ends_with(".jpg") || lower.ends_with(".jpeg")
@@ -861,6 +1001,15 @@ impl ChatComposer {
                 self.app_event_tx.send(AppEvent::ExitRequest);
                 (InputResult::None, true)
             }
+            KeyEvent {
+                code: KeyCode::Char('r'),
+                modifiers: KeyModifiers::CONTROL,
+                kind: KeyEventKind::Press | KeyEventKind::Repeat,
+                ..
+            } => {
+                self.open_history_search();
+                (InputResult::None, true)
+            }
             // -------------------------------------------------------------
             // History navigation (Up / Down) – only when the composer is not
             // empty or when the cursor is at the correct position, to avoid
@@ -1317,6 +1466,7 @@ impl ChatComposer {
             esc_backtrack_hint: self.esc_backtrack_hint,
             use_shift_enter_hint: self.use_shift_enter_hint,
             is_task_running: self.is_task_running,
+            tab_indicator: self.tab_indicator,
         }
     }
 
@@ -1447,6 +1597,10 @@ impl ChatComposer {
         self.is_task_running = running;
     }
 
+    pub(crate) fn set_tab_indicator(&mut self, indicator: Option<TabIndicator>) {
+        self.tab_indicator = indicator;
+    }
+
     pub(crate) fn set_esc_backtrack_hint(&mut self, show: bool) {
         self.esc_backtrack_hint = show;
         if show {
@@ -1467,6 +1621,9 @@ impl WidgetRef for ChatComposer {
             ActivePopup::File(popup) => {
                 popup.render_ref(popup_rect, buf);
             }
+            ActivePopup::History(popup) => {
+                popup.render_ref(popup_rect, buf);
+            }
             ActivePopup::None => {
                 let footer_props = self.footer_props();
                 let custom_height = self.custom_footer_height();
@@ -1592,6 +1749,7 @@ mod tests {
     use crate::bottom_pane::chat_composer::LARGE_PASTE_CHAR_THRESHOLD;
     use crate::bottom_pane::prompt_args::extract_positional_args_for_prompt_line;
     use crate::bottom_pane::textarea::TextArea;
+    use codex_core::protocol::Op;
     use tokio::sync::mpsc::unbounded_channel;
 
     #[test]
@@ -3172,4 +3330,115 @@ mod tests {
         assert_eq!(composer.textarea.text(), "z".repeat(count));
         assert!(composer.pending_pastes.is_empty());
     }
+
+    #[test]
+    fn ctrl_r_searches_history_and_tab_loads_entry_for_editing() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+        composer.set_history_metadata(1, 2);
+        composer.history.record_local_submission("run the tests");
+
+        composer.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        assert!(matches!(composer.active_popup, ActivePopup::History(_)));
+        let AppEvent::CodexOp(op) = rx.try_recv().expect("expected search request") else {
+            panic!("unexpected event variant");
+        };
+        assert_eq!(
+            op,
+            Op::SearchHistoryRequest {
+                query: String::new(),
+                log_id: 1,
+                max_results: HISTORY_SEARCH_MAX_RESULTS,
+            }
+        );
+
+        // Typed characters go to the search query, not the composer.
+        composer.handle_key_event(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE));
+        composer.handle_key_event(KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE));
+        assert_eq!(composer.textarea.text(), "");
+        let AppEvent::CodexOp(Op::SearchHistoryRequest { query, .. }) =
+            rx.try_recv().expect("expected search request")
+        else {
+            panic!("unexpected event variant");
+        };
+        assert_eq!(query, "f");
+
+        assert!(composer.on_history_search_response(
+            "fb".to_string(),
+            1,
+            vec![
+                (1, "fix the build".to_string()),
+                (0, "find bugs".to_string())
+            ],
+        ));
+
+        composer.handle_key_event(KeyEvent::new(KeyCode::Down, KeyModifiers::NONE));
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE));
+        assert_eq!(result, InputResult::None);
+        assert!(matches!(composer.active_popup, ActivePopup::None));
+        assert_eq!(composer.textarea.text(), "fix the build");
+        assert_eq!(composer.textarea.cursor(), "fix the build".len());
+    }
+
+    #[test]
+    fn ctrl_r_enter_submits_selected_entry_and_esc_cancels() {
+        let (tx, _rx) = unbounded_channel::<AppEvent>();
+        let sender = AppEventSender::new(tx);
+        let mut composer = ChatComposer::new(
+            true,
+            sender,
+            false,
+            "Ask Codex to do anything".to_string(),
+            false,
+        );
+        composer
+            .history
+            .record_local_submission("explain this repo");
+        composer
+            .history
+            .record_local_submission("write a changelog");
+        composer.set_text_content("draft".to_string());
+
+        composer.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        composer.handle_key_event(KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE));
+        assert!(matches!(composer.active_popup, ActivePopup::None));
+        assert_eq!(composer.textarea.text(), "draft");
+
+        composer.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+        for ch in "xpl".chars() {
+            composer.handle_key_event(KeyEvent::new(KeyCode::Char(ch), KeyModifiers::NONE));
+        }
+        let (result, _) =
+            composer.handle_key_event(KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE));
+        assert_eq!(
+            result,
+            InputResult::Submitted("explain this repo".to_string())
+        );
+        assert!(matches!(composer.active_popup, ActivePopup::None));
+        assert!(composer.textarea.is_empty());
+    }
+
+    #[test]
+    fn history_search_popup_snapshot() {
+        snapshot_composer_state("history_search_popup", false, |composer| {
+            composer
+                .history
+                .record_local_submission("write docs for the parser");
+            composer
+                .history
+                .record_local_submission("fix the flaky test\nthen rerun the suite");
+            composer.history.record_local_submission("format the code");
+            composer.handle_key_event(KeyEvent::new(KeyCode::Char('r'), KeyModifiers::CONTROL));
+            composer.handle_key_event(KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE));
+            composer.handle_key_event(KeyEvent::new(KeyCode::Char('t'), KeyModifiers::NONE));
+        });
+    }
 }
@@ -155,6 +155,49 @@ impl ChatComposerHistory {
         None
     }
 
+    /// Prompts submitted during this UI session, newest first.
+    pub fn local_entries(&self) -> Vec<String> {
+        self.local_history.iter().rev().cloned().collect()
+    }
+
+    /// Ask core for every persistent entry matching `query` in a single
+    /// round-trip. Results arrive as a `SearchHistoryResponse` event.
+    pub fn search(&self, query: &str, max_results: usize, app_event_tx: &AppEventSender) {
+        let Some(log_id) = self.history_log_id else {
+            return;
+        };
+        if self.history_entry_count == 0 {
+            return;
+        }
+        app_event_tx.send(AppEvent::CodexOp(Op::SearchHistoryRequest {
+            query: query.to_string(),
+            log_id,
+            max_results,
+        }));
+    }
+
+    /// Integrate a SearchHistoryResponse event. Returns the matching texts
+    /// (newest first) when the response belongs to the current history log.
+    /// Matches are also cached so later Up/Down navigation does not need to
+    /// fetch them again.
+    pub fn on_search_response(
+        &mut self,
+        log_id: u64,
+        matches: Vec<(usize, String)>,
+    ) -> Option<Vec<String>> {
+        if self.history_log_id != Some(log_id) {
+            return None;
+        }
+        let mut texts = Vec::with_capacity(matches.len());
+        for (offset, text) in matches {
+            if offset < self.history_entry_count {
+                self.fetched_history.insert(offset, text.clone());
+            }
+            texts.push(text);
+        }
+        Some(texts)
+    }
+
     // ---------------------------------------------------------------------
     // Internal helpers
     // ---------------------------------------------------------------------
@@ -270,4 +313,43 @@ mod tests {
             history.on_entry_response(1, 1, Some("older".into()))
         );
     }
+
+    #[test]
+    fn search_requests_bulk_results_and_caches_them() {
+        let (tx, mut rx) = unbounded_channel::<AppEvent>();
+        let tx = AppEventSender::new(tx);
+
+        let mut history = ChatComposerHistory::new();
+        history.set_metadata(7, 3);
+        history.record_local_submission("local one");
+        history.record_local_submission("local two");
+        assert_eq!(history.local_entries(), vec!["local two", "local one"]);
+
+        history.search("fix", 50, &tx);
+        let AppEvent::CodexOp(op) = rx.try_recv().expect("expected search request") else {
+            panic!("unexpected event variant");
+        };
+        assert_eq!(
+            Op::SearchHistoryRequest {
+                query: "fix".into(),
+                log_id: 7,
+                max_results: 50,
+            },
+            op
+        );
+
+        // Responses for another log are ignored.
+        assert_eq!(None, history.on_search_response(8, vec![(2, "x".into())]));
+        assert_eq!(
+            Some(vec!["fix build".to_string(), "fix tests".to_string()]),
+            history.on_search_response(7, vec![(2, "fix build".into()), (0, "fix tests".into())])
+        );
+
+        // Cached entries are used by Up navigation without another request.
+        assert!(history.should_handle_navigation("", 0));
+        assert_eq!(history.navigate_up(&tx), Some("local two".into()));
+        assert_eq!(history.navigate_up(&tx), Some("local one".into()));
+        assert_eq!(history.navigate_up(&tx), Some("fix build".into()));
+        assert!(rx.try_recv().is_err());
+    }
 }
@@ -0,0 +1,492 @@
+//! Form for answering an MCP server's `elicitation/create` request.
+//!
+//! The requested schema is a flat object whose properties are strings,
+//! numbers, booleans or string enums; each becomes one field of the form.
+
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyModifiers;
+use mcp_types::ElicitRequestParamsRequestedSchema;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+use serde_json::Map;
+use serde_json::Value;
+
+use crate::app_event::AppEvent;
+use crate::app_event_sender::AppEventSender;
+use crate::wrapping::word_wrap_lines;
+use codex_core::protocol::McpElicitationAction;
+use codex_core::protocol::McpElicitationRequestEvent;
+use codex_core::protocol::Op;
+
+use super::CancellationEvent;
+use super::bottom_pane_view::BottomPaneView;
+
+const FOOTER_HINT: &str =
+    "Tab to move between fields · Enter to submit · Esc to decline · Ctrl+C to cancel";
+
+pub(crate) struct ElicitationForm {
+    id: String,
+    server_name: String,
+    message: String,
+    fields: Vec<FormField>,
+    focused: usize,
+    error: Option<String>,
+    app_event_tx: AppEventSender,
+    complete: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+struct FormField {
+    name: String,
+    label: String,
+    description: Option<String>,
+    required: bool,
+    kind: FieldKind,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum FieldKind {
+    Text(String),
+    Number {
+        value: String,
+        integer: bool,
+    },
+    Boolean(bool),
+    Choice {
+        values: Vec<String>,
+        labels: Vec<String>,
+        selected: usize,
+    },
+}
+
+impl ElicitationForm {
+    pub(crate) fn new(event: McpElicitationRequestEvent, app_event_tx: AppEventSender) -> Self {
+        let McpElicitationRequestEvent {
+            id,
+            server_name,
+            message,
+            requested_schema,
+        } = event;
+        Self {
+            id,
+            server_name,
+            message,
+            fields: parse_fields(&requested_schema),
+            focused: 0,
+            error: None,
+            app_event_tx,
+            complete: false,
+        }
+    }
+
+    fn respond(&mut self, action: McpElicitationAction, content: Option<Value>) {
+        self.app_event_tx
+            .send(AppEvent::CodexOp(Op::McpElicitationResponse {
+                id: self.id.clone(),
+                action,
+                content,
+            }));
+        self.complete = true;
+    }
+
+    fn submit(&mut self) {
+        match form_content(&self.fields) {
+            Ok(content) => self.respond(McpElicitationAction::Accept, Some(content)),
+            Err(error) => self.error = Some(error),
+        }
+    }
+
+    fn move_focus(&mut self, forward: bool) {
+        let len = self.fields.len();
+        if len == 0 {
+            return;
+        }
+        self.focused = if forward {
+            (self.focused + 1) % len
+        } else {
+            (self.focused + len - 1) % len
+        };
+    }
+
+    fn edit_focused(&mut self, key_event: KeyEvent) {
+        let Some(field) = self.fields.get_mut(self.focused) else {
+            return;
+        };
+        let plain = !key_event
+            .modifiers
+            .intersects(KeyModifiers::CONTROL | KeyModifiers::ALT);
+        match (&mut field.kind, key_event.code) {
+            (FieldKind::Text(value) | FieldKind::Number { value, .. }, KeyCode::Char(c))
+                if plain =>
+            {
+                value.push(c)
+            }
+            (FieldKind::Text(value) | FieldKind::Number { value, .. }, KeyCode::Backspace) => {
+                value.pop();
+            }
+            (FieldKind::Boolean(checked), KeyCode::Char(' ') | KeyCode::Left | KeyCode::Right) => {
+                *checked = !*checked
+            }
+            (
+                FieldKind::Choice {
+                    values, selected, ..
+                },
+                KeyCode::Right | KeyCode::Char(' '),
+            ) if !values.is_empty() => *selected = (*selected + 1) % values.len(),
+            (
+                FieldKind::Choice {
+                    values, selected, ..
+                },
+                KeyCode::Left,
+            ) if !values.is_empty() => {
+                *selected = (*selected + values.len() - 1) % values.len();
+            }
+            _ => return,
+        }
+        self.error = None;
+    }
+
+    fn lines(&self, width: u16) -> Vec<Line<'static>> {
+        let mut lines: Vec<Line<'static>> = vec![
+            Line::from(vec![
+                "MCP server ".bold(),
+                self.server_name.clone().bold().cyan(),
+                " is asking for input".bold(),
+            ]),
+            Line::from(self.message.clone()),
+            Line::from(""),
+        ];
+        for (idx, field) in self.fields.iter().enumerate() {
+            let focused = idx == self.focused;
+            let mut spans: Vec<Span<'static>> =
+                vec![if focused { "› ".cyan() } else { "  ".into() }];
+            let label = Span::from(field.label.clone());
+            spans.push(if focused { label.bold() } else { label });
+            if field.required {
+                spans.push("*".red());
+            }
+            spans.push(": ".into());
+            match &field.kind {
+                FieldKind::Text(value) | FieldKind::Number { value, .. } => {
+                    spans.push(value.clone().into());
+                    if focused {
+                        spans.push(" ".reversed());
+                    }
+                }
+                FieldKind::Boolean(checked) => {
+                    spans.push(if *checked { "[x]".into() } else { "[ ]".into() });
+                }
+                FieldKind::Choice {
+                    labels, selected, ..
+                } => {
+                    let label = labels.get(*selected).cloned().unwrap_or_default();
+                    spans.push(format!("‹ {label} ›").into());
+                }
+            }
+            lines.push(Line::from(spans));
+            if focused && let Some(description) = &field.description {
+                lines.push(Line::from(format!("    {description}").dim()));
+            }
+        }
+        if self.fields.is_empty() {
+            lines.push(Line::from("  (no fields requested)".dim()));
+        }
+        lines.push(Line::from(""));
+        if let Some(error) = &self.error {
+            lines.push(Line::from(error.clone().red()));
+        }
+        lines.push(Line::from(FOOTER_HINT.dim()));
+        word_wrap_lines(&lines, width.max(1) as usize)
+    }
+}
+
+impl BottomPaneView for ElicitationForm {
+    fn handle_key_event(&mut self, key_event: KeyEvent) {
+        match key_event.code {
+            KeyCode::Esc => self.respond(McpElicitationAction::Decline, None),
+            KeyCode::Enter => self.submit(),
+            KeyCode::Tab | KeyCode::Down => self.move_focus(true),
+            KeyCode::BackTab | KeyCode::Up => self.move_focus(false),
+            _ => self.edit_focused(key_event),
+        }
+    }
+
+    fn on_ctrl_c(&mut self) -> CancellationEvent {
+        if !self.complete {
+            self.respond(McpElicitationAction::Cancel, None);
+        }
+        CancellationEvent::Handled
+    }
+
+    fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    fn is_awaiting_approval(&self) -> bool {
+        !self.complete
+    }
+
+    fn desired_height(&self, width: u16) -> u16 {
+        self.lines(width).len() as u16
+    }
+
+    fn render(&self, area: Rect, buf: &mut Buffer) {
+        Paragraph::new(self.lines(area.width)).render(area, buf);
+    }
+
+    fn handle_paste(&mut self, pasted: String) -> bool {
+        match self
+            .fields
+            .get_mut(self.focused)
+            .map(|field| &mut field.kind)
+        {
+            Some(FieldKind::Text(value) | FieldKind::Number { value, .. }) => {
+                value.push_str(&pasted);
+                true
+            }
+            _ => false,
+        }
+    }
+}
+
+fn parse_fields(schema: &ElicitRequestParamsRequestedSchema) -> Vec<FormField> {
+    let required = schema.required.as_deref().unwrap_or_default();
+    let Some(properties) = schema.properties.as_object() else {
+        return Vec::new();
+    };
+    properties
+        .iter()
+        .map(|(name, property)| {
+            let str_field = |key: &str| property.get(key).and_then(Value::as_str);
+            let default = property.get("default");
+            let kind = match (str_field("type"), property.get("enum")) {
+                (_, Some(Value::Array(values))) => {
+                    let values: Vec<String> = values
+                        .iter()
+                        .filter_map(|v| v.as_str().map(str::to_string))
+                        .collect();
+                    let labels = match property.get("enumNames") {
+                        Some(Value::Array(names)) if names.len() == values.len() => names
+                            .iter()
+                            .zip(&values)
+                            .map(|(n, v)| n.as_str().unwrap_or(v.as_str()).to_string())
+                            .collect(),
+                        _ => values.clone(),
+                    };
+                    let selected = default
+                        .and_then(Value::as_str)
+                        .and_then(|d| values.iter().position(|v| v == d))
+                        .unwrap_or(0);
+                    FieldKind::Choice {
+                        values,
+                        labels,
+                        selected,
+                    }
+                }
+                (Some("boolean"), _) => {
+                    FieldKind::Boolean(default.and_then(Value::as_bool).unwrap_or(false))
+                }
+                (Some(ty @ ("number" | "integer")), _) => FieldKind::Number {
+                    value: default.map(Value::to_string).unwrap_or_default(),
+                    integer: ty == "integer",
+                },
+                _ => FieldKind::Text(
+                    default
+                        .and_then(Value::as_str)
+                        .unwrap_or_default()
+                        .to_string(),
+                ),
+            };
+            FormField {
+                name: name.clone(),
+                label: str_field("title").unwrap_or(name.as_str()).to_string(),
+                description: str_field("description").map(str::to_string),
+                required: required.contains(name),
+                kind,
+            }
+        })
+        .collect()
+}
+
+/// Collect the form's values into the object sent back to the server, or
+/// describe the first field that is missing or invalid.
+fn form_content(fields: &[FormField]) -> Result<Value, String> {
+    let mut content = Map::new();
+    for field in fields {
+        let value = match &field.kind {
+            FieldKind::Text(value) | FieldKind::Number { value, .. } if value.trim().is_empty() => {
+                if field.required {
+                    return Err(format!("{} is required", field.label));
+                }
+                continue;
+            }
+            FieldKind::Text(value) => Value::String(value.clone()),
+            FieldKind::Number { value, integer } => {
+                let value = value.trim();
+                let number = if *integer {
+                    value.parse::<i64>().ok().map(Value::from)
+                } else {
+                    value
+                        .parse::<f64>()
+                        .ok()
+                        .and_then(serde_json::Number::from_f64)
+                        .map(Value::Number)
+                };
+                number.ok_or_else(|| {
+                    let expected = if *integer {
+                        "a whole number"
+                    } else {
+                        "a number"
+                    };
+                    format!("{} must be {expected}", field.label)
+                })?
+            }
+            FieldKind::Boolean(checked) => Value::Bool(*checked),
+            FieldKind::Choice {
+                values, selected, ..
+            } => match values.get(*selected) {
+                Some(value) => Value::String(value.clone()),
+                None => continue,
+            },
+        };
+        content.insert(field.name.clone(), value);
+    }
+    Ok(Value::Object(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+    use serde_json::json;
+    use tokio::sync::mpsc::unbounded_channel;
+
+    fn schema() -> ElicitRequestParamsRequestedSchema {
+        ElicitRequestParamsRequestedSchema {
+            properties: json!({
+                "name": { "type": "string", "title": "Name" },
+                "age": { "type": "integer" },
+                "subscribe": { "type": "boolean", "default": true },
+                "plan": {
+                    "type": "string",
+                    "enum": ["free", "pro"],
+                    "enumNames": ["Free", "Pro"],
+                },
+            }),
+            required: Some(vec!["name".to_string()]),
+            r#type: "object".to_string(),
+        }
+    }
+
+    fn form() -> (
+        ElicitationForm,
+        tokio::sync::mpsc::UnboundedReceiver<AppEvent>,
+    ) {
+        let (tx, rx) = unbounded_channel::<AppEvent>();
+        let form = ElicitationForm::new(
+            McpElicitationRequestEvent {
+                id: "mcp-elicitation-0".to_string(),
+                server_name: "signup".to_string(),
+                message: "Create an account".to_string(),
+                requested_schema: schema(),
+            },
+            AppEventSender::new(tx),
+        );
+        (form, rx)
+    }
+
+    fn press(form: &mut ElicitationForm, code: KeyCode) {
+        form.handle_key_event(KeyEvent::new(code, KeyModifiers::NONE));
+    }
+
+    fn type_text(form: &mut ElicitationForm, text: &str) {
+        for c in text.chars() {
+            press(form, KeyCode::Char(c));
+        }
+    }
+
+    fn responses(rx: &mut tokio::sync::mpsc::UnboundedReceiver<AppEvent>) -> Vec<Op> {
+        let mut ops = Vec::new();
+        while let Ok(ev) = rx.try_recv() {
+            if let AppEvent::CodexOp(op) = ev {
+                ops.push(op);
+            }
+        }
+        ops
+    }
+
+    #[test]
+    fn submitting_sends_typed_values() {
+        let (mut form, mut rx) = form();
+        type_text(&mut form, "Ada");
+        press(&mut form, KeyCode::Tab);
+        type_text(&mut form, "36");
+        press(&mut form, KeyCode::Tab);
+        press(&mut form, KeyCode::Char(' '));
+        press(&mut form, KeyCode::Tab);
+        press(&mut form, KeyCode::Right);
+        press(&mut form, KeyCode::Enter);
+
+        assert!(form.is_complete());
+        assert_eq!(
+            responses(&mut rx),
+            vec![Op::McpElicitationResponse {
+                id: "mcp-elicitation-0".to_string(),
+                action: McpElicitationAction::Accept,
+                content: Some(json!({
+                    "name": "Ada",
+                    "age": 36,
+                    "subscribe": false,
+                    "plan": "pro",
+                })),
+            }]
+        );
+    }
+
+    #[test]
+    fn invalid_values_keep_the_form_open() {
+        let (mut form, mut rx) = form();
+        press(&mut form, KeyCode::Enter);
+        assert_eq!(form.error.as_deref(), Some("Name is required"));
+
+        type_text(&mut form, "Ada");
+        press(&mut form, KeyCode::Tab);
+        type_text(&mut form, "3.5");
+        press(&mut form, KeyCode::Enter);
+        assert_eq!(form.error.as_deref(), Some("age must be a whole number"));
+
+        assert!(!form.is_complete());
+        assert_eq!(responses(&mut rx), Vec::new());
+    }
+
+    #[test]
+    fn esc_declines_and_ctrl_c_cancels() {
+        let (mut declined, mut rx) = form();
+        press(&mut declined, KeyCode::Esc);
+        assert!(matches!(
+            responses(&mut rx).as_slice(),
+            [Op::McpElicitationResponse {
+                action: McpElicitationAction::Decline,
+                content: None,
+                ..
+            }]
+        ));
+
+        let (mut cancelled, mut rx) = form();
+        assert_eq!(cancelled.on_ctrl_c(), CancellationEvent::Handled);
+        assert!(matches!(
+            responses(&mut rx).as_slice(),
+            [Op::McpElicitationResponse {
+                action: McpElicitationAction::Cancel,
+                content: None,
+                ..
+            }]
+        ));
+    }
+}
@@ -5,6 +5,7 @@ use ratatui::buffer::Buffer;
 use ratatui::layout::Rect;
 use ratatui::style::Stylize;
 use ratatui::text::Line;
+use ratatui::text::Span;
 use ratatui::widgets::WidgetRef;
 use std::iter;
 
@@ -14,6 +15,17 @@ pub(crate) struct FooterProps {
     pub(crate) esc_backtrack_hint: bool,
     pub(crate) use_shift_enter_hint: bool,
     pub(crate) is_task_running: bool,
+    pub(crate) tab_indicator: Option<TabIndicator>,
+}
+
+/// Summary of the open conversation tabs, shown in the footer while more than
+/// one tab is open. Positions are 1-based, matching the `alt + N` shortcuts.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub(crate) struct TabIndicator {
+    pub(crate) active: usize,
+    pub(crate) count: usize,
+    /// A background tab that is blocked on an approval request.
+    pub(crate) awaiting_approval: Option<usize>,
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
@@ -75,16 +87,43 @@ fn footer_lines(props: FooterProps) -> Vec<Line<'static>> {
         FooterMode::CtrlCReminder => vec![ctrl_c_reminder_line(CtrlCReminderState {
             is_task_running: props.is_task_running,
         })],
-        FooterMode::ShortcutPrompt => vec![dim_line(indent_text("? for shortcuts"))],
+        FooterMode::ShortcutPrompt => {
+            let mut line = dim_line(indent_text("? for shortcuts"));
+            if let Some(tabs) = props.tab_indicator {
+                line.push_span(FOOTER_SEPARATOR.dim());
+                line.push_span(format!("tab {}/{}", tabs.active, tabs.count).dim());
+                if let Some(tab) = tabs.awaiting_approval {
+                    line.push_span(FOOTER_SEPARATOR.dim());
+                    line.push_span(approval_needed_span(tab));
+                }
+            }
+            vec![line]
+        }
         FooterMode::ShortcutOverlay => shortcut_overlay_lines(ShortcutsState {
             use_shift_enter_hint: props.use_shift_enter_hint,
             esc_backtrack_hint: props.esc_backtrack_hint,
         }),
         FooterMode::EscHint => vec![esc_hint_line(props.esc_backtrack_hint)],
-        FooterMode::Empty => Vec::new(),
+        // Keep a blocked background tab visible even while the user types.
+        FooterMode::Empty => props
+            .tab_indicator
+            .and_then(|tabs| tabs.awaiting_approval)
+            .map(|tab| {
+                vec![Line::from(vec![
+                    indent_text("").into(),
+                    approval_needed_span(tab),
+                ])]
+            })
+            .unwrap_or_default(),
     }
 }
 
+const FOOTER_SEPARATOR: &str = " · ";
+
+fn approval_needed_span(tab: usize) -> Span<'static> {
+    format!("tab {tab} needs approval (alt + {tab})").magenta()
+}
+
 #[derive(Clone, Copy, Debug)]
 struct CtrlCReminderState {
     is_task_running: bool,
@@ -122,6 +161,7 @@ fn shortcut_overlay_lines(state: ShortcutsState) -> Vec<Line<'static>> {
     let mut paste_image = String::new();
     let mut edit_previous = String::new();
     let mut quit = String::new();
+    let mut search_history = String::new();
     let mut show_transcript = String::new();
 
     for descriptor in SHORTCUTS {
@@ -133,6 +173,7 @@ fn shortcut_overlay_lines(state: ShortcutsState) -> Vec<Line<'static>> {
                 ShortcutId::PasteImage => paste_image = text,
                 ShortcutId::EditPrevious => edit_previous = text,
                 ShortcutId::Quit => quit = text,
+                ShortcutId::SearchHistory => search_history = text,
                 ShortcutId::ShowTranscript => show_transcript = text,
             }
         }
@@ -145,7 +186,7 @@ fn shortcut_overlay_lines(state: ShortcutsState) -> Vec<Line<'static>> {
         paste_image,
         edit_previous,
         quit,
-        String::new(),
+        search_history,
         show_transcript,
     ];
 
@@ -219,6 +260,7 @@ enum ShortcutId {
     PasteImage,
     EditPrevious,
     Quit,
+    SearchHistory,
     ShowTranscript,
 }
 
@@ -357,6 +399,17 @@ const SHORTCUTS: &[ShortcutDescriptor] = &[
         prefix: "",
         label: " to exit",
     },
+    ShortcutDescriptor {
+        id: ShortcutId::SearchHistory,
+        bindings: &[ShortcutBinding {
+            code: KeyCode::Char('r'),
+            modifiers: KeyModifiers::CONTROL,
+            overlay_text: "ctrl + r",
+            condition: DisplayCondition::Always,
+        }],
+        prefix: "",
+        label: " to search history",
+    },
     ShortcutDescriptor {
         id: ShortcutId::ShowTranscript,
         bindings: &[ShortcutBinding {
@@ -398,6 +451,7 @@ mod tests {
                 esc_backtrack_hint: false,
                 use_shift_enter_hint: false,
                 is_task_running: false,
+                tab_indicator: None,
             },
         );
 
@@ -408,6 +462,7 @@ mod tests {
                 esc_backtrack_hint: true,
                 use_shift_enter_hint: true,
                 is_task_running: false,
+                tab_indicator: None,
             },
         );
 
@@ -418,6 +473,7 @@ mod tests {
                 esc_backtrack_hint: false,
                 use_shift_enter_hint: false,
                 is_task_running: false,
+                tab_indicator: None,
             },
         );
 
@@ -428,6 +484,7 @@ mod tests {
                 esc_backtrack_hint: false,
                 use_shift_enter_hint: false,
                 is_task_running: true,
+                tab_indicator: None,
             },
         );
 
@@ -438,6 +495,7 @@ mod tests {
                 esc_backtrack_hint: false,
                 use_shift_enter_hint: false,
                 is_task_running: false,
+                tab_indicator: None,
             },
         );
 
@@ -448,6 +506,37 @@ mod tests {
                 esc_backtrack_hint: true,
                 use_shift_enter_hint: false,
                 is_task_running: false,
+                tab_indicator: None,
+            },
+        );
+
+        snapshot_footer(
+            "footer_tabs_background_approval",
+            FooterProps {
+                mode: FooterMode::ShortcutPrompt,
+                esc_backtrack_hint: false,
+                use_shift_enter_hint: false,
+                is_task_running: false,
+                tab_indicator: Some(TabIndicator {
+                    active: 1,
+                    count: 3,
+                    awaiting_approval: Some(3),
+                }),
+            },
+        );
+
+        snapshot_footer(
+            "footer_tabs_background_approval_while_typing",
+            FooterProps {
+                mode: FooterMode::Empty,
+                esc_backtrack_hint: false,
+                use_shift_enter_hint: false,
+                is_task_running: false,
+                tab_indicator: Some(TabIndicator {
+                    active: 1,
+                    count: 2,
+                    awaiting_approval: Some(2),
+                }),
             },
         );
     }
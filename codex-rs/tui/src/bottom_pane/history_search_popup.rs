@@ -0,0 +1,300 @@
+use codex_common::fuzzy_match::fuzzy_match;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Constraint;
+use ratatui::layout::Layout;
+use ratatui::layout::Rect;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+use ratatui::widgets::WidgetRef;
+
+use super::popup_consts::MAX_POPUP_ROWS;
+use super::scroll_state::ScrollState;
+use super::selection_popup_common::GenericDisplayRow;
+use super::selection_popup_common::render_rows;
+use crate::ui_consts::LIVE_PREFIX_COLS;
+
+/// Maximum number of persistent entries requested from core per query.
+pub(crate) const HISTORY_SEARCH_MAX_RESULTS: usize = 200;
+
+/// Number of lines of the selected entry shown below the match list.
+const PREVIEW_MAX_LINES: usize = 3;
+
+const HINT_LINE: &str = "Enter to send, Tab to edit, ^R/↓ next, ↑ previous, Esc to cancel";
+
+/// A single ranked search result.
+struct HistorySearchMatch {
+    text: String,
+    /// `text` flattened onto one line; match indices refer to this string.
+    display: String,
+    indices: Vec<usize>,
+}
+
+/// Visual state for the Ctrl+R reverse history search popup.
+///
+/// Candidates come from two sources: prompts submitted during this UI session
+/// (available immediately) and persistent entries returned in bulk by
+/// `Op::SearchHistoryRequest`. Both are ranked together with the shared fuzzy
+/// matcher; ties keep the newest entry first.
+pub(crate) struct HistorySearchPopup {
+    query: String,
+    /// Prompts submitted during this session, newest first.
+    local_entries: Vec<String>,
+    /// Persistent entries, newest first, for `persistent_query`.
+    persistent_entries: Vec<String>,
+    /// Query that `persistent_entries` were fetched for.
+    persistent_query: Option<String>,
+    matches: Vec<HistorySearchMatch>,
+    state: ScrollState,
+}
+
+impl HistorySearchPopup {
+    pub(crate) fn new(local_entries: Vec<String>) -> Self {
+        let mut popup = Self {
+            query: String::new(),
+            local_entries,
+            persistent_entries: Vec::new(),
+            persistent_query: None,
+            matches: Vec::new(),
+            state: ScrollState::new(),
+        };
+        popup.refresh_matches();
+        popup
+    }
+
+    pub(crate) fn query(&self) -> &str {
+        &self.query
+    }
+
+    pub(crate) fn push_char(&mut self, ch: char) {
+        self.query.push(ch);
+        self.refresh_matches();
+    }
+
+    /// Remove the last query character. Returns false when the query was
+    /// already empty.
+    pub(crate) fn pop_char(&mut self) -> bool {
+        if self.query.pop().is_none() {
+            return false;
+        }
+        self.refresh_matches();
+        true
+    }
+
+    /// Replace persistent candidates with a bulk search response. Responses
+    /// for any query other than the current one are ignored, except that a
+    /// response for a prefix of the current query is kept until a more
+    /// specific one arrives so the list does not flicker while typing.
+    pub(crate) fn set_persistent_entries(&mut self, query: &str, entries: Vec<String>) {
+        let is_current = query == self.query;
+        let is_useful_prefix = self.query.starts_with(query)
+            && self
+                .persistent_query
+                .as_ref()
+                .is_none_or(|prev| prev.len() < query.len());
+        if !is_current && !is_useful_prefix {
+            return;
+        }
+        self.persistent_query = Some(query.to_string());
+        self.persistent_entries = entries;
+        self.refresh_matches();
+    }
+
+    pub(crate) fn move_up(&mut self) {
+        let len = self.matches.len();
+        self.state.move_up_wrap(len);
+        self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
+    }
+
+    pub(crate) fn move_down(&mut self) {
+        let len = self.matches.len();
+        self.state.move_down_wrap(len);
+        self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
+    }
+
+    /// Full text of the currently selected entry.
+    pub(crate) fn selected_text(&self) -> Option<&str> {
+        self.state
+            .selected_idx
+            .and_then(|idx| self.matches.get(idx))
+            .map(|m| m.text.as_str())
+    }
+
+    pub(crate) fn calculate_required_height(&self) -> u16 {
+        let rows = self.matches.len().clamp(1, MAX_POPUP_ROWS);
+        // Query line + rows + preview + hint line.
+        (1 + rows + self.preview_lines().len() + 1) as u16
+    }
+
+    fn preview_lines(&self) -> Vec<&str> {
+        let Some(text) = self.selected_text() else {
+            return Vec::new();
+        };
+        // Single-line entries are fully visible in the list already.
+        if !text.contains('\n') {
+            return Vec::new();
+        }
+        text.lines().take(PREVIEW_MAX_LINES).collect()
+    }
+
+    fn refresh_matches(&mut self) {
+        let mut seen = std::collections::HashSet::new();
+        let mut ranked: Vec<(i32, HistorySearchMatch)> = Vec::new();
+        for text in self
+            .local_entries
+            .iter()
+            .chain(self.persistent_entries.iter())
+        {
+            if !seen.insert(text.as_str()) {
+                continue;
+            }
+            let display = flatten(text);
+            if let Some((indices, score)) = fuzzy_match(&display, &self.query) {
+                ranked.push((
+                    score,
+                    HistorySearchMatch {
+                        text: text.clone(),
+                        display,
+                        indices,
+                    },
+                ));
+            }
+        }
+        // Stable sort keeps candidates in newest-first order within a score.
+        ranked.sort_by_key(|(score, _)| *score);
+        self.matches = ranked.into_iter().map(|(_, m)| m).collect();
+        self.state.reset();
+        let len = self.matches.len();
+        self.state.clamp_selection(len);
+        self.state.ensure_visible(len, len.min(MAX_POPUP_ROWS));
+    }
+}
+
+/// Collapse a multi-line prompt onto a single display row. Newlines become a
+/// single character each so fuzzy-match indices line up with the text.
+fn flatten(text: &str) -> String {
+    text.chars()
+        .map(|c| if c == '\n' { '⏎' } else { c })
+        .collect()
+}
+
+impl WidgetRef for &HistorySearchPopup {
+    fn render_ref(&self, area: Rect, buf: &mut Buffer) {
+        let preview = self.preview_lines();
+        let [query_area, rows_area, preview_area, hint_area] = Layout::vertical([
+            Constraint::Length(1),
+            Constraint::Min(1),
+            Constraint::Length(preview.len() as u16),
+            Constraint::Length(1),
+        ])
+        .areas(area);
+
+        let indent = " ".repeat(LIVE_PREFIX_COLS as usize);
+        Paragraph::new(Line::from(vec![
+            indent.clone().into(),
+            "reverse-i-search: ".dim(),
+            self.query.clone().into(),
+        ]))
+        .render(query_area, buf);
+
+        let rows_all: Vec<GenericDisplayRow> = self
+            .matches
+            .iter()
+            .map(|m| GenericDisplayRow {
+                name: m.display.clone(),
+                match_indices: Some(m.indices.clone()),
+                is_current: false,
+                description: None,
+            })
+            .collect();
+        render_rows(
+            rows_area,
+            buf,
+            &rows_all,
+            &self.state,
+            MAX_POPUP_ROWS,
+            "no matches",
+            false,
+        );
+
+        let preview_lines: Vec<Line> = preview
+            .into_iter()
+            .map(|line| Line::from(vec![format!("{indent}│ ").dim(), line.to_string().dim()]))
+            .collect();
+        Paragraph::new(preview_lines).render(preview_area, buf);
+
+        Paragraph::new(Line::from(vec![indent.into(), HINT_LINE.dim()])).render(hint_area, buf);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use pretty_assertions::assert_eq;
+
+    fn texts(popup: &HistorySearchPopup) -> Vec<&str> {
+        popup.matches.iter().map(|m| m.text.as_str()).collect()
+    }
+
+    #[test]
+    fn ranks_local_and_persistent_entries_together() {
+        let mut popup = HistorySearchPopup::new(vec!["run the tests".to_string()]);
+        popup.set_persistent_entries(
+            "",
+            vec!["format the code".to_string(), "run the tests".to_string()],
+        );
+        assert_eq!(texts(&popup), vec!["run the tests", "format the code"]);
+
+        for ch in "fmt".chars() {
+            popup.push_char(ch);
+        }
+        assert_eq!(texts(&popup), vec!["format the code"]);
+        assert_eq!(popup.selected_text(), Some("format the code"));
+
+        assert!(popup.pop_char());
+        assert!(popup.pop_char());
+        assert!(popup.pop_char());
+        assert!(!popup.pop_char());
+        assert_eq!(texts(&popup).len(), 2);
+    }
+
+    #[test]
+    fn stale_persistent_results_are_ignored() {
+        let mut popup = HistorySearchPopup::new(Vec::new());
+        popup.push_char('a');
+        popup.push_char('b');
+
+        popup.set_persistent_entries("xyz", vec!["abc".to_string()]);
+        assert!(texts(&popup).is_empty());
+
+        // A response for a prefix of the query is used until the exact one
+        // arrives.
+        popup.set_persistent_entries("a", vec!["ab".to_string(), "a".to_string()]);
+        assert_eq!(texts(&popup), vec!["ab"]);
+
+        popup.set_persistent_entries("ab", vec!["abc".to_string()]);
+        assert_eq!(texts(&popup), vec!["abc"]);
+
+        // An older prefix response arriving late does not clobber it.
+        popup.set_persistent_entries("a", vec!["ab".to_string()]);
+        assert_eq!(texts(&popup), vec!["abc"]);
+    }
+
+    #[test]
+    fn multiline_entries_are_flattened_and_previewed() {
+        let mut popup =
+            HistorySearchPopup::new(vec!["first line\nsecond line\nthird\nfourth".to_string()]);
+        popup.push_char('s');
+        popup.push_char('e');
+        assert_eq!(
+            popup.matches[0].display,
+            "first line⏎second line⏎third⏎fourth"
+        );
+        assert_eq!(
+            popup.preview_lines(),
+            vec!["first line", "second line", "third"]
+        );
+        assert_eq!(popup.calculate_required_height(), 1 + 1 + 3 + 1);
+    }
+}
@@ -22,8 +22,11 @@ mod chat_composer;
 mod chat_composer_history;
 mod command_popup;
 pub mod custom_prompt_view;
+mod elicitation_form;
+pub(crate) use elicitation_form::ElicitationForm;
 mod file_search_popup;
 mod footer;
+mod history_search_popup;
 mod list_selection_view;
 mod prompt_args;
 pub(crate) use list_selection_view::SelectionViewParams;
@@ -42,6 +45,7 @@ pub(crate) enum CancellationEvent {
 pub(crate) use chat_composer::ChatComposer;
 pub(crate) use chat_composer::InputResult;
 use codex_protocol::custom_prompts::CustomPrompt;
+pub(crate) use footer::TabIndicator;
 
 use crate::status_indicator_widget::StatusIndicatorWidget;
 pub(crate) use list_selection_view::SelectionAction;
@@ -320,6 +324,18 @@ impl BottomPane {
 
     // esc_backtrack_hint_visible removed; hints are controlled internally.
 
+    pub(crate) fn set_tab_indicator(&mut self, indicator: Option<TabIndicator>) {
+        self.composer.set_tab_indicator(indicator);
+        self.request_redraw();
+    }
+
+    /// True when an approval modal is open or queued in this pane.
+    pub(crate) fn is_awaiting_approval(&self) -> bool {
+        self.view_stack
+            .iter()
+            .any(|view| view.is_awaiting_approval())
+    }
+
     pub fn set_task_running(&mut self, running: bool) {
         self.is_task_running = running;
         self.composer.set_task_running(running);
@@ -381,6 +397,12 @@ impl BottomPane {
         self.push_view(view);
     }
 
+    /// Show the form for an MCP server's elicitation request.
+    pub(crate) fn show_elicitation_form(&mut self, form: ElicitationForm) {
+        self.pause_status_timer_for_modal();
+        self.push_view(Box::new(form));
+    }
+
     /// Called when the agent requests user approval.
     pub fn push_approval_request(&mut self, request: ApprovalRequest) {
         let request = if let Some(view) = self.view_stack.last_mut() {
@@ -455,6 +477,21 @@ impl BottomPane {
         }
     }
 
+    pub(crate) fn on_history_search_response(
+        &mut self,
+        query: String,
+        log_id: u64,
+        matches: Vec<(usize, String)>,
+    ) {
+        let updated = self
+            .composer
+            .on_history_search_response(query, log_id, matches);
+
+        if updated {
+            self.request_redraw();
+        }
+    }
+
     pub(crate) fn on_file_search_result(&mut self, query: String, matches: Vec<FileMatch>) {
         self.composer.on_file_search_result(query, matches);
         self.request_redraw();
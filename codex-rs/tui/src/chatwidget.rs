@@ -25,14 +25,22 @@ use codex_core::protocol::ExitedReviewModeEvent;
 use codex_core::protocol::InputItem;
 use codex_core::protocol::InputMessageKind;
 use codex_core::protocol::ListCustomPromptsResponseEvent;
+use codex_core::protocol::McpElicitationRequestEvent;
 use codex_core::protocol::McpListToolsResponseEvent;
+use codex_core::protocol::McpSamplingRequestEvent;
 use codex_core::protocol::McpToolCallBeginEvent;
 use codex_core::protocol::McpToolCallEndEvent;
+use codex_core::protocol::McpToolCallProgressEvent;
 use codex_core::protocol::Op;
 use codex_core::protocol::PatchApplyBeginEvent;
 use codex_core::protocol::RateLimitSnapshot;
 use codex_core::protocol::ReviewRequest;
+use codex_core::protocol::ScreenshotCapturedEvent;
+use codex_core::protocol::SearchHistoryResponseEvent;
 use codex_core::protocol::StreamErrorEvent;
+use codex_core::protocol::SubAgentBeginEvent;
+use codex_core::protocol::SubAgentEndEvent;
+use codex_core::protocol::SubAgentProgressEvent;
 use codex_core::protocol::TaskCompleteEvent;
 use codex_core::protocol::TokenUsage;
 use codex_core::protocol::TokenUsageInfo;
@@ -59,18 +67,23 @@ use tracing::debug;
 
 use crate::app_event::AppEvent;
 use crate::app_event_sender::AppEventSender;
+use crate::app_tabs::TabSummary;
 use crate::bottom_pane::ApprovalRequest;
 use crate::bottom_pane::BottomPane;
 use crate::bottom_pane::BottomPaneParams;
 use crate::bottom_pane::CancellationEvent;
+use crate::bottom_pane::ElicitationForm;
 use crate::bottom_pane::InputResult;
 use crate::bottom_pane::SelectionAction;
 use crate::bottom_pane::SelectionItem;
 use crate::bottom_pane::SelectionViewParams;
+use crate::bottom_pane::TabIndicator;
 use crate::bottom_pane::custom_prompt_view::CustomPromptView;
 use crate::bottom_pane::popup_consts::STANDARD_POPUP_HINT_LINE;
 use crate::clipboard_paste::paste_image_to_temp_png;
 use crate::diff_render::display_path_for;
+use crate::diff_viewer::files_from_changes;
+use crate::diff_viewer::parse_unified_diff;
 use crate::exec_cell::CommandOutput;
 use crate::exec_cell::ExecCell;
 use crate::exec_cell::new_active_exec_command;
@@ -99,7 +112,7 @@ use chrono::Local;
 use codex_common::approval_presets::ApprovalPreset;
 use codex_common::approval_presets::builtin_approval_presets;
 use codex_common::model_presets::ModelPreset;
-use codex_common::model_presets::builtin_model_presets;
+use codex_common::model_presets::model_presets;
 use codex_core::AuthManager;
 use codex_core::ConversationManager;
 use codex_core::protocol::AskForApproval;
@@ -256,6 +269,8 @@ pub(crate) struct ChatWidget {
     ghost_snapshots_disabled: bool,
     // Whether to add a final message separator after the last message
     needs_final_message_separator: bool,
+    // Aggregated diff from the most recent turn that changed files.
+    last_turn_diff: Option<String>,
 
     last_rendered_width: std::cell::Cell<Option<usize>>,
 }
@@ -458,7 +473,12 @@ impl ChatWidget {
         // Finalize, log a gentle prompt, and clear running state.
         self.finalize_turn();
 
-        if reason != TurnAbortReason::ReviewEnded {
+        // A review ending needs no prompt, and an exhausted budget has already
+        // been reported by the preceding error event.
+        if !matches!(
+            reason,
+            TurnAbortReason::ReviewEnded | TurnAbortReason::BudgetExceeded
+        ) {
             self.add_to_history(history_cell::new_error_event(
                 "Conversation interrupted - tell the model what to do differently".to_owned(),
             ));
@@ -552,11 +572,49 @@ impl ChatWidget {
         self.defer_or_handle(|q| q.push_mcp_begin(ev), |s| s.handle_mcp_begin_now(ev2));
     }
 
+    /// Progress is transient, so updates for a call that is not the active
+    /// cell (e.g. while its begin event is still queued) are dropped.
+    fn on_mcp_tool_call_progress(&mut self, ev: McpToolCallProgressEvent) {
+        let summary = ev.summary();
+        if summary.is_empty() {
+            return;
+        }
+        if let Some(cell) = self
+            .active_cell
+            .as_mut()
+            .and_then(|cell| cell.as_any_mut().downcast_mut::<McpToolCallCell>())
+            && cell.call_id() == ev.call_id
+        {
+            cell.set_progress(summary);
+            self.request_redraw();
+        }
+    }
+
     fn on_mcp_tool_call_end(&mut self, ev: McpToolCallEndEvent) {
         let ev2 = ev.clone();
         self.defer_or_handle(|q| q.push_mcp_end(ev), |s| s.handle_mcp_end_now(ev2));
     }
 
+    fn on_sub_agent_begin(&mut self, ev: SubAgentBeginEvent) {
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_sub_agent_begin(ev.agent, ev.task));
+    }
+
+    fn on_sub_agent_progress(&mut self, ev: SubAgentProgressEvent) {
+        self.bottom_pane
+            .update_status_header(format!("{}: {}", ev.agent, ev.message));
+    }
+
+    fn on_sub_agent_end(&mut self, ev: SubAgentEndEvent) {
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_sub_agent_end(ev));
+    }
+
+    fn on_screenshot_captured(&mut self, ev: ScreenshotCapturedEvent) {
+        self.flush_answer_stream_with_separator();
+        self.add_to_history(history_cell::new_screenshot_captured(ev));
+    }
+
     fn on_web_search_begin(&mut self, _ev: WebSearchBeginEvent) {
         self.flush_answer_stream_with_separator();
     }
@@ -582,12 +640,29 @@ impl ChatWidget {
             .on_history_entry_response(log_id, offset, entry.map(|e| e.text));
     }
 
+    fn on_search_history_response(&mut self, event: SearchHistoryResponseEvent) {
+        let SearchHistoryResponseEvent {
+            query,
+            log_id,
+            matches,
+        } = event;
+        self.bottom_pane.on_history_search_response(
+            query,
+            log_id,
+            matches
+                .into_iter()
+                .map(|m| (m.offset, m.entry.text))
+                .collect(),
+        );
+    }
+
     fn on_shutdown_complete(&mut self) {
         self.app_event_tx.send(AppEvent::ExitRequest);
     }
 
     fn on_turn_diff(&mut self, unified_diff: String) {
         debug!("TurnDiffEvent: {unified_diff}");
+        self.last_turn_diff = Some(unified_diff);
     }
 
     fn on_background_event(&mut self, message: String) {
@@ -759,6 +834,7 @@ impl ChatWidget {
             id,
             reason: ev.reason,
             grant_root: ev.grant_root,
+            files: files_from_changes(&ev.changes, &self.config.cwd),
         };
         self.bottom_pane.push_approval_request(request);
         self.request_redraw();
@@ -768,6 +844,31 @@ impl ChatWidget {
         });
     }
 
+    fn on_mcp_sampling_request(&mut self, ev: McpSamplingRequestEvent) {
+        self.flush_answer_stream_with_separator();
+        self.notify(Notification::McpServerRequested {
+            server_name: ev.server_name.clone(),
+        });
+        self.bottom_pane
+            .push_approval_request(ApprovalRequest::McpSampling {
+                id: ev.id,
+                server_name: ev.server_name,
+                messages: ev.request.messages,
+                system_prompt: ev.request.system_prompt,
+            });
+        self.request_redraw();
+    }
+
+    fn on_mcp_elicitation_request(&mut self, ev: McpElicitationRequestEvent) {
+        self.flush_answer_stream_with_separator();
+        self.notify(Notification::McpServerRequested {
+            server_name: ev.server_name.clone(),
+        });
+        self.bottom_pane
+            .show_elicitation_form(ElicitationForm::new(ev, self.app_event_tx.clone()));
+        self.request_redraw();
+    }
+
     pub(crate) fn handle_exec_begin_now(&mut self, ev: ExecCommandBeginEvent) {
         // Ensure the status indicator is visible while the command runs.
         self.running_commands.insert(
@@ -917,6 +1018,7 @@ impl ChatWidget {
             ghost_snapshots: Vec::new(),
             ghost_snapshots_disabled: true,
             needs_final_message_separator: false,
+            last_turn_diff: None,
             last_rendered_width: std::cell::Cell::new(None),
         }
     }
@@ -980,6 +1082,7 @@ impl ChatWidget {
             ghost_snapshots: Vec::new(),
             ghost_snapshots_disabled: true,
             needs_final_message_separator: false,
+            last_turn_diff: None,
             last_rendered_width: std::cell::Cell::new(None),
         }
     }
@@ -1087,6 +1190,9 @@ impl ChatWidget {
             SlashCommand::New => {
                 self.app_event_tx.send(AppEvent::NewSession);
             }
+            SlashCommand::Tabs => {
+                self.app_event_tx.send(AppEvent::OpenTabsPopup);
+            }
             SlashCommand::Init => {
                 const INIT_PROMPT: &str = include_str!("../prompt_for_init_command.md");
                 self.submit_text_message(INIT_PROMPT.to_string());
@@ -1108,7 +1214,9 @@ impl ChatWidget {
                 self.app_event_tx.send(AppEvent::ExitRequest);
             }
             SlashCommand::Logout => {
-                if let Err(e) = codex_core::auth::logout(&self.config.codex_home) {
+                if let Err(e) =
+                    codex_core::auth::logout(&self.config.codex_home, &self.config.credential_store)
+                {
                     tracing::error!("failed to logout: {e}");
                 }
                 self.app_event_tx.send(AppEvent::ExitRequest);
@@ -1133,6 +1241,21 @@ impl ChatWidget {
                     tx.send(AppEvent::DiffResult(text));
                 });
             }
+            SlashCommand::TurnDiff => {
+                let files = self
+                    .last_turn_diff
+                    .as_deref()
+                    .map(parse_unified_diff)
+                    .unwrap_or_default();
+                if files.is_empty() {
+                    self.add_info_message("Codex has not changed any files yet.".to_string(), None);
+                } else {
+                    self.app_event_tx.send(AppEvent::OpenDiffViewer {
+                        title: "T U R N   D I F F".to_string(),
+                        files,
+                    });
+                }
+            }
             SlashCommand::Mention => {
                 self.insert_str("@");
             }
@@ -1383,7 +1506,7 @@ impl ChatWidget {
                 TurnAbortReason::Replaced => {
                     self.on_error("Turn aborted: replaced by a new task".to_owned())
                 }
-                TurnAbortReason::ReviewEnded => {
+                TurnAbortReason::ReviewEnded | TurnAbortReason::BudgetExceeded => {
                     self.on_interrupted_turn(ev.reason);
                 }
             },
@@ -1395,16 +1518,20 @@ impl ChatWidget {
             EventMsg::ApplyPatchApprovalRequest(ev) => {
                 self.on_apply_patch_approval_request(id.unwrap_or_default(), ev)
             }
+            EventMsg::McpSamplingRequest(ev) => self.on_mcp_sampling_request(ev),
+            EventMsg::McpElicitationRequest(ev) => self.on_mcp_elicitation_request(ev),
             EventMsg::ExecCommandBegin(ev) => self.on_exec_command_begin(ev),
             EventMsg::ExecCommandOutputDelta(delta) => self.on_exec_command_output_delta(delta),
             EventMsg::PatchApplyBegin(ev) => self.on_patch_apply_begin(ev),
             EventMsg::PatchApplyEnd(ev) => self.on_patch_apply_end(ev),
             EventMsg::ExecCommandEnd(ev) => self.on_exec_command_end(ev),
             EventMsg::McpToolCallBegin(ev) => self.on_mcp_tool_call_begin(ev),
+            EventMsg::McpToolCallProgress(ev) => self.on_mcp_tool_call_progress(ev),
             EventMsg::McpToolCallEnd(ev) => self.on_mcp_tool_call_end(ev),
             EventMsg::WebSearchBegin(ev) => self.on_web_search_begin(ev),
             EventMsg::WebSearchEnd(ev) => self.on_web_search_end(ev),
             EventMsg::GetHistoryEntryResponse(ev) => self.on_get_history_entry_response(ev),
+            EventMsg::SearchHistoryResponse(ev) => self.on_search_history_response(ev),
             EventMsg::McpListToolsResponse(ev) => self.on_list_mcp_tools(ev),
             EventMsg::ListCustomPromptsResponse(ev) => self.on_list_custom_prompts(ev),
             EventMsg::ShutdownComplete => self.on_shutdown_complete(),
@@ -1426,6 +1553,10 @@ impl ChatWidget {
                 self.on_entered_review_mode(review_request)
             }
             EventMsg::ExitedReviewMode(review) => self.on_exited_review_mode(review),
+            EventMsg::SubAgentBegin(ev) => self.on_sub_agent_begin(ev),
+            EventMsg::SubAgentProgress(ev) => self.on_sub_agent_progress(ev),
+            EventMsg::SubAgentEnd(ev) => self.on_sub_agent_end(ev),
+            EventMsg::ScreenshotCaptured(ev) => self.on_screenshot_captured(ev),
         }
     }
 
@@ -1565,6 +1696,7 @@ impl ChatWidget {
         self.add_to_history(crate::status::new_status_output(
             &self.config,
             usage_ref,
+            self.token_info.as_ref().and_then(|ti| ti.total_cost_usd),
             &self.conversation_id,
             self.rate_limit_snapshot.as_ref(),
         ));
@@ -1575,14 +1707,14 @@ impl ChatWidget {
         let current_model = self.config.model.clone();
         let current_effort = self.config.model_reasoning_effort;
         let auth_mode = self.auth_manager.auth().map(|auth| auth.mode);
-        let presets: Vec<ModelPreset> = builtin_model_presets(auth_mode);
+        let presets: Vec<ModelPreset> = model_presets(auth_mode, &self.config.models);
 
         let mut items: Vec<SelectionItem> = Vec::new();
         for preset in presets.iter() {
-            let name = preset.label.to_string();
-            let description = Some(preset.description.to_string());
+            let name = preset.label.clone();
+            let description = Some(preset.description.clone());
             let is_current = preset.model == current_model && preset.effort == current_effort;
-            let model_slug = preset.model.to_string();
+            let model_slug = preset.model.clone();
             let effort = preset.effort;
             let current_model = current_model.clone();
             let actions: Vec<SelectionAction> = vec![Box::new(move |tx| {
@@ -1772,7 +1904,11 @@ impl ChatWidget {
     }
 
     fn on_list_mcp_tools(&mut self, ev: McpListToolsResponseEvent) {
-        self.add_to_history(history_cell::new_mcp_tools_output(&self.config, ev.tools));
+        self.add_to_history(history_cell::new_mcp_tools_output(
+            &self.config,
+            ev.tools,
+            ev.servers,
+        ));
     }
 
     fn on_list_custom_prompts(&mut self, ev: ListCustomPromptsResponseEvent) {
@@ -1930,6 +2066,94 @@ impl ChatWidget {
         });
     }
 
+    /// Show the `/tabs` popup: one row per open conversation followed by the
+    /// tab actions.
+    pub(crate) fn open_tabs_popup(&mut self, tabs: Vec<TabSummary>) {
+        let can_close = tabs.len() > 1;
+        let mut items: Vec<SelectionItem> = tabs
+            .into_iter()
+            .enumerate()
+            .map(|(idx, tab)| {
+                let description = if tab.awaiting_approval {
+                    "needs approval"
+                } else if tab.is_working {
+                    "working"
+                } else {
+                    "idle"
+                };
+                let id = tab.id;
+                let actions: Vec<SelectionAction> = vec![Box::new(move |tx: &AppEventSender| {
+                    tx.send(AppEvent::SwitchTab(id));
+                })];
+                SelectionItem {
+                    name: format!("{}. {}", idx + 1, tab.title),
+                    description: Some(description.to_string()),
+                    is_current: tab.is_active,
+                    actions,
+                    dismiss_on_select: true,
+                    search_value: None,
+                }
+            })
+            .collect();
+
+        let mut push_action = |name: &str, description: &str, event: fn() -> AppEvent| {
+            items.push(SelectionItem {
+                name: name.to_string(),
+                description: Some(description.to_string()),
+                is_current: false,
+                actions: vec![Box::new(move |tx: &AppEventSender| tx.send(event()))],
+                dismiss_on_select: true,
+                search_value: None,
+            });
+        };
+        push_action("New tab", "start a fresh conversation", || AppEvent::NewTab);
+        push_action(
+            "Resume in new tab",
+            "pick a previous session to continue",
+            || AppEvent::ResumeInNewTab,
+        );
+        push_action("Rename tab", "give this tab a name", || {
+            AppEvent::OpenRenameTabPrompt
+        });
+        if can_close {
+            push_action("Close tab", "end this conversation", || AppEvent::CloseTab);
+        }
+
+        self.bottom_pane.show_selection_view(SelectionViewParams {
+            title: "Conversation tabs".to_string(),
+            subtitle: Some("Use alt + 1..9 to switch tabs directly".to_string()),
+            footer_hint: Some(STANDARD_POPUP_HINT_LINE.to_string()),
+            items,
+            ..Default::default()
+        });
+    }
+
+    pub(crate) fn show_rename_tab_prompt(&mut self, current_title: String) {
+        let tx = self.app_event_tx.clone();
+        let view = CustomPromptView::new(
+            "Rename tab".to_string(),
+            "Type a name and press Enter".to_string(),
+            Some(format!("Current name: {current_title}")),
+            Box::new(move |name: String| {
+                tx.send(AppEvent::RenameTab(name.trim().to_string()));
+            }),
+        );
+        self.bottom_pane.show_view(Box::new(view));
+    }
+
+    pub(crate) fn set_tab_indicator(&mut self, indicator: Option<TabIndicator>) {
+        self.bottom_pane.set_tab_indicator(indicator);
+    }
+
+    /// True while an approval modal is waiting on the user in this widget.
+    pub(crate) fn is_awaiting_approval(&self) -> bool {
+        self.bottom_pane.is_awaiting_approval()
+    }
+
+    pub(crate) fn is_task_running(&self) -> bool {
+        self.bottom_pane.is_task_running()
+    }
+
     pub(crate) fn show_review_custom_prompt(&mut self) {
         let tx = self.app_event_tx.clone();
         let view = CustomPromptView::new(
@@ -2013,6 +2237,7 @@ enum Notification {
     AgentTurnComplete { response: String },
     ExecApprovalRequested { command: String },
     EditApprovalRequested { cwd: PathBuf, changes: Vec<PathBuf> },
+    McpServerRequested { server_name: String },
 }
 
 impl Notification {
@@ -2036,6 +2261,9 @@ impl Notification {
                     }
                 )
             }
+            Notification::McpServerRequested { server_name } => {
+                format!("MCP server {server_name} needs your input")
+            }
         }
     }
 
@@ -2043,7 +2271,8 @@ impl Notification {
         match self {
             Notification::AgentTurnComplete { .. } => "agent-turn-complete",
             Notification::ExecApprovalRequested { .. }
-            | Notification::EditApprovalRequested { .. } => "approval-requested",
+            | Notification::EditApprovalRequested { .. }
+            | Notification::McpServerRequested { .. } => "approval-requested",
         }
     }
 
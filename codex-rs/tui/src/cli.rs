@@ -32,8 +32,8 @@ pub struct Cli {
     pub model: Option<String>,
 
     /// Convenience flag to select the local open source model provider.
-    /// Equivalent to -c model_provider=oss; verifies a local Ollama server is
-    /// running.
+    /// Equivalent to -c model_provider=oss; finds a local Ollama, LM Studio,
+    /// vLLM or llama.cpp server to use.
     #[arg(long = "oss", default_value_t = false)]
     pub oss: bool,
 
@@ -72,6 +72,26 @@ pub struct Cli {
     #[arg(long = "search", default_value_t = false)]
     pub web_search: bool,
 
+    /// Run the session in a new git worktree on its own branch. When the
+    /// session ends you can merge the changes, keep the branch, or discard it.
+    #[arg(long = "worktree", default_value_t = false)]
+    pub worktree: bool,
+
+    /// Record every model request and its streamed response into DIR so the
+    /// session can be replayed later with `--replay-cassette`.
+    #[arg(
+        long = "record-cassette",
+        value_name = "DIR",
+        conflicts_with = "replay_cassette"
+    )]
+    pub record_cassette: Option<PathBuf>,
+
+    /// Serve model responses from a cassette recorded with
+    /// `--record-cassette` instead of contacting the provider. Fails on any
+    /// request that was not recorded.
+    #[arg(long = "replay-cassette", value_name = "DIR")]
+    pub replay_cassette: Option<PathBuf>,
+
     #[clap(skip)]
     pub config_overrides: CliConfigOverrides,
 }
@@ -0,0 +1,1161 @@
+//! Interactive diff viewer rendered in the pager overlay.
+//!
+//! The viewer is shared by `/diff`, `/turn-diff` (the diff of the last turn that
+//! edited files) and the patch approval modal. It supports a unified layout and a
+//! side-by-side layout on wide terminals, intra-line word highlighting,
+//! per-file collapse/expand and jumping between files and hunks.
+
+use std::collections::HashMap;
+use std::io::Result;
+use std::path::Path;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use codex_core::protocol::FileChange;
+use crossterm::event::KeyCode;
+use crossterm::event::KeyEvent;
+use crossterm::event::KeyEventKind;
+use crossterm::event::KeyModifiers;
+use ratatui::buffer::Buffer;
+use ratatui::layout::Rect;
+use ratatui::style::Color;
+use ratatui::style::Modifier;
+use ratatui::style::Style;
+use ratatui::style::Stylize;
+use ratatui::text::Line;
+use ratatui::text::Span;
+use ratatui::widgets::Clear;
+use ratatui::widgets::Paragraph;
+use ratatui::widgets::Widget;
+use ratatui::widgets::WidgetRef;
+use similar::ChangeTag;
+use similar::TextDiff;
+use unicode_width::UnicodeWidthChar;
+
+use crate::diff_render::display_path_for;
+use crate::pager_overlay::PAGER_KEY_HINTS;
+use crate::pager_overlay::render_key_hints;
+use crate::tui;
+use crate::tui::TuiEvent;
+
+/// Terminals at least this wide start in the side-by-side layout.
+const SIDE_BY_SIDE_MIN_WIDTH: u16 = 120;
+
+/// Lines longer than this are not word-diffed; highlighting them is slow and
+/// rarely useful.
+const MAX_WORD_DIFF_CHARS: usize = 2_000;
+
+/// Pairs of lines that share less than this fraction of their words are shown
+/// without word highlighting, since most of the line would be highlighted.
+const MIN_WORD_DIFF_RATIO: f32 = 0.5;
+
+const TAB_WIDTH: usize = 4;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FileStatus {
+    Added,
+    Deleted,
+    Modified,
+    Renamed,
+}
+
+/// A single file section of a unified diff.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct FileDiff {
+    pub(crate) path: String,
+    pub(crate) move_path: Option<String>,
+    pub(crate) status: FileStatus,
+    pub(crate) hunks: Vec<DiffHunk>,
+    pub(crate) is_binary: bool,
+}
+
+impl FileDiff {
+    fn new(path: String) -> Self {
+        Self {
+            path,
+            move_path: None,
+            status: FileStatus::Modified,
+            hunks: Vec::new(),
+            is_binary: false,
+        }
+    }
+
+    /// Number of (inserted, deleted) lines across all hunks.
+    pub(crate) fn line_counts(&self) -> (usize, usize) {
+        self.hunks
+            .iter()
+            .flat_map(|h| h.lines.iter())
+            .fold((0, 0), |(a, d), l| match l {
+                DiffLine::Insert(_) => (a + 1, d),
+                DiffLine::Delete(_) => (a, d + 1),
+                DiffLine::Context(_) => (a, d),
+            })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct DiffHunk {
+    pub(crate) old_start: usize,
+    pub(crate) old_len: usize,
+    pub(crate) new_start: usize,
+    pub(crate) new_len: usize,
+    /// Trailing text of the `@@ ... @@` header (usually the enclosing function).
+    pub(crate) section: String,
+    pub(crate) lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) enum DiffLine {
+    Context(String),
+    Insert(String),
+    Delete(String),
+}
+
+/// Parse the output of `git diff` (or any concatenation of unified diffs) into
+/// per-file sections. Text that is not part of a diff is ignored.
+pub(crate) fn parse_unified_diff(text: &str) -> Vec<FileDiff> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    let mut current: Option<FileDiff> = None;
+    // Lines still expected in the hunk being read, as (old, new).
+    let mut remaining = (0usize, 0usize);
+
+    for line in text.lines() {
+        if (remaining.0 > 0 || remaining.1 > 0)
+            && let Some(hunk) = current.as_mut().and_then(|f| f.hunks.last_mut())
+        {
+            if let Some(rest) = line.strip_prefix('+') {
+                hunk.lines.push(DiffLine::Insert(rest.to_string()));
+                remaining.1 = remaining.1.saturating_sub(1);
+                continue;
+            } else if let Some(rest) = line.strip_prefix('-') {
+                hunk.lines.push(DiffLine::Delete(rest.to_string()));
+                remaining.0 = remaining.0.saturating_sub(1);
+                continue;
+            } else if line.is_empty() || line.starts_with(' ') {
+                let rest = line.strip_prefix(' ').unwrap_or(line);
+                hunk.lines.push(DiffLine::Context(rest.to_string()));
+                remaining.0 = remaining.0.saturating_sub(1);
+                remaining.1 = remaining.1.saturating_sub(1);
+                continue;
+            } else if line.starts_with('\\') {
+                // "\ No newline at end of file"
+                continue;
+            }
+            remaining = (0, 0);
+        }
+
+        if let Some(rest) = line.strip_prefix("diff --git ") {
+            files.extend(current.take());
+            let (old, new) = split_git_header_paths(rest);
+            let mut file = FileDiff::new(old.clone());
+            if old != new {
+                file.move_path = Some(new);
+                file.status = FileStatus::Renamed;
+            }
+            current = Some(file);
+        } else if let Some(rest) = line.strip_prefix("--- ") {
+            if current.as_ref().is_none_or(|f| !f.hunks.is_empty()) {
+                files.extend(current.take());
+                current = Some(FileDiff::new(String::new()));
+            }
+            if let Some(file) = current.as_mut() {
+                match header_path(rest, "a/") {
+                    Some(path) if file.path.is_empty() => file.path = path,
+                    Some(_) => {}
+                    None => file.status = FileStatus::Added,
+                }
+            }
+        } else if let Some(rest) = line.strip_prefix("+++ ") {
+            let file = current.get_or_insert_with(|| FileDiff::new(String::new()));
+            match header_path(rest, "b/") {
+                Some(path) if file.path.is_empty() => file.path = path,
+                Some(_) => {}
+                None => file.status = FileStatus::Deleted,
+            }
+        } else if line.starts_with("new file mode") {
+            if let Some(file) = current.as_mut() {
+                file.status = FileStatus::Added;
+            }
+        } else if line.starts_with("deleted file mode") {
+            if let Some(file) = current.as_mut() {
+                file.status = FileStatus::Deleted;
+            }
+        } else if let Some(rest) = line.strip_prefix("rename to ") {
+            if let Some(file) = current.as_mut() {
+                file.move_path = Some(rest.to_string());
+                file.status = FileStatus::Renamed;
+            }
+        } else if line.starts_with("Binary files ") {
+            if let Some(file) = current.as_mut() {
+                file.is_binary = true;
+            }
+        } else if line.starts_with("@@")
+            && let Some(hunk) = parse_hunk_header(line)
+        {
+            remaining = (hunk.old_len, hunk.new_len);
+            current
+                .get_or_insert_with(|| FileDiff::new(String::new()))
+                .hunks
+                .push(hunk);
+        }
+    }
+    files.extend(current);
+    files
+}
+
+/// Split the `a/<old> b/<new>` part of a `diff --git` header.
+fn split_git_header_paths(rest: &str) -> (String, String) {
+    let (old, new) = match rest.rfind(" b/") {
+        Some(idx) => (&rest[..idx], &rest[idx + 1..]),
+        None => (rest, rest),
+    };
+    let old = old.strip_prefix("a/").unwrap_or(old);
+    let new = new.strip_prefix("b/").unwrap_or(new);
+    (old.to_string(), new.to_string())
+}
+
+/// Path from a `---`/`+++` header, or `None` for `/dev/null`.
+fn header_path(rest: &str, prefix: &str) -> Option<String> {
+    // Some tools append a tab-separated timestamp.
+    let raw = rest.split('\t').next().unwrap_or(rest).trim_end();
+    if raw == "/dev/null" || raw == "NUL" {
+        return None;
+    }
+    Some(raw.strip_prefix(prefix).unwrap_or(raw).to_string())
+}
+
+fn parse_hunk_header(line: &str) -> Option<DiffHunk> {
+    let rest = line.strip_prefix("@@ ")?;
+    let end = rest.find(" @@")?;
+    let ranges = &rest[..end];
+    let section = rest[end + 3..].trim().to_string();
+    let mut parts = ranges.split_whitespace();
+    let (old_start, old_len) = parse_range(parts.next()?.strip_prefix('-')?)?;
+    let (new_start, new_len) = parse_range(parts.next()?.strip_prefix('+')?)?;
+    Some(DiffHunk {
+        old_start,
+        old_len,
+        new_start,
+        new_len,
+        section,
+        lines: Vec::new(),
+    })
+}
+
+fn parse_range(range: &str) -> Option<(usize, usize)> {
+    match range.split_once(',') {
+        Some((start, len)) => Some((start.parse().ok()?, len.parse().ok()?)),
+        None => Some((range.parse().ok()?, 1)),
+    }
+}
+
+/// Build viewer sections from the changes carried by patch events.
+pub(crate) fn files_from_changes(
+    changes: &HashMap<PathBuf, FileChange>,
+    cwd: &Path,
+) -> Vec<FileDiff> {
+    let mut entries: Vec<(&PathBuf, &FileChange)> = changes.iter().collect();
+    entries.sort_by(|a, b| a.0.cmp(b.0));
+    entries
+        .into_iter()
+        .map(|(path, change)| {
+            let mut file = FileDiff::new(display_path_for(path, cwd));
+            match change {
+                FileChange::Add { content } => {
+                    file.status = FileStatus::Added;
+                    file.hunks = whole_file_hunk(content, DiffLine::Insert);
+                }
+                FileChange::Delete { content } => {
+                    file.status = FileStatus::Deleted;
+                    file.hunks = whole_file_hunk(content, DiffLine::Delete);
+                }
+                FileChange::Update {
+                    unified_diff,
+                    move_path,
+                } => {
+                    file.hunks = parse_unified_diff(unified_diff)
+                        .into_iter()
+                        .flat_map(|f| f.hunks)
+                        .collect();
+                    if let Some(move_path) = move_path {
+                        file.move_path = Some(display_path_for(move_path, cwd));
+                        file.status = FileStatus::Renamed;
+                    }
+                }
+            }
+            file
+        })
+        .collect()
+}
+
+fn whole_file_hunk(content: &str, make_line: fn(String) -> DiffLine) -> Vec<DiffHunk> {
+    let lines: Vec<DiffLine> = content.lines().map(|l| make_line(l.to_string())).collect();
+    if lines.is_empty() {
+        return Vec::new();
+    }
+    let len = lines.len();
+    let is_insert = matches!(lines[0], DiffLine::Insert(_));
+    vec![DiffHunk {
+        old_start: if is_insert { 0 } else { 1 },
+        old_len: if is_insert { 0 } else { len },
+        new_start: if is_insert { 1 } else { 0 },
+        new_len: if is_insert { len } else { 0 },
+        section: String::new(),
+        lines,
+    }]
+}
+
+/// A run of text within a line, flagged when it differs from the paired line.
+type Segment = (String, bool);
+
+/// Compute word-level differences between a deleted and an inserted line.
+/// Returns `None` when highlighting would not help.
+fn word_diff(old: &str, new: &str) -> Option<(Vec<Segment>, Vec<Segment>)> {
+    if old.len() > MAX_WORD_DIFF_CHARS || new.len() > MAX_WORD_DIFF_CHARS {
+        return None;
+    }
+    let diff = TextDiff::from_words(old, new);
+    if diff.ratio() < MIN_WORD_DIFF_RATIO {
+        return None;
+    }
+    let mut old_segments: Vec<Segment> = Vec::new();
+    let mut new_segments: Vec<Segment> = Vec::new();
+    for change in diff.iter_all_changes() {
+        let value = change.value();
+        match change.tag() {
+            ChangeTag::Equal => {
+                push_segment(&mut old_segments, value, false);
+                push_segment(&mut new_segments, value, false);
+            }
+            ChangeTag::Delete => push_segment(&mut old_segments, value, true),
+            ChangeTag::Insert => push_segment(&mut new_segments, value, true),
+        }
+    }
+    Some((old_segments, new_segments))
+}
+
+fn push_segment(segments: &mut Vec<Segment>, text: &str, changed: bool) {
+    match segments.last_mut() {
+        Some((last, last_changed)) if *last_changed == changed => last.push_str(text),
+        _ => segments.push((text.to_string(), changed)),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum DiffLayout {
+    Unified,
+    SideBySide,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum LineKind {
+    Context,
+    Insert,
+    Delete,
+}
+
+impl LineKind {
+    fn sign(self) -> char {
+        match self {
+            LineKind::Context => ' ',
+            LineKind::Insert => '+',
+            LineKind::Delete => '-',
+        }
+    }
+
+    fn style(self) -> Style {
+        match self {
+            LineKind::Context => Style::default(),
+            LineKind::Insert => Style::default().fg(Color::Green),
+            LineKind::Delete => Style::default().fg(Color::Red),
+        }
+    }
+}
+
+/// One side of a rendered diff row.
+struct Cell {
+    line_number: usize,
+    kind: LineKind,
+    segments: Vec<Segment>,
+}
+
+/// A rendered hunk line, in either layout.
+enum Row {
+    Unified(Cell),
+    SideBySide(Option<Cell>, Option<Cell>),
+}
+
+/// Output of laying out the diff for a given width and set of view options.
+#[derive(Default)]
+struct RenderedDiff {
+    lines: Vec<Line<'static>>,
+    /// Index into `lines` of each file header.
+    file_starts: Vec<usize>,
+    /// Index into `lines` of each visible hunk header.
+    hunk_starts: Vec<usize>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+struct LayoutKey {
+    width: u16,
+    layout: DiffLayout,
+    word_diff: bool,
+}
+
+/// Pair up the lines of a hunk into rows for the chosen layout, computing
+/// word-level highlights for adjacent delete/insert blocks.
+fn hunk_rows(hunk: &DiffHunk, layout: DiffLayout, word_diff_enabled: bool) -> Vec<Row> {
+    let mut rows = Vec::new();
+    let mut old_ln = hunk.old_start;
+    let mut new_ln = hunk.new_start;
+    let mut i = 0;
+    while i < hunk.lines.len() {
+        if let DiffLine::Context(text) = &hunk.lines[i] {
+            let cell = |line_number| Cell {
+                line_number,
+                kind: LineKind::Context,
+                segments: vec![(text.clone(), false)],
+            };
+            rows.push(match layout {
+                DiffLayout::Unified => Row::Unified(cell(new_ln)),
+                DiffLayout::SideBySide => Row::SideBySide(Some(cell(old_ln)), Some(cell(new_ln))),
+            });
+            old_ln += 1;
+            new_ln += 1;
+            i += 1;
+            continue;
+        }
+
+        // Collect a block of consecutive deletions and insertions.
+        let mut deleted: Vec<&str> = Vec::new();
+        let mut inserted: Vec<&str> = Vec::new();
+        while let Some(line) = hunk.lines.get(i) {
+            match line {
+                DiffLine::Delete(text) => deleted.push(text),
+                DiffLine::Insert(text) => inserted.push(text),
+                DiffLine::Context(_) => break,
+            }
+            i += 1;
+        }
+
+        let mut deleted_cells: Vec<Cell> = Vec::with_capacity(deleted.len());
+        let mut inserted_cells: Vec<Cell> = Vec::with_capacity(inserted.len());
+        for idx in 0..deleted.len().max(inserted.len()) {
+            let highlighted = match (deleted.get(idx), inserted.get(idx)) {
+                (Some(old), Some(new)) if word_diff_enabled => word_diff(old, new),
+                _ => None,
+            };
+            let (old_segments, new_segments) = match highlighted {
+                Some((old_segments, new_segments)) => (Some(old_segments), Some(new_segments)),
+                None => (None, None),
+            };
+            if let Some(old) = deleted.get(idx) {
+                deleted_cells.push(Cell {
+                    line_number: old_ln,
+                    kind: LineKind::Delete,
+                    segments: old_segments.unwrap_or_else(|| vec![(old.to_string(), false)]),
+                });
+                old_ln += 1;
+            }
+            if let Some(new) = inserted.get(idx) {
+                inserted_cells.push(Cell {
+                    line_number: new_ln,
+                    kind: LineKind::Insert,
+                    segments: new_segments.unwrap_or_else(|| vec![(new.to_string(), false)]),
+                });
+                new_ln += 1;
+            }
+        }
+
+        match layout {
+            DiffLayout::Unified => {
+                rows.extend(deleted_cells.into_iter().map(Row::Unified));
+                rows.extend(inserted_cells.into_iter().map(Row::Unified));
+            }
+            DiffLayout::SideBySide => {
+                let mut deleted_cells = deleted_cells.into_iter();
+                let mut inserted_cells = inserted_cells.into_iter();
+                loop {
+                    match (deleted_cells.next(), inserted_cells.next()) {
+                        (None, None) => break,
+                        (left, right) => rows.push(Row::SideBySide(left, right)),
+                    }
+                }
+            }
+        }
+    }
+    rows
+}
+
+/// Split styled segments into display rows no wider than `width` columns.
+fn wrap_segments(segments: &[(String, Style)], width: usize) -> Vec<Vec<Span<'static>>> {
+    let width = width.max(1);
+    let mut rows: Vec<Vec<Span<'static>>> = Vec::new();
+    let mut row: Vec<Span<'static>> = Vec::new();
+    let mut row_width = 0;
+    for (text, style) in segments {
+        let mut chunk = String::new();
+        for ch in text.chars() {
+            let expanded: &[char] = if ch == '\t' { &[' '; TAB_WIDTH] } else { &[ch] };
+            for &c in expanded {
+                let w = c.width().unwrap_or(0);
+                if row_width + w > width && row_width > 0 {
+                    if !chunk.is_empty() {
+                        row.push(Span::styled(std::mem::take(&mut chunk), *style));
+                    }
+                    rows.push(std::mem::take(&mut row));
+                    row_width = 0;
+                }
+                chunk.push(c);
+                row_width += w;
+            }
+        }
+        if !chunk.is_empty() {
+            row.push(Span::styled(chunk, *style));
+        }
+    }
+    if !row.is_empty() || rows.is_empty() {
+        rows.push(row);
+    }
+    rows
+}
+
+/// Render one side of a row: a dimmed line-number gutter, the diff sign and
+/// the wrapped content, each output row padded to exactly `width` columns.
+fn render_cell(cell: Option<&Cell>, number_width: usize, width: usize) -> Vec<Vec<Span<'static>>> {
+    let gutter_width = number_width + 2;
+    let Some(cell) = cell else {
+        return vec![vec![Span::from(" ".repeat(width))]];
+    };
+    let base = cell.kind.style();
+    let styled: Vec<(String, Style)> = cell
+        .segments
+        .iter()
+        .map(|(text, changed)| {
+            let style = if *changed {
+                base.add_modifier(Modifier::REVERSED)
+            } else {
+                base
+            };
+            (text.clone(), style)
+        })
+        .collect();
+    let content_width = width.saturating_sub(gutter_width).max(1);
+    wrap_segments(&styled, content_width)
+        .into_iter()
+        .enumerate()
+        .map(|(idx, mut spans)| {
+            let gutter = if idx == 0 {
+                format!("{:>number_width$} ", cell.line_number)
+            } else {
+                " ".repeat(number_width + 1)
+            };
+            let sign = if idx == 0 { cell.kind.sign() } else { ' ' };
+            let used: usize = spans.iter().map(Span::width).sum();
+            let mut out = vec![
+                Span::styled(gutter, Style::default().add_modifier(Modifier::DIM)),
+                Span::styled(sign.to_string(), base),
+            ];
+            out.append(&mut spans);
+            let pad = content_width.saturating_sub(used);
+            if pad > 0 {
+                out.push(Span::from(" ".repeat(pad)));
+            }
+            out
+        })
+        .collect()
+}
+
+fn render_file_header(file: &FileDiff, collapsed: bool) -> Line<'static> {
+    let (added, removed) = file.line_counts();
+    let marker = if collapsed { "▸ " } else { "▾ " };
+    let mut spans: Vec<Span<'static>> = vec![marker.dim(), file.path.clone().bold()];
+    if let Some(move_path) = &file.move_path {
+        spans.push(" → ".into());
+        spans.push(move_path.clone().bold());
+    }
+    spans.push(" ".into());
+    match file.status {
+        FileStatus::Added => spans.push("(new) ".dim()),
+        FileStatus::Deleted => spans.push("(deleted) ".dim()),
+        FileStatus::Modified | FileStatus::Renamed => {}
+    }
+    spans.push("(".into());
+    spans.push(format!("+{added}").green());
+    spans.push(" ".into());
+    spans.push(format!("-{removed}").red());
+    spans.push(")".into());
+    if file.is_binary {
+        spans.push(" binary".dim());
+    }
+    Line::from(spans)
+}
+
+fn render_hunk_header(hunk: &DiffHunk) -> Line<'static> {
+    let mut spans: Vec<Span<'static>> = vec![
+        format!(
+            "@@ -{},{} +{},{} @@",
+            hunk.old_start, hunk.old_len, hunk.new_start, hunk.new_len
+        )
+        .cyan(),
+    ];
+    if !hunk.section.is_empty() {
+        spans.push(" ".into());
+        spans.push(hunk.section.clone().dim());
+    }
+    Line::from(spans)
+}
+
+fn render_files(
+    files: &[FileDiff],
+    collapsed: &[bool],
+    layout: DiffLayout,
+    word_diff_enabled: bool,
+    width: u16,
+) -> RenderedDiff {
+    let width = width.max(1) as usize;
+    let number_width = files
+        .iter()
+        .flat_map(|f| f.hunks.iter())
+        .map(|h| (h.old_start + h.old_len).max(h.new_start + h.new_len))
+        .max()
+        .unwrap_or(0)
+        .to_string()
+        .len();
+    let mut out = RenderedDiff::default();
+    for (idx, file) in files.iter().enumerate() {
+        if idx > 0 {
+            out.lines.push(Line::from(""));
+        }
+        let is_collapsed = collapsed.get(idx).copied().unwrap_or(false);
+        out.file_starts.push(out.lines.len());
+        out.lines.push(render_file_header(file, is_collapsed));
+        if is_collapsed {
+            continue;
+        }
+        for hunk in &file.hunks {
+            out.hunk_starts.push(out.lines.len());
+            out.lines.push(render_hunk_header(hunk));
+            for row in hunk_rows(hunk, layout, word_diff_enabled) {
+                match row {
+                    Row::Unified(cell) => {
+                        out.lines.extend(
+                            render_cell(Some(&cell), number_width, width)
+                                .into_iter()
+                                .map(Line::from),
+                        );
+                    }
+                    Row::SideBySide(left, right) => {
+                        let column = width.saturating_sub(1) / 2;
+                        let mut left = render_cell(left.as_ref(), number_width, column);
+                        let mut right = render_cell(right.as_ref(), number_width, column);
+                        let height = left.len().max(right.len());
+                        let blank = || vec![Span::from(" ".repeat(column))];
+                        left.resize_with(height, blank);
+                        right.resize_with(height, blank);
+                        for (mut l, mut r) in left.into_iter().zip(right) {
+                            l.push("│".dim());
+                            l.append(&mut r);
+                            out.lines.push(Line::from(l));
+                        }
+                    }
+                }
+            }
+        }
+    }
+    out
+}
+
+/// Full-screen, navigable diff viewer.
+pub(crate) struct DiffOverlay {
+    title: String,
+    files: Vec<FileDiff>,
+    collapsed: Vec<bool>,
+    /// Explicit layout choice; `None` picks one based on terminal width.
+    layout_override: Option<DiffLayout>,
+    word_diff: bool,
+    scroll_offset: usize,
+    rendered: Option<(LayoutKey, RenderedDiff)>,
+    last_content_area: Option<Rect>,
+    is_done: bool,
+}
+
+impl DiffOverlay {
+    pub(crate) fn new(files: Vec<FileDiff>, title: String) -> Self {
+        let collapsed = vec![false; files.len()];
+        Self {
+            title,
+            files,
+            collapsed,
+            layout_override: None,
+            word_diff: true,
+            scroll_offset: 0,
+            rendered: None,
+            last_content_area: None,
+            is_done: false,
+        }
+    }
+
+    pub(crate) fn is_done(&self) -> bool {
+        self.is_done
+    }
+
+    fn layout_for_width(&self, width: u16) -> DiffLayout {
+        self.layout_override
+            .unwrap_or(if width >= SIDE_BY_SIDE_MIN_WIDTH {
+                DiffLayout::SideBySide
+            } else {
+                DiffLayout::Unified
+            })
+    }
+
+    fn ensure_rendered(&mut self, width: u16) -> &RenderedDiff {
+        let key = LayoutKey {
+            width,
+            layout: self.layout_for_width(width),
+            word_diff: self.word_diff,
+        };
+        if !matches!(&self.rendered, Some((k, _)) if *k == key) {
+            let rendered = render_files(
+                &self.files,
+                &self.collapsed,
+                key.layout,
+                key.word_diff,
+                width,
+            );
+            self.rendered = Some((key, rendered));
+        }
+        match &self.rendered {
+            Some((_, rendered)) => rendered,
+            None => unreachable!("rendered diff was just populated"),
+        }
+    }
+
+    /// Index of the file whose section contains the top visible line.
+    fn current_file(&self) -> usize {
+        self.rendered
+            .as_ref()
+            .map(|(_, r)| {
+                r.file_starts
+                    .iter()
+                    .rposition(|&start| start <= self.scroll_offset)
+                    .unwrap_or(0)
+            })
+            .unwrap_or(0)
+    }
+
+    fn content_height(&self) -> usize {
+        self.last_content_area
+            .map(|a| a.height as usize)
+            .unwrap_or(0)
+    }
+
+    fn max_scroll(&self) -> usize {
+        let total = self
+            .rendered
+            .as_ref()
+            .map(|(_, r)| r.lines.len())
+            .unwrap_or(0);
+        total.saturating_sub(self.content_height())
+    }
+
+    fn jump_forward(&mut self, starts: impl Fn(&RenderedDiff) -> &[usize]) {
+        if let Some((_, rendered)) = &self.rendered
+            && let Some(&next) = starts(rendered).iter().find(|&&s| s > self.scroll_offset)
+        {
+            self.scroll_offset = next;
+        }
+    }
+
+    fn jump_backward(&mut self, starts: impl Fn(&RenderedDiff) -> &[usize]) {
+        if let Some((_, rendered)) = &self.rendered
+            && let Some(&prev) = starts(rendered)
+                .iter()
+                .rev()
+                .find(|&&s| s < self.scroll_offset)
+        {
+            self.scroll_offset = prev;
+        }
+    }
+
+    fn toggle_current_file(&mut self) {
+        let idx = self.current_file();
+        if let Some(flag) = self.collapsed.get_mut(idx) {
+            *flag = !*flag;
+            self.rendered = None;
+            if let Some(area) = self.last_content_area {
+                let start = self
+                    .ensure_rendered(area.width)
+                    .file_starts
+                    .get(idx)
+                    .copied();
+                self.scroll_offset = start.unwrap_or(0);
+            }
+        }
+    }
+
+    fn toggle_layout(&mut self) {
+        let width = self
+            .last_content_area
+            .map(|a| a.width)
+            .unwrap_or(SIDE_BY_SIDE_MIN_WIDTH);
+        self.layout_override = Some(match self.layout_for_width(width) {
+            DiffLayout::Unified => DiffLayout::SideBySide,
+            DiffLayout::SideBySide => DiffLayout::Unified,
+        });
+        self.keep_current_file_in_view();
+    }
+
+    fn toggle_word_diff(&mut self) {
+        self.word_diff = !self.word_diff;
+        self.keep_current_file_in_view();
+    }
+
+    /// After re-laying out the diff, scroll back to the file that was on top.
+    fn keep_current_file_in_view(&mut self) {
+        let idx = self.current_file();
+        if let Some(area) = self.last_content_area {
+            let start = self
+                .ensure_rendered(area.width)
+                .file_starts
+                .get(idx)
+                .copied();
+            self.scroll_offset = start.unwrap_or(0);
+        }
+    }
+
+    fn handle_key_event(&mut self, tui: &mut tui::Tui, key_event: KeyEvent) {
+        if !matches!(key_event.kind, KeyEventKind::Press | KeyEventKind::Repeat) {
+            return;
+        }
+        let page = self.content_height().max(1);
+        match key_event.code {
+            KeyCode::Char('q') => self.is_done = true,
+            KeyCode::Char('c') if key_event.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.is_done = true;
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.scroll_offset = self.scroll_offset.saturating_sub(1);
+            }
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.scroll_offset = self.scroll_offset.saturating_add(1);
+            }
+            KeyCode::PageUp => self.scroll_offset = self.scroll_offset.saturating_sub(page),
+            KeyCode::PageDown | KeyCode::Char(' ') => {
+                self.scroll_offset = self.scroll_offset.saturating_add(page);
+            }
+            KeyCode::Home => self.scroll_offset = 0,
+            KeyCode::End => self.scroll_offset = usize::MAX,
+            KeyCode::Char('n') => self.jump_forward(|r| &r.hunk_starts),
+            KeyCode::Char('p') => self.jump_backward(|r| &r.hunk_starts),
+            KeyCode::Char(']') => self.jump_forward(|r| &r.file_starts),
+            KeyCode::Char('[') => self.jump_backward(|r| &r.file_starts),
+            KeyCode::Enter => self.toggle_current_file(),
+            KeyCode::Char('s') => self.toggle_layout(),
+            KeyCode::Char('w') => self.toggle_word_diff(),
+            _ => return,
+        }
+        self.scroll_offset = self.scroll_offset.min(self.max_scroll());
+        tui.frame_requester()
+            .schedule_frame_in(Duration::from_millis(16));
+    }
+
+    pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
+        match event {
+            TuiEvent::Key(key_event) => {
+                self.handle_key_event(tui, key_event);
+                Ok(())
+            }
+            TuiEvent::Draw => {
+                tui.draw(u16::MAX, |frame| {
+                    self.render(frame.area(), frame.buffer);
+                })?;
+                Ok(())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    pub(crate) fn render(&mut self, area: Rect, buf: &mut Buffer) {
+        Clear.render(area, buf);
+        let top_h = area.height.saturating_sub(3);
+        let content_area = Rect::new(
+            area.x,
+            area.y.saturating_add(1),
+            area.width,
+            top_h.saturating_sub(2),
+        );
+        self.last_content_area = Some(content_area);
+        self.ensure_rendered(content_area.width);
+        self.scroll_offset = self.scroll_offset.min(self.max_scroll());
+
+        // Header
+        Span::from("/ ".repeat(area.width as usize / 2))
+            .dim()
+            .render_ref(area, buf);
+        format!("/ {}", self.title).dim().render_ref(area, buf);
+
+        // Content
+        let current_file = self.current_file();
+        let Some((key, rendered)) = &self.rendered else {
+            return;
+        };
+        let start = self.scroll_offset.min(rendered.lines.len());
+        let end = (start + content_area.height as usize).min(rendered.lines.len());
+        Paragraph::new(rendered.lines[start..end].to_vec()).render_ref(content_area, buf);
+        for y in (end - start)..content_area.height as usize {
+            let y = content_area.y.saturating_add(y as u16);
+            Span::from("~")
+                .dim()
+                .render_ref(Rect::new(content_area.x, y, 1, 1), buf);
+        }
+
+        // Bottom bar: separator, current file and scroll percentage.
+        let sep_rect = Rect::new(area.x, content_area.bottom(), area.width, 1);
+        Span::from("─".repeat(sep_rect.width as usize))
+            .dim()
+            .render_ref(sep_rect, buf);
+        if let Some(file) = self.files.get(current_file) {
+            format!(" {}/{} {} ", current_file + 1, self.files.len(), file.path)
+                .dim()
+                .render_ref(
+                    Rect::new(
+                        sep_rect.x + 1,
+                        sep_rect.y,
+                        sep_rect.width.saturating_sub(10),
+                        1,
+                    ),
+                    buf,
+                );
+        }
+        let max_scroll = rendered
+            .lines
+            .len()
+            .saturating_sub(content_area.height as usize);
+        let percent = if max_scroll == 0 {
+            100
+        } else {
+            ((self.scroll_offset as f32 / max_scroll as f32) * 100.0).round() as u8
+        };
+        let pct_text = format!(" {percent}% ");
+        let pct_w = pct_text.chars().count() as u16;
+        let pct_x = sep_rect.x + sep_rect.width.saturating_sub(pct_w + 1);
+        Span::from(pct_text)
+            .dim()
+            .render_ref(Rect::new(pct_x, sep_rect.y, pct_w, 1), buf);
+
+        // Key hints
+        let hints_y = area.y + top_h;
+        render_key_hints(
+            Rect::new(area.x, hints_y, area.width, 1),
+            buf,
+            PAGER_KEY_HINTS,
+        );
+        let layout_hint = match key.layout {
+            DiffLayout::Unified => "side-by-side",
+            DiffLayout::SideBySide => "unified",
+        };
+        let words_hint = if key.word_diff {
+            "hide word diff"
+        } else {
+            "word diff"
+        };
+        render_key_hints(
+            Rect::new(area.x, hints_y.saturating_add(1), area.width, 1),
+            buf,
+            &[
+                ("n/p", "hunk"),
+                ("[/]", "file"),
+                ("⏎", "fold"),
+                ("s", layout_hint),
+                ("w", words_hint),
+                ("q", "quit"),
+            ],
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use insta::assert_snapshot;
+    use pretty_assertions::assert_eq;
+    use ratatui::Terminal;
+    use ratatui::backend::TestBackend;
+
+    const GIT_DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,4 +1,4 @@ mod foo;
+ fn main() {
+-    let answer = compute(41);
++    let answer = compute(42);
+     println!(\"{answer}\");
+ }
+@@ -10,2 +10,3 @@ fn compute(x: u32) -> u32 {
+ fn helper() {}
++fn other() {}
+ // end
+diff --git a/notes.txt b/notes.txt
+new file mode 100644
+index 0000000..3333333
+--- /dev/null
++++ b/notes.txt
+@@ -0,0 +1,2 @@
++first
++--- not a header
+";
+
+    fn render_to_string(overlay: &mut DiffOverlay, width: u16, height: u16) -> String {
+        let mut term = Terminal::new(TestBackend::new(width, height)).expect("term");
+        term.draw(|f| overlay.render(f.area(), f.buffer_mut()))
+            .expect("draw");
+        let buf = term.backend().buffer();
+        let mut out = String::new();
+        for y in 0..height {
+            let mut row = String::new();
+            for x in 0..width {
+                row.push_str(buf[(x, y)].symbol());
+            }
+            out.push_str(row.trim_end());
+            out.push('\n');
+        }
+        out
+    }
+
+    #[test]
+    fn parses_multi_file_git_diff() {
+        let files = parse_unified_diff(GIT_DIFF);
+        assert_eq!(files.len(), 2);
+
+        let lib = &files[0];
+        assert_eq!(lib.path, "src/lib.rs");
+        assert_eq!(lib.status, FileStatus::Modified);
+        assert_eq!(lib.hunks.len(), 2);
+        assert_eq!(lib.hunks[0].section, "mod foo;");
+        assert_eq!(lib.line_counts(), (2, 1));
+
+        let notes = &files[1];
+        assert_eq!(notes.path, "notes.txt");
+        assert_eq!(notes.status, FileStatus::Added);
+        assert_eq!(
+            notes.hunks[0].lines,
+            vec![
+                DiffLine::Insert("first".to_string()),
+                DiffLine::Insert("--- not a header".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn parses_renames_and_deletions() {
+        let diff = "\
+diff --git a/old.rs b/new.rs
+similarity index 90%
+rename from old.rs
+rename to new.rs
+diff --git a/gone.txt b/gone.txt
+deleted file mode 100644
+--- a/gone.txt
++++ /dev/null
+@@ -1 +0,0 @@
+-bye
+";
+        let files = parse_unified_diff(diff);
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].path, "old.rs");
+        assert_eq!(files[0].move_path.as_deref(), Some("new.rs"));
+        assert_eq!(files[0].status, FileStatus::Renamed);
+        assert_eq!(files[1].status, FileStatus::Deleted);
+        assert_eq!(files[1].line_counts(), (0, 1));
+    }
+
+    #[test]
+    fn word_diff_marks_only_changed_words() {
+        let (old, new) =
+            word_diff("let answer = compute(41);", "let answer = compute(42);").expect("diff");
+        let changed_old: Vec<&str> = old
+            .iter()
+            .filter(|(_, c)| *c)
+            .map(|(s, _)| s.as_str())
+            .collect();
+        let changed_new: Vec<&str> = new
+            .iter()
+            .filter(|(_, c)| *c)
+            .map(|(s, _)| s.as_str())
+            .collect();
+        assert_eq!(changed_old, vec!["compute(41);"]);
+        assert_eq!(changed_new, vec!["compute(42);"]);
+        assert!(word_diff("alpha beta", "gamma delta").is_none());
+    }
+
+    #[test]
+    fn navigation_jumps_between_hunks_and_files() {
+        let mut overlay = DiffOverlay::new(parse_unified_diff(GIT_DIFF), "D I F F".into());
+        render_to_string(&mut overlay, 80, 8);
+        let (hunks, files) = {
+            let (_, rendered) = overlay.rendered.as_ref().expect("rendered");
+            (rendered.hunk_starts.clone(), rendered.file_starts.clone())
+        };
+        assert_eq!(files.len(), 2);
+        assert_eq!(hunks.len(), 3);
+
+        overlay.jump_forward(|r| &r.hunk_starts);
+        assert_eq!(overlay.scroll_offset, hunks[0]);
+        overlay.jump_forward(|r| &r.hunk_starts);
+        assert_eq!(overlay.scroll_offset, hunks[1]);
+        overlay.jump_forward(|r| &r.file_starts);
+        assert_eq!(overlay.scroll_offset, files[1]);
+        assert_eq!(overlay.current_file(), 1);
+        overlay.jump_backward(|r| &r.file_starts);
+        assert_eq!(overlay.scroll_offset, files[0]);
+    }
+
+    #[test]
+    fn collapsing_a_file_hides_its_hunks() {
+        let mut overlay = DiffOverlay::new(parse_unified_diff(GIT_DIFF), "D I F F".into());
+        render_to_string(&mut overlay, 80, 20);
+        overlay.toggle_current_file();
+        let rendered = render_to_string(&mut overlay, 80, 20);
+        assert!(rendered.contains("▸ src/lib.rs"));
+        assert!(!rendered.contains("compute(41)"));
+        assert!(rendered.contains("▾ notes.txt"));
+    }
+
+    #[test]
+    fn diff_overlay_unified_snapshot() {
+        let mut overlay = DiffOverlay::new(parse_unified_diff(GIT_DIFF), "D I F F".into());
+        assert_snapshot!(render_to_string(&mut overlay, 80, 24));
+    }
+
+    #[test]
+    fn diff_overlay_side_by_side_snapshot() {
+        let mut overlay = DiffOverlay::new(parse_unified_diff(GIT_DIFF), "D I F F".into());
+        assert_snapshot!(render_to_string(&mut overlay, 120, 20));
+    }
+
+    #[test]
+    fn files_from_changes_builds_hunks() {
+        let mut changes = HashMap::new();
+        changes.insert(
+            PathBuf::from("b.txt"),
+            FileChange::Update {
+                unified_diff: diffy::create_patch("one\ntwo\n", "one\n2\n").to_string(),
+                move_path: None,
+            },
+        );
+        changes.insert(
+            PathBuf::from("a.txt"),
+            FileChange::Add {
+                content: "hello\n".to_string(),
+            },
+        );
+        let files = files_from_changes(&changes, Path::new("/"));
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].status, FileStatus::Added);
+        assert_eq!(files[0].line_counts(), (1, 0));
+        assert_eq!(files[1].line_counts(), (1, 1));
+    }
+}
@@ -26,8 +26,13 @@ use codex_core::plan_tool::StepStatus;
 use codex_core::plan_tool::UpdatePlanArgs;
 use codex_core::protocol::FileChange;
 use codex_core::protocol::McpInvocation;
+use codex_core::protocol::McpServerStatus;
+use codex_core::protocol::ScreenshotCapturedEvent;
 use codex_core::protocol::SessionConfiguredEvent;
+use codex_core::protocol::SubAgentEndEvent;
+use codex_core::protocol::SubAgentStatus;
 use codex_core::protocol_config_types::ReasoningEffort as ReasoningEffortConfig;
+use codex_protocol::num_format::format_with_separators;
 use image::DynamicImage;
 use image::ImageReader;
 use mcp_types::EmbeddedResourceResource;
@@ -615,6 +620,8 @@ pub(crate) struct McpToolCallCell {
     start_time: Instant,
     duration: Option<Duration>,
     result: Option<Result<mcp_types::CallToolResult, String>>,
+    /// Latest progress or log message from the server, shown while running.
+    progress: Option<String>,
 }
 
 impl McpToolCallCell {
@@ -625,6 +632,7 @@ impl McpToolCallCell {
             start_time: Instant::now(),
             duration: None,
             result: None,
+            progress: None,
         }
     }
 
@@ -632,6 +640,10 @@ impl McpToolCallCell {
         &self.call_id
     }
 
+    pub(crate) fn set_progress(&mut self, progress: String) {
+        self.progress = Some(progress);
+    }
+
     pub(crate) fn complete(
         &mut self,
         duration: Duration,
@@ -658,24 +670,55 @@ impl McpToolCallCell {
         self.result = Some(Err("interrupted".to_string()));
     }
 
-    fn render_content_block(block: &mcp_types::ContentBlock, width: usize) -> String {
-        match block {
-            mcp_types::ContentBlock::TextContent(text) => {
-                format_and_truncate_tool_result(&text.text, TOOL_CALL_MAX_LINES, width)
-            }
-            mcp_types::ContentBlock::ImageContent(_) => "<image content>".to_string(),
-            mcp_types::ContentBlock::AudioContent(_) => "<audio content>".to_string(),
-            mcp_types::ContentBlock::EmbeddedResource(resource) => {
-                let uri = match &resource.resource {
-                    EmbeddedResourceResource::TextResourceContents(text) => text.uri.clone(),
-                    EmbeddedResourceResource::BlobResourceContents(blob) => blob.uri.clone(),
-                };
-                format!("embedded resource: {uri}")
-            }
-            mcp_types::ContentBlock::ResourceLink(ResourceLink { uri, .. }) => {
-                format!("link: {uri}")
+    /// One line summarizing the non-text blocks of a result, e.g.
+    /// `2 images; 1 link: file:///docs/styles.md`.
+    fn summarize_non_text_blocks(content: &[mcp_types::ContentBlock]) -> Option<String> {
+        let mut images = 0;
+        let mut audio = 0;
+        let mut resources: Vec<&str> = Vec::new();
+        let mut links: Vec<&str> = Vec::new();
+        for block in content {
+            match block {
+                mcp_types::ContentBlock::TextContent(_) => {}
+                mcp_types::ContentBlock::ImageContent(_) => images += 1,
+                mcp_types::ContentBlock::AudioContent(_) => audio += 1,
+                mcp_types::ContentBlock::EmbeddedResource(resource) => {
+                    resources.push(match &resource.resource {
+                        EmbeddedResourceResource::TextResourceContents(text) => text.uri.as_str(),
+                        EmbeddedResourceResource::BlobResourceContents(blob) => blob.uri.as_str(),
+                    });
+                }
+                mcp_types::ContentBlock::ResourceLink(ResourceLink { uri, .. }) => {
+                    links.push(uri.as_str());
+                }
             }
         }
+
+        let count = |n: usize, singular: &str, plural: &str| {
+            format!("{n} {}", if n == 1 { singular } else { plural })
+        };
+        let mut parts = Vec::new();
+        if images > 0 {
+            parts.push(count(images, "image", "images"));
+        }
+        if audio > 0 {
+            parts.push(count(audio, "audio clip", "audio clips"));
+        }
+        if !resources.is_empty() {
+            parts.push(format!(
+                "{}: {}",
+                count(resources.len(), "resource", "resources"),
+                resources.join(", ")
+            ));
+        }
+        if !links.is_empty() {
+            parts.push(format!(
+                "{}: {}",
+                count(links.len(), "link", "links"),
+                links.join(", ")
+            ));
+        }
+        (!parts.is_empty()).then(|| parts.join("; "))
     }
 }
 
@@ -719,22 +762,42 @@ impl HistoryCell for McpToolCallCell {
 
         let mut detail_lines: Vec<Line<'static>> = Vec::new();
 
+        if self.result.is_none()
+            && let Some(progress) = &self.progress
+        {
+            let line = Line::from(progress.clone().dim());
+            let wrapped = word_wrap_line(
+                &line,
+                RtOptions::new((width as usize).saturating_sub(4))
+                    .initial_indent("".into())
+                    .subsequent_indent("    ".into()),
+            );
+            detail_lines.extend(wrapped.iter().map(line_to_static));
+        }
+
         if let Some(result) = &self.result {
             match result {
                 Ok(mcp_types::CallToolResult { content, .. }) => {
-                    if !content.is_empty() {
-                        for block in content {
-                            let text = Self::render_content_block(block, width as usize);
-                            for segment in text.split('\n') {
-                                let line = Line::from(segment.to_string().dim());
-                                let wrapped = word_wrap_line(
-                                    &line,
-                                    RtOptions::new((width as usize).saturating_sub(4))
-                                        .initial_indent("".into())
-                                        .subsequent_indent("    ".into()),
-                                );
-                                detail_lines.extend(wrapped.iter().map(line_to_static));
-                            }
+                    let texts = content.iter().filter_map(|block| match block {
+                        mcp_types::ContentBlock::TextContent(text) => {
+                            Some(format_and_truncate_tool_result(
+                                &text.text,
+                                TOOL_CALL_MAX_LINES,
+                                width as usize,
+                            ))
+                        }
+                        _ => None,
+                    });
+                    for text in texts.chain(Self::summarize_non_text_blocks(content)) {
+                        for segment in text.split('\n') {
+                            let line = Line::from(segment.to_string().dim());
+                            let wrapped = word_wrap_line(
+                                &line,
+                                RtOptions::new((width as usize).saturating_sub(4))
+                                    .initial_indent("".into())
+                                    .subsequent_indent("    ".into()),
+                            );
+                            detail_lines.extend(wrapped.iter().map(line_to_static));
                         }
                     }
                 }
@@ -793,6 +856,72 @@ pub(crate) fn new_web_search_call(query: String) -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
 
+/// Announce a sub-agent started by the `spawn_agent` tool.
+pub(crate) fn new_sub_agent_begin(agent: String, task: String) -> PlainHistoryCell {
+    let task = task.lines().next().unwrap_or_default().to_string();
+    let lines: Vec<Line<'static>> = vec![
+        vec!["• ".dim(), "Delegated to ".into(), agent.bold()].into(),
+        vec!["  └ ".dim(), task.dim()].into(),
+    ];
+    PlainHistoryCell { lines }
+}
+
+/// Outcome of a sub-agent, with the first line of its final message.
+pub(crate) fn new_sub_agent_end(ev: SubAgentEndEvent) -> PlainHistoryCell {
+    let bullet = match ev.status {
+        SubAgentStatus::Completed => "• ".green().bold(),
+        SubAgentStatus::Failed | SubAgentStatus::BudgetExceeded => "• ".red().bold(),
+        SubAgentStatus::Interrupted => "• ".dim(),
+    };
+    let outcome = match ev.status {
+        SubAgentStatus::Completed => " finished",
+        SubAgentStatus::Failed => " failed",
+        SubAgentStatus::BudgetExceeded => " ran out of token budget",
+        SubAgentStatus::Interrupted => " was interrupted",
+    };
+    let tokens = format!(
+        " ({} tokens)",
+        format_with_separators(ev.token_usage.total_tokens)
+    );
+    let mut lines: Vec<Line<'static>> =
+        vec![vec![bullet, ev.agent.bold(), outcome.into(), tokens.dim()].into()];
+    if let Some(summary) = ev
+        .final_message
+        .as_deref()
+        .and_then(|message| message.lines().find(|line| !line.trim().is_empty()))
+    {
+        lines.push(vec!["  └ ".dim(), summary.trim().to_string().dim()].into());
+    }
+    PlainHistoryCell { lines }
+}
+
+/// Screenshot attached by the `capture_screenshot` tool. The image itself is
+/// not drawn; a box with the page's aspect ratio stands in for it.
+pub(crate) fn new_screenshot_captured(ev: ScreenshotCapturedEvent) -> PlainHistoryCell {
+    const THUMBNAIL_WIDTH: usize = 20;
+    // Terminal cells are roughly twice as tall as they are wide.
+    let inner_height = (THUMBNAIL_WIDTH as u64 * u64::from(ev.height)
+        / (u64::from(ev.width.max(1)) * 2))
+        .clamp(1, 6) as usize;
+    let size = format!("{}×{}", ev.width, ev.height);
+    let left = THUMBNAIL_WIDTH.saturating_sub(size.chars().count()) / 2;
+    let right = THUMBNAIL_WIDTH.saturating_sub(size.chars().count() + left);
+
+    let mut lines: Vec<Line<'static>> =
+        vec![vec!["• ".dim(), "Captured screenshot of ".into(), ev.url.bold()].into()];
+    lines.push(format!("  ┌{}┐", "─".repeat(THUMBNAIL_WIDTH)).dim().into());
+    for row in 0..inner_height {
+        let content = if row == inner_height / 2 {
+            format!("{}{size}{}", " ".repeat(left), " ".repeat(right))
+        } else {
+            " ".repeat(THUMBNAIL_WIDTH)
+        };
+        lines.push(format!("  │{content}│").dim().into());
+    }
+    lines.push(format!("  └{}┘", "─".repeat(THUMBNAIL_WIDTH)).dim().into());
+    PlainHistoryCell { lines }
+}
+
 /// If the first content is an image, return a new cell with the image.
 /// TODO(rgwood-dd): Handle images properly even if they're not the first result.
 fn try_new_completed_mcp_tool_call_with_image_output(
@@ -859,10 +988,12 @@ pub(crate) fn empty_mcp_output() -> PlainHistoryCell {
     PlainHistoryCell { lines }
 }
 
-/// Render MCP tools grouped by connection using the fully-qualified tool names.
+/// Render MCP tools grouped by connection using the fully-qualified tool names,
+/// along with each server's connection status.
 pub(crate) fn new_mcp_tools_output(
     config: &Config,
     tools: std::collections::HashMap<String, mcp_types::Tool>,
+    servers: std::collections::HashMap<String, McpServerStatus>,
 ) -> PlainHistoryCell {
     let mut lines: Vec<Line<'static>> = vec![
         "/mcp".magenta().into(),
@@ -871,13 +1002,22 @@ pub(crate) fn new_mcp_tools_output(
         "".into(),
     ];
 
-    if tools.is_empty() {
+    if tools.is_empty() && servers.is_empty() {
         lines.push("  • No MCP tools available.".italic().into());
         lines.push("".into());
         return PlainHistoryCell { lines };
     }
 
-    for (server, cfg) in config.mcp_servers.iter() {
+    // Prefer the servers reported by the session: they include servers added
+    // or removed in `config.toml` since `config` was loaded.
+    let mut server_names: Vec<&String> = if servers.is_empty() {
+        config.mcp_servers.keys().collect()
+    } else {
+        servers.keys().collect()
+    };
+    server_names.sort();
+
+    for server in server_names {
         let prefix = format!("{server}__");
         let mut names: Vec<String> = tools
             .keys()
@@ -888,8 +1028,29 @@ pub(crate) fn new_mcp_tools_output(
 
         lines.push(vec!["  • Server: ".into(), server.clone().into()].into());
 
-        match &cfg.transport {
-            McpServerTransportConfig::Stdio { command, args, env } => {
+        match servers.get(server) {
+            Some(McpServerStatus::Ready) => {
+                lines.push(vec!["    • Status: ".into(), "ready".green()].into());
+            }
+            Some(McpServerStatus::Starting) => {
+                lines.push(vec!["    • Status: ".into(), "starting".dim()].into());
+            }
+            Some(McpServerStatus::Failed { error }) => {
+                lines.push(
+                    vec![
+                        "    • Status: ".into(),
+                        "failed".red(),
+                        format!(" ({error})").dim(),
+                    ]
+                    .into(),
+                );
+            }
+            None => {}
+        }
+
+        let transport = config.mcp_servers.get(server).map(|cfg| &cfg.transport);
+        match transport {
+            Some(McpServerTransportConfig::Stdio { command, args, env }) => {
                 let args_suffix = if args.is_empty() {
                     String::new()
                 } else {
@@ -907,9 +1068,10 @@ pub(crate) fn new_mcp_tools_output(
                     lines.push(vec!["    • Env: ".into(), env_pairs.join(" ").into()].into());
                 }
             }
-            McpServerTransportConfig::StreamableHttp { url, .. } => {
+            Some(McpServerTransportConfig::StreamableHttp { url, .. }) => {
                 lines.push(vec!["    • URL: ".into(), url.clone().into()].into());
             }
+            None => {}
         }
 
         if names.is_empty() {
@@ -1263,6 +1425,52 @@ mod tests {
         insta::assert_snapshot!(rendered);
     }
 
+    #[test]
+    fn mcp_tools_output_lists_server_status() {
+        let tool = mcp_types::Tool {
+            annotations: None,
+            description: None,
+            input_schema: mcp_types::ToolInputSchema {
+                properties: None,
+                required: None,
+                r#type: "object".to_string(),
+            },
+            name: "search".to_string(),
+            output_schema: None,
+            title: None,
+        };
+        let tools = HashMap::from([("docs__search".to_string(), tool)]);
+        let servers = HashMap::from([
+            ("docs".to_string(), McpServerStatus::Ready),
+            (
+                "broken".to_string(),
+                McpServerStatus::Failed {
+                    error: "connection to the server was lost".to_string(),
+                },
+            ),
+        ]);
+
+        let cell = new_mcp_tools_output(&test_config(), tools, servers);
+
+        assert_eq!(
+            render_lines(&cell.display_lines(80)),
+            vec![
+                "/mcp",
+                "",
+                "🔌  MCP Tools",
+                "",
+                "  • Server: broken",
+                "    • Status: failed (connection to the server was lost)",
+                "    • Tools: (none)",
+                "",
+                "  • Server: docs",
+                "    • Status: ready",
+                "    • Tools: search",
+                "",
+            ]
+        );
+    }
+
     #[test]
     fn completed_mcp_tool_call_error_snapshot() {
         let invocation = McpInvocation {
@@ -1329,6 +1537,93 @@ mod tests {
         insta::assert_snapshot!(rendered);
     }
 
+    #[test]
+    fn completed_mcp_tool_call_summarizes_non_text_blocks() {
+        let invocation = McpInvocation {
+            server: "charts".into(),
+            tool: "render".into(),
+            arguments: None,
+        };
+        let image = || {
+            ContentBlock::ImageContent(mcp_types::ImageContent {
+                annotations: None,
+                data: "iVBORw0KGgo=".into(),
+                mime_type: "image/png".into(),
+                r#type: "image".into(),
+            })
+        };
+        let result = CallToolResult {
+            content: vec![
+                ContentBlock::TextContent(TextContent {
+                    annotations: None,
+                    text: "Rendered 2 charts.".into(),
+                    r#type: "text".into(),
+                }),
+                image(),
+                image(),
+                ContentBlock::EmbeddedResource(mcp_types::EmbeddedResource {
+                    annotations: None,
+                    resource: EmbeddedResourceResource::TextResourceContents(
+                        mcp_types::TextResourceContents {
+                            mime_type: None,
+                            text: "a,b\n1,2".into(),
+                            uri: "file:///data.csv".into(),
+                        },
+                    ),
+                    r#type: "resource".into(),
+                }),
+            ],
+            is_error: None,
+            structured_content: None,
+        };
+
+        let mut cell = new_active_mcp_tool_call("call-7".into(), invocation);
+        assert!(
+            cell.complete(Duration::from_millis(10), Ok(result))
+                .is_none()
+        );
+
+        let rendered = render_lines(&cell.display_lines(80));
+        assert_eq!(
+            rendered[1..].to_vec(),
+            vec![
+                "  └ Rendered 2 charts.",
+                "    2 images; 1 resource: file:///data.csv",
+            ]
+        );
+    }
+
+    #[test]
+    fn active_mcp_tool_call_shows_latest_progress_until_complete() {
+        let invocation = McpInvocation {
+            server: "builds".into(),
+            tool: "run".into(),
+            arguments: None,
+        };
+        let mut cell = new_active_mcp_tool_call("call-8".into(), invocation);
+        cell.set_progress("3/10 compiling".into());
+        cell.set_progress("4/10 linking".into());
+
+        let rendered = render_lines(&cell.display_lines(80));
+        assert_eq!(rendered[1..].to_vec(), vec!["  └ 4/10 linking"]);
+
+        let result = CallToolResult {
+            content: vec![ContentBlock::TextContent(TextContent {
+                annotations: None,
+                text: "Build finished.".into(),
+                r#type: "text".into(),
+            })],
+            is_error: None,
+            structured_content: None,
+        };
+        assert!(
+            cell.complete(Duration::from_millis(10), Ok(result))
+                .is_none()
+        );
+        let rendered = render_lines(&cell.display_lines(80));
+        assert_eq!(rendered[1..].to_vec(), vec!["  └ Build finished."]);
+    }
+
     #[test]
     fn completed_mcp_tool_call_wrapped_outputs_snapshot() {
         let invocation = McpInvocation {
@@ -1840,6 +2135,59 @@ mod tests {
         insta::assert_snapshot!(rendered);
     }
 
+    #[test]
+    fn sub_agent_cells_summarize_task_and_outcome() {
+        let begin = new_sub_agent_begin(
+            "tests".to_string(),
+            "Fix the failing tests in core\nThen run clippy".to_string(),
+        );
+        assert_eq!(
+            render_lines(&begin.display_lines(80)),
+            vec!["• Delegated to tests", "  └ Fix the failing tests in core"]
+        );
+
+        let end = new_sub_agent_end(SubAgentEndEvent {
+            call_id: "call-1".to_string(),
+            agent: "tests".to_string(),
+            status: SubAgentStatus::BudgetExceeded,
+            final_message: Some("\nFixed two of three tests.\nDetails...".to_string()),
+            token_usage: codex_core::protocol::TokenUsage {
+                total_tokens: 12_345,
+                ..Default::default()
+            },
+        });
+        assert_eq!(
+            render_lines(&end.display_lines(80)),
+            vec![
+                "• tests ran out of token budget (12,345 tokens)",
+                "  └ Fixed two of three tests.",
+            ]
+        );
+    }
+
+    #[test]
+    fn screenshot_cell_shows_placeholder_with_aspect_ratio() {
+        let cell = new_screenshot_captured(ScreenshotCapturedEvent {
+            call_id: "call-1".to_string(),
+            url: "http://localhost:3000/".to_string(),
+            width: 800,
+            height: 400,
+        });
+        assert_eq!(
+            render_lines(&cell.display_lines(80)),
+            vec![
+                "• Captured screenshot of http://localhost:3000/",
+                "  ┌────────────────────┐",
+                "  │                    │",
+                "  │                    │",
+                "  │      800×400       │",
+                "  │                    │",
+                "  │                    │",
+                "  └────────────────────┘",
+            ]
+        );
+    }
+
     #[test]
     fn plan_update_with_note_and_wrapping_snapshot() {
         // Long explanation forces wrapping; include long step text to verify step wrapping and alignment.
@@ -10,14 +10,18 @@ use codex_core::AuthManager;
 use codex_core::BUILT_IN_OSS_MODEL_PROVIDER_ID;
 use codex_core::CodexAuth;
 use codex_core::RolloutRecorder;
+use codex_core::cassette::Cassette;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::config::ConfigToml;
 use codex_core::config::find_codex_home;
 use codex_core::config::load_config_as_toml_with_cli_overrides;
+use codex_core::config::worktrees_dir;
 use codex_core::find_conversation_path_by_id_str;
 use codex_core::protocol::AskForApproval;
 use codex_core::protocol::SandboxPolicy;
+use codex_git_tooling::CreateWorktreeOptions;
+use codex_git_tooling::create_session_worktree;
 use codex_ollama::DEFAULT_OSS_MODEL;
 use codex_protocol::config_types::SandboxMode;
 use opentelemetry_appender_tracing::layer::OpenTelemetryTracingBridge;
@@ -32,6 +36,7 @@ mod app;
 mod app_backtrack;
 mod app_event;
 mod app_event_sender;
+mod app_tabs;
 mod ascii_animation;
 mod bottom_pane;
 mod chatwidget;
@@ -41,6 +46,7 @@ mod clipboard_paste;
 mod color;
 pub mod custom_terminal;
 mod diff_render;
+mod diff_viewer;
 mod exec_cell;
 mod exec_command;
 mod file_search;
@@ -203,6 +209,33 @@ pub async fn run_main(
         cli_profile_override,
     )?;
 
+    config.cassette = match (cli.record_cassette.clone(), cli.replay_cassette.clone()) {
+        (Some(dir), _) => Some(Cassette::record(dir)),
+        (None, Some(dir)) => Some(Cassette::replay(dir)),
+        (None, None) => None,
+    };
+    // Sampling approvals and elicitation forms are shown in the bottom pane.
+    config.answers_mcp_server_requests = true;
+
+    // Run the session in its own worktree once the sandbox policy is final so
+    // that writable roots can be moved along with the cwd.
+    let session_worktree = if cli.worktree || config.worktree {
+        let worktrees_dir = worktrees_dir(&config);
+        #[allow(clippy::print_stderr)]
+        match create_session_worktree(&CreateWorktreeOptions::new(&config.cwd, &worktrees_dir)) {
+            Ok(worktree) => {
+                config.relocate_to_worktree(worktree.repo_root(), worktree.path());
+                Some(worktree)
+            }
+            Err(err) => {
+                eprintln!("Error creating session worktree: {err}");
+                std::process::exit(1);
+            }
+        }
+    } else {
+        None
+    };
+
     let log_dir = codex_core::config::log_dir(&config)?;
     std::fs::create_dir_all(&log_dir)?;
     // Open (or create) your log file, appending to it.
@@ -239,7 +272,7 @@ pub async fn run_main(
         .with_filter(env_filter());
 
     if cli.oss {
-        codex_ollama::ensure_oss_ready(&config)
+        codex_ollama::ensure_oss_ready(&mut config)
             .await
             .map_err(|e| std::io::Error::other(format!("OSS setup failed: {e}")))?;
     }
@@ -270,6 +303,10 @@ pub async fn run_main(
 
     run_ratatui_app(cli, config, active_profile, should_show_trust_screen)
         .await
+        .map(|exit_info| AppExitInfo {
+            session_worktree,
+            ..exit_info
+        })
         .map_err(|err| std::io::Error::other(err.to_string()))
 }
 
@@ -361,7 +398,8 @@ async fn run_ratatui_app(
     // Initialize high-fidelity session event logging if enabled.
     session_log::maybe_init(&config);
 
-    let auth_manager = AuthManager::shared(config.codex_home.clone());
+    let auth_manager =
+        AuthManager::shared(config.codex_home.clone(), config.credential_store.clone());
     let login_status = get_login_status(&config);
     let should_show_onboarding =
         should_show_onboarding(login_status, &config, should_show_trust_screen);
@@ -409,6 +447,7 @@ async fn run_ratatui_app(
                 return Ok(AppExitInfo {
                     token_usage: codex_core::protocol::TokenUsage::default(),
                     conversation_id: None,
+                    session_worktree: None,
                 });
             }
             other => other,
@@ -460,7 +499,7 @@ fn get_login_status(config: &Config) -> LoginStatus {
         // Reading the OpenAI API key is an async operation because it may need
         // to refresh the token. Block on it.
         let codex_home = config.codex_home.clone();
-        match CodexAuth::from_codex_home(&codex_home) {
+        match CodexAuth::from_codex_home(&codex_home, &config.credential_store) {
             Ok(Some(auth)) => LoginStatus::AuthMode(auth.mode),
             Ok(None) => LoginStatus::NotAuthenticated,
             Err(err) => {
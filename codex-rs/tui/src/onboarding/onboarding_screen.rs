@@ -84,6 +84,7 @@ impl OnboardingScreen {
                 error: None,
                 sign_in_state: Arc::new(RwLock::new(SignInState::PickMode)),
                 codex_home: codex_home.clone(),
+                credential_store: config.credential_store,
                 login_status,
                 auth_manager,
             }))
@@ -2,6 +2,8 @@ use std::io::Result;
 use std::sync::Arc;
 use std::time::Duration;
 
+use crate::diff_viewer::DiffOverlay;
+use crate::diff_viewer::FileDiff;
 use crate::history_cell::HistoryCell;
 use crate::render::line_utils::push_owned_lines;
 use crate::tui;
@@ -26,6 +28,7 @@ use ratatui::widgets::WidgetRef;
 pub(crate) enum Overlay {
     Transcript(TranscriptOverlay),
     Static(StaticOverlay),
+    Diff(DiffOverlay),
 }
 
 impl Overlay {
@@ -37,10 +40,15 @@ impl Overlay {
         Self::Static(StaticOverlay::with_title(lines, title))
     }
 
+    pub(crate) fn new_diff(files: Vec<FileDiff>, title: String) -> Self {
+        Self::Diff(DiffOverlay::new(files, title))
+    }
+
     pub(crate) fn handle_event(&mut self, tui: &mut tui::Tui, event: TuiEvent) -> Result<()> {
         match self {
             Overlay::Transcript(o) => o.handle_event(tui, event),
             Overlay::Static(o) => o.handle_event(tui, event),
+            Overlay::Diff(o) => o.handle_event(tui, event),
         }
     }
 
@@ -48,19 +56,20 @@ impl Overlay {
         match self {
             Overlay::Transcript(o) => o.is_done(),
             Overlay::Static(o) => o.is_done(),
+            Overlay::Diff(o) => o.is_done(),
         }
     }
 }
 
 // Common pager navigation hints rendered on the first line
-const PAGER_KEY_HINTS: &[(&str, &str)] = &[
+pub(crate) const PAGER_KEY_HINTS: &[(&str, &str)] = &[
     ("↑/↓", "scroll"),
     ("PgUp/PgDn", "page"),
     ("Home/End", "jump"),
 ];
 
 // Render a single line of key hints from (key, description) pairs.
-fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(&str, &str)]) {
+pub(crate) fn render_key_hints(area: Rect, buf: &mut Buffer, pairs: &[(&str, &str)]) {
     let key_hint_style = Style::default().fg(Color::Cyan);
     let mut spans: Vec<Span<'static>> = vec![" ".into()];
     let mut first = true;
@@ -16,10 +16,12 @@ pub enum SlashCommand {
     Approvals,
     Review,
     New,
+    Tabs,
     Init,
     Compact,
     Undo,
     Diff,
+    TurnDiff,
     Mention,
     Status,
     Mcp,
@@ -34,12 +36,14 @@ impl SlashCommand {
     pub fn description(self) -> &'static str {
         match self {
             SlashCommand::New => "start a new chat during a conversation",
+            SlashCommand::Tabs => "open, switch, rename or close conversation tabs",
             SlashCommand::Init => "create an AGENTS.md file with instructions for Codex",
             SlashCommand::Compact => "summarize conversation to prevent hitting the context limit",
             SlashCommand::Review => "review my current changes and find issues",
             SlashCommand::Undo => "restore the workspace to the last Codex snapshot",
             SlashCommand::Quit => "exit Codex",
             SlashCommand::Diff => "show git diff (including untracked files)",
+            SlashCommand::TurnDiff => "show the changes from the last turn that edited files",
             SlashCommand::Mention => "mention a file",
             SlashCommand::Status => "show current session configuration and token usage",
             SlashCommand::Model => "choose what model and reasoning effort to use",
@@ -69,6 +73,8 @@ impl SlashCommand {
             | SlashCommand::Review
             | SlashCommand::Logout => false,
             SlashCommand::Diff
+            | SlashCommand::Tabs
+            | SlashCommand::TurnDiff
             | SlashCommand::Mention
             | SlashCommand::Status
             | SlashCommand::Mcp
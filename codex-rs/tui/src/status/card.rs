@@ -5,6 +5,7 @@ use crate::history_cell::with_border_with_inner_width;
 use crate::version::CODEX_CLI_VERSION;
 use codex_common::create_config_summary_entries;
 use codex_core::config::Config;
+use codex_core::config_layers::ConfigLayer;
 use codex_core::protocol::SandboxPolicy;
 use codex_core::protocol::TokenUsage;
 use codex_protocol::ConversationId;
@@ -40,24 +41,32 @@ pub(crate) struct StatusTokenUsageData {
 struct StatusHistoryCell {
     model_name: String,
     model_details: Vec<String>,
+    model_origin: Option<ConfigLayer>,
     directory: PathBuf,
     approval: String,
+    approval_origin: Option<ConfigLayer>,
     sandbox: String,
+    sandbox_origin: Option<ConfigLayer>,
+    /// The project `.codex/config.toml` in effect and the settings it supplies.
+    project_config: Option<(PathBuf, Vec<String>)>,
     agents_summary: String,
     account: Option<StatusAccountDisplay>,
     session_id: Option<String>,
     token_usage: StatusTokenUsageData,
+    cost_usd: Option<f64>,
+    max_cost_usd: Option<f64>,
     rate_limits: StatusRateLimitData,
 }
 
 pub(crate) fn new_status_output(
     config: &Config,
     usage: &TokenUsage,
+    total_cost_usd: Option<f64>,
     session_id: &Option<ConversationId>,
     rate_limits: Option<&RateLimitSnapshotDisplay>,
 ) -> CompositeHistoryCell {
     let command = PlainHistoryCell::new(vec!["/status".magenta().into()]);
-    let card = StatusHistoryCell::new(config, usage, session_id, rate_limits);
+    let card = StatusHistoryCell::new(config, usage, total_cost_usd, session_id, rate_limits);
 
     CompositeHistoryCell::new(vec![Box::new(command), Box::new(card)])
 }
@@ -66,6 +75,7 @@ impl StatusHistoryCell {
     fn new(
         config: &Config,
         usage: &TokenUsage,
+        total_cost_usd: Option<f64>,
         session_id: &Option<ConversationId>,
         rate_limits: Option<&RateLimitSnapshotDisplay>,
     ) -> Self {
@@ -81,6 +91,17 @@ impl StatusHistoryCell {
             SandboxPolicy::ReadOnly => "read-only".to_string(),
             SandboxPolicy::WorkspaceWrite { .. } => "workspace-write".to_string(),
         };
+        let layers = &config.config_layers;
+        let profile = config.active_profile.as_deref();
+        let model_origin = layers.origin_with_profile(profile, "model");
+        let approval_origin = layers.origin_with_profile(profile, "approval_policy");
+        let sandbox_origin = layers
+            .origin_with_profile(profile, "sandbox_mode")
+            .max(layers.origin("sandbox_workspace_write"));
+        let project_config = layers
+            .project_config
+            .clone()
+            .map(|path| (path, layers.settings_from(ConfigLayer::Project)));
         let agents_summary = compose_agents_summary(config);
         let account = compose_account_display(config);
         let session_id = session_id.as_ref().map(std::string::ToString::to_string);
@@ -94,13 +115,19 @@ impl StatusHistoryCell {
         Self {
             model_name,
             model_details,
+            model_origin,
             directory: config.cwd.clone(),
             approval,
+            approval_origin,
             sandbox,
+            sandbox_origin,
+            project_config,
             agents_summary,
             account,
             session_id,
             token_usage,
+            cost_usd: total_cost_usd,
+            max_cost_usd: config.budget.max_cost_usd,
             rate_limits,
         }
     }
@@ -123,6 +150,24 @@ impl StatusHistoryCell {
         ]
     }
 
+    /// Spend so far according to `model_prices`, with the `[budget]` cap if
+    /// one is set. `None` when neither is known.
+    fn cost_spans(&self) -> Option<Vec<Span<'static>>> {
+        match (self.cost_usd, self.max_cost_usd) {
+            (None, None) => None,
+            (spent, None) => spent.map(|spent| vec![Span::from(format!("${spent:.2}"))]),
+            (spent, Some(limit)) => {
+                let spent = spent
+                    .map(|spent| format!("${spent:.2}"))
+                    .unwrap_or_else(|| "unknown".to_string());
+                Some(vec![
+                    Span::from(spent),
+                    Span::from(format!(" (limit ${limit:.2})")).dim(),
+                ])
+            }
+        }
+    }
+
     fn rate_limit_lines(
         &self,
         available_inner_width: usize,
@@ -228,6 +273,9 @@ impl HistoryCell for StatusHistoryCell {
                 .collect();
         let mut seen: BTreeSet<String> = labels.iter().cloned().collect();
 
+        if self.project_config.is_some() {
+            push_label(&mut labels, &mut seen, "Project config");
+        }
         if account_value.is_some() {
             push_label(&mut labels, &mut seen, "Account");
         }
@@ -235,6 +283,10 @@ impl HistoryCell for StatusHistoryCell {
             push_label(&mut labels, &mut seen, "Session");
         }
         push_label(&mut labels, &mut seen, "Token usage");
+        let cost_spans = self.cost_spans();
+        if cost_spans.is_some() {
+            push_label(&mut labels, &mut seen, "Cost");
+        }
         self.collect_rate_limit_labels(&mut seen, &mut labels);
 
         let formatter = FieldFormatter::from_labels(labels.iter().map(String::as_str));
@@ -246,14 +298,26 @@ impl HistoryCell for StatusHistoryCell {
             model_spans.push(Span::from(self.model_details.join(", ")).dim());
             model_spans.push(Span::from(")").dim());
         }
+        push_origin(&mut model_spans, self.model_origin);
 
         let directory_value = format_directory_display(&self.directory, Some(value_width));
 
         lines.push(formatter.line("Model", model_spans));
         lines.push(formatter.line("Directory", vec![Span::from(directory_value)]));
-        lines.push(formatter.line("Approval", vec![Span::from(self.approval.clone())]));
-        lines.push(formatter.line("Sandbox", vec![Span::from(self.sandbox.clone())]));
+        let mut approval_spans = vec![Span::from(self.approval.clone())];
+        push_origin(&mut approval_spans, self.approval_origin);
+        lines.push(formatter.line("Approval", approval_spans));
+        let mut sandbox_spans = vec![Span::from(self.sandbox.clone())];
+        push_origin(&mut sandbox_spans, self.sandbox_origin);
+        lines.push(formatter.line("Sandbox", sandbox_spans));
         lines.push(formatter.line("Agents.md", vec![Span::from(self.agents_summary.clone())]));
+        if let Some((path, settings)) = self.project_config.as_ref() {
+            let path = format_directory_display(path, Some(value_width));
+            lines.push(formatter.line("Project config", vec![Span::from(path)]));
+            if !settings.is_empty() {
+                lines.push(formatter.continuation(vec![Span::from(settings.join(", ")).dim()]));
+            }
+        }
 
         if let Some(account_value) = account_value {
             lines.push(formatter.line("Account", vec![Span::from(account_value)]));
@@ -265,6 +329,9 @@ impl HistoryCell for StatusHistoryCell {
 
         lines.push(Line::from(Vec::<Span<'static>>::new()));
         lines.push(formatter.line("Token usage", self.token_usage_spans()));
+        if let Some(cost_spans) = cost_spans {
+            lines.push(formatter.line("Cost", cost_spans));
+        }
 
         lines.extend(self.rate_limit_lines(available_inner_width, &formatter));
 
@@ -278,3 +345,11 @@ impl HistoryCell for StatusHistoryCell {
         with_border_with_inner_width(truncated_lines, inner_width)
     }
 }
+
+/// Tag a value with the config layer that set it; built-in defaults are left
+/// untagged.
+fn push_origin(spans: &mut Vec<Span<'static>>, origin: Option<ConfigLayer>) {
+    if let Some(origin) = origin {
+        spans.push(Span::from(format!(" [{origin}]")).dim());
+    }
+}
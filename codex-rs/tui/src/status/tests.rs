@@ -5,6 +5,7 @@ use chrono::TimeZone;
 use codex_core::config::Config;
 use codex_core::config::ConfigOverrides;
 use codex_core::config::ConfigToml;
+use codex_core::config_layers::ConfigLayer;
 use codex_core::protocol::RateLimitSnapshot;
 use codex_core::protocol::RateLimitWindow;
 use codex_core::protocol::SandboxPolicy;
@@ -103,7 +104,7 @@ fn status_snapshot_includes_reasoning_details() {
         .expect("timestamp");
     let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
 
-    let composite = new_status_output(&config, &usage, &None, Some(&rate_display));
+    let composite = new_status_output(&config, &usage, None, &None, Some(&rate_display));
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
         for line in &mut rendered_lines {
@@ -144,7 +145,7 @@ fn status_snapshot_includes_monthly_limit() {
         .expect("timestamp");
     let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
 
-    let composite = new_status_output(&config, &usage, &None, Some(&rate_display));
+    let composite = new_status_output(&config, &usage, None, &None, Some(&rate_display));
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
         for line in &mut rendered_lines {
@@ -170,7 +171,7 @@ fn status_card_token_usage_excludes_cached_tokens() {
         total_tokens: 2_100,
     };
 
-    let composite = new_status_output(&config, &usage, &None, None);
+    let composite = new_status_output(&config, &usage, None, &None, None);
     let rendered = render_lines(&composite.display_lines(120));
 
     assert!(
@@ -179,6 +180,71 @@ fn status_card_token_usage_excludes_cached_tokens() {
     );
 }
 
+#[test]
+fn status_card_shows_cost_against_budget() {
+    let temp_home = TempDir::new().expect("temp home");
+    let mut config = test_config(&temp_home);
+    config.model = "gpt-5-codex".to_string();
+    config.cwd = PathBuf::from("/workspace/tests");
+
+    let usage = TokenUsage::default();
+    let composite = new_status_output(&config, &usage, None, &None, None);
+    let rendered = render_lines(&composite.display_lines(120));
+    assert!(
+        rendered.iter().all(|line| !line.contains("Cost")),
+        "cost should be hidden without prices or a budget, got: {rendered:?}"
+    );
+
+    config.budget.max_cost_usd = Some(5.0);
+    let composite = new_status_output(&config, &usage, Some(0.4213), &None, None);
+    let rendered = render_lines(&composite.display_lines(120));
+    assert!(
+        rendered
+            .iter()
+            .any(|line| line.contains("Cost:") && line.contains("$0.42 (limit $5.00)")),
+        "expected cost line, got: {rendered:?}"
+    );
+}
+
+#[test]
+fn status_card_shows_config_layers() {
+    let temp_home = TempDir::new().expect("temp home");
+    let mut config = test_config(&temp_home);
+    config.model = "o3".to_string();
+    config.cwd = PathBuf::from("/workspace/tests");
+    config
+        .config_layers
+        .record_key("model", ConfigLayer::Project);
+    config
+        .config_layers
+        .record_key("mcp_servers.docs.command", ConfigLayer::Project);
+    config
+        .config_layers
+        .record_key("approval_policy", ConfigLayer::Cli);
+    config.config_layers.project_config =
+        Some(PathBuf::from("/workspace/tests/.codex/config.toml"));
+
+    let usage = TokenUsage::default();
+    let composite = new_status_output(&config, &usage, None, &None, None);
+    let rendered = render_lines(&composite.display_lines(120));
+    let find = |label: &str| {
+        rendered
+            .iter()
+            .find(|line| line.contains(label))
+            .unwrap_or_else(|| panic!("missing {label} line, got: {rendered:?}"))
+    };
+    assert!(find("Model:").contains("[project]"));
+    assert!(find("Approval:").contains("[cli]"));
+    assert!(!find("Sandbox:").contains('['));
+    assert!(find("Project config:").contains(".codex/config.toml"));
+    assert!(
+        rendered
+            .iter()
+            .any(|line| line.contains("mcp_servers.docs, model")),
+        "expected project settings, got: {rendered:?}"
+    );
+}
+
 #[test]
 fn status_snapshot_truncates_in_narrow_terminal() {
     let temp_home = TempDir::new().expect("temp home");
@@ -211,7 +277,7 @@ fn status_snapshot_truncates_in_narrow_terminal() {
         .expect("timestamp");
     let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
 
-    let composite = new_status_output(&config, &usage, &None, Some(&rate_display));
+    let composite = new_status_output(&config, &usage, None, &None, Some(&rate_display));
     let mut rendered_lines = render_lines(&composite.display_lines(46));
     if cfg!(windows) {
         for line in &mut rendered_lines {
@@ -238,7 +304,7 @@ fn status_snapshot_shows_missing_limits_message() {
         total_tokens: 750,
     };
 
-    let composite = new_status_output(&config, &usage, &None, None);
+    let composite = new_status_output(&config, &usage, None, &None, None);
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
         for line in &mut rendered_lines {
@@ -274,7 +340,7 @@ fn status_snapshot_shows_empty_limits_message() {
         .expect("timestamp");
     let rate_display = rate_limit_snapshot_display(&snapshot, captured_at);
 
-    let composite = new_status_output(&config, &usage, &None, Some(&rate_display));
+    let composite = new_status_output(&config, &usage, None, &None, Some(&rate_display));
     let mut rendered_lines = render_lines(&composite.display_lines(80));
     if cfg!(windows) {
         for line in &mut rendered_lines {
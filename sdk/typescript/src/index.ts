@@ -17,6 +17,7 @@ export type {
   CommandExecutionItem,
   FileChangeItem,
   McpToolCallItem,
+  McpToolCallProgress,
   WebSearchItem,
   TodoListItem,
   ErrorItem,
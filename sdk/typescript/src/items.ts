@@ -29,12 +29,19 @@ export type FileChangeItem = {
 
 export type McpToolCallStatus = "in_progress" | "completed" | "failed";
 
+export type McpToolCallProgress = {
+  progress?: number;
+  total?: number;
+  message?: string;
+};
+
 export type McpToolCallItem = {
   id: string;
   item_type: "mcp_tool_call";
   server: string;
   tool: string;
   status: McpToolCallStatus;
+  progress?: McpToolCallProgress;
 };
 
 export type AssistantMessageItem = {